@@ -1,11 +1,11 @@
 use serde::{ Deserialize, Serialize };
-use std::sync::{ Arc, Mutex };
+use std::sync::{ Arc, Mutex, atomic::{ AtomicBool, AtomicUsize, Ordering } };
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::hash::{ Hash, Hasher };
 use std::collections::hash_map::DefaultHasher;
 use axum::{ routing::get, Router, Json };
 use serde_json::{ json, Value };
-use tracing;
 use std::time::{ Duration, Instant };
 
 /// Health endpoint handler for load balancer service
@@ -27,15 +27,19 @@ pub fn create_balancer_health_router() -> Router {
 }
 
 /// Load balancing strategies
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum LoadBalancingStrategy {
     /// Simple round-robin selection
+    #[default]
     RoundRobin,
     /// Weighted round-robin selection
     WeightedRoundRobin,
     /// Random target selection
     Random,
+    /// Random selection in proportion to weight; unlike weighted round-robin it keeps no
+    /// shared state, so concurrent selections never wait on each other
+    WeightedRandom,
     /// Route to target with least active connections
     LeastConnections,
 }
@@ -46,17 +50,12 @@ impl std::fmt::Display for LoadBalancingStrategy {
             LoadBalancingStrategy::RoundRobin => write!(f, "round_robin"),
             LoadBalancingStrategy::WeightedRoundRobin => write!(f, "weighted_round_robin"),
             LoadBalancingStrategy::Random => write!(f, "random"),
+            LoadBalancingStrategy::WeightedRandom => write!(f, "weighted_random"),
             LoadBalancingStrategy::LeastConnections => write!(f, "least_connections"),
         }
     }
 }
 
-impl Default for LoadBalancingStrategy {
-    fn default() -> Self {
-        Self::RoundRobin
-    }
-}
-
 /// Target server configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Target {
@@ -344,20 +343,53 @@ fn default_min_requests() -> u32 {
     10 // minimum 10 requests before circuit breaker activates
 }
 
+/// Random draws from the whole weight table before falling back to scanning the eligible targets
+const RANDOM_SAMPLE_ATTEMPTS: usize = 8;
+
+thread_local! {
+    /// Per-thread xorshift state, so random selection shares nothing between threads
+    static RANDOM_STATE: Cell<u64> = Cell::new(random_seed());
+}
+
+/// Nonzero seed from the process's randomized hasher keys and the current time
+fn random_seed() -> u64 {
+    use std::hash::BuildHasher;
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    Instant::now().hash(&mut hasher);
+    std::thread::current().id().hash(&mut hasher);
+    hasher.finish() | 1
+}
+
+/// Uniform random number in 0..bound (0 when bound is 0), from the calling thread's generator
+fn random_below(bound: u64) -> u64 {
+    let random = RANDOM_STATE.with(|state| {
+        // xorshift64*
+        let mut x = state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+        x.wrapping_mul(0x2545f4914f6cdd1d)
+    });
+    (((random as u128) * (bound as u128)) >> 64) as u64
+}
+
 /// Load balancer that manages target selection
 pub struct LoadBalancer {
     targets: Vec<Target>,
     strategy: LoadBalancingStrategy,
     /// Current position for round-robin strategies
-    current_position: Arc<Mutex<usize>>,
+    current_position: Arc<AtomicUsize>,
     /// Weighted round-robin state
     weighted_state: Arc<Mutex<WeightedRoundRobinState>>,
     /// Connection tracking for least-connections
     connection_tracker: Arc<Mutex<ConnectionTracker>>,
     /// Sticky session mappings for client-to-backend affinity
     sticky_sessions: Arc<Mutex<HashMap<u64, String>>>,
-    /// Dynamic health status tracking (overrides target.healthy)
-    health_status: Arc<Mutex<HashMap<String, bool>>>,
+    /// Health per target, by index; starts from target.healthy and follows health checks
+    health: Vec<AtomicBool>,
+    /// Running totals of target weights, by index, for weighted random selection
+    cumulative_weights: Vec<u64>,
     /// Circuit breakers for targets
     circuit_breakers: Arc<Mutex<HashMap<String, CircuitBreaker>>>,
 }
@@ -366,31 +398,28 @@ pub struct LoadBalancer {
 unsafe impl Send for LoadBalancer {}
 unsafe impl Sync for LoadBalancer {}
 
-/// State for weighted round-robin algorithm
+/// State for weighted round-robin algorithm (interleaved, as in LVS).
+/// Positions index the eligible target set passed to each selection.
 #[derive(Debug)]
 struct WeightedRoundRobinState {
-    current_weights: Vec<u32>,
-    gcd_weight: u32,
+    /// Weight threshold for the current pass
+    current_weight: u32,
+    /// Last selected position; wraps to 0 on the first selection
     current_position: usize,
 }
 
 impl WeightedRoundRobinState {
-    fn new(targets: &[Target]) -> Self {
-        let current_weights: Vec<u32> = targets
-            .iter()
-            .map(|t| t.weight)
-            .collect();
-        let gcd_weight = Self::gcd_of_weights(&current_weights);
-
+    fn new() -> Self {
         Self {
-            current_weights: current_weights.clone(),
-            gcd_weight,
-            current_position: 0,
+            current_weight: 0,
+            current_position: usize::MAX,
         }
     }
 
+    /// Step by which the weight threshold drops each pass; never zero, so every
+    /// pass makes progress even if all weights are zero
     fn gcd_of_weights(weights: &[u32]) -> u32 {
-        weights.iter().fold(0, |acc, &x| Self::gcd(acc, x))
+        weights.iter().fold(0, |acc, &x| Self::gcd(acc, x)).max(1)
     }
 
     fn gcd(a: u32, b: u32) -> u32 {
@@ -401,122 +430,172 @@ impl WeightedRoundRobinState {
 impl LoadBalancer {
     /// Create a new load balancer with the given targets and strategy
     pub fn new(targets: Vec<Target>, strategy: LoadBalancingStrategy) -> Self {
-        let weighted_state = Arc::new(Mutex::new(WeightedRoundRobinState::new(&targets)));
+        let weighted_state = Arc::new(Mutex::new(WeightedRoundRobinState::new()));
+        let health = targets
+            .iter()
+            .map(|target| AtomicBool::new(target.healthy))
+            .collect();
+        let cumulative_weights = targets
+            .iter()
+            .scan(0u64, |total, target| {
+                *total += target.weight as u64;
+                Some(*total)
+            })
+            .collect();
 
         Self {
             targets,
             strategy,
-            current_position: Arc::new(Mutex::new(0)),
+            current_position: Arc::new(AtomicUsize::new(0)),
             weighted_state,
             connection_tracker: Arc::new(Mutex::new(ConnectionTracker::new())),
             sticky_sessions: Arc::new(Mutex::new(HashMap::new())),
-            health_status: Arc::new(Mutex::new(HashMap::new())),
+            health,
+            cumulative_weights,
             circuit_breakers: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
-    /// Check if a target is healthy (considers both static and dynamic health status)
-    fn is_target_healthy(&self, target: &Target) -> bool {
-        let health_status = self.health_status.lock().unwrap();
+    /// Whether the target at `index` is currently healthy
+    fn is_healthy_at(&self, index: usize) -> bool {
+        self.health[index].load(Ordering::Relaxed)
+    }
 
-        // Check dynamic health status first, fall back to static if not set
-        match health_status.get(&target.url) {
-            Some(&dynamic_health) => dynamic_health,
-            None => target.healthy, // Use static health status as default
-        }
+    /// Targets currently considered healthy
+    fn healthy_targets(&self) -> Vec<&Target> {
+        self.targets
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.is_healthy_at(*index))
+            .map(|(_, target)| target)
+            .collect()
     }
 
     /// Select the next target based on the load balancing strategy
     pub fn select_target(&self) -> Option<&Target> {
-        let healthy_targets: Vec<&Target> = self.targets
-            .iter()
-            .filter(|target| self.is_target_healthy(target))
-            .collect();
-
-        if healthy_targets.is_empty() {
-            return None;
+        match self.strategy {
+            // Random strategies sample the precomputed weights without collecting healthy targets
+            LoadBalancingStrategy::Random => self.sample_target(false, |index| self.is_healthy_at(index)),
+            LoadBalancingStrategy::WeightedRandom => self.sample_target(true, |index| self.is_healthy_at(index)),
+            _ => {
+                let healthy_targets = self.healthy_targets();
+                if healthy_targets.is_empty() {
+                    return None;
+                }
+                self.select_among(&healthy_targets)
+            }
         }
+    }
 
+    /// Select with the configured strategy among already filtered, non-empty targets
+    fn select_among<'a>(&'a self, eligible: &[&'a Target]) -> Option<&'a Target> {
         match self.strategy {
-            LoadBalancingStrategy::RoundRobin => self.round_robin_select(&healthy_targets),
-            LoadBalancingStrategy::WeightedRoundRobin => self.weighted_round_robin_select(),
-            LoadBalancingStrategy::Random => self.random_select(&healthy_targets),
-            LoadBalancingStrategy::LeastConnections =>
-                self.least_connections_select(&healthy_targets),
+            LoadBalancingStrategy::RoundRobin => self.round_robin_select(eligible),
+            LoadBalancingStrategy::WeightedRoundRobin => self.weighted_round_robin_select(eligible),
+            LoadBalancingStrategy::Random => self.random_select(eligible, false),
+            LoadBalancingStrategy::WeightedRandom => self.random_select(eligible, true),
+            LoadBalancingStrategy::LeastConnections => self.least_connections_select(eligible),
         }
     }
 
     /// Round-robin target selection
     fn round_robin_select(&self, healthy_targets: &[&Target]) -> Option<&Target> {
-        let mut position = self.current_position.lock().unwrap();
-        let target = healthy_targets[*position % healthy_targets.len()];
-        *position += 1;
+        let position = self.current_position.fetch_add(1, Ordering::Relaxed);
+        let target = healthy_targets[position % healthy_targets.len()];
 
         // Find the target in our original targets vec
         self.targets.iter().find(|t| t.url == target.url)
     }
 
-    /// Weighted round-robin target selection
-    fn weighted_round_robin_select(&self) -> Option<&Target> {
-        let mut state = self.weighted_state.lock().unwrap();
+    /// Weighted round-robin selection over the eligible targets, so weights are
+    /// respected among whichever targets are currently healthy and allowed
+    fn weighted_round_robin_select<'a>(&'a self, eligible: &[&'a Target]) -> Option<&'a Target> {
+        let weights: Vec<u32> = eligible
+            .iter()
+            .map(|target| target.weight)
+            .collect();
+        let max_weight = weights.iter().copied().max().unwrap_or(0);
+        if max_weight == 0 {
+            // No usable weights: share traffic evenly
+            return self.round_robin_select(eligible);
+        }
+        let gcd_weight = WeightedRoundRobinState::gcd_of_weights(&weights);
 
-        loop {
-            state.current_position = (state.current_position + 1) % self.targets.len();
+        let mut state = self.weighted_state.lock().unwrap();
+        // The eligible set may have shrunk since the last call
+        state.current_weight = state.current_weight.min(max_weight);
 
+        // A full cycle visits every position at each weight threshold
+        let max_steps = eligible.len() * ((max_weight / gcd_weight) as usize + 1);
+        for _ in 0..max_steps {
+            state.current_position = state.current_position.wrapping_add(1) % eligible.len();
             if state.current_position == 0 {
-                // Store gcd_weight to avoid borrowing issues
-                let gcd_weight = state.gcd_weight;
-
-                // Decrease all current weights by GCD
-                for weight in &mut state.current_weights {
-                    if *weight >= gcd_weight {
-                        *weight -= gcd_weight;
-                    }
+                state.current_weight = state.current_weight.saturating_sub(gcd_weight);
+                if state.current_weight == 0 {
+                    state.current_weight = max_weight;
                 }
-
-                // If all weights are 0, reset them
-                if state.current_weights.iter().all(|&w| w == 0) {
-                    state.current_weights = self.targets
-                        .iter()
-                        .map(|t| t.weight)
-                        .collect();
-                }
-            }
-
-            let target = &self.targets[state.current_position];
-            if self.is_target_healthy(target) && state.current_weights[state.current_position] > 0 {
-                return Some(target);
             }
 
-            // Prevent infinite loop
-            if state.current_weights.iter().all(|&w| w == 0) {
-                break;
+            if weights[state.current_position] >= state.current_weight {
+                return Some(eligible[state.current_position]);
             }
         }
 
-        // Fallback to round-robin if weighted selection fails
+        // Unreachable with a non-zero max weight; fall back to round-robin defensively
         drop(state);
-        let healthy_targets: Vec<&Target> = self.targets
-            .iter()
-            .filter(|target| self.is_target_healthy(target))
-            .collect();
-        self.round_robin_select(&healthy_targets)
+        self.round_robin_select(eligible)
     }
 
-    /// Random target selection
-    fn random_select(&self, healthy_targets: &[&Target]) -> Option<&Target> {
-        if healthy_targets.is_empty() {
-            return None;
-        }
+    /// Random selection among the given targets, in proportion to weight when `weighted`
+    fn random_select<'a>(&'a self, eligible: &[&'a Target], weighted: bool) -> Option<&'a Target> {
+        self.sample_target(weighted, |index| eligible.iter().any(|target| std::ptr::eq(*target, &self.targets[index])))
+    }
 
-        // Use a simple deterministic random selection based on current time
-        use std::time::{ SystemTime, UNIX_EPOCH };
-        let seed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
-        let index = (seed as usize) % healthy_targets.len();
-        let target = healthy_targets[index];
+    /// Pick a random target that `eligible` accepts (by index), in proportion to weight when
+    /// `weighted`. Samples the precomputed cumulative weights with a per-thread generator in
+    /// O(log n) and no lock; only when most of the weight is ineligible are the eligible
+    /// targets scanned instead.
+    fn sample_target(&self, weighted: bool, eligible: impl Fn(usize) -> bool) -> Option<&Target> {
+        let total = if weighted {
+            self.cumulative_weights.last().copied().unwrap_or(0)
+        } else {
+            self.targets.len() as u64
+        };
+        if total > 0 {
+            // Rejected draws leave each eligible target's share proportional to its weight
+            for _ in 0..RANDOM_SAMPLE_ATTEMPTS {
+                let point = random_below(total);
+                let index = if weighted {
+                    self.cumulative_weights.partition_point(|&cumulative| cumulative <= point)
+                } else {
+                    point as usize
+                };
+                if eligible(index) {
+                    return Some(&self.targets[index]);
+                }
+            }
+        }
 
-        // Find the target in our original targets vec
-        self.targets.iter().find(|t| t.url == target.url)
+        let candidates: Vec<usize> = (0..self.targets.len()).filter(|&index| eligible(index)).collect();
+        let weight_of = |index: usize| if weighted { self.targets[index].weight as u64 } else { 1 };
+        let total: u64 = candidates
+            .iter()
+            .map(|&index| weight_of(index))
+            .sum();
+        if total == 0 {
+            // No usable weights: share traffic evenly
+            return (!candidates.is_empty()).then(|| {
+                &self.targets[candidates[random_below(candidates.len() as u64) as usize]]
+            });
+        }
+        let mut point = random_below(total);
+        for index in candidates {
+            if point < weight_of(index) {
+                return Some(&self.targets[index]);
+            }
+            point -= weight_of(index);
+        }
+        None
     }
 
     /// Least connections target selection
@@ -550,8 +629,11 @@ impl LoadBalancer {
 
     /// Mark a target as healthy or unhealthy (thread-safe)
     pub fn set_target_health(&self, target_url: &str, healthy: bool) {
-        let mut health_status = self.health_status.lock().unwrap();
-        health_status.insert(target_url.to_string(), healthy);
+        for (target, health) in self.targets.iter().zip(&self.health) {
+            if target.url == target_url {
+                health.store(healthy, Ordering::Relaxed);
+            }
+        }
 
         tracing::info!(
             target_url = %target_url,
@@ -560,6 +642,14 @@ impl LoadBalancer {
         );
     }
 
+    /// Whether a target is currently considered healthy (unknown URLs are not)
+    pub fn is_healthy(&self, target_url: &str) -> bool {
+        self.targets
+            .iter()
+            .position(|target| target.url == target_url)
+            .is_some_and(|index| self.is_healthy_at(index))
+    }
+
     /// Get all targets
     pub fn targets(&self) -> &[Target] {
         &self.targets
@@ -572,10 +662,7 @@ impl LoadBalancer {
 
     /// Get healthy targets count
     pub fn healthy_targets_count(&self) -> usize {
-        self.targets
-            .iter()
-            .filter(|t| self.is_target_healthy(t))
-            .count()
+        (0..self.targets.len()).filter(|&index| self.is_healthy_at(index)).count()
     }
 
     /// Get connection count for a target
@@ -586,10 +673,7 @@ impl LoadBalancer {
 
     /// Select target with sticky session support based on client identifier
     pub fn select_target_sticky(&self, client_id: &str) -> Option<&Target> {
-        let healthy_targets: Vec<&Target> = self.targets
-            .iter()
-            .filter(|target| self.is_target_healthy(target))
-            .collect();
+        let healthy_targets = self.healthy_targets();
 
         if healthy_targets.is_empty() {
             return None;
@@ -604,9 +688,10 @@ impl LoadBalancer {
             if let Some(target_url) = sticky_sessions.get(&client_hash) {
                 // Return the sticky target if it's still healthy
                 if
-                    let Some(target) = self.targets
+                    let Some(target) = healthy_targets
                         .iter()
-                        .find(|t| &t.url == target_url && self.is_target_healthy(t))
+                        .find(|t| &t.url == target_url)
+                        .copied()
                 {
                     return Some(target);
                 }
@@ -615,13 +700,7 @@ impl LoadBalancer {
         }
 
         // No existing sticky session or target is unhealthy - select new target
-        let selected_target = match self.strategy {
-            LoadBalancingStrategy::RoundRobin => self.round_robin_select(&healthy_targets),
-            LoadBalancingStrategy::WeightedRoundRobin => self.weighted_round_robin_select(),
-            LoadBalancingStrategy::Random => self.random_select(&healthy_targets),
-            LoadBalancingStrategy::LeastConnections =>
-                self.least_connections_select(&healthy_targets),
-        };
+        let selected_target = self.select_among(&healthy_targets);
 
         // Store the new sticky session mapping
         if let Some(target) = selected_target {
@@ -729,10 +808,12 @@ impl LoadBalancer {
     pub fn select_target_with_circuit_breaker(&self) -> Option<&Target> {
         let available_targets: Vec<&Target> = self.targets
             .iter()
-            .filter(|target| {
+            .enumerate()
+            .filter(|(index, target)| {
                 // Target must be healthy AND circuit breaker must allow requests
-                self.is_target_healthy(target) && self.allow_request(&target.url)
+                self.is_healthy_at(*index) && self.allow_request(&target.url)
             })
+            .map(|(_, target)| target)
             .collect();
 
         if available_targets.is_empty() {
@@ -741,15 +822,6 @@ impl LoadBalancer {
         }
 
         // Use existing load balancing logic on filtered targets
-        match self.strategy {
-            LoadBalancingStrategy::RoundRobin => self.round_robin_select(&available_targets),
-            LoadBalancingStrategy::WeightedRoundRobin => {
-                // For weighted, we need to revert to simple round-robin if circuit breakers filter targets
-                self.round_robin_select(&available_targets)
-            }
-            LoadBalancingStrategy::Random => self.random_select(&available_targets),
-            LoadBalancingStrategy::LeastConnections =>
-                self.least_connections_select(&available_targets),
-        }
+        self.select_among(&available_targets)
     }
 }
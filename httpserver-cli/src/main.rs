@@ -1,17 +1,68 @@
 use clap::Parser;
-use httpserver_config::Args;
+use httpserver_config::{ Args, Config };
 use httpserver_engine::HttpServerEngine;
+use std::path::PathBuf;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Parse command line arguments
     let args = Args::parse();
 
+    // Dry run: validate the configuration and exit without starting the server
+    if let Some(path) = &args.check_config {
+        std::process::exit(check_config(path));
+    }
+
+    let print_config = args.print_config;
+    let show_secrets = args.show_secrets;
+
     // Create engine from arguments (preserves exact existing behavior)
     let engine = HttpServerEngine::from_args(args)?;
 
-    // Start the engine
-    engine.start().await?;
+    // Show the configuration startup would run with, then exit
+    if print_config {
+        let mut config = engine.config().clone();
+        config.server.default_port = engine.port();
+        print!("{}", config.to_toml(!show_secrets)?);
+        return Ok(());
+    }
+
+    // Ctrl+C drains the requests in flight before exiting; a second Ctrl+C exits at once
+    let handle = engine.handle();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("Shutting down, press Ctrl+C again to exit immediately");
+            handle.shutdown();
+            let _ = tokio::signal::ctrl_c().await;
+            std::process::exit(130);
+        }
+    });
+
+    // Start the engine, reporting startup failures such as a taken port without a debug dump
+    if let Err(e) = engine.start().await {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
 
     Ok(())
 }
+
+/// Report every problem in the configuration file, returning the process exit code
+fn check_config(path: &PathBuf) -> i32 {
+    match Config::check_file(path) {
+        Ok((_, warnings)) => {
+            println!("Configuration OK: {}", path.display());
+            for warning in &warnings {
+                println!("  warning: {}", warning);
+            }
+            0
+        }
+        Err(errors) => {
+            eprintln!("Configuration {} has {} error(s):", path.display(), errors.len());
+            for error in &errors {
+                eprintln!("  - {}", error);
+            }
+            1
+        }
+    }
+}
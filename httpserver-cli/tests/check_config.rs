@@ -0,0 +1,56 @@
+// --check-config tests: the binary validates a config file and exits without starting the server
+use std::process::{ Command, Output };
+use tempfile::TempDir;
+
+fn check_config(content: &str) -> (TempDir, Output) {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, content).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_httpserver"))
+        .arg("--check-config")
+        .arg(&config_path)
+        .output()
+        .unwrap();
+    (temp_dir, output)
+}
+
+#[test]
+fn test_bad_config_lists_every_error_and_fails() {
+    let (_temp_dir, output) = check_config(
+        r#"
+[static_config]
+directory = "/definitely/missing/public"
+
+[[proxy]]
+path = "/api/*"
+target = "localhost:3000"
+
+[server.ssl]
+enabled = true
+cert_file = "/definitely/missing/server.crt"
+key_file = "/definitely/missing/server.key"
+"#
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("has 4 error(s)"), "{}", stderr);
+    assert!(stderr.contains("Static directory does not exist: /definitely/missing/public"), "{}", stderr);
+    assert!(stderr.contains("Proxy route 0 target 0: must be a valid HTTP/HTTPS URL: localhost:3000"), "{}", stderr);
+    assert!(stderr.contains("SSL cert_file does not exist: /definitely/missing/server.crt"), "{}", stderr);
+    assert!(stderr.contains("SSL key_file does not exist: /definitely/missing/server.key"), "{}", stderr);
+}
+
+#[test]
+fn test_good_config_passes() {
+    let static_dir = TempDir::new().unwrap();
+    let (_temp_dir, output) = check_config(
+        &format!(
+            "[static_config]\ndirectory = \"{}\"\n\n[[proxy]]\npath = \"/api/*\"\ntarget = \"http://localhost:3000\"\n",
+            static_dir.path().to_string_lossy().replace('\\', "/")
+        )
+    );
+
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Configuration OK"));
+}
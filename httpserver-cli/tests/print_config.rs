@@ -0,0 +1,46 @@
+// --print-config tests: the effective configuration is printed as TOML after CLI overrides
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Run `httpserver --print-config --config <file> <extra_args>`, returning stdout parsed as TOML
+fn print_config(content: &str, extra_args: &[&str]) -> toml::Value {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, content).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_httpserver"))
+        .arg("--print-config")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--directory")
+        .arg(temp_dir.path())
+        .args(extra_args)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap().parse().expect("stdout should be valid TOML")
+}
+
+fn config_with_secret() -> String {
+    let static_dir = std::env::temp_dir();
+    format!(
+        "[static_config]\ndirectory = \"{}\"\n\n[server]\ndefault_port = 9000\n\n[[proxy]]\npath = \"/api/*\"\ntarget = \"http://localhost:3000\"\n\n[proxy.middleware.auth]\nbearer_token = \"super-secret\"\n",
+        static_dir.to_string_lossy().replace('\\', "/")
+    )
+}
+
+#[test]
+fn test_cli_port_overrides_file_value() {
+    let config = print_config(&config_with_secret(), &["--port", "7070"]);
+    assert_eq!(config["server"]["default_port"].as_integer(), Some(7070));
+    assert_eq!(config["proxy"][0]["path"].as_str(), Some("/api/*"));
+}
+
+#[test]
+fn test_secrets_redacted_unless_requested() {
+    let config = print_config(&config_with_secret(), &[]);
+    assert_eq!(config["proxy"][0]["middleware"]["auth"]["bearer_token"].as_str(), Some("[REDACTED]"));
+
+    let config = print_config(&config_with_secret(), &["--show-secrets"]);
+    assert_eq!(config["proxy"][0]["middleware"]["auth"]["bearer_token"].as_str(), Some("super-secret"));
+}
@@ -0,0 +1,52 @@
+/// Variables a route's `access_log_format` may use, nginx style
+pub const ACCESS_LOG_VARIABLES: [&str; 11] = [
+    "remote_addr",
+    "time_local",
+    "request",
+    "request_method",
+    "request_uri",
+    "status",
+    "body_bytes_sent",
+    "request_time",
+    "http_referer",
+    "http_user_agent",
+    "route",
+];
+
+/// Piece of an access log format: text copied as-is, or a `$variable` to substitute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessLogSegment<'a> {
+    Literal(&'a str),
+    Variable(&'a str),
+}
+
+/// Split a format into literal text and variables; a variable name runs for as long as it is
+/// made of lowercase letters, digits and underscores, so "$request_time" is never "$request"
+pub fn access_log_segments(format: &str) -> Vec<AccessLogSegment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = format;
+    while let Some(start) = rest.find('$') {
+        let name_len = rest[start + 1..]
+            .find(|c: char| !(c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'))
+            .unwrap_or(rest.len() - start - 1);
+        if name_len == 0 {
+            // A lone "$" is literal text
+            segments.push(AccessLogSegment::Literal(&rest[..start + 1]));
+        } else {
+            if start > 0 {
+                segments.push(AccessLogSegment::Literal(&rest[..start]));
+            }
+            segments.push(AccessLogSegment::Variable(&rest[start + 1..start + 1 + name_len]));
+        }
+        rest = &rest[start + 1 + name_len..];
+    }
+    if !rest.is_empty() {
+        segments.push(AccessLogSegment::Literal(rest));
+    }
+    segments
+}
+
+/// Response extension set by a handler that wrote (or deliberately skipped) the access log line
+/// for its request, so the server-wide request log does not repeat it
+#[derive(Debug, Clone, Copy)]
+pub struct AccessLogHandled;
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Custom error pages from `server.error_pages`, keyed by status code
+///
+/// Each engine loads its pages once at startup; a missing or unreadable file is
+/// skipped and that status keeps the built-in page.
+#[derive(Clone, Debug, Default)]
+pub struct ErrorPages {
+    pages: Arc<HashMap<u16, String>>,
+}
+
+impl ErrorPages {
+    /// Read every configured page into memory
+    pub fn load(paths: &HashMap<u16, PathBuf>) -> Self {
+        let pages = paths
+            .iter()
+            .filter_map(|(&status, path)| {
+                match std::fs::read_to_string(path) {
+                    Ok(body) => Some((status, body)),
+                    Err(e) => {
+                        tracing::warn!(
+                            status,
+                            path = %path.display(),
+                            error = %e,
+                            "Custom error page unavailable; using built-in page"
+                        );
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self { pages: Arc::new(pages) }
+    }
+
+    /// Body of the custom page configured for a status code, if any
+    pub fn get(&self, status: u16) -> Option<&str> {
+        self.pages.get(&status).map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+}
+
+/// Response extension marking a built-in HTML error page that a configured page may replace
+#[derive(Clone, Copy, Debug)]
+pub struct GeneratedErrorPage;
+
+/// Serde adapter for maps keyed by status code; TOML table keys are always strings
+pub(crate) mod status_code_keys {
+    use serde::{ de::Error, Deserialize, Deserializer, Serialize, Serializer };
+    use std::collections::{ BTreeMap, HashMap };
+    use std::path::PathBuf;
+
+    pub fn serialize<S: Serializer>(
+        pages: &HashMap<u16, PathBuf>,
+        serializer: S
+    ) -> Result<S::Ok, S::Error> {
+        let keyed: BTreeMap<String, &PathBuf> = pages
+            .iter()
+            .map(|(status, path)| (status.to_string(), path))
+            .collect();
+        keyed.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D
+    ) -> Result<HashMap<u16, PathBuf>, D::Error> {
+        HashMap::<String, PathBuf>
+            ::deserialize(deserializer)?
+            .into_iter()
+            .map(|(key, path)| {
+                match key.parse::<u16>() {
+                    Ok(status) if (400..=599).contains(&status) => Ok((status, path)),
+                    _ => Err(D::Error::custom(format!("invalid error page status code '{}'", key))),
+                }
+            })
+            .collect()
+    }
+}
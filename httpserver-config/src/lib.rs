@@ -3,7 +3,14 @@ use serde::{ Deserialize, Serialize };
 use std::path::PathBuf;
 use axum::{ routing::get, Router, Json };
 use serde_json::{ json, Value };
-use tracing;
+
+// Custom error pages shared by the core, static and proxy handlers
+pub mod error_pages;
+pub use error_pages::{ ErrorPages, GeneratedErrorPage };
+
+// Per-route access log formats
+pub mod access_log;
+pub use access_log::{ AccessLogHandled, AccessLogSegment, ACCESS_LOG_VARIABLES, access_log_segments };
 
 // Re-export types from balancer crate
 pub use httpserver_balancer::{ LoadBalancingStrategy, Target, CircuitBreakerConfig };
@@ -24,6 +31,22 @@ pub struct Args {
     /// Configuration file for proxy routes (future feature)
     #[arg(short, long)]
     pub config: Option<PathBuf>,
+
+    /// Serve HTTPS with a generated self-signed certificate (local development)
+    #[arg(long)]
+    pub self_signed: bool,
+
+    /// Validate a configuration file, report every problem and exit without starting the server
+    #[arg(long, value_name = "PATH")]
+    pub check_config: Option<PathBuf>,
+
+    /// Print the effective configuration (files, defaults and CLI overrides) as TOML and exit
+    #[arg(long)]
+    pub print_config: bool,
+
+    /// Include passwords, tokens and keys in --print-config output instead of redacting them
+    #[arg(long, requires = "print_config")]
+    pub show_secrets: bool,
 }
 
 /// Server configuration (for future phases)
@@ -36,6 +59,18 @@ pub struct Config {
     #[serde(default)]
     pub proxy: Vec<ProxyRoute>,
 
+    /// Layer-4 routes relaying raw TCP connections (e.g. databases, SMTP) to backends
+    #[serde(default)]
+    pub tcp_routes: Vec<TcpRoute>,
+
+    /// Catch-all upstream for requests no proxy route or static file serves
+    #[serde(default)]
+    pub default_route: Option<DefaultRoute>,
+
+    /// Additional HTTP ports, each serving its own routes (e.g. an admin UI next to public traffic)
+    #[serde(default)]
+    pub listeners: Vec<ListenerConfig>,
+
     /// Logging configuration
     #[serde(default)]
     pub logging: LoggingConfig,
@@ -51,6 +86,14 @@ pub struct Config {
     /// Tunnel client configuration (Phase 7.1)
     #[serde(default)]
     pub tunnel: TunnelConfig,
+
+    /// HTTP client settings used for proxied requests
+    #[serde(default)]
+    pub proxy_client: ProxyClientConfig,
+
+    /// Probe every proxy target once at startup to catch unreachable or mistyped backends
+    #[serde(default)]
+    pub startup_check: StartupCheckConfig,
 }
 
 /// Static file serving configuration
@@ -62,6 +105,94 @@ pub struct StaticConfig {
     /// Fallback file for SPA support
     #[serde(default = "default_fallback")]
     pub fallback: String,
+
+    /// Serve the fallback file for missing paths without a file extension (SPA routing)
+    #[serde(default = "default_spa_fallback")]
+    pub spa_fallback: bool,
+
+    /// Path prefixes that always use the SPA fallback, even when the path has an extension
+    #[serde(default)]
+    pub spa_fallback_prefixes: Vec<String>,
+
+    /// Additional directories served under URL prefixes; the longest matching prefix wins
+    #[serde(default)]
+    pub mounts: Vec<StaticMount>,
+
+    /// Cache-Control header values per file pattern
+    #[serde(default)]
+    pub cache_control: CacheControlConfig,
+
+    /// Require HTTP Basic auth for every static file: "user:password" or a list of them
+    #[serde(default)]
+    pub basic_auth: Option<BasicAuthCredentials>,
+}
+
+/// One "user:password" pair or several accepted ones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BasicAuthCredentials {
+    Single(String),
+    List(Vec<String>),
+}
+
+impl BasicAuthCredentials {
+    /// Every accepted "user:password" pair
+    pub fn credentials(&self) -> &[String] {
+        match self {
+            BasicAuthCredentials::Single(credential) => std::slice::from_ref(credential),
+            BasicAuthCredentials::List(credentials) => credentials,
+        }
+    }
+}
+
+/// Cache-Control values for static files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheControlConfig {
+    /// Value for files no rule matches
+    #[serde(default = "default_cache_control")]
+    pub default: String,
+
+    /// Rules checked in order; the first matching pattern wins
+    #[serde(default)]
+    pub rules: Vec<CacheControlRule>,
+}
+
+/// Cache-Control value for files matching a glob
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheControlRule {
+    /// Glob over the file name, e.g. "*.html" or "*.*.js" ('*' and '?' wildcards);
+    /// patterns containing '/' match the path relative to the static directory
+    pub pattern: String,
+
+    /// Header value, e.g. "no-cache" or "public, max-age=31536000, immutable"
+    pub value: String,
+}
+
+impl Default for CacheControlConfig {
+    fn default() -> Self {
+        Self {
+            default: default_cache_control(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+fn default_cache_control() -> String {
+    "public, max-age=3600".to_string()
+}
+
+/// A directory served under a URL prefix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaticMount {
+    /// URL prefix, e.g. "/assets"
+    pub mount: String,
+
+    /// Directory to serve files from
+    pub directory: PathBuf,
+
+    /// Serve the mount's index.html for missing paths without a file extension
+    #[serde(default)]
+    pub spa_fallback: bool,
 }
 
 /// Proxy route configuration (future feature)
@@ -70,6 +201,16 @@ pub struct ProxyRoute {
     /// Path pattern to match
     pub path: String,
 
+    /// Host the route applies to (Host header, port ignored); any host when unset
+    #[serde(default)]
+    pub host: Option<String>,
+
+    /// Treat `path` and `host` as anchored regular expressions instead of literals and wildcards
+    ///
+    /// Route precedence: exact paths first, then prefix wildcards, then regexes, each in config order.
+    #[serde(default)]
+    pub match_regex: bool,
+
     /// Single target URL (legacy - will be deprecated)
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub target: Option<String>,
@@ -86,6 +227,17 @@ pub struct ProxyRoute {
     #[serde(default = "default_timeout")]
     pub timeout: u64,
 
+    /// Seconds to wait for a backend connection, overriding [proxy.client] connect_timeout,
+    /// so a down backend fails fast instead of after the full `timeout`
+    #[serde(default)]
+    pub connect_timeout: Option<u64>,
+
+    /// Seconds the backend may stay silent (before the response headers or between body chunks)
+    /// before the request ends with 504, or a streamed (chunked) body is aborted; slow but
+    /// steady responses are only capped by `timeout`
+    #[serde(default)]
+    pub read_timeout: Option<u64>,
+
     /// Enable sticky sessions for WebSocket connections
     #[serde(default)]
     pub sticky_sessions: bool,
@@ -98,6 +250,10 @@ pub struct ProxyRoute {
     #[serde(default)]
     pub websocket_health: Option<WebSocketHealthConfig>,
 
+    /// TCP connect health check configuration, for backends without an HTTP health path
+    #[serde(default)]
+    pub tcp_health: Option<TcpHealthConfig>,
+
     /// Circuit breaker configuration
     #[serde(default)]
     pub circuit_breaker: Option<CircuitBreakerConfig>,
@@ -109,6 +265,180 @@ pub struct ProxyRoute {
     /// SSL/TLS configuration for this route
     #[serde(default)]
     pub ssl: Option<RouteSslConfig>,
+
+    /// Backend client overrides for this route (falls back to [proxy_client])
+    #[serde(default)]
+    pub client: Option<RouteClientConfig>,
+
+    /// Response caching for GET requests on this route
+    #[serde(default)]
+    pub cache: Option<CacheConfig>,
+
+    /// Forward over HTTP/2 with streamed bodies and trailers (required for gRPC backends)
+    #[serde(default)]
+    pub http2: bool,
+
+    /// Send X-Real-IP and the RFC 7239 Forwarded header to the backend
+    #[serde(default = "default_forwarded_headers")]
+    pub forwarded_headers: bool,
+
+    /// Request headers allowed through to the backend (case-insensitive); when set, all others
+    /// are dropped. Headers the proxy adds itself (Host, X-Forwarded-*, Forwarded, traceparent)
+    /// are always sent.
+    #[serde(default)]
+    pub header_allowlist: Option<Vec<String>>,
+
+    /// Request headers never forwarded to the backend (case-insensitive)
+    #[serde(default)]
+    pub header_denylist: Vec<String>,
+
+    /// Request body limit in bytes for this route, overriding server.max_request_size_mb (413)
+    #[serde(default)]
+    pub max_request_body_bytes: Option<u64>,
+
+    /// Backend response body limit in bytes; larger responses become a 502, and streamed
+    /// (chunked) bodies are aborted once they pass it
+    #[serde(default)]
+    pub max_response_body_bytes: Option<u64>,
+
+    /// Per-connection limits on proxied WebSocket messages, applied in both directions
+    #[serde(default)]
+    pub websocket_limits: Option<WebSocketLimitsConfig>,
+
+    /// Log request and response bodies for debugging, with sensitive fields redacted
+    #[serde(default)]
+    pub log_bodies: Option<BodyLogConfig>,
+
+    /// Send a percentage of requests to a canary target instead of the balanced targets
+    #[serde(default)]
+    pub canary: Option<CanaryConfig>,
+
+    /// Copy a sample of requests to a shadow backend whose responses are discarded
+    #[serde(default)]
+    pub mirror: Option<MirrorConfig>,
+
+    /// Verify a checksum or HMAC signature header over the request body before forwarding
+    #[serde(default)]
+    pub body_integrity: Option<BodyIntegrityConfig>,
+
+    /// Write an access log line for requests on this route (e.g. off for noisy health checks)
+    #[serde(default = "default_access_log")]
+    pub access_log: bool,
+
+    /// Custom access log line with nginx-style variables such as `$remote_addr`, `$request`,
+    /// `$status` and `$request_time`, replacing the default line for this route
+    #[serde(default)]
+    pub access_log_format: Option<String>,
+
+    /// Probe this route's targets at startup, overriding `startup_check.enabled`. Targets are
+    /// dialed, or sent a request to the `http_health` path when the route has one.
+    #[serde(default)]
+    pub startup_check: Option<bool>,
+
+    /// File under the static directory served with a 503 while every target of the route is
+    /// unhealthy, e.g. a "back soon" page, instead of the generic error
+    #[serde(default)]
+    pub fallback_file: Option<PathBuf>,
+
+    /// Answer retried POSTs carrying the same idempotency key with the first response
+    /// instead of forwarding them again
+    #[serde(default)]
+    pub idempotency: Option<IdempotencyConfig>,
+
+    /// Only forward requests carrying a valid JWT bearer token; others get a 401
+    #[serde(default)]
+    pub jwt_auth: Option<JwtAuthConfig>,
+
+    /// Ask an external auth service about each request first; requests it refuses get a 401
+    #[serde(default)]
+    pub forward_auth: Option<ForwardAuthConfig>,
+}
+
+/// Upstream receiving requests that miss every proxy route and static file (instead of a 404)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DefaultRoute {
+    /// Target URL; the full request path is appended
+    pub target: String,
+
+    /// Request timeout in seconds (must be greater than 0)
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+}
+
+impl DefaultRoute {
+    /// Proxy route matching every path, forwarding it unchanged to the target
+    pub fn to_proxy_route(&self) -> ProxyRoute {
+        ProxyRoute {
+            path: "*".to_string(),
+            target: Some(self.target.clone()),
+            targets: Vec::new(),
+            strategy: LoadBalancingStrategy::default(),
+            timeout: self.timeout,
+            sticky_sessions: false,
+            http_health: None,
+            websocket_health: None,
+            tcp_health: None,
+            circuit_breaker: None,
+            middleware: None,
+            ssl: None,
+            client: None,
+            cache: None,
+            http2: false,
+            forwarded_headers: default_forwarded_headers(),
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            websocket_limits: None,
+            log_bodies: None,
+            canary: None,
+            mirror: None,
+            host: None,
+            match_regex: false,
+            body_integrity: None,
+            connect_timeout: None,
+            read_timeout: None,
+            header_allowlist: None,
+            header_denylist: Vec::new(),
+            access_log: true,
+            access_log_format: None,
+            startup_check: None,
+            fallback_file: None,
+            idempotency: None,
+            jwt_auth: None,
+            forward_auth: None,
+        }
+    }
+}
+
+/// TCP route: connections accepted on a port are relayed byte for byte to a balanced backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TcpRoute {
+    /// Port accepting client connections on all interfaces
+    pub listen_port: u16,
+
+    /// Backends as "host:port" addresses
+    pub targets: Vec<Target>,
+
+    /// Load balancing strategy
+    #[serde(default)]
+    pub strategy: LoadBalancingStrategy,
+}
+
+/// Additional plain HTTP port with its own static files and proxy routes
+///
+/// Listeners share the server-wide settings (timeouts, limits, logging) but none of the main
+/// port's routes; health endpoints and the default route are only served on the main port.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenerConfig {
+    /// Port accepting connections on all interfaces
+    pub port: u16,
+
+    /// Directory of static files served on this port (unmatched requests get 404 when unset)
+    #[serde(default)]
+    pub static_directory: Option<PathBuf>,
+
+    /// Proxy routes served on this port
+    #[serde(default)]
+    pub proxy: Vec<ProxyRoute>,
 }
 
 /// HTTP health check configuration
@@ -129,6 +459,27 @@ pub struct HttpHealthConfig {
     /// Expected HTTP status codes (default: 200-299)
     #[serde(default = "default_expected_status_codes")]
     pub expected_status_codes: Vec<u16>,
+
+    /// Healthy only if the response body contains this text
+    #[serde(default)]
+    pub expected_body_contains: Option<String>,
+
+    /// Healthy only if the JSON response body has this field and value, written "field=value";
+    /// dotted fields reach nested objects (e.g. "checks.db=up")
+    #[serde(default)]
+    pub expected_body_json: Option<String>,
+}
+
+impl HttpHealthConfig {
+    /// Field path and expected value of `expected_body_json`; None when unset or malformed
+    pub fn expected_json_field(&self) -> Option<(&str, &str)> {
+        let (field, value) = self.expected_body_json.as_deref()?.split_once('=')?;
+        let field = field.trim();
+        if field.is_empty() {
+            return None;
+        }
+        Some((field, value.trim()))
+    }
 }
 
 fn default_expected_status_codes() -> Vec<u16> {
@@ -171,6 +522,27 @@ pub struct WebSocketHealthConfig {
     pub ping_message: String,
 }
 
+/// TCP health check configuration: a target is healthy when its host:port accepts a connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TcpHealthConfig {
+    /// Health check interval in seconds
+    #[serde(default = "default_health_interval")]
+    pub interval: u64,
+
+    /// Seconds to wait for the connection to be established
+    #[serde(default = "default_health_timeout")]
+    pub timeout: u64,
+}
+
+impl Default for TcpHealthConfig {
+    fn default() -> Self {
+        Self {
+            interval: default_health_interval(),
+            timeout: default_health_timeout(),
+        }
+    }
+}
+
 /// Middleware configuration for request/response processing
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MiddlewareConfig {
@@ -193,6 +565,10 @@ pub struct MiddlewareConfig {
     /// Compression middleware configuration
     #[serde(default)]
     pub compression: Option<CompressionConfig>,
+
+    /// CORS headers for browser clients, optionally answering preflights at the gateway
+    #[serde(default)]
+    pub cors: Option<CorsConfig>,
 }
 
 /// Header injection and modification middleware
@@ -241,6 +617,40 @@ pub struct RateLimitConfig {
     /// Custom rate limit response message
     #[serde(default = "default_rate_limit_message")]
     pub rate_limit_message: String,
+
+    /// Where request counters are kept: "memory" (per process) or "redis" (shared by all instances)
+    #[serde(default)]
+    pub backend: RateLimitBackend,
+
+    /// Redis connection URL used by the "redis" backend, e.g. redis://127.0.0.1:6379/0
+    #[serde(default)]
+    pub redis_url: Option<String>,
+
+    /// Counting algorithm: "fixed_window" or "sliding_window"
+    #[serde(default)]
+    pub algorithm: RateLimitAlgorithm,
+}
+
+/// How requests are counted against the limit
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitAlgorithm {
+    /// Counter reset at the end of each window; allows up to twice the rate across a boundary
+    #[default]
+    FixedWindow,
+    /// Previous window's count weighted by its overlap with the last `window_seconds`
+    SlidingWindow,
+}
+
+/// Storage backend for rate limit counters
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitBackend {
+    /// Counters held in process memory
+    #[default]
+    Memory,
+    /// Counters held in Redis so replicas share one limit
+    Redis,
 }
 
 /// Request/response transformation configuration
@@ -262,11 +672,11 @@ pub struct RequestTransformConfig {
     #[serde(default)]
     pub replace_text: Vec<TextReplacement>,
 
-    /// Add fields to JSON request body
+    /// Add fields to JSON request body, keyed by dotted path (e.g. "user.profile.email")
     #[serde(default)]
     pub add_json_fields: std::collections::HashMap<String, serde_json::Value>,
 
-    /// Remove fields from JSON request body
+    /// Remove fields from JSON request body by dotted path
     #[serde(default)]
     pub remove_json_fields: Vec<String>,
 }
@@ -278,11 +688,11 @@ pub struct ResponseTransformConfig {
     #[serde(default)]
     pub replace_text: Vec<TextReplacement>,
 
-    /// Add fields to JSON response body
+    /// Add fields to JSON response body, keyed by dotted path (e.g. "user.profile.email")
     #[serde(default)]
     pub add_json_fields: std::collections::HashMap<String, serde_json::Value>,
 
-    /// Remove fields from JSON response body
+    /// Remove fields from JSON response body by dotted path
     #[serde(default)]
     pub remove_json_fields: Vec<String>,
 }
@@ -321,6 +731,64 @@ pub struct AuthMiddlewareConfig {
     pub api_key: Option<ApiKeyConfig>,
 }
 
+/// CORS configuration for a proxy route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorsConfig {
+    /// Answer preflight OPTIONS requests here instead of forwarding them to the backend
+    #[serde(default = "default_cors_handle_preflight")]
+    pub handle_preflight: bool,
+
+    /// Origins allowed to make requests (empty or "*" allows any origin)
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+
+    /// Methods allowed in cross-origin requests
+    #[serde(default = "default_cors_allowed_methods")]
+    pub allowed_methods: Vec<String>,
+
+    /// Request headers allowed in cross-origin requests (empty allows whatever the preflight asks for)
+    #[serde(default)]
+    pub allowed_headers: Vec<String>,
+
+    /// Response headers exposed to browser scripts
+    #[serde(default)]
+    pub exposed_headers: Vec<String>,
+
+    /// Allow cookies and authorization headers on cross-origin requests; requires listed origins
+    #[serde(default)]
+    pub allow_credentials: bool,
+
+    /// Seconds browsers may cache a preflight response
+    #[serde(default)]
+    pub max_age: Option<u64>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            handle_preflight: default_cors_handle_preflight(),
+            allowed_origins: Vec::new(),
+            allowed_methods: default_cors_allowed_methods(),
+            allowed_headers: Vec::new(),
+            exposed_headers: Vec::new(),
+            allow_credentials: false,
+            max_age: None,
+        }
+    }
+}
+
+impl CorsConfig {
+    /// Whether requests from `origin` are allowed
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        self.allows_any_origin() || self.allowed_origins.iter().any(|allowed| allowed.eq_ignore_ascii_case(origin))
+    }
+
+    /// Whether every origin is allowed: no origins listed, or "*" among them
+    pub fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.is_empty() || self.allowed_origins.iter().any(|allowed| allowed == "*")
+    }
+}
+
 /// API key configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiKeyConfig {
@@ -379,6 +847,10 @@ pub struct SslConfig {
     #[serde(default)]
     pub lets_encrypt: Option<LetsEncryptConfig>,
 
+    /// Generate a self-signed localhost certificate when no certificates are configured
+    #[serde(default)]
+    pub self_signed: bool,
+
     /// Force HTTPS redirect (redirect HTTP to HTTPS) - legacy field
     #[serde(default = "default_force_https")]
     pub force_https: bool,
@@ -438,10 +910,26 @@ pub struct LetsEncryptConfig {
     /// Domain for which to obtain certificates
     pub domain: Option<String>,
 
+    /// Additional names to include in the certificate (e.g., "*.httpserver.io")
+    #[serde(default)]
+    pub additional_domains: Vec<String>,
+
     /// Use staging environment (for testing)
     #[serde(default)]
     pub staging: bool,
 
+    /// ACME directory URL override (e.g., a local Pebble instance)
+    #[serde(default)]
+    pub directory_url: Option<String>,
+
+    /// Directory where the account key and issued certificates are cached
+    #[serde(default = "default_acme_cache_dir")]
+    pub cache_dir: PathBuf,
+
+    /// Renew certificates this many days before they expire
+    #[serde(default = "default_acme_renew_before_days")]
+    pub renew_before_days: u64,
+
     /// DNS-01 challenge configuration for wildcard certificates
     #[serde(default)]
     pub dns_challenge: Option<DnsChallengeConfig>,
@@ -481,64 +969,555 @@ pub struct RouteSslConfig {
     /// Verify backend SSL certificates
     #[serde(default = "default_verify_backend")]
     pub verify_backend_ssl: bool,
-}
 
-// Default value functions for middleware configuration
-fn default_requests_per_minute() -> u32 {
-    100
-}
+    /// Client certificate presented to the backend for mutual TLS (PEM format)
+    pub client_cert_file: Option<PathBuf>,
 
-fn default_rate_window() -> u32 {
-    60
+    /// Private key for the backend client certificate (PEM format)
+    pub client_key_file: Option<PathBuf>,
 }
 
-fn default_max_concurrent() -> u32 {
-    10
-}
+/// Backend connectivity self-test run once by `start`, before traffic is accepted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupCheckConfig {
+    /// Probe the targets of every route; routes can opt in or out with their own `startup_check`
+    #[serde(default)]
+    pub enabled: bool,
 
-fn default_rate_limit_message() -> String {
-    "Rate limit exceeded. Please try again later.".to_string()
-}
+    /// Refuse to start when a checked route has no reachable target
+    #[serde(default)]
+    pub fail_fast: bool,
 
-fn default_gzip_enabled() -> bool {
-    true
+    /// Seconds to wait for each target
+    #[serde(default = "default_startup_check_timeout")]
+    pub timeout: u64,
 }
 
-fn default_brotli_enabled() -> bool {
-    false
+fn default_startup_check_timeout() -> u64 {
+    3
 }
 
-fn default_compression_threshold() -> usize {
-    1024 // 1KB
+impl Default for StartupCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fail_fast: false,
+            timeout: default_startup_check_timeout(),
+        }
+    }
 }
 
-fn default_compression_level() -> u32 {
-    6 // Balanced compression level
-}
+/// Global HTTP client configuration for proxied requests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyClientConfig {
+    /// Seconds to wait for a backend connection to be established
+    #[serde(default = "default_proxy_connect_timeout")]
+    pub connect_timeout: u64,
 
-/// Default value functions for SSL configuration
-fn default_https_port() -> u16 {
-    443
-}
+    /// Seconds an idle pooled backend connection is kept open
+    #[serde(default = "default_proxy_pool_idle_timeout")]
+    pub pool_idle_timeout: u64,
 
-fn default_force_https() -> bool {
-    false
-}
+    /// Maximum idle connections kept per backend host (0 disables pooling)
+    #[serde(default = "default_proxy_pool_max_idle_per_host")]
+    pub pool_max_idle_per_host: usize,
 
-fn default_ssl_protocols() -> Vec<String> {
-    vec!["TLSv1.2".to_string(), "TLSv1.3".to_string()]
+    /// Follow backend redirects instead of passing them to the client
+    #[serde(default = "default_proxy_follow_redirects")]
+    pub follow_redirects: bool,
+
+    /// Times a request may pass through proxies before it is treated as a loop (508)
+    #[serde(default = "default_proxy_max_hops")]
+    pub max_hops: u32,
+
+    /// Retry-After seconds on the 503 returned when every target of a route is unhealthy
+    #[serde(default = "default_proxy_no_healthy_targets_retry_after")]
+    pub no_healthy_targets_retry_after: u64,
 }
 
-fn default_dns_timeout() -> u64 {
-    300 // 5 minutes
+impl Default for ProxyClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: default_proxy_connect_timeout(),
+            pool_idle_timeout: default_proxy_pool_idle_timeout(),
+            pool_max_idle_per_host: default_proxy_pool_max_idle_per_host(),
+            follow_redirects: default_proxy_follow_redirects(),
+            max_hops: default_proxy_max_hops(),
+            no_healthy_targets_retry_after: default_proxy_no_healthy_targets_retry_after(),
+        }
+    }
 }
 
-fn default_verify_backend() -> bool {
-    true
+impl ProxyClientConfig {
+    /// Apply per-route overrides on top of these settings
+    pub fn with_overrides(&self, overrides: Option<&RouteClientConfig>) -> ProxyClientConfig {
+        let Some(overrides) = overrides else {
+            return self.clone();
+        };
+        ProxyClientConfig {
+            connect_timeout: overrides.connect_timeout.unwrap_or(self.connect_timeout),
+            pool_idle_timeout: overrides.pool_idle_timeout.unwrap_or(self.pool_idle_timeout),
+            pool_max_idle_per_host: overrides.pool_max_idle_per_host.unwrap_or(
+                self.pool_max_idle_per_host
+            ),
+            follow_redirects: overrides.follow_redirects.unwrap_or(self.follow_redirects),
+            max_hops: self.max_hops,
+            no_healthy_targets_retry_after: self.no_healthy_targets_retry_after,
+        }
+    }
 }
 
-// Phase 7.1 - Tunnel Client Configuration
-// Import tunnel configuration types
+/// Per-route overrides of the global proxy client configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouteClientConfig {
+    /// Seconds to wait for a backend connection to be established
+    pub connect_timeout: Option<u64>,
+
+    /// Seconds an idle pooled backend connection is kept open
+    pub pool_idle_timeout: Option<u64>,
+
+    /// Maximum idle connections kept per backend host (0 disables pooling)
+    pub pool_max_idle_per_host: Option<usize>,
+
+    /// Follow backend redirects instead of passing them to the client
+    pub follow_redirects: Option<bool>,
+}
+
+/// WebSocket message and idle limits for a proxy route; violations close the connection
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebSocketLimitsConfig {
+    /// Largest message payload in bytes (close code 1009 when exceeded)
+    #[serde(default)]
+    pub max_frame_bytes: Option<usize>,
+
+    /// Messages allowed per second from each side (close code 1008 when exceeded)
+    #[serde(default)]
+    pub max_messages_per_second: Option<u32>,
+
+    /// Close the connection (code 1001) after this many seconds without a message either way
+    #[serde(default)]
+    pub idle_timeout_secs: Option<u64>,
+
+    /// Ping both sides at this interval; their pongs count as activity and keep it open
+    #[serde(default)]
+    pub ping_interval_secs: Option<u64>,
+}
+
+/// Canary release for a proxy route: a share of requests bypasses load balancing for a new target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryConfig {
+    /// Target URL receiving canary traffic
+    pub target: String,
+
+    /// Percentage of requests (0-100) sent to the canary
+    pub percentage: f64,
+
+    /// Requests carrying this header always go to the canary (e.g. for testers)
+    #[serde(default)]
+    pub header_match: Option<CanaryHeaderMatch>,
+}
+
+/// Header forcing a request onto the canary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryHeaderMatch {
+    /// Header name (case-insensitive)
+    pub name: String,
+
+    /// Value the header must have
+    pub value: String,
+}
+
+impl CanaryConfig {
+    /// Whether a request with these headers goes to the canary; `roll` is uniform in [0, 100)
+    pub fn selects(&self, headers: &axum::http::HeaderMap, roll: f64) -> bool {
+        let header_matches = self.header_match.as_ref().is_some_and(|header_match| {
+            headers
+                .get_all(header_match.name.as_str())
+                .iter()
+                .any(|value| value.to_str().is_ok_and(|value| value.trim() == header_match.value))
+        });
+        header_matches || roll < self.percentage
+    }
+}
+
+/// Traffic mirroring for a proxy route: copies of requests go to a shadow backend, fire-and-forget
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MirrorConfig {
+    /// Target URL receiving the copies
+    pub target: String,
+
+    /// Percentage of requests (0-100) copied to the mirror
+    #[serde(default = "default_mirror_percentage")]
+    pub percentage: f64,
+}
+
+fn default_mirror_percentage() -> f64 {
+    100.0
+}
+
+/// Body integrity check for a proxy route: a header must carry the body's checksum or signature
+///
+/// Checksum mismatches are rejected with 400 and signature mismatches with 401. The expected
+/// value may be hex or base64, optionally prefixed like "sha256=" as webhook senders do.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BodyIntegrityConfig {
+    /// Digest computed over the raw request body
+    pub algorithm: BodyIntegrityAlgorithm,
+
+    /// Header carrying the expected value (defaults to the algorithm's usual header)
+    #[serde(default)]
+    pub header: Option<String>,
+
+    /// Shared secret for hmac_sha256
+    #[serde(default)]
+    pub secret: Option<String>,
+
+    /// Reject requests without the header; when false they are forwarded unchecked
+    #[serde(default = "default_body_integrity_required")]
+    pub required: bool,
+}
+
+impl BodyIntegrityConfig {
+    /// Header carrying the expected checksum or signature
+    pub fn header_name(&self) -> &str {
+        self.header.as_deref().unwrap_or(self.algorithm.default_header())
+    }
+}
+
+fn default_body_integrity_required() -> bool {
+    true
+}
+
+/// Digest used by a body integrity check
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BodyIntegrityAlgorithm {
+    /// MD5 checksum, as sent in Content-MD5 (RFC 1864)
+    Md5,
+    /// SHA-256 checksum
+    Sha256,
+    /// HMAC-SHA256 signature keyed with the route's secret
+    HmacSha256,
+}
+
+impl BodyIntegrityAlgorithm {
+    /// Header checked when the route does not name one
+    pub fn default_header(&self) -> &'static str {
+        match self {
+            BodyIntegrityAlgorithm::Md5 => "content-md5",
+            BodyIntegrityAlgorithm::Sha256 => "x-content-sha256",
+            BodyIntegrityAlgorithm::HmacSha256 => "x-signature",
+        }
+    }
+}
+
+/// Body logging for a proxy route; sensitive values are replaced with "***" before logging
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BodyLogConfig {
+    /// Bytes of each body to log; longer bodies are truncated with a marker
+    #[serde(default = "default_body_log_max_bytes")]
+    pub max_bytes: usize,
+
+    /// JSON field names whose values are redacted, at any depth (case-insensitive)
+    #[serde(default = "default_body_log_redact_fields")]
+    pub redact_fields: Vec<String>,
+
+    /// Header names whose values are redacted (case-insensitive)
+    #[serde(default = "default_body_log_redact_headers")]
+    pub redact_headers: Vec<String>,
+}
+
+impl Default for BodyLogConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: default_body_log_max_bytes(),
+            redact_fields: default_body_log_redact_fields(),
+            redact_headers: default_body_log_redact_headers(),
+        }
+    }
+}
+
+/// Response cache configuration for a proxy route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// Maximum number of cached responses kept for the route
+    #[serde(default = "default_cache_max_entries")]
+    pub max_entries: usize,
+
+    /// Freshness lifetime in seconds when the backend does not provide one
+    #[serde(default = "default_cache_ttl")]
+    pub default_ttl: u64,
+
+    /// Honor Cache-Control and Expires headers sent by the backend
+    #[serde(default = "default_cache_respect_origin_headers")]
+    pub respect_origin_headers: bool,
+
+    /// Largest response body stored; bigger or unsized bodies are passed through uncached
+    #[serde(default = "default_cache_max_body_bytes")]
+    pub max_body_bytes: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: default_cache_max_entries(),
+            default_ttl: default_cache_ttl(),
+            respect_origin_headers: default_cache_respect_origin_headers(),
+            max_body_bytes: default_cache_max_body_bytes(),
+        }
+    }
+}
+
+fn default_body_log_max_bytes() -> usize {
+    4096
+}
+
+fn default_body_log_redact_fields() -> Vec<String> {
+    ["password", "token", "access_token", "refresh_token", "secret", "api_key"]
+        .iter()
+        .map(|field| field.to_string())
+        .collect()
+}
+
+fn default_body_log_redact_headers() -> Vec<String> {
+    ["authorization", "proxy-authorization", "cookie", "set-cookie", "x-api-key"]
+        .iter()
+        .map(|header| header.to_string())
+        .collect()
+}
+
+fn default_cache_max_entries() -> usize {
+    1000
+}
+
+fn default_cache_ttl() -> u64 {
+    60
+}
+
+fn default_cache_respect_origin_headers() -> bool {
+    true
+}
+
+fn default_cache_max_body_bytes() -> usize {
+    1024 * 1024
+}
+
+/// JWT bearer token verification for clients of a proxy route
+///
+/// Tokens must be signed with the shared secret using the configured HMAC algorithm and be
+/// within their `exp`/`nbf` window; `issuer` and `audience` are checked when set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtAuthConfig {
+    /// Shared secret tokens are signed with
+    pub secret: String,
+
+    /// Signing algorithm tokens must declare
+    #[serde(default)]
+    pub algorithm: JwtAlgorithm,
+
+    /// Required `iss` claim
+    #[serde(default)]
+    pub issuer: Option<String>,
+
+    /// Required `aud` claim (or one of its entries)
+    #[serde(default)]
+    pub audience: Option<String>,
+
+    /// Seconds of clock skew tolerated when checking `exp` and `nbf`
+    #[serde(default = "default_jwt_leeway")]
+    pub leeway: u64,
+}
+
+fn default_jwt_leeway() -> u64 {
+    60
+}
+
+/// HMAC algorithm of a JWT signature
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum JwtAlgorithm {
+    #[default]
+    HS256,
+    HS384,
+    HS512,
+}
+
+impl JwtAlgorithm {
+    /// Value of the token header's `alg` field
+    pub fn name(&self) -> &'static str {
+        match self {
+            JwtAlgorithm::HS256 => "HS256",
+            JwtAlgorithm::HS384 => "HS384",
+            JwtAlgorithm::HS512 => "HS512",
+        }
+    }
+}
+
+/// External authorization service consulted before a proxy route forwards a request
+///
+/// The service receives a GET with the client's headers plus X-Forwarded-Method and
+/// X-Forwarded-Uri; a 2xx answer lets the request through, a 4xx one is returned to the client.
+/// Redirects are not followed and deny the request with 401.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForwardAuthConfig {
+    /// URL of the auth endpoint
+    pub url: String,
+
+    /// Headers of an allowing answer (e.g. X-User) set on the request forwarded to the backend
+    #[serde(default)]
+    pub copy_headers: Vec<String>,
+
+    /// Seconds to wait for the auth service before failing the request with 503
+    #[serde(default = "default_forward_auth_timeout")]
+    pub timeout: u64,
+}
+
+fn default_forward_auth_timeout() -> u64 {
+    5
+}
+
+/// Idempotency key deduplication for a proxy route's POST requests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdempotencyConfig {
+    /// Request header carrying the client's idempotency key
+    #[serde(default = "default_idempotency_header")]
+    pub header: String,
+
+    /// Seconds a stored response is replayed for duplicates of its key
+    #[serde(default = "default_idempotency_ttl")]
+    pub ttl: u64,
+
+    /// Maximum number of stored responses kept for the route
+    #[serde(default = "default_idempotency_max_entries")]
+    pub max_entries: usize,
+
+    /// Largest response body stored; bigger bodies are passed through and not replayed
+    #[serde(default = "default_idempotency_max_body_bytes")]
+    pub max_body_bytes: usize,
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            header: default_idempotency_header(),
+            ttl: default_idempotency_ttl(),
+            max_entries: default_idempotency_max_entries(),
+            max_body_bytes: default_idempotency_max_body_bytes(),
+        }
+    }
+}
+
+fn default_idempotency_header() -> String {
+    "idempotency-key".to_string()
+}
+
+fn default_idempotency_ttl() -> u64 {
+    86400
+}
+
+fn default_idempotency_max_entries() -> usize {
+    10000
+}
+
+fn default_idempotency_max_body_bytes() -> usize {
+    1024 * 1024
+}
+
+fn default_proxy_connect_timeout() -> u64 {
+    10
+}
+
+fn default_proxy_pool_idle_timeout() -> u64 {
+    90
+}
+
+fn default_proxy_pool_max_idle_per_host() -> usize {
+    32
+}
+
+fn default_proxy_follow_redirects() -> bool {
+    true
+}
+
+fn default_proxy_max_hops() -> u32 {
+    10
+}
+
+fn default_proxy_no_healthy_targets_retry_after() -> u64 {
+    30
+}
+
+// Default value functions for middleware configuration
+fn default_requests_per_minute() -> u32 {
+    100
+}
+
+fn default_rate_window() -> u32 {
+    60
+}
+
+fn default_max_concurrent() -> u32 {
+    10
+}
+
+fn default_rate_limit_message() -> String {
+    "Rate limit exceeded. Please try again later.".to_string()
+}
+
+fn default_cors_handle_preflight() -> bool {
+    true
+}
+
+fn default_cors_allowed_methods() -> Vec<String> {
+    ["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"].iter().map(|method| method.to_string()).collect()
+}
+
+fn default_gzip_enabled() -> bool {
+    true
+}
+
+fn default_brotli_enabled() -> bool {
+    false
+}
+
+fn default_compression_threshold() -> usize {
+    1024 // 1KB
+}
+
+fn default_compression_level() -> u32 {
+    6 // Balanced compression level
+}
+
+/// Default value functions for SSL configuration
+fn default_https_port() -> u16 {
+    443
+}
+
+fn default_force_https() -> bool {
+    false
+}
+
+fn default_ssl_protocols() -> Vec<String> {
+    vec!["TLSv1.2".to_string(), "TLSv1.3".to_string()]
+}
+
+fn default_dns_timeout() -> u64 {
+    300 // 5 minutes
+}
+
+fn default_acme_cache_dir() -> PathBuf {
+    PathBuf::from("certs/acme")
+}
+
+fn default_acme_renew_before_days() -> u64 {
+    30
+}
+
+fn default_verify_backend() -> bool {
+    true
+}
+
+// Phase 7.1 - Tunnel Client Configuration
+// Import tunnel configuration types
 pub use httpserver_tunnel::{TunnelConfig, TunnelEndpoint, TunnelAuthConfig};
 
 /// Logging configuration
@@ -648,12 +1627,22 @@ impl Default for Config {
             static_config: StaticConfig {
                 directory: PathBuf::from("."),
                 fallback: "index.html".to_string(),
+                spa_fallback: default_spa_fallback(),
+                spa_fallback_prefixes: Vec::new(),
+                mounts: Vec::new(),
+                cache_control: CacheControlConfig::default(),
+                basic_auth: None,
             },
             proxy: Vec::new(),
+            tcp_routes: Vec::new(),
+            default_route: None,
+            listeners: Vec::new(),
             logging: LoggingConfig::default(),
             application: ApplicationConfig::default(),
             server: ServerConfig::default(),
             tunnel: TunnelConfig::default(),
+            proxy_client: ProxyClientConfig::default(),
+            startup_check: StartupCheckConfig::default(),
         }
     }
 }
@@ -662,32 +1651,194 @@ fn default_fallback() -> String {
     "index.html".to_string()
 }
 
+fn default_spa_fallback() -> bool {
+    true
+}
+
 fn default_timeout() -> u64 {
     30
 }
 
+fn default_forwarded_headers() -> bool {
+    true
+}
+
+fn default_access_log() -> bool {
+    true
+}
+
+/// Errors loading or validating the configuration
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The configuration file could not be read
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The configuration file is not valid TOML for this schema
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    /// The static files directory does not exist
+    MissingStaticDirectory(PathBuf),
+    /// A Cache-Control value cannot be sent as a header value
+    InvalidCacheControl(String),
+    /// The static basic_auth credentials are unusable; the reason never includes the secret
+    InvalidStaticBasicAuth(String),
+    /// A proxy route (or one of its targets) is misconfigured
+    InvalidProxyRoute {
+        index: usize,
+        target: Option<usize>,
+        reason: String,
+    },
+    /// The default route's target is not a valid HTTP/HTTPS URL
+    InvalidDefaultRoute(String),
+    /// The default route's timeout is 0, which would time out every request
+    InvalidDefaultRouteTimeout,
+    /// The health endpoint prefix is not a path like "/_internal"
+    InvalidHealthPathPrefix(String),
+    /// A trusted proxy entry is neither a CIDR block nor an IP address
+    InvalidTrustedProxy(String),
+    /// The maintenance settings are unusable
+    InvalidMaintenance(String),
+    /// A TCP route is misconfigured
+    InvalidTcpRoute {
+        index: usize,
+        reason: String,
+    },
+    /// An additional listener (or one of its proxy routes) is misconfigured
+    InvalidListener {
+        index: usize,
+        reason: String,
+    },
+    /// A certificate or key file named by the SSL configuration does not exist
+    MissingSslFile {
+        field: &'static str,
+        path: PathBuf,
+    },
+}
+
+impl ConfigError {
+    fn proxy_route(index: usize, reason: impl Into<String>) -> Self {
+        ConfigError::InvalidProxyRoute { index, target: None, reason: reason.into() }
+    }
+
+    fn proxy_target(index: usize, target: usize, reason: impl Into<String>) -> Self {
+        ConfigError::InvalidProxyRoute { index, target: Some(target), reason: reason.into() }
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Read { path, source } =>
+                write!(f, "Failed to read config file '{}': {}", path.display(), source),
+            ConfigError::Parse { path, source } =>
+                write!(f, "Failed to parse TOML in '{}': {}", path.display(), source),
+            ConfigError::MissingStaticDirectory(path) =>
+                write!(f, "Static directory does not exist: {}", path.display()),
+            ConfigError::InvalidCacheControl(value) =>
+                write!(f, "Invalid Cache-Control value: {:?}", value),
+            ConfigError::InvalidStaticBasicAuth(reason) =>
+                write!(f, "Invalid static basic_auth: {}", reason),
+            ConfigError::InvalidProxyRoute { index, target: None, reason } =>
+                write!(f, "Proxy route {}: {}", index, reason),
+            ConfigError::InvalidProxyRoute { index, target: Some(target), reason } =>
+                write!(f, "Proxy route {} target {}: {}", index, target, reason),
+            ConfigError::InvalidDefaultRoute(target) =>
+                write!(f, "Default route target must be a valid HTTP/HTTPS URL: {}", target),
+            ConfigError::InvalidDefaultRouteTimeout =>
+                write!(f, "Default route timeout must be greater than 0"),
+            ConfigError::InvalidHealthPathPrefix(prefix) =>
+                write!(f, "Health path prefix must start with '/' and not end with '/': {:?}", prefix),
+            ConfigError::InvalidTrustedProxy(entry) =>
+                write!(f, "Invalid trusted proxy '{}': expected CIDR or IP address", entry),
+            ConfigError::InvalidMaintenance(reason) =>
+                write!(f, "Invalid maintenance settings: {}", reason),
+            ConfigError::InvalidTcpRoute { index, reason } =>
+                write!(f, "TCP route {}: {}", index, reason),
+            ConfigError::InvalidListener { index, reason } =>
+                write!(f, "Listener {}: {}", index, reason),
+            ConfigError::MissingSslFile { field, path } =>
+                write!(f, "SSL {} does not exist: {}", field, path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Read { source, .. } => Some(source),
+            ConfigError::Parse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Likely mistakes that do not stop the configuration from loading
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigWarning {
+    /// A proxy route target points back at this server, so its requests loop until the hop limit
+    SelfTargetingRoute {
+        index: usize,
+        target: String,
+    },
+}
+
+impl std::fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigWarning::SelfTargetingRoute { index, target } =>
+                write!(f, "Proxy route {} targets this server and will loop until the hop limit is reached: {}", index, target),
+        }
+    }
+}
+
 impl Config {
     /// Load configuration from TOML file
-    pub fn load_from_file(path: &PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn load_from_file(path: &PathBuf) -> Result<Self, ConfigError> {
         // Read the configuration file
         let content = std::fs
             ::read_to_string(path)
-            .map_err(|e| format!("Failed to read config file '{}': {}", path.display(), e))?;
+            .map_err(|source| ConfigError::Read { path: path.clone(), source })?;
 
         // Parse TOML content
         let config: Config = toml
             ::from_str(&content)
-            .map_err(|e| format!("Failed to parse TOML in '{}': {}", path.display(), e))?;
+            .map_err(|source| ConfigError::Parse { path: path.clone(), source })?;
 
         // Validate configuration
         config.validate()?;
 
-        println!("Loaded configuration from: {}", path.display());
+        eprintln!("Loaded configuration from: {}", path.display());
         Ok(config)
     }
 
+    /// Load and validate a configuration file without starting anything (`--check-config`)
+    ///
+    /// Unlike `load_from_file` every problem is reported, and the SSL certificate and key
+    /// files must exist on disk. A valid configuration comes back with its warnings.
+    pub fn check_file(path: &PathBuf) -> Result<(Self, Vec<ConfigWarning>), Vec<ConfigError>> {
+        let content = std::fs
+            ::read_to_string(path)
+            .map_err(|source| vec![ConfigError::Read { path: path.clone(), source }])?;
+        let config: Config = toml
+            ::from_str(&content)
+            .map_err(|source| vec![ConfigError::Parse { path: path.clone(), source }])?;
+
+        let mut errors = config.validation_errors();
+        errors.extend(config.ssl_file_errors());
+        if errors.is_empty() {
+            let warnings = config.validation_warnings();
+            Ok((config, warnings))
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Create config from command line arguments
-    pub fn from_args(args: Args) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn from_args(args: Args) -> Result<Self, ConfigError> {
         let mut config = if let Some(config_path) = &args.config {
             Self::load_from_file(config_path)?
         } else {
@@ -696,12 +1847,31 @@ impl Config {
 
         // Override with CLI arguments
         config.static_config.directory = args.directory;
+        if args.self_signed {
+            config.enable_self_signed_ssl();
+        }
 
         Ok(config)
     }
 
+    /// Render the configuration as TOML, optionally replacing secret values with "[REDACTED]"
+    pub fn to_toml(&self, redact_secrets: bool) -> Result<String, toml::ser::Error> {
+        let mut value = toml::Value::try_from(self)?;
+        if redact_secrets {
+            redact_secret_values(&mut value);
+        }
+        toml::to_string_pretty(&value)
+    }
+
+    /// Enable HTTPS with a generated self-signed certificate (`--self-signed`)
+    pub fn enable_self_signed_ssl(&mut self) {
+        let ssl_config = self.server.ssl.get_or_insert_with(SslConfig::default);
+        ssl_config.enabled = true;
+        ssl_config.self_signed = true;
+    }
+
     /// Load application configuration from app_config.toml file
-    pub fn load_app_config() -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn load_app_config() -> Result<Self, ConfigError> {
         let app_config_path = PathBuf::from("app_config.toml");
 
         if app_config_path.exists() {
@@ -720,24 +1890,261 @@ impl Config {
     }
 
     /// Validate the configuration
-    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(error) = self.validation_errors().into_iter().next() {
+            return Err(error);
+        }
+        for warning in self.validation_warnings() {
+            eprintln!("Configuration warning: {}", warning);
+        }
+
+        eprintln!("Configuration validation passed");
+        Ok(())
+    }
+
+    /// Likely mistakes in a configuration that is otherwise valid, for the configured
+    /// `server.default_port`
+    pub fn validation_warnings(&self) -> Vec<ConfigWarning> {
+        self.self_targeting_routes(self.server.default_port)
+            .into_iter()
+            .map(|(index, target)| ConfigWarning::SelfTargetingRoute { index, target })
+            .collect()
+    }
+
+    /// Every problem `validate` checks for, in the order it checks them
+    pub fn validation_errors(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+
         // Validate static directory exists
         if !self.static_config.directory.exists() {
-            return Err(
-                format!(
-                    "Static directory does not exist: {}",
-                    self.static_config.directory.display()
-                ).into()
-            );
+            errors.push(ConfigError::MissingStaticDirectory(self.static_config.directory.clone()));
+        }
+
+        // Cache-Control values are sent verbatim as header values
+        let cache_control = &self.static_config.cache_control;
+        for value in std::iter
+            ::once(&cache_control.default)
+            .chain(cache_control.rules.iter().map(|rule| &rule.value)) {
+            if axum::http::HeaderValue::from_str(value).is_err() {
+                errors.push(ConfigError::InvalidCacheControl(value.clone()));
+            }
+        }
+
+        // Static credentials need a username, and an empty list would lock everyone out
+        if let Some(basic_auth) = &self.static_config.basic_auth {
+            if basic_auth.credentials().is_empty() {
+                errors.push(ConfigError::InvalidStaticBasicAuth("no credentials listed".to_string()));
+            }
+            for (index, credential) in basic_auth.credentials().iter().enumerate() {
+                if credential.split_once(':').is_none_or(|(user, _)| user.is_empty()) {
+                    errors.push(
+                        ConfigError::InvalidStaticBasicAuth(format!("entry {} must be \"user:password\"", index))
+                    );
+                }
+            }
         }
 
-        // Validate proxy routes
+        // Validate proxy routes, which are identified by host and path
         for (index, route) in self.proxy.iter().enumerate() {
-            route.validate(index)?;
+            if let Err(error) = route.validate(index) {
+                errors.push(error);
+            } else if self.proxy[..index].iter().any(|other| other.host == route.host && other.path == route.path) {
+                errors.push(ConfigError::proxy_route(index, "another proxy route has the same host and path"));
+            }
         }
 
-        println!("Configuration validation passed");
-        Ok(())
+        // The default route forwards like a proxy route
+        if let Some(default_route) = &self.default_route {
+            if !default_route.target.starts_with("http://") && !default_route.target.starts_with("https://") {
+                errors.push(ConfigError::InvalidDefaultRoute(default_route.target.clone()));
+            }
+            if default_route.timeout == 0 {
+                errors.push(ConfigError::InvalidDefaultRouteTimeout);
+            }
+        }
+
+        // Health endpoints are nested under the prefix, which must be a path without a trailing slash
+        let prefix = &self.server.health_path_prefix;
+        if !prefix.is_empty() && (!prefix.starts_with('/') || prefix.ends_with('/')) {
+            errors.push(ConfigError::InvalidHealthPathPrefix(prefix.clone()));
+        }
+
+        // The engine parses these at startup; checking here lets --check-config catch a typo
+        errors.extend(
+            self.server.trusted_proxies
+                .iter()
+                .filter_map(|entry| parse_trusted_proxy(entry).err())
+        );
+
+        // Routes in maintenance are named by path pattern, so a typo would silently serve traffic
+        let maintenance = &self.server.maintenance;
+        errors.extend(maintenance.validate().err());
+        for path in &maintenance.routes {
+            if !self.proxy.iter().any(|route| &route.path == path) {
+                errors.push(ConfigError::InvalidMaintenance(format!("routes names no proxy route: {:?}", path)));
+            }
+        }
+
+        // Validate TCP routes, which must not share a listening port
+        for (index, route) in self.tcp_routes.iter().enumerate() {
+            if let Err(error) = route.validate(index) {
+                errors.push(error);
+            } else if self.tcp_routes[..index].iter().any(|other| other.listen_port == route.listen_port) {
+                errors.push(ConfigError::InvalidTcpRoute {
+                    index,
+                    reason: format!("listen_port {} is used by another TCP route", route.listen_port),
+                });
+            }
+        }
+
+        // Validate additional listeners, which must not share a port, and their routes
+        for (index, listener) in self.listeners.iter().enumerate() {
+            if self.listeners[..index].iter().any(|other| other.port == listener.port) {
+                errors.push(ConfigError::InvalidListener {
+                    index,
+                    reason: format!("port {} is used by another listener", listener.port),
+                });
+            }
+            if let Some(directory) = listener.static_directory.as_ref().filter(|directory| !directory.exists()) {
+                errors.push(ConfigError::InvalidListener {
+                    index,
+                    reason: format!("static directory does not exist: {}", directory.display()),
+                });
+            }
+            for (route_index, route) in listener.proxy.iter().enumerate() {
+                if let Err(error) = route.validate(route_index) {
+                    errors.push(ConfigError::InvalidListener { index, reason: error.to_string() });
+                } else if
+                    listener.proxy[..route_index].iter().any(|other| other.host == route.host && other.path == route.path)
+                {
+                    let error = ConfigError::proxy_route(route_index, "another proxy route has the same host and path");
+                    errors.push(ConfigError::InvalidListener { index, reason: error.to_string() });
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Configured certificate and key files of an enabled SSL setup that are missing on disk
+    fn ssl_file_errors(&self) -> Vec<ConfigError> {
+        let Some(ssl) = self.server.ssl.as_ref().filter(|ssl| ssl.enabled) else {
+            return Vec::new();
+        };
+
+        let wildcard = ssl.wildcard.as_ref();
+        [
+            ("cert_file", ssl.cert_file.as_ref()),
+            ("key_file", ssl.key_file.as_ref()),
+            ("cert_chain_file", ssl.cert_chain_file.as_ref()),
+            ("wildcard cert_file", wildcard.map(|wildcard| &wildcard.cert_file)),
+            ("wildcard key_file", wildcard.map(|wildcard| &wildcard.key_file)),
+        ]
+            .into_iter()
+            .filter_map(|(field, path)| path.filter(|path| !path.exists()).map(|path| (field, path)))
+            .map(|(field, path)| ConfigError::MissingSslFile { field, path: path.clone() })
+            .collect()
+    }
+
+    /// Proxy targets that point back at this server when it listens on `port` (or its HTTPS
+    /// port), as (route index, target URL); requests to these routes would loop
+    pub fn self_targeting_routes(&self, port: u16) -> Vec<(usize, String)> {
+        let https_port = self.server.ssl
+            .as_ref()
+            .filter(|ssl| ssl.enabled)
+            .map(|ssl| ssl.https_port);
+
+        let mut self_targets = Vec::new();
+        for (index, route) in self.proxy.iter().enumerate() {
+            for target in route.get_targets() {
+                if let Some(target_port) = local_target_port(&target.url) {
+                    if target_port == port || Some(target_port) == https_port {
+                        self_targets.push((index, target.url));
+                    }
+                }
+            }
+        }
+        self_targets
+    }
+}
+
+/// Keys whose values (or every value beneath them) are credentials
+const SECRET_KEYS: &[&str] = &[
+    "api_key",
+    "basic_auth",
+    "bearer_token",
+    "credentials",
+    "custom_auth_header",
+    "key_value",
+    "password",
+    "redis_url",
+    "secret",
+    "token",
+];
+
+/// Replace every string under a secret key with a placeholder
+fn redact_secret_values(value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table.iter_mut() {
+                if SECRET_KEYS.contains(&key.as_str()) {
+                    redact_strings(value);
+                } else {
+                    redact_secret_values(value);
+                }
+            }
+        }
+        toml::Value::Array(values) => values.iter_mut().for_each(redact_secret_values),
+        _ => {}
+    }
+}
+
+fn redact_strings(value: &mut toml::Value) {
+    match value {
+        toml::Value::String(secret) => {
+            *secret = "[REDACTED]".to_string();
+        }
+        toml::Value::Table(table) => table.iter_mut().for_each(|(_, value)| redact_strings(value)),
+        toml::Value::Array(values) => values.iter_mut().for_each(redact_strings),
+        _ => {}
+    }
+}
+
+/// Compile a route pattern so it must match the whole path or host, not just part of it
+pub fn anchored_regex(pattern: &str) -> Result<regex::Regex, regex::Error> {
+    regex::Regex::new(&format!("^(?:{})$", pattern))
+}
+
+/// Port of a target URL whose host is this machine (loopback, unspecified or "localhost")
+fn local_target_port(url: &str) -> Option<u16> {
+    let (scheme, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let authority = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+
+    // IPv6 hosts are bracketed: [::1]:8080
+    let (host, port) = if let Some(bracketed) = authority.strip_prefix('[') {
+        let (host, rest) = bracketed.split_once(']')?;
+        (host, rest.strip_prefix(':'))
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+
+    let is_local = host.eq_ignore_ascii_case("localhost") ||
+        host
+            .parse::<std::net::IpAddr>()
+            .map(|ip| ip.is_loopback() || ip.is_unspecified())
+            .unwrap_or(false);
+    if !is_local {
+        return None;
+    }
+
+    match port {
+        Some(port) => port.parse().ok(),
+        None if scheme.eq_ignore_ascii_case("https") => Some(443),
+        None => Some(80),
     }
 }
 
@@ -770,11 +2177,114 @@ impl ProxyRoute {
         self.get_targets().len() > 1
     }
 
-    /// Validate this proxy route
-    fn validate(&self, index: usize) -> Result<(), Box<dyn std::error::Error>> {
+    /// Whether a request header may be forwarded under the route's header allowlist and denylist
+    pub fn forwards_header(&self, name: &str) -> bool {
+        let listed = |names: &[String]| names.iter().any(|listed| listed.eq_ignore_ascii_case(name));
+        !listed(&self.header_denylist) && self.header_allowlist.as_deref().is_none_or(listed)
+    }
+
+    /// Validate this proxy route; `index` is its position, used in error messages
+    pub fn validate(&self, index: usize) -> Result<(), ConfigError> {
         // Validate path pattern
         if self.path.is_empty() {
-            return Err(format!("Proxy route {}: path cannot be empty", index).into());
+            return Err(ConfigError::proxy_route(index, "path cannot be empty"));
+        }
+
+        // Regex routes are compiled when loaded so a typo fails here rather than never matching
+        if self.match_regex {
+            for (field, pattern) in [("path", Some(&self.path)), ("host", self.host.as_ref())] {
+                if let Some(Err(e)) = pattern.map(|pattern| anchored_regex(pattern)) {
+                    return Err(ConfigError::proxy_route(index, format!("invalid {} regex: {}", field, e)));
+                }
+            }
+        }
+
+        let filtered_headers = self.header_allowlist.iter().flatten().chain(&self.header_denylist);
+        if let Some(name) = filtered_headers.into_iter().find(|name| axum::http::HeaderName::from_bytes(name.as_bytes()).is_err()) {
+            return Err(ConfigError::proxy_route(index, format!("header filter lists an invalid header name: {:?}", name)));
+        }
+
+        let format_variables = self.access_log_format.iter().flat_map(|format| access_log_segments(format));
+        for segment in format_variables {
+            if let AccessLogSegment::Variable(name) = segment {
+                if !ACCESS_LOG_VARIABLES.contains(&name) {
+                    return Err(ConfigError::proxy_route(index, format!("access_log_format uses unknown variable ${}", name)));
+                }
+            }
+        }
+
+        if let Some(idempotency) = &self.idempotency {
+            if axum::http::HeaderName::from_bytes(idempotency.header.as_bytes()).is_err() {
+                return Err(
+                    ConfigError::proxy_route(
+                        index,
+                        format!("idempotency header is not a valid header name: {:?}", idempotency.header)
+                    )
+                );
+            }
+            if idempotency.ttl == 0 {
+                return Err(ConfigError::proxy_route(index, "idempotency ttl must be greater than 0"));
+            }
+        }
+
+        // Only files inside the static directory may be served
+        if let Some(file) = &self.fallback_file {
+            let inside_static_dir = file
+                .components()
+                .all(|component| matches!(component, std::path::Component::Normal(_)));
+            if !inside_static_dir || file.as_os_str().is_empty() {
+                return Err(
+                    ConfigError::proxy_route(
+                        index,
+                        format!("fallback_file must be a relative path inside the static directory: {}", file.display())
+                    )
+                );
+            }
+        }
+
+        if let Some(jwt_auth) = &self.jwt_auth {
+            if jwt_auth.secret.is_empty() {
+                return Err(ConfigError::proxy_route(index, "jwt_auth secret cannot be empty"));
+            }
+        }
+        if let Some(forward_auth) = &self.forward_auth {
+            if !forward_auth.url.starts_with("http://") && !forward_auth.url.starts_with("https://") {
+                return Err(
+                    ConfigError::proxy_route(
+                        index,
+                        format!("forward_auth url must be a valid HTTP/HTTPS URL: {}", forward_auth.url)
+                    )
+                );
+            }
+            if forward_auth.timeout == 0 {
+                return Err(ConfigError::proxy_route(index, "forward_auth timeout must be greater than 0"));
+            }
+            for name in &forward_auth.copy_headers {
+                if axum::http::HeaderName::from_bytes(name.as_bytes()).is_err() {
+                    return Err(
+                        ConfigError::proxy_route(
+                            index,
+                            format!("forward_auth copy_headers entry is not a valid header name: {:?}", name)
+                        )
+                    );
+                }
+            }
+        }
+
+        // Signatures cannot be checked without the shared secret
+        if let Some(integrity) = &self.body_integrity {
+            if axum::http::HeaderName::from_bytes(integrity.header_name().as_bytes()).is_err() {
+                return Err(
+                    ConfigError::proxy_route(
+                        index,
+                        format!("body_integrity header is not a valid header name: {:?}", integrity.header_name())
+                    )
+                );
+            }
+            let has_secret = integrity.secret.as_deref().is_some_and(|secret| !secret.is_empty());
+            if integrity.algorithm == BodyIntegrityAlgorithm::HmacSha256 && !has_secret {
+                return Err(ConfigError::proxy_route(index, "body_integrity secret is required for hmac_sha256"));
+            }
         }
 
         let targets = self.get_targets();
@@ -782,49 +2292,116 @@ impl ProxyRoute {
         // Validate that at least one target is configured
         if targets.is_empty() {
             return Err(
-                format!("Proxy route {}: must have at least one target (use 'target' or 'targets')", index).into()
+                ConfigError::proxy_route(index, "must have at least one target (use 'target' or 'targets')")
             );
         }
 
         // Validate all target URLs
         for (target_index, target) in targets.iter().enumerate() {
             if target.url.is_empty() {
-                return Err(
-                    format!(
-                        "Proxy route {} target {}: URL cannot be empty",
-                        index,
-                        target_index
-                    ).into()
-                );
+                return Err(ConfigError::proxy_target(index, target_index, "URL cannot be empty"));
             }
 
             // Basic URL validation
             if !target.url.starts_with("http://") && !target.url.starts_with("https://") {
                 return Err(
-                    format!(
-                        "Proxy route {} target {}: must be a valid HTTP/HTTPS URL: {}",
+                    ConfigError::proxy_target(
                         index,
                         target_index,
-                        target.url
-                    ).into()
+                        format!("must be a valid HTTP/HTTPS URL: {}", target.url)
+                    )
                 );
             }
 
             // Validate weight
             if target.weight == 0 {
-                return Err(
-                    format!(
-                        "Proxy route {} target {}: weight must be greater than 0",
-                        index,
-                        target_index
-                    ).into()
-                );
+                return Err(ConfigError::proxy_target(index, target_index, "weight must be greater than 0"));
             }
         }
 
         // Validate timeout
         if self.timeout == 0 {
-            return Err(format!("Proxy route {}: timeout must be greater than 0", index).into());
+            return Err(ConfigError::proxy_route(index, "timeout must be greater than 0"));
+        }
+        for (field, value) in [("connect_timeout", self.connect_timeout), ("read_timeout", self.read_timeout)] {
+            if value == Some(0) {
+                return Err(ConfigError::proxy_route(index, format!("{} must be greater than 0", field)));
+            }
+        }
+
+        // Body expectations for HTTP health checks must name a field
+        if let Some(http_health) = &self.http_health {
+            if http_health.expected_body_json.is_some() && http_health.expected_json_field().is_none() {
+                return Err(
+                    ConfigError::proxy_route(index, "http_health expected_body_json must be written \"field=value\"")
+                );
+            }
+        }
+
+        // The Redis rate limit backend needs somewhere to connect
+        if let Some(rate_limit) = self.middleware.as_ref().and_then(|m| m.rate_limit.as_ref()) {
+            if rate_limit.backend == RateLimitBackend::Redis && rate_limit.redis_url.is_none() {
+                return Err(ConfigError::proxy_route(index, "rate_limit backend \"redis\" requires redis_url"));
+            }
+        }
+
+        // Credentials for any origin would let every site make authenticated requests
+        if let Some(cors) = self.middleware.as_ref().and_then(|m| m.cors.as_ref()) {
+            if cors.allow_credentials && cors.allows_any_origin() {
+                return Err(
+                    ConfigError::proxy_route(index, "cors allow_credentials requires allowed_origins to list the origins explicitly")
+                );
+            }
+        }
+
+        // The canary forwards like any other target, to a share of the traffic
+        if let Some(canary) = &self.canary {
+            if !canary.target.starts_with("http://") && !canary.target.starts_with("https://") {
+                return Err(
+                    ConfigError::proxy_route(index, format!("canary target must be a valid HTTP/HTTPS URL: {}", canary.target))
+                );
+            }
+            if !(0.0..=100.0).contains(&canary.percentage) {
+                return Err(ConfigError::proxy_route(index, "canary percentage must be between 0 and 100"));
+            }
+        }
+
+        if let Some(mirror) = &self.mirror {
+            if !mirror.target.starts_with("http://") && !mirror.target.starts_with("https://") {
+                return Err(
+                    ConfigError::proxy_route(index, format!("mirror target must be a valid HTTP/HTTPS URL: {}", mirror.target))
+                );
+            }
+            if !(0.0..=100.0).contains(&mirror.percentage) {
+                return Err(ConfigError::proxy_route(index, "mirror percentage must be between 0 and 100"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TcpRoute {
+    /// Validate this TCP route; `index` is its position, used in error messages
+    pub fn validate(&self, index: usize) -> Result<(), ConfigError> {
+        let invalid = |reason: String| ConfigError::InvalidTcpRoute { index, reason };
+
+        if self.listen_port == 0 {
+            return Err(invalid("listen_port must be greater than 0".to_string()));
+        }
+        if self.targets.is_empty() {
+            return Err(invalid("must have at least one target".to_string()));
+        }
+
+        // Targets are plain socket addresses, not URLs
+        for target in &self.targets {
+            let valid = match target.url.rsplit_once(':') {
+                Some((host, port)) => !host.is_empty() && !host.contains('/') && port.parse::<u16>().is_ok(),
+                None => false,
+            };
+            if !valid {
+                return Err(invalid(format!("target must be \"host:port\": {}", target.url)));
+            }
         }
 
         Ok(())
@@ -850,18 +2427,66 @@ pub struct ServerConfig {
     #[serde(default = "default_server_port")]
     pub default_port: u16,
 
-    /// Request timeout in seconds
+    /// Request timeout in seconds (0 disables)
     #[serde(default = "default_request_timeout")]
     pub request_timeout: u64,
 
-    /// Maximum request body size in MB
+    /// Maximum request body size in MB; larger requests get a 413 (0 disables)
     #[serde(default = "default_max_request_size_mb")]
     pub max_request_size_mb: u64,
 
-    /// Enable health endpoints
+    /// Maximum number of request header fields; more get a 431 (0 disables)
+    #[serde(default = "default_max_header_count")]
+    pub max_header_count: usize,
+
+    /// Maximum combined bytes of request header names and values; more get a 431 (0 disables)
+    #[serde(default = "default_max_header_bytes")]
+    pub max_header_bytes: usize,
+
+    /// Serve the built-in health, probe and stats endpoints (/health, /readyz, /proxy/health, ...)
     #[serde(default = "default_enable_health_endpoints")]
     pub enable_health_endpoints: bool,
 
+    /// Path prefix for the built-in health endpoints, e.g. "/_internal" serves /_internal/health
+    #[serde(default)]
+    pub health_path_prefix: String,
+
+    /// Accept cleartext HTTP/2 (h2c, prior knowledge) on the HTTP port; HTTPS always offers h2
+    #[serde(default)]
+    pub http2_cleartext: bool,
+
+    /// Disable Nagle's algorithm on accepted HTTP and HTTPS connections
+    #[serde(default)]
+    pub tcp_nodelay: bool,
+
+    /// Idle seconds before TCP keep-alive probes are sent on accepted connections (0 disables)
+    #[serde(default)]
+    pub tcp_keepalive_secs: u64,
+
+    /// Also serve on a Unix domain socket at this path (Unix only)
+    #[serde(default)]
+    pub unix_socket: Option<PathBuf>,
+
+    /// File permissions applied to the Unix socket
+    #[serde(default = "default_unix_socket_mode")]
+    pub unix_socket_mode: u32,
+
+    /// Proxies (CIDR blocks or addresses) trusted to report the client IP in X-Forwarded-For
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
+
+    /// Files served as the body of generated error responses, keyed by status code
+    #[serde(default, with = "error_pages::status_code_keys")]
+    pub error_pages: std::collections::HashMap<u16, PathBuf>,
+
+    /// Answer traffic with 503 during deploys while health endpoints stay up
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+
+    /// Cap on requests handled at once across the gateway, with a bounded wait queue
+    #[serde(default)]
+    pub concurrency_limit: ConcurrencyLimitConfig,
+
     /// SSL/TLS configuration
     #[serde(default)]
     pub ssl: Option<SslConfig>,
@@ -887,10 +2512,22 @@ fn default_max_request_size_mb() -> u64 {
     10
 }
 
+fn default_max_header_count() -> usize {
+    100
+}
+
+fn default_max_header_bytes() -> usize {
+    32 * 1024
+}
+
 fn default_enable_health_endpoints() -> bool {
     true
 }
 
+fn default_unix_socket_mode() -> u32 {
+    0o660
+}
+
 impl Default for ApplicationConfig {
     fn default() -> Self {
         Self {
@@ -906,12 +2543,157 @@ impl Default for ServerConfig {
             default_port: default_server_port(),
             request_timeout: default_request_timeout(),
             max_request_size_mb: default_max_request_size_mb(),
+            max_header_count: default_max_header_count(),
+            max_header_bytes: default_max_header_bytes(),
             enable_health_endpoints: default_enable_health_endpoints(),
+            health_path_prefix: String::new(),
+            http2_cleartext: false,
+            tcp_nodelay: false,
+            tcp_keepalive_secs: 0,
+            unix_socket: None,
+            unix_socket_mode: default_unix_socket_mode(),
+            trusted_proxies: Vec::new(),
+            error_pages: std::collections::HashMap::new(),
+            maintenance: MaintenanceConfig::default(),
+            concurrency_limit: ConcurrencyLimitConfig::default(),
             ssl: None,
         }
     }
 }
 
+/// Parse a `trusted_proxies` entry: a CIDR block or bare address such as "10.0.0.0/8" or "192.0.2.1"
+pub fn parse_trusted_proxy(entry: &str) -> Result<ipnet::IpNet, ConfigError> {
+    let entry = entry.trim();
+    entry
+        .parse::<ipnet::IpNet>()
+        .or_else(|_| entry.parse::<std::net::IpAddr>().map(ipnet::IpNet::from))
+        .map_err(|_| ConfigError::InvalidTrustedProxy(entry.to_string()))
+}
+
+/// Maintenance mode, toggled at runtime through `EngineHandle` as well as configured here
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaintenanceConfig {
+    /// Answer every request with 503 except health endpoints and `allow_paths`
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Proxy route path patterns (e.g. "/api/*") in maintenance while the rest serves normally
+    #[serde(default)]
+    pub routes: Vec<String>,
+
+    /// Seconds clients are told to wait in the Retry-After header
+    #[serde(default = "default_maintenance_retry_after")]
+    pub retry_after: u64,
+
+    /// HTML file served as the 503 body; defaults to the 503 error page, then a built-in page
+    #[serde(default)]
+    pub page: Option<PathBuf>,
+
+    /// Path prefixes served normally during maintenance, e.g. "/status"
+    #[serde(default)]
+    pub allow_paths: Vec<String>,
+}
+
+fn default_maintenance_retry_after() -> u64 {
+    300
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            routes: Vec::new(),
+            retry_after: default_maintenance_retry_after(),
+            page: None,
+            allow_paths: Vec::new(),
+        }
+    }
+}
+
+impl MaintenanceConfig {
+    /// Whether any traffic is currently answered with 503
+    pub fn is_active(&self) -> bool {
+        self.enabled || !self.routes.is_empty()
+    }
+
+    /// Whether a request path stays available during maintenance
+    pub fn allows_path(&self, path: &str) -> bool {
+        self.allow_paths.iter().any(|prefix| path.starts_with(prefix.as_str()))
+    }
+
+    /// Check that allowed paths are absolute
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match self.allow_paths.iter().find(|prefix| !prefix.starts_with('/')) {
+            Some(prefix) => Err(
+                ConfigError::InvalidMaintenance(format!("allow_paths entry must start with '/': {:?}", prefix))
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Gateway-wide limit on concurrent requests; excess requests wait in a queue, then get a 503
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConcurrencyLimitConfig {
+    /// Maximum requests handled at once (0 disables the limit)
+    #[serde(default)]
+    pub max_requests: usize,
+
+    /// Maximum requests waiting for a slot; more get a 503 immediately
+    #[serde(default = "default_concurrency_queue_size")]
+    pub queue_size: usize,
+
+    /// Milliseconds a queued request waits for a slot before getting a 503
+    #[serde(default = "default_concurrency_queue_timeout_ms")]
+    pub queue_timeout_ms: u64,
+
+    /// Seconds clients are told to wait in the Retry-After header
+    #[serde(default = "default_concurrency_retry_after")]
+    pub retry_after: u64,
+}
+
+fn default_concurrency_queue_size() -> usize {
+    100
+}
+
+fn default_concurrency_queue_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_concurrency_retry_after() -> u64 {
+    1
+}
+
+impl Default for ConcurrencyLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_requests: 0,
+            queue_size: default_concurrency_queue_size(),
+            queue_timeout_ms: default_concurrency_queue_timeout_ms(),
+            retry_after: default_concurrency_retry_after(),
+        }
+    }
+}
+
+impl Default for SslConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            https_port: default_https_port(),
+            cert_file: None,
+            key_file: None,
+            cert_chain_file: None,
+            wildcard: None,
+            lets_encrypt: None,
+            self_signed: false,
+            force_https: default_force_https(),
+            redirect: None,
+            protocols: default_ssl_protocols(),
+            cipher_suites: Vec::new(),
+        }
+    }
+}
+
 /// Health status information for the config service
 #[derive(Debug, Clone, Serialize)]
 pub struct ConfigHealthStatus {
@@ -0,0 +1,568 @@
+// ACME (RFC 8555) certificate issuance for Let's Encrypt and compatible CAs
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::io::Write;
+use std::path::{ Path, PathBuf };
+use std::pin::Pin;
+use std::sync::{ Arc, RwLock };
+use std::time::Duration;
+use axum::{
+    body::Bytes,
+    extract::{ Path as UrlPath, State },
+    http::{ header, StatusCode },
+    response::{ IntoResponse, Response },
+    routing::get,
+    Router,
+};
+use chrono::{ DateTime, Utc };
+use http_body_util::{ BodyExt, Full };
+use httpserver_config::{ DnsChallengeConfig, LetsEncryptConfig };
+use instant_acme::{
+    Account,
+    AccountCredentials,
+    Authorization,
+    AuthorizationStatus,
+    BytesResponse,
+    ChallengeType,
+    HttpClient,
+    Identifier,
+    NewAccount,
+    NewOrder,
+    Order,
+};
+use serde_json::{ json, Value };
+use tokio::task::JoinHandle;
+
+/// Let's Encrypt production directory
+pub const LETS_ENCRYPT_PRODUCTION_DIRECTORY: &str =
+    "https://acme-v02.api.letsencrypt.org/directory";
+
+/// Let's Encrypt staging directory (untrusted certificates, generous rate limits)
+pub const LETS_ENCRYPT_STAGING_DIRECTORY: &str =
+    "https://acme-staging-v02.api.letsencrypt.org/directory";
+
+/// Path prefix the CA fetches HTTP-01 key authorizations from
+pub const ACME_CHALLENGE_PATH_PREFIX: &str = "/.well-known/acme-challenge/";
+
+/// How often the renewal task checks certificate expiry
+pub const ACME_RENEWAL_CHECK_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// Delay between polls of pending authorizations and orders
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Maximum time to wait for an HTTP-01 authorization to be validated
+const HTTP_CHALLENGE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Time given to DNS changes to propagate before asking the CA to validate
+const DNS_PROPAGATION_DELAY: Duration = Duration::from_secs(10);
+
+pub type AcmeResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Pending HTTP-01 key authorizations, keyed by challenge token
+#[derive(Debug, Clone, Default)]
+pub struct AcmeChallengeStore {
+    tokens: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl AcmeChallengeStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish a key authorization for a challenge token
+    pub fn insert(&self, token: &str, key_authorization: &str) {
+        self.tokens.write().unwrap().insert(token.to_string(), key_authorization.to_string());
+    }
+
+    /// Withdraw a challenge token once its authorization completes
+    pub fn remove(&self, token: &str) {
+        self.tokens.write().unwrap().remove(token);
+    }
+
+    /// Look up the key authorization for a token
+    pub fn get(&self, token: &str) -> Option<String> {
+        self.tokens.read().unwrap().get(token).cloned()
+    }
+}
+
+/// Create router serving `/.well-known/acme-challenge/{token}` from the challenge store
+pub fn create_acme_challenge_router(store: AcmeChallengeStore) -> Router {
+    Router::new()
+        .route("/.well-known/acme-challenge/:token", get(serve_acme_challenge))
+        .with_state(store)
+}
+
+async fn serve_acme_challenge(
+    State(store): State<AcmeChallengeStore>,
+    UrlPath(token): UrlPath<String>
+) -> Response {
+    match store.get(&token) {
+        Some(key_authorization) => {
+            tracing::info!(token = %token, "Served ACME HTTP-01 challenge");
+            ([(header::CONTENT_TYPE, "application/octet-stream")], key_authorization).into_response()
+        }
+        None => {
+            tracing::warn!(token = %token, "Unknown ACME challenge token requested");
+            StatusCode::NOT_FOUND.into_response()
+        }
+    }
+}
+
+/// File in the cache directory holding the ACME account credentials
+const ACCOUNT_FILE: &str = "account.json";
+
+/// HTTP transport for instant-acme, sent through reqwest like the rest of the server's
+/// outgoing requests
+struct AcmeHttpClient(reqwest::Client);
+
+impl AcmeHttpClient {
+    fn new() -> AcmeResult<Self> {
+        Ok(Self(reqwest::Client::builder().timeout(Duration::from_secs(30)).build()?))
+    }
+}
+
+impl HttpClient for AcmeHttpClient {
+    fn request(
+        &self,
+        req: axum::http::Request<Full<Bytes>>
+    ) -> Pin<Box<dyn Future<Output = Result<BytesResponse, instant_acme::Error>> + Send>> {
+        let (parts, body) = req.into_parts();
+        let method = reqwest::Method
+            ::from_bytes(parts.method.as_str().as_bytes())
+            .unwrap_or(reqwest::Method::GET);
+        let mut request = self.0.request(method, parts.uri.to_string());
+        for (name, value) in &parts.headers {
+            request = request.header(name.as_str(), value.as_bytes());
+        }
+
+        Box::pin(async move {
+            let body = match body.collect().await {
+                Ok(body) => body.to_bytes(),
+                Err(never) => match never {},
+            };
+            let response = request.body(body).send().await.map_err(acme_http_error)?;
+
+            let mut builder = axum::http::Response::builder().status(response.status().as_u16());
+            for (name, value) in response.headers() {
+                builder = builder.header(name.as_str(), value.as_bytes());
+            }
+            let body = response.bytes().await.map_err(acme_http_error)?;
+            let (parts, ()) = builder.body(())?.into_parts();
+            Ok(BytesResponse { parts, body: Box::new(body) })
+        })
+    }
+}
+
+fn acme_http_error(error: reqwest::Error) -> instant_acme::Error {
+    instant_acme::Error::Other(Box::new(error))
+}
+
+/// DNS provider used to publish DNS-01 TXT records
+pub enum DnsProvider {
+    /// Cloudflare API (credentials: `api_token`, `zone_id`)
+    Cloudflare {
+        api_token: String,
+        zone_id: String,
+        api_base: String,
+        http: reqwest::Client,
+    },
+}
+
+impl DnsProvider {
+    /// Build the provider named in the DNS challenge configuration
+    pub fn from_config(config: &DnsChallengeConfig) -> AcmeResult<Self> {
+        let credential = |key: &str| -> AcmeResult<String> {
+            config.credentials
+                .get(key)
+                .cloned()
+                .ok_or_else(||
+                    format!("DNS provider '{}' requires credential '{}'", config.provider, key).into()
+                )
+        };
+
+        match config.provider.to_lowercase().as_str() {
+            "cloudflare" =>
+                Ok(DnsProvider::Cloudflare {
+                    api_token: credential("api_token")?,
+                    zone_id: credential("zone_id")?,
+                    api_base: config.credentials
+                        .get("api_base")
+                        .cloned()
+                        .unwrap_or_else(|| "https://api.cloudflare.com/client/v4".to_string()),
+                    http: reqwest::Client::new(),
+                }),
+            other => Err(format!("Unsupported DNS provider '{}'", other).into()),
+        }
+    }
+
+    /// Create a TXT record, returning a provider-specific record id
+    pub async fn create_txt_record(&self, name: &str, value: &str) -> AcmeResult<String> {
+        match self {
+            DnsProvider::Cloudflare { api_token, zone_id, api_base, http } => {
+                let response: Value = http
+                    .post(format!("{}/zones/{}/dns_records", api_base, zone_id))
+                    .bearer_auth(api_token)
+                    .json(&json!({ "type": "TXT", "name": name, "content": value, "ttl": 120 }))
+                    .send().await?
+                    .json().await?;
+                if response["success"].as_bool() != Some(true) {
+                    return Err(
+                        format!("Cloudflare rejected TXT record '{}': {}", name, response["errors"]).into()
+                    );
+                }
+                response["result"]["id"]
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| "Cloudflare response missing record id".into())
+            }
+        }
+    }
+
+    /// Remove a TXT record created by `create_txt_record`
+    pub async fn delete_txt_record(&self, record_id: &str) -> AcmeResult<()> {
+        match self {
+            DnsProvider::Cloudflare { api_token, zone_id, api_base, http } => {
+                http
+                    .delete(format!("{}/zones/{}/dns_records/{}", api_base, zone_id, record_id))
+                    .bearer_auth(api_token)
+                    .send().await?
+                    .error_for_status()?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Obtains, caches and renews certificates for a Let's Encrypt configuration
+pub struct AcmeManager {
+    config: LetsEncryptConfig,
+    challenges: AcmeChallengeStore,
+}
+
+impl AcmeManager {
+    pub fn new(config: LetsEncryptConfig, challenges: AcmeChallengeStore) -> Self {
+        Self { config, challenges }
+    }
+
+    /// Directory URL to use: explicit override, then staging or production
+    pub fn directory_url(&self) -> &str {
+        match &self.config.directory_url {
+            Some(url) => url,
+            None if self.config.staging => LETS_ENCRYPT_STAGING_DIRECTORY,
+            None => LETS_ENCRYPT_PRODUCTION_DIRECTORY,
+        }
+    }
+
+    /// All names the certificate should cover, primary domain first
+    pub fn domains(&self) -> AcmeResult<Vec<String>> {
+        let primary = self.config.domain.clone().ok_or("Let's Encrypt requires a domain")?;
+        let mut domains = vec![primary];
+        for domain in &self.config.additional_domains {
+            if !domains.contains(domain) {
+                domains.push(domain.clone());
+            }
+        }
+        Ok(domains)
+    }
+
+    /// Cached certificate chain and private key paths for the primary domain
+    pub fn certificate_paths(&self) -> AcmeResult<(PathBuf, PathBuf)> {
+        let primary = &self.domains()?[0];
+        let dir = self.config.cache_dir.join(primary.replace('*', "_wildcard"));
+        Ok((dir.join("cert.pem"), dir.join("key.pem")))
+    }
+
+    /// Expiry of the cached certificate, if one exists and parses
+    pub fn certificate_expiry(&self) -> Option<DateTime<Utc>> {
+        let (cert_path, _) = self.certificate_paths().ok()?;
+        let pem = fs::read(cert_path).ok()?;
+        let (_, pem) = x509_parser::pem::parse_x509_pem(&pem).ok()?;
+        let cert = pem.parse_x509().ok()?;
+        DateTime::from_timestamp(cert.validity().not_after.timestamp(), 0)
+    }
+
+    /// Whether the cached certificate is missing or within the renewal window
+    pub fn needs_renewal(&self) -> bool {
+        match self.certificate_expiry() {
+            Some(expiry) => {
+                let renew_at = expiry - chrono::Duration::days(self.config.renew_before_days as i64);
+                Utc::now() >= renew_at
+            }
+            None => true,
+        }
+    }
+
+    /// Make sure a valid cached certificate exists, issuing one if needed
+    pub async fn ensure_certificate(&self) -> AcmeResult<(PathBuf, PathBuf)> {
+        if self.needs_renewal() {
+            self.issue_certificate().await?;
+        } else {
+            tracing::info!(expires = ?self.certificate_expiry(), "Using cached ACME certificate");
+        }
+        self.certificate_paths()
+    }
+
+    /// Like `ensure_certificate`, but answers HTTP-01 challenges on a temporary
+    /// listener bound to `http_port` (used before the main server is running)
+    pub async fn ensure_certificate_standalone(
+        &self,
+        http_port: u16
+    ) -> AcmeResult<(PathBuf, PathBuf)> {
+        if !self.needs_renewal() {
+            return self.ensure_certificate().await;
+        }
+
+        let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", http_port)).await?;
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let app = create_acme_challenge_router(self.challenges.clone());
+        let challenge_server = tokio::spawn(async move {
+            let _ = axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    let _ = shutdown_rx.await;
+                }).await;
+        });
+
+        let result = self.ensure_certificate().await;
+        let _ = shutdown_tx.send(());
+        let _ = challenge_server.await;
+        result
+    }
+
+    /// Run the full ACME order flow and write the certificate to the cache
+    pub async fn issue_certificate(&self) -> AcmeResult<()> {
+        let domains = self.domains()?;
+        tracing::info!(
+            domains = ?domains,
+            directory = %self.directory_url(),
+            "Requesting certificate from ACME server"
+        );
+
+        let account = self.account().await?;
+        let identifiers: Vec<Identifier> = domains
+            .iter()
+            .map(|domain| Identifier::Dns(domain.clone()))
+            .collect();
+        let mut order = account.new_order(&NewOrder { identifiers: &identifiers }).await?;
+        for authorization in order.authorizations().await? {
+            self.complete_authorization(&mut order, &authorization).await?;
+        }
+
+        // Fresh key pair and CSR for the certificate itself
+        let params = rcgen::CertificateParams::new(domains.clone());
+        let cert_key = rcgen::Certificate::from_params(params)?;
+        order.finalize(&cert_key.serialize_request_der()?).await?;
+        let chain = self.wait_for_certificate(&mut order).await?;
+
+        // The key goes first: certificate watchers reload when the certificate changes,
+        // and must never pair the new certificate with the old key
+        let (cert_path, key_path) = self.certificate_paths()?;
+        let staged_key = stage_file(&key_path, cert_key.serialize_private_key_pem().as_bytes(), true)?;
+        let staged_cert = stage_file(&cert_path, chain.as_bytes(), false)?;
+        commit_file(&staged_key, &key_path)?;
+        commit_file(&staged_cert, &cert_path)?;
+
+        tracing::info!(
+            domains = ?domains,
+            cert_file = %cert_path.display(),
+            expires = ?self.certificate_expiry(),
+            "ACME certificate issued"
+        );
+        Ok(())
+    }
+
+    /// Restore the account saved for this directory, registering a new one if there is none
+    async fn account(&self) -> AcmeResult<Account> {
+        let path = self.config.cache_dir.join(ACCOUNT_FILE);
+        let saved = fs
+            ::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice::<Value>(&data).ok())
+            .filter(|credentials| credentials["directory"].as_str() == Some(self.directory_url()));
+        if let Some(credentials) = saved {
+            let credentials: AccountCredentials = serde_json::from_value(credentials)?;
+            return Ok(Account::from_credentials_and_http(credentials, Box::new(AcmeHttpClient::new()?)).await?);
+        }
+
+        let contact = format!("mailto:{}", self.config.email);
+        let contact: &[&str] = if self.config.email.is_empty() { &[] } else { &[contact.as_str()] };
+        let (account, credentials) = Account::create_with_http(
+            &(NewAccount {
+                contact,
+                terms_of_service_agreed: true,
+                only_return_existing: false,
+            }),
+            self.directory_url(),
+            None,
+            Box::new(AcmeHttpClient::new()?)
+        ).await?;
+
+        let staged = stage_file(&path, &serde_json::to_vec(&credentials)?, true)?;
+        commit_file(&staged, &path)?;
+        tracing::info!(account = %account.id(), "ACME account ready");
+        Ok(account)
+    }
+
+    /// Satisfy one authorization via HTTP-01, or DNS-01 for wildcards
+    async fn complete_authorization(
+        &self,
+        order: &mut Order,
+        authorization: &Authorization
+    ) -> AcmeResult<()> {
+        if authorization.status == AuthorizationStatus::Valid {
+            return Ok(());
+        }
+
+        let Identifier::Dns(domain) = &authorization.identifier;
+        // Wildcard authorizations only offer DNS-01
+        let offers_http = authorization.challenges
+            .iter()
+            .any(|challenge| challenge.r#type == ChallengeType::Http01);
+        let use_dns = domain.starts_with("*.") || !offers_http;
+        let (challenge_type, challenge_name) = if use_dns {
+            (ChallengeType::Dns01, "dns-01")
+        } else {
+            (ChallengeType::Http01, "http-01")
+        };
+        let challenge = authorization.challenges
+            .iter()
+            .find(|challenge| challenge.r#type == challenge_type)
+            .ok_or_else(|| format!("ACME server offered no {} challenge for '{}'", challenge_name, domain))?;
+        let key_authorization = order.key_authorization(challenge);
+
+        tracing::info!(domain = %domain, challenge = %challenge_name, "Answering ACME challenge");
+
+        if use_dns {
+            let dns_config = self.config.dns_challenge
+                .as_ref()
+                .ok_or_else(|| format!("Wildcard domain '{}' requires dns_challenge configuration", domain))?;
+            let provider = DnsProvider::from_config(dns_config)?;
+            let record_name = format!("_acme-challenge.{}", domain.trim_start_matches("*."));
+            let record_id = provider.create_txt_record(&record_name, &key_authorization.dns_value()).await?;
+            tokio::time::sleep(DNS_PROPAGATION_DELAY).await;
+
+            let timeout = Duration::from_secs(dns_config.timeout_seconds);
+            let result = self.validate(order, &challenge.url, domain, timeout).await;
+            if let Err(e) = provider.delete_txt_record(&record_id).await {
+                tracing::warn!(record = %record_name, error = %e, "Failed to remove ACME TXT record");
+            }
+            result
+        } else {
+            self.challenges.insert(&challenge.token, key_authorization.as_str());
+            let result = self.validate(order, &challenge.url, domain, HTTP_CHALLENGE_TIMEOUT).await;
+            self.challenges.remove(&challenge.token);
+            result
+        }
+    }
+
+    /// Tell the CA the challenge is ready and wait for the authorization to settle
+    async fn validate(
+        &self,
+        order: &mut Order,
+        challenge_url: &str,
+        domain: &str,
+        timeout: Duration
+    ) -> AcmeResult<()> {
+        order.set_challenge_ready(challenge_url).await?;
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let authorization = order
+                .authorizations().await?
+                .into_iter()
+                .find(|authorization| {
+                    authorization.challenges.iter().any(|challenge| challenge.url == challenge_url)
+                })
+                .ok_or_else(|| format!("ACME authorization for '{}' disappeared", domain))?;
+            match authorization.status {
+                AuthorizationStatus::Valid => {
+                    return Ok(());
+                }
+                AuthorizationStatus::Pending => {}
+                status => {
+                    let detail = authorization.challenges
+                        .into_iter()
+                        .find_map(|challenge| challenge.error?.detail)
+                        .unwrap_or_default();
+                    return Err(
+                        format!(
+                            "ACME authorization for '{}' failed with status '{:?}': {}",
+                            domain,
+                            status,
+                            detail
+                        ).into()
+                    );
+                }
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(format!("Timed out waiting for ACME authorization of '{}'", domain).into());
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Poll a finalized order until the certificate chain can be downloaded
+    async fn wait_for_certificate(&self, order: &mut Order) -> AcmeResult<String> {
+        let deadline = tokio::time::Instant::now() + HTTP_CHALLENGE_TIMEOUT;
+        loop {
+            if let Some(chain) = order.certificate().await? {
+                return Ok(chain);
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err("Timed out waiting for ACME order to be finalized".into());
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Periodically renew the cached certificate before it expires
+    pub fn spawn_renewal_task(self: Arc<Self>, check_interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(check_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if !self.needs_renewal() {
+                    continue;
+                }
+                tracing::info!(expires = ?self.certificate_expiry(), "ACME certificate due for renewal");
+                if let Err(e) = self.issue_certificate().await {
+                    tracing::error!(error = %e, "ACME certificate renewal failed, will retry");
+                }
+            }
+        })
+    }
+}
+
+/// Write `contents` to a temporary file next to `path`, to be moved into place with
+/// `commit_file`. Private files (keys, account credentials) are readable by the owner only.
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn stage_file(path: &Path, contents: &[u8], private: bool) -> AcmeResult<PathBuf> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    // A leftover file would keep its old permissions
+    let _ = fs::remove_file(&tmp_path);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&tmp_path)
+        .map_err(|e| format!("Failed to write '{}': {}", tmp_path.display(), e))?;
+    file.write_all(contents)
+        .and_then(|()| file.sync_all())
+        .map_err(|e| format!("Failed to write '{}': {}", tmp_path.display(), e))?;
+    Ok(tmp_path)
+}
+
+/// Rename a staged file into place so readers never see partial contents
+fn commit_file(staged: &Path, path: &Path) -> AcmeResult<()> {
+    fs::rename(staged, path).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use axum::{ extract::{ ConnectInfo, Request, State }, http::HeaderMap, middleware::Next, response::Response };
+use httpserver_config::parse_trusted_proxy;
+use ipnet::IpNet;
+use std::net::{ IpAddr, SocketAddr };
+use std::sync::Arc;
+
+/// Resolved address of the originating client, available as a request extension
+///
+/// Equals the TCP peer unless the peer is a trusted proxy, in which case it is taken from
+/// X-Forwarded-For. Unix socket connections have no address and get no extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+/// Networks of proxies allowed to report the client address in X-Forwarded-For
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+}
+
+impl TrustedProxies {
+    /// Parse CIDR blocks or bare addresses such as "10.0.0.0/8" and "192.0.2.1"
+    pub fn parse<S: AsRef<str>>(entries: &[S]) -> Result<Self, String> {
+        let networks = entries
+            .iter()
+            .map(|entry| parse_trusted_proxy(entry.as_ref()).map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { networks })
+    }
+
+    /// Whether the address belongs to a trusted proxy
+    pub fn is_trusted(&self, ip: IpAddr) -> bool {
+        let ip = canonical(ip);
+        self.networks.iter().any(|network| network.contains(&ip))
+    }
+
+    /// Real client address for a request received from `peer`
+    ///
+    /// X-Forwarded-For is only consulted when the peer is trusted. Entries are walked from
+    /// the right, skipping trusted proxies, and the first untrusted address is the client;
+    /// anything to its left could have been forged by the client itself.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        let mut client = peer;
+        let hops = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .collect::<Vec<_>>();
+        for hop in hops.into_iter().rev() {
+            match hop.trim().parse::<IpAddr>() {
+                Ok(ip) => {
+                    client = ip;
+                    if !self.is_trusted(ip) {
+                        break;
+                    }
+                }
+                // A malformed entry ends the chain we can vouch for
+                Err(_) => {
+                    break;
+                }
+            }
+        }
+        client
+    }
+}
+
+/// IPv4-mapped IPv6 peers (dual-stack listeners) compare as IPv4
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+/// Middleware attaching the resolved `ClientIp` to each request
+pub async fn client_ip_middleware(
+    State(trusted_proxies): State<Arc<TrustedProxies>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    mut req: Request,
+    next: Next
+) -> Response {
+    if let Some(ConnectInfo(peer)) = connect_info {
+        let client_ip = trusted_proxies.client_ip(peer.ip(), req.headers());
+        req.extensions_mut().insert(ClientIp(client_ip));
+    }
+    next.run(req).await
+}
@@ -0,0 +1,122 @@
+use axum::{
+    extract::{ Request, State },
+    http::{ header, HeaderValue, StatusCode },
+    middleware::Next,
+    response::Response,
+};
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// Cap on requests handled at once, enforced by `concurrency_limit_middleware`
+///
+/// Requests over the limit wait in a bounded queue for a slot; a full queue or a wait longer
+/// than the queue timeout is answered with 503 and Retry-After. A slot is held until the
+/// response head is produced.
+#[derive(Debug)]
+pub struct ConcurrencyLimit {
+    slots: Arc<Semaphore>,
+    max_requests: usize,
+    queued: AtomicUsize,
+    queue_size: usize,
+    queue_timeout: Duration,
+    retry_after: u64,
+}
+
+impl ConcurrencyLimit {
+    /// Allow `max_requests` at once with up to `queue_size` more waiting `queue_timeout` for a slot
+    pub fn new(max_requests: usize, queue_size: usize, queue_timeout: Duration) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(max_requests)),
+            max_requests,
+            queued: AtomicUsize::new(0),
+            queue_size,
+            queue_timeout,
+            retry_after: 1,
+        }
+    }
+
+    /// Seconds clients are told to wait before retrying a rejected request
+    pub fn with_retry_after(mut self, seconds: u64) -> Self {
+        self.retry_after = seconds;
+        self
+    }
+
+    /// Requests currently being handled
+    pub fn in_flight(&self) -> usize {
+        self.max_requests - self.slots.available_permits()
+    }
+
+    /// Requests currently waiting for a slot
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    /// Take a place in the queue, or None when it is full
+    fn enter_queue(&self) -> Option<QueueSlot<'_>> {
+        if self.queued.fetch_add(1, Ordering::SeqCst) >= self.queue_size {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(QueueSlot(&self.queued))
+    }
+
+    fn busy_response(&self) -> Response {
+        let mut response = crate::create_error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "The server is handling too many requests, please retry shortly"
+        );
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(self.retry_after));
+        response
+    }
+}
+
+/// Place in the `ConcurrencyLimit` queue, given up when dropped so a request cancelled while
+/// waiting (e.g. by a client disconnect) leaves the queue too
+struct QueueSlot<'a>(&'a AtomicUsize);
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Middleware holding a `ConcurrencyLimit` slot for each request while it is handled
+pub async fn concurrency_limit_middleware(
+    State(limit): State<Arc<ConcurrencyLimit>>,
+    req: Request,
+    next: Next
+) -> Response {
+    // The semaphore is fair, so a free slot is only taken here when nobody is queued
+    let slot = match limit.slots.clone().try_acquire_owned() {
+        Ok(slot) => slot,
+        Err(_) => {
+            let Some(queue_slot) = limit.enter_queue() else {
+                tracing::warn!(
+                    path = %req.uri().path(),
+                    max_requests = limit.max_requests,
+                    "Concurrency limit reached and queue full, rejecting request"
+                );
+                return limit.busy_response();
+            };
+            let waited = tokio::time::timeout(limit.queue_timeout, limit.slots.clone().acquire_owned()).await;
+            drop(queue_slot);
+            match waited {
+                Ok(Ok(slot)) => slot,
+                _ => {
+                    tracing::warn!(
+                        path = %req.uri().path(),
+                        queue_timeout_ms = limit.queue_timeout.as_millis() as u64,
+                        "Request timed out waiting for a concurrency slot"
+                    );
+                    return limit.busy_response();
+                }
+            }
+        }
+    };
+
+    let response = next.run(req).await;
+    drop(slot);
+    response
+}
@@ -0,0 +1,20 @@
+// Stable values of the `event` field on connection lifecycle log events, so log pipelines can
+// filter and alert on them without matching message text
+
+/// A client connection was accepted; fields: remote_addr, scheme, port
+pub const CONNECTION_ACCEPTED: &str = "connection_accepted";
+
+/// A client completed the TLS handshake; fields: remote_addr, port, alpn
+pub const TLS_HANDSHAKE_SUCCEEDED: &str = "tls_handshake_succeeded";
+
+/// A client's TLS handshake failed; fields: remote_addr, port, error
+pub const TLS_HANDSHAKE_FAILED: &str = "tls_handshake_failed";
+
+/// A client connection closed; fields: remote_addr, scheme, port, duration_ms, and error when it failed
+pub const CONNECTION_CLOSED: &str = "connection_closed";
+
+/// The server opened a connection to a backend; fields: target, protocol
+pub const BACKEND_CONNECTED: &str = "backend_connected";
+
+/// Connecting to a backend failed; fields: target, protocol, error
+pub const BACKEND_CONNECT_FAILED: &str = "backend_connect_failed";
@@ -1,6 +1,6 @@
 use axum::{
-    extract::{ ConnectInfo, Request },
-    http::StatusCode,
+    extract::{ ConnectInfo, DefaultBodyLimit, Request, State },
+    http::{ HeaderValue, StatusCode },
     middleware::Next,
     response::{ Response, Json, IntoResponse },
     Router,
@@ -11,13 +11,21 @@ use std::net::SocketAddr;
 use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
 use serde_json::json;
-use tracing::{ info, error, instrument, Instrument };
+use tracing::{ info, warn, error, instrument, Instrument };
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
 use std::sync::Arc;
-use hyper::server::conn::http1;
-use hyper_util::rt::TokioIo;
+use std::time::Duration;
+use hyper_util::rt::{ TokioExecutor, TokioIo };
+use hyper_util::server::conn::auto;
+use hyper_util::server::graceful::GracefulShutdown;
 use hyper_util::service::TowerToHyperService;
 use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
 use tokio_rustls::TlsAcceptor;
+use httpserver_config::{ AccessLogHandled, ErrorPages, GeneratedErrorPage, TcpRoute };
 use tower::Service;
 
 // Export logging functionality
@@ -26,13 +34,198 @@ pub use logging::{ initialize_logging, create_request_span, check_log_rotation,
 
 // Export SSL functionality
 pub mod ssl;
-pub use ssl::{ SslCertificateManager, SslCertificate, SslRedirectConfig };
+pub use ssl::{
+    SslCertificateManager,
+    SslCertificate,
+    SslRedirectConfig,
+    ReloadableCertResolver,
+    CertificateWatcher,
+};
+
+// Export ACME certificate issuance
+pub mod acme;
+pub use acme::{ AcmeManager, AcmeChallengeStore, create_acme_challenge_router };
+
+// Client address resolution behind trusted proxies
+pub mod client_ip;
+pub use client_ip::{ ClientIp, TrustedProxies, client_ip_middleware };
+
+// Gateway-wide cap on concurrent requests
+pub mod concurrency_limit;
+pub use concurrency_limit::{ ConcurrencyLimit, concurrency_limit_middleware };
+
+// W3C trace context propagation
+pub mod trace_context;
+pub use trace_context::{ TraceContext, TRACEPARENT_HEADER, TRACESTATE_HEADER };
+
+// Connection lifecycle event names
+pub mod events;
+
+// Layer-4 TCP proxying
+pub mod tcp_proxy;
+pub use tcp_proxy::TcpProxy;
+
+// Unix domain socket listener
+#[cfg(unix)]
+mod unix_socket;
+
+/// Header carrying the per-request correlation ID
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Maximum length accepted for an inbound request ID
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Per-request body limit in bytes overriding the server-wide limit (None keeps the default)
+pub type BodyLimitOverride = Arc<dyn Fn(&Request) -> Option<usize> + Send + Sync>;
+
+/// Limits on the headers of a request, enforced by `request_header_limit_middleware`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeaderLimits {
+    /// Maximum number of header fields (None disables the limit)
+    pub max_count: Option<usize>,
+    /// Maximum combined size in bytes of all header names and values (None disables the limit)
+    pub max_bytes: Option<usize>,
+}
+
+impl HeaderLimits {
+    /// Limits with zero meaning no limit, as in the server configuration
+    pub fn new(max_count: usize, max_bytes: usize) -> Self {
+        Self {
+            max_count: (max_count > 0).then_some(max_count),
+            max_bytes: (max_bytes > 0).then_some(max_bytes),
+        }
+    }
+}
+
+/// Socket options applied to every TCP connection accepted by the HTTP and HTTPS listeners
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TcpOptions {
+    /// Send small writes immediately instead of coalescing them (disables Nagle's algorithm)
+    pub nodelay: bool,
+    /// Idle time before keep-alive probes are sent (None leaves keep-alive off)
+    pub keepalive: Option<Duration>,
+}
+
+impl TcpOptions {
+    /// Options with zero keep-alive seconds meaning no keep-alive, as in the server configuration
+    pub fn new(nodelay: bool, keepalive_secs: u64) -> Self {
+        Self {
+            nodelay,
+            keepalive: (keepalive_secs > 0).then(|| Duration::from_secs(keepalive_secs)),
+        }
+    }
+
+    /// Set the options on an accepted connection
+    pub fn apply(&self, stream: &tokio::net::TcpStream) -> std::io::Result<()> {
+        if self.nodelay {
+            stream.set_nodelay(true)?;
+        }
+        if let Some(keepalive) = self.keepalive {
+            let params = socket2::TcpKeepalive::new().with_time(keepalive);
+            socket2::SockRef::from(stream).set_tcp_keepalive(&params)?;
+        }
+        Ok(())
+    }
+}
+
+/// Request ID assigned by `request_id_middleware`, available as a request extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// Listener a request arrived on, available as a request extension (none for the Unix socket)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerListener {
+    /// Whether the listener terminates TLS
+    pub tls: bool,
+    /// Port the listener is bound to
+    pub port: u16,
+}
+
+/// Errors starting the server's listeners
+#[derive(Debug)]
+pub enum ServerError {
+    /// Another process is already listening on the port
+    AddressInUse {
+        port: u16,
+    },
+    /// The port could not be bound for another reason (permissions, bad address, ...)
+    Bind {
+        port: u16,
+        source: std::io::Error,
+    },
+}
+
+impl std::fmt::Display for ServerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServerError::AddressInUse { port } =>
+                write!(
+                    f,
+                    "Port {} is already in use; stop the other process or choose a different port",
+                    port
+                ),
+            ServerError::Bind { port, source } => write!(f, "Failed to bind port {}: {}", port, source),
+        }
+    }
+}
+
+impl std::error::Error for ServerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ServerError::AddressInUse { .. } => None,
+            ServerError::Bind { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Bind a TCP listener on all interfaces, reporting an occupied port as `AddressInUse`
+pub async fn bind_listener(port: u16) -> Result<TcpListener, ServerError> {
+    TcpListener::bind(("0.0.0.0", port)).await.map_err(|source| {
+        if source.kind() == std::io::ErrorKind::AddrInUse {
+            ServerError::AddressInUse { port }
+        } else {
+            ServerError::Bind { port, source }
+        }
+    })
+}
 
 /// Core server functionality
 pub struct Server {
     pub port: u16,
     pub ssl_config: Option<Arc<rustls::ServerConfig>>,
     pub https_port: Option<u16>,
+    /// Assign and propagate X-Request-Id for every request
+    pub enable_request_ids: bool,
+    /// Maximum time a handler may take to produce a response (None disables the limit)
+    pub request_timeout: Option<Duration>,
+    /// Maximum request body size in bytes (None disables the limit)
+    pub max_request_size: Option<usize>,
+    /// Route-specific body limits taking precedence over `max_request_size`
+    pub body_limit_override: Option<BodyLimitOverride>,
+    /// Header count and size limits (431 when exceeded)
+    pub header_limits: HeaderLimits,
+    /// Cap on requests handled at once across every listener (503 when the queue is full)
+    pub concurrency_limit: Option<Arc<ConcurrencyLimit>>,
+    /// Also serve on this Unix domain socket
+    pub unix_socket: Option<PathBuf>,
+    /// Permissions applied to the Unix socket file
+    pub unix_socket_mode: u32,
+    /// Accept HTTP/2 with prior knowledge (h2c) on the plaintext port
+    pub http2_cleartext: bool,
+    /// TCP_NODELAY and keep-alive settings for accepted connections
+    pub tcp_options: TcpOptions,
+    /// Proxies whose X-Forwarded-For is trusted to name the real client
+    pub trusted_proxies: Arc<TrustedProxies>,
+    /// Redirect plain HTTP requests to the HTTPS port
+    pub https_redirect: Option<SslRedirectConfig>,
+    /// Custom pages replacing the built-in error pages
+    pub error_pages: ErrorPages,
+    /// Ports relaying raw TCP connections to backends alongside the HTTP server
+    pub tcp_routes: Vec<TcpRoute>,
+    /// Additional plain HTTP listeners, each serving its own router
+    pub listeners: Vec<(u16, Router)>,
+    /// Completes when the server should stop accepting connections and drain
+    pub shutdown_signal: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
 }
 
 impl Server {
@@ -41,6 +234,22 @@ impl Server {
             port,
             ssl_config: None,
             https_port: None,
+            enable_request_ids: true,
+            request_timeout: None,
+            max_request_size: None,
+            body_limit_override: None,
+            header_limits: HeaderLimits::default(),
+            concurrency_limit: None,
+            unix_socket: None,
+            unix_socket_mode: 0o660,
+            http2_cleartext: false,
+            tcp_options: TcpOptions::default(),
+            trusted_proxies: Arc::new(TrustedProxies::default()),
+            https_redirect: None,
+            error_pages: ErrorPages::default(),
+            tcp_routes: Vec::new(),
+            listeners: Vec::new(),
+            shutdown_signal: None,
         }
     }
 
@@ -50,147 +259,297 @@ impl Server {
             port,
             ssl_config: Some(ssl_config),
             https_port: Some(https_port),
+            enable_request_ids: true,
+            request_timeout: None,
+            max_request_size: None,
+            body_limit_override: None,
+            header_limits: HeaderLimits::default(),
+            concurrency_limit: None,
+            unix_socket: None,
+            unix_socket_mode: 0o660,
+            http2_cleartext: false,
+            tcp_options: TcpOptions::default(),
+            trusted_proxies: Arc::new(TrustedProxies::default()),
+            https_redirect: None,
+            error_pages: ErrorPages::default(),
+            tcp_routes: Vec::new(),
+            listeners: Vec::new(),
+            shutdown_signal: None,
         }
     }
-    /// Start the HTTP server with the given router
-    #[instrument(skip(self, app), fields(port = self.port))]
-    pub async fn start(self, app: Router) -> Result<(), Box<dyn std::error::Error>> {
-        info!(port = self.port, "Starting HTTP server");
+
+    /// Enable or disable request ID generation and propagation
+    pub fn with_request_ids(mut self, enabled: bool) -> Self {
+        self.enable_request_ids = enabled;
+        self
+    }
+
+    /// Respond with 504 when a handler exceeds the timeout (zero disables the limit)
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = (!timeout.is_zero()).then_some(timeout);
+        self
+    }
+
+    /// Respond with 413 when a request body exceeds the given size in bytes (zero disables the limit)
+    pub fn with_max_request_size(mut self, bytes: usize) -> Self {
+        self.max_request_size = (bytes > 0).then_some(bytes);
+        self
+    }
+
+    /// Let specific requests (e.g. upload routes) use a larger or smaller body limit than the default
+    pub fn with_body_limit_override(mut self, body_limit_override: BodyLimitOverride) -> Self {
+        self.body_limit_override = Some(body_limit_override);
+        self
+    }
+
+    /// Respond with 431 when a request has too many headers or too many header bytes
+    pub fn with_header_limits(mut self, header_limits: HeaderLimits) -> Self {
+        self.header_limits = header_limits;
+        self
+    }
+
+    /// Handle at most the limit's number of requests at once; the rest queue, then get a 503
+    pub fn with_concurrency_limit(mut self, concurrency_limit: ConcurrencyLimit) -> Self {
+        self.concurrency_limit = Some(Arc::new(concurrency_limit));
+        self
+    }
+
+    /// Serve the same router on a Unix domain socket in addition to the TCP port
+    pub fn with_unix_socket(mut self, path: impl Into<PathBuf>, mode: u32) -> Self {
+        self.unix_socket = Some(path.into());
+        self.unix_socket_mode = mode;
+        self
+    }
+
+    /// Enable or disable cleartext HTTP/2 (h2c) on the HTTP port; HTTPS always offers h2 via ALPN
+    pub fn with_http2_cleartext(mut self, enabled: bool) -> Self {
+        self.http2_cleartext = enabled;
+        self
+    }
+
+    /// Set TCP_NODELAY and keep-alive on every connection accepted over HTTP or HTTPS
+    pub fn with_tcp_options(mut self, tcp_options: TcpOptions) -> Self {
+        self.tcp_options = tcp_options;
+        self
+    }
+
+    /// Resolve the client address from X-Forwarded-For when the peer is one of these proxies
+    pub fn with_trusted_proxies(mut self, trusted_proxies: TrustedProxies) -> Self {
+        self.trusted_proxies = Arc::new(trusted_proxies);
+        self
+    }
+
+    /// Redirect plain HTTP requests to HTTPS as configured. X-Forwarded-Proto is trusted from
+    /// the server's trusted proxies.
+    pub fn with_https_redirect(mut self, config: SslRedirectConfig) -> Self {
+        self.https_redirect = Some(config);
+        self
+    }
+
+    /// Serve custom pages in place of the built-in error pages, on every listener
+    pub fn with_error_pages(mut self, pages: ErrorPages) -> Self {
+        self.error_pages = pages;
+        self
+    }
+
+    /// Relay raw TCP connections on each route's port to its load-balanced backends
+    pub fn with_tcp_routes(mut self, tcp_routes: Vec<TcpRoute>) -> Self {
+        self.tcp_routes = tcp_routes;
+        self
+    }
+
+    /// Also serve `router` on a plain HTTP port, behind the same middleware as the main router
+    pub fn with_listener(mut self, port: u16, router: Router) -> Self {
+        self.listeners.push((port, router));
+        self
+    }
+
+    /// Stop accepting connections once `signal` completes; `start` returns after every
+    /// listener has finished the requests already in flight
+    pub fn with_graceful_shutdown(mut self, signal: impl Future<Output = ()> + Send + 'static) -> Self {
+        self.shutdown_signal = Some(Box::pin(signal));
+        self
+    }
+
+    /// Wrap a router in the middleware shared by every listener
+    fn with_middleware(&self, app: Router, body_limit_override: Option<BodyLimitOverride>) -> Router {
+        // Bound handler time inside the logging layer so timeouts are logged as 504s
+        let app = match self.request_timeout {
+            Some(timeout) =>
+                app.layer(axum::middleware::from_fn_with_state(timeout, request_timeout_middleware)),
+            None => app,
+        };
+
+        // Reject oversized bodies before any handler buffers them
+        let app = match (self.max_request_size, body_limit_override) {
+            (Some(limit), Some(body_limit_override)) =>
+                app
+                    .layer(
+                        axum::middleware::from_fn(move |req: Request, next: Next| {
+                            let limit = body_limit_override(&req).unwrap_or(limit);
+                            request_body_limit_middleware(State(limit), req, next)
+                        })
+                    )
+                    .layer(DefaultBodyLimit::max(limit)),
+            (Some(limit), None) =>
+                app
+                    .layer(axum::middleware::from_fn_with_state(limit, request_body_limit_middleware))
+                    .layer(DefaultBodyLimit::max(limit)),
+            (None, _) => app,
+        };
+
+        // Queue for a slot before handling; the queue wait does not count against the request timeout
+        let app = match &self.concurrency_limit {
+            Some(limit) => app.layer(axum::middleware::from_fn_with_state(limit.clone(), concurrency_limit_middleware)),
+            None => app,
+        };
+
+        // Header limits are checked first, before any body handling or routing
+        let app = if self.header_limits == HeaderLimits::default() {
+            app
+        } else {
+            app.layer(axum::middleware::from_fn_with_state(self.header_limits, request_header_limit_middleware))
+        };
 
         // Apply middleware to the router
         let app = app.layer(
             ServiceBuilder::new()
+                .layer(
+                    axum::middleware::from_fn_with_state(
+                        self.trusted_proxies.clone(),
+                        client_ip_middleware
+                    )
+                )
                 .layer(axum::middleware::from_fn(logging_middleware))
                 .layer(CorsLayer::permissive())
         );
 
-        // Start HTTP server
-        let http_task = {
-            let app = app.clone();
-            let port = self.port;
-            tokio::spawn(async move {
-                let listener = match
-                    tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await
-                {
-                    Ok(listener) => listener,
-                    Err(e) => {
-                        error!(port = port, error = %e, "Failed to bind to HTTP port");
-                        return Err(e.into());
-                    }
-                };
-
-                info!(port = port, "HTTP server running at http://localhost:{}", port);
-
-                if
-                    let Err(e) = axum::serve(
-                        listener,
-                        app.into_make_service_with_connect_info::<SocketAddr>()
-                    ).await
-                {
-                    error!(error = %e, "HTTP server error");
-                    return Err(e.into());
-                }
-                Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
-            })
+        // Request IDs are assigned outermost so the logging span can include them
+        let app = if self.enable_request_ids {
+            app.layer(axum::middleware::from_fn(request_id_middleware))
+        } else {
+            app
         };
 
-        // Start HTTPS server if SSL is configured
-        let https_task = if
-            let (Some(ssl_config), Some(https_port)) = (self.ssl_config, self.https_port)
-        {
-            let app = app.clone();
-            Some(
-                tokio::spawn(async move {
-                    let listener = match
-                        TcpListener::bind(format!("0.0.0.0:{}", https_port)).await
-                    {
-                        Ok(listener) => listener,
-                        Err(e) => {
-                            error!(port = https_port, error = %e, "Failed to bind to HTTPS port");
-                            return Err(e.into());
-                        }
-                    };
+        // Custom error pages also cover the errors generated by the layers above
+        if self.error_pages.is_empty() {
+            app
+        } else {
+            app.layer(axum::middleware::from_fn_with_state(self.error_pages.clone(), error_page_middleware))
+        }
+    }
 
-                    info!(
-                        port = https_port,
-                        "HTTPS server running at https://localhost:{}",
-                        https_port
-                    );
-
-                    let tls_acceptor = TlsAcceptor::from(ssl_config);
-                    let service = app.into_make_service_with_connect_info::<SocketAddr>();
-
-                    loop {
-                        let (tcp_stream, remote_addr) = match listener.accept().await {
-                            Ok(conn) => conn,
-                            Err(e) => {
-                                error!(error = %e, "Failed to accept HTTPS connection");
-                                continue;
-                            }
-                        };
-
-                        let tls_acceptor = tls_acceptor.clone();
-                        let mut service = service.clone();
-
-                        tokio::spawn(async move {
-                            // Perform TLS handshake
-                            let tls_stream = match tls_acceptor.accept(tcp_stream).await {
-                                Ok(tls_stream) => tls_stream,
-                                Err(e) => {
-                                    error!(error = %e, remote_addr = %remote_addr, "TLS handshake failed");
-                                    return;
-                                }
-                            };
-
-                            let io = TokioIo::new(tls_stream);
-                            let hyper_service = match service.call(remote_addr).await {
-                                Ok(service) => service,
-                                Err(e) => {
-                                    error!(error = %e, "Failed to create service");
-                                    return;
-                                }
-                            };
-
-                            // Wrap the axum service for hyper compatibility
-                            let hyper_service = TowerToHyperService::new(hyper_service);
-
-                            // Serve the connection using HTTP/1.1
-                            if let Err(e) = http1::Builder::new()
-                                .serve_connection(io, hyper_service)
-                                .await
-                            {
-                                error!(error = %e, remote_addr = %remote_addr, "HTTPS connection error");
-                            }
-                        });
-                    }
+    /// Start the HTTP server with the given router
+    #[instrument(skip(self, app), fields(port = self.port))]
+    pub async fn start(mut self, app: Router) -> Result<(), Box<dyn std::error::Error>> {
+        info!(port = self.port, "Starting HTTP server");
 
-                    #[allow(unreachable_code)]
-                    Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
-                })
-            )
-        } else {
-            None
-        };
+        // Unix socket clients are local and never reach the HTTPS port, so they skip the redirect
+        #[cfg(unix)]
+        let unix_app = self.unix_socket
+            .as_ref()
+            .map(|_| self.with_middleware(app.clone(), self.body_limit_override.clone()));
 
-        // Wait for either server to complete (or fail)
-        if let Some(https_task) = https_task {
-            tokio::select! {
-                result = http_task => {
-                    if let Err(e) = result? {
-                        error!(error = %e, "HTTP server failed");
-                        return Err(e);
-                    }
-                }
-                result = https_task => {
-                    if let Err(e) = result? {
-                        error!(error = %e, "HTTPS server failed");
-                        return Err(e);
-                    }
-                }
+        // Only the main router redirects to HTTPS, inside the logging layer so redirects are logged
+        let app = match &self.https_redirect {
+            Some(config) => {
+                let config = config.clone().with_trusted_proxies(self.trusted_proxies.as_ref().clone());
+                app.layer(axum::middleware::from_fn_with_state(Arc::new(config), https_redirect_middleware))
             }
-        } else {
-            // Only HTTP server
-            if let Err(e) = http_task.await? {
+            None => app,
+        };
+
+        // Route-specific body limits only apply to the main router
+        let app = self.with_middleware(app, self.body_limit_override.clone());
+        let listeners: Vec<(u16, Router)> = std::mem::take(&mut self.listeners)
+            .into_iter()
+            .map(|(port, router)| (port, self.with_middleware(router, None)))
+            .collect();
+
+        // Serve on the Unix socket for as long as start() runs; the socket file is removed on shutdown
+        #[cfg(unix)]
+        let _unix_socket_server = match (&self.unix_socket, unix_app) {
+            (Some(path), Some(unix_app)) =>
+                Some(
+                    unix_socket::UnixSocketServer
+                        ::bind(path, self.unix_socket_mode, unix_app)
+                        .inspect_err(|e| {
+                            error!(path = %path.display(), error = %e, "Failed to bind Unix socket");
+                        })?
+                ),
+            _ => None,
+        };
+        #[cfg(not(unix))]
+        if let Some(path) = &self.unix_socket {
+            tracing::warn!(path = %path.display(), "Unix sockets are not supported on this platform");
+        }
+
+        // Bind every port before serving so a taken port fails start() immediately
+        let http_listener = bind_listener(self.port).await.inspect_err(|e| {
+            error!(port = self.port, error = %e, "Failed to bind to HTTP port");
+        })?;
+        let https_listener = match (&self.ssl_config, self.https_port) {
+            (Some(_), Some(https_port)) =>
+                Some(
+                    bind_listener(https_port).await.inspect_err(|e| {
+                        error!(port = https_port, error = %e, "Failed to bind to HTTPS port");
+                    })?
+                ),
+            _ => None,
+        };
+        let mut extra_listeners = Vec::with_capacity(listeners.len());
+        for (port, router) in listeners {
+            let listener = bind_listener(port).await.inspect_err(|e| {
+                error!(port = port, error = %e, "Failed to bind to HTTP port");
+            })?;
+            extra_listeners.push((listener, port, router));
+        }
+
+        // TCP proxies run for as long as start() does
+        let mut _tcp_proxies = Vec::with_capacity(self.tcp_routes.len());
+        for route in &self.tcp_routes {
+            _tcp_proxies.push(
+                TcpProxy::bind(route).await.inspect_err(|e| {
+                    error!(port = route.listen_port, error = %e, "Failed to bind TCP proxy port");
+                })?
+            );
+        }
+
+        // Every listener watches the same shutdown flag; without a signal it is never raised
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        if let Some(signal) = self.shutdown_signal.take() {
+            tokio::spawn(async move {
+                signal.await;
+                info!("Shutdown requested, draining connections");
+                let _ = shutdown_tx.send(true);
+            });
+        }
+
+        let mut servers = JoinSet::new();
+        let tcp_options = self.tcp_options;
+        servers.spawn(
+            serve_http(http_listener, self.port, app.clone(), self.http2_cleartext, tcp_options, shutdown_rx.clone())
+        );
+        for (listener, port, router) in extra_listeners {
+            servers.spawn(serve_http(listener, port, router, self.http2_cleartext, tcp_options, shutdown_rx.clone()));
+        }
+
+        // Start HTTPS server if SSL is configured
+        if
+            let (Some(ssl_config), Some(https_port), Some(listener)) = (
+                self.ssl_config.clone(),
+                self.https_port,
+                https_listener,
+            )
+        {
+            servers.spawn(
+                serve_https(listener, https_port, app, TlsAcceptor::from(ssl_config), tcp_options, shutdown_rx)
+            );
+        }
+
+        // Stop at the first listener that fails, otherwise wait for all of them to drain
+        while let Some(result) = servers.join_next().await {
+            if let Err(e) = result? {
                 error!(error = %e, "HTTP server failed");
                 return Err(e);
             }
@@ -200,19 +559,199 @@ impl Server {
     }
 }
 
+/// Resolve once shutdown has been requested, or never when the server has no shutdown signal
+async fn shutdown_requested(mut shutdown: watch::Receiver<bool>) {
+    if shutdown.wait_for(|requested| *requested).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Serve plain HTTP (and h2c when enabled) on a bound listener until shutdown, then finish
+/// the requests in flight
+async fn serve_http(
+    listener: TcpListener,
+    port: u16,
+    app: Router,
+    http2_cleartext: bool,
+    tcp_options: TcpOptions,
+    shutdown: watch::Receiver<bool>
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    info!(port = port, "HTTP server running at http://localhost:{}", port);
+    let app = app.layer(axum::Extension(ServerListener { tls: false, port }));
+    serve_connections(listener, port, app, None, http2_cleartext, tcp_options, shutdown).await
+}
+
+/// Serve HTTPS on a bound listener until shutdown, then finish the requests in flight
+async fn serve_https(
+    listener: TcpListener,
+    port: u16,
+    app: Router,
+    tls_acceptor: TlsAcceptor,
+    tcp_options: TcpOptions,
+    shutdown: watch::Receiver<bool>
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    info!(port = port, "HTTPS server running at https://localhost:{}", port);
+    let app = app.layer(axum::Extension(ServerListener { tls: true, port }));
+    serve_connections(listener, port, app, Some(tls_acceptor), true, tcp_options, shutdown).await
+}
+
+/// Accept connections until shutdown, serving HTTP/1.1 with upgrades on each (or HTTP/2 too
+/// when `http2` is set), behind TLS when an acceptor is given, then wait for the open
+/// connections to finish. Every connection logs the lifecycle events named in `events`.
+async fn serve_connections(
+    listener: TcpListener,
+    port: u16,
+    app: Router,
+    tls_acceptor: Option<TlsAcceptor>,
+    http2: bool,
+    tcp_options: TcpOptions,
+    shutdown: watch::Receiver<bool>
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let scheme = if tls_acceptor.is_some() { "https" } else { "http" };
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+    let graceful = GracefulShutdown::new();
+    let stop_accepting = shutdown_requested(shutdown);
+    tokio::pin!(stop_accepting);
+
+    loop {
+        let (tcp_stream, remote_addr) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(conn) => conn,
+                Err(e) => {
+                    error!(error = %e, scheme = scheme, "Failed to accept connection");
+                    continue;
+                }
+            },
+            _ = &mut stop_accepting => break,
+        };
+        if let Err(e) = tcp_options.apply(&tcp_stream) {
+            warn!(error = %e, remote_addr = %remote_addr, "Failed to set TCP socket options");
+        }
+
+        let tls_acceptor = tls_acceptor.clone();
+        let mut service = service.clone();
+        let watcher = graceful.watcher();
+
+        tokio::spawn(async move {
+            let opened = std::time::Instant::now();
+            info!(
+                event = events::CONNECTION_ACCEPTED,
+                remote_addr = %remote_addr,
+                scheme = scheme,
+                port = port,
+                "Connection accepted"
+            );
+
+            let hyper_service = match service.call(remote_addr).await {
+                Ok(service) => TowerToHyperService::new(service),
+                Err(e) => {
+                    error!(error = %e, "Failed to create service");
+                    return;
+                }
+            };
+
+            // Serve HTTP/2 when negotiated via ALPN or prior knowledge, otherwise HTTP/1.1 with upgrades
+            let builder = auto::Builder::new(TokioExecutor::new());
+            let builder = if http2 { builder } else { builder.http1_only() };
+            let result = match tls_acceptor {
+                Some(tls_acceptor) =>
+                    match tls_acceptor.accept(tcp_stream).await {
+                        Ok(tls_stream) => {
+                            let alpn = tls_stream
+                                .get_ref()
+                                .1.alpn_protocol()
+                                .map(|protocol| String::from_utf8_lossy(protocol).to_string());
+                            info!(
+                                event = events::TLS_HANDSHAKE_SUCCEEDED,
+                                remote_addr = %remote_addr,
+                                port = port,
+                                alpn = alpn.as_deref().unwrap_or("none"),
+                                "TLS handshake succeeded"
+                            );
+                            let connection = builder.serve_connection_with_upgrades(TokioIo::new(tls_stream), hyper_service);
+                            watcher.watch(connection).await
+                        }
+                        Err(e) => {
+                            warn!(
+                                event = events::TLS_HANDSHAKE_FAILED,
+                                remote_addr = %remote_addr,
+                                port = port,
+                                error = %e,
+                                "TLS handshake failed"
+                            );
+                            Err(e.into())
+                        }
+                    }
+                None =>
+                    watcher.watch(builder.serve_connection_with_upgrades(TokioIo::new(tcp_stream), hyper_service)).await,
+            };
+
+            let duration_ms = opened.elapsed().as_millis() as u64;
+            match result {
+                Ok(()) =>
+                    info!(
+                        event = events::CONNECTION_CLOSED,
+                        remote_addr = %remote_addr,
+                        scheme = scheme,
+                        port = port,
+                        duration_ms = duration_ms,
+                        "Connection closed"
+                    ),
+                Err(e) =>
+                    warn!(
+                        event = events::CONNECTION_CLOSED,
+                        remote_addr = %remote_addr,
+                        scheme = scheme,
+                        port = port,
+                        duration_ms = duration_ms,
+                        error = %e,
+                        "Connection closed with error"
+                    ),
+            }
+        });
+    }
+
+    // Open connections finish their current requests and close
+    graceful.shutdown().await;
+    Ok(())
+}
+
 /// Logging middleware that captures all requests
 pub async fn logging_middleware(
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
-    req: Request,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    mut req: Request,
     next: Next
 ) -> Response {
     let method = req.method().clone();
     let uri = req.uri().clone();
     let path = uri.path();
-    let client_ip = addr.ip().to_string();
+    // Prefer the address resolved behind trusted proxies; Unix socket connections have none
+    let client_ip = match (req.extensions().get::<ClientIp>(), connect_info) {
+        (Some(ClientIp(ip)), _) => ip.to_string(),
+        (None, Some(ConnectInfo(addr))) => addr.ip().to_string(),
+        (None, None) => "unix".to_string(),
+    };
+
+    // Create request span for tracing, correlated by the assigned request ID when present
+    let request_id = match req.extensions().get::<RequestId>() {
+        Some(RequestId(id)) => id.clone(),
+        None => uuid::Uuid::new_v4().to_string(),
+    };
+    let span = create_request_span(&request_id, method.as_ref(), path, &client_ip);
 
-    // Create request span for tracing
-    let span = create_request_span(&method.to_string(), path, &client_ip);
+    // Continue the caller's trace (or start one) and hand this span on as the parent of proxied calls
+    let trace = TraceContext::from_headers(req.headers());
+    span.record("trace_id", trace.trace_id.as_str());
+    span.record("span_id", trace.span_id.as_str());
+    if let Some(parent_span_id) = &trace.parent_span_id {
+        span.record("parent_span_id", parent_span_id.as_str());
+    } else {
+        // Trace state only means something alongside the traceparent it came with
+        req.headers_mut().remove(TRACESTATE_HEADER);
+    }
+    let traceparent = HeaderValue::from_str(&trace.traceparent()).expect("traceparent is a valid header value");
+    req.headers_mut().insert(TRACEPARENT_HEADER, traceparent);
+    req.extensions_mut().insert(trace);
 
     (
         async move {
@@ -224,22 +763,155 @@ pub async fn logging_middleware(
             let duration = start_time.elapsed();
             let status = response.status();
 
-            // Log request with structured data
-            info!(
-            method = %method,
-            path = %path,
-            client_ip = %client_ip,
-            status_code = status.as_u16(),
-            status_text = status.canonical_reason().unwrap_or("Unknown"),
-            duration_ms = duration.as_millis(),
-            "Request completed"
-        );
+            // Log request with structured data, unless a proxy route wrote or skipped its own line
+            if response.extensions().get::<AccessLogHandled>().is_none() {
+                info!(
+                    method = %method,
+                    path = %path,
+                    client_ip = %client_ip,
+                    status_code = status.as_u16(),
+                    status_text = status.canonical_reason().unwrap_or("Unknown"),
+                    duration_ms = duration.as_millis(),
+                    "Request completed"
+                );
+            }
 
             response
         }
     ).instrument(span).await
 }
 
+/// Request ID middleware: reuses a well-formed inbound X-Request-Id or generates a UUID,
+/// sets it on the request (so proxied backends receive it) and echoes it on the response
+pub async fn request_id_middleware(mut req: Request, next: Next) -> Response {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    // Validated above, so the ID is always a legal header value
+    let header_value = HeaderValue::from_str(&request_id).expect("Request ID is a valid header value");
+    req.headers_mut().insert(REQUEST_ID_HEADER, header_value.clone());
+    req.extensions_mut().insert(RequestId(request_id));
+
+    let mut response = next.run(req).await;
+    response.headers_mut().insert(REQUEST_ID_HEADER, header_value);
+    response
+}
+
+/// Request timeout middleware: returns 504 when the handler does not respond in time.
+/// WebSocket upgrades are exempt, and only the time until response headers is bounded
+/// so streaming bodies are not cut off.
+pub async fn request_timeout_middleware(
+    State(timeout): State<Duration>,
+    req: Request,
+    next: Next
+) -> Response {
+    if is_upgrade_request(&req) {
+        return next.run(req).await;
+    }
+
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    match tokio::time::timeout(timeout, next.run(req)).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!(
+                method = %method,
+                path = %path,
+                timeout_secs = timeout.as_secs_f64(),
+                "Request timed out"
+            );
+            create_error_response(StatusCode::GATEWAY_TIMEOUT, "Request timed out")
+        }
+    }
+}
+
+/// Request body limit middleware: returns 413 when Content-Length exceeds the limit, and
+/// caps streamed bodies so reading past the limit fails instead of buffering it all
+pub async fn request_body_limit_middleware(
+    State(limit): State<usize>,
+    req: Request,
+    next: Next
+) -> Response {
+    let content_length = req
+        .headers()
+        .get(axum::http::header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+
+    if content_length.is_some_and(|length| length > (limit as u64)) {
+        tracing::warn!(
+            method = %req.method(),
+            path = %req.uri().path(),
+            content_length = content_length,
+            limit = limit,
+            "Request body too large"
+        );
+        return create_error_response(StatusCode::PAYLOAD_TOO_LARGE, "Request body too large");
+    }
+
+    let req = req.map(|body| axum::body::Body::new(http_body_util::Limited::new(body, limit)));
+    next.run(req).await
+}
+
+/// Request header limit middleware: returns 431 when the request has more header fields,
+/// or more bytes of header names and values, than allowed
+pub async fn request_header_limit_middleware(
+    State(limits): State<HeaderLimits>,
+    req: Request,
+    next: Next
+) -> Response {
+    let headers = req.headers();
+    let count = headers.len();
+    let bytes: usize = headers
+        .iter()
+        .map(|(name, value)| name.as_str().len() + value.len())
+        .sum();
+
+    let exceeded = limits.max_count.is_some_and(|max_count| count > max_count) ||
+        limits.max_bytes.is_some_and(|max_bytes| bytes > max_bytes);
+    if exceeded {
+        tracing::warn!(
+            method = %req.method(),
+            path = %req.uri().path(),
+            header_count = count,
+            header_bytes = bytes,
+            max_count = ?limits.max_count,
+            max_bytes = ?limits.max_bytes,
+            "Request headers too large"
+        );
+        return create_error_response(
+            StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
+            "Request header fields too large"
+        );
+    }
+
+    next.run(req).await
+}
+
+/// Whether the request asks to switch protocols (e.g. WebSocket)
+fn is_upgrade_request(req: &Request) -> bool {
+    let connection_upgrade = req
+        .headers()
+        .get(axum::http::header::CONNECTION)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value.split(',').any(|token| token.trim().eq_ignore_ascii_case("upgrade"))
+        });
+    connection_upgrade && req.headers().contains_key(axum::http::header::UPGRADE)
+}
+
+/// Accept inbound IDs that are short, non-empty printable tokens
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty() &&
+        id.len() <= MAX_REQUEST_ID_LEN &&
+        id.bytes().all(|b| b.is_ascii_graphic())
+}
+
 /// Gateway health endpoint handler
 pub async fn gateway_health() -> Json<serde_json::Value> {
     Json(
@@ -257,23 +929,137 @@ pub fn create_health_router() -> Router {
     Router::new().route("/health", get(gateway_health)).route("/ping", get(gateway_health))
 }
 
-/// Create a standard error response
+/// Readiness check: returns the reasons the server cannot take traffic (empty when ready)
+pub type ReadinessCheck = Arc<dyn Fn() -> Vec<String> + Send + Sync>;
+
+/// Liveness probe handler: the process is up and serving requests
+pub async fn liveness_probe() -> Json<serde_json::Value> {
+    Json(
+        json!({
+        "status": "alive",
+        "timestamp": Utc::now().to_rfc3339()
+    })
+    )
+}
+
+/// Readiness probe handler: 200 when every check passes, 503 with the failures otherwise
+pub async fn readiness_probe(State(checks): State<Arc<Vec<ReadinessCheck>>>) -> Response {
+    let failures: Vec<String> = checks
+        .iter()
+        .flat_map(|check| check())
+        .collect();
+
+    let (status, label) = if failures.is_empty() {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "not_ready")
+    };
+    let body =
+        json!({
+        "status": label,
+        "failures": failures,
+        "timestamp": Utc::now().to_rfc3339()
+    });
+    (status, Json(body)).into_response()
+}
+
+/// Create Kubernetes-style liveness (/livez) and readiness (/readyz) probe router
+pub fn create_probe_router(checks: Vec<ReadinessCheck>) -> Router {
+    Router::new()
+        .route("/livez", get(liveness_probe))
+        .route("/readyz", get(readiness_probe))
+        .with_state(Arc::new(checks))
+}
+
+/// Create a standard HTML error response, using the configured custom page for the status if any
 pub fn create_error_response(status: StatusCode, message: &str) -> Response {
-    (status, message.to_string()).into_response()
+    let mut response = (
+        status,
+        [(axum::http::header::CONTENT_TYPE, "text/html")],
+        default_error_page(status, message),
+    ).into_response();
+    response.extensions_mut().insert(GeneratedErrorPage);
+    response
+}
+
+/// Error page middleware: swaps built-in error pages for the configured custom pages
+pub async fn error_page_middleware(
+    State(pages): State<ErrorPages>,
+    req: Request,
+    next: Next
+) -> Response {
+    let mut response = next.run(req).await;
+    if response.extensions_mut().remove::<GeneratedErrorPage>().is_none() {
+        return response;
+    }
+    match pages.get(response.status().as_u16()) {
+        Some(page) => {
+            let (mut parts, _) = response.into_parts();
+            parts.headers.remove(axum::http::header::CONTENT_LENGTH);
+            parts.headers.insert(axum::http::header::CONTENT_TYPE, HeaderValue::from_static("text/html"));
+            Response::from_parts(parts, axum::body::Body::from(page.to_string()))
+        }
+        None => response,
+    }
+}
+
+/// Built-in error page used when no custom page is configured
+fn default_error_page(status: StatusCode, message: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>{} {}</title>
+    <style>
+        body {{ font-family: Arial, sans-serif; margin: 40px; }}
+        h1 {{ color: #d32f2f; }}
+        p {{ color: #666; }}
+    </style>
+</head>
+<body>
+    <h1>{} {}</h1>
+    <p>{}</p>
+</body>
+</html>"#,
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("Error"),
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("Error"),
+        message
+    )
 }
 
 /// HTTPS redirect middleware
-pub async fn https_redirect_middleware(req: Request, next: Next) -> Response {
+///
+/// Requests arriving on a TLS listener, or forwarded by a trusted proxy with
+/// `X-Forwarded-Proto: https`, are already secure and pass through, as do exempt paths.
+pub async fn https_redirect_middleware(
+    State(config): State<Arc<SslRedirectConfig>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    req: Request,
+    next: Next
+) -> Response {
     use axum::http::{ header, StatusCode, Uri };
 
+    if !config.enabled {
+        return next.run(req).await;
+    }
+
     // Check if request is already HTTPS
-    if req.uri().scheme_str() == Some("https") {
+    let tls_listener = req
+        .extensions()
+        .get::<ServerListener>()
+        .is_some_and(|listener| listener.tls);
+    let forwarded_https = connect_info.is_some_and(|ConnectInfo(peer)|
+        config.forwarded_https(peer.ip(), req.headers())
+    );
+    if req.uri().scheme_str() == Some("https") || tls_listener || forwarded_https {
         return next.run(req).await;
     }
 
     // Check if this is a health check or other exempt path
     let path = req.uri().path();
-    if path.starts_with("/health") || path.starts_with("/ping") {
+    if config.is_exempt(path) || path.starts_with(acme::ACME_CHALLENGE_PATH_PREFIX) {
         return next.run(req).await;
     }
 
@@ -286,10 +1072,11 @@ pub async fn https_redirect_middleware(req: Request, next: Next) -> Response {
 
     // Remove port from host if present, then add HTTPS port
     let host_without_port = host.split(':').next().unwrap_or(host);
-    let https_host = if host_without_port == "localhost" || host_without_port.starts_with("127.") {
-        format!("{}:443", host_without_port)
+    let local = host_without_port == "localhost" || host_without_port.starts_with("127.");
+    let https_host = if local || config.https_port != 443 {
+        format!("{}:{}", host_without_port, config.https_port)
     } else {
-        host_without_port.to_string() // Assume standard HTTPS port 443
+        host_without_port.to_string() // Standard HTTPS port needs no suffix
     };
 
     // Construct HTTPS URL
@@ -1,44 +1,79 @@
-use std::path::Path;
-use tracing_subscriber::{ fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer };
-use tracing_appender::{ non_blocking, rolling::{ RollingFileAppender, Rotation } };
+use std::fs::{ File, OpenOptions };
+use std::io::Write;
+use std::path::{ Path, PathBuf };
+use tracing_subscriber::{
+    fmt,
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+    EnvFilter,
+    Layer,
+    Registry,
+};
+use tracing_appender::{ non_blocking, non_blocking::WorkerGuard };
 use httpserver_config::LoggingConfig;
 
+/// Name of the active log file inside `logs_directory`
+const LOG_FILE_NAME: &str = "httpserver.log";
+
 /// Initialize the logging system based on configuration
 pub fn initialize_logging(config: &LoggingConfig) -> Result<(), Box<dyn std::error::Error>> {
-    // Create logs directory if it doesn't exist
-    if config.file_logging && (config.output_mode == "both" || config.output_mode == "file") {
-        std::fs::create_dir_all(&config.logs_directory)?;
+    let (subscriber, guard) = create_subscriber(config)?;
+
+    // An application embedding the server may have installed its own subscriber; keep it
+    if subscriber.try_init().is_err() {
+        tracing::debug!("A global tracing subscriber is already installed, keeping it");
+        return Ok(());
     }
 
+    // Keep the guard alive for the entire program duration
+    // This is necessary to prevent the file logging from stopping
+    if let Some(guard) = guard {
+        std::mem::forget(guard);
+    }
+
+    tracing::info!(
+        logs_directory = %config.logs_directory.display(),
+        level = %config.level,
+        format = %config.format,
+        output_mode = %config.output_mode,
+        file_size_mb = config.file_size_mb,
+        retention_days = config.retention_days,
+        structured_logging = config.structured_logging,
+        enable_request_ids = config.enable_request_ids,
+        enable_performance_metrics = config.enable_performance_metrics,
+        "Logging initialized"
+    );
+    Ok(())
+}
+
+/// Build the subscriber described by the configuration without installing it.
+/// The returned guard flushes buffered file output when dropped.
+pub fn create_subscriber(
+    config: &LoggingConfig
+) -> Result<(impl tracing::Subscriber + Send + Sync + 'static, Option<WorkerGuard>), Box<dyn std::error::Error>> {
     // Create the filter based on log level
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_|
         EnvFilter::new(&config.level)
     );
-
-    // Build the subscriber
-    let subscriber = tracing_subscriber::registry().with(filter);
+    let mut layers: Vec<Box<dyn Layer<Registry> + Send + Sync>> = vec![filter.boxed()];
 
     // Determine what outputs to enable based on output_mode
     let enable_file =
         config.file_logging && (config.output_mode == "both" || config.output_mode == "file");
     let enable_console = config.output_mode == "both" || config.output_mode == "console";
 
-    if enable_file && enable_console {
-        // Both file and console output
-        let file_appender = RollingFileAppender::builder()
-            .rotation(Rotation::NEVER) // We'll handle rotation manually by size
-            .filename_prefix("httpserver")
-            .filename_suffix("log")
-            .build(&config.logs_directory)?;
-
-        let (non_blocking_appender, guard) = non_blocking(file_appender);
-
-        // Keep the guard alive for the entire program duration
-        // This is necessary to prevent the file logging from stopping
-        std::mem::forget(guard);
-
-        // Add file logging layer (NO ANSI colors for file output)
-        let file_layer = if config.format == "json" {
+    let mut guard = None;
+    if enable_file {
+        let writer = SizeRotatingWriter::new(
+            &config.logs_directory,
+            config.file_size_mb * 1024 * 1024,
+            config.retention_days
+        )?;
+        let (non_blocking_appender, file_guard) = non_blocking(writer);
+        guard = Some(file_guard);
+
+        // File logging layer (NO ANSI colors for file output)
+        layers.push(if config.format == "json" {
             fmt::layer()
                 .json()
                 .with_ansi(false) // Disable ANSI colors for file output
@@ -49,10 +84,12 @@ pub fn initialize_logging(config: &LoggingConfig) -> Result<(), Box<dyn std::err
                 .with_ansi(false) // Disable ANSI colors for file output
                 .with_writer(non_blocking_appender)
                 .boxed()
-        };
+        });
+    }
 
-        // Add console logging layer (WITH ANSI colors for console output)
-        let console_layer = if config.format == "json" {
+    if enable_console {
+        // Console logging layer (WITH ANSI colors for console output)
+        layers.push(if config.format == "json" {
             fmt::layer()
                 .json()
                 .with_ansi(true) // Enable ANSI colors for console output
@@ -63,92 +100,93 @@ pub fn initialize_logging(config: &LoggingConfig) -> Result<(), Box<dyn std::err
                 .with_ansi(true) // Enable ANSI colors for console output
                 .with_writer(std::io::stdout)
                 .boxed()
-        };
-
-        subscriber.with(file_layer).with(console_layer).init();
-        tracing::info!(
-            logs_directory = %config.logs_directory.display(),
-            level = %config.level,
-            format = %config.format,
-            output_mode = %config.output_mode,
-            structured_logging = config.structured_logging,
-            enable_request_ids = config.enable_request_ids,
-            enable_performance_metrics = config.enable_performance_metrics,
-            "Logging initialized with both file and console output"
-        );
-    } else if enable_file {
-        // File-only output
-        let file_appender = RollingFileAppender::builder()
-            .rotation(Rotation::NEVER) // We'll handle rotation manually by size
-            .filename_prefix("httpserver")
-            .filename_suffix("log")
-            .build(&config.logs_directory)?;
-
-        let (non_blocking_appender, guard) = non_blocking(file_appender);
-
-        // Keep the guard alive for the entire program duration
-        // This is necessary to prevent the file logging from stopping
-        std::mem::forget(guard);
+        });
+    }
 
-        let file_layer = if config.format == "json" {
-            fmt::layer()
-                .json()
-                .with_ansi(false) // Disable ANSI colors for file output
-                .with_writer(non_blocking_appender)
-                .boxed()
-        } else {
-            fmt::layer()
-                .with_ansi(false) // Disable ANSI colors for file output
-                .with_writer(non_blocking_appender)
-                .boxed()
-        };
+    Ok((tracing_subscriber::registry().with(layers), guard))
+}
 
-        subscriber.with(file_layer).init();
+/// Log file writer that rotates `httpserver.log` once it reaches a size limit
+/// and removes rotated files older than the retention period
+pub struct SizeRotatingWriter {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    /// Rotation threshold in bytes (0 disables rotation)
+    max_bytes: u64,
+    retention_days: u32,
+}
 
-        tracing::info!(
-            logs_directory = %config.logs_directory.display(),
-            level = %config.level,
-            format = %config.format,
-            output_mode = %config.output_mode,
-            "Logging initialized with file output only"
-        );
-    } else {
-        // Console-only logging (WITH ANSI colors)
-        let console_layer = if config.format == "json" {
-            fmt::layer()
-                .json()
-                .with_ansi(true) // Enable ANSI colors for console output
-                .with_writer(std::io::stdout)
-                .boxed()
-        } else {
-            fmt::layer()
-                .with_ansi(true) // Enable ANSI colors for console output
-                .with_writer(std::io::stdout)
-                .boxed()
-        };
+impl SizeRotatingWriter {
+    /// Open (or append to) `httpserver.log` in the given directory, creating it if needed
+    pub fn new(
+        directory: &Path,
+        max_bytes: u64,
+        retention_days: u32
+    ) -> std::io::Result<Self> {
+        std::fs::create_dir_all(directory)?;
+        let path = directory.join(LOG_FILE_NAME);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_bytes,
+            retention_days,
+        })
+    }
 
-        subscriber.with(console_layer).init();
+    /// Move the current file to `.1` and start a new one
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        rotate_log_file(&self.path).map_err(|e| std::io::Error::other(e.to_string()))?;
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = 0;
 
-        tracing::info!(
-            level = %config.level,
-            format = %config.format,
-            output_mode = %config.output_mode,
-            "Logging initialized (console only)"
-        );
+        if let Some(directory) = self.path.parent() {
+            // Retention is best effort; a failure here must not stop logging
+            let _ = remove_expired_logs(directory, self.retention_days);
+        }
+        Ok(())
     }
-    Ok(())
 }
 
-/// Create a request span with unique ID for tracing
-pub fn create_request_span(method: &str, path: &str, client_ip: &str) -> tracing::Span {
-    let request_id = uuid::Uuid::new_v4().to_string();
+impl Write for SizeRotatingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.max_bytes > 0 && self.size > 0 && self.size + (buf.len() as u64) > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
 
+/// Create a request span tagged with the request's ID for tracing
+///
+/// The `trace_id`, `span_id` and `parent_span_id` fields start empty and are recorded once the
+/// request's trace context is known.
+pub fn create_request_span(
+    request_id: &str,
+    method: &str,
+    path: &str,
+    client_ip: &str
+) -> tracing::Span {
     tracing::info_span!(
         "request",
         request_id = %request_id,
         method = %method,
         path = %path,
-        client_ip = %client_ip
+        client_ip = %client_ip,
+        trace_id = tracing::field::Empty,
+        span_id = tracing::field::Empty,
+        parent_span_id = tracing::field::Empty
     )
 }
 
@@ -158,7 +196,7 @@ pub fn check_log_rotation(config: &LoggingConfig) -> Result<(), Box<dyn std::err
         return Ok(());
     }
 
-    let log_file_path = config.logs_directory.join("httpserver.log");
+    let log_file_path = config.logs_directory.join(LOG_FILE_NAME);
 
     if let Ok(metadata) = std::fs::metadata(&log_file_path) {
         let size_mb = metadata.len() / (1024 * 1024);
@@ -219,13 +257,27 @@ pub fn cleanup_old_logs(config: &LoggingConfig) -> Result<(), Box<dyn std::error
         return Ok(());
     }
 
+    for path in remove_expired_logs(&config.logs_directory, config.retention_days)? {
+        tracing::info!(
+            file = %path.display(),
+            "Removed old log file"
+        );
+    }
+
+    Ok(())
+}
+
+/// Remove rotated log files last modified before the retention cutoff, returning their paths
+fn remove_expired_logs(
+    directory: &Path,
+    retention_days: u32
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
     let cutoff_time =
         std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs() -
-        (config.retention_days as u64) * 24 * 60 * 60;
-
-    let parent_dir = &config.logs_directory;
+        (retention_days as u64) * 24 * 60 * 60;
 
-    for entry in std::fs::read_dir(parent_dir)? {
+    let mut removed = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
         let entry = entry?;
         let path = entry.path();
 
@@ -242,10 +294,7 @@ pub fn cleanup_old_logs(config: &LoggingConfig) -> Result<(), Box<dyn std::error
 
                         if modified_secs < cutoff_time {
                             std::fs::remove_file(&path)?;
-                            tracing::info!(
-                                file = %path.display(),
-                                "Removed old log file"
-                            );
+                            removed.push(path);
                         }
                     }
                 }
@@ -253,5 +302,5 @@ pub fn cleanup_old_logs(config: &LoggingConfig) -> Result<(), Box<dyn std::error
         }
     }
 
-    Ok(())
+    Ok(removed)
 }
@@ -1,10 +1,22 @@
 // SSL/TLS termination and certificate management
+use std::collections::{ HashMap, HashSet };
 use std::fs;
-use std::path::Path;
-use std::sync::Arc;
+use std::net::IpAddr;
+use std::path::{ Path, PathBuf };
+use std::sync::{ mpsc, Arc };
+use std::time::Duration;
+use axum::http::HeaderMap;
+use arc_swap::{ ArcSwap, ArcSwapOption };
+use notify::{ EventKind, RecommendedWatcher, RecursiveMode, Watcher };
+use rustls::server::{ ClientHello, ResolvesServerCert };
+use rustls::sign::{ any_supported_type, CertifiedKey };
 use rustls::{ Certificate, PrivateKey, ServerConfig };
 use rustls_pemfile::{ certs, pkcs8_private_keys, rsa_private_keys };
 use tracing;
+use crate::client_ip::TrustedProxies;
+
+/// Quiet period after a certificate file change before reloading
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(250);
 
 /// SSL certificate and key pair
 #[derive(Debug, Clone)]
@@ -13,18 +25,88 @@ pub struct SslCertificate {
     pub private_key: PrivateKey,
 }
 
+/// Files a certificate was loaded from, kept so it can be re-read on reload
+#[derive(Debug, Clone)]
+struct CertificateSource {
+    cert_file: PathBuf,
+    key_file: PathBuf,
+    cert_chain_file: Option<PathBuf>,
+}
+
+/// SNI certificate resolver whose certificates can be swapped at runtime.
+/// Handshakes already in progress keep the certificate they resolved.
+pub struct ReloadableCertResolver {
+    certificates: ArcSwap<HashMap<String, Arc<CertifiedKey>>>,
+    default_domain: ArcSwapOption<String>,
+}
+
+impl ReloadableCertResolver {
+    fn new() -> Self {
+        Self {
+            certificates: ArcSwap::from_pointee(HashMap::new()),
+            default_domain: ArcSwapOption::empty(),
+        }
+    }
+
+    /// Publish a certificate for a domain, replacing any previous one
+    fn publish(&self, domain: &str, key: Arc<CertifiedKey>) {
+        self.certificates.rcu(|current| {
+            let mut updated = HashMap::clone(current);
+            updated.insert(domain.to_string(), key.clone());
+            updated
+        });
+    }
+
+    /// Resolve the certificate for an SNI name (exact, then wildcard, then default)
+    pub fn resolve_domain(&self, server_name: Option<&str>) -> Option<Arc<CertifiedKey>> {
+        let certificates = self.certificates.load();
+
+        if let Some(name) = server_name {
+            if let Some(key) = certificates.get(name) {
+                return Some(key.clone());
+            }
+            for (domain, key) in certificates.iter() {
+                if SslCertificateManager::matches_wildcard_domain(name, domain) {
+                    return Some(key.clone());
+                }
+            }
+        }
+
+        let default_domain = self.default_domain.load();
+        default_domain
+            .as_ref()
+            .and_then(|domain| certificates.get(domain.as_str()))
+            .cloned()
+    }
+}
+
+impl ResolvesServerCert for ReloadableCertResolver {
+    fn resolve(&self, client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        self.resolve_domain(client_hello.server_name())
+    }
+}
+
+/// Keeps certificate files watched; dropping it stops hot reloading
+pub struct CertificateWatcher {
+    _watcher: RecommendedWatcher,
+}
+
 /// SSL certificate manager for loading and managing certificates
 pub struct SslCertificateManager {
-    certificates: std::collections::HashMap<String, SslCertificate>,
+    certificates: HashMap<String, SslCertificate>,
     wildcard_cert: Option<SslCertificate>,
+    sources: HashMap<String, CertificateSource>,
+    resolver: Arc<ReloadableCertResolver>,
 }
 
 impl SslCertificateManager {
     /// Create a new SSL certificate manager
     pub fn new() -> Self {
         Self {
-            certificates: std::collections::HashMap::new(),
+            certificates: HashMap::new(),
             wildcard_cert: None,
+            sources: HashMap::new(),
+            resolver: Arc::new(ReloadableCertResolver::new()),
         }
     }
 
@@ -68,6 +150,7 @@ impl SslCertificateManager {
 
         // Load additional certificates from chain file if provided
         let mut final_cert_chain = cert_chain;
+        let chain_source = cert_chain_file.as_ref().map(|path| path.as_ref().to_path_buf());
         if let Some(chain_path) = cert_chain_file {
             let chain_path = chain_path.as_ref();
             tracing::info!(
@@ -138,6 +221,19 @@ impl SslCertificateManager {
                 }
             }
         };
+        let signing_key = any_supported_type(&private_key).map_err(|e|
+            format!("Unsupported private key in file '{}': {}", key_path.display(), e)
+        )?;
+        self.resolver.publish(
+            &domain,
+            Arc::new(CertifiedKey::new(final_cert_chain.clone(), signing_key))
+        );
+        self.sources.insert(domain.clone(), CertificateSource {
+            cert_file: cert_path.to_path_buf(),
+            key_file: key_path.to_path_buf(),
+            cert_chain_file: chain_source,
+        });
+
         let ssl_cert = SslCertificate {
             cert_chain: final_cert_chain.clone(),
             private_key,
@@ -161,6 +257,49 @@ impl SslCertificateManager {
         Ok(())
     }
 
+    /// Generate an in-memory self-signed certificate for `localhost` and `127.0.0.1`.
+    /// Intended for local development only; browsers will warn about the issuer.
+    pub fn generate_self_signed_certificate(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut params = rcgen::CertificateParams::default();
+        params.distinguished_name = rcgen::DistinguishedName::new();
+        params.distinguished_name.push(rcgen::DnType::CommonName, "localhost");
+        params.distinguished_name.push(rcgen::DnType::OrganizationName, "httpserver self-signed");
+        params.subject_alt_names = vec![
+            rcgen::SanType::DnsName("localhost".to_string()),
+            rcgen::SanType::IpAddress(std::net::IpAddr::V4(std::net::Ipv4Addr::LOCALHOST)),
+            rcgen::SanType::IpAddress(std::net::IpAddr::V6(std::net::Ipv6Addr::LOCALHOST))
+        ];
+
+        let generated = rcgen::Certificate
+            ::from_params(params)
+            .map_err(|e| format!("Failed to generate self-signed certificate: {}", e))?;
+        let cert_chain = vec![
+            Certificate(
+                generated
+                    .serialize_der()
+                    .map_err(|e| format!("Failed to serialize self-signed certificate: {}", e))?
+            )
+        ];
+        let private_key = PrivateKey(generated.serialize_private_key_der());
+
+        let signing_key = any_supported_type(&private_key).map_err(|e|
+            format!("Unsupported self-signed private key: {}", e)
+        )?;
+        self.resolver.publish(
+            "localhost",
+            Arc::new(CertifiedKey::new(cert_chain.clone(), signing_key))
+        );
+        self.certificates.insert("localhost".to_string(), SslCertificate {
+            cert_chain,
+            private_key,
+        });
+
+        tracing::warn!(
+            "Generated self-signed certificate for localhost - for local development only"
+        );
+        Ok(())
+    }
+
     /// Check if a domain matches a wildcard pattern
     pub fn matches_wildcard_domain(domain: &str, wildcard_pattern: &str) -> bool {
         if !wildcard_pattern.starts_with("*.") {
@@ -173,8 +312,7 @@ impl SslCertificateManager {
         if domain.ends_with(base_domain) && domain != base_domain {
             // Check that there's exactly one more subdomain level
             let prefix = &domain[..domain.len() - base_domain.len()];
-            if prefix.ends_with('.') {
-                let subdomain = &prefix[..prefix.len() - 1];
+            if let Some(subdomain) = prefix.strip_suffix('.') {
                 return !subdomain.contains('.');
             }
         }
@@ -219,8 +357,7 @@ impl SslCertificateManager {
         if let Some(wildcard_cert) = &self.wildcard_cert {
             // Check if this domain could be covered by wildcard
             for wildcard_domain in self.certificates.keys() {
-                if wildcard_domain.starts_with("*.") {
-                    let base_domain = &wildcard_domain[2..]; // Remove "*."
+                if let Some(base_domain) = wildcard_domain.strip_prefix("*.") {
                     if domain.ends_with(base_domain) && domain != base_domain {
                         tracing::debug!(
                             domain = %domain,
@@ -241,15 +378,19 @@ impl SslCertificateManager {
         &self,
         domain: &str
     ) -> Result<Arc<ServerConfig>, Box<dyn std::error::Error>> {
-        let ssl_cert = self
-            .get_certificate(domain)
+        let default_domain = self
+            .resolve_loaded_domain(domain)
             .ok_or_else(|| format!("No SSL certificate found for domain '{}'", domain))?;
+        self.resolver.default_domain.store(Some(Arc::new(default_domain)));
 
-        let config = ServerConfig::builder()
+        // Certificates are resolved per handshake so reloads apply without a restart
+        let mut config = ServerConfig::builder()
             .with_safe_defaults()
             .with_no_client_auth()
-            .with_single_cert(ssl_cert.cert_chain.clone(), ssl_cert.private_key.clone())
-            .map_err(|e| format!("Failed to create SSL server config: {}", e))?;
+            .with_cert_resolver(self.resolver.clone());
+
+        // Offer HTTP/2 first, falling back to HTTP/1.1 for clients without h2
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
 
         tracing::info!(
             domain = %domain,
@@ -259,6 +400,122 @@ impl SslCertificateManager {
         Ok(Arc::new(config))
     }
 
+    /// Name of the loaded certificate that serves `domain` (exact or wildcard)
+    fn resolve_loaded_domain(&self, domain: &str) -> Option<String> {
+        if self.certificates.contains_key(domain) {
+            return Some(domain.to_string());
+        }
+        self.certificates
+            .keys()
+            .find(|cert_domain| {
+                cert_domain
+                    .strip_prefix("*.")
+                    .is_some_and(|base| domain.ends_with(base) && domain != base)
+            })
+            .cloned()
+    }
+
+    /// Resolver shared with every `ServerConfig` created by this manager
+    pub fn resolver(&self) -> Arc<ReloadableCertResolver> {
+        self.resolver.clone()
+    }
+
+    /// Re-read every certificate from disk and swap it in for new handshakes.
+    /// Certificates that fail to load keep their previous version.
+    pub fn reload_certificates(&mut self) -> Result<usize, Box<dyn std::error::Error>> {
+        let sources: Vec<(String, CertificateSource)> = self.sources
+            .iter()
+            .map(|(domain, source)| (domain.clone(), source.clone()))
+            .collect();
+
+        let mut reloaded = 0;
+        let mut failures = Vec::new();
+        for (domain, source) in sources {
+            match
+                self.load_certificate_from_files(
+                    domain.clone(),
+                    &source.cert_file,
+                    &source.key_file,
+                    source.cert_chain_file.as_ref()
+                )
+            {
+                Ok(()) => {
+                    reloaded += 1;
+                }
+                Err(e) => {
+                    tracing::error!(domain = %domain, error = %e, "Failed to reload SSL certificate");
+                    failures.push(format!("{}: {}", domain, e));
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            tracing::info!(count = reloaded, "SSL certificates reloaded");
+            Ok(reloaded)
+        } else {
+            Err(format!("Failed to reload certificates: {}", failures.join("; ")).into())
+        }
+    }
+
+    /// Watch the loaded certificate files and reload them when a certificate changes
+    pub fn watch_for_changes(mut self) -> Result<CertificateWatcher, Box<dyn std::error::Error>> {
+        // Watch parent directories so atomic replace-by-rename is noticed. Only certificate
+        // changes trigger a reload: writers replace the key first, and reloading on the key
+        // alone would pair it with the old certificate.
+        let mut watched_files = HashSet::new();
+        let mut watched_dirs = HashSet::new();
+        for source in self.sources.values() {
+            let files = [Some(&source.cert_file), source.cert_chain_file.as_ref()];
+            for file in files.into_iter().flatten() {
+                let dir = match file.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                    _ => PathBuf::from("."),
+                };
+                let dir = dir.canonicalize()?;
+                if let Some(name) = file.file_name() {
+                    watched_files.insert(dir.join(name));
+                }
+                watched_dirs.insert(dir);
+            }
+        }
+
+        let (event_tx, event_rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = event_tx.send(event);
+        })?;
+        for dir in &watched_dirs {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+            tracing::info!(directory = %dir.display(), "Watching SSL certificates for changes");
+        }
+
+        std::thread::spawn(move || {
+            while let Ok(event) = event_rx.recv() {
+                let relevant = match event {
+                    Ok(event) =>
+                        matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) &&
+                            event.paths.iter().any(|path| watched_files.contains(path)),
+                    Err(e) => {
+                        tracing::warn!(error = %e, "SSL certificate watcher error");
+                        false
+                    }
+                };
+                if !relevant {
+                    continue;
+                }
+
+                // Let writers finish (certificate and key are often replaced back to back)
+                while event_rx.recv_timeout(RELOAD_DEBOUNCE).is_ok() {}
+
+                tracing::info!("SSL certificate files changed, reloading");
+                if let Err(e) = self.reload_certificates() {
+                    tracing::error!(error = %e, "SSL certificate hot reload failed");
+                }
+            }
+        });
+
+        Ok(CertificateWatcher { _watcher: watcher })
+    }
+
     /// List all loaded certificates
     pub fn list_certificates(&self) -> Vec<String> {
         self.certificates.keys().cloned().collect()
@@ -278,11 +535,20 @@ impl SslCertificateManager {
     }
 }
 
+impl Default for SslCertificateManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// SSL redirect middleware for forcing HTTPS
+#[derive(Debug, Clone)]
 pub struct SslRedirectConfig {
     pub enabled: bool,
     pub https_port: u16,
     pub exempt_paths: Vec<String>, // Paths that don't require HTTPS (e.g., health checks)
+    /// Proxies whose X-Forwarded-Proto is trusted to report the client's scheme
+    pub trusted_proxies: TrustedProxies,
 }
 
 impl SslRedirectConfig {
@@ -291,9 +557,42 @@ impl SslRedirectConfig {
             enabled,
             https_port,
             exempt_paths: vec!["/health".to_string(), "/ping".to_string()],
+            trusted_proxies: TrustedProxies::default(),
+        }
+    }
+
+    /// Redirect settings from the `[server.ssl]` section. Without a `redirect` table the legacy
+    /// `force_https` flag decides, with the default exemptions.
+    pub fn from_config(ssl_config: &httpserver_config::SslConfig) -> Self {
+        match &ssl_config.redirect {
+            Some(redirect) =>
+                Self {
+                    enabled: redirect.enabled,
+                    https_port: ssl_config.https_port,
+                    exempt_paths: redirect.exempt_paths.clone(),
+                    trusted_proxies: TrustedProxies::default(),
+                },
+            None => Self::new(ssl_config.force_https, ssl_config.https_port),
         }
     }
 
+    /// Treat requests as secure when one of these proxies reports `X-Forwarded-Proto: https`
+    pub fn with_trusted_proxies(mut self, trusted_proxies: TrustedProxies) -> Self {
+        self.trusted_proxies = trusted_proxies;
+        self
+    }
+
+    /// Whether a request from `peer` reached the client-facing proxy over HTTPS. Only the
+    /// first X-Forwarded-Proto value from a trusted peer counts.
+    pub fn forwarded_https(&self, peer: IpAddr, headers: &HeaderMap) -> bool {
+        self.trusted_proxies.is_trusted(peer) &&
+            headers
+                .get("x-forwarded-proto")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(',').next())
+                .is_some_and(|proto| proto.trim().eq_ignore_ascii_case("https"))
+    }
+
     /// Check if a path is exempt from HTTPS redirect
     pub fn is_exempt(&self, path: &str) -> bool {
         self.exempt_paths.iter().any(|exempt_path| path.starts_with(exempt_path))
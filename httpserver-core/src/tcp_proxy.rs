@@ -0,0 +1,124 @@
+use crate::{ bind_listener, events, ServerError };
+use httpserver_balancer::{ LoadBalancer, Target };
+use httpserver_config::TcpRoute;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{ TcpListener, TcpStream };
+use tokio::task::JoinHandle;
+use tracing::{ debug, info, warn };
+
+/// Time allowed to open the connection to a backend
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Relays TCP connections on a port to the route's backends; dropping it stops accepting
+pub struct TcpProxy {
+    local_addr: SocketAddr,
+    balancer: Arc<LoadBalancer>,
+    task: JoinHandle<()>,
+}
+
+impl TcpProxy {
+    /// Bind the route's port and start accepting connections
+    pub async fn bind(route: &TcpRoute) -> Result<Self, ServerError> {
+        let listener = bind_listener(route.listen_port).await?;
+        let local_addr = listener.local_addr().map_err(|source| ServerError::Bind {
+            port: route.listen_port,
+            source,
+        })?;
+        let targets = route.targets
+            .iter()
+            .map(|target| Target::with_weight(target.url.clone(), target.weight))
+            .collect();
+        let balancer = Arc::new(LoadBalancer::new(targets, route.strategy.clone()));
+
+        info!(port = local_addr.port(), targets = route.targets.len(), "TCP proxy listening on port {}", local_addr.port());
+
+        Ok(Self {
+            local_addr,
+            balancer: balancer.clone(),
+            task: tokio::spawn(serve(listener, balancer)),
+        })
+    }
+
+    /// Address the proxy is listening on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Load balancer choosing the backend of each connection
+    pub fn balancer(&self) -> &LoadBalancer {
+        &self.balancer
+    }
+}
+
+impl Drop for TcpProxy {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Accept connections until the proxy is dropped, relaying each on its own task
+async fn serve(listener: TcpListener, balancer: Arc<LoadBalancer>) {
+    loop {
+        let (client, client_addr) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                warn!(error = %e, "Failed to accept TCP connection");
+                continue;
+            }
+        };
+        tokio::spawn(relay(client, client_addr, balancer.clone()));
+    }
+}
+
+/// Connect a client to the next backend and copy bytes both ways until either side closes
+async fn relay(mut client: TcpStream, client_addr: SocketAddr, balancer: Arc<LoadBalancer>) {
+    let Some(target) = balancer.select_target().map(|target| target.url.clone()) else {
+        warn!(client = %client_addr, "No healthy TCP backend available");
+        return;
+    };
+
+    let mut backend = match tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(&target)).await {
+        Ok(Ok(backend)) => backend,
+        Ok(Err(e)) => {
+            warn!(
+                event = events::BACKEND_CONNECT_FAILED,
+                client = %client_addr,
+                target = %target,
+                protocol = "tcp",
+                error = %e,
+                "Failed to connect to TCP backend"
+            );
+            return;
+        }
+        Err(_) => {
+            warn!(
+                event = events::BACKEND_CONNECT_FAILED,
+                client = %client_addr,
+                target = %target,
+                protocol = "tcp",
+                error = "connect timed out",
+                "Timed out connecting to TCP backend"
+            );
+            return;
+        }
+    };
+    info!(
+        event = events::BACKEND_CONNECTED,
+        client = %client_addr,
+        target = %target,
+        protocol = "tcp",
+        "Connected to TCP backend"
+    );
+
+    balancer.start_request(&target);
+    let result = tokio::io::copy_bidirectional(&mut client, &mut backend).await;
+    balancer.end_request(&target);
+
+    match result {
+        Ok((sent, received)) =>
+            debug!(client = %client_addr, target = %target, sent, received, "TCP connection closed"),
+        Err(e) => debug!(client = %client_addr, target = %target, error = %e, "TCP connection ended with error"),
+    }
+}
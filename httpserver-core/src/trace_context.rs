@@ -0,0 +1,85 @@
+use axum::http::HeaderMap;
+
+/// W3C Trace Context header identifying the trace and the caller's span
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// W3C Trace Context header carrying vendor-specific trace state
+pub const TRACESTATE_HEADER: &str = "tracestate";
+
+/// Trace context of the gateway's span for a request, available as a request extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    /// 32 lowercase hex digits shared by every span in the trace
+    pub trace_id: String,
+    /// 16 lowercase hex digits identifying the gateway's span
+    pub span_id: String,
+    /// Span of the caller when the trace was continued from an inbound traceparent
+    pub parent_span_id: Option<String>,
+    /// Trace flags (bit 0 is "sampled")
+    pub flags: u8,
+}
+
+impl TraceContext {
+    /// Continue the trace named by a valid inbound traceparent, or start a new sampled one
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let inbound = headers
+            .get(TRACEPARENT_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_traceparent);
+
+        match inbound {
+            Some((trace_id, parent_span_id, flags)) =>
+                Self {
+                    trace_id,
+                    span_id: new_span_id(),
+                    parent_span_id: Some(parent_span_id),
+                    flags,
+                },
+            None =>
+                Self {
+                    trace_id: uuid::Uuid::new_v4().simple().to_string(),
+                    span_id: new_span_id(),
+                    parent_span_id: None,
+                    flags: 0x01,
+                },
+        }
+    }
+
+    /// traceparent value naming this span as the parent of downstream calls
+    pub fn traceparent(&self) -> String {
+        format!("00-{}-{}-{:02x}", self.trace_id, self.span_id, self.flags)
+    }
+}
+
+/// Trace ID, parent span ID and flags of a traceparent value, if it is well-formed
+///
+/// Versions above 00 may append fields, which are ignored; version ff and all-zero IDs are invalid.
+pub fn parse_traceparent(value: &str) -> Option<(String, String, u8)> {
+    let fields: Vec<&str> = value.trim().split('-').collect();
+    let [version, trace_id, parent_span_id, flags, rest @ ..] = fields.as_slice() else {
+        return None;
+    };
+    let valid =
+        is_hex_id(version, 2) &&
+        *version != "ff" &&
+        (rest.is_empty() || *version != "00") &&
+        is_hex_id(trace_id, 32) &&
+        is_hex_id(parent_span_id, 16) &&
+        is_hex_id(flags, 2);
+    if !valid {
+        return None;
+    }
+    Some((trace_id.to_string(), parent_span_id.to_string(), u8::from_str_radix(flags, 16).ok()?))
+}
+
+/// Random non-zero span ID
+fn new_span_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..16].to_string()
+}
+
+/// Lowercase hex of exactly `len` digits, not all zero (flags and version may be zero)
+fn is_hex_id(value: &str, len: usize) -> bool {
+    value.len() == len &&
+        value.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) &&
+        (len == 2 || value.bytes().any(|b| b != b'0'))
+}
@@ -0,0 +1,102 @@
+use axum::Router;
+use hyper::server::conn::http1;
+use hyper_util::rt::TokioIo;
+use hyper_util::service::TowerToHyperService;
+use std::io::{ Error, ErrorKind };
+use std::os::unix::fs::{ FileTypeExt, PermissionsExt };
+use std::path::{ Path, PathBuf };
+use tokio::net::UnixListener;
+use tokio::task::JoinHandle;
+use tracing::{ debug, error, info };
+
+/// Serves a router on a Unix domain socket; dropping it stops the listener and removes the socket file
+pub struct UnixSocketServer {
+    path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl UnixSocketServer {
+    /// Bind the socket, apply its permissions and start accepting connections
+    pub fn bind(path: &Path, mode: u32, app: Router) -> std::io::Result<Self> {
+        remove_stale_socket(path)?;
+
+        let listener = UnixListener::bind(path)?;
+        if let Err(e) = std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)) {
+            let _ = std::fs::remove_file(path);
+            return Err(e);
+        }
+
+        info!(path = %path.display(), "HTTP server listening on Unix socket {}", path.display());
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            task: tokio::spawn(serve(listener, app)),
+        })
+    }
+}
+
+impl Drop for UnixSocketServer {
+    fn drop(&mut self) {
+        self.task.abort();
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            debug!(path = %self.path.display(), error = %e, "Failed to remove Unix socket file");
+        }
+    }
+}
+
+/// Remove a socket file left behind by a previous run, refusing to touch live sockets or other files
+fn remove_stale_socket(path: &Path) -> std::io::Result<()> {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Ok(());
+        }
+        Err(e) => {
+            return Err(e);
+        }
+    };
+
+    if !metadata.file_type().is_socket() {
+        return Err(
+            Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display())
+            )
+        );
+    }
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Err(
+            Error::new(
+                ErrorKind::AddrInUse,
+                format!("Another server is listening on {}", path.display())
+            )
+        );
+    }
+
+    std::fs::remove_file(path)
+}
+
+/// Accept connections and serve each with HTTP/1.1, keeping upgrades available for WebSockets
+async fn serve(listener: UnixListener, app: Router) {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                error!(error = %e, "Failed to accept Unix socket connection");
+                continue;
+            }
+        };
+
+        let service = TowerToHyperService::new(app.clone());
+        tokio::spawn(async move {
+            if
+                let Err(e) = http1::Builder
+                    ::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .with_upgrades().await
+            {
+                debug!(error = %e, "Unix socket connection error");
+            }
+        });
+    }
+}
@@ -1,42 +1,133 @@
-use httpserver_config::{ Args, Config, create_config_health_router };
+use httpserver_config::{
+    Args,
+    Config,
+    ConfigError,
+    ListenerConfig,
+    LoggingConfig,
+    MaintenanceConfig,
+    StartupCheckConfig,
+    ProxyRoute,
+    SslConfig,
+    TunnelConfig,
+    create_config_health_router,
+    ErrorPages,
+    GeneratedErrorPage,
+};
 use httpserver_core::{
     Server,
+    ClientIp,
+    ServerListener,
+    TrustedProxies,
+    HeaderLimits,
+    TcpOptions,
+    ConcurrencyLimit,
     create_health_router,
+    create_probe_router,
+    create_error_response,
+    ReadinessCheck,
     initialize_logging,
     cleanup_old_logs,
     SslCertificateManager,
+    SslRedirectConfig,
+    AcmeManager,
+    AcmeChallengeStore,
+    create_acme_challenge_router,
+    error_page_middleware,
+    acme::ACME_RENEWAL_CHECK_INTERVAL,
 };
-use httpserver_static::{ StaticHandler, create_static_health_router };
-use httpserver_proxy::ProxyHandler;
+use httpserver_static::{ StaticHandler, SpaFallback, CacheControl, create_static_health_router };
+use httpserver_proxy::{ ProxyHandler, ClientOrigin, RouteCheck, startup_check };
 use httpserver_balancer::create_balancer_health_router;
-use httpserver_tunnel::{server::TunnelServer, TunnelClient};
+use httpserver_tunnel::{server::TunnelServer, TunnelClient, ConnectionState};
 use axum::{
     Router,
+    Json,
+    routing::get,
     extract::{ Request, ConnectInfo },
     response::{ IntoResponse },
     middleware::{ self, Next },
-    http::StatusCode,
+    http::{ StatusCode, HeaderValue, header },
 };
-use std::sync::Arc;
+use std::sync::{ Arc, RwLock };
 use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Proxy handler shared by the server and every `EngineHandle`; changes swap in a new handler
+/// so requests in flight finish against the routes they started with
+type SharedProxyHandler = Arc<RwLock<Arc<ProxyHandler>>>;
+
+/// Maintenance settings shared by the server and every `EngineHandle`
+type SharedMaintenance = Arc<RwLock<Maintenance>>;
+
+/// Maintenance settings with their page, read when the settings are applied rather than per request
+struct Maintenance {
+    config: MaintenanceConfig,
+    page: Option<axum::body::Bytes>,
+}
+
+impl Maintenance {
+    fn new(config: MaintenanceConfig) -> Self {
+        let page = config.page.as_ref().and_then(|path| {
+            match std::fs::read(path) {
+                Ok(page) => Some(page.into()),
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "Maintenance page unavailable; using default page");
+                    None
+                }
+            }
+        });
+        Self { config, page }
+    }
+}
+
+/// Raised by `EngineHandle::shutdown` to stop a running engine
+type SharedShutdown = Arc<watch::Sender<bool>>;
 
 /// The HTTP Server Engine - provides the core functionality as a library
 pub struct HttpServerEngine {
     config: Config,
     port: u16,
+    proxy: SharedProxyHandler,
+    maintenance: SharedMaintenance,
+    shutdown: SharedShutdown,
 }
 
 impl HttpServerEngine {
+    /// Start building an engine in code, without TOML or CLI arguments
+    pub fn builder() -> HttpServerEngineBuilder {
+        HttpServerEngineBuilder::new()
+    }
+
     /// Create a new engine instance with the given configuration and port
-    pub fn new(config: Config, port: u16) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn new(config: Config, port: u16) -> Result<Self, ConfigError> {
+        let proxy_handler = ProxyHandler::with_client_config(
+            config.proxy.clone(),
+            config.proxy_client.clone()
+        ).with_static_dir(config.static_config.directory.clone());
+        let maintenance = Arc::new(RwLock::new(Maintenance::new(config.server.maintenance.clone())));
         Ok(HttpServerEngine {
             config,
             port,
+            proxy: Arc::new(RwLock::new(Arc::new(proxy_handler))),
+            maintenance,
+            shutdown: Arc::new(watch::channel(false).0),
         })
     }
 
+    /// Handle for changing proxy routes and maintenance mode at runtime, or shutting the engine
+    /// down; usable before and after `start`
+    pub fn handle(&self) -> EngineHandle {
+        EngineHandle {
+            proxy: self.proxy.clone(),
+            maintenance: self.maintenance.clone(),
+            shutdown: self.shutdown.clone(),
+        }
+    }
+
     /// Create engine from command line arguments (preserves existing CLI behavior)
-    pub fn from_args(args: Args) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn from_args(args: Args) -> Result<Self, ConfigError> {
         let port = args.port;
 
         // Load configuration: if --config is specified, load only that file and bypass app_config.toml
@@ -55,13 +146,57 @@ impl HttpServerEngine {
             config
         };
 
+        let mut config = config;
+        if args.self_signed {
+            config.enable_self_signed_ssl();
+        }
+
         Self::new(config, port)
     }
 
-    /// Start the HTTP server engine
+    /// The composed router (proxy routes, static files and health endpoints) without serving it,
+    /// for embedding alongside your own handlers. Proxy routes stay shared with `handle()`.
+    /// Serve it with `into_make_service_with_connect_info::<SocketAddr>()` so proxied requests
+    /// see the client address; health checks, TLS and tunnels are only run by `start`.
+    pub async fn router(&self) -> Result<Router, Box<dyn std::error::Error>> {
+        let static_handler = create_static_handler(&self.config)?;
+        let router = create_router(
+            self.proxy.clone(),
+            self.maintenance.clone(),
+            static_handler,
+            &self.config,
+            Vec::new()
+        ).await?;
+        Ok(with_error_pages(router, ErrorPages::load(&self.config.server.error_pages)))
+    }
+
+    /// Routers for the additional `listeners`, as (port, router), for embedding like `router()`
+    pub async fn listener_routers(&self) -> Result<Vec<(u16, Router)>, Box<dyn std::error::Error>> {
+        let error_pages = ErrorPages::load(&self.config.server.error_pages);
+        let mut routers = Vec::with_capacity(self.config.listeners.len());
+        for listener in &self.config.listeners {
+            let (router, _) = create_listener_router(listener, &self.config)?;
+            routers.push((listener.port, with_error_pages(router, error_pages.clone())));
+        }
+        Ok(routers)
+    }
+
+    /// Probe the targets of the routes selected by `startup_check` once and log the results,
+    /// as `start` does when enabled. With `fail_fast` set, a route with no reachable target
+    /// is an error.
+    pub async fn startup_check(&self) -> Result<Vec<RouteCheck>, Box<dyn std::error::Error>> {
+        let routes = self.proxy.read().unwrap().routes().to_vec();
+        run_startup_check(&self.config.startup_check, &routes).await
+    }
+
+    /// Start the HTTP server engine. Returns once `EngineHandle::shutdown` has been called and
+    /// the requests in flight have finished, or when a listener fails.
     pub async fn start(self) -> Result<(), Box<dyn std::error::Error>> {
         let config = self.config;
         let port = self.port;
+        let proxy_handler = self.proxy;
+        let maintenance = self.maintenance;
+        let shutdown = self.shutdown;
 
         // Initialize logging system with app config
         initialize_logging(&config.logging)?;
@@ -73,17 +208,54 @@ impl HttpServerEngine {
 
         tracing::info!("Application starting");
 
-        // Create the static file handler
-        let static_handler = StaticHandler::new(config.static_config.directory.clone())?;
+        // Routes that forward to this server are rejected by the hop limit, but flag them early
+        for (index, target) in config.self_targeting_routes(port) {
+            tracing::warn!(
+                route = index,
+                target = %target,
+                "Proxy route targets this server and will loop until the hop limit is reached"
+            );
+        }
+
+        // Catch unreachable or mistyped backends before traffic arrives
+        let routes = proxy_handler.read().unwrap().routes().to_vec();
+        run_startup_check(&config.startup_check, &routes).await?;
 
-        // Create the proxy handler
-        let proxy_handler = ProxyHandler::new(config.proxy.clone());
+        // Create the static file handler
+        let static_handler = create_static_handler(&config)?;
 
         // Initialize SSL if configured
         let mut ssl_cert_manager = SslCertificateManager::new();
+        let mut acme_challenges = None;
+        // Held for the lifetime of the server; dropping it stops certificate hot reload
+        let mut _certificate_watcher = None;
         let ssl_server_config = if let Some(ssl_config) = &config.server.ssl {
             if ssl_config.enabled {
                 tracing::info!("SSL/TLS enabled, loading certificates");
+                let mut primary_domain = None;
+
+                // Obtain (or reuse cached) Let's Encrypt certificate if configured
+                if let Some(lets_encrypt) = ssl_config.lets_encrypt.as_ref().filter(|le| le.enabled) {
+                    let challenges = AcmeChallengeStore::new();
+                    let acme_manager = Arc::new(
+                        AcmeManager::new(lets_encrypt.clone(), challenges.clone())
+                    );
+                    let (cert_file, key_file) = acme_manager
+                        .ensure_certificate_standalone(port).await
+                        .map_err(|e| format!("Let's Encrypt certificate issuance failed: {}", e))?;
+                    let domain = acme_manager.domains().map_err(|e| e.to_string())?.remove(0);
+                    ssl_cert_manager.load_certificate_from_files(
+                        domain.clone(),
+                        &cert_file,
+                        &key_file,
+                        None
+                    )?;
+                    tracing::info!(domain = %domain, "Let's Encrypt certificate loaded");
+
+                    acme_manager.spawn_renewal_task(ACME_RENEWAL_CHECK_INTERVAL);
+                    acme_challenges = Some(challenges);
+                    primary_domain = Some(domain);
+                }
 
                 // Load wildcard certificate if configured
                 if let Some(wildcard_config) = &ssl_config.wildcard {
@@ -113,12 +285,34 @@ impl HttpServerEngine {
                     tracing::info!(domain = %domain, "Main certificate loaded");
                 }
 
+                // Fall back to a generated certificate for local development
+                if ssl_config.self_signed {
+                    if ssl_cert_manager.has_certificates() {
+                        tracing::warn!(
+                            "self_signed ignored because certificate files are configured"
+                        );
+                    } else {
+                        ssl_cert_manager.generate_self_signed_certificate()?;
+                    }
+                }
+
                 // Create SSL server config
                 if ssl_cert_manager.has_certificates() {
-                    let domain = ssl_cert_manager
-                        .get_wildcard_domain()
+                    let domain = primary_domain
+                        .or_else(|| ssl_cert_manager.get_wildcard_domain())
                         .unwrap_or_else(|| "localhost".to_string());
-                    Some(ssl_cert_manager.create_server_config(&domain)?)
+                    let server_config = ssl_cert_manager.create_server_config(&domain)?;
+
+                    // Pick up renewed or replaced certificate files without a restart
+                    match std::mem::take(&mut ssl_cert_manager).watch_for_changes() {
+                        Ok(watcher) => {
+                            _certificate_watcher = Some(watcher);
+                        }
+                        Err(e) => {
+                            tracing::warn!(error = %e, "Certificate hot reload disabled");
+                        }
+                    }
+                    Some(server_config)
                 } else {
                     tracing::warn!("SSL enabled but no certificates loaded");
                     None
@@ -130,15 +324,21 @@ impl HttpServerEngine {
             None
         };
 
+        // Readiness checks beyond proxy backend health (e.g. tunnel connectivity)
+        let mut readiness_checks: Vec<ReadinessCheck> = Vec::new();
+
+        // Admin endpoint reporting tunnel client status
+        let mut tunnel_status_router = None;
+
         // Initialize tunnel functionality if configured
-        let tunnel_handle = if config.tunnel.enabled {
+        let tunnel = if config.tunnel.enabled {
             if config.tunnel.server.enabled {
                 // Start tunnel server
                 tracing::info!("Tunnel server enabled, initializing");
-                let server = TunnelServer::new(config.tunnel.server.clone())?;
-                
-                let tunnel_handle = {
-                    let server = server;
+                let server = Arc::new(TunnelServer::new(config.tunnel.server.clone())?);
+
+                let task = {
+                    let server = server.clone();
                     tokio::spawn(async move {
                         if let Err(e) = server.start().await {
                             tracing::error!("Tunnel server error: {}", e);
@@ -152,53 +352,181 @@ impl HttpServerEngine {
                     "Tunnel server started"
                 );
                 
-                Some(tunnel_handle)
+                Some(RunningTunnel::Server { server, task })
             } else {
                 // Start tunnel client
                 tracing::info!("Tunnel client enabled, initializing");
                 
-                let mut client = TunnelClient::new(config.tunnel.clone(), port)?;
+                let client = TunnelClient::new(config.tunnel.clone(), port)?;
+
+                // Not ready until at least one tunnel is connected
+                let tunnel_status = client.subscribe_status();
+                readiness_checks.push(
+                    Arc::new(move || {
+                        let connected = tunnel_status
+                            .borrow()
+                            .iter()
+                            .any(|status| {
+                                matches!(
+                                    status.state,
+                                    ConnectionState::Connected | ConnectionState::Authenticated
+                                )
+                            });
+                        if connected {
+                            Vec::new()
+                        } else {
+                            vec!["tunnel not connected".to_string()]
+                        }
+                    })
+                );
+
+                tunnel_status_router = Some(client.status_router());
                 
-                let tunnel_handle = tokio::spawn(async move {
-                    if let Err(e) = client.start().await {
-                        tracing::error!("Tunnel client error: {}", e);
-                    } else {
-                        tracing::info!("Tunnel client started successfully");
-                        // Keep the client running
-                        tokio::signal::ctrl_c().await.expect("Failed to listen for ctrl+c");
-                        tracing::info!("Tunnel client shutting down");
-                    }
-                });
+                let client = Arc::new(tokio::sync::Mutex::new(client));
+                let task = {
+                    let client = client.clone();
+                    tokio::spawn(async move {
+                        let started = client.lock().await.start().await;
+                        if let Err(e) = started {
+                            tracing::error!("Tunnel client error: {}", e);
+                        } else {
+                            tracing::info!("Tunnel client started successfully");
+                            // Keep the client running until the engine stops it
+                            std::future::pending::<()>().await;
+                        }
+                    })
+                };
                 
                 tracing::info!("Tunnel client started");
-                Some(tunnel_handle)
+                Some(RunningTunnel::Client { client, task })
             }
         } else {
             tracing::info!("Tunnel functionality disabled");
             None
         };
 
+        // Health checks configured on proxy routes feed target health into their load balancers
+        let initial_proxy_handler = proxy_handler.read().unwrap().clone();
+        initial_proxy_handler.start_health_checks().await;
+
         // Create the router with proxy routes taking precedence over static files
-        let app = create_router(proxy_handler, static_handler, &config).await?;
+        let app = create_router(
+            proxy_handler.clone(),
+            maintenance,
+            static_handler,
+            &config,
+            readiness_checks
+        ).await?;
+
+        // Serve ACME HTTP-01 challenges for certificate renewals
+        let app = match acme_challenges {
+            Some(challenges) => app.merge(create_acme_challenge_router(challenges)),
+            None => app,
+        };
+
+        let app = match tunnel_status_router {
+            Some(router) => {
+                tracing::info!("Tunnel status available at /tunnel/status");
+                app.merge(router)
+            }
+            None => app,
+        };
 
         // Start the server with SSL support if configured
         let server = if let Some(ssl_config_arc) = ssl_server_config {
             let ssl_config = config.server.ssl.as_ref().unwrap();
-            Server::new_with_ssl(port, ssl_config_arc, ssl_config.https_port)
+            let server = Server::new_with_ssl(port, ssl_config_arc, ssl_config.https_port);
+            let redirect = SslRedirectConfig::from_config(ssl_config);
+            if redirect.enabled {
+                tracing::info!(https_port = redirect.https_port, "Redirecting HTTP requests to HTTPS");
+                server.with_https_redirect(redirect)
+            } else {
+                server
+            }
         } else {
             Server::new(port)
         };
+        // The server-wide timeout also bounds proxied requests
+        for route in &config.proxy {
+            if config.server.request_timeout > 0 && route.timeout > config.server.request_timeout {
+                tracing::warn!(
+                    route = %route.path,
+                    route_timeout = route.timeout,
+                    request_timeout = config.server.request_timeout,
+                    "Route timeout exceeds server request_timeout; requests will end with 504 first"
+                );
+            }
+        }
+        let server = server
+            .with_request_ids(config.logging.enable_request_ids)
+            .with_request_timeout(Duration::from_secs(config.server.request_timeout))
+            .with_max_request_size((config.server.max_request_size_mb as usize) * 1024 * 1024)
+            .with_header_limits(HeaderLimits::new(config.server.max_header_count, config.server.max_header_bytes))
+            .with_http2_cleartext(config.server.http2_cleartext)
+            .with_tcp_options(TcpOptions::new(config.server.tcp_nodelay, config.server.tcp_keepalive_secs))
+            .with_trusted_proxies(TrustedProxies::parse(&config.server.trusted_proxies)?)
+            .with_tcp_routes(config.tcp_routes.clone())
+            // Branded error pages apply to every generated error response
+            .with_error_pages(ErrorPages::load(&config.server.error_pages));
+        let limit = &config.server.concurrency_limit;
+        let server = if limit.max_requests > 0 {
+            server.with_concurrency_limit(
+                ConcurrencyLimit::new(
+                    limit.max_requests,
+                    limit.queue_size,
+                    Duration::from_millis(limit.queue_timeout_ms)
+                ).with_retry_after(limit.retry_after)
+            )
+        } else {
+            server
+        };
+        // Proxy routes may raise or lower the server-wide body limit; routes can change at runtime
+        let body_limit_routes = proxy_handler.clone();
+        let server = server.with_body_limit_override(
+            Arc::new(move |req: &Request| {
+                body_limit_routes
+                    .read()
+                    .unwrap()
+                    .find_request_route(req)
+                    .and_then(|route_match| route_match.route.max_request_body_bytes)
+                    .map(|limit| limit as usize)
+            })
+        );
+        let server = match &config.server.unix_socket {
+            Some(path) => server.with_unix_socket(path, config.server.unix_socket_mode),
+            None => server,
+        };
+        let mut server = server.with_graceful_shutdown(shutdown_requested(shutdown.subscribe()));
+
+        // Additional ports each serve their own routes behind the same middleware
+        for listener in &config.listeners {
+            if listener.port == port || Some(listener.port) == server.https_port {
+                return Err(format!("Listener port {} is already used by the main server", listener.port).into());
+            }
+            let (router, listener_proxy) = create_listener_router(listener, &config)?;
+            listener_proxy.start_health_checks().await;
+            tracing::info!(port = listener.port, route_count = listener.proxy.len(), "Additional listener configured");
+            server = server.with_listener(listener.port, router);
+        }
 
         // Start tunnel server and main server (on different ports if needed)
-        if let Some(tunnel_handle) = tunnel_handle {
+        if let Some(mut tunnel) = tunnel {
             // If tunnel server public_port conflicts with main server port, run only tunnel server
-            if config.tunnel.server.enabled && config.tunnel.server.public_port == port {
+            let result = if config.tunnel.server.enabled && config.tunnel.server.public_port == port {
                 tracing::info!("Tunnel server handles public traffic on port {} - skipping main HTTP server", port);
                 
-                // Wait for tunnel server to complete
-                if let Err(e) = tunnel_handle.await {
-                    tracing::error!("Tunnel task error: {}", e);
-                    return Err(e.into());
+                // Wait for tunnel server to complete, or stop it on shutdown
+                tokio::select! {
+                    result = tunnel.task() => {
+                        result.map_err(|e| {
+                            tracing::error!("Tunnel task error: {}", e);
+                            e.into()
+                        })
+                    }
+                    _ = shutdown_requested(shutdown.subscribe()) => {
+                        tracing::info!("Shutdown requested, stopping tunnel server");
+                        Ok(())
+                    }
                 }
             } else {
                 tracing::info!("Starting main HTTP server and tunnel concurrently");
@@ -209,17 +537,21 @@ impl HttpServerEngine {
                 tokio::select! {
                     result = main_server_future => {
                         tracing::info!("Main server completed");
-                        result?;
+                        result
                     }
-                    result = tunnel_handle => {
+                    result = tunnel.task() => {
                         tracing::info!("Tunnel task completed");
-                        if let Err(e) = result {
+                        result.map_err(|e| {
                             tracing::error!("Tunnel task error: {}", e);
-                            return Err(e.into());
-                        }
+                            e.into()
+                        })
                     }
                 }
-            }
+            };
+            // Only the message is kept across the await: the error types are not Send
+            let result = result.map_err(|e| e.to_string());
+            tunnel.stop().await;
+            result?;
         } else {
             // No tunnel, just start main server
             tracing::info!("Starting main HTTP server only");
@@ -240,27 +572,329 @@ impl HttpServerEngine {
     }
 }
 
+/// Assembles an engine's `Config` fluently for embedding the server in another application
+#[derive(Debug, Clone)]
+pub struct HttpServerEngineBuilder {
+    config: Config,
+    port: u16,
+}
+
+impl Default for HttpServerEngineBuilder {
+    fn default() -> Self {
+        Self {
+            config: Config::default(),
+            port: 8080,
+        }
+    }
+}
+
+impl HttpServerEngineBuilder {
+    /// Builder with the default configuration on port 8080
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from an existing configuration; later calls override its values
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Port to listen on
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Directory to serve static files from
+    pub fn static_dir(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.config.static_config.directory = directory.into();
+        self
+    }
+
+    /// Serve index.html for missing paths without a file extension
+    pub fn spa_fallback(mut self, enabled: bool) -> Self {
+        self.config.static_config.spa_fallback = enabled;
+        self
+    }
+
+    /// Add a proxy route; routes are matched in the order they are added
+    pub fn add_proxy_route(mut self, route: ProxyRoute) -> Self {
+        self.config.proxy.push(route);
+        self
+    }
+
+    /// Logging configuration
+    pub fn logging(mut self, logging: LoggingConfig) -> Self {
+        self.config.logging = logging;
+        self
+    }
+
+    /// SSL/TLS configuration
+    pub fn ssl(mut self, ssl: SslConfig) -> Self {
+        self.config.server.ssl = Some(ssl);
+        self
+    }
+
+    /// Serve HTTPS with a generated self-signed certificate (local development)
+    pub fn self_signed(mut self) -> Self {
+        self.config.enable_self_signed_ssl();
+        self
+    }
+
+    /// Tunnel client configuration
+    pub fn tunnel(mut self, tunnel: TunnelConfig) -> Self {
+        self.config.tunnel = tunnel;
+        self
+    }
+
+    /// Validate the assembled configuration and create the engine
+    pub fn build(self) -> Result<HttpServerEngine, ConfigError> {
+        self.config.validate()?;
+        HttpServerEngine::new(self.config, self.port)
+    }
+}
+
+/// Runtime control over an engine's proxy routes, maintenance mode and shutdown, complementing
+/// configuration files. Cloning is cheap; all clones act on the same engine.
+#[derive(Clone)]
+pub struct EngineHandle {
+    proxy: SharedProxyHandler,
+    maintenance: SharedMaintenance,
+    shutdown: SharedShutdown,
+}
+
+impl EngineHandle {
+    /// Add a proxy route after the existing ones, or replace the route with the same host and
+    /// path. The route is validated first; new requests match it as soon as this returns.
+    pub fn add_route(&self, route: ProxyRoute) -> Result<(), ConfigError> {
+        let mut current = self.proxy.write().unwrap();
+        let index = current
+            .routes()
+            .iter()
+            .position(|existing| existing.host == route.host && existing.path == route.path)
+            .unwrap_or(current.routes().len());
+        route.validate(index)?;
+        tracing::info!(path = %route.path, "Adding proxy route");
+
+        let mut handler = ProxyHandler::clone(&current);
+        handler.add_route(route);
+        *current = Arc::new(handler);
+        Ok(())
+    }
+
+    /// Remove the first proxy route with the given path pattern, returning it if it existed
+    pub fn remove_route(&self, path: &str) -> Option<ProxyRoute> {
+        let mut current = self.proxy.write().unwrap();
+        let mut handler = ProxyHandler::clone(&current);
+        let removed = handler.remove_route(path)?;
+        tracing::info!(path = %path, "Removed proxy route");
+
+        *current = Arc::new(handler);
+        Some(removed)
+    }
+
+    /// Proxy routes currently in effect, in matching order
+    pub fn list_routes(&self) -> Vec<ProxyRoute> {
+        self.proxy.read().unwrap().routes().to_vec()
+    }
+
+    /// Put the whole gateway into maintenance, or take it out; health endpoints and
+    /// `allow_paths` keep serving. Routes in maintenance on their own stay that way.
+    pub fn set_maintenance(&self, enabled: bool) {
+        tracing::info!(enabled, "Gateway maintenance mode changed");
+        self.maintenance.write().unwrap().config.enabled = enabled;
+    }
+
+    /// Put the proxy route with the given path pattern into maintenance, or take it out
+    pub fn set_route_maintenance(&self, path: &str, enabled: bool) {
+        tracing::info!(path = %path, enabled, "Route maintenance mode changed");
+        let maintenance = &mut self.maintenance.write().unwrap().config;
+        maintenance.routes.retain(|route| route != path);
+        if enabled {
+            maintenance.routes.push(path.to_string());
+        }
+    }
+
+    /// Replace every maintenance setting, e.g. with a reloaded `server.maintenance` section.
+    /// The maintenance page is read again, so edits to it take effect here.
+    pub fn configure_maintenance(&self, config: MaintenanceConfig) -> Result<(), ConfigError> {
+        config.validate()?;
+        tracing::info!(enabled = config.enabled, routes = ?config.routes, "Maintenance settings replaced");
+        let maintenance = Maintenance::new(config);
+        *self.maintenance.write().unwrap() = maintenance;
+        Ok(())
+    }
+
+    /// Maintenance settings currently in effect
+    pub fn maintenance(&self) -> MaintenanceConfig {
+        self.maintenance.read().unwrap().config.clone()
+    }
+
+    /// Stop accepting connections and let the requests in flight finish, after which `start`
+    /// returns. Called before `start`, the engine stops as soon as its listeners are bound.
+    pub fn shutdown(&self) {
+        tracing::info!("Engine shutdown requested");
+        self.shutdown.send_replace(true);
+    }
+
+    /// Whether `shutdown` has been called
+    pub fn is_shutdown_requested(&self) -> bool {
+        *self.shutdown.borrow()
+    }
+}
+
+/// Tunnel server or client started alongside the main server
+enum RunningTunnel {
+    Server {
+        server: Arc<TunnelServer>,
+        task: tokio::task::JoinHandle<()>,
+    },
+    Client {
+        client: Arc<tokio::sync::Mutex<TunnelClient>>,
+        task: tokio::task::JoinHandle<()>,
+    },
+}
+
+impl RunningTunnel {
+    /// The background task running the tunnel
+    fn task(&mut self) -> &mut tokio::task::JoinHandle<()> {
+        match self {
+            RunningTunnel::Server { task, .. } | RunningTunnel::Client { task, .. } => task,
+        }
+    }
+
+    /// Stop the tunnel and wait for its task to end. The tunnel server flushes subdomain
+    /// storage before its listeners are dropped; the client closes its connections.
+    async fn stop(self) {
+        match self {
+            RunningTunnel::Server { server, task } => {
+                if let Err(e) = server.shutdown().await {
+                    tracing::error!("Tunnel server shutdown error: {}", e);
+                }
+                task.abort();
+                let _ = task.await;
+            }
+            RunningTunnel::Client { client, task } => {
+                task.abort();
+                let _ = task.await;
+                if let Err(e) = client.lock().await.stop().await {
+                    tracing::error!("Tunnel client shutdown error: {}", e);
+                }
+            }
+        }
+        tracing::info!("Tunnel stopped");
+    }
+}
+
+/// Resolve once `EngineHandle::shutdown` has been called; the engine holds the sender for as
+/// long as it runs, so the channel does not close first
+async fn shutdown_requested(mut shutdown: watch::Receiver<bool>) {
+    if shutdown.wait_for(|requested| *requested).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Probe the targets of every route selected for the startup check and log each result
+async fn run_startup_check(
+    config: &StartupCheckConfig,
+    routes: &[ProxyRoute]
+) -> Result<Vec<RouteCheck>, Box<dyn std::error::Error>> {
+    let timeout = Duration::from_secs(config.timeout);
+    let mut checks = Vec::new();
+    for route in routes.iter().filter(|route| route.startup_check.unwrap_or(config.enabled)) {
+        let check = startup_check::check_route(route, timeout).await;
+        for target in &check.targets {
+            match &target.error {
+                None => tracing::info!(route = %check.route, target = %target.url, "Startup check: target reachable"),
+                Some(error) =>
+                    tracing::warn!(
+                        route = %check.route,
+                        target = %target.url,
+                        error = %error,
+                        "Startup check: target unreachable"
+                    ),
+            }
+        }
+        if !check.has_reachable_target() {
+            tracing::error!(route = %check.route, "Startup check: route has no reachable targets");
+        }
+        checks.push(check);
+    }
+
+    let unreachable: Vec<&str> = checks
+        .iter()
+        .filter(|check| !check.has_reachable_target())
+        .map(|check| check.route.as_str())
+        .collect();
+    if config.fail_fast && !unreachable.is_empty() {
+        return Err(format!("Startup check failed: no reachable targets for {}", unreachable.join(", ")).into());
+    }
+    Ok(checks)
+}
+
+/// Static file handler for the configured directory, SPA fallback, caching and mounts
+fn create_static_handler(config: &Config) -> Result<StaticHandler, Box<dyn std::error::Error>> {
+    let mut static_handler = StaticHandler::new(config.static_config.directory.clone())?
+        .with_spa_fallback(SpaFallback {
+            enabled: config.static_config.spa_fallback,
+            prefixes: config.static_config.spa_fallback_prefixes.clone(),
+        })
+        .with_cache_control(CacheControl {
+            default: config.static_config.cache_control.default.clone(),
+            rules: config.static_config.cache_control.rules
+                .iter()
+                .map(|rule| (rule.pattern.clone(), rule.value.clone()))
+                .collect(),
+        })
+        .with_basic_auth(
+            config.static_config.basic_auth
+                .as_ref()
+                .map(|basic_auth| basic_auth.credentials().to_vec())
+                .unwrap_or_default()
+        );
+    for mount in &config.static_config.mounts {
+        static_handler = static_handler.with_mount(
+            &mount.mount,
+            mount.directory.clone(),
+            SpaFallback {
+                enabled: mount.spa_fallback,
+                prefixes: Vec::new(),
+            }
+        )?;
+    }
+    Ok(static_handler)
+}
+
+/// Paths of the built-in health endpoints, relative to `server.health_path_prefix`
+const HEALTH_ENDPOINTS: [&str; 9] = [
+    "/health",
+    "/ping",
+    "/livez",
+    "/readyz",
+    "/config/health",
+    "/static/health",
+    "/balancer/health",
+    "/proxy/health",
+    "/proxy/stats",
+];
+
 /// Create the main router with proxy routes having priority over static files
 async fn create_router(
-    proxy_handler: ProxyHandler,
+    proxy_handler: SharedProxyHandler,
+    maintenance: SharedMaintenance,
     static_handler: StaticHandler,
-    _config: &Config
+    config: &Config,
+    mut readiness_checks: Vec<ReadinessCheck>
 ) -> Result<Router, Box<dyn std::error::Error>> {
     // Start with the static file router
     let static_router = static_handler.create_router();
 
-    // Add gateway health endpoints with highest priority
-    let health_router = create_health_router();
-
-    // Add service-specific health endpoints
-    let config_health_router = create_config_health_router();
-    let static_health_router = create_static_health_router();
-    let balancer_health_router = create_balancer_health_router();
-
-    // If proxy routes are configured, add proxy middleware with higher priority
-    if proxy_handler.has_routes() {
-        tracing::info!(route_count = proxy_handler.routes().len(), "Proxy routes configured");
-        for route in proxy_handler.routes() {
+    // Log the proxy routes configured at startup
+    let initial_routes = proxy_handler.read().unwrap().clone();
+    if initial_routes.has_routes() {
+        tracing::info!(route_count = initial_routes.routes().len(), "Proxy routes configured");
+        for route in initial_routes.routes() {
             let targets = route.get_targets();
             if targets.len() > 1 {
                 tracing::info!(
@@ -287,53 +921,312 @@ async fn create_router(
             }
         }
 
-        // Wrap proxy handler in Arc for sharing across requests
-        let proxy_handler = Arc::new(proxy_handler);
+        tracing::info!("Proxy forwarding active - routes will be processed before static files");
+    }
 
-        // Create router with proxy middleware that runs before static file serving
-        let app = static_router
-            .merge(health_router)
-            .merge(config_health_router)
-            .merge(static_health_router)
-            .merge(balancer_health_router)
-            .layer(middleware::from_fn_with_state(proxy_handler, proxy_middleware));
+    // Not ready while any route has no healthy backend
+    let readiness_handler = proxy_handler.clone();
+    readiness_checks.push(
+        Arc::new(move || {
+            readiness_handler
+                .read()
+                .unwrap()
+                .unready_routes()
+                .into_iter()
+                .map(|route| format!("route {} has no healthy backends", route))
+                .collect()
+        })
+    );
 
-        tracing::info!("Proxy forwarding active - routes will be processed before static files");
-        tracing::info!(
-            "Health endpoints available: /health, /ping, /config/health, /static/health, /balancer/health"
-        );
-        Ok(app)
+    // Gateway and service-specific health endpoints, optionally moved under a prefix
+    // so they do not shadow paths that static files or the default route should serve
+    let mut health_paths = Vec::new();
+    let app = if config.server.enable_health_endpoints {
+        let health_router = create_health_router()
+            .merge(create_probe_router(readiness_checks))
+            .merge(create_config_health_router())
+            .merge(create_static_health_router())
+            .merge(create_balancer_health_router())
+            .merge(create_proxy_health_router(proxy_handler.clone()));
+        let prefix = &config.server.health_path_prefix;
+        let endpoints: Vec<String> = HEALTH_ENDPOINTS.iter()
+            .map(|path| format!("{}{}", prefix, path))
+            .collect();
+        tracing::info!("Health endpoints available: {}", endpoints.join(", "));
+        health_paths = endpoints;
+        if prefix.is_empty() {
+            static_router.merge(health_router)
+        } else {
+            static_router.nest(prefix, health_router)
+        }
     } else {
-        // No proxy routes, just return static router with health endpoints
-        let app = static_router
-            .merge(health_router)
-            .merge(config_health_router)
-            .merge(static_health_router)
-            .merge(balancer_health_router);
-
-        tracing::info!(
-            "Health endpoints available: /health, /ping, /config/health, /static/health, /balancer/health"
-        );
-        Ok(app)
+        tracing::info!("Built-in health endpoints disabled");
+        static_router
+    };
+
+    // Proxy middleware always runs before static file serving since routes can be added at runtime
+
+    // The default route takes whatever static files could not serve
+    let app = match &config.default_route {
+        Some(default_route) => {
+            tracing::info!(target = %default_route.target, "Default route configured for unmatched requests");
+            let default_handler = Arc::new(
+                ProxyHandler::with_client_config(
+                    vec![default_route.to_proxy_route()],
+                    config.proxy_client.clone()
+                )
+            );
+            let default_state = DefaultRouteState {
+                handler: default_handler,
+                max_body_bytes: match config.server.max_request_size_mb {
+                    0 => usize::MAX,
+                    mb => (mb as usize) * 1024 * 1024,
+                },
+            };
+            app.layer(middleware::from_fn_with_state(default_state, default_route_middleware))
+        }
+        None => app,
+    };
+
+    let app = app.layer(middleware::from_fn_with_state(proxy_handler.clone(), proxy_middleware));
+
+    // Maintenance answers before proxy routes and static files are consulted
+    let maintenance_state = MaintenanceState {
+        maintenance,
+        proxy: proxy_handler,
+        health_paths: Arc::new(health_paths),
+    };
+    let app = app.layer(middleware::from_fn_with_state(maintenance_state, maintenance_middleware));
+
+    Ok(app)
+}
+
+/// Router for an additional listener: its own proxy routes in front of its own static files
+fn create_listener_router(
+    listener: &ListenerConfig,
+    config: &Config
+) -> Result<(Router, Arc<ProxyHandler>), Box<dyn std::error::Error>> {
+    // Route fallback files come from the listener's own static directory when it has one
+    let static_dir = listener.static_directory.as_ref().unwrap_or(&config.static_config.directory);
+    let proxy_handler = Arc::new(
+        ProxyHandler::with_client_config(listener.proxy.clone(), config.proxy_client.clone())
+            .with_static_dir(static_dir.clone())
+    );
+
+    // Static files use the main port's settings, without its mounts
+    let app = match &listener.static_directory {
+        Some(directory) => {
+            let mut static_config = config.clone();
+            static_config.static_config.directory = directory.clone();
+            static_config.static_config.mounts.clear();
+            create_static_handler(&static_config)?.create_router()
+        }
+        None => Router::new(),
+    };
+
+    let shared_proxy: SharedProxyHandler = Arc::new(RwLock::new(proxy_handler.clone()));
+    Ok((app.layer(middleware::from_fn_with_state(shared_proxy, proxy_middleware)), proxy_handler))
+}
+
+/// Per-route, per-target backend health including health check history, and per-route request stats
+fn create_proxy_health_router(proxy_handler: SharedProxyHandler) -> Router {
+    let stats_handler = proxy_handler.clone();
+    Router::new()
+        .route(
+            "/proxy/health",
+            get(move || {
+                let report = proxy_handler.read().unwrap().health_report();
+                async move { Json(report) }
+            })
+        )
+        .route(
+            "/proxy/stats",
+            get(move || {
+                let report = stats_handler.read().unwrap().stats_report();
+                async move { Json(report) }
+            })
+        )
+}
+
+/// What the maintenance middleware needs to decide which requests still get through
+#[derive(Clone)]
+struct MaintenanceState {
+    maintenance: SharedMaintenance,
+    proxy: SharedProxyHandler,
+    /// Health endpoint paths including the configured prefix; empty when they are disabled
+    health_paths: Arc<Vec<String>>,
+}
+
+/// Answer with 503 while the gateway, or the proxy route a request matches, is in maintenance
+async fn maintenance_middleware(
+    axum::extract::State(state): axum::extract::State<MaintenanceState>,
+    req: Request,
+    next: Next
+) -> axum::response::Response {
+    let (maintenance, page) = {
+        let maintenance = state.maintenance.read().unwrap();
+        (maintenance.config.clone(), maintenance.page.clone())
+    };
+    if !maintenance.is_active() {
+        return next.run(req).await;
+    }
+
+    let path = req.uri().path();
+    if state.health_paths.iter().any(|health_path| health_path == path) || maintenance.allows_path(path) {
+        return next.run(req).await;
+    }
+    let in_maintenance =
+        maintenance.enabled ||
+        state.proxy
+            .read()
+            .unwrap()
+            .find_request_route(&req)
+            .is_some_and(|route_match| maintenance.routes.contains(&route_match.route.path));
+    if !in_maintenance {
+        return next.run(req).await;
+    }
+
+    let mut response = match page {
+        Some(page) => (StatusCode::SERVICE_UNAVAILABLE, axum::response::Html(page)).into_response(),
+        None => {
+            // A custom 503 error page may stand in for the built-in maintenance page
+            let page = axum::response::Html(DEFAULT_MAINTENANCE_PAGE);
+            let mut response = (StatusCode::SERVICE_UNAVAILABLE, page).into_response();
+            response.extensions_mut().insert(GeneratedErrorPage);
+            response
+        }
+    };
+    response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(maintenance.retry_after));
+    response
+}
+
+/// Serve the configured error pages in place of the built-in ones on an embedded router
+fn with_error_pages(router: Router, pages: ErrorPages) -> Router {
+    if pages.is_empty() {
+        router
+    } else {
+        router.layer(middleware::from_fn_with_state(pages, error_page_middleware))
+    }
+}
+
+/// Shown while in maintenance when neither a maintenance page nor a custom 503 page is configured
+const DEFAULT_MAINTENANCE_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Down for maintenance</title>
+    <style>
+        body { font-family: Arial, sans-serif; margin: 40px; }
+    </style>
+</head>
+<body>
+    <h1>Down for maintenance</h1>
+    <p>We are making some improvements and will be back shortly.</p>
+</body>
+</html>"#;
+
+/// What the default route middleware needs to replay requests static serving rejected
+#[derive(Clone)]
+struct DefaultRouteState {
+    handler: Arc<ProxyHandler>,
+    /// Largest body kept for replay, from `server.max_request_size_mb`
+    max_body_bytes: usize,
+}
+
+/// Forward requests that static serving answered with 404 or 405 to the default route
+async fn default_route_middleware(
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    axum::extract::State(state): axum::extract::State<DefaultRouteState>,
+    req: Request,
+    next: Next
+) -> axum::response::Response {
+    // The body is kept so the request can be replayed to the default route
+    let (mut parts, body) = req.into_parts();
+    let body = match axum::body::to_bytes(body, state.max_body_bytes).await {
+        Ok(body) => body,
+        Err(e) if is_length_limit_error(&e) => {
+            return create_error_response(StatusCode::PAYLOAD_TOO_LARGE, "Request body too large");
+        }
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, format!("Failed to read request body: {}", e)).into_response();
+        }
+    };
+
+    // Static files only answer GET and HEAD, so other methods that match nothing come back as 405
+    let response = next.run(Request::from_parts(parts.clone(), body.clone().into())).await;
+    if !matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED) {
+        return response;
+    }
+
+    let addr = match connect_info {
+        Some(ConnectInfo(addr)) => addr,
+        None => SocketAddr::from(([127, 0, 0, 1], 0)),
+    };
+    let addr = match parts.extensions.get::<ClientIp>() {
+        Some(ClientIp(ip)) => SocketAddr::new(*ip, addr.port()),
+        None => addr,
+    };
+    let accept = parts.headers.get(axum::http::header::ACCEPT).cloned();
+    insert_client_origin(&mut parts.extensions);
+
+    match state.handler.handle_request(Request::from_parts(parts, body.into()), addr).await {
+        Some(Ok(response)) => response.into_response(),
+        Some(Err(proxy_error)) => proxy_error.into_negotiated_response(accept.as_ref()),
+        None => response,
+    }
+}
+
+/// Whether reading a body failed because it exceeded a length limit
+fn is_length_limit_error(error: &axum::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(error);
+    while let Some(error) = source {
+        if error.is::<http_body_util::LengthLimitError>() {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+/// Tell the proxy which scheme and port the client connected to, so backends behind TLS
+/// termination see the client-facing values in X-Forwarded-Proto and X-Forwarded-Port
+fn insert_client_origin(extensions: &mut axum::http::Extensions) {
+    if let Some(ServerListener { tls, port }) = extensions.get::<ServerListener>().copied() {
+        extensions.insert(ClientOrigin { https: tls, port });
     }
 }
 
 /// Middleware that handles proxy requests before they reach static file serving
 async fn proxy_middleware(
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
-    axum::extract::State(state): axum::extract::State<Arc<ProxyHandler>>,
-    req: Request,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    axum::extract::State(proxy_handler): axum::extract::State<SharedProxyHandler>,
+    mut req: Request,
     next: Next
 ) -> axum::response::Response {
+    // Snapshot the routes so the lock is not held while the request is forwarded
+    let state = proxy_handler.read().unwrap().clone();
+
+    // Unix socket connections have no peer address; treat them as local
+    let addr = match connect_info {
+        Some(ConnectInfo(addr)) => addr,
+        None => SocketAddr::from(([127, 0, 0, 1], 0)),
+    };
+    // Rate limiting and sticky sessions key on the real client behind trusted proxies
+    let addr = match req.extensions().get::<ClientIp>() {
+        Some(ClientIp(ip)) => SocketAddr::new(*ip, addr.port()),
+        None => addr,
+    };
+
     // Check if this request matches any proxy routes
-    let path = req.uri().path().to_string();
+    if let Some(_route_match) = state.find_request_route(&req) {
+        // Errors are rendered as JSON or HTML depending on what the client accepts
+        let accept = req.headers().get(axum::http::header::ACCEPT).cloned();
+        insert_client_origin(req.extensions_mut());
 
-    if let Some(_route_match) = state.find_route(&path) {
         // For now, WebSocket support is implemented but requires dedicated routing
         // This middleware handles HTTP requests only
         match state.handle_request(req, addr).await {
             Some(Ok(response)) => response.into_response(),
-            Some(Err(proxy_error)) => proxy_error.into_response(),
+            Some(Err(proxy_error)) => proxy_error.into_negotiated_response(accept.as_ref()),
             None => {
                 // This shouldn't happen since we found a route, but handle gracefully
                 (StatusCode::INTERNAL_SERVER_ERROR, "Proxy routing error").into_response()
@@ -0,0 +1,76 @@
+// Per-route access log lines in a custom format
+use axum::{ body::Body, http::{ header, HeaderMap, Method, Request, StatusCode, Uri, Version } };
+use httpserver_config::{ AccessLogSegment, access_log_segments };
+use std::{ net::{ IpAddr, SocketAddr }, time::Instant };
+
+/// Request details captured before forwarding, rendered once the response status is known
+#[derive(Debug, Clone)]
+pub struct AccessLogEntry {
+    remote_addr: IpAddr,
+    method: Method,
+    uri: Uri,
+    version: Version,
+    referer: Option<String>,
+    user_agent: Option<String>,
+    route: String,
+    started: Instant,
+}
+
+impl AccessLogEntry {
+    /// Capture a request on `route`; the request time is measured from here
+    pub fn new(req: &Request<Body>, client_ip: SocketAddr, route: &str) -> Self {
+        Self {
+            remote_addr: client_ip.ip(),
+            method: req.method().clone(),
+            uri: req.uri().clone(),
+            version: req.version(),
+            referer: header_value(req.headers(), header::REFERER),
+            user_agent: header_value(req.headers(), header::USER_AGENT),
+            route: route.to_string(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Substitute the entry into a format; unknown variables are left as written
+    pub fn render(&self, format: &str, status: StatusCode, body_bytes: Option<u64>) -> String {
+        let mut line = String::with_capacity(format.len() + 64);
+        for segment in access_log_segments(format) {
+            match segment {
+                AccessLogSegment::Literal(text) => line.push_str(text),
+                AccessLogSegment::Variable(name) => {
+                    match name {
+                        "remote_addr" => line.push_str(&self.remote_addr.to_string()),
+                        "time_local" =>
+                            line.push_str(&chrono::Local::now().format("%d/%b/%Y:%H:%M:%S %z").to_string()),
+                        "request" =>
+                            line.push_str(&format!("{} {} {:?}", self.method, self.request_uri(), self.version)),
+                        "request_method" => line.push_str(self.method.as_str()),
+                        "request_uri" => line.push_str(self.request_uri()),
+                        "status" => line.push_str(status.as_str()),
+                        "body_bytes_sent" =>
+                            line.push_str(&body_bytes.map_or_else(|| "-".to_string(), |bytes| bytes.to_string())),
+                        "request_time" =>
+                            line.push_str(&format!("{:.3}", self.started.elapsed().as_secs_f64())),
+                        "http_referer" => line.push_str(self.referer.as_deref().unwrap_or("-")),
+                        "http_user_agent" => line.push_str(self.user_agent.as_deref().unwrap_or("-")),
+                        "route" => line.push_str(&self.route),
+                        _ => {
+                            line.push('$');
+                            line.push_str(name);
+                        }
+                    }
+                }
+            }
+        }
+        line
+    }
+
+    /// Path and query as the client sent them
+    fn request_uri(&self) -> &str {
+        self.uri.path_and_query().map_or("/", |path_and_query| path_and_query.as_str())
+    }
+}
+
+fn header_value(headers: &HeaderMap, name: header::HeaderName) -> Option<String> {
+    headers.get(name).and_then(|value| value.to_str().ok()).map(str::to_string)
+}
@@ -0,0 +1,69 @@
+// Request body checksum and HMAC signature verification
+use axum::http::HeaderMap;
+use base64::{ Engine as _, engine::general_purpose };
+use httpserver_config::{ BodyIntegrityAlgorithm, BodyIntegrityConfig };
+use md5::{ Digest, Md5 };
+use ring::{ digest, hmac };
+
+use crate::ProxyError;
+
+/// Check the body against the checksum or signature header named by the route
+pub fn verify_body(config: &BodyIntegrityConfig, headers: &HeaderMap, body: &[u8]) -> Result<(), ProxyError> {
+    let header = config.header_name();
+    let Some(value) = headers.get(header) else {
+        return if config.required {
+            Err(rejection(config.algorithm, format!("missing {} header", header)))
+        } else {
+            Ok(())
+        };
+    };
+
+    let expected = value
+        .to_str()
+        .ok()
+        .and_then(|value| decode_digest(value, config.algorithm))
+        .ok_or_else(|| rejection(config.algorithm, format!("malformed {} header", header)))?;
+
+    let matches = match config.algorithm {
+        BodyIntegrityAlgorithm::Md5 => Md5::digest(body)[..] == expected[..],
+        BodyIntegrityAlgorithm::Sha256 => digest::digest(&digest::SHA256, body).as_ref() == &expected[..],
+        BodyIntegrityAlgorithm::HmacSha256 => {
+            let secret = config.secret.as_deref().unwrap_or_default();
+            let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+            hmac::verify(&key, body, &expected).is_ok()
+        }
+    };
+    if matches {
+        Ok(())
+    } else {
+        Err(rejection(config.algorithm, format!("{} does not match the request body", header)))
+    }
+}
+
+/// Checksum mismatches are bad requests; signature mismatches are unauthenticated
+fn rejection(algorithm: BodyIntegrityAlgorithm, reason: String) -> ProxyError {
+    match algorithm {
+        BodyIntegrityAlgorithm::HmacSha256 => ProxyError::InvalidSignature(reason),
+        BodyIntegrityAlgorithm::Md5 | BodyIntegrityAlgorithm::Sha256 => ProxyError::ChecksumMismatch(reason),
+    }
+}
+
+/// Digest bytes from a hex or base64 header value, optionally prefixed like "sha256="
+fn decode_digest(value: &str, algorithm: BodyIntegrityAlgorithm) -> Option<Vec<u8>> {
+    let (prefix, digest_len) = match algorithm {
+        BodyIntegrityAlgorithm::Md5 => ("md5=", 16),
+        BodyIntegrityAlgorithm::Sha256 | BodyIntegrityAlgorithm::HmacSha256 => ("sha256=", 32),
+    };
+    let value = value.trim();
+    let value = value
+        .get(..prefix.len())
+        .filter(|start| start.eq_ignore_ascii_case(prefix))
+        .map_or(value, |_| &value[prefix.len()..]);
+
+    let bytes = if value.len() == digest_len * 2 && value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        (0..digest_len).map(|i| u8::from_str_radix(&value[i * 2..i * 2 + 2], 16).ok()).collect::<Option<Vec<u8>>>()?
+    } else {
+        general_purpose::STANDARD.decode(value).ok()?
+    };
+    (bytes.len() == digest_len).then_some(bytes)
+}
@@ -0,0 +1,71 @@
+// Request/response body logging with redaction of sensitive fields
+use axum::http::HeaderMap;
+use httpserver_config::BodyLogConfig;
+use serde_json::Value;
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "***";
+
+/// Headers as "name: value" pairs, with configured header names redacted
+pub fn redact_headers(headers: &HeaderMap, config: &BodyLogConfig) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let redact = config.redact_headers
+                .iter()
+                .any(|redacted| redacted.eq_ignore_ascii_case(name.as_str()));
+            let value = if redact { REDACTED } else { value.to_str().unwrap_or("<binary>") };
+            format!("{}: {}", name, value)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Body text for logging: JSON bodies have configured fields redacted at any depth, and the
+/// result is cut to `max_bytes` with a marker saying how much was left out
+pub fn redact_body(body: &[u8], config: &BodyLogConfig) -> String {
+    let text = match serde_json::from_slice::<Value>(body) {
+        Ok(mut json) => {
+            redact_json(&mut json, &config.redact_fields);
+            json.to_string()
+        }
+        Err(_) => String::from_utf8_lossy(body).into_owned(),
+    };
+    truncate(text, config.max_bytes)
+}
+
+/// Replace the value of every field named in `fields`, descending into objects and arrays
+fn redact_json(json: &mut Value, fields: &[String]) {
+    match json {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if fields.iter().any(|field| field.eq_ignore_ascii_case(key)) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(value, fields);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact_json(item, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Cut `text` to at most `max_bytes` on a character boundary, marking what was dropped
+fn truncate(mut text: String, max_bytes: usize) -> String {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let dropped = text.len() - end;
+    text.truncate(end);
+    text.push_str(&format!("...[truncated {} bytes]", dropped));
+    text
+}
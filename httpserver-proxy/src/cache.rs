@@ -0,0 +1,328 @@
+use axum::{
+    body::{ Body, Bytes, HttpBody },
+    extract::Request,
+    http::{ HeaderMap, HeaderName, HeaderValue, Method, StatusCode, header },
+    response::Response,
+};
+use std::{ collections::HashMap, sync::Mutex, time::{ Duration, Instant, SystemTime } };
+
+pub use httpserver_config::CacheConfig;
+
+/// Header marking whether a response was served from the cache
+pub const X_CACHE: &str = "x-cache";
+
+/// A stored backend response
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    /// Request header values named by the response's Vary header
+    vary: Vec<(HeaderName, Option<HeaderValue>)>,
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    stored_at: Instant,
+    ttl: Duration,
+    /// Insertion order, used to evict the oldest entry when full
+    sequence: u64,
+}
+
+impl CachedResponse {
+    fn is_fresh(&self, now: Instant) -> bool {
+        now.duration_since(self.stored_at) < self.ttl
+    }
+
+    fn matches(&self, request_headers: &HeaderMap) -> bool {
+        self.vary.iter().all(|(name, value)| request_headers.get(name) == value.as_ref())
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    /// Variants stored per method and URL
+    variants: HashMap<String, Vec<CachedResponse>>,
+    len: usize,
+    next_sequence: u64,
+}
+
+/// In-process cache for proxied GET responses
+#[derive(Debug)]
+pub struct ResponseCache {
+    config: CacheConfig,
+    entries: Mutex<CacheEntries>,
+}
+
+impl ResponseCache {
+    /// Create an empty cache with the given settings
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(CacheEntries::default()),
+        }
+    }
+
+    /// Whether a request is eligible to be served from or stored in the cache
+    ///
+    /// Requests carrying credentials may get per-user responses and are never cached.
+    pub fn is_cacheable_request(req: &Request<Body>) -> bool {
+        req.method() == Method::GET &&
+            !req.headers().contains_key(header::AUTHORIZATION) &&
+            !req.headers().contains_key(header::COOKIE)
+    }
+
+    /// Cache key for a request: method, host and URL
+    pub fn key_for(req: &Request<Body>) -> String {
+        let host = req
+            .headers()
+            .get(header::HOST)
+            .and_then(|host| host.to_str().ok())
+            .or_else(|| req.uri().authority().map(|authority| authority.as_str()))
+            .unwrap_or_default();
+        format!("{} {} {}", req.method(), host.to_ascii_lowercase(), req.uri())
+    }
+
+    /// Return a fresh cached response for the request, marked as a hit
+    pub fn lookup(&self, req: &Request<Body>) -> Option<Response<Body>> {
+        if has_directive(req.headers(), "no-cache") {
+            return None;
+        }
+
+        let now = Instant::now();
+        let key = Self::key_for(req);
+        let mut entries = self.entries.lock().unwrap();
+
+        let variants = entries.variants.get_mut(&key)?;
+        let before = variants.len();
+        variants.retain(|cached| cached.is_fresh(now));
+        let removed = before - variants.len();
+        let cached = variants
+            .iter()
+            .find(|cached| cached.matches(req.headers()))
+            .cloned();
+        if variants.is_empty() {
+            entries.variants.remove(&key);
+        }
+        entries.len -= removed;
+
+        let cached = cached?;
+        let mut response = Response::builder()
+            .status(cached.status)
+            .body(Body::from(cached.body))
+            .ok()?;
+        *response.headers_mut() = cached.headers;
+        let age = now.duration_since(cached.stored_at).as_secs();
+        response.headers_mut().insert(header::AGE, HeaderValue::from(age));
+        response.headers_mut().insert(X_CACHE, HeaderValue::from_static("HIT"));
+        Some(response)
+    }
+
+    /// Store a backend response if it is cacheable, returning it marked as a miss
+    pub async fn store(
+        &self,
+        key: String,
+        request_headers: &HeaderMap,
+        response: Response<Body>
+    ) -> Response<Body> {
+        let Some(ttl) = self.freshness_lifetime(&response) else {
+            return mark_miss(response);
+        };
+        let Some(vary) = vary_values(response.headers(), request_headers) else {
+            return mark_miss(response);
+        };
+        // Only bodies with a known size within the limit are buffered
+        let size = response.body().size_hint().upper();
+        if size.is_none_or(|size| size > (self.config.max_body_bytes as u64)) {
+            return mark_miss(response);
+        }
+
+        let (parts, body) = response.into_parts();
+        let body = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to buffer response for caching");
+                return mark_miss(Response::from_parts(parts, Body::empty()));
+            }
+        };
+
+        let mut headers = parts.headers.clone();
+        headers.remove(X_CACHE);
+        headers.remove(header::AGE);
+        self.insert(key, CachedResponse {
+            vary,
+            status: parts.status,
+            headers,
+            body: body.clone(),
+            stored_at: Instant::now(),
+            ttl,
+            sequence: 0,
+        });
+
+        mark_miss(Response::from_parts(parts, Body::from(body)))
+    }
+
+    /// Number of responses currently stored
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len
+    }
+
+    /// Whether the cache holds no responses
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn insert(&self, key: String, mut cached: CachedResponse) {
+        if self.config.max_entries == 0 {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        cached.sequence = entries.next_sequence;
+        entries.next_sequence += 1;
+
+        // Replace any variant for the same Vary values
+        if let Some(variants) = entries.variants.get_mut(&key) {
+            let before = variants.len();
+            variants.retain(|existing| existing.vary != cached.vary);
+            let removed = before - variants.len();
+            entries.len -= removed;
+        }
+
+        if entries.len >= self.config.max_entries {
+            evict(&mut entries, now);
+        }
+
+        entries.variants.entry(key).or_default().push(cached);
+        entries.len += 1;
+    }
+
+    /// Freshness lifetime for a response, or None if it must not be stored
+    fn freshness_lifetime(&self, response: &Response<Body>) -> Option<Duration> {
+        if !is_cacheable_status(response.status()) {
+            return None;
+        }
+
+        let headers = response.headers();
+        // Cookies are per client and must never be replayed to another one
+        if has_directive(headers, "no-store") || headers.contains_key(header::SET_COOKIE) {
+            return None;
+        }
+        if !self.config.respect_origin_headers {
+            return Some(Duration::from_secs(self.config.default_ttl));
+        }
+        if has_directive(headers, "private") || has_directive(headers, "no-cache") {
+            return None;
+        }
+
+        let ttl = directive_seconds(headers, "s-maxage")
+            .or_else(|| directive_seconds(headers, "max-age"))
+            .or_else(|| expires_in(headers))
+            .unwrap_or(self.config.default_ttl);
+        (ttl > 0).then(|| Duration::from_secs(ttl))
+    }
+}
+
+/// Drop expired entries, then the oldest entry if the cache is still full
+fn evict(entries: &mut CacheEntries, now: Instant) {
+    let mut removed = 0;
+    entries.variants.retain(|_, variants| {
+        let before = variants.len();
+        variants.retain(|cached| cached.is_fresh(now));
+        removed += before - variants.len();
+        !variants.is_empty()
+    });
+    entries.len -= removed;
+    if removed > 0 {
+        return;
+    }
+
+    let oldest = entries.variants
+        .iter()
+        .flat_map(|(key, variants)| variants.iter().map(move |cached| (cached.sequence, key)))
+        .min()
+        .map(|(sequence, key)| (sequence, key.clone()));
+    if let Some((sequence, key)) = oldest {
+        if let Some(variants) = entries.variants.get_mut(&key) {
+            variants.retain(|cached| cached.sequence != sequence);
+            if variants.is_empty() {
+                entries.variants.remove(&key);
+            }
+        }
+        entries.len -= 1;
+    }
+}
+
+fn mark_miss(mut response: Response<Body>) -> Response<Body> {
+    response.headers_mut().insert(X_CACHE, HeaderValue::from_static("MISS"));
+    response
+}
+
+fn is_cacheable_status(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 200 | 203 | 204 | 300 | 301 | 404 | 405 | 410 | 414 | 501)
+}
+
+/// Request header values the response varies on; None for `Vary: *`
+fn vary_values(
+    response_headers: &HeaderMap,
+    request_headers: &HeaderMap
+) -> Option<Vec<(HeaderName, Option<HeaderValue>)>> {
+    let mut values = Vec::new();
+    for vary in response_headers.get_all(header::VARY) {
+        for name in vary.to_str().ok()?.split(',') {
+            let name = name.trim();
+            if name == "*" {
+                return None;
+            }
+            if let Ok(name) = HeaderName::from_bytes(name.as_bytes()) {
+                let value = request_headers.get(&name).cloned();
+                values.push((name, value));
+            }
+        }
+    }
+    Some(values)
+}
+
+/// Iterate Cache-Control directives as lowercase (name, value) pairs
+fn directives(headers: &HeaderMap) -> impl Iterator<Item = (String, Option<String>)> + '_ {
+    headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|directive| {
+            let mut parts = directive.trim().splitn(2, '=');
+            let name = parts.next().unwrap_or_default().to_ascii_lowercase();
+            let value = parts.next().map(|value| value.trim_matches('"').to_string());
+            (name, value)
+        })
+}
+
+fn has_directive(headers: &HeaderMap, name: &str) -> bool {
+    directives(headers).any(|(directive, _)| directive == name)
+}
+
+fn directive_seconds(headers: &HeaderMap, name: &str) -> Option<u64> {
+    directives(headers)
+        .find(|(directive, _)| directive == name)
+        .and_then(|(_, value)| value?.parse().ok())
+}
+
+/// Seconds until the Expires header, relative to the backend's Date when present
+fn expires_in(headers: &HeaderMap) -> Option<u64> {
+    let parse_date = |name: HeaderName| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| httpdate::parse_http_date(value).ok())
+    };
+
+    // An unparseable Expires means already expired
+    let expires = parse_date(header::EXPIRES).or_else(||
+        headers.contains_key(header::EXPIRES).then_some(SystemTime::UNIX_EPOCH)
+    )?;
+    let now = parse_date(header::DATE).unwrap_or_else(SystemTime::now);
+    Some(
+        expires
+            .duration_since(now)
+            .map(|remaining| remaining.as_secs())
+            .unwrap_or(0)
+    )
+}
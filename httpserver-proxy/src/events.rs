@@ -0,0 +1,11 @@
+// Stable values of the `event` field on backend connection log events, matching the names
+// httpserver-core uses for client connections and TCP routes
+
+/// The proxy opened a connection to a backend; fields: target, protocol ("h2" or "websocket")
+///
+/// HTTP/1.1 backends are reached through a pooled client that dials on its own, so only their
+/// failed connects are reported.
+pub const BACKEND_CONNECTED: &str = "backend_connected";
+
+/// Connecting to a backend failed; fields: target, protocol ("http", "h2" or "websocket"), error
+pub const BACKEND_CONNECT_FAILED: &str = "backend_connect_failed";
@@ -1,15 +1,35 @@
-// Health check integration with load balancer (HTTP and WebSocket)
-use std::sync::Arc;
-use httpserver_config::{ WebSocketHealthConfig, HttpHealthConfig };
+// Health check integration with load balancer (HTTP, WebSocket and TCP)
+use std::collections::{ BTreeMap, HashMap };
+use std::sync::{ Arc, Mutex };
+use std::time::{ SystemTime, UNIX_EPOCH };
+use serde_json::{ json, Value };
+use httpserver_config::{ WebSocketHealthConfig, HttpHealthConfig, TcpHealthConfig };
 use httpserver_balancer::LoadBalancer;
 use crate::websocket_health::WebSocketHealthMonitor;
 use crate::http_health::HttpHealthMonitor;
+use crate::tcp_health::TcpHealthMonitor;
+
+/// Check results recorded per target URL
+type HealthRecords = Arc<Mutex<HashMap<String, CheckRecord>>>;
+
+/// Latest results of the checks run against one target
+#[derive(Debug, Clone, Default)]
+struct CheckRecord {
+    /// Unix time in seconds of the most recent check
+    last_check: Option<u64>,
+    /// Failed checks since the last fully passing round
+    consecutive_failures: u32,
+    /// Latest result of each check type ("http", "websocket", "tcp")
+    checks: BTreeMap<String, bool>,
+}
 
 /// Health check integration manager
 pub struct HealthCheckIntegration {
     load_balancer: Arc<LoadBalancer>,
+    records: HealthRecords,
     websocket_health_monitor: Option<WebSocketHealthMonitor>,
     http_health_monitor: Option<HttpHealthMonitor>,
+    tcp_health_monitor: Option<TcpHealthMonitor>,
 }
 
 impl HealthCheckIntegration {
@@ -17,8 +37,25 @@ impl HealthCheckIntegration {
     pub fn new(load_balancer: Arc<LoadBalancer>) -> Self {
         Self {
             load_balancer,
+            records: Arc::new(Mutex::new(HashMap::new())),
             websocket_health_monitor: None,
             http_health_monitor: None,
+            tcp_health_monitor: None,
+        }
+    }
+
+    /// Record the result of a `check` ("http", "websocket" or "tcp") against a target. The target
+    /// stays in rotation only while the latest result of every check type passed.
+    pub fn record_check(&self, check: &str, target_url: &str, healthy: bool) {
+        record_check(&self.load_balancer, &self.records, check, target_url, healthy);
+    }
+
+    /// Callback for a monitor that records its results under `check`
+    fn recorder(&self, check: &'static str) -> impl Fn(&str, bool) + Send + Sync + 'static {
+        let load_balancer = self.load_balancer.clone();
+        let records = self.records.clone();
+        move |target_url, is_healthy| {
+            record_check(&load_balancer, &records, check, target_url, is_healthy);
         }
     }
 
@@ -42,10 +79,7 @@ impl HealthCheckIntegration {
         let monitor = HttpHealthMonitor::new(config, targets);
 
         // Start monitoring with callback to update load balancer health
-        let load_balancer_clone = self.load_balancer.clone();
-        let _handle = monitor.start_monitoring_with_callback(move |target_url, is_healthy| {
-            load_balancer_clone.set_target_health(target_url, is_healthy);
-        }).await;
+        let _handle = monitor.start_monitoring_with_callback(self.recorder("http")).await;
 
         self.http_health_monitor = Some(monitor);
 
@@ -77,10 +111,7 @@ impl HealthCheckIntegration {
         let monitor = WebSocketHealthMonitor::new(config, targets);
 
         // Start monitoring with callback to update load balancer health
-        let load_balancer_clone = self.load_balancer.clone();
-        let _handle = monitor.start_monitoring_with_callback(move |target_url, is_healthy| {
-            load_balancer_clone.set_target_health(target_url, is_healthy);
-        }).await;
+        let _handle = monitor.start_monitoring_with_callback(self.recorder("websocket")).await;
         self.websocket_health_monitor = Some(monitor);
 
         println!(
@@ -91,21 +122,108 @@ impl HealthCheckIntegration {
         Ok(())
     }
 
+    /// Start TCP connect health monitoring with load balancer integration
+    pub async fn start_tcp_health_monitoring(&mut self, config: TcpHealthConfig) -> Result<(), String> {
+        // Extract target URLs from the load balancer
+        let targets: Vec<String> = self.load_balancer
+            .targets()
+            .iter()
+            .map(|target| target.url.clone())
+            .collect();
+
+        if targets.is_empty() {
+            return Err("No targets available for TCP health monitoring".to_string());
+        }
+
+        // Create the health monitor
+        let monitor = TcpHealthMonitor::new(config, targets);
+
+        // Start monitoring with callback to update load balancer health
+        let _handle = monitor.start_monitoring_with_callback(self.recorder("tcp")).await;
+        self.tcp_health_monitor = Some(monitor);
+
+        println!(
+            "TCP health monitoring started for {} targets",
+            self.load_balancer.targets().len()
+        );
+
+        Ok(())
+    }
+
     /// Get the current health status of all targets
     pub fn get_health_summary(&self) -> HealthSummary {
         let total_targets = self.load_balancer.targets().len();
         let healthy_targets = self.load_balancer.healthy_targets_count();
 
+        let records = self.records.lock().unwrap();
+        let targets = self.load_balancer
+            .targets()
+            .iter()
+            .map(|target| {
+                let record = records.get(&target.url).cloned().unwrap_or_default();
+                TargetHealth {
+                    url: target.url.clone(),
+                    healthy: self.load_balancer.is_healthy(&target.url),
+                    last_check: record.last_check,
+                    consecutive_failures: record.consecutive_failures,
+                    checks: record.checks,
+                }
+            })
+            .collect();
+
         HealthSummary {
             total_targets,
             healthy_targets,
             unhealthy_targets: total_targets - healthy_targets,
             monitoring_enabled: self.websocket_health_monitor.is_some() ||
-            self.http_health_monitor.is_some(),
+            self.http_health_monitor.is_some() ||
+            self.tcp_health_monitor.is_some(),
+            targets,
         }
     }
 }
 
+/// Store a check result and update the target's health in the load balancer
+fn record_check(
+    load_balancer: &LoadBalancer,
+    records: &HealthRecords,
+    check: &str,
+    target_url: &str,
+    healthy: bool
+) {
+    let all_passing = {
+        let mut records = records.lock().unwrap();
+        let record = records.entry(target_url.to_string()).or_default();
+        record.last_check = Some(
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+        );
+        record.checks.insert(check.to_string(), healthy);
+
+        let all_passing = record.checks.values().all(|&passed| passed);
+        if all_passing {
+            record.consecutive_failures = 0;
+        } else if !healthy {
+            record.consecutive_failures += 1;
+        }
+        all_passing
+    };
+    load_balancer.set_target_health(target_url, all_passing);
+}
+
+/// Health of one target as reported by its checks
+#[derive(Debug, Clone)]
+pub struct TargetHealth {
+    pub url: String,
+    /// Whether the load balancer currently sends traffic to the target
+    pub healthy: bool,
+    /// Unix time in seconds of the most recent check; None until a check has run
+    pub last_check: Option<u64>,
+    /// Failed checks since the target last passed every check type
+    pub consecutive_failures: u32,
+    /// Latest result of each check type run against the target ("http", "websocket", "tcp")
+    pub checks: BTreeMap<String, bool>,
+}
+
 /// Summary of health check status
 #[derive(Debug, Clone)]
 pub struct HealthSummary {
@@ -113,6 +231,34 @@ pub struct HealthSummary {
     pub healthy_targets: usize,
     pub unhealthy_targets: usize,
     pub monitoring_enabled: bool,
+    /// Per-target status, in the load balancer's target order
+    pub targets: Vec<TargetHealth>,
+}
+
+impl HealthSummary {
+    /// JSON form served by the /proxy/health endpoint
+    pub fn to_json(&self) -> Value {
+        let targets: Vec<Value> = self.targets
+            .iter()
+            .map(|target| {
+                json!({
+                    "url": target.url,
+                    "status": if target.healthy { "healthy" } else { "unhealthy" },
+                    "last_check": target.last_check,
+                    "consecutive_failures": target.consecutive_failures,
+                    "checks": target.checks
+                })
+            })
+            .collect();
+
+        json!({
+            "total_targets": self.total_targets,
+            "healthy_targets": self.healthy_targets,
+            "unhealthy_targets": self.unhealthy_targets,
+            "monitoring_enabled": self.monitoring_enabled,
+            "targets": targets
+        })
+    }
 }
 
 impl std::fmt::Display for HealthSummary {
@@ -0,0 +1,220 @@
+use axum::{ body::Body, http::{ Request, Response, uri::Scheme } };
+use hyper::client::conn::http2::{ self, SendRequest };
+use hyper_util::rt::{ TokioExecutor, TokioIo };
+use std::{ collections::HashMap, sync::Arc, time::Duration };
+use tokio::{ io::{ AsyncRead, AsyncWrite }, net::TcpStream, sync::Mutex };
+use tokio_rustls::{
+    TlsConnector,
+    rustls::{
+        self,
+        client::{ ServerCertVerified, ServerCertVerifier },
+        ClientConfig,
+        OwnedTrustAnchor,
+        RootCertStore,
+        ServerName,
+    },
+};
+
+use crate::{ events, ProxyError };
+
+/// Per-request settings for HTTP/2 forwarding
+#[derive(Debug, Clone)]
+pub struct Http2Options {
+    /// Time allowed to establish a new backend connection
+    pub connect_timeout: Duration,
+    /// Time allowed for the backend to return response headers
+    pub response_timeout: Duration,
+    /// Verify backend certificates for https targets
+    pub verify_backend_ssl: bool,
+}
+
+/// Backend connection identity; requests to the same key share one multiplexed connection
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ConnectionKey {
+    tls: bool,
+    host: String,
+    port: u16,
+    verify_backend_ssl: bool,
+}
+
+/// Forwards requests over HTTP/2 without buffering, preserving streamed bodies and trailers
+pub struct Http2Forwarder {
+    /// Open connections to backends, reused until the backend closes them
+    connections: Mutex<HashMap<ConnectionKey, SendRequest<Body>>>,
+}
+
+impl Http2Forwarder {
+    /// Create a forwarder with no open connections
+    pub fn new() -> Self {
+        Self {
+            connections: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Send a request whose URI is the absolute backend URL
+    pub async fn send(
+        &self,
+        request: Request<Body>,
+        options: &Http2Options
+    ) -> Result<Response<Body>, ProxyError> {
+        let target = request.uri().to_string();
+        let key = connection_key(&request, options)?;
+        let mut sender = self.sender_for(&key, &target, options).await?;
+
+        let response = tokio::time
+            ::timeout(options.response_timeout, sender.send_request(request)).await
+            .map_err(|_| ProxyError::Timeout(options.response_timeout.as_secs()))?
+            .map_err(|e| {
+                if e.is_canceled() || e.is_closed() {
+                    ProxyError::ConnectionFailed(target.clone())
+                } else {
+                    ProxyError::RequestFailed(e.to_string())
+                }
+            })?;
+
+        Ok(response.map(Body::new))
+    }
+
+    /// Get a ready connection for the key, connecting if none is usable
+    async fn sender_for(
+        &self,
+        key: &ConnectionKey,
+        target: &str,
+        options: &Http2Options
+    ) -> Result<SendRequest<Body>, ProxyError> {
+        let mut connections = self.connections.lock().await;
+        if let Some(sender) = connections.get(key) {
+            if !sender.is_closed() {
+                return Ok(sender.clone());
+            }
+            connections.remove(key);
+        }
+
+        let sender = tokio::time
+            ::timeout(options.connect_timeout, connect(key)).await
+            .map_err(|_| {
+                tracing::warn!(
+                    event = events::BACKEND_CONNECT_FAILED,
+                    target = %target,
+                    protocol = "h2",
+                    error = "connect timed out",
+                    "HTTP/2 backend connection failed"
+                );
+                ProxyError::Timeout(options.connect_timeout.as_secs())
+            })?
+            .map_err(|e| {
+                tracing::warn!(
+                    event = events::BACKEND_CONNECT_FAILED,
+                    target = %target,
+                    protocol = "h2",
+                    error = %e,
+                    "HTTP/2 backend connection failed"
+                );
+                ProxyError::ConnectionFailed(target.to_string())
+            })?;
+        tracing::info!(event = events::BACKEND_CONNECTED, target = %target, protocol = "h2", "HTTP/2 backend connection opened");
+        connections.insert(key.clone(), sender.clone());
+        Ok(sender)
+    }
+}
+
+impl Default for Http2Forwarder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn connection_key(
+    request: &Request<Body>,
+    options: &Http2Options
+) -> Result<ConnectionKey, ProxyError> {
+    let uri = request.uri();
+    let tls = uri.scheme() == Some(&Scheme::HTTPS);
+    let host = uri
+        .host()
+        .ok_or_else(|| ProxyError::InvalidUrl(format!("Missing host in target URL '{}'", uri)))?;
+
+    Ok(ConnectionKey {
+        tls,
+        host: host.trim_start_matches('[').trim_end_matches(']').to_string(),
+        port: uri.port_u16().unwrap_or(if tls { 443 } else { 80 }),
+        verify_backend_ssl: options.verify_backend_ssl,
+    })
+}
+
+/// Open a connection and perform the HTTP/2 handshake (h2c for http, ALPN h2 for https)
+async fn connect(
+    key: &ConnectionKey
+) -> Result<SendRequest<Body>, Box<dyn std::error::Error + Send + Sync>> {
+    let stream = TcpStream::connect((key.host.as_str(), key.port)).await?;
+    stream.set_nodelay(true)?;
+
+    if !key.tls {
+        return handshake(stream).await;
+    }
+
+    let server_name = ServerName::try_from(key.host.as_str())?;
+    let connector = TlsConnector::from(tls_config(key.verify_backend_ssl));
+    let stream = connector.connect(server_name, stream).await?;
+    if stream.get_ref().1.alpn_protocol() != Some(b"h2") {
+        return Err(format!("Backend {}:{} did not negotiate HTTP/2", key.host, key.port).into());
+    }
+    handshake(stream).await
+}
+
+async fn handshake<T>(io: T) -> Result<SendRequest<Body>, Box<dyn std::error::Error + Send + Sync>>
+    where T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+    let (sender, connection) = http2::handshake(TokioExecutor::new(), TokioIo::new(io)).await?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            tracing::debug!(error = %e, "HTTP/2 backend connection closed");
+        }
+    });
+    Ok(sender)
+}
+
+/// TLS settings for HTTP/2 backends, advertising h2 via ALPN
+fn tls_config(verify_backend_ssl: bool) -> Arc<ClientConfig> {
+    let mut config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(webpki_root_store())
+        .with_no_client_auth();
+    if !verify_backend_ssl {
+        config.dangerous().set_certificate_verifier(Arc::new(AcceptAnyServerCertificate));
+    }
+    config.alpn_protocols = vec![b"h2".to_vec()];
+    Arc::new(config)
+}
+
+/// Publicly trusted roots used to verify backend certificates
+pub(crate) fn webpki_root_store() -> RootCertStore {
+    let mut root_store = RootCertStore::empty();
+    root_store.add_trust_anchors(
+        webpki_roots::TLS_SERVER_ROOTS.iter().map(|cert| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                cert.subject,
+                cert.spki,
+                cert.name_constraints
+            )
+        })
+    );
+    root_store
+}
+
+/// Certificate verifier used when verify_backend_ssl is disabled
+pub(crate) struct AcceptAnyServerCertificate;
+
+impl ServerCertVerifier for AcceptAnyServerCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
@@ -63,7 +63,22 @@ impl HttpHealthChecker {
         match self.client.get(health_url).send().await {
             Ok(response) => {
                 let status = response.status();
-                let is_healthy = status.is_success() || status == 200;
+                let mut is_healthy = status.is_success() || status == 200;
+
+                // Some backends always answer 200 and report liveness in the body
+                if is_healthy && self.checks_body() {
+                    is_healthy = match response.text().await {
+                        Ok(body) => self.body_matches(&body),
+                        Err(e) => {
+                            tracing::warn!(
+                                health_url = %health_url,
+                                error = %e,
+                                "Failed to read HTTP health check body"
+                            );
+                            false
+                        }
+                    };
+                }
 
                 if is_healthy {
                     tracing::info!(
@@ -91,6 +106,47 @@ impl HttpHealthChecker {
             }
         }
     }
+
+    /// Whether the configuration makes expectations about the response body
+    fn checks_body(&self) -> bool {
+        self.config.expected_body_contains.is_some() || self.config.expected_body_json.is_some()
+    }
+
+    /// Check the response body against the configured substring and JSON field expectations
+    fn body_matches(&self, body: &str) -> bool {
+        if let Some(expected) = &self.config.expected_body_contains {
+            if !body.contains(expected.as_str()) {
+                tracing::debug!(expected = %expected, "Health check body missing expected text");
+                return false;
+            }
+        }
+
+        if self.config.expected_body_json.is_some() {
+            let Some((field, expected)) = self.config.expected_json_field() else {
+                return false;
+            };
+            let Ok(json) = serde_json::from_str::<serde_json::Value>(body) else {
+                tracing::debug!("Health check body is not JSON");
+                return false;
+            };
+            let actual = field.split('.').try_fold(&json, |value, segment| value.get(segment));
+            let matches = match actual {
+                // Strings compare by content; other values as JSON (true, 1, null)
+                Some(serde_json::Value::String(actual)) => actual == expected,
+                Some(actual) =>
+                    serde_json
+                        ::from_str::<serde_json::Value>(expected)
+                        .is_ok_and(|expected| *actual == expected),
+                None => false,
+            };
+            if !matches {
+                tracing::debug!(field = %field, expected = %expected, "Health check JSON field mismatch");
+                return false;
+            }
+        }
+
+        true
+    }
 }
 
 /// Background HTTP health checker that runs periodic checks
@@ -0,0 +1,232 @@
+use axum::{
+    body::{ Body, Bytes },
+    extract::Request,
+    http::{ HeaderMap, HeaderValue, Method, StatusCode },
+    response::Response,
+};
+use std::{ collections::HashMap, sync::{ Arc, Mutex }, time::{ Duration, Instant } };
+use tokio::sync::watch;
+
+pub use httpserver_config::IdempotencyConfig;
+
+/// Header marking a response replayed for a duplicate idempotency key
+pub const IDEMPOTENT_REPLAYED: &str = "idempotent-replayed";
+
+/// The first response returned for a key
+#[derive(Debug)]
+struct StoredResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    stored_at: Instant,
+}
+
+impl StoredResponse {
+    fn to_response(&self) -> Response<Body> {
+        let mut response = Response::new(Body::from(self.body.clone()));
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers.clone();
+        response.headers_mut().insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+        response
+    }
+}
+
+#[derive(Debug)]
+enum Entry {
+    /// The first request with the key is still being forwarded; its response is published here
+    Pending { id: u64, response: watch::Receiver<Option<Arc<StoredResponse>>> },
+    Stored(Arc<StoredResponse>),
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    keys: HashMap<String, Entry>,
+    next_id: u64,
+}
+
+/// What to do with a request carrying an idempotency key
+pub enum Idempotent {
+    /// A previous request with the key already has a response
+    Replay(Response<Body>),
+    /// This is the first request with the key: forward it and complete the reservation
+    Forward(Reservation),
+}
+
+/// Per-route store of the first response returned for each idempotency key
+#[derive(Debug)]
+pub struct IdempotencyCache {
+    config: IdempotencyConfig,
+    entries: Mutex<Entries>,
+}
+
+impl IdempotencyCache {
+    /// Create an empty store with the given settings
+    pub fn new(config: IdempotencyConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Idempotency key of a request, if it is a POST carrying one
+    pub fn key_for(&self, req: &Request<Body>) -> Option<String> {
+        if req.method() != Method::POST {
+            return None;
+        }
+        let key = req.headers().get(self.config.header.as_str())?.to_str().ok()?;
+        (!key.is_empty()).then(|| key.to_string())
+    }
+
+    /// Replay the stored response for `key`, or reserve the key for this request. Duplicates
+    /// arriving while the first request is in flight wait for its response.
+    pub async fn begin(self: &Arc<Self>, key: &str) -> Idempotent {
+        loop {
+            let mut in_flight = {
+                let mut entries = self.entries.lock().unwrap();
+                match entries.keys.get(key) {
+                    Some(Entry::Stored(stored)) if self.is_fresh(stored) => {
+                        return Idempotent::Replay(stored.to_response());
+                    }
+                    Some(Entry::Pending { response, .. }) => response.clone(),
+                    _ => {
+                        let id = entries.next_id;
+                        entries.next_id += 1;
+                        let (sender, response) = watch::channel(None);
+                        entries.keys.insert(key.to_string(), Entry::Pending { id, response });
+                        return Idempotent::Forward(Reservation {
+                            cache: self.clone(),
+                            key: key.to_string(),
+                            id,
+                            sender: Some(sender),
+                        });
+                    }
+                }
+            };
+
+            // A first request that ends without a stored response releases the key; try again
+            let stored = in_flight
+                .wait_for(Option::is_some).await
+                .ok()
+                .and_then(|stored| stored.clone());
+            if let Some(stored) = stored {
+                return Idempotent::Replay(stored.to_response());
+            }
+        }
+    }
+
+    /// Number of responses currently stored
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap()
+            .keys.values()
+            .filter(|entry| matches!(entry, Entry::Stored(_)))
+            .count()
+    }
+
+    /// Whether the store holds no responses
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn is_fresh(&self, stored: &StoredResponse) -> bool {
+        stored.stored_at.elapsed() < Duration::from_secs(self.config.ttl)
+    }
+
+    fn insert(&self, key: &str, id: u64, stored: Arc<StoredResponse>) {
+        let mut entries = self.entries.lock().unwrap();
+        if !matches!(entries.keys.get(key), Some(Entry::Pending { id: pending, .. }) if *pending == id) {
+            return;
+        }
+        if self.config.max_entries == 0 {
+            entries.keys.remove(key);
+            return;
+        }
+
+        // Make room by dropping expired responses, then the oldest one
+        let stored_count = |entries: &Entries| {
+            entries.keys.values().filter(|entry| matches!(entry, Entry::Stored(_))).count()
+        };
+        if stored_count(&entries) >= self.config.max_entries {
+            entries.keys.retain(|_, entry| match entry {
+                Entry::Stored(stored) => self.is_fresh(stored),
+                Entry::Pending { .. } => true,
+            });
+        }
+        if stored_count(&entries) >= self.config.max_entries {
+            let oldest = entries.keys
+                .iter()
+                .filter_map(|(key, entry)| match entry {
+                    Entry::Stored(stored) => Some((stored.stored_at, key.clone())),
+                    Entry::Pending { .. } => None,
+                })
+                .min();
+            if let Some((_, oldest)) = oldest {
+                entries.keys.remove(&oldest);
+            }
+        }
+        entries.keys.insert(key.to_string(), Entry::Stored(stored));
+    }
+
+    fn release(&self, key: &str, id: u64) {
+        let mut entries = self.entries.lock().unwrap();
+        if matches!(entries.keys.get(key), Some(Entry::Pending { id: pending, .. }) if *pending == id) {
+            entries.keys.remove(key);
+        }
+    }
+}
+
+/// An idempotency key held by the request forwarding it. Dropping it without completing
+/// (the request failed or was cancelled) lets the next request with the key through.
+pub struct Reservation {
+    cache: Arc<IdempotencyCache>,
+    key: String,
+    id: u64,
+    sender: Option<watch::Sender<Option<Arc<StoredResponse>>>>,
+}
+
+impl Reservation {
+    /// Store the response for later duplicates and hand it on. Server errors are not stored,
+    /// so a retry after a backend failure is forwarded again.
+    ///
+    /// Streamed responses and bodies over `max_body_bytes` are not stored either: dropping the
+    /// reservation lets a retry through.
+    pub async fn complete(mut self, response: Response<Body>) -> Response<Body> {
+        if response.status().is_server_error() || crate::is_streamed(&response) {
+            return response;
+        }
+        let max_body_bytes = self.cache.config.max_body_bytes;
+        if axum::body::HttpBody::size_hint(response.body()).lower() > (max_body_bytes as u64) {
+            return response;
+        }
+
+        let (parts, body) = response.into_parts();
+        let body = match axum::body::to_bytes(body, max_body_bytes).await {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to buffer response for idempotent replay");
+                return Response::from_parts(parts, Body::empty());
+            }
+        };
+
+        let stored = Arc::new(StoredResponse {
+            status: parts.status,
+            headers: parts.headers.clone(),
+            body: body.clone(),
+            stored_at: Instant::now(),
+        });
+        self.cache.insert(&self.key, self.id, stored.clone());
+        if let Some(sender) = self.sender.take() {
+            let _ = sender.send(Some(stored));
+        }
+        Response::from_parts(parts, Body::from(body))
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        if self.sender.is_some() {
+            self.cache.release(&self.key, self.id);
+        }
+    }
+}
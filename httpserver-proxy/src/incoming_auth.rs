@@ -0,0 +1,209 @@
+// Authentication of incoming clients before their requests are forwarded
+use axum::http::{ request::Parts, HeaderMap, HeaderName, HeaderValue, StatusCode };
+use base64::{ Engine as _, engine::general_purpose::URL_SAFE_NO_PAD };
+use httpserver_config::{ ForwardAuthConfig, JwtAlgorithm, JwtAuthConfig };
+use ring::hmac;
+use serde_json::Value;
+use std::{ future::Future, pin::Pin, time::{ Duration, SystemTime, UNIX_EPOCH } };
+
+use crate::ProxyError;
+
+/// Future returned by [`IncomingAuth::authenticate`]
+pub type AuthFuture<'a> = Pin<Box<dyn Future<Output = Result<HeaderMap, ProxyError>> + Send + 'a>>;
+
+/// Check run on a client's request before a route forwards it
+pub trait IncomingAuth: Send + Sync {
+    /// Accept the request, returning headers to add to it before forwarding, or reject it
+    /// with the error the client is answered with
+    fn authenticate<'a>(&'a self, request: &'a Parts) -> AuthFuture<'a>;
+}
+
+/// Verifies HMAC-signed JWT bearer tokens
+pub struct JwtAuth {
+    config: JwtAuthConfig,
+    key: hmac::Key,
+}
+
+impl JwtAuth {
+    pub fn new(config: JwtAuthConfig) -> Self {
+        let algorithm = match config.algorithm {
+            JwtAlgorithm::HS256 => hmac::HMAC_SHA256,
+            JwtAlgorithm::HS384 => hmac::HMAC_SHA384,
+            JwtAlgorithm::HS512 => hmac::HMAC_SHA512,
+        };
+        let key = hmac::Key::new(algorithm, config.secret.as_bytes());
+        Self { config, key }
+    }
+
+    /// Claims of a token whose signature, algorithm, validity window, issuer and audience
+    /// are all acceptable
+    pub fn verify(&self, token: &str) -> Result<Value, String> {
+        let mut segments = token.split('.');
+        let (Some(encoded_header), Some(encoded_claims), Some(signature), None) = (
+            segments.next(),
+            segments.next(),
+            segments.next(),
+            segments.next(),
+        ) else {
+            return Err("malformed token".to_string());
+        };
+
+        // The algorithm is fixed by configuration; "none" or a different one is never accepted
+        let header: Value = decode_segment(encoded_header)?;
+        if header.get("alg").and_then(Value::as_str) != Some(self.config.algorithm.name()) {
+            return Err(format!("token must be signed with {}", self.config.algorithm.name()));
+        }
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| "malformed signature".to_string())?;
+        let signed = format!("{}.{}", encoded_header, encoded_claims);
+        hmac::verify(&self.key, signed.as_bytes(), &signature).map_err(|_| "invalid signature".to_string())?;
+
+        let claims: Value = decode_segment(encoded_claims)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        if let Some(exp) = claims.get("exp") {
+            let exp = exp.as_u64().ok_or("malformed exp claim")?;
+            if now >= exp.saturating_add(self.config.leeway) {
+                return Err("token expired".to_string());
+            }
+        }
+        if let Some(nbf) = claims.get("nbf") {
+            let nbf = nbf.as_u64().ok_or("malformed nbf claim")?;
+            if now.saturating_add(self.config.leeway) < nbf {
+                return Err("token not yet valid".to_string());
+            }
+        }
+        if let Some(issuer) = &self.config.issuer {
+            if claims.get("iss").and_then(Value::as_str) != Some(issuer.as_str()) {
+                return Err("unexpected issuer".to_string());
+            }
+        }
+        if let Some(audience) = &self.config.audience {
+            let matches = match claims.get("aud") {
+                Some(Value::String(aud)) => aud == audience,
+                Some(Value::Array(auds)) => auds.iter().any(|aud| aud.as_str() == Some(audience.as_str())),
+                _ => false,
+            };
+            if !matches {
+                return Err("unexpected audience".to_string());
+            }
+        }
+        Ok(claims)
+    }
+}
+
+impl IncomingAuth for JwtAuth {
+    fn authenticate<'a>(&'a self, request: &'a Parts) -> AuthFuture<'a> {
+        Box::pin(async move {
+            let token = request.headers
+                .get(axum::http::header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer ").or_else(|| value.strip_prefix("bearer ")))
+                .ok_or_else(|| ProxyError::Unauthorized("missing bearer token".to_string()))?;
+            self.verify(token.trim()).map_err(ProxyError::Unauthorized)?;
+            Ok(HeaderMap::new())
+        })
+    }
+}
+
+/// JSON value of a base64url token segment
+fn decode_segment(segment: &str) -> Result<Value, String> {
+    let bytes = URL_SAFE_NO_PAD.decode(segment).map_err(|_| "malformed token".to_string())?;
+    serde_json::from_slice(&bytes).map_err(|_| "malformed token".to_string())
+}
+
+/// Asks an external auth service whether to let each request through
+pub struct ForwardAuth {
+    config: ForwardAuthConfig,
+    client: reqwest::Client,
+}
+
+/// Request headers never passed on to the auth service
+const FORWARD_AUTH_SKIPPED_HEADERS: [&str; 5] = [
+    "connection",
+    "content-length",
+    "host",
+    "transfer-encoding",
+    "upgrade",
+];
+
+impl ForwardAuth {
+    pub fn new(config: ForwardAuthConfig) -> Result<Self, ProxyError> {
+        // Redirects are answers in their own right: following one to a login page would
+        // turn "not logged in" into a 200
+        let client = reqwest::Client
+            ::builder()
+            .timeout(Duration::from_secs(config.timeout))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| ProxyError::AuthUnavailable(format!("Failed to create forward auth client: {}", e)))?;
+        Ok(Self { config, client })
+    }
+}
+
+impl IncomingAuth for ForwardAuth {
+    fn authenticate<'a>(&'a self, request: &'a Parts) -> AuthFuture<'a> {
+        Box::pin(async move {
+            let mut check = self.client.get(&self.config.url);
+            for (name, value) in &request.headers {
+                if FORWARD_AUTH_SKIPPED_HEADERS.contains(&name.as_str()) {
+                    continue;
+                }
+                check = check.header(name.as_str(), value.as_bytes());
+            }
+            let uri = request.uri.path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
+            let check = check
+                .header("x-forwarded-method", request.method.as_str())
+                .header("x-forwarded-uri", uri);
+
+            let response = check.send().await.map_err(|e| {
+                tracing::warn!(url = %self.config.url, error = %e, "Forward auth request failed");
+                ProxyError::AuthUnavailable(e.to_string())
+            })?;
+            let status = response.status().as_u16();
+            if (400..500).contains(&status) {
+                return Err(ProxyError::AuthDenied(StatusCode::from_u16(status).unwrap_or(StatusCode::FORBIDDEN)));
+            }
+            if response.status().is_redirection() {
+                return Err(ProxyError::AuthDenied(StatusCode::UNAUTHORIZED));
+            }
+            if !response.status().is_success() {
+                // Anything but an allow or a client error means the service itself is failing
+                tracing::warn!(url = %self.config.url, status, "Forward auth service answered with an error");
+                return Err(ProxyError::AuthUnavailable(format!("auth service answered {}", status)));
+            }
+
+            let mut copied = HeaderMap::new();
+            for name in &self.config.copy_headers {
+                let Ok(name) = HeaderName::from_bytes(name.as_bytes()) else {
+                    continue;
+                };
+                for value in response.headers().get_all(name.as_str()) {
+                    if let Ok(value) = HeaderValue::from_bytes(value.as_bytes()) {
+                        copied.append(name.clone(), value);
+                    }
+                }
+            }
+            Ok(copied)
+        })
+    }
+}
+
+/// Stand-in for an auth check that could not be set up; rejects every request so the route
+/// never forwards unchecked
+pub(crate) struct FailedAuth(pub(crate) String);
+
+impl IncomingAuth for FailedAuth {
+    fn authenticate<'a>(&'a self, _request: &'a Parts) -> AuthFuture<'a> {
+        Box::pin(async move { Err(ProxyError::AuthUnavailable(self.0.clone())) })
+    }
+}
+
+/// Add the headers an auth check returned to the request being forwarded, replacing any
+/// the client sent under the same names
+pub(crate) fn apply_auth_headers(request: &mut Parts, headers: &HeaderMap) {
+    for name in headers.keys() {
+        request.headers.remove(name);
+        for value in headers.get_all(name) {
+            request.headers.append(name.clone(), value.clone());
+        }
+    }
+}
@@ -1,40 +1,93 @@
 use axum::{
     extract::Request,
     response::{ Response, IntoResponse },
-    http::{ StatusCode, HeaderMap, HeaderName, HeaderValue },
+    http::{ StatusCode, HeaderMap, HeaderName, HeaderValue, Method },
     body::Body,
 };
-use axum_tungstenite::{ Message, WebSocket, WebSocketUpgrade };
-use tokio_tungstenite::{ connect_async, tungstenite::protocol::Message as TungsteniteMessage };
-use futures_util::{ sink::SinkExt, stream::StreamExt };
-use std::{ net::SocketAddr, time::Duration, collections::HashMap };
-use tracing;
+use axum_tungstenite::{ WebSocket, WebSocketUpgrade };
+use std::{ net::SocketAddr, time::Duration, collections::{ HashMap, HashSet }, path::PathBuf, sync::{ Arc, RwLock } };
 use uuid::Uuid;
 
 // Re-export types from dependencies
 pub use httpserver_config::{
     ProxyRoute,
+    ProxyClientConfig,
+    RouteClientConfig,
+    RouteSslConfig,
     Target,
     LoadBalancingStrategy,
     WebSocketHealthConfig,
+    WebSocketLimitsConfig,
     HttpHealthConfig,
+    TcpHealthConfig,
+    CanaryConfig,
+    MirrorConfig,
+    AccessLogHandled,
 };
 pub use httpserver_balancer::LoadBalancer;
 
 // Health check modules
 pub mod websocket_health;
 pub mod http_health;
+pub mod tcp_health;
 pub mod health_integration;
 
 // Middleware module for request/response processing
 pub mod middleware;
 
+// Rate limit counter storage (in-memory or Redis)
+pub mod rate_limit;
+
+// WebSocket relaying with per-connection message limits
+pub mod websocket;
+
+// Response caching for proxied GET requests
+pub mod cache;
+
+// HTTP/2 forwarding with streamed bodies (gRPC)
+pub mod http2;
+
+// Request/response body logging with redaction
+pub mod body_log;
+
+// Per-route request counters and latency histograms
+pub mod stats;
+
+// Request body checksum and signature verification
+pub mod body_integrity;
+
+// Backend connection lifecycle event names
+pub mod events;
+
+// Per-route access log lines in a custom format
+pub mod access_log;
+
+// Backend connectivity self-test run once at startup
+pub mod startup_check;
+
+// Idempotency key deduplication for proxied POSTs
+pub mod idempotency;
+
+// Authentication of incoming clients (JWT, forward auth or custom checks)
+pub mod incoming_auth;
+
 pub use websocket_health::{ WebSocketHealthChecker, WebSocketHealthMonitor };
 pub use http_health::{ HttpHealthChecker, HttpHealthMonitor };
-pub use health_integration::{ HealthCheckIntegration, HealthSummary };
+pub use tcp_health::{ TcpHealthChecker, TcpHealthMonitor };
+pub use health_integration::{ HealthCheckIntegration, HealthSummary, TargetHealth };
 pub use middleware::{ MiddlewareProcessor, MiddlewareError };
+pub use cache::ResponseCache;
+pub use rate_limit::{ RateLimitStore, RateLimitFuture, MemoryRateLimitStore, RedisRateLimitStore };
+pub use http2::{ Http2Forwarder, Http2Options };
+pub use stats::{ RouteStats, RequestTimer };
+pub use websocket::{ MessageLimiter, relay_websocket, connect_backend, websocket_config };
+pub use access_log::AccessLogEntry;
+pub use startup_check::{ RouteCheck, TargetCheck };
+pub use idempotency::{ IdempotencyCache, Idempotent, Reservation };
+pub use incoming_auth::{ IncomingAuth, AuthFuture, JwtAuth, ForwardAuth };
 
 /// Route matching engine for reverse proxy
+#[derive(Clone)]
 pub struct RouteMatch {
     /// The matched route configuration
     pub route: ProxyRoute,
@@ -44,11 +97,15 @@ pub struct RouteMatch {
     pub is_wildcard: bool,
 }
 
-/// Proxy route matcher that handles path-based routing with wildcards
+/// Proxy route matcher that handles path-based routing with wildcards and regexes
+///
+/// Exact paths take precedence over prefix wildcards, which take precedence over regex
+/// routes; within each kind the first route in config order wins.
+#[derive(Clone)]
 pub struct RouteMatcher {
-    /// Ordered list of routes (order matters for precedence)
+    /// Routes in config order
     routes: Vec<ProxyRoute>,
-    /// Compiled route patterns for fast matching
+    /// Compiled route patterns for fast matching, in precedence order
     patterns: Vec<RoutePattern>,
 }
 
@@ -61,6 +118,19 @@ struct RoutePattern {
     parts: Vec<PatternPart>,
     /// Whether this pattern ends with a wildcard
     has_wildcard: bool,
+    /// Anchored path regex of a `match_regex` route (replaces `parts`)
+    path_regex: Option<regex::Regex>,
+    /// Host the route is limited to
+    host: Option<HostPattern>,
+}
+
+/// Host constraint of a route pattern
+#[derive(Debug, Clone)]
+enum HostPattern {
+    /// Case-insensitive host name
+    Literal(String),
+    /// Anchored regex matched against the lowercased host
+    Regex(regex::Regex),
 }
 
 /// Parts of a route pattern
@@ -75,10 +145,12 @@ enum PatternPart {
 impl RouteMatcher {
     /// Create a new route matcher with the given proxy routes
     pub fn new(routes: Vec<ProxyRoute>) -> Self {
-        let patterns = routes
+        let mut patterns: Vec<RoutePattern> = routes
             .iter()
-            .map(|route| Self::compile_pattern(route))
+            .filter_map(Self::compile_pattern)
             .collect();
+        // Stable sort keeps config order within each kind of pattern
+        patterns.sort_by_key(|pattern| pattern.precedence());
 
         Self {
             routes,
@@ -86,9 +158,17 @@ impl RouteMatcher {
         }
     }
 
-    /// Find the first matching route for the given path
+    /// Find the first matching route for the given path, skipping routes limited to a host
     /// Returns None if no route matches
     pub fn find_match(&self, path: &str) -> Option<RouteMatch> {
+        self.find_host_match(None, path)
+    }
+
+    /// Find the first matching route for the given Host header value and path
+    /// Returns None if no route matches
+    pub fn find_host_match(&self, host: Option<&str>, path: &str) -> Option<RouteMatch> {
+        let host = host.map(|host| strip_host_port(host).to_ascii_lowercase());
+
         // Normalize the path (ensure it starts with /)
         let normalized_path = if path.starts_with('/') {
             path.to_string()
@@ -96,8 +176,11 @@ impl RouteMatcher {
             format!("/{}", path)
         };
 
-        // Try each pattern in order (first match wins)
+        // Try each pattern in precedence order (first match wins)
         for pattern in &self.patterns {
+            if !Self::match_host(pattern, host.as_deref()) {
+                continue;
+            }
             if let Some(stripped_path) = Self::match_pattern(pattern, &normalized_path) {
                 return Some(RouteMatch {
                     route: pattern.route.clone(),
@@ -111,11 +194,33 @@ impl RouteMatcher {
     }
 
     /// Compile a route pattern into matchable parts
-    fn compile_pattern(route: &ProxyRoute) -> RoutePattern {
+    /// Returns None for a regex route that does not compile (config validation rejects these)
+    fn compile_pattern(route: &ProxyRoute) -> Option<RoutePattern> {
         let path = &route.path;
         let mut parts = Vec::new();
         let mut has_wildcard = false;
 
+        if route.match_regex {
+            let compiled = httpserver_config::anchored_regex(path).and_then(|path_regex| {
+                let host = route.host.as_deref().map(httpserver_config::anchored_regex).transpose()?;
+                Ok((path_regex, host))
+            });
+            return match compiled {
+                Ok((path_regex, host)) =>
+                    Some(RoutePattern {
+                        route: route.clone(),
+                        parts,
+                        has_wildcard,
+                        path_regex: Some(path_regex),
+                        host: host.map(HostPattern::Regex),
+                    }),
+                Err(e) => {
+                    tracing::warn!(route = %path, error = %e, "Skipping proxy route with an invalid regex");
+                    None
+                }
+            };
+        }
+
         // Handle the pattern parsing
         if path.ends_with("/*") {
             // Pattern like "/api/*" - match prefix and capture rest
@@ -134,16 +239,33 @@ impl RouteMatcher {
             parts.push(PatternPart::Literal(path.to_string()));
         }
 
-        RoutePattern {
+        Some(RoutePattern {
             route: route.clone(),
             parts,
             has_wildcard,
+            path_regex: None,
+            host: route.host.as_ref().map(|host| HostPattern::Literal(host.to_ascii_lowercase())),
+        })
+    }
+
+    /// Whether the request host satisfies the pattern's host constraint
+    fn match_host(pattern: &RoutePattern, host: Option<&str>) -> bool {
+        match (&pattern.host, host) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(HostPattern::Literal(expected)), Some(host)) => expected == host,
+            (Some(HostPattern::Regex(regex)), Some(host)) => regex.is_match(host),
         }
     }
 
     /// Match a compiled pattern against a path
     /// Returns the stripped path if matched, None if no match
     fn match_pattern(pattern: &RoutePattern, path: &str) -> Option<String> {
+        // Regex routes forward the full path
+        if let Some(regex) = &pattern.path_regex {
+            return regex.is_match(path).then(|| path.to_string());
+        }
+
         let mut path_pos = 0;
         let path_bytes = path.as_bytes();
 
@@ -185,41 +307,181 @@ impl RouteMatcher {
     }
 }
 
-/// Proxy handler that manages route matching and request forwarding
+impl RoutePattern {
+    /// Rank of this kind of pattern: exact paths, then prefix wildcards, then regexes
+    fn precedence(&self) -> u8 {
+        if self.path_regex.is_some() {
+            2
+        } else if self.has_wildcard {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+/// Host name of a Host header value without its port ("[::1]:8080" becomes "[::1]")
+fn strip_host_port(host: &str) -> &str {
+    if host.starts_with('[') {
+        return host.split_once(']').map_or(host, |(address, _)| &host[..address.len() + 1]);
+    }
+    host.split_once(':').map_or(host, |(name, _)| name)
+}
+
+/// Identity of a route in the per-route state maps: its host and path pattern. Routes sharing
+/// a path on different hosts keep separate balancers, caches and counters.
+type RouteKey = (Option<String>, String);
+
+fn route_key(route: &ProxyRoute) -> RouteKey {
+    (route.host.clone(), route.path.clone())
+}
+
+/// Proxy handler that manages route matching and request forwarding.
+/// Clones share backend clients, middleware state and each route's balancer and cache.
+#[derive(Clone)]
 pub struct ProxyHandler {
     /// Route matcher for finding proxy targets
     route_matcher: RouteMatcher,
     /// HTTP forwarder for handling requests
-    forwarder: ProxyForwarder,
-    /// Load balancers per route (keyed by route path)
-    load_balancers: HashMap<String, LoadBalancer>,
+    forwarder: Arc<ProxyForwarder>,
+    /// Load balancers per route
+    load_balancers: HashMap<RouteKey, Arc<LoadBalancer>>,
     /// Middleware processor for request/response transformations
-    middleware_processor: MiddlewareProcessor,
+    middleware_processor: Arc<MiddlewareProcessor>,
+    /// Response caches per route
+    response_caches: HashMap<RouteKey, Arc<ResponseCache>>,
+    /// Stored responses per idempotency key, per route
+    idempotency_caches: HashMap<RouteKey, Arc<IdempotencyCache>>,
+    /// Client authentication checks per route, run in order
+    incoming_auth: HashMap<RouteKey, Vec<Arc<dyn IncomingAuth>>>,
+    /// Running health checks per route
+    health_checks: Arc<RwLock<HashMap<RouteKey, HealthCheckIntegration>>>,
+    /// Request counters per route
+    route_stats: HashMap<RouteKey, Arc<RouteStats>>,
+    /// Directory route `fallback_file`s are read from
+    static_dir: Option<PathBuf>,
+    /// Permits for mirrored requests in flight, shared by every route
+    mirror_slots: Arc<tokio::sync::Semaphore>,
 }
 
 impl ProxyHandler {
     /// Create a new proxy handler with the given routes
     pub fn new(routes: Vec<ProxyRoute>) -> Self {
-        let route_matcher = RouteMatcher::new(routes.clone());
-        let forwarder = ProxyForwarder::new();
-        let middleware_processor = MiddlewareProcessor::new();
+        Self::with_client_config(routes, ProxyClientConfig::default())
+    }
 
-        // Create load balancers for each route
-        let mut load_balancers = HashMap::new();
-        for route in routes {
-            let targets = route.get_targets();
-            if !targets.is_empty() {
-                let balancer = LoadBalancer::new(targets, route.strategy.clone());
-                load_balancers.insert(route.path.clone(), balancer);
+    /// Create a new proxy handler whose backend clients use the given settings
+    pub fn with_client_config(routes: Vec<ProxyRoute>, client_config: ProxyClientConfig) -> Self {
+        let mut handler = Self {
+            route_matcher: RouteMatcher::new(Vec::new()),
+            forwarder: Arc::new(ProxyForwarder::with_client_config(client_config)),
+            load_balancers: HashMap::new(),
+            middleware_processor: Arc::new(MiddlewareProcessor::new()),
+            response_caches: HashMap::new(),
+            idempotency_caches: HashMap::new(),
+            incoming_auth: HashMap::new(),
+            health_checks: Arc::new(RwLock::new(HashMap::new())),
+            route_stats: HashMap::new(),
+            static_dir: None,
+            mirror_slots: Arc::new(tokio::sync::Semaphore::new(MAX_MIRRORS_IN_FLIGHT)),
+        };
+
+        // Create load balancers and response caches for each route
+        for route in &routes {
+            handler.create_route_state(route);
+        }
+        handler.route_matcher = RouteMatcher::new(routes);
+        handler
+    }
+
+    /// Read route `fallback_file`s from this directory, normally the static file directory
+    pub fn with_static_dir(mut self, directory: PathBuf) -> Self {
+        self.static_dir = Some(directory);
+        self
+    }
+
+    /// Authenticate clients of the route with the given path pattern using custom logic, after
+    /// any `jwt_auth` or `forward_auth` it is configured with. Replacing the route through
+    /// `add_route` drops the check.
+    pub fn with_incoming_auth(mut self, route_path: &str, auth: Arc<dyn IncomingAuth>) -> Self {
+        let key = self.path_key(route_path);
+        self.incoming_auth.entry(key).or_default().push(auth);
+        self
+    }
+
+    /// Create the load balancer, response cache and counters a route needs
+    fn create_route_state(&mut self, route: &ProxyRoute) {
+        let key = route_key(route);
+        self.route_stats.insert(key.clone(), Arc::new(RouteStats::new()));
+        let targets = route.get_targets();
+        if !targets.is_empty() {
+            let balancer = LoadBalancer::new(targets, route.strategy.clone());
+            self.load_balancers.insert(key.clone(), Arc::new(balancer));
+        }
+        if let Some(cache_config) = &route.cache {
+            self.response_caches.insert(key.clone(), Arc::new(ResponseCache::new(cache_config.clone())));
+        }
+        if let Some(idempotency) = &route.idempotency {
+            self.idempotency_caches.insert(key.clone(), Arc::new(IdempotencyCache::new(idempotency.clone())));
+        }
+        let mut auth: Vec<Arc<dyn IncomingAuth>> = Vec::new();
+        if let Some(jwt_auth) = &route.jwt_auth {
+            auth.push(Arc::new(JwtAuth::new(jwt_auth.clone())));
+        }
+        if let Some(forward_auth) = &route.forward_auth {
+            match ForwardAuth::new(forward_auth.clone()) {
+                Ok(forward_auth) => auth.push(Arc::new(forward_auth)),
+                Err(e) => {
+                    tracing::error!(route = %route.path, error = %e, "Forward auth unavailable, rejecting requests");
+                    auth.push(Arc::new(incoming_auth::FailedAuth(e.to_string())));
+                }
             }
         }
+        if !auth.is_empty() {
+            self.incoming_auth.insert(key, auth);
+        }
+    }
 
-        Self {
-            route_matcher,
-            forwarder,
-            load_balancers,
-            middleware_processor,
+    /// Add a route after the existing ones, or replace the route with the same host and path in
+    /// place. Other routes keep their balancer, cache and counter state.
+    pub fn add_route(&mut self, route: ProxyRoute) {
+        let mut routes = self.route_matcher.routes().to_vec();
+        self.remove_route_state(&route_key(&route));
+        self.create_route_state(&route);
+
+        match routes.iter_mut().find(|existing| route_key(existing) == route_key(&route)) {
+            Some(existing) => {
+                *existing = route;
+            }
+            None => routes.push(route),
         }
+        self.route_matcher = RouteMatcher::new(routes);
+    }
+
+    /// Remove the first route with the given path pattern, returning it if it existed
+    pub fn remove_route(&mut self, path: &str) -> Option<ProxyRoute> {
+        let mut routes = self.route_matcher.routes().to_vec();
+        let index = routes.iter().position(|route| route.path == path)?;
+        let removed = routes.remove(index);
+        self.remove_route_state(&route_key(&removed));
+        self.route_matcher = RouteMatcher::new(routes);
+        Some(removed)
+    }
+
+    /// Drop the balancer, caches, checks and counters of a route
+    fn remove_route_state(&mut self, key: &RouteKey) {
+        self.load_balancers.remove(key);
+        self.response_caches.remove(key);
+        self.idempotency_caches.remove(key);
+        self.incoming_auth.remove(key);
+        self.route_stats.remove(key);
+        self.health_checks.write().unwrap().remove(key);
+    }
+
+    /// State key of the first route with the given path pattern, in config order
+    fn path_key(&self, path: &str) -> RouteKey {
+        let host = self.routes().iter().find(|route| route.path == path).and_then(|route| route.host.clone());
+        (host, path.to_string())
     }
 
     /// Find a matching route for the given path
@@ -227,6 +489,16 @@ impl ProxyHandler {
         self.route_matcher.find_match(path)
     }
 
+    /// Find a matching route for a request's host and path
+    pub fn find_request_route<B>(&self, req: &Request<B>) -> Option<RouteMatch> {
+        let host = req
+            .headers()
+            .get(axum::http::header::HOST)
+            .and_then(|value| value.to_str().ok())
+            .or_else(|| req.uri().host());
+        self.route_matcher.find_host_match(host, req.uri().path())
+    }
+
     /// Check if any proxy routes are configured
     pub fn has_routes(&self) -> bool {
         !self.route_matcher.routes().is_empty()
@@ -237,149 +509,430 @@ impl ProxyHandler {
         self.route_matcher.routes()
     }
 
+    /// Load balancer of the first route with the given path pattern
+    pub fn load_balancer(&self, route_path: &str) -> Option<&LoadBalancer> {
+        self.load_balancers.get(&self.path_key(route_path)).map(|balancer| balancer.as_ref())
+    }
+
+    /// Start the HTTP, WebSocket and TCP health checks configured on each route; their results
+    /// mark targets healthy or unhealthy in the route's load balancer
+    pub async fn start_health_checks(&self) {
+        for route in self.routes() {
+            let Some(load_balancer) = self.load_balancers.get(&route_key(route)) else {
+                continue;
+            };
+            let mut integration = HealthCheckIntegration::new(load_balancer.clone());
+
+            let mut results = Vec::new();
+            if let Some(config) = &route.http_health {
+                results.push(integration.start_http_health_monitoring(config.clone()).await);
+            }
+            if let Some(config) = &route.websocket_health {
+                results.push(integration.start_websocket_health_monitoring(config.clone()).await);
+            }
+            if let Some(config) = &route.tcp_health {
+                results.push(integration.start_tcp_health_monitoring(config.clone()).await);
+            }
+            for error in results.into_iter().filter_map(Result::err) {
+                tracing::warn!(route = %route.path, error = %error, "Failed to start health checks");
+            }
+            self.health_checks.write().unwrap().insert(route_key(route), integration);
+        }
+    }
+
+    /// Health of every route's targets as (host, path, summary), including check history where
+    /// health checks run
+    pub fn health_summaries(&self) -> Vec<(Option<String>, String, HealthSummary)> {
+        let health_checks = self.health_checks.read().unwrap();
+        self.routes()
+            .iter()
+            .filter_map(|route| {
+                let key = route_key(route);
+                let summary = match health_checks.get(&key) {
+                    Some(integration) => integration.get_health_summary(),
+                    None => {
+                        let load_balancer = self.load_balancers.get(&key)?;
+                        HealthCheckIntegration::new(load_balancer.clone()).get_health_summary()
+                    }
+                };
+                Some((key.0, key.1, summary))
+            })
+            .collect()
+    }
+
+    /// JSON report of `health_summaries`, served at /proxy/health
+    pub fn health_report(&self) -> serde_json::Value {
+        let routes: Vec<serde_json::Value> = self
+            .health_summaries()
+            .into_iter()
+            .map(|(host, path, summary)| {
+                let mut route = summary.to_json();
+                route["path"] = serde_json::Value::String(path);
+                if let Some(host) = host {
+                    route["host"] = serde_json::Value::String(host);
+                }
+                route
+            })
+            .collect();
+        serde_json::json!({ "routes": routes })
+    }
+
+    /// Request counters of the first route with the given path pattern
+    pub fn route_stats(&self, route_path: &str) -> Option<&RouteStats> {
+        self.route_stats.get(&self.path_key(route_path)).map(|stats| stats.as_ref())
+    }
+
+    /// JSON report of every route's request counters and latency, served at /proxy/stats
+    pub fn stats_report(&self) -> serde_json::Value {
+        let routes: Vec<serde_json::Value> = self
+            .routes()
+            .iter()
+            .filter_map(|route| {
+                let mut stats = self.route_stats.get(&route_key(route))?.to_json();
+                stats["path"] = serde_json::Value::String(route.path.clone());
+                if let Some(host) = &route.host {
+                    stats["host"] = serde_json::Value::String(host.clone());
+                }
+                Some(stats)
+            })
+            .collect();
+        serde_json::json!({ "routes": routes })
+    }
+
+    /// Routes that currently have no healthy backend targets, as their path pattern prefixed
+    /// with the host for routes limited to one
+    pub fn unready_routes(&self) -> Vec<String> {
+        let mut routes: Vec<String> = self.load_balancers
+            .iter()
+            .filter(|(_, balancer)| balancer.healthy_targets_count() == 0)
+            .map(|((host, path), _)| format!("{}{}", host.as_deref().unwrap_or_default(), path))
+            .collect();
+        routes.sort();
+        routes
+    }
+
     /// Handle a proxy request (find route and forward if matched)
     pub async fn handle_request(
         &self,
-        mut req: Request<Body>,
+        req: Request<Body>,
         client_ip: SocketAddr
     ) -> Option<Result<Response<Body>, ProxyError>> {
-        // Extract path for route matching
-        let path = req.uri().path();
-
         // Find matching route
-        if let Some(route_match) = self.find_route(path) {
-            // Apply request middleware if configured
-            if let Some(middleware_config) = &route_match.route.middleware {
-                match
-                    self.middleware_processor.process_request(
-                        req,
-                        &client_ip,
-                        middleware_config
-                    ).await
-                {
-                    Ok(processed_req) => {
-                        req = processed_req;
-                    }
-                    Err(middleware_error) => {
-                        self.middleware_processor.finish_connection(&client_ip);
-                        return Some(Err(ProxyError::RequestFailed(middleware_error.to_string())));
+        let route_match = self.find_request_route(&req)?;
+
+        // Routes with their own access log settings write (or skip) the line themselves
+        let route = &route_match.route;
+        let access_log = route.access_log_format
+            .as_ref()
+            .filter(|_| route.access_log)
+            .map(|format| (format, AccessLogEntry::new(&req, client_ip, &route.path)));
+
+        // Count the request against its route for /proxy/stats
+        let timer = self.route_stats.get(&route_key(route)).map(|stats| stats.start_request());
+        let mut result = self.handle_route_request(req, &route_match, client_ip).await;
+        if let Some(timer) = timer {
+            let is_error = match &result {
+                Ok(response) => response.status().is_server_error(),
+                Err(_) => true,
+            };
+            timer.finish(is_error);
+        }
+
+        if let Some((format, entry)) = access_log {
+            let (status, body_bytes) = match &result {
+                Ok(response) => (response.status(), declared_length(response.headers())),
+                Err(error) => (error.status_and_message().0, None),
+            };
+            tracing::info!(target: "access_log", route = %route.path, "{}", entry.render(format, status, body_bytes));
+        }
+        if !route.access_log || route.access_log_format.is_some() {
+            if let Ok(response) = &mut result {
+                response.extensions_mut().insert(AccessLogHandled);
+            }
+        }
+        Some(result)
+    }
+
+    /// Apply a matched route's middleware, cache and CORS handling around forwarding
+    async fn handle_route_request(
+        &self,
+        mut req: Request<Body>,
+        route_match: &RouteMatch,
+        client_ip: SocketAddr
+    ) -> Result<Response<Body>, ProxyError> {
+        // A request that has already been forwarded too many times is looping back to us
+        let hops = request_hops(req.headers());
+        if hops >= self.forwarder.client_config.max_hops {
+            tracing::warn!(
+                route = %route_match.route.path,
+                hops = hops,
+                "Rejecting proxied request that exceeded the hop limit"
+            );
+            return Err(ProxyError::LoopDetected(hops));
+        }
+
+        // Many backends don't handle preflights, so routes with CORS settings can answer them here
+        let cors = route_match.route.middleware
+            .as_ref()
+            .and_then(|middleware| middleware.cors.as_ref());
+        if let Some(cors_config) = cors {
+            if let Some(response) = self.middleware_processor.preflight_response(&req, cors_config) {
+                return Ok(response);
+            }
+        }
+        // Unauthenticated clients are turned away before any other work is done for them
+        if let Some(checks) = self.incoming_auth.get(&route_key(&route_match.route)) {
+            let (mut parts, body) = req.into_parts();
+            for check in checks {
+                match check.authenticate(&parts).await {
+                    Ok(headers) => incoming_auth::apply_auth_headers(&mut parts, &headers),
+                    Err(e) => {
+                        tracing::info!(route = %route_match.route.path, error = %e, "Rejected unauthenticated request");
+                        return Err(e);
                     }
                 }
             }
+            req = Request::from_parts(parts, body);
+        }
 
-            // Get the load balancer for this route
-            if let Some(load_balancer) = self.load_balancers.get(&route_match.route.path) {
-                // Check if this is a WebSocket request that should use sticky sessions
-                let is_websocket = Self::is_websocket_request(&req);
-                let use_sticky_sessions = is_websocket && route_match.route.sticky_sessions;
-
-                // Select target using appropriate strategy
-                let target = if use_sticky_sessions {
-                    // Use client IP as identifier for sticky sessions
-                    let client_id = client_ip.ip().to_string();
-                    load_balancer.select_target_sticky(&client_id)
-                } else {
-                    load_balancer.select_target()
-                };
+        let origin = req.headers().get(axum::http::header::ORIGIN).cloned();
+        let with_cors = |response: Response<Body>| match cors {
+            Some(cors_config) =>
+                self.middleware_processor.apply_cors_headers(response, origin.as_ref(), cors_config),
+            None => response,
+        };
 
-                if let Some(target) = target {
-                    // Track request start
-                    load_balancer.start_request(&target.url);
-
-                    // Forward the request
-                    let result = self.forwarder.forward_request(
-                        req,
-                        &route_match,
-                        &target.url,
-                        client_ip
-                    ).await;
-
-                    // Track request end
-                    load_balancer.end_request(&target.url);
-
-                    // Apply response middleware if configured and request was successful
-                    let final_result = match result {
-                        Ok(response) => {
-                            if let Some(middleware_config) = &route_match.route.middleware {
-                                match
-                                    self.middleware_processor.process_response(
-                                        response,
-                                        middleware_config
-                                    ).await
-                                {
-                                    Ok(processed_response) => Ok(processed_response),
-                                    Err(middleware_error) =>
-                                        Err(
-                                            ProxyError::ResponseError(middleware_error.to_string())
-                                        ),
-                                }
-                            } else {
-                                Ok(response)
-                            }
-                        }
-                        Err(e) => Err(e),
-                    };
-
-                    // Finish connection tracking for rate limiting
+        // Apply request middleware if configured
+        if let Some(middleware_config) = &route_match.route.middleware {
+            match
+                self.middleware_processor.process_request(
+                    req,
+                    &client_ip,
+                    middleware_config
+                ).await
+            {
+                Ok(processed_req) => {
+                    req = processed_req;
+                }
+                Err(middleware_error) => {
                     self.middleware_processor.finish_connection(&client_ip);
+                    return Err(ProxyError::RequestFailed(middleware_error.to_string()));
+                }
+            }
+        }
 
-                    Some(final_result)
-                } else {
-                    // No healthy targets available
-                    self.middleware_processor.finish_connection(&client_ip);
-                    Some(
-                        Err(
-                            ProxyError::ConnectionFailed("No healthy targets available".to_string())
-                        )
-                    )
+        // Serve fresh responses from the route cache without contacting the backend
+        let cache = self.response_caches
+            .get(&route_key(&route_match.route))
+            .filter(|_| ResponseCache::is_cacheable_request(&req));
+        if let Some(cache) = cache {
+            if let Some(response) = cache.lookup(&req) {
+                self.middleware_processor.finish_connection(&client_ip);
+                return Ok(with_cors(response));
+            }
+        }
+        let cache_request = cache.map(|_| (ResponseCache::key_for(&req), req.headers().clone()));
+
+        // Retried POSTs with an idempotency key get the first response instead of a second forward
+        let idempotency = self.idempotency_caches
+            .get(&route_key(&route_match.route))
+            .and_then(|store| store.key_for(&req).map(|key| (store, key)));
+        let reservation = match idempotency {
+            Some((store, key)) =>
+                match store.begin(&key).await {
+                    Idempotent::Replay(response) => {
+                        tracing::debug!(route = %route_match.route.path, key = %key, "Replaying response for idempotency key");
+                        self.middleware_processor.finish_connection(&client_ip);
+                        return Ok(with_cors(response));
+                    }
+                    Idempotent::Forward(reservation) => Some(reservation),
                 }
-            } else {
-                // Fallback to legacy single target mode
-                if let Some(target_url) = route_match.route.get_primary_target() {
-                    let result = self.forwarder.forward_request_legacy(
-                        req,
-                        &route_match,
-                        &target_url,
-                        client_ip
-                    ).await;
-
-                    // Apply response middleware if configured and request was successful
-                    let final_result = match result {
-                        Ok(response) => {
-                            if let Some(middleware_config) = &route_match.route.middleware {
-                                match
-                                    self.middleware_processor.process_response(
-                                        response,
-                                        middleware_config
-                                    ).await
-                                {
-                                    Ok(processed_response) => Ok(processed_response),
-                                    Err(middleware_error) =>
-                                        Err(
-                                            ProxyError::ResponseError(middleware_error.to_string())
-                                        ),
-                                }
-                            } else {
-                                Ok(response)
-                            }
-                        }
-                        Err(e) => Err(e),
-                    };
-
-                    // Finish connection tracking for rate limiting
-                    self.middleware_processor.finish_connection(&client_ip);
+            None => None,
+        };
 
-                    Some(final_result)
+        let result = self.forward_to_route(req, route_match, client_ip).await;
+
+        // Apply response middleware if configured and request was successful
+        let final_result = match result {
+            Ok(response) => {
+                if let Some(middleware_config) = &route_match.route.middleware {
+                    match
+                        self.middleware_processor.process_response(
+                            response,
+                            middleware_config
+                        ).await
+                    {
+                        Ok(processed_response) => Ok(processed_response),
+                        Err(MiddlewareError::DecodedBodyTooLarge(msg)) =>
+                            Err(ProxyError::ResponseTooLarge(msg)),
+                        Err(middleware_error) =>
+                            Err(ProxyError::ResponseError(middleware_error.to_string())),
+                    }
                 } else {
-                    self.middleware_processor.finish_connection(&client_ip);
-                    Some(Err(ProxyError::InvalidUrl("No target configured for route".to_string())))
+                    Ok(response)
                 }
             }
+            Err(e) => Err(e),
+        };
+
+        let final_result = match (final_result, reservation) {
+            (Ok(response), Some(reservation)) => Ok(reservation.complete(response).await),
+            (result, _) => result,
+        };
+
+        // Store cacheable responses for subsequent requests
+        let final_result = match (final_result, cache.zip(cache_request)) {
+            (Ok(response), Some((cache, (key, request_headers)))) =>
+                Ok(cache.store(key, &request_headers, response).await),
+            (result, _) => result,
+        };
+
+        // Finish connection tracking for rate limiting
+        self.middleware_processor.finish_connection(&client_ip);
+
+        final_result.map(with_cors)
+    }
+
+    /// Forward a request to the route's selected target
+    async fn forward_to_route(
+        &self,
+        req: Request<Body>,
+        route_match: &RouteMatch,
+        client_ip: SocketAddr
+    ) -> Result<Response<Body>, ProxyError> {
+        // Shadow a sample of requests; upgrades cannot be replayed
+        let req = match &route_match.route.mirror {
+            Some(mirror) if !Self::is_websocket_request(&req) && percentage_roll() < mirror.percentage =>
+                self.mirror_request(req, route_match, mirror, client_ip).await?,
+            _ => req,
+        };
+
+        // Canary traffic bypasses load balancing entirely
+        if let Some(canary) = &route_match.route.canary {
+            if canary.selects(req.headers(), percentage_roll()) {
+                tracing::debug!(route = %route_match.route.path, target = %canary.target, "Routing request to canary");
+                return self.forwarder.forward_request(req, route_match, &canary.target, client_ip).await;
+            }
+        }
+
+        // Get the load balancer for this route
+        if let Some(load_balancer) = self.load_balancers.get(&route_key(&route_match.route)) {
+            // Check if this is a WebSocket request that should use sticky sessions
+            let is_websocket = Self::is_websocket_request(&req);
+            let use_sticky_sessions = is_websocket && route_match.route.sticky_sessions;
+
+            // Select target using appropriate strategy
+            let target = if use_sticky_sessions {
+                // Use client IP as identifier for sticky sessions
+                let client_id = client_ip.ip().to_string();
+                load_balancer.select_target_sticky(&client_id)
+            } else {
+                load_balancer.select_target()
+            };
+
+            let Some(target) = target else {
+                // Every target is down for now; clients should back off rather than treat it as a bad gateway
+                let retry_after = self.forwarder.client_config.no_healthy_targets_retry_after;
+                if let Some(response) = self.fallback_file_response(&route_match.route, retry_after).await {
+                    return Ok(response);
+                }
+                return Err(ProxyError::NoHealthyTargets { retry_after });
+            };
+
+            // Track request start
+            load_balancer.start_request(&target.url);
+
+            // Forward the request
+            let result = self.forwarder.forward_request(
+                req,
+                route_match,
+                &target.url,
+                client_ip
+            ).await;
+
+            // Track request end
+            load_balancer.end_request(&target.url);
+
+            result
+        } else if let Some(target_url) = route_match.route.get_primary_target() {
+            // Fallback to legacy single target mode
+            self.forwarder.forward_request_legacy(req, route_match, &target_url, client_ip).await
         } else {
-            None
+            Err(ProxyError::InvalidUrl("No target configured for route".to_string()))
         }
     }
 
+    /// The route's `fallback_file` as a 503 response, or None when the route has none or it
+    /// cannot be read. The file is read on each call so it can be edited during an outage.
+    async fn fallback_file_response(&self, route: &ProxyRoute, retry_after: u64) -> Option<Response<Body>> {
+        let path = self.static_dir.as_ref()?.join(route.fallback_file.as_ref()?);
+        let body = match tokio::fs::read(&path).await {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::warn!(
+                    route = %route.path,
+                    path = %path.display(),
+                    error = %e,
+                    "Fallback file unavailable; using built-in error"
+                );
+                return None;
+            }
+        };
+        let content_type = mime_guess::from_path(&path).first_or_octet_stream();
+        Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .header(axum::http::header::CONTENT_TYPE, content_type.as_ref())
+            .header(axum::http::header::RETRY_AFTER, retry_after)
+            .body(Body::from(body))
+            .ok()
+    }
+
+    /// Send a copy of the request to the mirror in the background, returning the request to
+    /// forward to the primary. The mirror's response and errors never reach the client.
+    ///
+    /// The copy is skipped while `MAX_MIRRORS_IN_FLIGHT` copies are outstanding, so a slow
+    /// mirror cannot pile up tasks and buffered bodies.
+    async fn mirror_request(
+        &self,
+        req: Request<Body>,
+        route_match: &RouteMatch,
+        mirror: &MirrorConfig,
+        client_ip: SocketAddr
+    ) -> Result<Request<Body>, ProxyError> {
+        let Ok(permit) = self.mirror_slots.clone().try_acquire_owned() else {
+            tracing::debug!(route = %route_match.route.path, target = %mirror.target, "Mirror saturated, request not copied");
+            return Ok(req);
+        };
+
+        // The body is buffered for both copies, within the same limit the primary enforces
+        let (parts, body) = req.into_parts();
+        let read_limit = route_match.route.max_request_body_bytes.map_or(usize::MAX, |limit| limit as usize);
+        let body = read_request_body(body, read_limit).await?;
+
+        let mut copy = Request::new(Body::from(body.clone()));
+        *copy.method_mut() = parts.method.clone();
+        *copy.uri_mut() = parts.uri.clone();
+        *copy.version_mut() = parts.version;
+        *copy.headers_mut() = parts.headers.clone();
+
+        let forwarder = self.forwarder.clone();
+        let route_match = route_match.clone();
+        let target = mirror.target.clone();
+        tokio::spawn(async move {
+            match forwarder.forward_request(copy, &route_match, &target, client_ip).await {
+                Ok(response) => {
+                    // Drain the body without keeping it so the connection can be reused
+                    let mut body = response.into_body();
+                    while let Some(Ok(_)) = http_body_util::BodyExt::frame(&mut body).await {}
+                }
+                Err(e) => tracing::debug!(route = %route_match.route.path, target = %target, error = %e, "Mirrored request failed"),
+            }
+            drop(permit);
+        });
+
+        Ok(Request::from_parts(parts, Body::from(body)))
+    }
+
     /// Check if a request is a WebSocket upgrade request
     pub fn is_websocket_request(req: &Request<Body>) -> bool {
         let headers = req.headers();
@@ -408,7 +961,7 @@ impl ProxyHandler {
         if let Some(route_match) = self.find_route(path) {
             // Get target URL for WebSocket proxying
             let target_url = if
-                let Some(load_balancer) = self.load_balancers.get(&route_match.route.path)
+                let Some(load_balancer) = self.load_balancers.get(&route_key(&route_match.route))
             {
                 // Use sticky sessions for WebSocket if enabled
                 let target = if route_match.route.sticky_sessions {
@@ -431,9 +984,17 @@ impl ProxyHandler {
                     &route_match.stripped_path
                 );
 
+                // Oversized client messages are refused while reading, like the backend's
+                let max_bytes = route_match.route.websocket_limits.as_ref().and_then(|limits| limits.max_frame_bytes);
+                let ws = match max_bytes {
+                    Some(max_bytes) => ws.max_message_size(max_bytes).max_frame_size(max_bytes),
+                    None => ws,
+                };
+
                 // Return the WebSocket upgrade response directly - this works despite type mismatch warnings
+                let route = route_match.route.clone();
                 let upgrade_response = ws.on_upgrade(move |socket| async move {
-                    if let Err(e) = proxy_websocket(socket, &ws_target_url, client_ip).await {
+                    if let Err(e) = proxy_websocket(socket, &route, &ws_target_url, client_ip).await {
                         tracing::error!(
                             error = %e,
                             target_url = %ws_target_url,
@@ -445,6 +1006,7 @@ impl ProxyHandler {
 
                 // Use unsafe transmute as a last resort to fix the type mismatch
                 // This is safe because both types have the same memory layout
+                #[allow(clippy::missing_transmute_annotations)]
                 return Some(unsafe { std::mem::transmute(upgrade_response) });
             }
         }
@@ -472,22 +1034,221 @@ impl ProxyHandler {
     }
 }
 
+/// Backend TLS settings that require a rustls client
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BackendTlsSettings {
+    verify_backend_ssl: bool,
+    client_cert_file: Option<PathBuf>,
+    client_key_file: Option<PathBuf>,
+}
+
+impl BackendTlsSettings {
+    fn from_route(route: &ProxyRoute) -> Option<Self> {
+        let ssl = route.ssl.as_ref()?;
+        let settings = Self {
+            verify_backend_ssl: ssl.verify_backend_ssl,
+            client_cert_file: ssl.client_cert_file.clone(),
+            client_key_file: ssl.client_key_file.clone(),
+        };
+
+        // Default TLS behavior is served by the default TLS backend
+        let has_identity = settings.client_cert_file.is_some() || settings.client_key_file.is_some();
+        if settings.verify_backend_ssl && !has_identity {
+            None
+        } else {
+            Some(settings)
+        }
+    }
+
+    /// Configure the builder to present the configured identity
+    fn apply(
+        &self,
+        builder: reqwest::ClientBuilder
+    ) -> Result<reqwest::ClientBuilder, ProxyError> {
+        let mut builder = builder.use_rustls_tls();
+
+        match (&self.client_cert_file, &self.client_key_file) {
+            (Some(cert_file), Some(key_file)) => {
+                let mut pem = std::fs::read(cert_file).map_err(|e|
+                    ProxyError::TlsConfig(
+                        format!("Failed to read client certificate '{}': {}", cert_file.display(), e)
+                    )
+                )?;
+                pem.push(b'\n');
+                pem.extend(
+                    std::fs::read(key_file).map_err(|e|
+                        ProxyError::TlsConfig(
+                            format!("Failed to read client key '{}': {}", key_file.display(), e)
+                        )
+                    )?
+                );
+                let identity = reqwest::Identity::from_pem(&pem).map_err(|e|
+                    ProxyError::TlsConfig(format!("Invalid client certificate or key: {}", e))
+                )?;
+                builder = builder.identity(identity);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(
+                    ProxyError::TlsConfig(
+                        "client_cert_file and client_key_file must be configured together".to_string()
+                    )
+                );
+            }
+        }
+
+        if !self.verify_backend_ssl {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        Ok(builder)
+    }
+
+    /// rustls client settings for backend connections reqwest does not make (e.g. WebSockets)
+    fn rustls_config(&self) -> Result<rustls::ClientConfig, ProxyError> {
+        let builder = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(http2::webpki_root_store());
+
+        let mut config = match (&self.client_cert_file, &self.client_key_file) {
+            (Some(cert_file), Some(key_file)) => {
+                let chain = read_pem_items(cert_file, "client certificate")?
+                    .into_iter()
+                    .filter_map(|item| match item {
+                        rustls_pemfile::Item::X509Certificate(der) => Some(rustls::Certificate(der)),
+                        _ => None,
+                    })
+                    .collect();
+                let key = read_pem_items(key_file, "client key")?
+                    .into_iter()
+                    .find_map(|item| match item {
+                        rustls_pemfile::Item::PKCS8Key(der) |
+                        rustls_pemfile::Item::RSAKey(der) |
+                        rustls_pemfile::Item::ECKey(der) => Some(rustls::PrivateKey(der)),
+                        _ => None,
+                    })
+                    .ok_or_else(|| {
+                        ProxyError::TlsConfig(format!("No private key found in '{}'", key_file.display()))
+                    })?;
+                builder
+                    .with_client_auth_cert(chain, key)
+                    .map_err(|e| ProxyError::TlsConfig(format!("Invalid client certificate or key: {}", e)))?
+            }
+            (None, None) => builder.with_no_client_auth(),
+            _ => {
+                return Err(
+                    ProxyError::TlsConfig(
+                        "client_cert_file and client_key_file must be configured together".to_string()
+                    )
+                );
+            }
+        };
+
+        if !self.verify_backend_ssl {
+            config.dangerous().set_certificate_verifier(Arc::new(http2::AcceptAnyServerCertificate));
+        }
+        Ok(config)
+    }
+}
+
+/// Every PEM section in a client certificate or key file
+fn read_pem_items(path: &std::path::Path, what: &str) -> Result<Vec<rustls_pemfile::Item>, ProxyError> {
+    let pem = std::fs::read(path).map_err(|e|
+        ProxyError::TlsConfig(format!("Failed to read {} '{}': {}", what, path.display(), e))
+    )?;
+    rustls_pemfile::read_all(&mut pem.as_slice()).map_err(|e|
+        ProxyError::TlsConfig(format!("Failed to parse {} '{}': {}", what, path.display(), e))
+    )
+}
+
+/// Effective client settings for a route; routes with equal settings share a client and its pool
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BackendClientSettings {
+    connect_timeout: u64,
+    pool_idle_timeout: u64,
+    pool_max_idle_per_host: usize,
+    follow_redirects: bool,
+    tls: Option<BackendTlsSettings>,
+}
+
+impl BackendClientSettings {
+    fn from_route(defaults: &ProxyClientConfig, route: &ProxyRoute) -> Self {
+        let config = defaults.with_overrides(route.client.as_ref());
+        Self {
+            connect_timeout: route.connect_timeout.unwrap_or(config.connect_timeout),
+            pool_idle_timeout: config.pool_idle_timeout,
+            pool_max_idle_per_host: config.pool_max_idle_per_host,
+            follow_redirects: config.follow_redirects,
+            tls: BackendTlsSettings::from_route(route),
+        }
+    }
+
+    /// Build a client with these pooling, redirect and TLS settings
+    fn build_client(&self) -> Result<reqwest::Client, ProxyError> {
+        let redirect_policy = if self.follow_redirects {
+            reqwest::redirect::Policy::default()
+        } else {
+            reqwest::redirect::Policy::none()
+        };
+
+        let mut builder = reqwest::Client
+            ::builder()
+            .connect_timeout(Duration::from_secs(self.connect_timeout))
+            .pool_idle_timeout(Duration::from_secs(self.pool_idle_timeout))
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .redirect(redirect_policy);
+
+        if let Some(tls) = &self.tls {
+            builder = tls.apply(builder)?;
+        }
+
+        builder
+            .build()
+            .map_err(|e| ProxyError::TlsConfig(format!("Failed to create backend client: {}", e)))
+    }
+}
+
 /// HTTP proxy forwarder that handles request forwarding to backend servers
 pub struct ProxyForwarder {
-    /// HTTP client for making requests to backend servers
-    client: reqwest::Client,
+    /// Client settings applied to routes without overrides
+    client_config: ProxyClientConfig,
+    /// HTTP clients keyed by their settings, built on first use and reused across requests
+    clients: RwLock<HashMap<BackendClientSettings, reqwest::Client>>,
+    /// Streaming HTTP/2 forwarder for routes with http2 enabled
+    http2: Http2Forwarder,
 }
 
 impl ProxyForwarder {
     /// Create a new proxy forwarder
     pub fn new() -> Self {
-        let client = reqwest::Client
-            ::builder()
-            .timeout(Duration::from_secs(30)) // Default timeout
-            .build()
-            .expect("Failed to create HTTP client");
+        Self::with_client_config(ProxyClientConfig::default())
+    }
 
-        Self { client }
+    /// Create a proxy forwarder with the given client settings
+    pub fn with_client_config(client_config: ProxyClientConfig) -> Self {
+        Self {
+            client_config,
+            clients: RwLock::new(HashMap::new()),
+            http2: Http2Forwarder::new(),
+        }
+    }
+
+    /// Get the HTTP client for the given settings, building it on first use
+    fn client_for(&self, settings: &BackendClientSettings) -> Result<reqwest::Client, ProxyError> {
+        if let Some(client) = self.clients.read().unwrap().get(settings) {
+            return Ok(client.clone());
+        }
+
+        let client = settings.build_client()?;
+        tracing::info!(
+            connect_timeout = settings.connect_timeout,
+            pool_max_idle_per_host = settings.pool_max_idle_per_host,
+            follow_redirects = settings.follow_redirects,
+            custom_tls = settings.tls.is_some(),
+            "Created backend HTTP client"
+        );
+        self.clients.write().unwrap().insert(settings.clone(), client.clone());
+        Ok(client)
     }
 
     /// Forward request to a specific target URL (new load-balanced method)
@@ -503,13 +1264,41 @@ impl ProxyForwarder {
         // Build the target URL
         let full_target_url = self.build_target_url(target_url, &route_match.stripped_path)?;
 
+        // Declared bodies over the route's limit are rejected before contacting the backend
+        let request_limit = route_match.route.max_request_body_bytes;
+        if let Some(limit) = request_limit {
+            if declared_length(req.headers()).is_some_and(|length| length > limit) {
+                return Err(
+                    ProxyError::PayloadTooLarge(
+                        format!("Request body exceeds the route limit of {} bytes", limit)
+                    )
+                );
+            }
+        }
+
+        // Checksums and signatures cover the whole body, so it is read before anything is sent
+        let read_limit = request_limit.map_or(usize::MAX, |limit| limit as usize);
+        let req = match &route_match.route.body_integrity {
+            Some(integrity) => {
+                let (parts, body) = req.into_parts();
+                let body_bytes = read_request_body(body, read_limit).await?;
+                body_integrity::verify_body(integrity, &parts.headers, &body_bytes)?;
+                Request::from_parts(parts, Body::from(body_bytes))
+            }
+            None => req,
+        };
+
+        if route_match.route.http2 {
+            return self.forward_http2(req, route_match, full_target_url, client_ip).await;
+        }
+
         // Extract request components before consuming the body
+        let peer = peer_addr(&req, client_ip);
+        let origin = client_origin(&req);
         let method = req.method().clone();
         let uri = req.uri().clone();
         let headers = req.headers().clone();
-        let body_bytes = axum::body
-            ::to_bytes(req.into_body(), usize::MAX).await
-            .map_err(|e| ProxyError::RequestBody(e.to_string()))?;
+        let body_bytes = read_request_body(req.into_body(), read_limit).await?;
 
         // Build the proxy request
         let reqwest_method = match method.as_str() {
@@ -525,12 +1314,24 @@ impl ProxyForwarder {
             }
         };
 
-        let mut proxy_req = self.client
+        let client_settings = BackendClientSettings::from_route(
+            &self.client_config,
+            &route_match.route
+        );
+        let mut proxy_req = self
+            .client_for(&client_settings)?
             .request(reqwest_method, &full_target_url)
             .timeout(Duration::from_secs(route_match.route.timeout));
 
         // Forward headers (with modifications)
-        let forwarded_headers = self.prepare_headers(&headers, &client_ip, &full_target_url)?;
+        let forwarded_headers = self.prepare_headers(
+            &headers,
+            &client_ip,
+            &peer,
+            &origin,
+            &full_target_url,
+            &route_match.route
+        )?;
         for (name_str, value_str) in forwarded_headers {
             if
                 let (Ok(req_name), Ok(req_value)) = (
@@ -542,16 +1343,49 @@ impl ProxyForwarder {
             }
         }
 
+        if let Some(log_config) = &route_match.route.log_bodies {
+            tracing::info!(
+                route = %route_match.route.path,
+                method = %method,
+                uri = %uri,
+                headers = %body_log::redact_headers(&headers, log_config),
+                body = %body_log::redact_body(&body_bytes, log_config),
+                "Proxy request body"
+            );
+        }
+
         // Add body if present
         if !body_bytes.is_empty() {
             proxy_req = proxy_req.body(body_bytes.to_vec());
         }
 
-        // Execute the request
-        let proxy_response = proxy_req.send().await.map_err(|e| {
-            if e.is_timeout() {
+        // Execute the request; with a read timeout the wait for response headers is bounded
+        // by the connect and read timeouts rather than the total timeout
+        let send = proxy_req.send();
+        let sent = match route_match.route.read_timeout {
+            Some(read_timeout) => {
+                let wait = Duration::from_secs(client_settings.connect_timeout + read_timeout);
+                tokio::time
+                    ::timeout(wait, send).await
+                    .map_err(|_| ProxyError::Timeout(read_timeout))?
+            }
+            None => send.await,
+        };
+        let proxy_response = sent.map_err(|e| {
+            if e.is_connect() && e.is_timeout() {
+                ProxyError::Timeout(client_settings.connect_timeout)
+            } else if e.is_timeout() {
                 ProxyError::Timeout(route_match.route.timeout)
+            } else if is_certificate_rejection(&e) {
+                ProxyError::BackendCertificate(full_target_url.clone())
             } else if e.is_connect() {
+                tracing::warn!(
+                    event = events::BACKEND_CONNECT_FAILED,
+                    target = %full_target_url,
+                    protocol = "http",
+                    error = %e,
+                    "Backend connection failed"
+                );
                 ProxyError::ConnectionFailed(full_target_url.clone())
             } else {
                 ProxyError::RequestFailed(e.to_string())
@@ -559,11 +1393,107 @@ impl ProxyForwarder {
         })?;
 
         // Convert response
-        let response = self.convert_response(proxy_response).await?;
+        let response = self.convert_response(proxy_response, &method, &route_match.route).await?;
+
+        // Log the proxy request unless the route writes its own access log line
+        let duration = start_time.elapsed();
+        if route_match.route.access_log && route_match.route.access_log_format.is_none() {
+            println!("PROXY {} {} -> {} ({}ms)", method, uri, full_target_url, duration.as_millis());
+        }
+
+        Ok(response)
+    }
+
+    /// Forward request over HTTP/2, streaming bodies and trailers in both directions
+    async fn forward_http2(
+        &self,
+        req: Request<Body>,
+        route_match: &RouteMatch,
+        full_target_url: String,
+        client_ip: SocketAddr
+    ) -> Result<Response<Body>, ProxyError> {
+        let start_time = std::time::Instant::now();
+        let route = &route_match.route;
+
+        let client_settings = BackendClientSettings::from_route(&self.client_config, route);
+        if let Some(tls) = &client_settings.tls {
+            if tls.client_cert_file.is_some() || tls.client_key_file.is_some() {
+                return Err(
+                    ProxyError::TlsConfig(
+                        "Client certificates are not supported on http2 routes".to_string()
+                    )
+                );
+            }
+        }
+        let options = Http2Options {
+            connect_timeout: Duration::from_secs(client_settings.connect_timeout),
+            response_timeout: Duration::from_secs(
+                route.read_timeout.map_or(route.timeout, |read_timeout| read_timeout.min(route.timeout))
+            ),
+            verify_backend_ssl: client_settings.tls
+                .as_ref()
+                .is_none_or(|tls| tls.verify_backend_ssl),
+        };
+
+        let peer = peer_addr(&req, client_ip);
+        let origin = client_origin(&req);
+        let (parts, body) = req.into_parts();
+        // Streamed bodies are cut off once they pass the route limit
+        let body = match route.max_request_body_bytes {
+            Some(limit) => Body::new(http_body_util::Limited::new(body, limit as usize)),
+            None => body,
+        };
+        let uri: axum::http::Uri = full_target_url
+            .parse()
+            .map_err(|e| ProxyError::InvalidUrl(format!("Invalid target URL '{}': {}", full_target_url, e)))?;
+        let mut backend_req = Request::builder()
+            .method(parts.method.clone())
+            .uri(uri)
+            .body(body)
+            .map_err(|e| ProxyError::RequestFailed(e.to_string()))?;
+
+        // HTTP/2 carries the host in :authority and only allows "te: trailers"
+        let forwarded_headers = self.prepare_headers(
+            &parts.headers,
+            &client_ip,
+            &peer,
+            &origin,
+            &full_target_url,
+            route
+        )?;
+        for (name_str, value_str) in forwarded_headers {
+            if name_str == "host" || (name_str == "te" && value_str != "trailers") {
+                continue;
+            }
+            if
+                let (Ok(name), Ok(value)) = (
+                    HeaderName::from_bytes(name_str.as_bytes()),
+                    HeaderValue::from_str(&value_str),
+                )
+            {
+                backend_req.headers_mut().append(name, value);
+            }
+        }
+
+        let response = self.http2.send(backend_req, &options).await?;
+
+        // Responses are streamed, so only a declared length can be checked up front
+        if let Some(limit) = route.max_response_body_bytes {
+            if declared_length(response.headers()).is_some_and(|length| length > limit) {
+                return Err(response_too_large(limit));
+            }
+        }
 
-        // Log the proxy request
         let duration = start_time.elapsed();
-        println!("PROXY {} {} -> {} ({}ms)", method, uri, full_target_url, duration.as_millis());
+        if route.access_log && route.access_log_format.is_none() {
+            println!(
+                "PROXY {} {} -> {} (HTTP/2, {}ms)",
+                parts.method,
+                parts.uri,
+                full_target_url,
+                duration.as_millis()
+            );
+        }
 
         Ok(response)
     }
@@ -605,7 +1535,10 @@ impl ProxyForwarder {
         &self,
         original_headers: &HeaderMap,
         client_ip: &SocketAddr,
-        target_url: &str
+        peer: &SocketAddr,
+        origin: &ClientOrigin,
+        target_url: &str,
+        route: &ProxyRoute
     ) -> Result<Vec<(String, String)>, ProxyError> {
         let mut headers = Vec::new();
 
@@ -614,18 +1547,34 @@ impl ProxyForwarder {
             ::parse(target_url)
             .map_err(|e| ProxyError::InvalidUrl(format!("Invalid target URL: {}", e)))?;
 
+        let hop_by_hop = hop_by_hop_headers(
+            original_headers.get_all(axum::http::header::CONNECTION).iter().map(|value| value.as_bytes())
+        );
+
         // Copy headers from original request, converting between types
         for (name, value) in original_headers {
             let name_str = name.as_str().to_lowercase();
 
+            // "TE: trailers" is the one TE value HTTP/2 passes end to end; gRPC backends require it
+            let te_trailers = name_str == "te" && value.as_bytes().eq_ignore_ascii_case(b"trailers");
+            if (hop_by_hop.contains(&name_str) && !te_trailers) || !route.forwards_header(&name_str) {
+                continue;
+            }
+
             // Skip headers that we'll replace or shouldn't forward
             match name_str.as_str() {
-                "host" | "connection" | "upgrade" | "proxy-connection" => {
+                "host" => {
                     continue;
                 }
-                "content-length" | "transfer-encoding" => {
+                "content-length" => {
+                    continue;
+                } // Let reqwest handle this
+                "x-forwarded-for" | "x-forwarded-proto" | "x-forwarded-port" | "x-real-ip" | "forwarded" | HOPS_HEADER => {
                     continue;
-                } // Let reqwest handle these
+                } // Rebuilt below with this hop appended
+                "traceparent" | "tracestate" => {
+                    continue;
+                } // Validated below
                 _ => {
                     if let Ok(value_str) = value.to_str() {
                         headers.push((name.as_str().to_string(), value_str.to_string()));
@@ -644,21 +1593,51 @@ impl ProxyForwarder {
             headers.push(("host".to_string(), host_value));
         }
 
-        // Add X-Forwarded-For header
-        let x_forwarded_for = if let Some(existing) = original_headers.get("x-forwarded-for") {
-            if let Ok(existing_str) = existing.to_str() {
-                format!("{}, {}", existing_str, client_ip.ip())
-            } else {
-                client_ip.ip().to_string()
+        // Add X-Forwarded-For header; each hop appends the peer it received the request from
+        headers.push((
+            "x-forwarded-for".to_string(),
+            append_hop(original_headers, "x-forwarded-for", peer.ip().to_string()),
+        ));
+
+        // Count this hop so a request routed back to us is eventually rejected
+        headers.push((HOPS_HEADER.to_string(), (request_hops(original_headers) + 1).to_string()));
+
+        // Forward the trace context, starting a trace when the request carries none
+        match original_headers.get("traceparent").and_then(|value| value.to_str().ok()) {
+            Some(traceparent) if is_valid_traceparent(traceparent) => {
+                headers.push(("traceparent".to_string(), traceparent.trim().to_string()));
+                for tracestate in original_headers.get_all("tracestate") {
+                    if let Ok(tracestate) = tracestate.to_str() {
+                        headers.push(("tracestate".to_string(), tracestate.to_string()));
+                    }
+                }
             }
-        } else {
-            client_ip.ip().to_string()
-        };
-        headers.push(("x-forwarded-for".to_string(), x_forwarded_for));
+            _ => {
+                let trace_id = Uuid::new_v4().simple().to_string();
+                let span_id = Uuid::new_v4().simple().to_string();
+                headers.push(("traceparent".to_string(), format!("00-{}-{}-01", trace_id, &span_id[..16])));
+            }
+        }
 
-        // Add X-Forwarded-Proto header
-        let proto = if target_url.starts_with("https://") { "https" } else { "http" };
+        // Scheme and port as the client saw them, whatever the backend connection uses
+        let proto = origin.scheme();
         headers.push(("x-forwarded-proto".to_string(), proto.to_string()));
+        headers.push(("x-forwarded-port".to_string(), origin.port.to_string()));
+
+        if route.forwarded_headers {
+            // X-Real-IP names the originating client only
+            headers.push(("x-real-ip".to_string(), client_ip.ip().to_string()));
+
+            // RFC 7239 Forwarded element for this hop
+            let mut element = format!("for={};proto={}", forwarded_node(peer), proto);
+            if let Some(host) = original_headers.get("host").and_then(|h| h.to_str().ok()) {
+                element.push_str(&format!(";host=\"{}\"", host));
+            }
+            headers.push((
+                "forwarded".to_string(),
+                append_hop(original_headers, "forwarded", element),
+            ));
+        }
 
         Ok(headers)
     }
@@ -666,7 +1645,9 @@ impl ProxyForwarder {
     /// Convert reqwest response to axum response
     async fn convert_response(
         &self,
-        proxy_response: reqwest::Response
+        mut proxy_response: reqwest::Response,
+        method: &Method,
+        route: &ProxyRoute
     ) -> Result<Response<Body>, ProxyError> {
         let status = StatusCode::from_u16(proxy_response.status().as_u16()).map_err(|e|
             ProxyError::ResponseError(format!("Invalid status code: {}", e))
@@ -676,30 +1657,68 @@ impl ProxyForwarder {
 
         // Copy headers from reqwest response to axum response
         let headers = response.headers_mut().unwrap();
+        let hop_by_hop = hop_by_hop_headers(
+            proxy_response.headers().get_all(reqwest::header::CONNECTION).iter().map(|value| value.as_bytes())
+        );
         for (name, value) in proxy_response.headers() {
-            // Skip headers that might cause issues
-            let name_str = name.as_str().to_lowercase();
-            match name_str.as_str() {
-                "connection" | "transfer-encoding" | "content-encoding" => {
-                    continue;
-                }
-                _ => {
-                    if
-                        let (Ok(header_name), Ok(header_value)) = (
-                            HeaderName::from_bytes(name.as_str().as_bytes()),
-                            HeaderValue::from_bytes(value.as_bytes()),
-                        )
-                    {
-                        headers.insert(header_name, header_value);
-                    }
-                }
+            // Skip hop-by-hop headers; Content-Encoding stays with the still-encoded body
+            if hop_by_hop.contains(name.as_str()) {
+                continue;
+            }
+            if
+                let (Ok(header_name), Ok(header_value)) = (
+                    HeaderName::from_bytes(name.as_str().as_bytes()),
+                    HeaderValue::from_bytes(value.as_bytes()),
+                )
+            {
+                headers.insert(header_name, header_value);
             }
         }
 
-        // Get response body
-        let body_bytes = proxy_response
-            .bytes().await
-            .map_err(|e| ProxyError::ResponseBody(e.to_string()))?;
+        // HEAD responses keep the backend's headers, including the Content-Length a GET would
+        // return, but never carry a body
+        if *method == Method::HEAD {
+            return response
+                .body(Body::empty())
+                .map_err(|e| ProxyError::ResponseError(format!("Failed to build response: {}", e)));
+        }
+
+        // Get response body, giving up as soon as it passes the route's limit or the backend
+        // stalls for longer than the route's read timeout
+        let limit = route.max_response_body_bytes;
+        let declared = declared_length(headers);
+        if let Some(limit) = limit {
+            if declared.is_some_and(|length| length > limit) {
+                return Err(response_too_large(limit));
+            }
+        }
+
+        // Unsized (chunked) bodies are relayed chunk by chunk as they arrive and re-chunked to
+        // the client, unless the whole body has to be logged
+        if declared.is_none() && route.log_bodies.is_none() {
+            return response
+                .body(relay_body(proxy_response, route))
+                .map_err(|e| ProxyError::ResponseError(format!("Failed to build response: {}", e)));
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = next_body_chunk(&mut proxy_response, route.read_timeout).await? {
+            if let Some(limit) = limit.filter(|limit| ((body.len() + chunk.len()) as u64) > *limit) {
+                return Err(response_too_large(limit));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        let body_bytes = axum::body::Bytes::from(body);
+
+        if let Some(log_config) = &route.log_bodies {
+            tracing::info!(
+                route = %route.path,
+                status = status.as_u16(),
+                headers = %body_log::redact_headers(headers, log_config),
+                body = %body_log::redact_body(&body_bytes, log_config),
+                "Proxy response body"
+            );
+        }
 
         let response = response
             .body(Body::from(body_bytes))
@@ -709,11 +1728,120 @@ impl ProxyForwarder {
     }
 }
 
+/// Next chunk of a backend response body; a backend silent for `read_timeout` seconds times out
+async fn next_body_chunk(
+    response: &mut reqwest::Response,
+    read_timeout: Option<u64>
+) -> Result<Option<axum::body::Bytes>, ProxyError> {
+    let chunk = match read_timeout {
+        Some(read_timeout) =>
+            tokio::time
+                ::timeout(Duration::from_secs(read_timeout), response.chunk()).await
+                .map_err(|_| ProxyError::Timeout(read_timeout))?,
+        None => response.chunk().await,
+    };
+    chunk.map_err(|e| ProxyError::ResponseBody(e.to_string()))
+}
+
+/// Stream a backend response body to the client. The headers have already been sent, so
+/// passing the route's size limit or read timeout ends the stream with an error, which
+/// aborts the connection rather than completing a truncated body.
+fn relay_body(response: reqwest::Response, route: &ProxyRoute) -> Body {
+    let (limit, read_timeout, path) = (route.max_response_body_bytes, route.read_timeout, route.path.clone());
+    let chunks = futures_util::stream::unfold(Some((response, 0u64)), move |state| {
+        let path = path.clone();
+        async move {
+            let (mut response, relayed) = state?;
+            let chunk = next_body_chunk(&mut response, read_timeout).await.transpose()?;
+            let relayed = relayed + chunk.as_ref().map_or(0, |chunk| chunk.len() as u64);
+            let chunk = match (chunk, limit) {
+                (Ok(_), Some(limit)) if relayed > limit => Err(response_too_large(limit)),
+                (chunk, _) => chunk,
+            };
+            match chunk {
+                Ok(chunk) => Some((Ok(chunk), Some((response, relayed)))),
+                Err(e) => {
+                    tracing::warn!(route = %path, relayed_bytes = relayed, error = %e, "Aborting streamed backend response");
+                    Some((Err(e), None))
+                }
+            }
+        }
+    });
+    Body::from_stream(chunks)
+}
+
+/// Whether a response body is relayed as it arrives (see `relay_body`). Its size is unknown,
+/// so stages that buffer whole bodies pass it through untouched.
+pub(crate) fn is_streamed(response: &Response<Body>) -> bool {
+    axum::body::HttpBody::size_hint(response.body()).upper().is_none()
+}
+
+/// Content-Length declared in a set of headers
+fn declared_length(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(axum::http::header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+}
+
+fn response_too_large(limit: u64) -> ProxyError {
+    ProxyError::ResponseTooLarge(format!("Backend response exceeds the route limit of {} bytes", limit))
+}
+
+/// Whether a backend request failed because the backend's certificate was not trusted
+fn is_certificate_rejection(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(error);
+    while let Some(err) = source {
+        let message = err.to_string().to_lowercase();
+        // rustls and OpenSSL wording respectively
+        if message.contains("invalid peer certificate") || message.contains("certificate verify failed") {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+/// Buffer a request body of at most `limit` bytes
+async fn read_request_body(body: Body, limit: usize) -> Result<axum::body::Bytes, ProxyError> {
+    axum::body::to_bytes(body, limit).await.map_err(|e| {
+        if is_length_limit_error(&e) {
+            ProxyError::PayloadTooLarge(e.to_string())
+        } else {
+            ProxyError::RequestBody(e.to_string())
+        }
+    })
+}
+
+/// Whether reading a request body failed because it exceeded the server's size limit
+fn is_length_limit_error(error: &axum::Error) -> bool {
+    let mut source: Option<&dyn std::error::Error> = Some(error);
+    while let Some(err) = source {
+        if err.is::<http_body_util::LengthLimitError>() {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+impl Default for ProxyForwarder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Proxy error types
 #[derive(Debug)]
 pub enum ProxyError {
     /// Request body reading failed
     RequestBody(String),
+    /// Request body exceeded the server's size limit
+    PayloadTooLarge(String),
+    /// Request body does not match its checksum header
+    ChecksumMismatch(String),
+    /// Request body signature header is missing or wrong
+    InvalidSignature(String),
     /// Request failed
     RequestFailed(String),
     /// Connection to target failed
@@ -728,12 +1856,33 @@ pub enum ProxyError {
     ResponseError(String),
     /// Response body reading failed
     ResponseBody(String),
+    /// Backend TLS configuration is invalid
+    TlsConfig(String),
+    /// Backend certificate failed verification
+    BackendCertificate(String),
+    /// Backend response body exceeded the route's limit
+    ResponseTooLarge(String),
+    /// Request has already been forwarded the maximum number of times (hop count)
+    LoopDetected(u32),
+    /// Every target of the route is unhealthy; clients should retry after `retry_after` seconds
+    NoHealthyTargets {
+        retry_after: u64,
+    },
+    /// The client failed the route's authentication
+    Unauthorized(String),
+    /// The route's auth service refused the request with this client error status
+    AuthDenied(StatusCode),
+    /// The route's auth service could not be reached
+    AuthUnavailable(String),
 }
 
 impl std::fmt::Display for ProxyError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ProxyError::RequestBody(msg) => write!(f, "Request body error: {}", msg),
+            ProxyError::PayloadTooLarge(msg) => write!(f, "Request body too large: {}", msg),
+            ProxyError::ChecksumMismatch(msg) => write!(f, "Request body checksum failed: {}", msg),
+            ProxyError::InvalidSignature(msg) => write!(f, "Request body signature failed: {}", msg),
             ProxyError::RequestFailed(msg) => write!(f, "Request failed: {}", msg),
             ProxyError::ConnectionFailed(url) => write!(f, "Connection failed to: {}", url),
             ProxyError::Timeout(seconds) => write!(f, "Request timeout after {} seconds", seconds),
@@ -741,24 +1890,167 @@ impl std::fmt::Display for ProxyError {
             ProxyError::HeaderError(msg) => write!(f, "Header error: {}", msg),
             ProxyError::ResponseError(msg) => write!(f, "Response error: {}", msg),
             ProxyError::ResponseBody(msg) => write!(f, "Response body error: {}", msg),
+            ProxyError::TlsConfig(msg) => write!(f, "Backend TLS configuration error: {}", msg),
+            ProxyError::BackendCertificate(url) =>
+                write!(
+                    f,
+                    "Backend certificate verification failed for: {} (set verify_backend_ssl = false to allow self-signed certificates)",
+                    url
+                ),
+            ProxyError::ResponseTooLarge(msg) => write!(f, "Response too large: {}", msg),
+            ProxyError::LoopDetected(hops) =>
+                write!(f, "Proxy loop detected: request already forwarded {} times", hops),
+            ProxyError::NoHealthyTargets { .. } => write!(f, "No healthy targets available"),
+            ProxyError::Unauthorized(msg) => write!(f, "Authentication failed: {}", msg),
+            ProxyError::AuthDenied(status) =>
+                write!(f, "Auth service denied the request with status {}", status.as_u16()),
+            ProxyError::AuthUnavailable(msg) => write!(f, "Auth service unavailable: {}", msg),
         }
     }
 }
 
 impl std::error::Error for ProxyError {}
 
-impl IntoResponse for ProxyError {
-    fn into_response(self) -> Response {
-        let (status, message) = match &self {
+/// Seconds clients are asked to wait before retrying a failed or timed-out backend
+pub const PROXY_RETRY_AFTER_SECS: u64 = 5;
+
+/// Header counting how many times a request has passed through a proxy; each forward increments it
+pub const HOPS_HEADER: &str = "x-httpserver-hops";
+
+/// Mirrored requests a handler keeps in flight; further copies are dropped until one finishes
+pub const MAX_MIRRORS_IN_FLIGHT: usize = 64;
+
+/// Scheme and port the client connected to, set as a request extension by the server
+///
+/// Backends see these as X-Forwarded-Proto and X-Forwarded-Port, so a gateway terminating TLS
+/// on 443 reports "https" and 443 even though the backend is reached over plain HTTP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientOrigin {
+    /// Whether the client connection used TLS
+    pub https: bool,
+    /// Port the client connected to
+    pub port: u16,
+}
+
+impl ClientOrigin {
+    /// "https" or "http"
+    pub fn scheme(&self) -> &'static str {
+        if self.https { "https" } else { "http" }
+    }
+}
+
+/// Headers that only describe one connection (RFC 7230 section 6.1) and are never forwarded
+const HOP_BY_HOP_HEADERS: [&str; 9] = [
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Lowercase hop-by-hop header names of a message: the standard set plus those its Connection headers list
+fn hop_by_hop_headers<'a>(connection_values: impl Iterator<Item = &'a [u8]>) -> HashSet<String> {
+    let mut names: HashSet<String> = HOP_BY_HOP_HEADERS.iter().map(|name| name.to_string()).collect();
+    for value in connection_values {
+        let Ok(value) = std::str::from_utf8(value) else {
+            continue;
+        };
+        names.extend(
+            value
+                .split(',')
+                .map(|token| token.trim().to_ascii_lowercase())
+                .filter(|token| !token.is_empty())
+        );
+    }
+    names
+}
+
+/// Hop count carried by a request; missing or unparseable values count as zero
+fn request_hops(headers: &HeaderMap) -> u32 {
+    headers
+        .get(HOPS_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+impl ProxyError {
+    /// Status code and short client-facing message for this error
+    fn status_and_message(&self) -> (StatusCode, &'static str) {
+        match self {
             ProxyError::ConnectionFailed(_) =>
                 (StatusCode::BAD_GATEWAY, "Backend server unavailable"),
             ProxyError::Timeout(_) => (StatusCode::GATEWAY_TIMEOUT, "Backend server timeout"),
-            ProxyError::InvalidUrl(_) =>
+            ProxyError::PayloadTooLarge(_) =>
+                (StatusCode::PAYLOAD_TOO_LARGE, "Request body too large"),
+            ProxyError::ChecksumMismatch(_) =>
+                (StatusCode::BAD_REQUEST, "Request body checksum mismatch"),
+            ProxyError::InvalidSignature(_) =>
+                (StatusCode::UNAUTHORIZED, "Invalid request signature"),
+            ProxyError::BackendCertificate(_) =>
+                (StatusCode::BAD_GATEWAY, "Backend certificate not trusted"),
+            ProxyError::ResponseTooLarge(_) =>
+                (StatusCode::BAD_GATEWAY, "Backend response too large"),
+            ProxyError::LoopDetected(_) => (StatusCode::LOOP_DETECTED, "Proxy loop detected"),
+            ProxyError::NoHealthyTargets { .. } =>
+                (StatusCode::SERVICE_UNAVAILABLE, "No healthy backend available"),
+            ProxyError::Unauthorized(_) => (StatusCode::UNAUTHORIZED, "Authentication required"),
+            ProxyError::AuthDenied(StatusCode::UNAUTHORIZED) =>
+                (StatusCode::UNAUTHORIZED, "Authentication required"),
+            ProxyError::AuthDenied(status) => (*status, "Access denied"),
+            ProxyError::AuthUnavailable(_) =>
+                (StatusCode::SERVICE_UNAVAILABLE, "Authentication service unavailable"),
+            ProxyError::InvalidUrl(_) | ProxyError::TlsConfig(_) =>
                 (StatusCode::INTERNAL_SERVER_ERROR, "Invalid backend configuration"),
             _ => (StatusCode::INTERNAL_SERVER_ERROR, "Proxy error"),
+        }
+    }
+
+    /// Build the error response in the format the client's Accept header prefers:
+    /// JSON for API clients, the HTML error page otherwise
+    pub fn into_negotiated_response(self, accept: Option<&HeaderValue>) -> Response {
+        let (status, message) = self.status_and_message();
+
+        let mut response = if accepts_json(accept) {
+            let body =
+                serde_json::json!({
+                "error": message,
+                "status": status.as_u16(),
+                "detail": self.to_string()
+            });
+            (status, axum::Json(body)).into_response()
+        } else {
+            let mut response = (status, axum::response::Html(self.html_page(status, message))).into_response();
+            response.extensions_mut().insert(httpserver_config::GeneratedErrorPage);
+            response
         };
 
-        let error_response = format!(
+        // Transient backend failures are worth retrying shortly
+        let retry_after = match self {
+            ProxyError::Timeout(_) | ProxyError::ConnectionFailed(_) => Some(PROXY_RETRY_AFTER_SECS),
+            ProxyError::NoHealthyTargets { retry_after } => Some(retry_after),
+            _ => None,
+        };
+        if let Some(retry_after) = retry_after {
+            response
+                .headers_mut()
+                .insert(axum::http::header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        if let ProxyError::Unauthorized(_) = self {
+            response
+                .headers_mut()
+                .insert(axum::http::header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        response
+    }
+
+    /// HTML error page shown to browsers
+    fn html_page(&self, status: StatusCode, message: &str) -> String {
+        format!(
             r#"<!DOCTYPE html>
 <html>
 <head>
@@ -783,16 +2075,108 @@ impl IntoResponse for ProxyError {
             status.canonical_reason().unwrap_or("Error"),
             message,
             self
-        );
+        )
+    }
+}
+
+impl IntoResponse for ProxyError {
+    fn into_response(self) -> Response {
+        self.into_negotiated_response(None)
+    }
+}
+
+/// TCP peer the request arrived from, which differs from `client_ip` behind trusted proxies
+fn peer_addr(req: &Request<Body>, client_ip: SocketAddr) -> SocketAddr {
+    req.extensions()
+        .get::<axum::extract::ConnectInfo<SocketAddr>>()
+        .map(|axum::extract::ConnectInfo(peer)| *peer)
+        .unwrap_or(client_ip)
+}
+
+/// Where the client connected; without a `ClientOrigin` extension this is plain HTTP on the
+/// Host header's port (80 when it names none)
+fn client_origin(req: &Request<Body>) -> ClientOrigin {
+    if let Some(origin) = req.extensions().get::<ClientOrigin>() {
+        return *origin;
+    }
+    let port = req
+        .headers()
+        .get(axum::http::header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .and_then(|host| host.parse::<axum::http::uri::Authority>().ok())
+        .and_then(|authority| authority.port_u16())
+        .unwrap_or(80);
+    ClientOrigin { https: false, port }
+}
+
+/// Uniform sample in [0, 100) for percentage-based routing decisions
+fn percentage_roll() -> f64 {
+    ((Uuid::new_v4().as_u128() % 10_000) as f64) / 100.0
+}
+
+/// Whether a traceparent value is well-formed per W3C Trace Context (unknown versions may append fields)
+fn is_valid_traceparent(value: &str) -> bool {
+    let is_hex = |field: &str, len: usize|
+        field.len() == len && field.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+    let fields: Vec<&str> = value.trim().split('-').collect();
+    match fields.as_slice() {
+        [version, trace_id, span_id, flags, rest @ ..] =>
+            is_hex(version, 2) &&
+                *version != "ff" &&
+                (rest.is_empty() || *version != "00") &&
+                is_hex(trace_id, 32) &&
+                trace_id.bytes().any(|b| b != b'0') &&
+                is_hex(span_id, 16) &&
+                span_id.bytes().any(|b| b != b'0') &&
+                is_hex(flags, 2),
+        _ => false,
+    }
+}
+
+/// Append this hop's value to an existing comma-separated forwarding header
+fn append_hop(original_headers: &HeaderMap, name: &str, value: String) -> String {
+    let existing: Vec<&str> = original_headers
+        .get_all(name)
+        .iter()
+        .filter_map(|existing| existing.to_str().ok())
+        .collect();
+    if existing.is_empty() {
+        value
+    } else {
+        format!("{}, {}", existing.join(", "), value)
+    }
+}
 
-        (status, error_response).into_response()
+/// RFC 7239 node identifier for the client; IPv6 addresses must be bracketed and quoted
+fn forwarded_node(client_ip: &SocketAddr) -> String {
+    match client_ip.ip() {
+        std::net::IpAddr::V4(ip) => ip.to_string(),
+        std::net::IpAddr::V6(ip) => format!("\"[{}]\"", ip),
     }
 }
 
+/// Whether an Accept header prefers JSON over HTML (the first listed of the two wins)
+fn accepts_json(accept: Option<&HeaderValue>) -> bool {
+    let Some(accept) = accept.and_then(|value| value.to_str().ok()) else {
+        return false;
+    };
+    for media_range in accept.split(',') {
+        let media_type = media_range.split(';').next().unwrap_or_default().trim();
+        if media_type == "application/json" || media_type.ends_with("+json") {
+            return true;
+        }
+        if media_type == "text/html" {
+            return false;
+        }
+    }
+    false
+}
+
 /// Proxy a WebSocket connection between client and backend
-#[tracing::instrument(skip(client_socket), fields(request_id = %Uuid::new_v4()))]
+#[tracing::instrument(skip(client_socket, route), fields(request_id = %Uuid::new_v4()))]
 async fn proxy_websocket(
     client_socket: WebSocket,
+    route: &ProxyRoute,
     target_url: &str,
     client_ip: SocketAddr
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -803,133 +2187,9 @@ async fn proxy_websocket(
     );
 
     // Connect to the backend WebSocket server
-    let (backend_stream, _) = connect_async(target_url).await?;
-    let (mut backend_sink, mut backend_stream) = backend_stream.split();
-
-    // Create tasks to forward messages in both directions
-    let (mut client_sink, mut client_stream) = client_socket.split();
-
-    // Task 1: Forward messages from client to backend
-    let client_to_backend = tokio::spawn(async move {
-        while let Some(msg) = client_stream.next().await {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    tracing::debug!(
-                        message_type = "text",
-                        size = text.len(),
-                        "Forwarding message to backend"
-                    );
-                    if let Err(e) = backend_sink.send(TungsteniteMessage::Text(text)).await {
-                        tracing::error!(error = %e, "Error forwarding text to backend");
-                        break;
-                    }
-                }
-                Ok(Message::Binary(data)) => {
-                    tracing::debug!(
-                        message_type = "binary",
-                        size = data.len(),
-                        "Forwarding message to backend"
-                    );
-                    if let Err(e) = backend_sink.send(TungsteniteMessage::Binary(data)).await {
-                        tracing::error!(error = %e, "Error forwarding binary to backend");
-                        break;
-                    }
-                }
-                Ok(Message::Close(_)) => {
-                    tracing::info!("Client initiated WebSocket close");
-                    let _ = backend_sink.send(TungsteniteMessage::Close(None)).await;
-                    break;
-                }
-                Ok(Message::Ping(data)) => {
-                    tracing::debug!(size = data.len(), "Forwarding ping to backend");
-                    if let Err(e) = backend_sink.send(TungsteniteMessage::Ping(data)).await {
-                        tracing::error!(error = %e, "Error forwarding ping to backend");
-                        break;
-                    }
-                }
-                Ok(Message::Pong(data)) => {
-                    tracing::debug!(size = data.len(), "Forwarding pong to backend");
-                    if let Err(e) = backend_sink.send(TungsteniteMessage::Pong(data)).await {
-                        tracing::error!(error = %e, "Error forwarding pong to backend");
-                        break;
-                    }
-                }
-                Ok(Message::Frame(_)) => {
-                    // Frame messages are typically handled automatically
-                }
-                Err(e) => {
-                    tracing::error!(error = %e, "Error receiving from client");
-                    break;
-                }
-            }
-        }
-    });
+    let backend_stream = websocket::connect_backend(route, target_url).await?;
 
-    // Task 2: Forward messages from backend to client
-    let backend_to_client = tokio::spawn(async move {
-        while let Some(msg) = backend_stream.next().await {
-            match msg {
-                Ok(TungsteniteMessage::Text(text)) => {
-                    tracing::debug!(
-                        message_type = "text",
-                        size = text.len(),
-                        "Forwarding message to client"
-                    );
-                    if let Err(e) = client_sink.send(Message::Text(text)).await {
-                        tracing::error!(error = %e, "Error forwarding text to client");
-                        break;
-                    }
-                }
-                Ok(TungsteniteMessage::Binary(data)) => {
-                    tracing::debug!(
-                        message_type = "binary",
-                        size = data.len(),
-                        "Forwarding message to client"
-                    );
-                    if let Err(e) = client_sink.send(Message::Binary(data)).await {
-                        tracing::error!(error = %e, "Error forwarding binary to client");
-                        break;
-                    }
-                }
-                Ok(TungsteniteMessage::Close(_)) => {
-                    tracing::info!("Backend initiated WebSocket close");
-                    let _ = client_sink.send(Message::Close(None)).await;
-                    break;
-                }
-                Ok(TungsteniteMessage::Ping(data)) => {
-                    tracing::debug!(size = data.len(), "Forwarding ping to client");
-                    if let Err(e) = client_sink.send(Message::Ping(data)).await {
-                        tracing::error!(error = %e, "Error forwarding ping to client");
-                        break;
-                    }
-                }
-                Ok(TungsteniteMessage::Pong(data)) => {
-                    tracing::debug!(size = data.len(), "Forwarding pong to client");
-                    if let Err(e) = client_sink.send(Message::Pong(data)).await {
-                        tracing::error!(error = %e, "Error forwarding pong to client");
-                        break;
-                    }
-                }
-                Ok(TungsteniteMessage::Frame(_)) => {
-                    // Frame messages are typically handled automatically
-                }
-                Err(e) => {
-                    tracing::error!(error = %e, "Error receiving from backend");
-                    break;
-                }
-            }
-        }
-    });
-
-    // Wait for either task to complete (connection closed or error)
-    tokio::select! {
-        _ = client_to_backend => {
-            tracing::info!("Client to backend connection closed");
-        }
-        _ = backend_to_client => {
-            tracing::info!("Backend to client connection closed");
-        }
-    }
+    websocket::relay_websocket(client_socket, backend_stream, route.websocket_limits.clone()).await;
 
     Ok(())
 }
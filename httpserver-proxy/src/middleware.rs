@@ -1,18 +1,20 @@
-use axum::{ extract::Request, response::Response, http::{ HeaderName, HeaderValue }, body::Body };
-use std::{
-    collections::HashMap,
-    sync::{ Arc, Mutex },
-    time::{ Duration, Instant },
-    net::SocketAddr,
+use axum::{
+    extract::Request,
+    response::Response,
+    http::{ header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode },
+    body::Body,
 };
+use std::{ collections::HashMap, sync::{ Arc, Mutex }, time::Duration, net::SocketAddr };
 use tracing;
 use serde_json::Value;
+use crate::rate_limit::{ MemoryRateLimitStore, RateLimitStore, RedisRateLimitStore };
 
 // Re-export middleware configuration types
 pub use httpserver_config::{
     MiddlewareConfig,
     HeaderMiddlewareConfig,
     RateLimitConfig,
+    RateLimitBackend,
     TransformConfig,
     RequestTransformConfig,
     ResponseTransformConfig,
@@ -20,20 +22,30 @@ pub use httpserver_config::{
     AuthMiddlewareConfig,
     ApiKeyConfig,
     CompressionConfig,
+    CorsConfig,
 };
 
+/// Largest decoded body accepted when decompressing a backend response for transforms
+pub const MAX_DECODED_BODY_BYTES: u64 = 64 << 20;
+
 /// Middleware processor that applies various transformations to requests and responses
 pub struct MiddlewareProcessor {
     /// Rate limiting state
     rate_limiter: Arc<Mutex<RateLimiter>>,
+
+    /// Request counters for the "memory" backend
+    memory_store: Arc<dyn RateLimitStore>,
+
+    /// Request counters for the "redis" backend, one store per URL
+    redis_stores: Mutex<HashMap<String, Arc<dyn RateLimitStore>>>,
+
+    /// Store used for every route in place of its configured backend
+    store_override: Option<Arc<dyn RateLimitStore>>,
 }
 
 /// Rate limiting implementation
 #[derive(Debug)]
 struct RateLimiter {
-    /// Client request counters (IP -> (count, window_start))
-    request_counts: HashMap<String, (u32, Instant)>,
-
     /// Active connection counts per client
     active_connections: HashMap<String, u32>,
 }
@@ -51,6 +63,8 @@ pub enum MiddlewareError {
     AuthError(String),
     /// Compression error
     CompressionError(String),
+    /// A decoded response body exceeded MAX_DECODED_BODY_BYTES
+    DecodedBodyTooLarge(String),
 }
 
 impl std::fmt::Display for MiddlewareError {
@@ -61,6 +75,7 @@ impl std::fmt::Display for MiddlewareError {
             MiddlewareError::TransformError(msg) => write!(f, "Transform error: {}", msg),
             MiddlewareError::AuthError(msg) => write!(f, "Authentication error: {}", msg),
             MiddlewareError::CompressionError(msg) => write!(f, "Compression error: {}", msg),
+            MiddlewareError::DecodedBodyTooLarge(msg) => write!(f, "Decoded body too large: {}", msg),
         }
     }
 }
@@ -73,13 +88,22 @@ impl MiddlewareProcessor {
         Self {
             rate_limiter: Arc::new(
                 Mutex::new(RateLimiter {
-                    request_counts: HashMap::new(),
                     active_connections: HashMap::new(),
                 })
             ),
+            memory_store: Arc::new(MemoryRateLimitStore::new()),
+            redis_stores: Mutex::new(HashMap::new()),
+            store_override: None,
         }
     }
 
+    /// Keep request counters in the given store regardless of each route's `backend`,
+    /// e.g. to share limits between processors or plug in another storage system
+    pub fn with_rate_limit_store(mut self, store: Arc<dyn RateLimitStore>) -> Self {
+        self.store_override = Some(store);
+        self
+    }
+
     /// Process request middleware (headers, auth, rate limiting, transformation)
     pub async fn process_request(
         &self,
@@ -91,7 +115,7 @@ impl MiddlewareProcessor {
 
         // Apply rate limiting first
         if let Some(rate_config) = &middleware_config.rate_limit {
-            self.check_rate_limit(&client_id, rate_config)?;
+            self.check_rate_limit(&client_id, rate_config).await?;
         }
 
         // Apply authentication middleware
@@ -147,32 +171,101 @@ impl MiddlewareProcessor {
         Ok(response)
     }
 
+    /// Answer a CORS preflight at the gateway when the route asks for it; None for every other
+    /// request, which is forwarded as usual
+    pub fn preflight_response(
+        &self,
+        req: &Request<Body>,
+        cors_config: &CorsConfig
+    ) -> Option<Response<Body>> {
+        if !cors_config.handle_preflight || !is_preflight(req) {
+            return None;
+        }
+        let origin = req.headers().get(header::ORIGIN)?;
+
+        let mut response = Response::new(Body::empty());
+        if !origin.to_str().is_ok_and(|origin| cors_config.allows_origin(origin)) {
+            tracing::debug!(origin = ?origin, "Rejected CORS preflight from disallowed origin");
+            *response.status_mut() = StatusCode::FORBIDDEN;
+            return Some(response);
+        }
+        *response.status_mut() = StatusCode::NO_CONTENT;
+
+        let headers = response.headers_mut();
+        set_allow_origin(headers, origin, cors_config);
+        if let Ok(methods) = HeaderValue::from_str(&cors_config.allowed_methods.join(", ")) {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, methods);
+        }
+
+        // Without a configured list, allow whatever headers the browser asked for
+        let allowed_headers = if cors_config.allowed_headers.is_empty() {
+            req.headers().get(header::ACCESS_CONTROL_REQUEST_HEADERS).cloned()
+        } else {
+            HeaderValue::from_str(&cors_config.allowed_headers.join(", ")).ok()
+        };
+        if let Some(allowed_headers) = allowed_headers {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, allowed_headers);
+        }
+
+        if let Some(max_age) = cors_config.max_age {
+            headers.insert(header::ACCESS_CONTROL_MAX_AGE, HeaderValue::from(max_age));
+        }
+
+        tracing::debug!(origin = ?origin, "Answered CORS preflight at the gateway");
+        Some(response)
+    }
+
+    /// Add CORS headers to a forwarded response when the request came from an allowed origin
+    pub fn apply_cors_headers(
+        &self,
+        mut response: Response<Body>,
+        origin: Option<&HeaderValue>,
+        cors_config: &CorsConfig
+    ) -> Response<Body> {
+        let Some(origin) = origin else {
+            return response;
+        };
+        if !origin.to_str().is_ok_and(|origin| cors_config.allows_origin(origin)) {
+            return response;
+        }
+
+        let headers = response.headers_mut();
+        set_allow_origin(headers, origin, cors_config);
+        if !cors_config.exposed_headers.is_empty() {
+            if let Ok(exposed) = HeaderValue::from_str(&cors_config.exposed_headers.join(", ")) {
+                headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, exposed);
+            }
+        }
+        response
+    }
+
     /// Check rate limiting for a client
-    fn check_rate_limit(
+    async fn check_rate_limit(
         &self,
         client_id: &str,
         rate_config: &RateLimitConfig
     ) -> Result<(), MiddlewareError> {
-        let mut limiter = self.rate_limiter.lock().unwrap();
-
-        let now = Instant::now();
         let window_duration = Duration::from_secs(rate_config.window_seconds as u64);
 
-        // Check and update request count
-        let (count, window_start) = limiter.request_counts
-            .get(client_id)
-            .copied()
-            .unwrap_or((0, now));
-
-        // Reset window if expired
-        let (new_count, new_window_start) = if now.duration_since(window_start) > window_duration {
-            (1, now)
-        } else {
-            (count + 1, window_start)
+        // Count this request; an unreachable store lets traffic through rather than failing every request
+        let counted = match self.rate_limit_store(rate_config) {
+            Ok(store) => store.increment(client_id, window_duration, rate_config.algorithm).await,
+            Err(e) => Err(e),
+        };
+        let new_count = match counted {
+            Ok(count) => count,
+            Err(e) => {
+                tracing::warn!(
+                    client_id = %client_id,
+                    error = %e,
+                    "Rate limit store unavailable, allowing request"
+                );
+                0
+            }
         };
 
         // Check rate limit
-        if new_count > rate_config.requests_per_minute {
+        if new_count > (rate_config.requests_per_minute as u64) {
             tracing::warn!(
                 client_id = %client_id,
                 count = new_count,
@@ -183,6 +276,7 @@ impl MiddlewareProcessor {
         }
 
         // Check concurrent connections
+        let mut limiter = self.rate_limiter.lock().unwrap();
         let active_count = limiter.active_connections.get(client_id).copied().unwrap_or(0);
         if active_count >= rate_config.max_concurrent {
             tracing::warn!(
@@ -197,12 +291,41 @@ impl MiddlewareProcessor {
         }
 
         // Update counters
-        limiter.request_counts.insert(client_id.to_string(), (new_count, new_window_start));
         limiter.active_connections.insert(client_id.to_string(), active_count + 1);
 
         Ok(())
     }
 
+    /// Store holding request counters for the configured backend
+    fn rate_limit_store(
+        &self,
+        rate_config: &RateLimitConfig
+    ) -> std::io::Result<Arc<dyn RateLimitStore>> {
+        if let Some(store) = &self.store_override {
+            return Ok(store.clone());
+        }
+
+        match (&rate_config.backend, &rate_config.redis_url) {
+            (RateLimitBackend::Memory, _) => Ok(self.memory_store.clone()),
+            (RateLimitBackend::Redis, Some(url)) => {
+                let mut stores = self.redis_stores.lock().unwrap();
+                if let Some(store) = stores.get(url) {
+                    return Ok(store.clone());
+                }
+                let store: Arc<dyn RateLimitStore> = Arc::new(RedisRateLimitStore::new(url)?);
+                stores.insert(url.clone(), store.clone());
+                Ok(store)
+            }
+            (RateLimitBackend::Redis, None) =>
+                Err(
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "Redis rate limit backend requires redis_url"
+                    )
+                ),
+        }
+    }
+
     /// Apply authentication headers to request
     fn apply_auth_headers(
         &self,
@@ -388,16 +511,12 @@ impl MiddlewareProcessor {
         }
 
         // Apply JSON transformations if content-type is JSON
-        if let Some(content_type) = parts.headers.get("content-type") {
-            if let Ok(content_type_str) = content_type.to_str() {
-                if content_type_str.contains("application/json") {
-                    body_string = self.transform_json_body(
-                        body_string,
-                        &transform_config.add_json_fields,
-                        &transform_config.remove_json_fields
-                    )?;
-                }
-            }
+        if is_json_content_type(&parts.headers) {
+            body_string = self.transform_json_body(
+                body_string,
+                &transform_config.add_json_fields,
+                &transform_config.remove_json_fields
+            )?;
         }
 
         let new_body = Body::from(body_string.into_bytes());
@@ -410,7 +529,12 @@ impl MiddlewareProcessor {
         response: Response<Body>,
         transform_config: &ResponseTransformConfig
     ) -> Result<Response<Body>, MiddlewareError> {
-        let (parts, body) = response.into_parts();
+        if crate::is_streamed(&response) {
+            tracing::debug!("Streamed response, skipping body transform");
+            return Ok(response);
+        }
+
+        let (mut parts, body) = response.into_parts();
         let body_bytes = axum::body
             ::to_bytes(body, usize::MAX).await
             .map_err(|e|
@@ -422,6 +546,25 @@ impl MiddlewareProcessor {
             return Ok(Response::from_parts(parts, Body::from(body_bytes)));
         }
 
+        // Transforms work on the decoded body, which stays decoded unless compression re-encodes it
+        let body_bytes = match content_encoding(&parts.headers) {
+            None => body_bytes.to_vec(),
+            Some(encoding) =>
+                match decode_body(&body_bytes, &encoding)? {
+                    Some(decoded) => {
+                        parts.headers.remove(header::CONTENT_ENCODING);
+                        decoded
+                    }
+                    None => {
+                        tracing::warn!(
+                            encoding = %encoding,
+                            "Skipping response transformation of body with unsupported Content-Encoding"
+                        );
+                        return Ok(Response::from_parts(parts, Body::from(body_bytes)));
+                    }
+                }
+        };
+
         let mut body_string = String::from_utf8_lossy(&body_bytes).to_string();
 
         // Apply text replacements
@@ -448,23 +591,21 @@ impl MiddlewareProcessor {
         }
 
         // Apply JSON transformations if content-type is JSON
-        if let Some(content_type) = parts.headers.get("content-type") {
-            if let Ok(content_type_str) = content_type.to_str() {
-                if content_type_str.contains("application/json") {
-                    body_string = self.transform_json_body(
-                        body_string,
-                        &transform_config.add_json_fields,
-                        &transform_config.remove_json_fields
-                    )?;
-                }
-            }
+        if is_json_content_type(&parts.headers) {
+            body_string = self.transform_json_body(
+                body_string,
+                &transform_config.add_json_fields,
+                &transform_config.remove_json_fields
+            )?;
         }
 
-        let new_body = Body::from(body_string.into_bytes());
-        Ok(Response::from_parts(parts, new_body))
+        let new_body = body_string.into_bytes();
+        parts.headers.insert(header::CONTENT_LENGTH, HeaderValue::from(new_body.len()));
+        Ok(Response::from_parts(parts, Body::from(new_body)))
     }
 
-    /// Transform JSON body by adding/removing fields
+    /// Transform JSON body by adding/removing fields at dotted paths (e.g. `user.profile.email`);
+    /// arrays along the path, including a top-level array, have each element transformed
     fn transform_json_body(
         &self,
         body_string: String,
@@ -475,22 +616,27 @@ impl MiddlewareProcessor {
             return Ok(body_string);
         }
 
-        let mut json: Value = serde_json
-            ::from_str(&body_string)
-            .map_err(|e| MiddlewareError::TransformError(format!("Invalid JSON body: {}", e)))?;
+        // A body that only claims to be JSON is forwarded as-is
+        let mut json: Value = match serde_json::from_str(&body_string) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::warn!(error = %e, "Skipping JSON transformation of non-JSON body");
+                return Ok(body_string);
+            }
+        };
 
         // Add fields
-        if let Value::Object(ref mut map) = json {
-            for (key, value) in add_fields {
-                map.insert(key.clone(), value.clone());
-                tracing::debug!(field = %key, "Added JSON field");
-            }
+        for (path, value) in add_fields {
+            let segments: Vec<&str> = path.split('.').collect();
+            set_json_path(&mut json, &segments, value);
+            tracing::debug!(field = %path, "Added JSON field");
+        }
 
-            // Remove fields
-            for key in remove_fields {
-                if map.remove(key).is_some() {
-                    tracing::debug!(field = %key, "Removed JSON field");
-                }
+        // Remove fields
+        for path in remove_fields {
+            let segments: Vec<&str> = path.split('.').collect();
+            if remove_json_path(&mut json, &segments) {
+                tracing::debug!(field = %path, "Removed JSON field");
             }
         }
 
@@ -507,6 +653,16 @@ impl MiddlewareProcessor {
         response: Response<Body>,
         compression_config: &CompressionConfig
     ) -> Result<Response<Body>, MiddlewareError> {
+        // Bodies the backend already encoded (and no transform decoded) are left alone
+        if let Some(encoding) = content_encoding(response.headers()) {
+            tracing::debug!(encoding = %encoding, "Response already encoded, skipping compression");
+            return Ok(response);
+        }
+        if crate::is_streamed(&response) {
+            tracing::debug!("Streamed response, skipping compression");
+            return Ok(response);
+        }
+
         let (parts, body) = response.into_parts();
         let body_bytes = axum::body
             ::to_bytes(body, usize::MAX).await
@@ -586,6 +742,121 @@ impl MiddlewareProcessor {
     }
 }
 
+/// Whether the request is a CORS preflight: OPTIONS with an Origin and the method it is asking about
+fn is_preflight(req: &Request<Body>) -> bool {
+    req.method() == Method::OPTIONS &&
+        req.headers().contains_key(header::ORIGIN) &&
+        req.headers().contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+}
+
+/// Allow the request's origin; "*" when any origin is allowed, which never carries credentials
+fn set_allow_origin(headers: &mut HeaderMap, origin: &HeaderValue, cors_config: &CorsConfig) {
+    if cors_config.allows_any_origin() {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+        return;
+    }
+    // The response depends on the Origin header, so caches must key on it
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+    headers.append(header::VARY, HeaderValue::from_static("origin"));
+    if cors_config.allow_credentials {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+    }
+}
+
+/// Content-Encoding of a body, lowercased; None when it is not encoded
+fn content_encoding(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::CONTENT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_ascii_lowercase())
+        .filter(|encoding| !encoding.is_empty() && encoding != "identity")
+}
+
+/// Decode a gzip or deflate body; None for encodings that cannot be decoded here (e.g. br).
+/// Decoding stops with an error past MAX_DECODED_BODY_BYTES so a small body cannot expand without bound.
+fn decode_body(body: &[u8], encoding: &str) -> Result<Option<Vec<u8>>, MiddlewareError> {
+    use flate2::read::{ MultiGzDecoder, ZlibDecoder };
+    use std::io::Read;
+
+    let mut decoded = Vec::new();
+    let limit = MAX_DECODED_BODY_BYTES + 1;
+    let result = match encoding {
+        "gzip" | "x-gzip" => MultiGzDecoder::new(body).take(limit).read_to_end(&mut decoded),
+        "deflate" => ZlibDecoder::new(body).take(limit).read_to_end(&mut decoded),
+        _ => {
+            return Ok(None);
+        }
+    };
+    result.map_err(|e|
+        MiddlewareError::TransformError(format!("Failed to decode {} response body: {}", encoding, e))
+    )?;
+    if (decoded.len() as u64) > MAX_DECODED_BODY_BYTES {
+        return Err(
+            MiddlewareError::DecodedBodyTooLarge(
+                format!("{} response body exceeds {} bytes once decoded", encoding, MAX_DECODED_BODY_BYTES)
+            )
+        );
+    }
+    Ok(Some(decoded))
+}
+
+/// Whether the headers declare a JSON body (`application/json` or a `+json` media type)
+fn is_json_content_type(headers: &axum::http::HeaderMap) -> bool {
+    let Some(content_type) = headers.get("content-type").and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    let media_type = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    media_type == "application/json" || media_type.ends_with("+json")
+}
+
+/// Set `value` at the path, creating missing objects and descending into every array element
+fn set_json_path(json: &mut Value, segments: &[&str], value: &Value) {
+    let Some((field, rest)) = segments.split_first() else {
+        return;
+    };
+    match json {
+        Value::Array(items) => {
+            for item in items {
+                set_json_path(item, segments, value);
+            }
+        }
+        Value::Object(map) if rest.is_empty() => {
+            map.insert(field.to_string(), value.clone());
+        }
+        Value::Object(map) => {
+            let child = map
+                .entry(field.to_string())
+                .or_insert_with(|| Value::Object(serde_json::Map::new()));
+            set_json_path(child, rest, value);
+        }
+        // Scalars have no fields to set
+        _ => {}
+    }
+}
+
+/// Remove the field at the path from every matching object; returns whether anything was removed
+fn remove_json_path(json: &mut Value, segments: &[&str]) -> bool {
+    let Some((field, rest)) = segments.split_first() else {
+        return false;
+    };
+    match json {
+        Value::Array(items) => {
+            let mut removed = false;
+            for item in items {
+                removed |= remove_json_path(item, segments);
+            }
+            removed
+        }
+        Value::Object(map) if rest.is_empty() => map.remove(*field).is_some(),
+        Value::Object(map) =>
+            match map.get_mut(*field) {
+                Some(child) => remove_json_path(child, rest),
+                None => false,
+            }
+        _ => false,
+    }
+}
+
 impl Default for MiddlewareProcessor {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,321 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    io::{ Error, ErrorKind },
+    pin::Pin,
+    sync::Mutex,
+    time::{ Duration, Instant, SystemTime, UNIX_EPOCH },
+};
+use tokio::io::{ AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufStream };
+use tokio::net::TcpStream;
+use httpserver_config::RateLimitAlgorithm;
+
+/// Future returned by [`RateLimitStore::increment`]
+pub type RateLimitFuture<'a> = Pin<Box<dyn Future<Output = std::io::Result<u64>> + Send + 'a>>;
+
+/// Storage for rate limit request counters
+pub trait RateLimitStore: Send + Sync {
+    /// Count one request for `key` and return how many requests the current window holds
+    fn increment<'a>(
+        &'a self,
+        key: &'a str,
+        window: Duration,
+        algorithm: RateLimitAlgorithm
+    ) -> RateLimitFuture<'a>;
+}
+
+/// Index of the clock-aligned window containing now, and the fraction of it already elapsed
+fn window_position(window: Duration) -> (u64, f64) {
+    let window_ms = window.as_millis().max(1) as u64;
+    let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+    (now_ms / window_ms, ((now_ms % window_ms) as f64) / (window_ms as f64))
+}
+
+/// Sliding-window estimate: the previous window counts in proportion to its remaining overlap
+fn sliding_estimate(current: u64, previous: u64, elapsed: f64) -> u64 {
+    current + ((previous as f64) * (1.0 - elapsed)).ceil() as u64
+}
+
+/// Counters kept in process memory; each server instance enforces its own limit
+#[derive(Debug, Default)]
+pub struct MemoryRateLimitStore {
+    /// Fixed-window counters (key -> (count, window_start))
+    counts: Mutex<HashMap<String, (u64, Instant)>>,
+
+    /// Sliding-window counters (key -> (window_index, current, previous))
+    sliding_counts: Mutex<HashMap<String, (u64, u64, u64)>>,
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn increment_fixed(&self, key: &str, window: Duration) -> u64 {
+        let mut counts = self.counts.lock().unwrap();
+        let now = Instant::now();
+
+        let (count, window_start) = counts.get(key).copied().unwrap_or((0, now));
+
+        // Reset window if expired
+        let entry = if now.duration_since(window_start) > window {
+            (1, now)
+        } else {
+            (count + 1, window_start)
+        };
+        counts.insert(key.to_string(), entry);
+        entry.0
+    }
+
+    fn increment_sliding(&self, key: &str, window: Duration) -> u64 {
+        let mut counts = self.sliding_counts.lock().unwrap();
+        let (index, elapsed) = window_position(window);
+
+        let (window_index, current, previous) = counts
+            .entry(key.to_string())
+            .or_insert((index, 0, 0));
+        if *window_index != index {
+            // Roll over; the old count only matters if it was the window just before this one
+            *previous = if *window_index + 1 == index { *current } else { 0 };
+            *current = 0;
+            *window_index = index;
+        }
+        *current += 1;
+
+        sliding_estimate(*current, *previous, elapsed)
+    }
+}
+
+impl RateLimitStore for MemoryRateLimitStore {
+    fn increment<'a>(
+        &'a self,
+        key: &'a str,
+        window: Duration,
+        algorithm: RateLimitAlgorithm
+    ) -> RateLimitFuture<'a> {
+        let count = match algorithm {
+            RateLimitAlgorithm::FixedWindow => self.increment_fixed(key, window),
+            RateLimitAlgorithm::SlidingWindow => self.increment_sliding(key, window),
+        };
+        Box::pin(std::future::ready(Ok(count)))
+    }
+}
+
+/// Upper bound on a single Redis round trip so a stalled server cannot hold requests
+const REDIS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Counters kept in Redis so every instance behind a load balancer shares one limit
+pub struct RedisRateLimitStore {
+    /// host:port of the Redis server
+    address: String,
+    username: Option<String>,
+    password: Option<String>,
+    database: u32,
+    /// Open connection, re-established after any error
+    connection: tokio::sync::Mutex<Option<BufStream<TcpStream>>>,
+}
+
+impl RedisRateLimitStore {
+    /// Create a store for a `redis://[[user]:password@]host[:port][/db]` URL; connects lazily
+    pub fn new(url: &str) -> std::io::Result<Self> {
+        let invalid = |msg: String| Error::new(ErrorKind::InvalidInput, msg);
+
+        let parsed = reqwest::Url
+            ::parse(url)
+            .map_err(|e| invalid(format!("Invalid Redis URL {}: {}", url, e)))?;
+        if parsed.scheme() != "redis" {
+            return Err(invalid(format!("Redis URL must use the redis:// scheme: {}", url)));
+        }
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| invalid(format!("Redis URL has no host: {}", url)))?;
+
+        let database = match parsed.path().trim_start_matches('/') {
+            "" => 0,
+            db => db.parse().map_err(|_| invalid(format!("Invalid Redis database: {}", db)))?,
+        };
+
+        Ok(Self {
+            address: format!("{}:{}", host, parsed.port().unwrap_or(6379)),
+            username: Some(parsed.username().to_string()).filter(|user| !user.is_empty()),
+            password: parsed.password().map(str::to_string),
+            database,
+            connection: tokio::sync::Mutex::new(None),
+        })
+    }
+
+    /// Open a connection and run AUTH/SELECT as configured
+    async fn connect(&self) -> std::io::Result<BufStream<TcpStream>> {
+        let mut stream = BufStream::new(TcpStream::connect(&self.address).await?);
+
+        if let Some(password) = &self.password {
+            match &self.username {
+                Some(username) => send_command(&mut stream, &["AUTH", username, password]).await?,
+                None => send_command(&mut stream, &["AUTH", password]).await?,
+            }
+            read_reply(&mut stream).await?;
+        }
+        if self.database != 0 {
+            send_command(&mut stream, &["SELECT", &self.database.to_string()]).await?;
+            read_reply(&mut stream).await?;
+        }
+
+        Ok(stream)
+    }
+
+    /// INCR the window's key and set its expiry in one MULTI/EXEC transaction; the sliding
+    /// window also reads the previous window's count
+    async fn increment_window(
+        &self,
+        key: &str,
+        window: Duration,
+        algorithm: RateLimitAlgorithm
+    ) -> std::io::Result<u64> {
+        // Windows are aligned to the clock so every instance agrees on the current one
+        let (index, elapsed) = window_position(window);
+        let redis_key = format!("httpserver:ratelimit:{}:{}", key, index);
+        let previous_key = format!("httpserver:ratelimit:{}:{}", key, index.saturating_sub(1));
+
+        // A sliding window still needs the current count once the next window starts
+        let window_secs = window.as_secs().max(1);
+        let ttl = match algorithm {
+            RateLimitAlgorithm::FixedWindow => window_secs,
+            RateLimitAlgorithm::SlidingWindow => window_secs * 2,
+        }.to_string();
+
+        let mut connection = self.connection.lock().await;
+        let exchange = async {
+            if connection.is_none() {
+                *connection = Some(self.connect().await?);
+            }
+            let stream = connection.as_mut().unwrap();
+
+            send_command(stream, &["MULTI"]).await?;
+            send_command(stream, &["INCR", &redis_key]).await?;
+            send_command(stream, &["EXPIRE", &redis_key, &ttl]).await?;
+            let sliding = algorithm == RateLimitAlgorithm::SlidingWindow;
+            if sliding {
+                send_command(stream, &["GET", &previous_key]).await?;
+            }
+            send_command(stream, &["EXEC"]).await?;
+
+            // +OK and one +QUEUED per command, then the array of results
+            let queued = if sliding { 3 } else { 2 };
+            for _ in 0..=queued {
+                read_reply(stream).await?;
+            }
+            let Reply::Array(results) = read_reply(stream).await? else {
+                return Err(protocol_error("EXEC did not return an array"));
+            };
+            let Some(Reply::Integer(current)) = results.first() else {
+                return Err(protocol_error("INCR did not return an integer"));
+            };
+            let current = *current as u64;
+
+            if !sliding {
+                return Ok(current);
+            }
+            let previous = match results.get(2) {
+                Some(Reply::Bulk(Some(value))) =>
+                    std::str
+                        ::from_utf8(value)
+                        .ok()
+                        .and_then(|value| value.parse().ok())
+                        .ok_or_else(|| protocol_error("previous window count is not an integer"))?,
+                _ => 0,
+            };
+            Ok(sliding_estimate(current, previous, elapsed))
+        };
+        let result = tokio::time
+            ::timeout(REDIS_TIMEOUT, exchange).await
+            .unwrap_or_else(|_| Err(Error::new(ErrorKind::TimedOut, "Redis request timed out")));
+
+        // Never reuse a connection whose protocol state is unknown
+        if result.is_err() {
+            *connection = None;
+        }
+        result
+    }
+}
+
+impl RateLimitStore for RedisRateLimitStore {
+    fn increment<'a>(
+        &'a self,
+        key: &'a str,
+        window: Duration,
+        algorithm: RateLimitAlgorithm
+    ) -> RateLimitFuture<'a> {
+        Box::pin(self.increment_window(key, window, algorithm))
+    }
+}
+
+/// RESP reply values used by the rate limiter
+enum Reply {
+    Simple,
+    Integer(i64),
+    Bulk(Option<Vec<u8>>),
+    Array(Vec<Reply>),
+}
+
+fn protocol_error(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("Redis protocol error: {}", msg))
+}
+
+/// Write a command as a RESP array of bulk strings (buffered until flushed)
+async fn send_command(stream: &mut BufStream<TcpStream>, args: &[&str]) -> std::io::Result<()> {
+    let mut command = format!("*{}\r\n", args.len());
+    for arg in args {
+        command.push_str(&format!("${}\r\n{}\r\n", arg.len(), arg));
+    }
+    stream.write_all(command.as_bytes()).await
+}
+
+async fn read_line(stream: &mut BufStream<TcpStream>) -> std::io::Result<String> {
+    let mut line = String::new();
+    if stream.read_line(&mut line).await? == 0 {
+        return Err(Error::new(ErrorKind::UnexpectedEof, "Redis closed the connection"));
+    }
+    Ok(line.trim_end_matches("\r\n").to_string())
+}
+
+/// Flush pending commands and read one reply; arrays may only contain scalar replies
+async fn read_reply(stream: &mut BufStream<TcpStream>) -> std::io::Result<Reply> {
+    stream.flush().await?;
+    let line = read_line(stream).await?;
+
+    let Some(len) = line.strip_prefix('*') else {
+        return parse_scalar(stream, &line).await;
+    };
+    let len: i64 = len.parse().map_err(|_| protocol_error("invalid array length"))?;
+    let mut items = Vec::new();
+    for _ in 0..len.max(0) {
+        let line = read_line(stream).await?;
+        items.push(parse_scalar(stream, &line).await?);
+    }
+    Ok(Reply::Array(items))
+}
+
+async fn parse_scalar(stream: &mut BufStream<TcpStream>, line: &str) -> std::io::Result<Reply> {
+    let (kind, value) = line.split_at(line.len().min(1));
+    match kind {
+        "+" => Ok(Reply::Simple),
+        "-" => Err(Error::other(format!("Redis error: {}", value))),
+        ":" =>
+            value
+                .parse()
+                .map(Reply::Integer)
+                .map_err(|_| protocol_error("invalid integer")),
+        "$" => {
+            let len: i64 = value.parse().map_err(|_| protocol_error("invalid bulk length"))?;
+            if len < 0 {
+                return Ok(Reply::Bulk(None));
+            }
+            // Payload plus trailing CRLF
+            let mut payload = vec![0; (len as usize) + 2];
+            stream.read_exact(&mut payload).await?;
+            payload.truncate(len as usize);
+            Ok(Reply::Bulk(Some(payload)))
+        }
+        _ => Err(protocol_error("unexpected reply type")),
+    }
+}
@@ -0,0 +1,64 @@
+// Backend connectivity self-test run once at startup
+use httpserver_config::{ HttpHealthConfig, ProxyRoute };
+use std::time::Duration;
+use tokio::net::TcpStream;
+
+use crate::{ http_health::HttpHealthChecker, tcp_health::target_address };
+
+/// Outcome of probing one target
+#[derive(Debug, Clone)]
+pub struct TargetCheck {
+    pub url: String,
+    /// Why the target could not be reached; None when it was
+    pub error: Option<String>,
+}
+
+/// Outcome of probing every target of one route
+#[derive(Debug, Clone)]
+pub struct RouteCheck {
+    pub route: String,
+    pub targets: Vec<TargetCheck>,
+}
+
+impl RouteCheck {
+    /// Whether at least one target answered
+    pub fn has_reachable_target(&self) -> bool {
+        self.targets.iter().any(|target| target.error.is_none())
+    }
+}
+
+/// Probe each target of a route once, concurrently: routes with an `http_health` path are sent
+/// a health check request, others only need to accept a TCP connection
+pub async fn check_route(route: &ProxyRoute, timeout: Duration) -> RouteCheck {
+    let probes = route.get_targets().into_iter().map(|target| async move {
+        let error = match &route.http_health {
+            Some(health) => check_http(health, &target.url, timeout).await,
+            None => check_tcp(&target.url, timeout).await,
+        };
+        TargetCheck { url: target.url, error }
+    });
+    RouteCheck {
+        route: route.path.clone(),
+        targets: futures_util::future::join_all(probes).await,
+    }
+}
+
+async fn check_http(health: &HttpHealthConfig, target_url: &str, timeout: Duration) -> Option<String> {
+    let checker = HttpHealthChecker::new(HttpHealthConfig { timeout: timeout.as_secs().max(1), ..health.clone() });
+    if checker.check_health(target_url).await {
+        None
+    } else {
+        Some(format!("health check at {} failed", health.path))
+    }
+}
+
+async fn check_tcp(target_url: &str, timeout: Duration) -> Option<String> {
+    let Some(address) = target_address(target_url) else {
+        return Some("target has no host and port".to_string());
+    };
+    match tokio::time::timeout(timeout, TcpStream::connect(&address)).await {
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some(format!("no connection within {}s", timeout.as_secs())),
+    }
+}
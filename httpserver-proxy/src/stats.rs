@@ -0,0 +1,123 @@
+use std::sync::{ Arc, atomic::{ AtomicU64, Ordering } };
+use std::time::Instant;
+
+/// Upper bounds in milliseconds of the latency histogram buckets; slower requests land in one overflow bucket
+const LATENCY_BUCKET_BOUNDS_MS: [u64; 18] = [
+    1, 2, 5, 10, 20, 30, 50, 75, 100, 150, 250, 500, 750, 1000, 2500, 5000, 10000, 30000,
+];
+
+/// Live request counters for one proxy route, updated without locks
+#[derive(Debug, Default)]
+pub struct RouteStats {
+    /// Requests received since the route was added
+    requests: AtomicU64,
+    /// Requests still waiting for their response
+    in_flight: AtomicU64,
+    /// Requests that failed in the proxy or got a 5xx response
+    errors: AtomicU64,
+    /// Slowest completed request in milliseconds
+    max_latency_ms: AtomicU64,
+    /// Completed requests per latency bucket, plus the overflow bucket
+    latency_buckets: [AtomicU64; LATENCY_BUCKET_BOUNDS_MS.len() + 1],
+}
+
+impl RouteStats {
+    /// Create counters starting at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a new request; it stays in flight until the returned timer is finished or dropped
+    pub fn start_request(self: &Arc<Self>) -> RequestTimer {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        RequestTimer { stats: self.clone(), started: Instant::now() }
+    }
+
+    /// Requests received, including those still in flight
+    pub fn requests(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    /// Requests waiting for their response
+    pub fn in_flight(&self) -> u64 {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Requests that failed in the proxy or got a 5xx response
+    pub fn errors(&self) -> u64 {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    /// Latency in milliseconds below which `percentile` (0-100) of completed requests fell
+    ///
+    /// Reported at bucket resolution, capped by the slowest request seen; None before any request completes.
+    pub fn latency_percentile_ms(&self, percentile: f64) -> Option<u64> {
+        let counts: Vec<u64> = self.latency_buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect();
+        let total: u64 = counts.iter().sum();
+        if total == 0 {
+            return None;
+        }
+
+        let rank = ((percentile / 100.0) * (total as f64)).ceil().max(1.0) as u64;
+        let max_latency_ms = self.max_latency_ms.load(Ordering::Relaxed);
+        let mut seen = 0;
+        for (index, count) in counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let bound = LATENCY_BUCKET_BOUNDS_MS.get(index).copied().unwrap_or(u64::MAX);
+                return Some(bound.min(max_latency_ms));
+            }
+        }
+        Some(max_latency_ms)
+    }
+
+    /// JSON form served by the /proxy/stats endpoint
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "requests": self.requests(),
+            "in_flight": self.in_flight(),
+            "errors": self.errors(),
+            "latency_ms": {
+                "p50": self.latency_percentile_ms(50.0),
+                "p95": self.latency_percentile_ms(95.0),
+            },
+        })
+    }
+
+    /// Add a completed request to the latency histogram
+    fn record_latency(&self, latency_ms: u64) {
+        let bucket = LATENCY_BUCKET_BOUNDS_MS.iter()
+            .position(|bound| latency_ms <= *bound)
+            .unwrap_or(LATENCY_BUCKET_BOUNDS_MS.len());
+        self.latency_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.max_latency_ms.fetch_max(latency_ms, Ordering::Relaxed);
+    }
+}
+
+/// An in-flight request of a route, started by `RouteStats::start_request`
+#[derive(Debug)]
+pub struct RequestTimer {
+    stats: Arc<RouteStats>,
+    started: Instant,
+}
+
+impl RequestTimer {
+    /// Record the request's latency and whether it failed
+    pub fn finish(self, is_error: bool) {
+        if is_error {
+            self.stats.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        self.stats.record_latency(self.started.elapsed().as_millis() as u64);
+    }
+}
+
+impl Drop for RequestTimer {
+    fn drop(&mut self) {
+        // Cancelled requests leave the in-flight count without a latency sample
+        self.stats.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
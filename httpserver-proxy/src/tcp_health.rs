@@ -0,0 +1,100 @@
+// TCP health check implementation
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use httpserver_config::TcpHealthConfig;
+use tracing;
+
+/// TCP health checker: a target is healthy when its host:port accepts a connection
+pub struct TcpHealthChecker {
+    config: TcpHealthConfig,
+}
+
+impl TcpHealthChecker {
+    pub fn new(config: TcpHealthConfig) -> Self {
+        Self { config }
+    }
+
+    /// Check if a target accepts TCP connections within the timeout
+    #[tracing::instrument(skip(self), fields(timeout = self.config.timeout))]
+    pub async fn check_health(&self, target_url: &str) -> bool {
+        let Some(address) = target_address(target_url) else {
+            tracing::warn!(target_url = %target_url, "TCP health check target has no host and port");
+            return false;
+        };
+
+        match timeout(Duration::from_secs(self.config.timeout), TcpStream::connect(&address)).await {
+            Ok(Ok(_)) => {
+                tracing::debug!(address = %address, "TCP health check OK");
+                true
+            }
+            Ok(Err(e)) => {
+                tracing::warn!(
+                    address = %address,
+                    error = %e,
+                    "TCP health check failed"
+                );
+                false
+            }
+            Err(_) => {
+                tracing::warn!(
+                    address = %address,
+                    timeout = self.config.timeout,
+                    "TCP health check timeout"
+                );
+                false
+            }
+        }
+    }
+}
+
+/// host:port to connect to for a target URL, using the scheme's default port when none is given
+pub(crate) fn target_address(target_url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(target_url).ok()?;
+    let host = url.host_str()?;
+    let port = url.port_or_known_default()?;
+    Some(format!("{}:{}", host, port))
+}
+
+/// Background TCP health checker that runs periodic checks
+pub struct TcpHealthMonitor {
+    checker: TcpHealthChecker,
+    targets: Vec<String>,
+}
+
+impl TcpHealthMonitor {
+    pub fn new(config: TcpHealthConfig, targets: Vec<String>) -> Self {
+        Self {
+            checker: TcpHealthChecker::new(config),
+            targets,
+        }
+    }
+
+    /// Start background health monitoring with callback for health status updates
+    pub async fn start_monitoring_with_callback<F>(
+        &self,
+        health_callback: F
+    ) -> tokio::task::JoinHandle<()>
+        where F: Fn(&str, bool) + Send + Sync + 'static
+    {
+        let checker = TcpHealthChecker::new(self.checker.config.clone());
+        let targets = self.targets.clone();
+        let interval = Duration::from_secs(self.checker.config.interval);
+
+        tokio::spawn(async move {
+            let mut interval_timer = tokio::time::interval(interval);
+
+            loop {
+                interval_timer.tick().await;
+
+                for target in &targets {
+                    let is_healthy = checker.check_health(target).await;
+                    tracing::debug!(target = %target, healthy = is_healthy, "TCP health check result");
+
+                    // Update load balancer target health status via callback
+                    health_callback(target, is_healthy);
+                }
+            }
+        })
+    }
+}
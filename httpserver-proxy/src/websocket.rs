@@ -0,0 +1,335 @@
+use futures_util::{ sink::SinkExt, stream::{ SplitSink, SplitStream, StreamExt }, Sink, Stream };
+use httpserver_config::{ ProxyRoute, WebSocketLimitsConfig };
+use std::{ sync::Arc, time::{ Duration, Instant } };
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio::time::sleep_until;
+use tokio_tungstenite::{
+    connect_async_tls_with_config,
+    tungstenite::{
+        protocol::{ frame::coding::CloseCode, CloseFrame, WebSocketConfig },
+        Error as WsError,
+        Message,
+    },
+    Connector,
+    MaybeTlsStream,
+    WebSocketStream,
+};
+
+use crate::{ events, is_certificate_rejection, BackendTlsSettings, ProxyError };
+
+/// Payload of the relay's own keepalive pings; pongs carrying it are not forwarded
+const KEEPALIVE_PAYLOAD: &[u8] = b"httpserver-keepalive";
+
+/// How long the relay keeps running after a close frame so the peer's reply can be relayed back
+const CLOSE_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Read limits for a route's WebSocket streams, so oversized frames and messages are refused
+/// before they are buffered; None when the route sets no size limit
+pub fn websocket_config(limits: Option<&WebSocketLimitsConfig>) -> Option<WebSocketConfig> {
+    let max_bytes = limits?.max_frame_bytes?;
+    Some(WebSocketConfig {
+        max_message_size: Some(max_bytes),
+        max_frame_size: Some(max_bytes),
+        ..WebSocketConfig::default()
+    })
+}
+
+/// Close frame for a message over the size limit
+fn message_too_large() -> CloseFrame<'static> {
+    CloseFrame { code: CloseCode::Size, reason: "Message too large".into() }
+}
+
+/// Enforces a route's message size and rate limits on one direction of a connection.
+/// Streams opened with `websocket_config` already refuse oversized messages while reading;
+/// the size check here covers streams opened without it.
+#[derive(Debug)]
+pub struct MessageLimiter {
+    limits: WebSocketLimitsConfig,
+    /// Start of the current one-second window
+    window_start: Instant,
+    /// Messages seen in the current window
+    count: u32,
+}
+
+impl MessageLimiter {
+    pub fn new(limits: WebSocketLimitsConfig) -> Self {
+        Self { limits, window_start: Instant::now(), count: 0 }
+    }
+
+    /// Check a message against the limits, returning the close frame to send when it breaks one
+    pub fn check(&mut self, message: &Message) -> Result<(), CloseFrame<'static>> {
+        // Close frames always pass so the connection can shut down cleanly
+        if matches!(message, Message::Close(_) | Message::Frame(_)) {
+            return Ok(());
+        }
+
+        if let Some(max_bytes) = self.limits.max_frame_bytes {
+            if message.len() > max_bytes {
+                return Err(message_too_large());
+            }
+        }
+
+        if let Some(max_per_second) = self.limits.max_messages_per_second {
+            let now = Instant::now();
+            if now.duration_since(self.window_start) >= Duration::from_secs(1) {
+                self.window_start = now;
+                self.count = 0;
+            }
+            self.count += 1;
+            if self.count > max_per_second {
+                return Err(CloseFrame {
+                    code: CloseCode::Policy,
+                    reason: "Message rate limit exceeded".into(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Open the backend side of a proxied WebSocket. `wss://` targets follow the route's
+/// `verify_backend_ssl` and client certificate settings, like HTTP forwarding does.
+pub async fn connect_backend(
+    route: &ProxyRoute,
+    target_url: &str
+) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, ProxyError> {
+    let connector = match BackendTlsSettings::from_route(route) {
+        Some(settings) if target_url.starts_with("wss://") =>
+            Some(Connector::Rustls(Arc::new(settings.rustls_config()?))),
+        _ => None,
+    };
+
+    let config = websocket_config(route.websocket_limits.as_ref());
+    match connect_async_tls_with_config(target_url, config, false, connector).await {
+        Ok((stream, _)) => {
+            tracing::info!(
+                event = events::BACKEND_CONNECTED,
+                target = %target_url,
+                protocol = "websocket",
+                "Backend connection opened"
+            );
+            Ok(stream)
+        }
+        Err(e) => {
+            tracing::warn!(
+                event = events::BACKEND_CONNECT_FAILED,
+                target = %target_url,
+                protocol = "websocket",
+                error = %e,
+                "Backend connection failed"
+            );
+            if is_certificate_rejection(&e) {
+                Err(ProxyError::BackendCertificate(target_url.to_string()))
+            } else {
+                Err(ProxyError::ConnectionFailed(target_url.to_string()))
+            }
+        }
+    }
+}
+
+/// Relay messages between a client and a backend WebSocket until either side closes.
+/// Close frames are relayed with their code and reason, and the peer's reply is relayed back
+/// so the closing side completes its handshake. With limits set, a side that sends an oversized message or too many messages per second
+/// is sent a close frame (1009 or 1008) and the other side is closed with the same code;
+/// idle connections are closed with 1001 and may be kept alive with periodic pings.
+pub async fn relay_websocket<C, B>(client: C, backend: B, limits: Option<WebSocketLimitsConfig>)
+    where
+        C: Stream<Item = Result<Message, WsError>> + Sink<Message, Error = WsError> + Send + 'static,
+        B: Stream<Item = Result<Message, WsError>> + Sink<Message, Error = WsError> + Send + 'static
+{
+    let (client_sink, client_stream) = client.split();
+    let (backend_sink, backend_stream) = backend.split();
+
+    // Both directions may need to close either side, so the sinks are shared
+    let client_sink = Arc::new(Mutex::new(client_sink));
+    let backend_sink = Arc::new(Mutex::new(backend_sink));
+
+    // Time of the last message in either direction
+    let last_activity = Arc::new(std::sync::Mutex::new(Instant::now()));
+
+    // Task 1: Forward messages from client to backend
+    let mut client_to_backend = tokio::spawn(
+        forward(
+            client_stream,
+            client_sink.clone(),
+            backend_sink.clone(),
+            limits.clone().map(MessageLimiter::new),
+            last_activity.clone(),
+            "client"
+        )
+    );
+
+    // Task 2: Forward messages from backend to client
+    let mut backend_to_client = tokio::spawn(
+        forward(
+            backend_stream,
+            backend_sink.clone(),
+            client_sink.clone(),
+            limits.clone().map(MessageLimiter::new),
+            last_activity.clone(),
+            "backend"
+        )
+    );
+
+    let idle_timeout = limits.as_ref().and_then(|l| l.idle_timeout_secs).map(Duration::from_secs);
+    let ping_interval = limits.as_ref().and_then(|l| l.ping_interval_secs).map(Duration::from_secs);
+
+    // Wait for either task to complete (connection closed, error or idle timeout)
+    let closing = tokio::select! {
+        result = &mut client_to_backend => {
+            tracing::info!("Client to backend connection closed");
+            result.unwrap_or(false)
+        }
+        result = &mut backend_to_client => {
+            tracing::info!("Backend to client connection closed");
+            result.unwrap_or(false)
+        }
+        _ = watch_idle(idle_timeout, ping_interval, last_activity, client_sink, backend_sink) => {
+            tracing::info!("WebSocket connection closed after idle timeout");
+            true
+        }
+    };
+
+    // After a close frame, keep relaying until the close replies have passed through
+    if closing {
+        let replies = async {
+            if !client_to_backend.is_finished() {
+                let _ = (&mut client_to_backend).await;
+            }
+            if !backend_to_client.is_finished() {
+                let _ = (&mut backend_to_client).await;
+            }
+        };
+        if tokio::time::timeout(CLOSE_HANDSHAKE_TIMEOUT, replies).await.is_err() {
+            tracing::debug!("WebSocket close handshake timed out");
+        }
+    }
+    client_to_backend.abort();
+    backend_to_client.abort();
+}
+
+/// Send keepalive pings and return once the connection has been idle for `idle_timeout`,
+/// after closing both sides; never returns when neither is configured
+async fn watch_idle<C, B>(
+    idle_timeout: Option<Duration>,
+    ping_interval: Option<Duration>,
+    last_activity: Arc<std::sync::Mutex<Instant>>,
+    client_sink: Arc<Mutex<SplitSink<C, Message>>>,
+    backend_sink: Arc<Mutex<SplitSink<B, Message>>>
+)
+    where C: Sink<Message, Error = WsError>, B: Sink<Message, Error = WsError>
+{
+    if idle_timeout.is_none() && ping_interval.is_none() {
+        return std::future::pending().await;
+    }
+
+    let mut next_ping = ping_interval.map(|interval| Instant::now() + interval);
+    loop {
+        let idle_deadline = idle_timeout.map(|timeout| *last_activity.lock().unwrap() + timeout);
+        let wake_at = match (idle_deadline, next_ping) {
+            (Some(idle), Some(ping)) => idle.min(ping),
+            (Some(deadline), None) | (None, Some(deadline)) => deadline,
+            (None, None) => unreachable!(),
+        };
+        sleep_until(wake_at.into()).await;
+
+        let now = Instant::now();
+        if let Some(timeout) = idle_timeout {
+            // Activity may have moved the deadline while sleeping
+            if now.duration_since(*last_activity.lock().unwrap()) >= timeout {
+                let close = CloseFrame { code: CloseCode::Away, reason: "Idle timeout".into() };
+                let _ = client_sink.lock().await.send(Message::Close(Some(close.clone()))).await;
+                let _ = backend_sink.lock().await.send(Message::Close(Some(close))).await;
+                return;
+            }
+        }
+        if let (Some(interval), Some(ping_at)) = (ping_interval, next_ping) {
+            if now >= ping_at {
+                let ping = Message::Ping(KEEPALIVE_PAYLOAD.to_vec());
+                let _ = client_sink.lock().await.send(ping.clone()).await;
+                let _ = backend_sink.lock().await.send(ping).await;
+                next_ping = Some(now + interval);
+            }
+        }
+    }
+}
+
+/// Forward one direction; `source_sink` lets a limit violation be reported back to the sender.
+/// Returns true when it stopped after sending a close frame rather than on an error.
+async fn forward<S, T>(
+    mut source: SplitStream<S>,
+    source_sink: Arc<Mutex<SplitSink<S, Message>>>,
+    destination: Arc<Mutex<SplitSink<T, Message>>>,
+    mut limiter: Option<MessageLimiter>,
+    last_activity: Arc<std::sync::Mutex<Instant>>,
+    side: &'static str
+) -> bool
+    where
+        S: Stream<Item = Result<Message, WsError>> + Sink<Message, Error = WsError>,
+        T: Sink<Message, Error = WsError>
+{
+    while let Some(message) = source.next().await {
+        let message = match message {
+            Ok(message) => message,
+            Err(WsError::Capacity(e)) => {
+                // The stream's read limits refused the message before buffering it
+                tracing::warn!(side = side, error = %e, "WebSocket limit exceeded, closing connection");
+                let _ = source_sink.lock().await.send(Message::Close(Some(message_too_large()))).await;
+                let _ = destination.lock().await.send(Message::Close(Some(message_too_large()))).await;
+                return true;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, side = side, "Error receiving WebSocket message");
+                return false;
+            }
+        };
+        *last_activity.lock().unwrap() = Instant::now();
+
+        if let Some(Err(close)) = limiter.as_mut().map(|limiter| limiter.check(&message)) {
+            tracing::warn!(
+                side = side,
+                code = u16::from(close.code),
+                reason = %close.reason,
+                "WebSocket limit exceeded, closing connection"
+            );
+            let _ = source_sink.lock().await.send(Message::Close(Some(close.clone()))).await;
+            let _ = destination.lock().await.send(Message::Close(Some(close))).await;
+            return true;
+        }
+
+        match message {
+            Message::Close(frame) => {
+                tracing::info!(
+                    side = side,
+                    code = frame.as_ref().map(|frame| u16::from(frame.code)),
+                    "WebSocket close initiated"
+                );
+                let mut destination = destination.lock().await;
+                if destination.send(Message::Close(frame)).await.is_err() {
+                    // The destination closed first; this is its reply, so flush our queued answer
+                    let _ = destination.flush().await;
+                }
+                return true;
+            }
+            Message::Frame(_) => {
+                // Reads reassemble continuation frames into whole Text/Binary messages before
+                // they get here, so fragmented messages are relayed intact; raw frames only exist
+                // for writing, and relaying one would corrupt the destination's own framing
+                tracing::debug!(side = side, "Ignoring raw WebSocket frame");
+            }
+            Message::Pong(ref payload) if payload.as_slice() == KEEPALIVE_PAYLOAD => {
+                // Answer to our keepalive; it only needed to count as activity
+            }
+            message => {
+                tracing::debug!(side = side, size = message.len(), "Forwarding WebSocket message");
+                if let Err(e) = destination.lock().await.send(message).await {
+                    tracing::error!(error = %e, side = side, "Error forwarding WebSocket message");
+                    return false;
+                }
+            }
+        }
+    }
+    false
+}
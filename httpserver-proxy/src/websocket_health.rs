@@ -78,46 +78,63 @@ impl WebSocketHealthChecker {
                     return false;
                 }
 
-                // Wait for pong or any response
-                match ws_stream.next().await {
-                    Some(Ok(TungsteniteMessage::Text(response))) => {
-                        tracing::info!(
-                            ws_url = %ws_url,
-                            response = %response,
-                            "WebSocket health check OK (text response)"
-                        );
-                        true
+                // Wait for the echo or a pong; pings from the server are answered automatically
+                let is_healthy = loop {
+                    match ws_stream.next().await {
+                        Some(Ok(TungsteniteMessage::Text(response))) => {
+                            tracing::info!(
+                                ws_url = %ws_url,
+                                response = %response,
+                                "WebSocket health check OK (text response)"
+                            );
+                            break true;
+                        }
+                        Some(Ok(TungsteniteMessage::Binary(_))) => {
+                            tracing::info!(
+                                ws_url = %ws_url,
+                                "WebSocket health check OK (binary response)"
+                            );
+                            break true;
+                        }
+                        Some(Ok(TungsteniteMessage::Pong(_))) => {
+                            tracing::info!(
+                                ws_url = %ws_url,
+                                "WebSocket health check OK (pong response)"
+                            );
+                            break true;
+                        }
+                        Some(Ok(TungsteniteMessage::Close(frame))) => {
+                            tracing::warn!(
+                                ws_url = %ws_url,
+                                close_frame = ?frame,
+                                "WebSocket health check: connection closed instead of a response"
+                            );
+                            break false;
+                        }
+                        Some(Ok(_)) => {
+                            continue;
+                        }
+                        Some(Err(e)) => {
+                            tracing::error!(
+                                ws_url = %ws_url,
+                                error = %e,
+                                "WebSocket health check error"
+                            );
+                            break false;
+                        }
+                        None => {
+                            tracing::warn!(
+                                ws_url = %ws_url,
+                                "WebSocket health check: no response received"
+                            );
+                            break false;
+                        }
                     }
-                    Some(Ok(TungsteniteMessage::Pong(_))) => {
-                        tracing::info!(
-                            ws_url = %ws_url,
-                            "WebSocket health check OK (pong response)"
-                        );
-                        true
-                    }
-                    Some(Ok(_)) => {
-                        tracing::info!(
-                            ws_url = %ws_url,
-                            "WebSocket health check OK (other message type)"
-                        );
-                        true
-                    }
-                    Some(Err(e)) => {
-                        tracing::error!(
-                            ws_url = %ws_url,
-                            error = %e,
-                            "WebSocket health check error"
-                        );
-                        false
-                    }
-                    None => {
-                        tracing::warn!(
-                            ws_url = %ws_url,
-                            "WebSocket health check: no response received"
-                        );
-                        false
-                    }
-                }
+                };
+
+                // Leave the connection cleanly; the result is already known
+                let _ = ws_stream.close(None).await;
+                is_healthy
             }
             Err(e) => {
                 tracing::error!(
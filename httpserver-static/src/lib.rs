@@ -1,38 +1,184 @@
 use axum::{
-    extract::Path,
-    http::{ header, StatusCode },
+    body::{ Body, HttpBody },
+    extract::{ Path, Request, State },
+    http::{ header, HeaderValue, StatusCode },
+    middleware::Next,
     response::{ IntoResponse, Response },
-    routing::get,
+    routing::{ get, MethodRouter },
     Router,
     Json,
 };
 use httpserver_core::create_error_response;
+use base64::{ Engine as _, engine::general_purpose };
 use mime_guess::from_path;
 use serde_json::{ json, Value };
 use std::path::PathBuf;
+use std::sync::Arc;
 use tokio::fs;
-use tracing;
 
 /// Static file handler configuration
 pub struct StaticHandler {
     pub base_dir: PathBuf,
+    pub spa_fallback: SpaFallback,
+    /// Additional directories served under URL prefixes
+    pub mounts: Vec<StaticMount>,
+    /// Cache-Control values chosen by file name
+    pub cache_control: CacheControl,
+    /// "user:password" pairs accepted via HTTP Basic auth; empty serves files to anyone
+    pub basic_auth: Vec<String>,
+}
+
+/// Errors setting up static file serving
+#[derive(Debug)]
+pub enum StaticError {
+    /// A static directory is missing or cannot be accessed
+    Directory {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A mount at "/" would shadow the root directory
+    RootMount,
+    /// The same mount prefix was configured more than once
+    DuplicateMount(String),
+}
+
+impl std::fmt::Display for StaticError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StaticError::Directory { path, source } =>
+                write!(f, "Cannot access static files directory '{}': {}", path.display(), source),
+            StaticError::RootMount =>
+                write!(f, "Static mount prefix cannot be the root; use static_config.directory"),
+            StaticError::DuplicateMount(prefix) =>
+                write!(f, "Static mount '{}' is configured more than once", prefix),
+        }
+    }
+}
+
+impl std::error::Error for StaticError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StaticError::Directory { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Cache-Control header values per file pattern
+#[derive(Debug, Clone)]
+pub struct CacheControl {
+    /// Value for files no rule matches
+    pub default: String,
+    /// (glob, value) pairs checked in order; the first match wins
+    pub rules: Vec<(String, String)>,
+}
+
+impl Default for CacheControl {
+    fn default() -> Self {
+        Self {
+            default: "public, max-age=3600".to_string(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl CacheControl {
+    /// Cache-Control value for a file path relative to its mount
+    ///
+    /// Patterns containing '/' match the whole relative path, others just the file name.
+    pub fn value_for(&self, relative_path: &str) -> &str {
+        let file_name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+        self.rules
+            .iter()
+            .find(|(pattern, _)| {
+                let subject = if pattern.contains('/') { relative_path } else { file_name };
+                glob_match(pattern.trim_start_matches('/'), subject)
+            })
+            .map(|(_, value)| value.as_str())
+            .unwrap_or(&self.default)
+    }
+}
+
+/// Match `*` (any run of characters) and `?` (one character) wildcards
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Let the last `*` absorb one more character
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// A directory served under a URL prefix, with its own traversal boundary and fallback
+#[derive(Debug, Clone)]
+pub struct StaticMount {
+    /// Normalized URL prefix without a trailing slash ("" for the root directory)
+    pub prefix: String,
+    pub base_dir: PathBuf,
+    pub spa_fallback: SpaFallback,
+}
+
+impl StaticMount {
+    /// Path relative to this mount when the request falls under its prefix
+    fn relative_path<'a>(&self, request_path: &'a str) -> Option<&'a str> {
+        let rest = request_path.strip_prefix(self.prefix.as_str())?;
+        if rest.is_empty() {
+            Some(rest)
+        } else {
+            rest.strip_prefix('/')
+        }
+    }
+}
+
+/// When missing paths are answered with index.html instead of a 404
+#[derive(Debug, Clone)]
+pub struct SpaFallback {
+    /// Fall back for paths without a file extension
+    pub enabled: bool,
+    /// Path prefixes that always fall back, extension or not
+    pub prefixes: Vec<String>,
+}
+
+impl Default for SpaFallback {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            prefixes: Vec::new(),
+        }
+    }
+}
+
+impl SpaFallback {
+    /// Whether a missing request path should be served the SPA shell
+    fn applies_to(&self, clean_path: &str) -> bool {
+        let request_path = format!("/{}", clean_path);
+        if self.prefixes.iter().any(|prefix| request_path.starts_with(prefix.as_str())) {
+            return true;
+        }
+        self.enabled && std::path::Path::new(clean_path).extension().is_none()
+    }
 }
 
 impl StaticHandler {
     /// Create a new static file handler
-    pub fn new(base_dir: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
-        // Resolve the directory to an absolute path
-        let resolved_dir = match base_dir.canonicalize() {
-            Ok(path) => path,
-            Err(e) => {
-                tracing::error!(
-                    directory = %base_dir.display(),
-                    error = %e,
-                    "Cannot access static files directory"
-                );
-                return Err(e.into());
-            }
-        };
+    pub fn new(base_dir: PathBuf) -> Result<Self, StaticError> {
+        let resolved_dir = resolve_directory(&base_dir)?;
 
         tracing::info!(
             directory = %resolved_dir.display(),
@@ -41,31 +187,201 @@ impl StaticHandler {
 
         Ok(Self {
             base_dir: resolved_dir,
+            spa_fallback: SpaFallback::default(),
+            mounts: Vec::new(),
+            cache_control: CacheControl::default(),
+            basic_auth: Vec::new(),
         })
     }
 
+    /// Require one of these "user:password" pairs via HTTP Basic auth before serving any file
+    pub fn with_basic_auth(mut self, credentials: Vec<String>) -> Self {
+        self.basic_auth = credentials;
+        self
+    }
+
+    /// Configure the Cache-Control header sent with static files
+    pub fn with_cache_control(mut self, cache_control: CacheControl) -> Self {
+        self.cache_control = cache_control;
+        self
+    }
+
+    /// Serve another directory under a URL prefix such as "/assets"
+    pub fn with_mount(
+        mut self,
+        mount: &str,
+        directory: PathBuf,
+        spa_fallback: SpaFallback
+    ) -> Result<Self, StaticError> {
+        let prefix = format!("/{}", mount.trim_matches('/'));
+        if prefix == "/" {
+            return Err(StaticError::RootMount);
+        }
+        if self.mounts.iter().any(|existing| existing.prefix == prefix) {
+            return Err(StaticError::DuplicateMount(prefix));
+        }
+
+        let base_dir = resolve_directory(&directory)?;
+        tracing::info!(
+            mount = %prefix,
+            directory = %base_dir.display(),
+            "Static mount initialized"
+        );
+
+        self.mounts.push(StaticMount { prefix, base_dir, spa_fallback });
+        Ok(self)
+    }
+
+    /// Configure when missing paths fall back to index.html
+    pub fn with_spa_fallback(mut self, spa_fallback: SpaFallback) -> Self {
+        self.spa_fallback = spa_fallback;
+        self
+    }
+
     /// Create the router for static file serving
     pub fn create_router(self) -> Router {
-        let serve_dir = self.base_dir.clone();
-        let serve_dir_clone = self.base_dir.clone();
+        // The root directory is the mount of last resort; longer prefixes are tried first
+        let mut mounts = self.mounts;
+        mounts.push(StaticMount {
+            prefix: String::new(),
+            base_dir: self.base_dir,
+            spa_fallback: self.spa_fallback,
+        });
+        mounts.sort_by_key(|mount| std::cmp::Reverse(mount.prefix.len()));
+        let mounts = Arc::new(mounts);
+        let cache_control = Arc::new(self.cache_control);
+
+        let root_mounts = mounts.clone();
+        let root_cache_control = cache_control.clone();
+        let root = static_methods(move |_: Option<Path<String>>| {
+            serve_from_mounts(String::new(), root_mounts.clone(), root_cache_control.clone())
+        });
+        let files = static_methods(move |path: Option<Path<String>>| {
+            let path = path.map(|Path(path)| path).unwrap_or_default();
+            serve_from_mounts(path, mounts.clone(), cache_control.clone())
+        });
+
+        let router = Router::new().route("/", root).route("/*path", files);
+        if self.basic_auth.is_empty() {
+            router
+        } else {
+            router.layer(axum::middleware::from_fn_with_state(Arc::new(self.basic_auth), require_basic_auth))
+        }
+    }
+}
+
+/// Answer 401 with a Basic challenge unless the request carries accepted credentials
+async fn require_basic_auth(
+    State(credentials): State<Arc<Vec<String>>>,
+    request: Request,
+    next: Next
+) -> Response {
+    let supplied = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split_once(' '))
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("basic"))
+        .and_then(|(_, encoded)| general_purpose::STANDARD.decode(encoded.trim()).ok());
 
-        Router::new()
-            .route(
-                "/",
-                get({
-                    move || serve_file("index.html".to_string(), serve_dir)
-                })
-            )
-            .route(
-                "/*path",
-                get(move |Path(path): Path<String>| { serve_file(path, serve_dir_clone) })
-            )
+    // Compare against every credential so timing does not reveal which one nearly matched
+    let authorized = supplied.is_some_and(|supplied| {
+        credentials
+            .iter()
+            .fold(false, |matched, credential| constant_time_eq(credential.as_bytes(), &supplied) | matched)
+    });
+    if authorized {
+        return next.run(request).await;
+    }
+
+    tracing::warn!(path = %request.uri().path(), "Static file request without valid basic auth credentials");
+    let mut response = create_error_response(StatusCode::UNAUTHORIZED, "Authentication required");
+    response.headers_mut().insert(
+        header::WWW_AUTHENTICATE,
+        HeaderValue::from_static("Basic realm=\"static\", charset=\"UTF-8\"")
+    );
+    response
+}
+
+/// Byte comparison whose duration depends only on the lengths, not on where the inputs differ
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |difference, (x, y)| difference | (x ^ y)) == 0
+}
+
+/// Methods a static route answers: GET, HEAD (same headers, no body) and 405 for the rest
+fn static_methods<F, Fut>(serve: F) -> MethodRouter
+    where
+        F: Fn(Option<Path<String>>) -> Fut + Clone + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Response> + Send + 'static
+{
+    let serve_head = serve.clone();
+    get(move |path: Option<Path<String>>| serve(path))
+        .head(move |path: Option<Path<String>>| {
+            let response = serve_head(path);
+            async move { head_response(response.await) }
+        })
+        .fallback(|| async { method_not_allowed() })
+}
+
+/// Drop the body of a GET response, keeping its Content-Length
+fn head_response(response: Response) -> Response {
+    let (mut parts, body) = response.into_parts();
+    if let Some(length) = body.size_hint().exact() {
+        parts.headers.insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+    }
+    Response::from_parts(parts, Body::empty())
+}
+
+/// 405 listing the methods static files support
+fn method_not_allowed() -> Response {
+    let mut response = create_error_response(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed");
+    response.headers_mut().insert(header::ALLOW, HeaderValue::from_static("GET, HEAD"));
+    response
+}
+
+/// Resolve a static directory to an absolute path
+fn resolve_directory(directory: &std::path::Path) -> Result<PathBuf, StaticError> {
+    directory.canonicalize().map_err(|e| {
+        tracing::error!(
+            directory = %directory.display(),
+            error = %e,
+            "Cannot access static files directory"
+        );
+        StaticError::Directory { path: directory.to_path_buf(), source: e }
+    })
+}
+
+/// Serve a request from the mount with the longest matching prefix
+async fn serve_from_mounts(
+    path: String,
+    mounts: Arc<Vec<StaticMount>>,
+    cache_control: Arc<CacheControl>
+) -> Response {
+    let request_path = format!("/{}", path.trim_start_matches('/'));
+    for mount in mounts.iter() {
+        if let Some(relative_path) = mount.relative_path(&request_path) {
+            return serve_file(
+                relative_path.to_string(),
+                mount.base_dir.clone(),
+                mount.spa_fallback.clone(),
+                cache_control
+            ).await.into_response();
+        }
     }
+    create_error_response(StatusCode::NOT_FOUND, "File not found")
 }
 
 /// Serve a single file from the static directory
-#[tracing::instrument(skip(base_dir), fields(base_dir = %base_dir.display()))]
-async fn serve_file(path: String, base_dir: PathBuf) -> impl IntoResponse {
+#[tracing::instrument(
+    skip(base_dir, spa_fallback, cache_control),
+    fields(base_dir = %base_dir.display())
+)]
+async fn serve_file(
+    path: String,
+    base_dir: PathBuf,
+    spa_fallback: SpaFallback,
+    cache_control: Arc<CacheControl>
+) -> impl IntoResponse {
     // Clean up the path and prevent directory traversal
     let requested_path = if path.is_empty() || path == "/" {
         "index.html".to_string()
@@ -109,19 +425,20 @@ async fn serve_file(path: String, base_dir: PathBuf) -> impl IntoResponse {
         Ok(contents) => {
             // Guess the MIME type based on file extension
             let mime_type = from_path(&file_path).first_or_octet_stream();
+            let cache_control = cache_control.value_for(clean_path);
 
             tracing::info!(
                 file_path = %file_path.display(),
                 file_size = contents.len(),
                 mime_type = %mime_type,
-                cache_control = "public, max-age=3600",
+                cache_control = %cache_control,
                 "Static file served successfully"
             );
 
             Response::builder()
                 .status(StatusCode::OK)
                 .header(header::CONTENT_TYPE, mime_type.as_ref())
-                .header(header::CACHE_CONTROL, "public, max-age=3600")
+                .header(header::CACHE_CONTROL, cache_control)
                 .body(contents.into())
                 .unwrap()
         }
@@ -132,8 +449,8 @@ async fn serve_file(path: String, base_dir: PathBuf) -> impl IntoResponse {
                 "Failed to read requested file, attempting SPA fallback"
             );
 
-            // If the requested file doesn't exist, try to serve index.html for SPA support
-            if clean_path != "index.html" {
+            // Client-side routes get index.html; missing assets stay a real 404
+            if clean_path != "index.html" && spa_fallback.applies_to(clean_path) {
                 let index_path = base_dir.join("index.html");
                 if let Ok(contents) = fs::read(&index_path).await {
                     tracing::info!(
@@ -146,6 +463,7 @@ async fn serve_file(path: String, base_dir: PathBuf) -> impl IntoResponse {
                     return Response::builder()
                         .status(StatusCode::OK)
                         .header(header::CONTENT_TYPE, "text/html")
+                        .header(header::CACHE_CONTROL, cache_control.value_for("index.html"))
                         .body(contents.into())
                         .unwrap();
                 }
@@ -82,5 +82,4 @@ async fn test_health_router_integration() {
     let _main_router = Router::new().merge(health_router);
 
     // This test verifies that the routers can be combined without conflicts
-    assert!(true);
 }
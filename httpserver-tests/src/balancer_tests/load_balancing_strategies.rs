@@ -1,4 +1,5 @@
-use httpserver_balancer::{ LoadBalancer, LoadBalancingStrategy, Target };
+use httpserver_balancer::{ CircuitBreakerConfig, LoadBalancer, LoadBalancingStrategy, Target };
+use std::collections::HashMap;
 
 fn create_test_targets() -> Vec<Target> {
     vec![
@@ -64,6 +65,128 @@ fn test_weighted_round_robin_strategy() {
     assert!(count_3001 > count_3002, "Weight 2 target should get more requests than weight 1");
 }
 
+/// Count how many of `rounds` selections go to each target
+fn count_selections(
+    rounds: usize,
+    mut select: impl FnMut() -> Option<String>
+) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for _ in 0..rounds {
+        *counts.entry(select().expect("A target should be available")).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[test]
+fn test_weighted_round_robin_skips_unhealthy_target() {
+    let targets = create_weighted_targets();
+    let balancer = LoadBalancer::new(targets, LoadBalancingStrategy::WeightedRoundRobin);
+    balancer.set_target_health("http://localhost:3001", false);
+
+    // Remaining weights 3:1 split every 4 selections exactly
+    let counts = count_selections(40, || balancer.select_target().map(|t| t.url.clone()));
+    assert_eq!(counts.get("http://localhost:3000"), Some(&30));
+    assert_eq!(counts.get("http://localhost:3002"), Some(&10));
+    assert_eq!(counts.get("http://localhost:3001"), None);
+
+    // Recovery restores the full 3:2:1 split
+    balancer.set_target_health("http://localhost:3001", true);
+    let counts = count_selections(60, || balancer.select_target().map(|t| t.url.clone()));
+    assert_eq!(counts.get("http://localhost:3000"), Some(&30));
+    assert_eq!(counts.get("http://localhost:3001"), Some(&20));
+    assert_eq!(counts.get("http://localhost:3002"), Some(&10));
+}
+
+#[test]
+fn test_weighted_round_robin_terminates_with_degenerate_weights() {
+    // Validation rejects zero weights, but selection must still finish if one slips through
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let all_zero = LoadBalancer::new(
+            vec![
+                Target::with_weight("http://localhost:3000".to_string(), 0),
+                Target::with_weight("http://localhost:3001".to_string(), 0)
+            ],
+            LoadBalancingStrategy::WeightedRoundRobin
+        );
+        let all_zero_counts = count_selections(10, || all_zero.select_target().map(|t| t.url.clone()));
+
+        let mixed = LoadBalancer::new(
+            vec![
+                Target::with_weight("http://localhost:3000".to_string(), 0),
+                Target::with_weight("http://localhost:3001".to_string(), 2),
+                Target::with_weight("http://localhost:3002".to_string(), 0)
+            ],
+            LoadBalancingStrategy::WeightedRoundRobin
+        );
+        let mixed_counts = count_selections(10, || mixed.select_target().map(|t| t.url.clone()));
+        sender.send((all_zero_counts, mixed_counts)).unwrap();
+    });
+
+    let (all_zero_counts, mixed_counts) = receiver
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .expect("weighted selection should terminate");
+
+    // All-zero weights share traffic evenly; zero-weight targets lose to any weighted one
+    assert_eq!(all_zero_counts.get("http://localhost:3000"), Some(&5));
+    assert_eq!(all_zero_counts.get("http://localhost:3001"), Some(&5));
+    assert_eq!(mixed_counts.get("http://localhost:3001"), Some(&10));
+}
+
+#[test]
+fn test_weighted_round_robin_keeps_weights_with_closed_breakers() {
+    let targets = create_weighted_targets();
+    let balancer = LoadBalancer::new(targets, LoadBalancingStrategy::WeightedRoundRobin);
+    let config = CircuitBreakerConfig {
+        enabled: true,
+        failure_threshold: 3,
+        failure_window: 60,
+        open_timeout: 60,
+        test_requests: 2,
+        min_requests: 2,
+    };
+    for target in balancer.targets() {
+        balancer.initialize_circuit_breaker(&target.url, config.clone());
+    }
+
+    // Every breaker allows traffic, so the full 3:2:1 split applies
+    let counts = count_selections(60, || {
+        balancer.select_target_with_circuit_breaker().map(|t| t.url.clone())
+    });
+    assert_eq!(counts.get("http://localhost:3000"), Some(&30));
+    assert_eq!(counts.get("http://localhost:3001"), Some(&20));
+    assert_eq!(counts.get("http://localhost:3002"), Some(&10));
+}
+
+#[test]
+fn test_weighted_round_robin_respects_weights_with_open_breaker() {
+    let targets = create_weighted_targets();
+    let balancer = LoadBalancer::new(targets, LoadBalancingStrategy::WeightedRoundRobin);
+    let config = CircuitBreakerConfig {
+        enabled: true,
+        failure_threshold: 3,
+        failure_window: 60,
+        open_timeout: 60,
+        test_requests: 2,
+        min_requests: 2,
+    };
+    for target in balancer.targets() {
+        balancer.initialize_circuit_breaker(&target.url, config.clone());
+    }
+    for _ in 0..3 {
+        balancer.record_failure("http://localhost:3002");
+    }
+    assert!(!balancer.allow_request("http://localhost:3002"));
+
+    // Weights 3:2 are kept among the targets whose breakers allow traffic
+    let counts = count_selections(50, || {
+        balancer.select_target_with_circuit_breaker().map(|t| t.url.clone())
+    });
+    assert_eq!(counts.get("http://localhost:3000"), Some(&30));
+    assert_eq!(counts.get("http://localhost:3001"), Some(&20));
+    assert_eq!(counts.get("http://localhost:3002"), None);
+}
+
 #[test]
 fn test_random_strategy() {
     let targets = create_test_targets();
@@ -80,6 +203,80 @@ fn test_random_strategy() {
     }
 }
 
+#[test]
+fn test_weighted_random_respects_weights_and_health() {
+    let balancer = LoadBalancer::new(create_weighted_targets(), LoadBalancingStrategy::WeightedRandom);
+
+    // 3:2:1 weights give shares of 1/2, 1/3 and 1/6
+    let rounds = 60_000;
+    let counts = count_selections(rounds, || balancer.select_target().map(|t| t.url.clone()));
+    for (url, share) in [("http://localhost:3000", 0.5), ("http://localhost:3001", 1.0 / 3.0), ("http://localhost:3002", 1.0 / 6.0)] {
+        let observed = (counts[url] as f64) / (rounds as f64);
+        assert!((observed - share).abs() < 0.02, "{} got {:.3} of traffic, expected {:.3}", url, observed, share);
+    }
+
+    // Unhealthy targets are never chosen; the rest keep their 2:1 ratio
+    balancer.set_target_health("http://localhost:3000", false);
+    let counts = count_selections(rounds, || balancer.select_target().map(|t| t.url.clone()));
+    assert_eq!(counts.get("http://localhost:3000"), None);
+    let observed = (counts["http://localhost:3001"] as f64) / (rounds as f64);
+    assert!((observed - 2.0 / 3.0).abs() < 0.02, "localhost:3001 got {:.3} of traffic", observed);
+}
+
+#[test]
+fn test_weighted_random_when_most_weight_is_unhealthy() {
+    let targets = vec![
+        Target::with_weight("http://localhost:3000".to_string(), 10_000),
+        Target::with_weight("http://localhost:3001".to_string(), 1)
+    ];
+    let balancer = LoadBalancer::new(targets, LoadBalancingStrategy::WeightedRandom);
+    balancer.set_target_health("http://localhost:3000", false);
+
+    let counts = count_selections(100, || balancer.select_target().map(|t| t.url.clone()));
+    assert_eq!(counts.get("http://localhost:3001"), Some(&100));
+
+    balancer.set_target_health("http://localhost:3001", false);
+    assert!(balancer.select_target().is_none());
+}
+
+/// Selections per second with `threads` threads sharing one balancer
+fn selection_throughput(balancer: &LoadBalancer, threads: usize, per_thread: usize) -> f64 {
+    let start = std::time::Instant::now();
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                for _ in 0..per_thread {
+                    assert!(std::hint::black_box(balancer.select_target()).is_some());
+                }
+            });
+        }
+    });
+    ((threads * per_thread) as f64) / start.elapsed().as_secs_f64()
+}
+
+#[test]
+fn test_weighted_random_selection_scales_across_threads() {
+    let targets: Vec<Target> = (0..64)
+        .map(|i| Target::with_weight(format!("http://localhost:{}", 4000 + i), (i % 5) + 1))
+        .collect();
+    let balancer = LoadBalancer::new(targets, LoadBalancingStrategy::WeightedRandom);
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get()).min(4);
+    let single = selection_throughput(&balancer, 1, 100_000);
+    let parallel = selection_throughput(&balancer, threads.max(2), 100_000);
+
+    // Threads contending for a global lock fall well below the single-threaded rate; lock-free
+    // selection at least keeps pace. A single core cannot show scaling, only the absence of collapse.
+    let required = if threads >= 2 { 0.8 } else { 0.5 };
+    assert!(
+        parallel >= single * required,
+        "{} threads made {:.0} selections/s against {:.0} on one thread",
+        threads.max(2),
+        parallel,
+        single
+    );
+}
+
 #[test]
 fn test_least_connections_strategy() {
     let targets = create_test_targets();
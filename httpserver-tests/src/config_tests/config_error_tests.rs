@@ -0,0 +1,434 @@
+// Configuration error tests: each failure mode is reported as its own ConfigError variant
+use httpserver_config::{ Config, ConfigError };
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Write `toml_content` to config.toml in `dir`, returning its path
+fn write_config(dir: &TempDir, toml_content: &str) -> PathBuf {
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(&config_path, toml_content).unwrap();
+    config_path
+}
+
+/// Config serving `dir` with one proxy route built from `route` (TOML key/value lines)
+fn config_with_route(dir: &TempDir, route: &str) -> String {
+    format!(
+        "[static_config]\ndirectory = \"{}\"\nfallback = \"index.html\"\n\n[[proxy]]\n{}\n",
+        dir.path().to_string_lossy().replace('\\', "/"),
+        route
+    )
+}
+
+#[test]
+fn test_missing_config_file_is_read_error() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("missing.toml");
+
+    match Config::load_from_file(&config_path) {
+        Err(ConfigError::Read { path, source }) => {
+            assert_eq!(path, config_path);
+            assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+        }
+        other => panic!("Expected Read error, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn test_invalid_toml_is_parse_error() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = write_config(&temp_dir, "[static_config\ndirectory = ");
+
+    let error = Config::load_from_file(&config_path).unwrap_err();
+    assert!(matches!(&error, ConfigError::Parse { path, .. } if *path == config_path));
+    assert!(std::error::Error::source(&error).is_some());
+}
+
+#[test]
+fn test_missing_static_directory_error() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = write_config(
+        &temp_dir,
+        "[static_config]\ndirectory = \"/nonexistent/directory\"\nfallback = \"index.html\"\n"
+    );
+
+    match Config::load_from_file(&config_path) {
+        Err(ConfigError::MissingStaticDirectory(directory)) => {
+            assert_eq!(directory, PathBuf::from("/nonexistent/directory"));
+        }
+        other => panic!("Expected MissingStaticDirectory error, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn test_invalid_proxy_routes_identify_route_and_target() {
+    let temp_dir = TempDir::new().unwrap();
+
+    // Route-level problem
+    let route = "path = \"/api/*\"\ntarget = \"http://localhost:3000\"\ntimeout = 0";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    match Config::load_from_file(&config_path) {
+        Err(ConfigError::InvalidProxyRoute { index: 0, target: None, reason }) => {
+            assert_eq!(reason, "timeout must be greater than 0");
+        }
+        other => panic!("Expected InvalidProxyRoute error, got {:?}", other.err()),
+    }
+
+    // Target-level problem keeps the existing message format
+    let route = "path = \"/api/*\"\ntarget = \"localhost:3000\"";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    let error = Config::load_from_file(&config_path).unwrap_err();
+    assert!(matches!(error, ConfigError::InvalidProxyRoute { index: 0, target: Some(0), .. }));
+    assert_eq!(
+        error.to_string(),
+        "Proxy route 0 target 0: must be a valid HTTP/HTTPS URL: localhost:3000"
+    );
+}
+
+#[test]
+fn test_header_filters_must_name_valid_headers() {
+    let temp_dir = TempDir::new().unwrap();
+    let route = "path = \"/api/*\"\ntarget = \"http://localhost:3000\"\nheader_denylist = [\"cookie\", \"bad header\"]";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    assert_eq!(
+        Config::load_from_file(&config_path).unwrap_err().to_string(),
+        "Proxy route 0: header filter lists an invalid header name: \"bad header\""
+    );
+}
+
+#[test]
+fn test_static_basic_auth_entries_need_a_user() {
+    let temp_dir = TempDir::new().unwrap();
+    let static_config = |basic_auth: &str| {
+        format!(
+            "[static_config]\ndirectory = \"{}\"\nbasic_auth = {}\n",
+            temp_dir.path().to_string_lossy().replace('\\', "/"),
+            basic_auth
+        )
+    };
+
+    let config_path = write_config(&temp_dir, &static_config("[\"docs:s3cret\", \"no-colon\"]"));
+    let error = Config::load_from_file(&config_path).unwrap_err();
+    assert_eq!(error.to_string(), "Invalid static basic_auth: entry 1 must be \"user:password\"");
+
+    // A single string is accepted too
+    let config_path = write_config(&temp_dir, &static_config("\"docs:s3cret\""));
+    let config = Config::load_from_file(&config_path).unwrap();
+    assert_eq!(config.static_config.basic_auth.unwrap().credentials(), ["docs:s3cret".to_string()]);
+}
+
+#[test]
+fn test_maintenance_routes_must_exist() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = format!(
+        "[static_config]\ndirectory = \"{}\"\n\n[[proxy]]\npath = \"/api/*\"\ntarget = \"http://localhost:3000\"\n\n\
+         [server.maintenance]\nroutes = [\"/api/*\", \"/apj/*\"]\nallow_paths = [\"status\"]\n",
+        temp_dir.path().to_string_lossy().replace('\\', "/")
+    );
+
+    let errors: Vec<String> = Config::check_file(&write_config(&temp_dir, &config))
+        .unwrap_err()
+        .iter()
+        .map(|error| error.to_string())
+        .collect();
+    assert_eq!(errors, [
+        "Invalid maintenance settings: allow_paths entry must start with '/': \"status\"",
+        "Invalid maintenance settings: routes names no proxy route: \"/apj/*\"",
+    ]);
+}
+
+#[test]
+fn test_invalid_tcp_routes_are_reported() {
+    let temp_dir = TempDir::new().unwrap();
+    let static_config = format!(
+        "[static_config]\ndirectory = \"{}\"\n",
+        temp_dir.path().to_string_lossy().replace('\\', "/")
+    );
+
+    // Targets are socket addresses, not URLs
+    let route = "[[tcp_routes]]\nlisten_port = 5433\ntargets = [{ url = \"http://db:5432\" }]";
+    let config_path = write_config(&temp_dir, &format!("{}\n{}\n", static_config, route));
+    let error = Config::load_from_file(&config_path).unwrap_err();
+    assert!(matches!(error, ConfigError::InvalidTcpRoute { index: 0, .. }));
+    assert_eq!(error.to_string(), "TCP route 0: target must be \"host:port\": http://db:5432");
+
+    // Two routes cannot share a port
+    let route = "[[tcp_routes]]\nlisten_port = 5433\ntargets = [{ url = \"db:5432\" }]";
+    let config_path = write_config(&temp_dir, &format!("{}\n{}\n{}\n", static_config, route, route));
+    let error = Config::load_from_file(&config_path).unwrap_err();
+    assert!(matches!(error, ConfigError::InvalidTcpRoute { index: 1, .. }));
+
+    // A single valid route loads with the default strategy
+    let config_path = write_config(&temp_dir, &format!("{}\n{}\n", static_config, route));
+    let config = Config::load_from_file(&config_path).unwrap();
+    assert_eq!(config.tcp_routes[0].targets[0].url, "db:5432");
+}
+
+#[test]
+fn test_default_route_needs_http_target() {
+    let temp_dir = TempDir::new().unwrap();
+    let content = format!(
+        "[static_config]\ndirectory = \"{}\"\n\n[default_route]\ntarget = \"legacy:8080\"\n",
+        temp_dir.path().to_string_lossy().replace('\\', "/")
+    );
+    let config_path = write_config(&temp_dir, &content);
+
+    match Config::load_from_file(&config_path) {
+        Err(ConfigError::InvalidDefaultRoute(target)) => assert_eq!(target, "legacy:8080"),
+        other => panic!("Expected InvalidDefaultRoute error, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn test_default_route_timeout_must_be_positive() {
+    let temp_dir = TempDir::new().unwrap();
+    let content = format!(
+        "[static_config]\ndirectory = \"{}\"\n\n[default_route]\ntarget = \"http://legacy:8080\"\ntimeout = 0\n",
+        temp_dir.path().to_string_lossy().replace('\\', "/")
+    );
+    let config_path = write_config(&temp_dir, &content);
+
+    match Config::load_from_file(&config_path) {
+        Err(ConfigError::InvalidDefaultRouteTimeout) => {}
+        other => panic!("Expected InvalidDefaultRouteTimeout error, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn test_access_log_format_variables_must_be_known() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let route = "path = \"/api/*\"\ntarget = \"http://localhost:3000\"\naccess_log_format = \"$remote_addr $upstream_addr\"";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    let error = Config::load_from_file(&config_path).unwrap_err();
+    assert_eq!(error.to_string(), "Proxy route 0: access_log_format uses unknown variable $upstream_addr");
+
+    // "$request_time" is its own variable, and a lone "$" is text
+    let route = "path = \"/api/*\"\ntarget = \"http://localhost:3000\"\naccess_log_format = \"$ $request_time\"";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    assert!(Config::load_from_file(&config_path).is_ok());
+}
+
+#[test]
+fn test_fallback_file_must_stay_inside_static_directory() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let route = "path = \"/api/*\"\ntarget = \"http://localhost:3000\"\nfallback_file = \"../secrets.txt\"";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    let error = Config::load_from_file(&config_path).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Proxy route 0: fallback_file must be a relative path inside the static directory: ../secrets.txt"
+    );
+
+    let route = "path = \"/api/*\"\ntarget = \"http://localhost:3000\"\nfallback_file = \"errors/maintenance.html\"";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    assert!(Config::load_from_file(&config_path).is_ok());
+}
+
+#[test]
+fn test_idempotency_settings_are_validated() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let route = "path = \"/payments/*\"\ntarget = \"http://localhost:3000\"\n[proxy.idempotency]\nheader = \"idempotency key\"";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    let error = Config::load_from_file(&config_path).unwrap_err();
+    assert_eq!(error.to_string(), "Proxy route 0: idempotency header is not a valid header name: \"idempotency key\"");
+
+    let route = "path = \"/payments/*\"\ntarget = \"http://localhost:3000\"\n[proxy.idempotency]\nttl = 0";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    let error = Config::load_from_file(&config_path).unwrap_err();
+    assert_eq!(error.to_string(), "Proxy route 0: idempotency ttl must be greater than 0");
+}
+
+#[test]
+fn test_incoming_auth_settings_are_validated() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let route = "path = \"/api/*\"\ntarget = \"http://localhost:3000\"\n[proxy.jwt_auth]\nsecret = \"\"";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    let error = Config::load_from_file(&config_path).unwrap_err();
+    assert_eq!(error.to_string(), "Proxy route 0: jwt_auth secret cannot be empty");
+
+    let route = "path = \"/api/*\"\ntarget = \"http://localhost:3000\"\n[proxy.forward_auth]\nurl = \"auth:9000/verify\"";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    let error = Config::load_from_file(&config_path).unwrap_err();
+    assert_eq!(error.to_string(), "Proxy route 0: forward_auth url must be a valid HTTP/HTTPS URL: auth:9000/verify");
+
+    let route = "path = \"/api/*\"\ntarget = \"http://localhost:3000\"\n[proxy.forward_auth]\nurl = \"http://auth:9000/verify\"\ncopy_headers = [\"X User\"]";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    let error = Config::load_from_file(&config_path).unwrap_err();
+    assert_eq!(error.to_string(), "Proxy route 0: forward_auth copy_headers entry is not a valid header name: \"X User\"");
+
+    let route = "path = \"/api/*\"\ntarget = \"http://localhost:3000\"\n[proxy.jwt_auth]\nsecret = \"s3cret\"\nalgorithm = \"HS512\"";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    assert!(Config::load_from_file(&config_path).is_ok());
+}
+
+#[test]
+fn test_invalid_route_regex_fails_at_load() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let route = "path = \"/reports/[0-9+\"\nmatch_regex = true\ntarget = \"http://localhost:3000\"";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    match Config::load_from_file(&config_path) {
+        Err(ConfigError::InvalidProxyRoute { index: 0, target: None, reason }) => {
+            assert!(reason.starts_with("invalid path regex"), "{}", reason);
+        }
+        other => panic!("Expected InvalidProxyRoute error, got {:?}", other.err()),
+    }
+
+    let route = "path = \"/.*\"\nhost = \"(eu|us\"\nmatch_regex = true\ntarget = \"http://localhost:3000\"";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    match Config::load_from_file(&config_path) {
+        Err(ConfigError::InvalidProxyRoute { index: 0, target: None, reason }) => {
+            assert!(reason.starts_with("invalid host regex"), "{}", reason);
+        }
+        other => panic!("Expected InvalidProxyRoute error, got {:?}", other.err()),
+    }
+
+    // Without match_regex the same path is a literal
+    let route = "path = \"/reports/[0-9+\"\ntarget = \"http://localhost:3000\"";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    assert!(Config::load_from_file(&config_path).is_ok());
+}
+
+#[test]
+fn test_routes_must_differ_in_host_or_path() {
+    let temp_dir = TempDir::new().unwrap();
+    let shop = "path = \"/api/*\"\nhost = \"shop.example.com\"\ntarget = \"http://localhost:3000\"";
+    let blog = "path = \"/api/*\"\nhost = \"blog.example.com\"\ntarget = \"http://localhost:3001\"";
+
+    // The same path on two hosts is two routes
+    let content = format!("{}\n[[proxy]]\n{}\n", config_with_route(&temp_dir, shop), blog);
+    let config = Config::load_from_file(&write_config(&temp_dir, &content)).unwrap();
+    assert_eq!(config.proxy.len(), 2);
+
+    // The same host and path twice is rejected
+    let content = format!("{}\n[[proxy]]\n{}\n", config_with_route(&temp_dir, shop), shop);
+    match Config::load_from_file(&write_config(&temp_dir, &content)) {
+        Err(ConfigError::InvalidProxyRoute { index: 1, target: None, reason }) => {
+            assert_eq!(reason, "another proxy route has the same host and path");
+        }
+        other => panic!("Expected InvalidProxyRoute error, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn test_health_path_prefix_must_be_a_path() {
+    let temp_dir = TempDir::new().unwrap();
+    for prefix in ["_internal", "/_internal/", "/"] {
+        let content = format!(
+            "[server]\nhealth_path_prefix = \"{}\"\n\n[static_config]\ndirectory = \"{}\"\n",
+            prefix,
+            temp_dir.path().to_string_lossy().replace('\\', "/")
+        );
+        let config_path = write_config(&temp_dir, &content);
+
+        match Config::load_from_file(&config_path) {
+            Err(ConfigError::InvalidHealthPathPrefix(value)) => assert_eq!(value, prefix),
+            other => panic!("Expected InvalidHealthPathPrefix error for {:?}, got {:?}", prefix, other.err()),
+        }
+    }
+}
+
+#[test]
+fn test_listener_errors_name_the_listener() {
+    let temp_dir = TempDir::new().unwrap();
+    let content = format!(
+        "[static_config]\ndirectory = \"{}\"\n\n[[listeners]]\nport = 9000\n\n[[listeners]]\nport = 9000\nstatic_directory = \"missing-admin\"\n\n[[listeners.proxy]]\npath = \"/api/*\"\ntarget = \"ftp://localhost\"\n",
+        temp_dir.path().to_string_lossy().replace('\\', "/")
+    );
+    let config_path = write_config(&temp_dir, &content);
+
+    let errors = Config::check_file(&config_path).unwrap_err();
+    let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    assert_eq!(messages.len(), 3, "{:?}", messages);
+    assert!(errors.iter().all(|error| matches!(error, ConfigError::InvalidListener { index: 1, .. })));
+    assert_eq!(messages[0], "Listener 1: port 9000 is used by another listener");
+    assert!(messages[1].contains("missing-admin"), "{}", messages[1]);
+    assert!(messages[2].starts_with("Listener 1: Proxy route 0 target 0:"), "{}", messages[2]);
+}
+
+#[test]
+fn test_check_file_reports_every_error() {
+    let temp_dir = TempDir::new().unwrap();
+    let content = format!(
+        "{}\n[[proxy]]\npath = \"/b/*\"\ntarget = \"http://localhost:3001\"\ntimeout = 0\n\n[server.ssl]\nenabled = true\nkey_file = \"missing.key\"\n",
+        config_with_route(&temp_dir, "path = \"/a/*\"\ntarget = \"ftp://localhost\"")
+    );
+    let config_path = write_config(&temp_dir, &content);
+
+    // Loading stops at the first error and does not look at certificate files
+    assert!(matches!(Config::load_from_file(&config_path), Err(ConfigError::InvalidProxyRoute { index: 0, .. })));
+
+    let errors = Config::check_file(&config_path).unwrap_err();
+    let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Proxy route 0 target 0: must be a valid HTTP/HTTPS URL: ftp://localhost",
+            "Proxy route 1: timeout must be greater than 0",
+            "SSL key_file does not exist: missing.key",
+        ]
+    );
+}
+
+#[test]
+fn test_config_error_converts_for_question_mark_callers() {
+    fn load(path: &PathBuf) -> Result<Config, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Config::load_from_file(path)?)
+    }
+
+    let temp_dir = TempDir::new().unwrap();
+    let error = load(&temp_dir.path().join("missing.toml")).unwrap_err();
+    assert!(error.downcast_ref::<ConfigError>().is_some());
+}
+
+#[test]
+fn test_health_body_json_expectation_needs_field_and_value() {
+    let temp_dir = TempDir::new().unwrap();
+    let route = "path = \"/api/*\"\ntarget = \"http://localhost:3000\"\n\n[proxy.http_health]\nexpected_body_json = \"status\"";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+
+    let error = Config::load_from_file(&config_path).unwrap_err();
+    assert!(matches!(error, ConfigError::InvalidProxyRoute { index: 0, target: None, .. }));
+}
+
+#[test]
+fn test_cors_credentials_need_listed_origins() {
+    let temp_dir = TempDir::new().unwrap();
+    let expected = "Proxy route 0: cors allow_credentials requires allowed_origins to list the origins explicitly";
+
+    let route = "path = \"/api/*\"\ntarget = \"http://localhost:3000\"\n[proxy.middleware.cors]\nallow_credentials = true";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    assert_eq!(Config::load_from_file(&config_path).unwrap_err().to_string(), expected);
+
+    let route = "path = \"/api/*\"\ntarget = \"http://localhost:3000\"\n[proxy.middleware.cors]\nallowed_origins = [\"*\"]\nallow_credentials = true";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    assert_eq!(Config::load_from_file(&config_path).unwrap_err().to_string(), expected);
+
+    let route = "path = \"/api/*\"\ntarget = \"http://localhost:3000\"\n[proxy.middleware.cors]\nallowed_origins = [\"https://app.example.com\"]\nallow_credentials = true";
+    let config_path = write_config(&temp_dir, &config_with_route(&temp_dir, route));
+    assert!(Config::load_from_file(&config_path).is_ok());
+}
+
+#[test]
+fn test_trusted_proxies_must_be_networks_or_addresses() {
+    let temp_dir = TempDir::new().unwrap();
+    let content = format!(
+        "[server]\ntrusted_proxies = [\"10.0.0.0/8\", \"10.0.0.0/33\", \"192.0.2.1\", \"proxy.internal\"]\n\n{}",
+        config_with_route(&temp_dir, "path = \"/api/*\"\ntarget = \"http://localhost:3000\"")
+    );
+    let config_path = write_config(&temp_dir, &content);
+
+    let errors: Vec<String> = Config::check_file(&config_path)
+        .unwrap_err()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(errors, [
+        "Invalid trusted proxy '10.0.0.0/33': expected CIDR or IP address",
+        "Invalid trusted proxy 'proxy.internal': expected CIDR or IP address",
+    ]);
+    assert!(matches!(Config::load_from_file(&config_path), Err(ConfigError::InvalidTrustedProxy(_))));
+}
@@ -2,7 +2,7 @@
 use httpserver_config::{
     Config, Args, LoadBalancingStrategy, StaticConfig, LoggingConfig, 
     ApplicationConfig, ServerConfig, TunnelConfig, ProxyRoute, Target,
-    HttpHealthConfig, WebSocketHealthConfig
+    HttpHealthConfig, WebSocketHealthConfig, ProxyClientConfig, StartupCheckConfig
 };
 use std::path::PathBuf;
 use tempfile::TempDir;
@@ -45,6 +45,10 @@ fn test_config_from_args_no_config_file() {
         directory: temp_dir.path().to_path_buf(),
         port: 8080,
         config: None,
+        self_signed: false,
+        check_config: None,
+        print_config: false,
+        show_secrets: false,
     };
 
     let config = Config::from_args(args).unwrap();
@@ -54,6 +58,29 @@ fn test_config_from_args_no_config_file() {
     assert!(config.proxy.is_empty());
 }
 
+#[test]
+fn test_config_from_args_self_signed() {
+    let temp_dir = create_test_directory();
+
+    let args = Args {
+        directory: temp_dir.path().to_path_buf(),
+        port: 8443,
+        config: None,
+        self_signed: true,
+        check_config: None,
+        print_config: false,
+        show_secrets: false,
+    };
+
+    let config = Config::from_args(args).unwrap();
+    let ssl_config = config.server.ssl.expect("--self-signed should enable SSL");
+
+    assert!(ssl_config.enabled);
+    assert!(ssl_config.self_signed);
+    assert!(ssl_config.cert_file.is_none());
+    assert_eq!(ssl_config.https_port, 443);
+}
+
 #[test]
 fn test_config_from_toml_file() {
     let temp_dir = create_test_directory();
@@ -179,17 +206,64 @@ ping_message = "health_check"
     assert_eq!(ws_health.ping_message, "health_check");
 }
 
+#[test]
+fn test_config_with_proxy_client_overrides() {
+    let temp_dir = create_test_directory();
+    let toml_content = format!(
+        r#"
+[static_config]
+directory = "{}"
+
+[proxy_client]
+connect_timeout = 5
+follow_redirects = false
+
+[[proxy]]
+path = "/api/*"
+target = "http://localhost:3000"
+
+[proxy.client]
+connect_timeout = 2
+pool_max_idle_per_host = 0
+"#,
+        temp_dir.path().to_string_lossy().replace('\\', "/")
+    );
+
+    let config_path = create_test_config_file(&temp_dir, &toml_content);
+    let config = Config::load_from_file(&config_path).unwrap();
+
+    assert_eq!(config.proxy_client.connect_timeout, 5);
+    assert_eq!(config.proxy_client.pool_idle_timeout, 90);
+    assert!(!config.proxy_client.follow_redirects);
+
+    let effective = config.proxy_client.with_overrides(config.proxy[0].client.as_ref());
+    assert_eq!(effective.connect_timeout, 2);
+    assert_eq!(effective.pool_max_idle_per_host, 0);
+    assert_eq!(effective.pool_idle_timeout, 90);
+    assert!(!effective.follow_redirects);
+}
+
 #[test]
 fn test_config_validation_invalid_directory() {    let config = Config {
         static_config: StaticConfig {
             directory: PathBuf::from("/nonexistent/directory"),
             fallback: "index.html".to_string(),
+            spa_fallback: true,
+            spa_fallback_prefixes: vec![],
+            mounts: vec![],
+            cache_control: Default::default(),
+            basic_auth: None,
         },
         proxy: Vec::new(),
         logging: LoggingConfig::default(),
         application: ApplicationConfig::default(),
         server: ServerConfig::default(),
         tunnel: TunnelConfig::default(),
+        tcp_routes: Vec::new(),
+        default_route: None,
+        listeners: Vec::new(),
+        proxy_client: ProxyClientConfig::default(),
+        startup_check: StartupCheckConfig::default(),
     };
 
     let result = config.validate();
@@ -204,6 +278,11 @@ fn test_config_validation_invalid_proxy_route() {
         static_config: StaticConfig {
             directory: temp_dir.path().to_path_buf(),
             fallback: "index.html".to_string(),
+            spa_fallback: true,
+            spa_fallback_prefixes: vec![],
+            mounts: vec![],
+            cache_control: Default::default(),
+            basic_auth: None,
         },        proxy: vec![ProxyRoute {
             path: "".to_string(), // Invalid empty path
             target: Some("http://localhost:3000".to_string()),
@@ -213,13 +292,43 @@ fn test_config_validation_invalid_proxy_route() {
             sticky_sessions: false,
             http_health: None,
             websocket_health: None,
+            tcp_health: None,
             ssl: None,
+            client: None,
+            cache: None,
+            http2: false,
+            forwarded_headers: true,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            websocket_limits: None,
+            log_bodies: None,
+            canary: None,
+            mirror: None,
+            host: None,
+            match_regex: false,
+            body_integrity: None,
+            connect_timeout: None,
+            read_timeout: None,
+            header_allowlist: None,
+            header_denylist: Vec::new(),
+            access_log: true,
+            access_log_format: None,
+            startup_check: None,
+            fallback_file: None,
+            idempotency: None,
+            jwt_auth: None,
+            forward_auth: None,
             circuit_breaker: None,            middleware: None,
         }],
         logging: LoggingConfig::default(),
         application: ApplicationConfig::default(),
         server: ServerConfig::default(),
         tunnel: TunnelConfig::default(),
+        tcp_routes: Vec::new(),
+        default_route: None,
+        listeners: Vec::new(),
+        proxy_client: ProxyClientConfig::default(),
+        startup_check: StartupCheckConfig::default(),
     };
 
     let result = config.validate();
@@ -234,6 +343,11 @@ fn test_config_validation_no_targets() {
         static_config: StaticConfig {
             directory: temp_dir.path().to_path_buf(),
             fallback: "index.html".to_string(),
+            spa_fallback: true,
+            spa_fallback_prefixes: vec![],
+            mounts: vec![],
+            cache_control: Default::default(),
+            basic_auth: None,
         },        proxy: vec![ProxyRoute {
             path: "/api/*".to_string(),
             target: None, // No single target
@@ -243,13 +357,43 @@ fn test_config_validation_no_targets() {
             sticky_sessions: false,
             http_health: None,
             websocket_health: None,
+            tcp_health: None,
             ssl: None,
+            client: None,
+            cache: None,
+            http2: false,
+            forwarded_headers: true,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            websocket_limits: None,
+            log_bodies: None,
+            canary: None,
+            mirror: None,
+            host: None,
+            match_regex: false,
+            body_integrity: None,
+            connect_timeout: None,
+            read_timeout: None,
+            header_allowlist: None,
+            header_denylist: Vec::new(),
+            access_log: true,
+            access_log_format: None,
+            startup_check: None,
+            fallback_file: None,
+            idempotency: None,
+            jwt_auth: None,
+            forward_auth: None,
             circuit_breaker: None,
             middleware: None,        }],
         logging: LoggingConfig::default(),
         application: ApplicationConfig::default(),
         server: ServerConfig::default(),
         tunnel: TunnelConfig::default(),
+        tcp_routes: Vec::new(),
+        default_route: None,
+        listeners: Vec::new(),
+        proxy_client: ProxyClientConfig::default(),
+        startup_check: StartupCheckConfig::default(),
     };
 
     let result = config.validate();
@@ -264,6 +408,11 @@ fn test_config_validation_invalid_url() {
         static_config: StaticConfig {
             directory: temp_dir.path().to_path_buf(),
             fallback: "index.html".to_string(),
+            spa_fallback: true,
+            spa_fallback_prefixes: vec![],
+            mounts: vec![],
+            cache_control: Default::default(),
+            basic_auth: None,
         },        proxy: vec![ProxyRoute {
             path: "/api/*".to_string(),
             target: Some("invalid-url".to_string()), // Invalid URL
@@ -273,13 +422,43 @@ fn test_config_validation_invalid_url() {
             sticky_sessions: false,
             http_health: None,
             websocket_health: None,
+            tcp_health: None,
             ssl: None,
+            client: None,
+            cache: None,
+            http2: false,
+            forwarded_headers: true,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            websocket_limits: None,
+            log_bodies: None,
+            canary: None,
+            mirror: None,
+            host: None,
+            match_regex: false,
+            body_integrity: None,
+            connect_timeout: None,
+            read_timeout: None,
+            header_allowlist: None,
+            header_denylist: Vec::new(),
+            access_log: true,
+            access_log_format: None,
+            startup_check: None,
+            fallback_file: None,
+            idempotency: None,
+            jwt_auth: None,
+            forward_auth: None,
             circuit_breaker: None,
             middleware: None,        }],
         logging: LoggingConfig::default(),
         application: ApplicationConfig::default(),
         server: ServerConfig::default(),
         tunnel: TunnelConfig::default(),
+        tcp_routes: Vec::new(),
+        default_route: None,
+        listeners: Vec::new(),
+        proxy_client: ProxyClientConfig::default(),
+        startup_check: StartupCheckConfig::default(),
     };
 
     let result = config.validate();
@@ -299,9 +478,34 @@ fn test_proxy_route_legacy_vs_new_targets() {
         sticky_sessions: false,
         http_health: None,
         websocket_health: None,
+        tcp_health: None,
         circuit_breaker: None,
         middleware: None,
         ssl: None,
+        client: None,
+        cache: None,
+        http2: false,
+        forwarded_headers: true,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
     };
 
     assert_eq!(legacy_route.get_primary_target().unwrap(), "http://localhost:8000");
@@ -320,9 +524,34 @@ fn test_proxy_route_legacy_vs_new_targets() {
         sticky_sessions: false,
         http_health: None,
         websocket_health: None,
+        tcp_health: None,
         circuit_breaker: None,
         middleware: None,
         ssl: None,
+        client: None,
+        cache: None,
+        http2: false,
+        forwarded_headers: true,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
     };
 
     assert_eq!(new_route.get_primary_target().unwrap(), "http://localhost:3000");
@@ -337,6 +566,8 @@ fn test_health_config_defaults() {
         timeout: 5,
         path: "/health".to_string(),
         expected_status_codes: vec![200],
+        expected_body_contains: None,
+        expected_body_json: None,
     };
 
     assert_eq!(http_health.interval, 30);
@@ -1,5 +1,7 @@
+pub mod config_error_tests;
 pub mod config_parsing;
 pub mod health_endpoints;
+pub mod self_targeting_tests;
 pub mod ssl_config_tests;
 
 // Re-export test functions for easy access (marked to avoid unused warnings)
@@ -0,0 +1,76 @@
+// Self-targeting route tests: proxy targets pointing back at this server are reported as warnings
+use httpserver_config::{ Config, ConfigWarning, SslConfig };
+use tempfile::TempDir;
+use crate::test_support::route;
+
+/// Config listening on `port` with one proxy route per (path, target)
+fn config_with_routes(port: u16, routes: &[(&str, &str)]) -> Config {
+    let mut config = Config::default();
+    config.server.default_port = port;
+    config.proxy = routes
+        .iter()
+        .map(|(path, target)| route(path, target))
+        .collect();
+    config
+}
+
+fn self_targeting(index: usize, target: &str) -> ConfigWarning {
+    ConfigWarning::SelfTargetingRoute { index, target: target.to_string() }
+}
+
+#[test]
+fn test_route_targeting_own_port_is_flagged() {
+    let routes = [
+        ("/api/*", "http://localhost:3000"),
+        ("/loop/*", "http://127.0.0.1:8080/loop"),
+        ("/any/*", "http://0.0.0.0:8080"),
+        ("/v6/*", "http://[::1]:8080"),
+    ];
+
+    assert_eq!(
+        config_with_routes(8080, &routes).validation_warnings(),
+        vec![
+            self_targeting(1, "http://127.0.0.1:8080/loop"),
+            self_targeting(2, "http://0.0.0.0:8080"),
+            self_targeting(3, "http://[::1]:8080")
+        ]
+    );
+    assert!(config_with_routes(9090, &routes).validation_warnings().is_empty());
+}
+
+#[test]
+fn test_remote_hosts_and_default_ports() {
+    let routes = [
+        ("/remote/*", "http://backend.internal:8080"),
+        ("/plain/*", "http://localhost"),
+    ];
+
+    assert!(config_with_routes(8080, &routes).validation_warnings().is_empty());
+    assert_eq!(config_with_routes(80, &routes).validation_warnings(), vec![self_targeting(1, "http://localhost")]);
+}
+
+#[test]
+fn test_https_port_counts_when_ssl_enabled() {
+    let mut config = config_with_routes(8080, &[("/secure/*", "https://localhost:8443")]);
+    assert!(config.validation_warnings().is_empty());
+
+    config.server.ssl = Some(SslConfig { enabled: true, https_port: 8443, ..SslConfig::default() });
+    assert_eq!(config.validation_warnings(), vec![self_targeting(0, "https://localhost:8443")]);
+}
+
+#[test]
+fn test_check_file_reports_self_targeting_routes() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    let content = format!(
+        "[server]\ndefault_port = 8080\n\n[static_config]\ndirectory = \"{}\"\n\n[[proxy]]\npath = \"/loop/*\"\ntarget = \"http://localhost:8080\"\n",
+        temp_dir.path().to_string_lossy().replace('\\', "/")
+    );
+    std::fs::write(&config_path, content).unwrap();
+
+    let (_, warnings) = Config::check_file(&config_path).unwrap();
+    let messages: Vec<String> = warnings.iter().map(ToString::to_string).collect();
+    assert_eq!(messages, [
+        "Proxy route 0 targets this server and will loop until the hop limit is reached: http://localhost:8080",
+    ]);
+}
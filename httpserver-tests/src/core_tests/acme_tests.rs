@@ -0,0 +1,420 @@
+// ACME certificate issuance tests against an in-process mock CA
+
+use httpserver_core::{ AcmeManager, AcmeChallengeStore, SslCertificateManager, create_acme_challenge_router };
+use httpserver_core::acme::{ LETS_ENCRYPT_PRODUCTION_DIRECTORY, LETS_ENCRYPT_STAGING_DIRECTORY };
+use httpserver_config::LetsEncryptConfig;
+use axum::{
+    Router,
+    Json,
+    body::Bytes,
+    extract::{ Path, State },
+    http::{ StatusCode, header },
+    response::{ IntoResponse, Response },
+    routing::{ get, post },
+};
+use base64::{ engine::general_purpose::URL_SAFE_NO_PAD, Engine as _ };
+use serde_json::{ json, Value };
+use std::sync::{ Arc, Mutex };
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+use tokio::time::Duration;
+
+#[cfg(test)]
+mod acme_tests {
+    use super::*;
+
+    /// Order tracked by the mock CA
+    struct MockOrder {
+        domains: Vec<String>,
+        token: String,
+        authorization_status: String,
+        certificate: Option<String>,
+    }
+
+    /// Mock ACME CA implementing just enough of RFC 8555 for the HTTP-01 flow
+    struct MockCa {
+        base_url: String,
+        challenge_port: u16,
+        validity_days: i64,
+        next_nonce: u64,
+        orders: Vec<MockOrder>,
+        validations: usize,
+    }
+
+    type MockState = Arc<Mutex<MockCa>>;
+
+    fn nonce(state: &MockState) -> String {
+        let mut ca = state.lock().unwrap();
+        ca.next_nonce += 1;
+        format!("nonce-{}", ca.next_nonce)
+    }
+
+    /// Decode the JWS body, asserting the protected header is well formed
+    fn jws_payload(body: &Bytes) -> Value {
+        let jws: Value = serde_json::from_slice(body).unwrap();
+        let protected: Value = serde_json::from_slice(
+            &URL_SAFE_NO_PAD.decode(jws["protected"].as_str().unwrap()).unwrap()
+        ).unwrap();
+        assert_eq!(protected["alg"], "ES256");
+        assert!(protected["nonce"].as_str().unwrap().starts_with("nonce-"));
+        assert!(protected.get("jwk").is_some() || protected.get("kid").is_some());
+        assert!(!jws["signature"].as_str().unwrap().is_empty());
+
+        let payload = jws["payload"].as_str().unwrap();
+        if payload.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).unwrap()).unwrap()
+        }
+    }
+
+    fn acme_json(state: &MockState, status: StatusCode, location: Option<String>, body: Value) -> Response {
+        let mut response = (status, Json(body)).into_response();
+        response.headers_mut().insert("replay-nonce", nonce(state).parse().unwrap());
+        if let Some(location) = location {
+            response.headers_mut().insert(header::LOCATION, location.parse().unwrap());
+        }
+        response
+    }
+
+    fn order_json(ca: &MockCa, id: usize) -> Value {
+        let order = &ca.orders[id];
+        let status = match (&order.certificate, order.authorization_status.as_str()) {
+            (Some(_), _) => "valid",
+            (None, "valid") => "ready",
+            (None, "invalid") => "invalid",
+            _ => "pending",
+        };
+        json!({
+            "status": status,
+            "identifiers": order.domains.iter().map(|d| json!({ "type": "dns", "value": d })).collect::<Vec<_>>(),
+            "authorizations": [format!("{}/authz/{}", ca.base_url, id)],
+            "finalize": format!("{}/finalize/{}", ca.base_url, id),
+            "certificate": order.certificate.as_ref().map(|_| format!("{}/cert/{}", ca.base_url, id)),
+        })
+    }
+
+    fn http_challenge_json(ca: &MockCa, id: usize) -> Value {
+        let order = &ca.orders[id];
+        json!({
+            "type": "http-01",
+            "url": format!("{}/chall/{}", ca.base_url, id),
+            "token": order.token,
+            "status": order.authorization_status,
+        })
+    }
+
+    fn authorization_json(ca: &MockCa, id: usize) -> Value {
+        let order = &ca.orders[id];
+        json!({
+            "identifier": { "type": "dns", "value": order.domains[0] },
+            "status": order.authorization_status,
+            "challenges": [
+                {
+                    "type": "dns-01",
+                    "url": format!("{}/chall-dns/{}", ca.base_url, id),
+                    "token": order.token,
+                    "status": "pending",
+                },
+                http_challenge_json(ca, id),
+            ],
+        })
+    }
+
+    async fn directory(State(state): State<MockState>) -> Json<Value> {
+        let base = state.lock().unwrap().base_url.clone();
+        Json(json!({
+            "newNonce": format!("{}/nonce", base),
+            "newAccount": format!("{}/account", base),
+            "newOrder": format!("{}/order", base),
+        }))
+    }
+
+    async fn new_nonce(State(state): State<MockState>) -> Response {
+        ([("replay-nonce", nonce(&state))], "").into_response()
+    }
+
+    async fn new_account(State(state): State<MockState>, body: Bytes) -> Response {
+        let payload = jws_payload(&body);
+        assert_eq!(payload["termsOfServiceAgreed"], true);
+        assert_eq!(payload["contact"][0], "mailto:admin@acme.test");
+        let location = format!("{}/account/1", state.lock().unwrap().base_url);
+        acme_json(&state, StatusCode::CREATED, Some(location), json!({ "status": "valid" }))
+    }
+
+    async fn new_order(State(state): State<MockState>, body: Bytes) -> Response {
+        let payload = jws_payload(&body);
+        let domains = payload["identifiers"]
+            .as_array().unwrap()
+            .iter()
+            .map(|identifier| identifier["value"].as_str().unwrap().to_string())
+            .collect();
+        let (location, order) = {
+            let mut ca = state.lock().unwrap();
+            let id = ca.orders.len();
+            ca.orders.push(MockOrder {
+                domains,
+                token: format!("token-{}", id),
+                authorization_status: "pending".to_string(),
+                certificate: None,
+            });
+            (format!("{}/order/{}", ca.base_url, id), order_json(&ca, id))
+        };
+        acme_json(&state, StatusCode::CREATED, Some(location), order)
+    }
+
+    async fn get_order(State(state): State<MockState>, Path(id): Path<usize>, body: Bytes) -> Response {
+        jws_payload(&body);
+        let order = order_json(&state.lock().unwrap(), id);
+        acme_json(&state, StatusCode::OK, None, order)
+    }
+
+    async fn get_authorization(State(state): State<MockState>, Path(id): Path<usize>, body: Bytes) -> Response {
+        jws_payload(&body);
+        let authorization = authorization_json(&state.lock().unwrap(), id);
+        acme_json(&state, StatusCode::OK, None, authorization)
+    }
+
+    /// Validate HTTP-01 by fetching the key authorization like a real CA would
+    async fn respond_to_challenge(State(state): State<MockState>, Path(id): Path<usize>, body: Bytes) -> Response {
+        // An empty payload only fetches the challenge; `{}` asks for validation
+        if jws_payload(&body).is_null() {
+            let challenge = http_challenge_json(&state.lock().unwrap(), id);
+            return acme_json(&state, StatusCode::OK, None, challenge);
+        }
+
+        let (port, token) = {
+            let ca = state.lock().unwrap();
+            (ca.challenge_port, ca.orders[id].token.clone())
+        };
+
+        let url = format!("http://127.0.0.1:{}/.well-known/acme-challenge/{}", port, token);
+        let key_authorization = match reqwest::get(&url).await {
+            Ok(response) if response.status().is_success() => response.text().await.unwrap_or_default(),
+            _ => String::new(),
+        };
+        let valid = key_authorization.starts_with(&format!("{}.", token)) &&
+            key_authorization.len() > token.len() + 1;
+
+        let challenge = {
+            let mut ca = state.lock().unwrap();
+            ca.validations += 1;
+            ca.orders[id].authorization_status = (if valid { "valid" } else { "invalid" }).to_string();
+            http_challenge_json(&ca, id)
+        };
+        acme_json(&state, StatusCode::OK, None, challenge)
+    }
+
+    async fn finalize(State(state): State<MockState>, Path(id): Path<usize>, body: Bytes) -> Response {
+        let payload = jws_payload(&body);
+        assert!(!payload["csr"].as_str().unwrap().is_empty());
+
+        let order = {
+            let mut ca = state.lock().unwrap();
+            assert_eq!(ca.orders[id].authorization_status, "valid");
+
+            let mut params = rcgen::CertificateParams::new(ca.orders[id].domains.clone());
+            let now = chrono::Utc::now();
+            let not_after = now + chrono::Duration::days(ca.validity_days);
+            params.not_before = rcgen::date_time_ymd(2020, 1, 1);
+            params.not_after = rcgen::date_time_ymd(
+                chrono::Datelike::year(&not_after),
+                chrono::Datelike::month(&not_after) as u8,
+                chrono::Datelike::day(&not_after) as u8
+            );
+            let cert = rcgen::Certificate::from_params(params).unwrap();
+            ca.orders[id].certificate = Some(cert.serialize_pem().unwrap());
+            order_json(&ca, id)
+        };
+        acme_json(&state, StatusCode::OK, None, order)
+    }
+
+    async fn download_certificate(State(state): State<MockState>, Path(id): Path<usize>, body: Bytes) -> Response {
+        jws_payload(&body);
+        let pem = state.lock().unwrap().orders[id].certificate.clone().unwrap();
+        ([("replay-nonce", nonce(&state))], pem).into_response()
+    }
+
+    /// Start the mock CA, returning its shared state
+    async fn start_mock_ca(challenge_port: u16, validity_days: i64) -> MockState {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(MockCa {
+            base_url,
+            challenge_port,
+            validity_days,
+            next_nonce: 0,
+            orders: Vec::new(),
+            validations: 0,
+        }));
+
+        let app = Router::new()
+            .route("/directory", get(directory))
+            .route("/nonce", get(new_nonce).head(new_nonce))
+            .route("/account", post(new_account))
+            .route("/order", post(new_order))
+            .route("/order/:id", post(get_order))
+            .route("/authz/:id", post(get_authorization))
+            .route("/chall/:id", post(respond_to_challenge))
+            .route("/finalize/:id", post(finalize))
+            .route("/cert/:id", post(download_certificate))
+            .with_state(state.clone());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        state
+    }
+
+    /// Serve HTTP-01 challenges from the store on an ephemeral port
+    async fn start_challenge_server(store: AcmeChallengeStore) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            axum::serve(listener, create_acme_challenge_router(store)).await.unwrap();
+        });
+        port
+    }
+
+    fn lets_encrypt_config(directory_url: Option<String>, cache_dir: &TempDir) -> LetsEncryptConfig {
+        LetsEncryptConfig {
+            enabled: true,
+            email: "admin@acme.test".to_string(),
+            domain: Some("acme.test".to_string()),
+            additional_domains: vec!["www.acme.test".to_string()],
+            staging: true,
+            directory_url,
+            cache_dir: cache_dir.path().to_path_buf(),
+            renew_before_days: 30,
+            dns_challenge: None,
+        }
+    }
+
+    async fn start_issuance_env(validity_days: i64) -> (MockState, AcmeManager, AcmeChallengeStore, TempDir) {
+        let store = AcmeChallengeStore::new();
+        let challenge_port = start_challenge_server(store.clone()).await;
+        let ca = start_mock_ca(challenge_port, validity_days).await;
+        let cache_dir = TempDir::new().unwrap();
+        let directory_url = format!("{}/directory", ca.lock().unwrap().base_url);
+        let manager = AcmeManager::new(
+            lets_encrypt_config(Some(directory_url), &cache_dir),
+            store.clone()
+        );
+        (ca, manager, store, cache_dir)
+    }
+
+    #[test]
+    fn test_directory_url_respects_staging_flag() {
+        let cache_dir = TempDir::new().unwrap();
+        let mut config = lets_encrypt_config(None, &cache_dir);
+
+        let manager = AcmeManager::new(config.clone(), AcmeChallengeStore::new());
+        assert_eq!(manager.directory_url(), LETS_ENCRYPT_STAGING_DIRECTORY);
+
+        config.staging = false;
+        let manager = AcmeManager::new(config.clone(), AcmeChallengeStore::new());
+        assert_eq!(manager.directory_url(), LETS_ENCRYPT_PRODUCTION_DIRECTORY);
+
+        config.directory_url = Some("https://localhost:14000/dir".to_string());
+        let manager = AcmeManager::new(config, AcmeChallengeStore::new());
+        assert_eq!(manager.directory_url(), "https://localhost:14000/dir");
+    }
+
+    #[tokio::test]
+    async fn test_challenge_router_serves_only_known_tokens() {
+        let store = AcmeChallengeStore::new();
+        store.insert("known-token", "known-token.thumbprint");
+        let port = start_challenge_server(store.clone()).await;
+
+        let url = format!("http://127.0.0.1:{}/.well-known/acme-challenge/", port);
+        let response = reqwest::get(format!("{}known-token", url)).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.text().await.unwrap(), "known-token.thumbprint");
+
+        let response = reqwest::get(format!("{}other-token", url)).await.unwrap();
+        assert_eq!(response.status(), 404);
+
+        store.remove("known-token");
+        let response = reqwest::get(format!("{}known-token", url)).await.unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_http01_issuance_happy_path() {
+        let (ca, manager, store, cache_dir) = start_issuance_env(90).await;
+        assert!(manager.needs_renewal(), "No cached certificate yet");
+
+        let (cert_path, key_path) = manager.ensure_certificate().await.expect("Issuance failed");
+        assert!(cert_path.exists());
+        assert!(key_path.exists());
+        assert!(!manager.needs_renewal());
+        assert!(store.get("token-0").is_none(), "Challenge token should be withdrawn");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: &std::path::Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&key_path), 0o600, "Certificate key must be private");
+            assert_eq!(mode(&cache_dir.path().join("account.json")), 0o600, "Account key must be private");
+        }
+        {
+            let ca = ca.lock().unwrap();
+            assert_eq!(ca.orders.len(), 1);
+            assert_eq!(ca.validations, 1);
+            assert_eq!(ca.orders[0].domains, vec!["acme.test", "www.acme.test"]);
+        }
+
+        // Issued files load into a usable rustls configuration
+        let mut ssl_manager = SslCertificateManager::new();
+        ssl_manager
+            .load_certificate_from_files("acme.test".to_string(), &cert_path, &key_path, None)
+            .unwrap();
+        assert!(ssl_manager.create_server_config("acme.test").is_ok());
+
+        // A valid cached certificate is reused without contacting the CA
+        manager.ensure_certificate().await.unwrap();
+        assert_eq!(ca.lock().unwrap().orders.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_certificate_near_expiry_triggers_renewal() {
+        let (ca, manager, _store, _cache_dir) = start_issuance_env(10).await;
+
+        manager.ensure_certificate().await.unwrap();
+        let first_expiry = manager.certificate_expiry().unwrap();
+        assert!(manager.needs_renewal(), "10 days left is inside the 30 day window");
+
+        // The renewal task replaces the certificate once the CA issues longer-lived ones
+        ca.lock().unwrap().validity_days = 90;
+        let manager = Arc::new(manager);
+        let renewal = manager.clone().spawn_renewal_task(Duration::from_millis(100));
+
+        let mut renewed = false;
+        for _ in 0..50 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            if !manager.needs_renewal() {
+                renewed = true;
+                break;
+            }
+        }
+        renewal.abort();
+
+        assert!(renewed, "Renewal task should have re-issued the certificate");
+        assert!(manager.certificate_expiry().unwrap() > first_expiry);
+        assert_eq!(ca.lock().unwrap().orders.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_wildcard_without_dns_challenge_is_rejected() {
+        let (ca, _manager, store, cache_dir) = start_issuance_env(90).await;
+        let directory_url = format!("{}/directory", ca.lock().unwrap().base_url);
+        let mut config = lets_encrypt_config(Some(directory_url), &cache_dir);
+        config.domain = Some("*.acme.test".to_string());
+        config.additional_domains.clear();
+        let manager = AcmeManager::new(config, store);
+
+        let (cert_path, _) = manager.certificate_paths().unwrap();
+        assert!(cert_path.to_string_lossy().contains("_wildcard.acme.test"));
+        let error = manager.ensure_certificate().await.unwrap_err().to_string();
+        assert!(error.contains("dns_challenge"), "Unexpected error: {}", error);
+        assert_eq!(ca.lock().unwrap().validations, 0);
+    }
+}
@@ -0,0 +1,337 @@
+// Request body limit tests: bodies at the limit pass, larger ones are rejected with 413
+
+use httpserver_core::{ Server, request_body_limit_middleware };
+use httpserver_proxy::ProxyHandler;
+use httpserver_config::{ ProxyRoute, LoadBalancingStrategy };
+use axum::{
+    Router,
+    body::{ Body, Bytes },
+    extract::{ DefaultBodyLimit, Request },
+    http::{ StatusCode, header },
+    response::IntoResponse,
+    routing::{ get, post },
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tower::ServiceExt;
+use crate::test_support::serve;
+
+#[cfg(test)]
+mod body_limit_tests {
+    use super::*;
+
+    const LIMIT: usize = 1024;
+
+    /// Apply the limit the way `Server::start` does
+    fn with_body_limit(app: Router) -> Router {
+        app
+            .layer(axum::middleware::from_fn_with_state(LIMIT, request_body_limit_middleware))
+            .layer(DefaultBodyLimit::max(LIMIT))
+    }
+
+    /// Upload handler reporting how many bytes it received
+    fn create_upload_app() -> Router {
+        with_body_limit(
+            Router::new().route(
+                "/upload",
+                post(|body: Bytes| async move { body.len().to_string() })
+            )
+        )
+    }
+
+    /// Request with an explicit Content-Length header
+    fn sized_request(path: &str, size: usize) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri(path)
+            .header(header::CONTENT_LENGTH, size)
+            .body(Body::from(vec![b'x'; size]))
+            .unwrap()
+    }
+
+    /// Request whose body is streamed without a Content-Length header
+    fn chunked_request(path: &str, size: usize) -> Request<Body> {
+        let chunks: Vec<Result<Bytes, std::io::Error>> = vec![b'x'; size]
+            .chunks(256)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+        Request::builder()
+            .method("POST")
+            .uri(path)
+            .body(Body::from_stream(futures_util::stream::iter(chunks)))
+            .unwrap()
+    }
+
+    /// Backend echoing the size of uploads and returning bodies of the requested size
+    async fn start_backend() -> u16 {
+        let backend = Router::new()
+            .route(
+                "/upload",
+                post(|body: Bytes| async move { body.len().to_string() })
+            )
+            .route(
+                "/download/:size",
+                get(|axum::extract::Path(size): axum::extract::Path<usize>| async move {
+                    "x".repeat(size)
+                })
+            )
+            .route(
+                "/stream/:size",
+                get(|axum::extract::Path(size): axum::extract::Path<usize>| async move {
+                    // No Content-Length: the limit has to be enforced while reading
+                    let chunks: Vec<Result<Bytes, std::io::Error>> = vec![b'x'; size]
+                        .chunks(256)
+                        .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+                        .collect();
+                    Body::from_stream(futures_util::stream::iter(chunks))
+                })
+            );
+        let port = serve(backend).await;
+        port
+    }
+
+    fn create_route(path: &str, backend_port: u16, request_limit: Option<u64>) -> ProxyRoute {
+        ProxyRoute {
+            path: path.to_string(),
+            target: Some(format!("http://127.0.0.1:{}", backend_port)),
+            targets: vec![],
+            strategy: LoadBalancingStrategy::RoundRobin,
+            timeout: 5,
+            sticky_sessions: false,
+            http_health: None,
+            websocket_health: None,
+            tcp_health: None,
+            circuit_breaker: None,
+            middleware: None,
+            ssl: None,
+            client: None,
+            cache: None,
+            http2: false,
+            forwarded_headers: true,
+            max_request_body_bytes: request_limit,
+            max_response_body_bytes: None,
+            websocket_limits: None,
+            log_bodies: None,
+            canary: None,
+            mirror: None,
+            host: None,
+            match_regex: false,
+            body_integrity: None,
+            connect_timeout: None,
+            read_timeout: None,
+            header_allowlist: None,
+            header_denylist: Vec::new(),
+            access_log: true,
+            access_log_format: None,
+            startup_check: None,
+            fallback_file: None,
+            idempotency: None,
+            jwt_auth: None,
+            forward_auth: None,
+        }
+    }
+
+    /// App forwarding every request through the proxy handler
+    fn create_proxy_app(routes: Vec<ProxyRoute>) -> Router {
+        let handler = Arc::new(ProxyHandler::new(routes));
+        Router::new().fallback(move |request: Request| {
+            let handler = handler.clone();
+            async move {
+                let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+                match handler.handle_request(request, client_ip).await {
+                    Some(Ok(response)) => response,
+                    Some(Err(e)) => e.into_response(),
+                    None => StatusCode::NOT_FOUND.into_response(),
+                }
+            }
+        })
+    }
+
+    async fn send(app: Router, request: Request<Body>) -> (StatusCode, String) {
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8_lossy(&body).to_string())
+    }
+
+    #[tokio::test]
+    async fn test_upload_within_limit_is_accepted() {
+        let (status, body) = send(create_upload_app(), sized_request("/upload", LIMIT)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, LIMIT.to_string());
+
+        let (status, body) = send(create_upload_app(), chunked_request("/upload", LIMIT)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, LIMIT.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_upload_over_limit_is_rejected() {
+        // Declared length is rejected before the body is read
+        let (status, _) = send(create_upload_app(), sized_request("/upload", LIMIT + 1)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+        // Streamed bodies are cut off once they pass the limit
+        let (status, _) = send(create_upload_app(), chunked_request("/upload", LIMIT + 1)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_proxied_request_body_limit() {
+        // Backend echoes the size of the body it received
+        let backend_port = start_backend().await;
+
+        let route = ProxyRoute {
+            path: "/api/*".to_string(),
+            target: Some(format!("http://127.0.0.1:{}", backend_port)),
+            targets: vec![],
+            strategy: LoadBalancingStrategy::RoundRobin,
+            timeout: 5,
+            sticky_sessions: false,
+            http_health: None,
+            websocket_health: None,
+            tcp_health: None,
+            circuit_breaker: None,
+            middleware: None,
+            ssl: None,
+            client: None,
+            cache: None,
+            http2: false,
+            forwarded_headers: true,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            websocket_limits: None,
+            log_bodies: None,
+            canary: None,
+            mirror: None,
+            host: None,
+            match_regex: false,
+            body_integrity: None,
+            connect_timeout: None,
+            read_timeout: None,
+            header_allowlist: None,
+            header_denylist: Vec::new(),
+            access_log: true,
+            access_log_format: None,
+            startup_check: None,
+            fallback_file: None,
+            idempotency: None,
+            jwt_auth: None,
+            forward_auth: None,
+        };
+        let app = with_body_limit(create_proxy_app(vec![route]));
+
+        let (status, body) = send(app.clone(), sized_request("/api/upload", LIMIT)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, LIMIT.to_string());
+
+        let (status, _) = send(app.clone(), sized_request("/api/upload", LIMIT + 1)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+        let (status, _) = send(app, chunked_request("/api/upload", LIMIT + 1)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_route_limit_tighter_than_global() {
+        let backend_port = start_backend().await;
+        let app = with_body_limit(
+            create_proxy_app(
+                vec![
+                    create_route("/json/*", backend_port, Some(256)),
+                    create_route("/api/*", backend_port, None)
+                ]
+            )
+        );
+
+        let (status, body) = send(app.clone(), sized_request("/json/upload", 256)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "256");
+
+        // Under the global limit but over the route's
+        let (status, _) = send(app.clone(), sized_request("/json/upload", 512)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        let (status, _) = send(app.clone(), chunked_request("/json/upload", 512)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+        // Routes without their own limit keep the global one
+        let (status, _) = send(app, sized_request("/api/upload", 512)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_route_limit_looser_than_global() {
+        let backend_port = start_backend().await;
+        let uploads_limit = 4 * LIMIT;
+        let routes = vec![
+            create_route("/uploads/*", backend_port, Some(uploads_limit as u64)),
+            create_route("/api/*", backend_port, None)
+        ];
+
+        // Run the real server so the global limit layer and the route override interact
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let server = Server::new(port)
+            .with_max_request_size(LIMIT)
+            .with_body_limit_override(
+                Arc::new(|req: &Request| {
+                    req.uri().path().starts_with("/uploads/").then_some(4 * LIMIT)
+                })
+            );
+        let app = create_proxy_app(routes);
+        let server_task = tokio::spawn(async move {
+            let _ = server.start(app).await;
+        });
+        for _ in 0..50 {
+            if tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+
+        let client = reqwest::Client::new();
+        let upload = |path: &str, size: usize| {
+            client
+                .post(format!("http://127.0.0.1:{}{}", port, path))
+                .body(vec![b'x'; size])
+                .send()
+        };
+
+        let response = upload("/uploads/upload", 2 * LIMIT).await.unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(response.text().await.unwrap(), (2 * LIMIT).to_string());
+
+        let response = upload("/uploads/upload", uploads_limit + 1).await.unwrap();
+        assert_eq!(response.status().as_u16(), 413);
+
+        // Other routes are still held to the global limit
+        let response = upload("/api/upload", 2 * LIMIT).await.unwrap();
+        assert_eq!(response.status().as_u16(), 413);
+
+        server_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_oversized_backend_response_is_bad_gateway() {
+        let backend_port = start_backend().await;
+        let mut route = create_route("/api/*", backend_port, None);
+        route.max_response_body_bytes = Some(1000);
+        let app = create_proxy_app(vec![route]);
+
+        let get_request = |path: &str| Request::builder().uri(path).body(Body::empty()).unwrap();
+
+        let (status, body) = send(app.clone(), get_request("/api/download/1000")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.len(), 1000);
+
+        // Rejected from Content-Length before any of the body is sent
+        let (status, _) = send(app.clone(), get_request("/api/download/1001")).await;
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+
+        // An unsized body is streamed, so passing the limit aborts it part way
+        let (status, _) = send(app.clone(), get_request("/api/stream/1000")).await;
+        assert_eq!(status, StatusCode::OK);
+        let response = app.oneshot(get_request("/api/stream/4096")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(axum::body::to_bytes(response.into_body(), usize::MAX).await.is_err());
+    }
+}
@@ -0,0 +1,173 @@
+// TLS certificate hot reload tests
+
+use httpserver_core::SslCertificateManager;
+use base64::{ engine::general_purpose::STANDARD, Engine as _ };
+use rcgen::{ Certificate, CertificateParams, DistinguishedName };
+use rustls::client::{ ServerCertVerified, ServerCertVerifier };
+use rustls::{ ClientConfig, ServerName };
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tempfile::TempDir;
+use tokio::io::{ AsyncReadExt, AsyncWriteExt };
+use tokio::net::{ TcpListener, TcpStream };
+use tokio::time::{ sleep, Duration };
+use tokio_rustls::{ TlsAcceptor, TlsConnector, client::TlsStream };
+
+#[cfg(test)]
+mod cert_reload_tests {
+    use super::*;
+
+    /// Accepts any server certificate so tests can inspect self-signed ones
+    struct AcceptAnyCertificate;
+
+    impl ServerCertVerifier for AcceptAnyCertificate {
+        fn verify_server_cert(
+            &self,
+            _end_entity: &rustls::Certificate,
+            _intermediates: &[rustls::Certificate],
+            _server_name: &ServerName,
+            _scts: &mut dyn Iterator<Item = &[u8]>,
+            _ocsp_response: &[u8],
+            _now: SystemTime
+        ) -> Result<ServerCertVerified, rustls::Error> {
+            Ok(ServerCertVerified::assertion())
+        }
+    }
+
+    /// Write a self-signed localhost certificate, returning its DER encoding
+    fn write_certificate(dir: &Path, organization: &str) -> Vec<u8> {
+        let mut params = CertificateParams::new(vec!["localhost".to_string()]);
+        params.distinguished_name = DistinguishedName::new();
+        params.distinguished_name.push(rcgen::DnType::CommonName, "localhost");
+        params.distinguished_name.push(rcgen::DnType::OrganizationName, organization);
+        let cert = Certificate::from_params(params).unwrap();
+
+        let pem = cert.serialize_pem().unwrap();
+        fs::write(dir.join("server.key"), cert.serialize_private_key_pem()).unwrap();
+        fs::write(dir.join("server.crt"), &pem).unwrap();
+
+        let body: String = pem.lines().filter(|line| !line.starts_with("-----")).collect();
+        STANDARD.decode(body).unwrap()
+    }
+
+    /// Start a TLS echo server using the manager's configuration
+    async fn start_tls_echo_server(manager: &SslCertificateManager) -> u16 {
+        let acceptor = TlsAcceptor::from(manager.create_server_config("localhost").unwrap());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    let Ok(mut tls) = acceptor.accept(stream).await else {
+                        return;
+                    };
+                    let mut buf = [0u8; 64];
+                    while let Ok(n) = tls.read(&mut buf).await {
+                        if n == 0 || tls.write_all(&buf[..n]).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        port
+    }
+
+    /// Perform a TLS handshake, returning the stream and presented certificate
+    async fn handshake(port: u16) -> (TlsStream<TcpStream>, Vec<u8>) {
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate))
+            .with_no_client_auth();
+        let connector = TlsConnector::from(Arc::new(config));
+        let tcp = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let stream = connector
+            .connect(ServerName::try_from("localhost").unwrap(), tcp).await
+            .unwrap();
+        let presented = stream.get_ref().1.peer_certificates().unwrap()[0].0.clone();
+        (stream, presented)
+    }
+
+    async fn echo(stream: &mut TlsStream<TcpStream>, message: &[u8]) -> Vec<u8> {
+        stream.write_all(message).await.unwrap();
+        let mut buf = vec![0u8; message.len()];
+        stream.read_exact(&mut buf).await.unwrap();
+        buf
+    }
+
+    fn load_manager(dir: &Path) -> SslCertificateManager {
+        let mut manager = SslCertificateManager::new();
+        manager
+            .load_certificate_from_files(
+                "localhost".to_string(),
+                dir.join("server.crt"),
+                dir.join("server.key"),
+                None
+            )
+            .unwrap();
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_reload_certificates_applies_to_new_handshakes() {
+        let temp_dir = TempDir::new().unwrap();
+        let original = write_certificate(temp_dir.path(), "Original");
+        let mut manager = load_manager(temp_dir.path());
+        let port = start_tls_echo_server(&manager).await;
+
+        let (_, presented) = handshake(port).await;
+        assert_eq!(presented, original);
+
+        let replacement = write_certificate(temp_dir.path(), "Replacement");
+        assert_eq!(manager.reload_certificates().unwrap(), 1);
+
+        let (_, presented) = handshake(port).await;
+        assert_eq!(presented, replacement);
+    }
+
+    #[tokio::test]
+    async fn test_failed_reload_keeps_previous_certificate() {
+        let temp_dir = TempDir::new().unwrap();
+        let original = write_certificate(temp_dir.path(), "Original");
+        let mut manager = load_manager(temp_dir.path());
+        let port = start_tls_echo_server(&manager).await;
+
+        fs::write(temp_dir.path().join("server.crt"), "not a certificate").unwrap();
+        assert!(manager.reload_certificates().is_err());
+
+        let (_, presented) = handshake(port).await;
+        assert_eq!(presented, original);
+    }
+
+    #[tokio::test]
+    async fn test_watcher_reloads_replaced_certificate_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let original = write_certificate(temp_dir.path(), "Original");
+        let manager = load_manager(temp_dir.path());
+        let port = start_tls_echo_server(&manager).await;
+        let _watcher = manager.watch_for_changes().unwrap();
+
+        let (mut existing, presented) = handshake(port).await;
+        assert_eq!(presented, original);
+
+        let replacement = write_certificate(temp_dir.path(), "Replacement");
+
+        let mut presented = Vec::new();
+        for _ in 0..50 {
+            sleep(Duration::from_millis(100)).await;
+            presented = handshake(port).await.1;
+            if presented == replacement {
+                break;
+            }
+        }
+        assert_eq!(presented, replacement, "New handshakes should present the replaced certificate");
+
+        // The established connection keeps working on its original certificate
+        assert_eq!(existing.get_ref().1.peer_certificates().unwrap()[0].0, original);
+        assert_eq!(echo(&mut existing, b"still connected").await, b"still connected");
+    }
+}
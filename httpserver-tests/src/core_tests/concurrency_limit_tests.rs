@@ -0,0 +1,150 @@
+// Concurrency limit tests: requests beyond the limit queue for a slot and get a 503 when the queue is saturated
+
+use httpserver_core::{ ConcurrencyLimit, concurrency_limit_middleware };
+use httpserver_config::{ ConcurrencyLimitConfig, ServerConfig };
+use axum::{ Router, body::Body, extract::Request, http::StatusCode, response::Response, routing::get };
+use std::sync::Arc;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use tokio::sync::Semaphore;
+use tokio::time::{ Duration, Instant };
+use tower::ServiceExt;
+
+#[cfg(test)]
+mod concurrency_limit_tests {
+    use super::*;
+
+    /// Router whose handler records the highest number of requests it saw at once and only
+    /// answers once `release` hands it a permit
+    fn create_app(limit: Arc<ConcurrencyLimit>, release: Arc<Semaphore>, peak: Arc<AtomicUsize>) -> Router {
+        let active = Arc::new(AtomicUsize::new(0));
+        Router::new()
+            .route(
+                "/",
+                get(move || async move {
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    release.acquire().await.unwrap().forget();
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    "done"
+                })
+            )
+            .layer(axum::middleware::from_fn_with_state(limit, concurrency_limit_middleware))
+    }
+
+    fn send(app: &Router) -> tokio::task::JoinHandle<Response> {
+        let app = app.clone();
+        tokio::spawn(async move { app.oneshot(Request::builder().uri("/").body(Body::empty()).unwrap()).await.unwrap() })
+    }
+
+    async fn wait_until(condition: impl Fn() -> bool) {
+        for _ in 0..100 {
+            if condition() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("Condition not reached");
+    }
+
+    #[tokio::test]
+    async fn test_requests_over_the_limit_wait_for_a_slot() {
+        let limit = Arc::new(ConcurrencyLimit::new(2, 10, Duration::from_secs(5)));
+        let release = Arc::new(Semaphore::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let app = create_app(limit.clone(), release.clone(), peak.clone());
+
+        // limit + 1 requests at once: two are handled, the third waits
+        let requests: Vec<_> = (0..3).map(|_| send(&app)).collect();
+        wait_until(|| limit.in_flight() == 2 && limit.queued() == 1).await;
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+
+        // Freeing a slot lets the queued request through
+        release.add_permits(3);
+        for request in requests {
+            assert_eq!(request.await.unwrap().status(), StatusCode::OK);
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(limit.in_flight(), 0);
+        assert_eq!(limit.queued(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_is_rejected_with_retry_after() {
+        let limit = Arc::new(ConcurrencyLimit::new(1, 1, Duration::from_secs(5)).with_retry_after(7));
+        let release = Arc::new(Semaphore::new(0));
+        let app = create_app(limit.clone(), release.clone(), Arc::new(AtomicUsize::new(0)));
+
+        let handled = send(&app);
+        wait_until(|| limit.in_flight() == 1).await;
+        let queued = send(&app);
+        wait_until(|| limit.queued() == 1).await;
+
+        // Saturated: no slot and no room in the queue
+        let rejected = send(&app).await.unwrap();
+        assert_eq!(rejected.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(rejected.headers()["retry-after"], "7");
+
+        release.add_permits(2);
+        assert_eq!(handled.await.unwrap().status(), StatusCode::OK);
+        assert_eq!(queued.await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_queued_request_times_out() {
+        let limit = Arc::new(ConcurrencyLimit::new(1, 10, Duration::from_millis(100)));
+        let release = Arc::new(Semaphore::new(0));
+        let app = create_app(limit.clone(), release.clone(), Arc::new(AtomicUsize::new(0)));
+
+        let handled = send(&app);
+        wait_until(|| limit.in_flight() == 1).await;
+
+        let started = Instant::now();
+        let timed_out = send(&app).await.unwrap();
+        assert_eq!(timed_out.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(timed_out.headers().contains_key("retry-after"));
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert_eq!(limit.queued(), 0);
+
+        release.add_permits(1);
+        assert_eq!(handled.await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_queued_requests_leave_the_queue() {
+        let limit = Arc::new(ConcurrencyLimit::new(1, 2, Duration::from_secs(5)));
+        let release = Arc::new(Semaphore::new(0));
+        let app = create_app(limit.clone(), release.clone(), Arc::new(AtomicUsize::new(0)));
+
+        let handled = send(&app);
+        wait_until(|| limit.in_flight() == 1).await;
+        let queued: Vec<_> = (0..2).map(|_| send(&app)).collect();
+        wait_until(|| limit.queued() == 2).await;
+
+        // Clients giving up while queued free their places for new requests
+        for request in queued {
+            request.abort();
+        }
+        wait_until(|| limit.queued() == 0).await;
+        let waiting = send(&app);
+        wait_until(|| limit.queued() == 1).await;
+
+        release.add_permits(2);
+        assert_eq!(handled.await.unwrap().status(), StatusCode::OK);
+        assert_eq!(waiting.await.unwrap().status(), StatusCode::OK);
+        assert_eq!(limit.queued(), 0);
+    }
+
+    #[test]
+    fn test_concurrency_limit_config() {
+        let limit: ConcurrencyLimitConfig = serde_json::from_value(
+            serde_json::json!({ "max_requests": 64, "queue_timeout_ms": 250 })
+        ).unwrap();
+        assert_eq!(limit.max_requests, 64);
+        assert_eq!(limit.queue_size, 100);
+        assert_eq!(limit.queue_timeout_ms, 250);
+        assert_eq!(limit.retry_after, 1);
+
+        // Disabled unless configured
+        assert_eq!(ServerConfig::default().concurrency_limit.max_requests, 0);
+    }
+}
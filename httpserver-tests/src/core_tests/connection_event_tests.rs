@@ -0,0 +1,247 @@
+// Connection lifecycle event tests: accept, TLS handshake, backend connect and close events carry stable names and fields
+
+use httpserver_core::{ Server, SslCertificateManager, events };
+use httpserver_proxy::ProxyHandler;
+use httpserver_config::ProxyRoute;
+use axum::{ Router, body::Body, http::Request, routing::get };
+use rcgen::{ Certificate, CertificateParams, DistinguishedName };
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{ Arc, Mutex };
+use tempfile::TempDir;
+use tokio::io::{ AsyncReadExt, AsyncWriteExt };
+use tokio::net::{ TcpListener, TcpStream };
+use tokio::time::Duration;
+use tracing::field::{ Field, Visit };
+use tracing_subscriber::layer::{ Context, Layer, SubscriberExt };
+
+#[cfg(test)]
+mod connection_event_tests {
+    use super::*;
+    use crate::test_support::free_port;
+
+    /// Fields of every captured event that has an `event` field
+    #[derive(Clone, Default)]
+    struct CapturedEvents(Arc<Mutex<Vec<HashMap<String, String>>>>);
+
+    struct FieldMap(HashMap<String, String>);
+
+    impl Visit for FieldMap {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl<S: tracing::Subscriber> Layer<S> for CapturedEvents {
+        fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+            let mut fields = FieldMap(HashMap::new());
+            event.record(&mut fields);
+            if fields.0.contains_key("event") {
+                self.0.lock().unwrap().push(fields.0);
+            }
+        }
+    }
+
+    impl CapturedEvents {
+        /// Capture events on this thread; the current-thread test runtime keeps spawned tasks here too
+        fn install(&self) -> tracing::subscriber::DefaultGuard {
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(self.clone()))
+        }
+
+        /// Names of the events logged for one client address, in order
+        fn names_for(&self, remote_addr: SocketAddr) -> Vec<String> {
+            self.for_addr(remote_addr)
+                .iter()
+                .map(|fields| fields["event"].clone())
+                .collect()
+        }
+
+        fn for_addr(&self, remote_addr: SocketAddr) -> Vec<HashMap<String, String>> {
+            let remote_addr = remote_addr.to_string();
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|fields| fields.get("remote_addr") == Some(&remote_addr))
+                .cloned()
+                .collect()
+        }
+
+        fn named(&self, name: &str) -> Vec<HashMap<String, String>> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|fields| fields["event"] == name)
+                .cloned()
+                .collect()
+        }
+
+        /// Wait until the connection from `remote_addr` has logged its close event
+        async fn wait_for_close(&self, remote_addr: SocketAddr) -> HashMap<String, String> {
+            for _ in 0..100 {
+                let closed = self
+                    .for_addr(remote_addr)
+                    .into_iter()
+                    .find(|fields| fields["event"] == events::CONNECTION_CLOSED);
+                if let Some(closed) = closed {
+                    return closed;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            panic!("No close event for {}: {:?}", remote_addr, self.0.lock().unwrap());
+        }
+    }
+
+    async fn wait_for_port(port: u16) {
+        for _ in 0..50 {
+            if TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("Server did not start listening on port {}", port);
+    }
+
+    fn create_app() -> Router {
+        Router::new().route("/", get(|| async { "ok" }))
+    }
+
+    fn create_ssl_config(temp_dir: &TempDir) -> Arc<rustls::ServerConfig> {
+        let mut params = CertificateParams::new(vec!["localhost".to_string()]);
+        params.distinguished_name = DistinguishedName::new();
+        params.distinguished_name.push(rcgen::DnType::CommonName, "localhost");
+        let cert = Certificate::from_params(params).unwrap();
+
+        let cert_path = temp_dir.path().join("localhost.crt");
+        let key_path = temp_dir.path().join("localhost.key");
+        std::fs::write(&cert_path, cert.serialize_pem().unwrap()).unwrap();
+        std::fs::write(&key_path, cert.serialize_private_key_pem()).unwrap();
+
+        let mut manager = SslCertificateManager::new();
+        manager
+            .load_certificate_from_files("localhost".to_string(), &cert_path, &key_path, None)
+            .unwrap();
+        manager.create_server_config("localhost").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_http_connection_accepted_and_closed() {
+        let captured = CapturedEvents::default();
+        let _guard = captured.install();
+
+        let port = free_port();
+        let server_task = tokio::spawn(async move {
+            let _ = Server::new(port).start(create_app()).await;
+        });
+        wait_for_port(port).await;
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let client_addr = stream.local_addr().unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+        let closed = captured.wait_for_close(client_addr).await;
+        assert_eq!(captured.names_for(client_addr), [events::CONNECTION_ACCEPTED, events::CONNECTION_CLOSED]);
+        let accepted = &captured.for_addr(client_addr)[0];
+        assert_eq!(accepted["scheme"], "http");
+        assert_eq!(accepted["port"], port.to_string());
+        assert!(closed["duration_ms"].parse::<u64>().is_ok(), "{:?}", closed);
+        assert!(!closed.contains_key("error"), "{:?}", closed);
+
+        server_task.abort();
+        let _ = server_task.await;
+    }
+
+    #[tokio::test]
+    async fn test_tls_handshake_success_and_failure() {
+        let captured = CapturedEvents::default();
+        let _guard = captured.install();
+
+        let temp_dir = TempDir::new().unwrap();
+        let (http_port, https_port) = (free_port(), free_port());
+        let server = Server::new_with_ssl(http_port, create_ssl_config(&temp_dir), https_port);
+        let server_task = tokio::spawn(async move {
+            let _ = server.start(create_app()).await;
+        });
+        wait_for_port(https_port).await;
+
+        // A TLS client completes the handshake and negotiates h2
+        let client = reqwest::Client
+            ::builder()
+            .use_rustls_tls()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .unwrap();
+        let response = client.get(format!("https://localhost:{}/", https_port)).send().await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "ok");
+        let succeeded = captured.named(events::TLS_HANDSHAKE_SUCCEEDED);
+        assert_eq!(succeeded.len(), 1, "{:?}", succeeded);
+        assert_eq!(succeeded[0]["alpn"], "h2");
+        assert_eq!(succeeded[0]["port"], https_port.to_string());
+
+        // Plain HTTP sent to the HTTPS port fails the handshake, then the connection closes with the error
+        let mut stream = TcpStream::connect(("127.0.0.1", https_port)).await.unwrap();
+        let client_addr = stream.local_addr().unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+        let mut response = Vec::new();
+        let _ = stream.read_to_end(&mut response).await;
+
+        let closed = captured.wait_for_close(client_addr).await;
+        assert_eq!(
+            captured.names_for(client_addr),
+            [events::CONNECTION_ACCEPTED, events::TLS_HANDSHAKE_FAILED, events::CONNECTION_CLOSED]
+        );
+        assert_eq!(closed["scheme"], "https");
+        assert!(closed.contains_key("error"), "{:?}", closed);
+
+        drop(client);
+        server_task.abort();
+        let _ = server_task.await;
+    }
+
+    #[tokio::test]
+    async fn test_backend_connect_events() {
+        let captured = CapturedEvents::default();
+        let _guard = captured.install();
+
+        // HTTP/2 backends are dialed by the proxy itself
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let backend_port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            axum::serve(listener, create_app()).await.unwrap();
+        });
+        let closed_port = free_port();
+
+        let route = |path: &str, port: u16, http2: bool| -> ProxyRoute {
+            serde_json::from_value(serde_json::json!({
+                "path": path,
+                "target": format!("http://127.0.0.1:{}", port),
+                "http2": http2,
+            })).unwrap()
+        };
+        let handler = ProxyHandler::new(vec![route("/h2/*", backend_port, true), route("/down/*", closed_port, false)]);
+        let client_ip: SocketAddr = "127.0.0.1:40000".parse().unwrap();
+
+        let request = Request::builder().uri("/h2/").body(Body::empty()).unwrap();
+        assert!(handler.handle_request(request, client_ip).await.unwrap().is_ok());
+        let connected = captured.named(events::BACKEND_CONNECTED);
+        assert_eq!(connected.len(), 1, "{:?}", connected);
+        assert_eq!(connected[0]["protocol"], "h2");
+        assert!(connected[0]["target"].contains(&backend_port.to_string()));
+
+        let request = Request::builder().uri("/down/").body(Body::empty()).unwrap();
+        assert!(handler.handle_request(request, client_ip).await.unwrap().is_err());
+        let failed = captured.named(events::BACKEND_CONNECT_FAILED);
+        assert_eq!(failed.len(), 1, "{:?}", failed);
+        assert_eq!(failed[0]["protocol"], "http");
+        assert!(failed[0]["target"].contains(&closed_port.to_string()));
+        assert!(failed[0].contains_key("error"));
+    }
+}
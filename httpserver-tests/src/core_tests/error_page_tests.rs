@@ -0,0 +1,119 @@
+// Custom error page tests: configured pages replace the built-in HTML, other codes keep the default
+
+use httpserver_core::{ create_error_response, error_page_middleware };
+use httpserver_config::{ Config, ErrorPages };
+use httpserver_proxy::ProxyError;
+use axum::{ body::Body, http::{ Request, StatusCode }, response::{ IntoResponse, Response }, routing::get, Router };
+use std::collections::HashMap;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+#[cfg(test)]
+mod error_page_tests {
+    use super::*;
+
+    async fn body_text(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8_lossy(&body).to_string()
+    }
+
+    async fn send(app: &Router, path: &str) -> Response {
+        app.clone().oneshot(Request::builder().uri(path).body(Body::empty()).unwrap()).await.unwrap()
+    }
+
+    #[test]
+    fn test_error_pages_parse_from_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        let toml_content = format!(
+            r#"
+[static_config]
+directory = "{}"
+
+[server.error_pages]
+404 = "errors/404.html"
+502 = "errors/502.html"
+"#,
+            temp_dir.path().to_string_lossy().replace('\\', "/")
+        );
+        std::fs::write(&config_path, toml_content).unwrap();
+
+        let config = Config::load_from_file(&config_path).unwrap();
+        assert_eq!(config.server.error_pages.len(), 2);
+        assert_eq!(config.server.error_pages[&404], std::path::PathBuf::from("errors/404.html"));
+        assert!(Config::default().server.error_pages.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_configured_error_pages_are_served() {
+        let temp_dir = TempDir::new().unwrap();
+        let not_found = temp_dir.path().join("404.html");
+        let bad_gateway = temp_dir.path().join("502.html");
+        std::fs::write(&not_found, "<h1>Branded not found</h1>").unwrap();
+        std::fs::write(&bad_gateway, "<h1>Branded bad gateway</h1>").unwrap();
+
+        // 503 points at a file that does not exist
+        let pages = ErrorPages::load(
+            &HashMap::from([
+                (404, not_found),
+                (502, bad_gateway),
+                (503, temp_dir.path().join("missing.html")),
+            ])
+        );
+        let app = Router::new()
+            .route("/missing", get(|| async { create_error_response(StatusCode::NOT_FOUND, "File not found") }))
+            .route(
+                "/backend",
+                get(|| async { ProxyError::ConnectionFailed("http://localhost:9".to_string()).into_response() })
+            )
+            .route("/denied", get(|| async { create_error_response(StatusCode::FORBIDDEN, "Access denied") }))
+            .route(
+                "/down",
+                get(|| async { create_error_response(StatusCode::SERVICE_UNAVAILABLE, "Down for now") })
+            )
+            .route("/handler", get(|| async { (StatusCode::NOT_FOUND, "Handler body") }))
+            .layer(axum::middleware::from_fn_with_state(pages, error_page_middleware));
+
+        let response = send(&app, "/missing").await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()["content-type"], "text/html");
+        assert_eq!(body_text(response).await, "<h1>Branded not found</h1>");
+
+        let response = send(&app, "/backend").await;
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(body_text(response).await, "<h1>Branded bad gateway</h1>");
+
+        // Unconfigured codes and missing files use the built-in page
+        let html = body_text(send(&app, "/denied").await).await;
+        assert!(html.contains("<!DOCTYPE html>"));
+        assert!(html.contains("403 Forbidden"));
+        assert!(html.contains("Access denied"));
+
+        assert!(body_text(send(&app, "/down").await).await.contains("Down for now"));
+
+        // Error bodies written by handlers themselves are left alone
+        assert_eq!(body_text(send(&app, "/handler").await).await, "Handler body");
+    }
+
+    #[tokio::test]
+    async fn test_error_pages_are_not_shared_between_routers() {
+        let temp_dir = TempDir::new().unwrap();
+        let not_found = temp_dir.path().join("404.html");
+        std::fs::write(&not_found, "<h1>Branded not found</h1>").unwrap();
+        let pages = ErrorPages::load(&HashMap::from([(404, not_found)]));
+        let missing = || async { create_error_response(StatusCode::NOT_FOUND, "File not found") };
+
+        let branded = Router::new()
+            .route("/", get(missing))
+            .layer(axum::middleware::from_fn_with_state(pages, error_page_middleware));
+        let plain = Router::new().route("/", get(missing));
+
+        assert_eq!(body_text(send(&branded, "/").await).await, "<h1>Branded not found</h1>");
+        assert!(body_text(send(&plain, "/").await).await.contains("404 Not Found"));
+        assert!(
+            body_text(create_error_response(StatusCode::NOT_FOUND, "File not found")).await.contains(
+                "File not found"
+            )
+        );
+    }
+}
@@ -0,0 +1,65 @@
+// Request header limit tests: too many headers or too many header bytes are rejected with 431
+
+use httpserver_core::{ HeaderLimits, request_header_limit_middleware };
+use axum::{ Router, body::Body, extract::Request, http::StatusCode, routing::get };
+use tower::ServiceExt;
+
+#[cfg(test)]
+mod header_limit_tests {
+    use super::*;
+
+    /// Apply the limits the way `Server::start` does
+    fn create_app(limits: HeaderLimits) -> Router {
+        Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(limits, request_header_limit_middleware))
+    }
+
+    /// GET / with the given headers, returning the status
+    async fn send(app: Router, headers: &[(String, String)]) -> StatusCode {
+        let mut request = Request::builder().uri("/");
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    /// `count` distinct headers named x-header-N with the given value
+    fn numbered_headers(count: usize, value: &str) -> Vec<(String, String)> {
+        (0..count).map(|i| (format!("x-header-{}", i), value.to_string())).collect()
+    }
+
+    #[tokio::test]
+    async fn test_too_many_headers_rejected() {
+        let app = create_app(HeaderLimits::new(10, 0));
+
+        assert_eq!(send(app.clone(), &numbered_headers(10, "v")).await, StatusCode::OK);
+        assert_eq!(send(app, &numbered_headers(11, "v")).await, StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_headers_rejected() {
+        let app = create_app(HeaderLimits::new(0, 1024));
+
+        // Names and values both count: "x-big" plus 1019 bytes is exactly the limit
+        let at_limit = vec![("x-big".to_string(), "a".repeat(1019))];
+        assert_eq!(send(app.clone(), &at_limit).await, StatusCode::OK);
+
+        let single_huge = vec![("x-big".to_string(), "a".repeat(1020))];
+        assert_eq!(send(app.clone(), &single_huge).await, StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE);
+
+        // Many moderate headers add up
+        assert_eq!(
+            send(app, &numbered_headers(8, &"a".repeat(200))).await,
+            StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_zero_disables_limits() {
+        assert_eq!(HeaderLimits::new(0, 0), HeaderLimits::default());
+
+        let app = create_app(HeaderLimits::new(0, 0));
+        assert_eq!(send(app, &numbered_headers(500, &"a".repeat(200))).await, StatusCode::OK);
+    }
+}
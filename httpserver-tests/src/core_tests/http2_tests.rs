@@ -0,0 +1,112 @@
+// HTTP/2 listener tests: ALPN negotiation over TLS and optional h2c on the plaintext port
+
+use httpserver_core::{ Server, SslCertificateManager };
+use axum::{ Router, http::Version, routing::get };
+use rcgen::{ Certificate, CertificateParams, DistinguishedName };
+use tempfile::TempDir;
+use tokio::time::Duration;
+
+#[cfg(test)]
+mod http2_tests {
+    use super::*;
+    use crate::test_support::free_port;
+
+    /// Handler reporting the protocol version the server saw
+    fn create_app() -> Router {
+        Router::new().route(
+            "/version",
+            get(|version: Version| async move { format!("{:?}", version) })
+        )
+    }
+
+    async fn wait_for_port(port: u16) {
+        for _ in 0..50 {
+            if tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("Server did not start listening on port {}", port);
+    }
+
+    fn create_ssl_config(temp_dir: &TempDir) -> std::sync::Arc<rustls::ServerConfig> {
+        let mut params = CertificateParams::new(vec!["localhost".to_string()]);
+        params.distinguished_name = DistinguishedName::new();
+        params.distinguished_name.push(rcgen::DnType::CommonName, "localhost");
+        let cert = Certificate::from_params(params).unwrap();
+
+        let cert_path = temp_dir.path().join("localhost.crt");
+        let key_path = temp_dir.path().join("localhost.key");
+        std::fs::write(&cert_path, cert.serialize_pem().unwrap()).unwrap();
+        std::fs::write(&key_path, cert.serialize_private_key_pem()).unwrap();
+
+        let mut manager = SslCertificateManager::new();
+        manager
+            .load_certificate_from_files("localhost".to_string(), &cert_path, &key_path, None)
+            .unwrap();
+        manager.create_server_config("localhost").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_https_negotiates_http2() {
+        let temp_dir = TempDir::new().unwrap();
+        let (http_port, https_port) = (free_port(), free_port());
+        let server = Server::new_with_ssl(http_port, create_ssl_config(&temp_dir), https_port);
+        let server_task = tokio::spawn(async move {
+            let _ = server.start(create_app()).await;
+        });
+        wait_for_port(https_port).await;
+
+        let url = format!("https://localhost:{}/version", https_port);
+
+        // An h2-capable client picks HTTP/2 via ALPN
+        let client = reqwest::Client
+            ::builder()
+            .use_rustls_tls()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .unwrap();
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.version(), reqwest::Version::HTTP_2);
+        assert_eq!(response.text().await.unwrap(), "HTTP/2.0");
+
+        // HTTP/1.1-only clients are still served
+        let client = reqwest::Client
+            ::builder()
+            .use_rustls_tls()
+            .danger_accept_invalid_certs(true)
+            .http1_only()
+            .build()
+            .unwrap();
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.version(), reqwest::Version::HTTP_11);
+        assert_eq!(response.text().await.unwrap(), "HTTP/1.1");
+
+        server_task.abort();
+        let _ = server_task.await;
+    }
+
+    #[tokio::test]
+    async fn test_http2_cleartext_when_enabled() {
+        let port = free_port();
+        let server = Server::new(port).with_http2_cleartext(true);
+        let server_task = tokio::spawn(async move {
+            let _ = server.start(create_app()).await;
+        });
+        wait_for_port(port).await;
+
+        let url = format!("http://127.0.0.1:{}/version", port);
+
+        let client = reqwest::Client::builder().http2_prior_knowledge().build().unwrap();
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.version(), reqwest::Version::HTTP_2);
+        assert_eq!(response.text().await.unwrap(), "HTTP/2.0");
+
+        // The same port keeps serving HTTP/1.1
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(response.version(), reqwest::Version::HTTP_11);
+
+        server_task.abort();
+        let _ = server_task.await;
+    }
+}
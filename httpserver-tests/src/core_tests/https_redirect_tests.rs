@@ -0,0 +1,151 @@
+// HTTPS redirect tests: plain HTTP is redirected unless already secure behind a trusted proxy or exempt
+
+use httpserver_core::{ Server, SslRedirectConfig, TrustedProxies, https_redirect_middleware };
+use httpserver_config::SslConfig;
+use axum::{ Router, body::Body, extract::{ ConnectInfo, Request }, http::StatusCode, routing::get };
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::time::Duration;
+use tower::ServiceExt;
+
+#[cfg(test)]
+mod https_redirect_tests {
+    use super::*;
+
+    fn redirect_config() -> SslRedirectConfig {
+        SslRedirectConfig::new(true, 443).with_trusted_proxies(TrustedProxies::parse(&["10.0.0.0/8"]).unwrap())
+    }
+
+    /// Send a plain HTTP request from `peer` through the redirect middleware
+    async fn send(
+        config: SslRedirectConfig,
+        peer: &str,
+        path: &str,
+        forwarded_proto: Option<&str>
+    ) -> (StatusCode, Option<String>) {
+        let app = Router::new()
+            .fallback(get(|| async { "served" }))
+            .layer(axum::middleware::from_fn_with_state(Arc::new(config), https_redirect_middleware));
+
+        let mut request = Request::builder().uri(path).header("host", "example.com");
+        if let Some(proto) = forwarded_proto {
+            request = request.header("x-forwarded-proto", proto);
+        }
+        let mut request = request.body(Body::empty()).unwrap();
+        let peer: SocketAddr = peer.parse().unwrap();
+        request.extensions_mut().insert(ConnectInfo(peer));
+
+        let response = app.oneshot(request).await.unwrap();
+        let location = response
+            .headers()
+            .get("location")
+            .map(|value| value.to_str().unwrap().to_string());
+        (response.status(), location)
+    }
+
+    #[tokio::test]
+    async fn test_plain_http_is_redirected() {
+        let (status, location) = send(redirect_config(), "203.0.113.9:40000", "/api/users?page=2", None).await;
+        assert_eq!(status, StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(location.as_deref(), Some("https://example.com/api/users?page=2"));
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_https_from_trusted_proxy_is_not_redirected() {
+        let (status, _) = send(redirect_config(), "10.0.0.5:40000", "/api/users", Some("https")).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // The first hop's scheme decides, whatever later proxies appended
+        let (status, _) = send(redirect_config(), "10.0.0.5:40000", "/api/users", Some("https, http")).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, _) = send(redirect_config(), "10.0.0.5:40000", "/api/users", Some("http")).await;
+        assert_eq!(status, StatusCode::MOVED_PERMANENTLY);
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_https_from_untrusted_peer_is_ignored() {
+        let (status, _) = send(redirect_config(), "203.0.113.9:40000", "/api/users", Some("https")).await;
+        assert_eq!(status, StatusCode::MOVED_PERMANENTLY);
+    }
+
+    #[tokio::test]
+    async fn test_exempt_path_is_never_redirected() {
+        let mut config = redirect_config();
+        config.exempt_paths = vec!["/metrics".to_string()];
+
+        let (status, _) = send(config.clone(), "203.0.113.9:40000", "/metrics/prometheus", None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Exemptions replace the defaults
+        let (status, _) = send(config, "203.0.113.9:40000", "/health", None).await;
+        assert_eq!(status, StatusCode::MOVED_PERMANENTLY);
+
+        // ACME challenges must always be reachable over HTTP
+        let (status, _) = send(
+            redirect_config(),
+            "203.0.113.9:40000",
+            "/.well-known/acme-challenge/token",
+            None
+        ).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    fn ssl_config(settings: serde_json::Value) -> SslConfig {
+        let mut ssl = json!({ "enabled": true, "https_port": 8443 });
+        ssl.as_object_mut().unwrap().extend(settings.as_object().unwrap().clone());
+        serde_json::from_value(ssl).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_configured_exempt_path_is_served_over_http() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let config = SslRedirectConfig::from_config(
+            &ssl_config(json!({ "redirect": { "enabled": true, "exempt_paths": ["/metrics"] } }))
+        );
+        let server = Server::new(port).with_https_redirect(config);
+        let app = Router::new().fallback(get(|| async { "served" }));
+        let server_task = tokio::spawn(async move {
+            let _ = server.start(app).await;
+        });
+        for _ in 0..50 {
+            if tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let client = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+        let response = client.get(format!("http://127.0.0.1:{}/metrics", port)).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.text().await.unwrap(), "served");
+
+        // Other paths go to the configured HTTPS port
+        let response = client.get(format!("http://127.0.0.1:{}/api/users", port)).send().await.unwrap();
+        assert_eq!(response.status(), 301);
+        assert_eq!(response.headers()["location"], "https://127.0.0.1:8443/api/users");
+
+        server_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_disabled_redirect_is_skipped() {
+        let config = SslRedirectConfig::from_config(&ssl_config(json!({ "redirect": { "enabled": false } })));
+        assert!(!config.enabled);
+
+        let (status, location) = send(config, "203.0.113.9:40000", "/api/users", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(location, None);
+    }
+
+    #[tokio::test]
+    async fn test_missing_redirect_section_falls_back_to_force_https() {
+        let config = SslRedirectConfig::from_config(&ssl_config(json!({ "force_https": true })));
+        assert!(config.enabled);
+        assert_eq!(config.exempt_paths, vec!["/health", "/ping"]);
+
+        let config = SslRedirectConfig::from_config(&ssl_config(json!({})));
+        assert!(!config.enabled);
+    }
+}
@@ -1,5 +1,7 @@
-use axum::{ http::StatusCode, response::Response, Router, routing::get };
-use httpserver_core::logging_middleware;
+use axum::{ body::Body, http::{ Request, StatusCode }, response::Response, Router, routing::get };
+use httpserver_core::{ logging_middleware, request_timeout_middleware, Server };
+use std::time::{ Duration, Instant };
+use tower::ServiceExt;
 
 async fn dummy_handler() -> &'static str {
     "Hello, World!"
@@ -13,7 +15,6 @@ async fn test_logging_middleware_compiles() {
         .layer(axum::middleware::from_fn(logging_middleware));
 
     // This test just verifies the middleware compiles correctly
-    assert!(true);
 }
 
 #[tokio::test]
@@ -24,7 +25,6 @@ async fn test_middleware_integration() {
     let _app_with_middleware = router.layer(axum::middleware::from_fn(logging_middleware));
 
     // This test verifies middleware integration compiles
-    assert!(true);
 }
 
 #[tokio::test]
@@ -38,7 +38,6 @@ async fn test_middleware_with_multiple_routes() {
     let _app_with_middleware = router.layer(axum::middleware::from_fn(logging_middleware));
 
     // This test verifies middleware can be applied to complex routing
-    assert!(true);
 }
 
 #[tokio::test]
@@ -62,7 +61,6 @@ async fn test_middleware_error_handling() {
     let _app_with_middleware = router.layer(axum::middleware::from_fn(logging_middleware));
 
     // This test verifies middleware compiles with error handling routes
-    assert!(true);
 }
 
 #[tokio::test]
@@ -73,5 +71,59 @@ async fn test_middleware_compilation_only() {
     let _final_app = router.layer(axum::middleware::from_fn(logging_middleware));
 
     // Just verify compilation succeeds
-    assert!(true);
+}
+
+/// Router with fast and slow routes behind the request timeout middleware
+fn create_timeout_app(timeout: Duration) -> Router {
+    Router::new()
+        .route("/fast", get(dummy_handler))
+        .route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                "Too late"
+            })
+        )
+        .layer(axum::middleware::from_fn_with_state(timeout, request_timeout_middleware))
+}
+
+#[tokio::test]
+async fn test_request_timeout_returns_504() {
+    let app = create_timeout_app(Duration::from_millis(200));
+
+    let started = Instant::now();
+    let request = Request::builder().uri("/slow").body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    assert!(started.elapsed() < Duration::from_secs(2), "Timeout should fire after ~200ms");
+
+    let request = Request::builder().uri("/fast").body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_request_timeout_exempts_websocket_upgrades() {
+    let app = create_timeout_app(Duration::from_millis(200));
+
+    let request = Request::builder()
+        .uri("/slow")
+        .header("connection", "keep-alive, Upgrade")
+        .header("upgrade", "websocket")
+        .body(Body::empty())
+        .unwrap();
+    let response = tokio::time
+        ::timeout(Duration::from_secs(10), app.oneshot(request)).await
+        .expect("Handler should run to completion")
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test]
+fn test_zero_request_timeout_disables_limit() {
+    let server = Server::new(8080).with_request_timeout(Duration::from_secs(30));
+    assert_eq!(server.request_timeout, Some(Duration::from_secs(30)));
+
+    let server = Server::new(8080).with_request_timeout(Duration::ZERO);
+    assert_eq!(server.request_timeout, None);
 }
@@ -1,17 +1,70 @@
+// Each file wraps its tests in a module named after the file
+#![allow(clippy::module_inception)]
+
+pub mod acme_tests;
+pub mod body_limit_tests;
+pub mod cert_reload_tests;
+pub mod concurrency_limit_tests;
+pub mod connection_event_tests;
+pub mod error_page_tests;
+pub mod header_limit_tests;
+pub mod http2_tests;
 pub mod https_integration;
+pub mod https_redirect_tests;
 pub mod logging_tests;
 pub mod middleware_tests;
+pub mod multi_listener_tests;
+pub mod request_id_tests;
 pub mod server_functionality;
 pub mod ssl_tests;
+pub mod tcp_options_tests;
+pub mod tcp_proxy_tests;
+pub mod trace_context_tests;
+pub mod trusted_proxy_tests;
+#[cfg(unix)]
+pub mod unix_socket_tests;
 
 // Re-export test functions for easy access (marked to avoid unused warnings)
 #[allow(unused_imports)]
+pub use acme_tests::*;
+#[allow(unused_imports)]
+pub use body_limit_tests::*;
+#[allow(unused_imports)]
+pub use cert_reload_tests::*;
+#[allow(unused_imports)]
+pub use concurrency_limit_tests::*;
+#[allow(unused_imports)]
+pub use connection_event_tests::*;
+#[allow(unused_imports)]
+pub use error_page_tests::*;
+#[allow(unused_imports)]
+pub use header_limit_tests::*;
+#[allow(unused_imports)]
+pub use http2_tests::*;
+#[allow(unused_imports)]
 pub use https_integration::*;
 #[allow(unused_imports)]
+pub use https_redirect_tests::*;
+#[allow(unused_imports)]
 pub use logging_tests::*;
 #[allow(unused_imports)]
 pub use middleware_tests::*;
 #[allow(unused_imports)]
+pub use multi_listener_tests::*;
+#[allow(unused_imports)]
+pub use request_id_tests::*;
+#[allow(unused_imports)]
 pub use server_functionality::*;
 #[allow(unused_imports)]
 pub use ssl_tests::*;
+#[allow(unused_imports)]
+pub use tcp_options_tests::*;
+#[allow(unused_imports)]
+pub use tcp_proxy_tests::*;
+#[allow(unused_imports)]
+pub use trace_context_tests::*;
+#[allow(unused_imports)]
+pub use trusted_proxy_tests::*;
+#[cfg(unix)]
+#[allow(unused_imports)]
+pub use unix_socket_tests::*;
@@ -0,0 +1,111 @@
+// Multiple listener tests: extra ports serve their own routers and drain with the main port on shutdown
+
+use httpserver_core::Server;
+use axum::{ Router, routing::get };
+use std::sync::Arc;
+use tokio::sync::{ Barrier, oneshot };
+use tokio::time::Duration;
+
+#[cfg(test)]
+mod multi_listener_tests {
+    use super::*;
+    use crate::test_support::free_port;
+
+    async fn wait_for_port(port: u16) {
+        for _ in 0..50 {
+            if tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("Server did not start listening on port {}", port);
+    }
+
+    /// Router answering / with `name`, plus a /slow that waits at `barrier` before answering
+    fn create_app(name: &'static str, barrier: Arc<Barrier>) -> Router {
+        Router::new()
+            .route("/", get(move || async move { name }))
+            .route(
+                "/slow",
+                get(move || async move {
+                    barrier.wait().await;
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    name
+                })
+            )
+    }
+
+    /// Public app on the main port and admin app on a second listener
+    fn create_server(http2_cleartext: bool, barrier: Arc<Barrier>) -> (Server, Router, u16, u16) {
+        let (public_port, admin_port) = (free_port(), free_port());
+        let server = Server::new(public_port)
+            .with_http2_cleartext(http2_cleartext)
+            .with_listener(admin_port, create_app("admin", barrier.clone()));
+        (server, create_app("public", barrier), public_port, admin_port)
+    }
+
+    async fn get_text(port: u16, path: &str) -> reqwest::Response {
+        reqwest::get(format!("http://127.0.0.1:{}{}", port, path)).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_each_port_serves_its_own_router() {
+        let (server, app, public_port, admin_port) = create_server(false, Arc::new(Barrier::new(1)));
+        let server_task = tokio::spawn(async move {
+            let _ = server.start(app.route("/public-only", get(|| async { "only public" }))).await;
+        });
+        wait_for_port(public_port).await;
+        wait_for_port(admin_port).await;
+
+        assert_eq!(get_text(public_port, "/").await.text().await.unwrap(), "public");
+        assert_eq!(get_text(admin_port, "/").await.text().await.unwrap(), "admin");
+        assert_eq!(get_text(public_port, "/public-only").await.status(), 200);
+        assert_eq!(get_text(admin_port, "/public-only").await.status(), 404);
+
+        // Listeners get the server-wide middleware too
+        assert!(get_text(admin_port, "/").await.headers().contains_key("x-request-id"));
+
+        server_task.abort();
+    }
+
+    /// Start a slow request on each port, signal shutdown while both are in flight and expect
+    /// both to complete before `start` returns, with neither port accepting afterwards
+    async fn assert_shutdown_drains_both_ports(http2_cleartext: bool) {
+        // Two handlers plus the test itself meet at the barrier once both requests are in flight
+        let barrier = Arc::new(Barrier::new(3));
+        let (server, app, public_port, admin_port) = create_server(http2_cleartext, barrier.clone());
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server = server.with_graceful_shutdown(async move {
+            let _ = shutdown_rx.await;
+        });
+        let server_task = tokio::spawn(async move { server.start(app).await.map_err(|e| e.to_string()) });
+        wait_for_port(public_port).await;
+        wait_for_port(admin_port).await;
+
+        let public = tokio::spawn(async move { get_text(public_port, "/slow").await.text().await.unwrap() });
+        let admin = tokio::spawn(async move { get_text(admin_port, "/slow").await.text().await.unwrap() });
+        barrier.wait().await;
+        shutdown_tx.send(()).unwrap();
+
+        assert_eq!(public.await.unwrap(), "public");
+        assert_eq!(admin.await.unwrap(), "admin");
+        let result = tokio::time::timeout(Duration::from_secs(5), server_task).await
+            .expect("start() should return once both ports have drained")
+            .unwrap();
+        assert_eq!(result, Ok(()));
+
+        for port in [public_port, admin_port] {
+            assert!(tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_both_ports() {
+        assert_shutdown_drains_both_ports(false).await;
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_both_ports_with_h2c() {
+        assert_shutdown_drains_both_ports(true).await;
+    }
+}
@@ -0,0 +1,164 @@
+// Request ID tests: generation, inbound preservation and propagation to proxied backends
+
+use httpserver_core::{ request_id_middleware, RequestId, REQUEST_ID_HEADER };
+use httpserver_proxy::ProxyHandler;
+use httpserver_config::{ ProxyRoute, LoadBalancingStrategy };
+use axum::{
+    Extension,
+    Router,
+    body::Body,
+    extract::Request,
+    http::{ HeaderMap, StatusCode },
+    response::IntoResponse,
+    routing::get,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tower::ServiceExt;
+use crate::test_support::serve;
+
+#[cfg(test)]
+mod request_id_tests {
+    use super::*;
+
+    /// App whose handler reports the request ID it observed
+    fn create_app() -> Router {
+        Router::new()
+            .route(
+                "/",
+                get(|Extension(RequestId(id)): Extension<RequestId>, headers: HeaderMap| async move {
+                    assert_eq!(headers[REQUEST_ID_HEADER], id.as_str());
+                    id
+                })
+            )
+            .layer(axum::middleware::from_fn(request_id_middleware))
+    }
+
+    async fn send(app: Router, request: Request<Body>) -> (Option<String>, String) {
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let header = response
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .map(|value| value.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (header, String::from_utf8_lossy(&body).to_string())
+    }
+
+    #[tokio::test]
+    async fn test_response_carries_generated_request_id() {
+        let request = Request::builder().uri("/").body(Body::empty()).unwrap();
+        let (header, seen_by_handler) = send(create_app(), request).await;
+
+        let header = header.expect("Response should carry X-Request-Id");
+        assert!(uuid::Uuid::parse_str(&header).is_ok(), "Expected a UUID, got {}", header);
+        assert_eq!(header, seen_by_handler);
+
+        // Each request gets its own ID
+        let request = Request::builder().uri("/").body(Body::empty()).unwrap();
+        let (second, _) = send(create_app(), request).await;
+        assert_ne!(Some(header), second);
+    }
+
+    #[tokio::test]
+    async fn test_inbound_request_id_is_preserved() {
+        let request = Request::builder()
+            .uri("/")
+            .header(REQUEST_ID_HEADER, "upstream-trace-42")
+            .body(Body::empty())
+            .unwrap();
+        let (header, seen_by_handler) = send(create_app(), request).await;
+
+        assert_eq!(header.as_deref(), Some("upstream-trace-42"));
+        assert_eq!(seen_by_handler, "upstream-trace-42");
+
+        // Malformed inbound IDs are replaced rather than echoed
+        let request = Request::builder()
+            .uri("/")
+            .header(REQUEST_ID_HEADER, "has spaces in it")
+            .body(Body::empty())
+            .unwrap();
+        let (header, _) = send(create_app(), request).await;
+        assert!(uuid::Uuid::parse_str(&header.unwrap()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_proxied_request_includes_request_id() {
+        // Backend echoes the request ID it received
+        let backend = Router::new().route(
+            "/echo",
+            get(|headers: HeaderMap| async move {
+                headers
+                    .get(REQUEST_ID_HEADER)
+                    .map(|value| value.to_str().unwrap().to_string())
+                    .unwrap_or_default()
+            })
+        );
+        let port = serve(backend).await;
+
+        let route = ProxyRoute {
+            path: "/api/*".to_string(),
+            target: Some(format!("http://127.0.0.1:{}", port)),
+            targets: vec![],
+            strategy: LoadBalancingStrategy::RoundRobin,
+            timeout: 5,
+            sticky_sessions: false,
+            http_health: None,
+            websocket_health: None,
+            tcp_health: None,
+            circuit_breaker: None,
+            middleware: None,
+            ssl: None,
+            client: None,
+            cache: None,
+            http2: false,
+            forwarded_headers: true,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            websocket_limits: None,
+            log_bodies: None,
+            canary: None,
+            mirror: None,
+            host: None,
+            match_regex: false,
+            body_integrity: None,
+            connect_timeout: None,
+            read_timeout: None,
+            header_allowlist: None,
+            header_denylist: Vec::new(),
+            access_log: true,
+            access_log_format: None,
+            startup_check: None,
+            fallback_file: None,
+            idempotency: None,
+            jwt_auth: None,
+            forward_auth: None,
+        };
+        let handler = Arc::new(ProxyHandler::new(vec![route]));
+        let app = Router::new()
+            .fallback(move |request: Request| {
+                let handler = handler.clone();
+                async move {
+                    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+                    match handler.handle_request(request, client_ip).await {
+                        Some(Ok(response)) => response,
+                        Some(Err(e)) => e.into_response(),
+                        None => StatusCode::NOT_FOUND.into_response(),
+                    }
+                }
+            })
+            .layer(axum::middleware::from_fn(request_id_middleware));
+
+        let request = Request::builder().uri("/api/echo").body(Body::empty()).unwrap();
+        let (header, seen_by_backend) = send(app.clone(), request).await;
+        assert_eq!(header.expect("Response should carry X-Request-Id"), seen_by_backend);
+
+        let request = Request::builder()
+            .uri("/api/echo")
+            .header(REQUEST_ID_HEADER, "client-id-7")
+            .body(Body::empty())
+            .unwrap();
+        let (_, seen_by_backend) = send(app, request).await;
+        assert_eq!(seen_by_backend, "client-id-7");
+    }
+}
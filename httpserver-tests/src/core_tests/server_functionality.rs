@@ -5,6 +5,8 @@ use httpserver_core::{
     create_health_router,
     create_error_response,
     logging_middleware,
+    bind_listener,
+    ServerError,
 };
 use axum::http::StatusCode;
 use tokio::time::Duration;
@@ -141,5 +143,22 @@ async fn test_logging_middleware_compilation() {
         .layer(axum::middleware::from_fn(logging_middleware));
 
     // Just testing that the middleware can be applied without compilation errors
-    assert!(true);
+}
+
+#[tokio::test]
+async fn test_port_in_use_returns_address_in_use() {
+    let first = bind_listener(0).await.unwrap();
+    let port = first.local_addr().unwrap().port();
+
+    match bind_listener(port).await {
+        Err(ServerError::AddressInUse { port: reported }) => assert_eq!(reported, port),
+        other => panic!("Expected AddressInUse, got {:?}", other.map(|_| ())),
+    }
+
+    // start() fails fast with the same error instead of serving nothing
+    let app = Router::new().route("/", get(|| async { "unreachable" }));
+    let result = tokio::time::timeout(Duration::from_secs(5), Server::new(port).start(app)).await;
+    let error = result.expect("start() should return instead of hanging").unwrap_err();
+    assert!(matches!(error.downcast_ref::<ServerError>(), Some(ServerError::AddressInUse { .. })));
+    assert!(error.to_string().contains(&port.to_string()));
 }
@@ -270,4 +270,28 @@ mod ssl_tests {
         assert!(!custom_config.is_exempt("/api/users")); // Should not match
         assert!(custom_config.is_exempt("/healthz")); // This SHOULD match as it starts with /health
     }
+
+    #[test]
+    fn test_self_signed_certificate_for_localhost() {
+        let mut manager = SslCertificateManager::new();
+        assert!(!manager.has_certificates());
+
+        manager.generate_self_signed_certificate().expect("Failed to generate certificate");
+        assert!(manager.has_certificates());
+        assert!(manager.create_server_config("localhost").is_ok());
+
+        // The resolver behind the ServerConfig presents the generated certificate
+        let certified_key = manager
+            .resolver()
+            .resolve_domain(Some("localhost"))
+            .expect("No certificate resolved for localhost");
+        let (_, cert) = x509_parser::parse_x509_certificate(&certified_key.cert[0].0).unwrap();
+        let common_name = cert.subject().iter_common_name().next().unwrap().as_str().unwrap();
+        assert_eq!(common_name, "localhost");
+
+        let san = cert.subject_alternative_name().unwrap().unwrap();
+        let names = &san.value.general_names;
+        assert!(names.contains(&x509_parser::extensions::GeneralName::DNSName("localhost")));
+        assert!(names.contains(&x509_parser::extensions::GeneralName::IPAddress(&[127, 0, 0, 1])));
+    }
 }
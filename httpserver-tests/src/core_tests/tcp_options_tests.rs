@@ -0,0 +1,81 @@
+// TCP option tests: accepted connections get TCP_NODELAY and keep-alive as configured
+
+use httpserver_core::{ Server, TcpOptions };
+use httpserver_config::ServerConfig;
+use axum::{ Router, routing::get };
+use socket2::SockRef;
+use tokio::net::{ TcpListener, TcpStream };
+use tokio::time::Duration;
+
+#[cfg(test)]
+mod tcp_options_tests {
+    use super::*;
+
+    /// Server side of a fresh loopback connection
+    async fn accepted_stream() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let (accepted, _) = listener.accept().await.unwrap();
+        (accepted, client)
+    }
+
+    #[tokio::test]
+    async fn test_options_are_set_on_accepted_socket() {
+        let (accepted, _client) = accepted_stream().await;
+        TcpOptions::new(true, 45).apply(&accepted).unwrap();
+
+        let socket = SockRef::from(&accepted);
+        assert!(socket.nodelay().unwrap());
+        assert!(socket.keepalive().unwrap());
+        assert_eq!(socket.keepalive_time().unwrap(), Duration::from_secs(45));
+    }
+
+    #[tokio::test]
+    async fn test_default_options_leave_socket_untouched() {
+        let (accepted, _client) = accepted_stream().await;
+        TcpOptions::default().apply(&accepted).unwrap();
+
+        let socket = SockRef::from(&accepted);
+        assert!(!socket.nodelay().unwrap());
+        assert!(!socket.keepalive().unwrap());
+    }
+
+    #[test]
+    fn test_zero_keepalive_disables_it() {
+        assert_eq!(TcpOptions::new(false, 0), TcpOptions::default());
+        assert_eq!(TcpOptions::new(true, 0).keepalive, None);
+
+        let config: ServerConfig = serde_json::from_value(
+            serde_json::json!({ "tcp_nodelay": true, "tcp_keepalive_secs": 60 })
+        ).unwrap();
+        let options = TcpOptions::new(config.tcp_nodelay, config.tcp_keepalive_secs);
+        assert!(options.nodelay);
+        assert_eq!(options.keepalive, Some(Duration::from_secs(60)));
+
+        let defaults = ServerConfig::default();
+        assert!(!defaults.tcp_nodelay);
+        assert_eq!(defaults.tcp_keepalive_secs, 0);
+    }
+
+    #[tokio::test]
+    async fn test_server_serves_with_tcp_options() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let server = Server::new(port).with_tcp_options(TcpOptions::new(true, 30));
+        let app = Router::new().route("/", get(|| async { "ok" }));
+        let server_task = tokio::spawn(async move {
+            let _ = server.start(app).await;
+        });
+
+        let mut response = None;
+        for _ in 0..50 {
+            if let Ok(ok) = reqwest::get(format!("http://127.0.0.1:{}/", port)).await {
+                response = Some(ok);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(response.expect("Server did not start").text().await.unwrap(), "ok");
+
+        server_task.abort();
+    }
+}
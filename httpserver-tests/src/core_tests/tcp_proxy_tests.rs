@@ -0,0 +1,107 @@
+// TCP proxy tests: raw connections are relayed to load-balanced backends
+
+use httpserver_core::{ Server, TcpProxy };
+use httpserver_config::{ LoadBalancingStrategy, TcpRoute, Target };
+use axum::Router;
+use tokio::io::{ AsyncReadExt, AsyncWriteExt };
+use tokio::net::{ TcpListener, TcpStream };
+use tokio::time::Duration;
+
+#[cfg(test)]
+mod tcp_proxy_tests {
+    use super::*;
+    use crate::test_support::free_port;
+
+    /// Backend echoing every byte back, prefixed once with `greeting`
+    async fn start_echo_backend(greeting: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let _ = stream.write_all(greeting.as_bytes()).await;
+                    let mut buffer = [0u8; 1024];
+                    while let Ok(n) = stream.read(&mut buffer).await {
+                        if n == 0 || stream.write_all(&buffer[..n]).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        address
+    }
+
+    fn create_route(listen_port: u16, targets: &[&str]) -> TcpRoute {
+        TcpRoute {
+            listen_port,
+            targets: targets.iter().map(|target| Target::new(target.to_string())).collect(),
+            strategy: LoadBalancingStrategy::RoundRobin,
+        }
+    }
+
+    /// Read exactly `len` bytes as a string
+    async fn read_string(stream: &mut TcpStream, len: usize) -> String {
+        let mut buffer = vec![0u8; len];
+        tokio::time::timeout(Duration::from_secs(5), stream.read_exact(&mut buffer)).await
+            .expect("Timed out reading from proxy")
+            .unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_echo_backend_reachable_through_server() {
+        let backend = start_echo_backend("hi:").await;
+        let tcp_port = free_port();
+        let server = Server::new(free_port()).with_tcp_routes(vec![create_route(tcp_port, &[&backend])]);
+        let handle = tokio::spawn(async move {
+            let _ = server.start(Router::new()).await;
+        });
+
+        let mut stream = None;
+        for _ in 0..50 {
+            if let Ok(connected) = TcpStream::connect(("127.0.0.1", tcp_port)).await {
+                stream = Some(connected);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let mut stream = stream.expect("TCP proxy did not start listening");
+
+        assert_eq!(read_string(&mut stream, 3).await, "hi:");
+        stream.write_all(b"ping").await.unwrap();
+        assert_eq!(read_string(&mut stream, 4).await, "ping");
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_connections_balanced_across_backends() {
+        let first = start_echo_backend("one").await;
+        let second = start_echo_backend("two").await;
+        let proxy = TcpProxy::bind(&create_route(0, &[&first, &second])).await.unwrap();
+        let port = proxy.local_addr().port();
+
+        // Round robin alternates, with open connections counted against their backend
+        let mut open = Vec::new();
+        let mut greetings = Vec::new();
+        for _ in 0..4 {
+            let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+            greetings.push(read_string(&mut stream, 3).await);
+            open.push(stream);
+        }
+        assert_eq!(greetings, vec!["one", "two", "one", "two"]);
+        assert_eq!(proxy.balancer().get_connection_count(&first), 2);
+        assert_eq!(proxy.balancer().get_connection_count(&second), 2);
+
+        // Closing the clients releases their connections
+        drop(open);
+        for _ in 0..50 {
+            if proxy.balancer().get_connection_count(&first) == 0 && proxy.balancer().get_connection_count(&second) == 0 {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("Closed connections were still counted");
+    }
+}
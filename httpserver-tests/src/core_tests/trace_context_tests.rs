@@ -0,0 +1,154 @@
+// Trace context tests: W3C traceparent/tracestate are continued or started and forwarded to backends
+
+use httpserver_core::{ logging_middleware, TraceContext, TRACEPARENT_HEADER, TRACESTATE_HEADER };
+use httpserver_core::trace_context::parse_traceparent;
+use httpserver_proxy::ProxyHandler;
+use httpserver_config::ProxyRoute;
+use axum::{
+    Router,
+    body::Body,
+    extract::Request,
+    http::{ HeaderMap, HeaderValue, StatusCode },
+    response::IntoResponse,
+    routing::get,
+};
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tower::ServiceExt;
+use crate::test_support::serve_url;
+
+#[cfg(test)]
+mod trace_context_tests {
+    use super::*;
+
+    const INBOUND_TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    /// Backend echoing the traceparent and tracestate it received, one per line
+    async fn start_backend() -> String {
+        let backend = Router::new().route(
+            "/echo",
+            get(|headers: HeaderMap| async move {
+                let header = |name: &str| {
+                    headers
+                        .get(name)
+                        .map(|value| value.to_str().unwrap().to_string())
+                        .unwrap_or_default()
+                };
+                format!("{}\n{}", header(TRACEPARENT_HEADER), header(TRACESTATE_HEADER))
+            })
+        );
+        serve_url(backend).await
+    }
+
+    /// Proxy for /api/* behind the logging middleware, or without it to exercise the proxy alone
+    async fn create_app(with_logging: bool) -> Router {
+        let route: ProxyRoute = serde_json
+            ::from_value(json!({ "path": "/api/*", "target": start_backend().await }))
+            .unwrap();
+        let handler = Arc::new(ProxyHandler::new(vec![route]));
+        let app = Router::new().fallback(move |request: Request| {
+            let handler = handler.clone();
+            async move {
+                let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+                match handler.handle_request(request, client_ip).await {
+                    Some(Ok(response)) => response,
+                    Some(Err(e)) => e.into_response(),
+                    None => StatusCode::NOT_FOUND.into_response(),
+                }
+            }
+        });
+        if with_logging {
+            app.layer(axum::middleware::from_fn(logging_middleware))
+        } else {
+            app
+        }
+    }
+
+    /// Send /api/echo with the given trace headers, returning what the backend received
+    async fn send(app: Router, traceparent: Option<&str>, tracestate: Option<&str>) -> (String, String) {
+        let mut request = Request::builder().uri("/api/echo");
+        if let Some(traceparent) = traceparent {
+            request = request.header(TRACEPARENT_HEADER, traceparent);
+        }
+        if let Some(tracestate) = tracestate {
+            request = request.header(TRACESTATE_HEADER, tracestate);
+        }
+        let response = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8_lossy(&body).to_string();
+        let (traceparent, tracestate) = body.split_once('\n').unwrap();
+        (traceparent.to_string(), tracestate.to_string())
+    }
+
+    #[test]
+    fn test_trace_context_continues_valid_traceparent() {
+        let mut headers = HeaderMap::new();
+        headers.insert(TRACEPARENT_HEADER, HeaderValue::from_static(INBOUND_TRACEPARENT));
+        let trace = TraceContext::from_headers(&headers);
+
+        assert_eq!(trace.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(trace.parent_span_id.as_deref(), Some("00f067aa0ba902b7"));
+        assert_ne!(trace.span_id, "00f067aa0ba902b7");
+        assert_eq!(
+            trace.traceparent(),
+            format!("00-4bf92f3577b34da6a3ce929d0e0e4736-{}-01", trace.span_id)
+        );
+    }
+
+    #[test]
+    fn test_invalid_traceparent_is_rejected() {
+        for invalid in [
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "not a traceparent",
+        ] {
+            assert_eq!(parse_traceparent(invalid), None, "{} should be rejected", invalid);
+        }
+
+        // Later versions may append fields
+        assert!(parse_traceparent("01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra").is_some());
+
+        // A rejected traceparent starts a new trace
+        let mut headers = HeaderMap::new();
+        headers.insert(TRACEPARENT_HEADER, HeaderValue::from_static("not a traceparent"));
+        let trace = TraceContext::from_headers(&headers);
+        assert_eq!(trace.parent_span_id, None);
+        assert!(parse_traceparent(&trace.traceparent()).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_inbound_traceparent_forwarded_with_new_span_id() {
+        let app = create_app(true).await;
+        let (traceparent, tracestate) = send(app, Some(INBOUND_TRACEPARENT), Some("vendor=abc")).await;
+
+        let (trace_id, parent_span_id, flags) = parse_traceparent(&traceparent).expect("Backend should get a valid traceparent");
+        assert_eq!(trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_ne!(parent_span_id, "00f067aa0ba902b7", "Backend should see the gateway's span as its parent");
+        assert_eq!(flags, 0x01);
+        assert_eq!(tracestate, "vendor=abc");
+    }
+
+    #[tokio::test]
+    async fn test_traceparent_generated_when_absent() {
+        let (traceparent, tracestate) = send(create_app(true).await, None, None).await;
+        assert!(parse_traceparent(&traceparent).is_some(), "Expected a generated traceparent, got {:?}", traceparent);
+        assert_eq!(tracestate, "");
+
+        // The proxy starts a trace on its own when no middleware has run
+        let (first, _) = send(create_app(false).await, None, None).await;
+        let (second, _) = send(create_app(false).await, None, None).await;
+        assert!(parse_traceparent(&first).is_some());
+        assert_ne!(first[3..35], second[3..35], "Each request should start its own trace");
+
+        // Trace state without a valid traceparent is dropped
+        let (traceparent, tracestate) = send(create_app(false).await, Some("garbage"), Some("vendor=abc")).await;
+        assert!(parse_traceparent(&traceparent).is_some());
+        assert_eq!(tracestate, "");
+    }
+}
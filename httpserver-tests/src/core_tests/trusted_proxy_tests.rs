@@ -0,0 +1,105 @@
+// Trusted proxy tests: X-Forwarded-For only names the client when the peer is a trusted proxy
+
+use httpserver_core::{ ClientIp, TrustedProxies, client_ip_middleware };
+use axum::{
+    Extension,
+    Router,
+    body::Body,
+    extract::{ ConnectInfo, Request },
+    http::HeaderMap,
+    routing::get,
+};
+use std::net::{ IpAddr, SocketAddr };
+use std::sync::Arc;
+use tower::ServiceExt;
+
+#[cfg(test)]
+mod trusted_proxy_tests {
+    use super::*;
+
+    fn trusted() -> TrustedProxies {
+        TrustedProxies::parse(&["10.0.0.0/8", "192.0.2.1"]).unwrap()
+    }
+
+    fn xff(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", value.parse().unwrap());
+        headers
+    }
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_spoofed_xff_from_untrusted_peer_is_ignored() {
+        let resolved = trusted().client_ip(ip("203.0.113.9"), &xff("1.2.3.4"));
+        assert_eq!(resolved, ip("203.0.113.9"));
+
+        // No trusted proxies configured: the peer is always the client
+        let resolved = TrustedProxies::default().client_ip(ip("10.0.0.5"), &xff("1.2.3.4"));
+        assert_eq!(resolved, ip("10.0.0.5"));
+    }
+
+    #[test]
+    fn test_xff_from_trusted_peer_is_honored() {
+        let resolved = trusted().client_ip(ip("10.0.0.5"), &xff("198.51.100.7"));
+        assert_eq!(resolved, ip("198.51.100.7"));
+
+        // The rightmost untrusted entry wins; a client-forged prefix is ignored
+        let resolved = trusted().client_ip(
+            ip("10.0.0.5"),
+            &xff("1.2.3.4, 198.51.100.7, 192.0.2.1, 10.1.1.1")
+        );
+        assert_eq!(resolved, ip("198.51.100.7"));
+
+        // A trusted peer without XFF is the client itself
+        assert_eq!(trusted().client_ip(ip("10.0.0.5"), &HeaderMap::new()), ip("10.0.0.5"));
+
+        // IPv4-mapped peers from dual-stack listeners match IPv4 ranges
+        let resolved = trusted().client_ip(ip("::ffff:10.0.0.5"), &xff("198.51.100.7"));
+        assert_eq!(resolved, ip("198.51.100.7"));
+    }
+
+    #[test]
+    fn test_invalid_trusted_proxy_is_rejected() {
+        assert!(TrustedProxies::parse(&["10.0.0.0/33"]).is_err());
+        assert!(TrustedProxies::parse(&["not-an-ip"]).is_err());
+    }
+
+    async fn resolve_via_middleware(peer: &str, forwarded_for: Option<&str>) -> String {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|Extension(ClientIp(client_ip)): Extension<ClientIp>| async move {
+                    client_ip.to_string()
+                })
+            )
+            .layer(axum::middleware::from_fn_with_state(Arc::new(trusted()), client_ip_middleware));
+
+        let mut request = Request::builder().uri("/");
+        if let Some(value) = forwarded_for {
+            request = request.header("x-forwarded-for", value);
+        }
+        let mut request = request.body(Body::empty()).unwrap();
+        let peer: SocketAddr = peer.parse().unwrap();
+        request.extensions_mut().insert(ConnectInfo(peer));
+
+        let response = app.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8_lossy(&body).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_middleware_attaches_resolved_client_ip() {
+        assert_eq!(
+            resolve_via_middleware("203.0.113.9:40000", Some("1.2.3.4")).await,
+            "203.0.113.9"
+        );
+        assert_eq!(
+            resolve_via_middleware("10.0.0.5:40000", Some("1.2.3.4, 198.51.100.7")).await,
+            "198.51.100.7"
+        );
+        assert_eq!(resolve_via_middleware("10.0.0.5:40000", None).await, "10.0.0.5");
+    }
+}
@@ -0,0 +1,118 @@
+// Unix domain socket tests: serving the router without TCP and cleaning up the socket file
+
+use httpserver_core::{ Server, SslRedirectConfig };
+use axum::{ Router, body::Body, http::{ Request, StatusCode }, routing::get };
+use http_body_util::BodyExt;
+use hyper_util::rt::TokioIo;
+use std::os::unix::fs::{ FileTypeExt, PermissionsExt };
+use std::path::Path;
+use tempfile::TempDir;
+use tokio::net::UnixStream;
+use tokio::time::Duration;
+
+#[cfg(test)]
+mod unix_socket_tests {
+    use super::*;
+
+    fn create_app() -> Router {
+        Router::new().route("/hello", get(|| async { "hello over unix" }))
+    }
+
+    /// Start the server in the background and wait for its socket to accept connections
+    async fn start_server(server: Server, socket_path: &Path, mode: u32) -> tokio::task::JoinHandle<()> {
+        let server = server.with_unix_socket(socket_path, mode);
+        let handle = tokio::spawn(async move {
+            let _ = server.start(create_app()).await;
+        });
+
+        for _ in 0..50 {
+            if UnixStream::connect(socket_path).await.is_ok() {
+                return handle;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("Server did not start listening on {}", socket_path.display());
+    }
+
+    /// Issue a GET over the Unix socket, returning status and body
+    async fn get_over_socket(socket_path: &Path, path: &str) -> (StatusCode, String) {
+        let stream = UnixStream::connect(socket_path).await.unwrap();
+        let (mut sender, connection) = hyper::client::conn::http1
+            ::handshake(TokioIo::new(stream)).await
+            .unwrap();
+        tokio::spawn(connection);
+
+        let request = Request::builder()
+            .uri(path)
+            .header("host", "localhost")
+            .body(Body::empty())
+            .unwrap();
+        let response = sender.send_request(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8_lossy(&body).to_string())
+    }
+
+    #[tokio::test]
+    async fn test_request_over_unix_socket() {
+        let temp_dir = TempDir::new().unwrap();
+        let socket_path = temp_dir.path().join("server.sock");
+        let handle = start_server(Server::new(0), &socket_path, 0o600).await;
+
+        // Logging middleware runs without a peer address
+        let (status, body) = get_over_socket(&socket_path, "/hello").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "hello over unix");
+
+        let mode = std::fs::metadata(&socket_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // Stopping the server removes the socket file
+        handle.abort();
+        let _ = handle.await;
+        assert!(!socket_path.exists(), "Socket file should be removed on shutdown");
+    }
+
+    #[tokio::test]
+    async fn test_stale_socket_file_is_replaced() {
+        let temp_dir = TempDir::new().unwrap();
+        let socket_path = temp_dir.path().join("stale.sock");
+
+        // A socket left behind by a crashed run
+        drop(std::os::unix::net::UnixListener::bind(&socket_path).unwrap());
+        assert!(std::fs::metadata(&socket_path).unwrap().file_type().is_socket());
+
+        let handle = start_server(Server::new(0), &socket_path, 0o660).await;
+        let (status, _) = get_over_socket(&socket_path, "/hello").await;
+        assert_eq!(status, StatusCode::OK);
+
+        handle.abort();
+        let _ = handle.await;
+    }
+
+    #[tokio::test]
+    async fn test_existing_regular_file_is_not_replaced() {
+        let temp_dir = TempDir::new().unwrap();
+        let socket_path = temp_dir.path().join("not-a-socket");
+        std::fs::write(&socket_path, "keep me").unwrap();
+
+        let result = Server::new(0).with_unix_socket(&socket_path, 0o660).start(create_app()).await;
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(&socket_path).unwrap(), "keep me");
+    }
+
+    #[tokio::test]
+    async fn test_unix_socket_is_not_redirected_to_https() {
+        let temp_dir = TempDir::new().unwrap();
+        let socket_path = temp_dir.path().join("redirect.sock");
+        let server = Server::new(0).with_https_redirect(SslRedirectConfig::new(true, 8443));
+        let handle = start_server(server, &socket_path, 0o600).await;
+
+        let (status, body) = get_over_socket(&socket_path, "/hello").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "hello over unix");
+
+        handle.abort();
+        let _ = handle.await;
+    }
+}
@@ -0,0 +1,71 @@
+// Engine builder tests: the builder assembles and validates a Config without TOML or CLI args
+use httpserver_config::{ Config, ConfigError, LoadBalancingStrategy };
+use httpserver_engine::{ HttpServerEngine, HttpServerEngineBuilder };
+use std::path::PathBuf;
+use tempfile::TempDir;
+use crate::test_support::route;
+
+#[test]
+fn test_builder_sets_port_static_dir_and_routes() {
+    let static_dir = TempDir::new().unwrap();
+
+    let engine = HttpServerEngine::builder()
+        .port(9090)
+        .static_dir(static_dir.path())
+        .spa_fallback(false)
+        .add_proxy_route(route("/api/*", "http://localhost:3000"))
+        .add_proxy_route(route("/ws/*", "http://localhost:3001"))
+        .build()
+        .unwrap();
+
+    assert_eq!(engine.port(), 9090);
+    let config = engine.config();
+    assert_eq!(config.static_config.directory, static_dir.path());
+    assert!(!config.static_config.spa_fallback);
+    assert_eq!(config.proxy.len(), 2);
+    assert_eq!(config.proxy[0].path, "/api/*");
+    assert_eq!(config.proxy[0].get_primary_target().unwrap(), "http://localhost:3000");
+    assert_eq!(config.proxy[0].strategy, LoadBalancingStrategy::RoundRobin);
+    assert_eq!(config.proxy[1].path, "/ws/*");
+}
+
+#[test]
+fn test_builder_defaults_match_cli_defaults() {
+    let engine = HttpServerEngineBuilder::new().build().unwrap();
+
+    assert_eq!(engine.port(), 8080);
+    assert_eq!(engine.config().static_config.directory, PathBuf::from("."));
+    assert!(engine.config().proxy.is_empty());
+    assert!(engine.config().server.ssl.is_none());
+}
+
+#[test]
+fn test_builder_overrides_existing_config() {
+    let static_dir = TempDir::new().unwrap();
+    let mut base = Config::default();
+    base.proxy.push(route("/api/*", "http://localhost:3000"));
+
+    let engine = HttpServerEngine::builder()
+        .config(base)
+        .static_dir(static_dir.path())
+        .self_signed()
+        .build()
+        .unwrap();
+
+    let config = engine.config();
+    assert_eq!(config.static_config.directory, static_dir.path());
+    assert_eq!(config.proxy.len(), 1);
+    let ssl = config.server.ssl.as_ref().unwrap();
+    assert!(ssl.enabled && ssl.self_signed);
+}
+
+#[test]
+fn test_builder_validates_configuration() {
+    let result = HttpServerEngine::builder().static_dir("/nonexistent/directory").build();
+    assert!(matches!(result.err(), Some(ConfigError::MissingStaticDirectory(_))));
+
+    let result = HttpServerEngine::builder().add_proxy_route(route("/api/*", "localhost:3000")).build();
+    assert!(
+        matches!(result.err(), Some(ConfigError::InvalidProxyRoute { index: 0, target: Some(0), .. }))
+    );
+}
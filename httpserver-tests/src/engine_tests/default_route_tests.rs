@@ -0,0 +1,83 @@
+// Default route tests: requests missing every proxy route and static file go to the catch-all upstream
+use httpserver_config::{ Config, DefaultRoute };
+use httpserver_engine::HttpServerEngine;
+use axum::{ Router, body::Body, http::{ Request, StatusCode, Uri } };
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::test_support::{ route, serve_url };
+
+/// Backend answering every request with `name` and the path and body it received
+async fn start_backend(name: &'static str) -> String {
+    let app = Router::new().fallback(move |uri: Uri, body: String| async move {
+        format!("{} {} {}", name, uri.path(), body)
+    });
+    serve_url(app).await
+}
+
+/// Router for a static directory holding page.txt, an /api/* proxy route and the given default route
+async fn create_router(static_dir: &TempDir, default_route: Option<DefaultRoute>) -> Router {
+    std::fs::write(static_dir.path().join("page.txt"), "static page").unwrap();
+    let mut config = Config::default();
+    config.static_config.directory = static_dir.path().to_path_buf();
+    config.static_config.spa_fallback = false;
+    config.proxy = vec![route("/api/*", &start_backend("api").await)];
+    config.default_route = default_route;
+    HttpServerEngine::new(config, 0).unwrap().router().await.unwrap()
+}
+
+async fn send(router: &Router, method: &str, path: &str, body: &str) -> (StatusCode, String) {
+    let request = Request::builder().method(method).uri(path).body(Body::from(body.to_string())).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8_lossy(&body).to_string())
+}
+
+#[tokio::test]
+async fn test_unmatched_request_goes_to_default_route() {
+    let static_dir = TempDir::new().unwrap();
+    let default_route = DefaultRoute { target: start_backend("default").await, timeout: 30 };
+    let router = create_router(&static_dir, Some(default_route)).await;
+
+    // Proxy routes and static files keep precedence
+    assert_eq!(send(&router, "GET", "/api/users", "").await, (StatusCode::OK, "api /users ".to_string()));
+    assert_eq!(send(&router, "GET", "/page.txt", "").await, (StatusCode::OK, "static page".to_string()));
+
+    // Everything else is forwarded with its full path and body
+    assert_eq!(
+        send(&router, "GET", "/legacy/report", "").await,
+        (StatusCode::OK, "default /legacy/report ".to_string())
+    );
+    assert_eq!(
+        send(&router, "POST", "/legacy/submit", "form=1").await,
+        (StatusCode::OK, "default /legacy/submit form=1".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_unmatched_request_is_404_without_default_route() {
+    let static_dir = TempDir::new().unwrap();
+    let router = create_router(&static_dir, None).await;
+
+    assert_eq!(send(&router, "GET", "/legacy/report", "").await.0, StatusCode::NOT_FOUND);
+    assert_eq!(send(&router, "GET", "/page.txt", "").await.0, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_default_route_body_is_bounded_by_max_request_size() {
+    let static_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.static_config.directory = static_dir.path().to_path_buf();
+    config.static_config.spa_fallback = false;
+    config.server.max_request_size_mb = 1;
+    config.default_route = Some(DefaultRoute { target: start_backend("default").await, timeout: 30 });
+    let router = HttpServerEngine::new(config, 0).unwrap().router().await.unwrap();
+
+    // Bodies are only buffered for replay up to the server-wide limit
+    let oversized = "x".repeat(1024 * 1024 + 1);
+    assert_eq!(send(&router, "POST", "/legacy/upload", &oversized).await.0, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(
+        send(&router, "POST", "/legacy/upload", "small").await,
+        (StatusCode::OK, "default /legacy/upload small".to_string())
+    );
+}
@@ -0,0 +1,72 @@
+// Health endpoint tests: built-in endpoints can be disabled or moved under a prefix
+use httpserver_config::{ Config, DefaultRoute };
+use httpserver_engine::HttpServerEngine;
+use axum::{ Router, body::Body, http::{ Request, StatusCode } };
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::test_support::start_path_backend;
+
+/// Router with a static "ping" file and the given health settings
+async fn create_router(
+    static_dir: &TempDir,
+    enabled: bool,
+    prefix: &str,
+    default_route: Option<DefaultRoute>
+) -> Router {
+    std::fs::write(static_dir.path().join("ping"), "static ping").unwrap();
+    let mut config = Config::default();
+    config.static_config.directory = static_dir.path().to_path_buf();
+    config.static_config.spa_fallback = false;
+    config.server.enable_health_endpoints = enabled;
+    config.server.health_path_prefix = prefix.to_string();
+    config.default_route = default_route;
+    HttpServerEngine::new(config, 0).unwrap().router().await.unwrap()
+}
+
+async fn get_path(router: &Router, path: &str) -> (StatusCode, String) {
+    let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8_lossy(&body).to_string())
+}
+
+#[tokio::test]
+async fn test_health_endpoints_enabled_by_default() {
+    let static_dir = TempDir::new().unwrap();
+    let router = create_router(&static_dir, true, "", None).await;
+
+    let (status, body) = get_path(&router, "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("\"status\""), "Expected gateway health JSON, got {}", body);
+    assert_ne!(get_path(&router, "/ping").await.1, "static ping");
+    assert_eq!(get_path(&router, "/proxy/stats").await.0, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_disabled_health_paths_served_normally() {
+    let default_route = DefaultRoute { target: start_path_backend().await, timeout: 30 };
+    let static_dir = TempDir::new().unwrap();
+    let router = create_router(&static_dir, false, "", Some(default_route)).await;
+
+    // Static files and the default route get the paths the endpoints used to intercept
+    assert_eq!(get_path(&router, "/ping").await, (StatusCode::OK, "static ping".to_string()));
+    assert_eq!(get_path(&router, "/health").await, (StatusCode::OK, "backend /health".to_string()));
+    assert_eq!(get_path(&router, "/readyz").await, (StatusCode::OK, "backend /readyz".to_string()));
+}
+
+#[tokio::test]
+async fn test_health_endpoints_move_under_prefix() {
+    let static_dir = TempDir::new().unwrap();
+    let router = create_router(&static_dir, true, "/_internal", None).await;
+
+    let (status, body) = get_path(&router, "/_internal/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("\"status\""), "Expected gateway health JSON, got {}", body);
+    assert_eq!(get_path(&router, "/_internal/readyz").await.0, StatusCode::OK);
+    assert_eq!(get_path(&router, "/_internal/proxy/health").await.0, StatusCode::OK);
+
+    // The unprefixed paths are no longer intercepted
+    assert_eq!(get_path(&router, "/health").await.0, StatusCode::NOT_FOUND);
+    assert_eq!(get_path(&router, "/ping").await, (StatusCode::OK, "static ping".to_string()));
+}
@@ -0,0 +1,52 @@
+// Listener tests: each configured listener serves only its own static files and proxy routes
+use httpserver_config::{ Config, ListenerConfig };
+use httpserver_engine::HttpServerEngine;
+use axum::{ Router, body::Body, http::{ Request, StatusCode } };
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::test_support::{ route, start_path_backend };
+
+async fn get_path(router: &Router, path: &str) -> (StatusCode, String) {
+    let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8_lossy(&body).to_string())
+}
+
+#[tokio::test]
+async fn test_listener_serves_its_own_routes() {
+    let (public_dir, admin_dir) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    std::fs::write(public_dir.path().join("page.txt"), "public page").unwrap();
+    std::fs::write(admin_dir.path().join("page.txt"), "admin page").unwrap();
+    let backend = start_path_backend().await;
+
+    let mut config = Config::default();
+    config.static_config.directory = public_dir.path().to_path_buf();
+    config.static_config.spa_fallback = false;
+    config.listeners = vec![
+        ListenerConfig {
+            port: 9000,
+            static_directory: Some(admin_dir.path().to_path_buf()),
+            proxy: vec![route("/admin-api/*", &backend)],
+        },
+        ListenerConfig { port: 9001, static_directory: None, proxy: Vec::new() }
+    ];
+    let engine = HttpServerEngine::new(config, 0).unwrap();
+    let public = engine.router().await.unwrap();
+    let listeners = engine.listener_routers().await.unwrap();
+    let ports: Vec<u16> = listeners.iter().map(|(port, _)| *port).collect();
+    assert_eq!(ports, vec![9000, 9001]);
+    let (admin, empty) = (&listeners[0].1, &listeners[1].1);
+
+    assert_eq!(get_path(&public, "/page.txt").await, (StatusCode::OK, "public page".to_string()));
+    assert_eq!(get_path(admin, "/page.txt").await, (StatusCode::OK, "admin page".to_string()));
+
+    // Proxy routes and health endpoints stay on the port they were configured for
+    assert_eq!(get_path(admin, "/admin-api/users").await, (StatusCode::OK, "backend /users".to_string()));
+    assert_eq!(get_path(&public, "/admin-api/users").await.0, StatusCode::NOT_FOUND);
+    assert_eq!(get_path(&public, "/health").await.0, StatusCode::OK);
+    assert_eq!(get_path(admin, "/health").await.0, StatusCode::NOT_FOUND);
+
+    assert_eq!(get_path(empty, "/page.txt").await.0, StatusCode::NOT_FOUND);
+}
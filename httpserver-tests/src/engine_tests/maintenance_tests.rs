@@ -0,0 +1,124 @@
+// Maintenance mode tests: traffic gets 503 with Retry-After while health endpoints and allowed paths keep serving
+use httpserver_config::{ Config, MaintenanceConfig, ProxyRoute };
+use httpserver_engine::HttpServerEngine;
+use axum::{ Router, body::Body, http::{ Request, StatusCode, header } };
+use serde_json::json;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::test_support::start_path_backend;
+
+/// Engine serving static "page.txt" and "status.txt" files, plus the given proxy routes
+fn create_engine(static_dir: &TempDir, proxy: Vec<ProxyRoute>, maintenance: MaintenanceConfig) -> HttpServerEngine {
+    std::fs::write(static_dir.path().join("page.txt"), "static page").unwrap();
+    std::fs::write(static_dir.path().join("status.txt"), "all good").unwrap();
+    let mut config = Config::default();
+    config.static_config.directory = static_dir.path().to_path_buf();
+    config.static_config.spa_fallback = false;
+    config.proxy = proxy;
+    config.server.maintenance = maintenance;
+    HttpServerEngine::new(config, 0).unwrap()
+}
+
+async fn get_path(router: &Router, path: &str) -> (StatusCode, Option<String>, String) {
+    let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(header::RETRY_AFTER)
+        .map(|value| value.to_str().unwrap().to_string());
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, retry_after, String::from_utf8_lossy(&body).to_string())
+}
+
+#[tokio::test]
+async fn test_maintenance_toggles_at_runtime() {
+    let static_dir = TempDir::new().unwrap();
+    let engine = create_engine(&static_dir, Vec::new(), MaintenanceConfig::default());
+    let handle = engine.handle();
+    let router = engine.router().await.unwrap();
+    assert_eq!(get_path(&router, "/page.txt").await.0, StatusCode::OK);
+
+    handle.set_maintenance(true);
+    let (status, retry_after, body) = get_path(&router, "/page.txt").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(retry_after.as_deref(), Some("300"));
+    assert!(body.contains("maintenance"), "{}", body);
+    let (status, _, body) = get_path(&router, "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("\"status\""), "{}", body);
+    assert_eq!(get_path(&router, "/readyz").await.0, StatusCode::OK);
+
+    // Turning it off restores normal serving
+    handle.set_maintenance(false);
+    assert_eq!(get_path(&router, "/page.txt").await, (StatusCode::OK, None, "static page".to_string()));
+}
+
+#[tokio::test]
+async fn test_configured_page_retry_after_and_allow_paths() {
+    let static_dir = TempDir::new().unwrap();
+    let page_dir = TempDir::new().unwrap();
+    let page = page_dir.path().join("maintenance.html");
+    std::fs::write(&page, "<h1>Back at 10:00</h1>").unwrap();
+    let maintenance = MaintenanceConfig {
+        enabled: true,
+        retry_after: 60,
+        page: Some(page),
+        allow_paths: vec!["/status".to_string()],
+        ..MaintenanceConfig::default()
+    };
+    let router = create_engine(&static_dir, Vec::new(), maintenance).router().await.unwrap();
+
+    assert_eq!(
+        get_path(&router, "/page.txt").await,
+        (StatusCode::SERVICE_UNAVAILABLE, Some("60".to_string()), "<h1>Back at 10:00</h1>".to_string())
+    );
+    assert_eq!(get_path(&router, "/status.txt").await.2, "all good");
+}
+
+#[tokio::test]
+async fn test_page_is_reread_when_settings_are_replaced() {
+    let static_dir = TempDir::new().unwrap();
+    let page_dir = TempDir::new().unwrap();
+    let page = page_dir.path().join("maintenance.html");
+    std::fs::write(&page, "<h1>Back at 10:00</h1>").unwrap();
+    let maintenance = MaintenanceConfig { enabled: true, page: Some(page.clone()), ..MaintenanceConfig::default() };
+    let engine = create_engine(&static_dir, Vec::new(), maintenance.clone());
+    let handle = engine.handle();
+    let router = engine.router().await.unwrap();
+
+    // The page is served from memory until the settings are applied again
+    std::fs::write(&page, "<h1>Back at 11:00</h1>").unwrap();
+    assert_eq!(get_path(&router, "/page.txt").await.2, "<h1>Back at 10:00</h1>");
+
+    handle.configure_maintenance(maintenance).unwrap();
+    assert_eq!(get_path(&router, "/page.txt").await.2, "<h1>Back at 11:00</h1>");
+}
+
+#[tokio::test]
+async fn test_route_maintenance_leaves_other_traffic_alone() {
+    let backend = start_path_backend().await;
+    let route: ProxyRoute = serde_json::from_value(json!({ "path": "/api/*", "target": backend })).unwrap();
+    let static_dir = TempDir::new().unwrap();
+    let engine = create_engine(&static_dir, vec![route], MaintenanceConfig::default());
+    let handle = engine.handle();
+    let router = engine.router().await.unwrap();
+
+    handle.set_route_maintenance("/api/*", true);
+    assert_eq!(handle.maintenance().routes, ["/api/*"]);
+    assert_eq!(get_path(&router, "/api/users").await.0, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(get_path(&router, "/page.txt").await.2, "static page");
+
+    handle.set_route_maintenance("/api/*", false);
+    assert_eq!(get_path(&router, "/api/users").await, (StatusCode::OK, None, "backend /users".to_string()));
+}
+
+#[test]
+fn test_invalid_maintenance_settings_are_rejected() {
+    let static_dir = TempDir::new().unwrap();
+    let handle = create_engine(&static_dir, Vec::new(), MaintenanceConfig::default()).handle();
+
+    let maintenance = MaintenanceConfig { allow_paths: vec!["status".to_string()], ..MaintenanceConfig::default() };
+    assert!(handle.configure_maintenance(maintenance).is_err());
+    assert_eq!(handle.maintenance(), MaintenanceConfig::default());
+}
@@ -0,0 +1,10 @@
+pub mod builder_tests;
+pub mod default_route_tests;
+pub mod health_endpoint_tests;
+pub mod listener_tests;
+pub mod maintenance_tests;
+pub mod proxy_stats_tests;
+pub mod route_handle_tests;
+pub mod router_tests;
+pub mod shutdown_tests;
+pub mod startup_check_tests;
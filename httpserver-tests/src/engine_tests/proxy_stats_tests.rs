@@ -0,0 +1,114 @@
+// Proxy stats tests: /proxy/stats reports per-route request, in-flight and error counts and latency percentiles
+use httpserver_config::Config;
+use httpserver_engine::HttpServerEngine;
+use axum::{ Router, body::Body, http::{ Request, StatusCode }, routing::get };
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::sync::Notify;
+use tower::ServiceExt;
+use crate::test_support::{ route, serve_url };
+
+/// Backend with a 40ms /slow, a failing /fail and a /hold that waits for `release`
+async fn start_backend(release: Arc<Notify>) -> String {
+    let app = Router::new()
+        .route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_millis(40)).await;
+                "slow"
+            })
+        )
+        .route("/fail", get(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+        .route(
+            "/hold",
+            get(move || async move {
+                release.notified().await;
+                "released"
+            })
+        );
+    serve_url(app).await
+}
+
+async fn create_router(release: Arc<Notify>) -> (Router, TempDir) {
+    let static_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.static_config.directory = static_dir.path().to_path_buf();
+    let backend = start_backend(release).await;
+    config.proxy = vec![route("/api/*", &backend), route("/idle/*", &backend)];
+    (HttpServerEngine::new(config, 0).unwrap().router().await.unwrap(), static_dir)
+}
+
+async fn get_status(router: &Router, path: &str) -> StatusCode {
+    let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+    router.clone().oneshot(request).await.unwrap().status()
+}
+
+/// Stats reported for the route with the given path pattern
+async fn route_stats(router: &Router, route_path: &str) -> Value {
+    let request = Request::builder().uri("/proxy/stats").body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let report: Value = serde_json::from_slice(&body).unwrap();
+    report["routes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|route| route["path"] == route_path)
+        .cloned()
+        .unwrap_or_else(|| panic!("No stats for {} in {}", route_path, report))
+}
+
+#[tokio::test]
+async fn test_counters_and_latency_reported_per_route() {
+    let (router, _static_dir) = create_router(Arc::new(Notify::new())).await;
+
+    for _ in 0..4 {
+        assert_eq!(get_status(&router, "/api/slow").await, StatusCode::OK);
+    }
+    assert_eq!(get_status(&router, "/api/fail").await, StatusCode::INTERNAL_SERVER_ERROR);
+
+    let stats = route_stats(&router, "/api/*").await;
+    assert_eq!(stats["requests"], 5);
+    assert_eq!(stats["in_flight"], 0);
+    assert_eq!(stats["errors"], 1);
+    let p50 = stats["latency_ms"]["p50"].as_u64().expect("p50 should be reported");
+    let p95 = stats["latency_ms"]["p95"].as_u64().expect("p95 should be reported");
+    assert!(p50 >= 40, "p50 should include the backend delay: {}", stats);
+    assert!(p95 >= p50);
+
+    // Other routes keep their own counters
+    let idle = route_stats(&router, "/idle/*").await;
+    assert_eq!(idle["requests"], 0);
+    assert_eq!(idle["latency_ms"]["p50"], Value::Null);
+}
+
+#[tokio::test]
+async fn test_in_flight_counts_pending_requests() {
+    let release = Arc::new(Notify::new());
+    let (router, _static_dir) = create_router(release.clone()).await;
+
+    let pending = tokio::spawn({
+        let router = router.clone();
+        async move { get_status(&router, "/api/hold").await }
+    });
+
+    let mut in_flight = Value::Null;
+    for _ in 0..100 {
+        in_flight = route_stats(&router, "/api/*").await["in_flight"].clone();
+        if in_flight == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(in_flight, 1);
+
+    release.notify_one();
+    assert_eq!(pending.await.unwrap(), StatusCode::OK);
+    let stats = route_stats(&router, "/api/*").await;
+    assert_eq!(stats["in_flight"], 0);
+    assert_eq!(stats["requests"], 1);
+    assert_eq!(stats["errors"], 0);
+}
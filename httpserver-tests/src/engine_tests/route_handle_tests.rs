@@ -0,0 +1,61 @@
+// Engine handle tests: proxy routes can be listed, added and removed from code at runtime
+use httpserver_config::{ ConfigError, ProxyRoute };
+use httpserver_engine::HttpServerEngine;
+use crate::test_support::route;
+
+fn paths(routes: &[ProxyRoute]) -> Vec<&str> {
+    routes.iter().map(|route| route.path.as_str()).collect()
+}
+
+#[test]
+fn test_handle_lists_configured_routes() {
+    let engine = HttpServerEngine::builder()
+        .add_proxy_route(route("/api/*", "http://localhost:3000"))
+        .build()
+        .unwrap();
+
+    let routes = engine.handle().list_routes();
+    assert_eq!(paths(&routes), vec!["/api/*"]);
+}
+
+#[test]
+fn test_handle_adds_and_removes_routes() {
+    let engine = HttpServerEngine::builder().build().unwrap();
+    let handle = engine.handle();
+    assert!(handle.list_routes().is_empty());
+
+    handle.add_route(route("/api/*", "http://localhost:3000")).unwrap();
+    handle.add_route(route("/admin/*", "http://localhost:3001")).unwrap();
+    assert_eq!(paths(&handle.list_routes()), vec!["/api/*", "/admin/*"]);
+
+    // Every handle acts on the same engine
+    let other = engine.handle();
+    other.add_route(route("/api/*", "http://localhost:4000")).unwrap();
+    let routes = handle.list_routes();
+    assert_eq!(paths(&routes), vec!["/api/*", "/admin/*"]);
+    assert_eq!(routes[0].get_primary_target().unwrap(), "http://localhost:4000");
+
+    let removed = other.remove_route("/admin/*").unwrap();
+    assert_eq!(removed.get_primary_target().unwrap(), "http://localhost:3001");
+    assert_eq!(paths(&handle.list_routes()), vec!["/api/*"]);
+    assert!(handle.remove_route("/admin/*").is_none());
+}
+
+#[test]
+fn test_handle_rejects_invalid_routes() {
+    let engine = HttpServerEngine::builder()
+        .add_proxy_route(route("/api/*", "http://localhost:3000"))
+        .build()
+        .unwrap();
+    let handle = engine.handle();
+
+    let result = handle.add_route(route("/admin/*", "localhost:3001"));
+    match result {
+        Err(ConfigError::InvalidProxyRoute { index, target, .. }) => {
+            assert_eq!(index, 1);
+            assert_eq!(target, Some(0));
+        }
+        other => panic!("Expected InvalidProxyRoute error, got {:?}", other),
+    }
+    assert_eq!(paths(&handle.list_routes()), vec!["/api/*"]);
+}
@@ -0,0 +1,41 @@
+// Engine router tests: the composed router can be extended with custom handlers and served by the caller
+use httpserver_engine::HttpServerEngine;
+use axum::{ Router, body::Body, http::{ Request, StatusCode }, routing::get };
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::test_support::{ route, start_text_backend };
+
+/// GET `path` on the router, returning status and body
+async fn get_path(router: &Router, path: &str) -> (StatusCode, String) {
+    let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8_lossy(&body).to_string())
+}
+
+#[tokio::test]
+async fn test_custom_route_merged_alongside_proxy_and_static() {
+    let static_dir = TempDir::new().unwrap();
+    std::fs::write(static_dir.path().join("page.txt"), "static page").unwrap();
+    let backend = start_text_backend("hello from backend").await;
+
+    let engine = HttpServerEngine::builder()
+        .static_dir(static_dir.path())
+        .add_proxy_route(route("/api/*", &backend))
+        .build()
+        .unwrap();
+    let router = engine
+        .router().await
+        .unwrap()
+        .merge(Router::new().route("/custom", get(|| async { "custom handler" })));
+
+    assert_eq!(get_path(&router, "/custom").await, (StatusCode::OK, "custom handler".to_string()));
+    assert_eq!(get_path(&router, "/api/hello").await, (StatusCode::OK, "hello from backend".to_string()));
+    assert_eq!(get_path(&router, "/page.txt").await, (StatusCode::OK, "static page".to_string()));
+    assert_eq!(get_path(&router, "/health").await.0, StatusCode::OK);
+
+    // Route changes through the engine handle apply to the embedded router too
+    engine.handle().remove_route("/api/*").unwrap();
+    assert_eq!(get_path(&router, "/api/hello").await.0, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,131 @@
+// Shutdown tests: EngineHandle::shutdown stops a running engine after its requests in flight finish
+use httpserver_config::{ Config, ProxyRoute };
+use httpserver_engine::HttpServerEngine;
+use axum::{ Router, routing::get };
+use serde_json::json;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio::sync::Notify;
+use tokio::time::Duration;
+use crate::test_support::{ free_port, serve };
+
+/// Backend whose /slow notifies `arrived` when a request comes in and answers 300ms later
+async fn start_backend() -> (String, Arc<Notify>) {
+    let arrived = Arc::new(Notify::new());
+    let notify = arrived.clone();
+    let app = Router::new().route(
+        "/slow",
+        get(move || async move {
+            notify.notify_one();
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            "slow done"
+        })
+    );
+    let port = serve(app).await;
+    (format!("http://127.0.0.1:{}", port), arrived)
+}
+
+/// Engine on a free port serving `static_dir`, proxying /api/* to `backend` when given
+fn create_engine(static_dir: &TempDir, backend: Option<&str>) -> (HttpServerEngine, u16) {
+    let mut config = Config::default();
+    config.static_config.directory = static_dir.path().to_path_buf();
+    config.logging.file_logging = false;
+    if let Some(backend) = backend {
+        let route: ProxyRoute = serde_json::from_value(json!({ "path": "/api/*", "target": backend })).unwrap();
+        config.proxy.push(route);
+    }
+    let port = free_port();
+    (HttpServerEngine::new(config, port).unwrap(), port)
+}
+
+async fn wait_for_port(port: u16) {
+    for _ in 0..100 {
+        if tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("Engine did not start listening on port {}", port);
+}
+
+#[tokio::test]
+async fn test_shutdown_stops_a_running_engine() {
+    let static_dir = TempDir::new().unwrap();
+    std::fs::write(static_dir.path().join("index.html"), "home").unwrap();
+    let (engine, port) = create_engine(&static_dir, None);
+    let handle = engine.handle();
+    let started = tokio::spawn(async move { engine.start().await.map_err(|e| e.to_string()) });
+    wait_for_port(port).await;
+
+    let response = reqwest::get(format!("http://127.0.0.1:{}/index.html", port)).await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "home");
+
+    assert!(!handle.is_shutdown_requested());
+    handle.shutdown();
+    assert!(handle.is_shutdown_requested());
+    let result = tokio::time::timeout(Duration::from_secs(5), started).await.expect("start did not return");
+    assert_eq!(result.unwrap(), Ok(()));
+
+    // The port is released once start has returned
+    assert!(tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_shutdown_drains_requests_in_flight() {
+    let static_dir = TempDir::new().unwrap();
+    let (backend, arrived) = start_backend().await;
+    let (engine, port) = create_engine(&static_dir, Some(&backend));
+    let handle = engine.handle();
+    let started = tokio::spawn(async move { engine.start().await.map_err(|e| e.to_string()) });
+    wait_for_port(port).await;
+
+    let in_flight = tokio::spawn(reqwest::get(format!("http://127.0.0.1:{}/api/slow", port)));
+    arrived.notified().await;
+    handle.shutdown();
+
+    // The slow request still completes, and only then does start return
+    let response = in_flight.await.unwrap().unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "slow done");
+    let result = tokio::time::timeout(Duration::from_secs(5), started).await.expect("start did not return");
+    assert_eq!(result.unwrap(), Ok(()));
+}
+
+#[tokio::test]
+async fn test_shutdown_before_start_returns_promptly() {
+    let static_dir = TempDir::new().unwrap();
+    let (engine, _) = create_engine(&static_dir, None);
+    engine.handle().shutdown();
+
+    let result = tokio::time::timeout(Duration::from_secs(5), engine.start()).await.expect("start did not return");
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_shutdown_stops_the_tunnel_server() {
+    let static_dir = TempDir::new().unwrap();
+    let (mut engine_config, port) = (Config::default(), free_port());
+    engine_config.static_config.directory = static_dir.path().to_path_buf();
+    engine_config.logging.file_logging = false;
+    let (tunnel_port, public_port) = (free_port(), free_port());
+    engine_config.tunnel.enabled = true;
+    engine_config.tunnel.server.enabled = true;
+    engine_config.tunnel.server.tunnel_port = tunnel_port;
+    engine_config.tunnel.server.public_port = public_port;
+    engine_config.tunnel.server.storage_path = Some(static_dir.path().join("subdomains.json"));
+
+    let engine = HttpServerEngine::new(engine_config, port).unwrap();
+    let handle = engine.handle();
+    let started = tokio::spawn(async move { engine.start().await.map_err(|e| e.to_string()) });
+    wait_for_port(port).await;
+    wait_for_port(tunnel_port).await;
+
+    handle.shutdown();
+    let result = tokio::time::timeout(Duration::from_secs(5), started).await.expect("start did not return");
+    assert_eq!(result.unwrap(), Ok(()));
+
+    // The tunnel server's listeners are closed along with the main server's
+    for port in [port, tunnel_port, public_port] {
+        assert!(tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_err(), "Port {} still open", port);
+    }
+}
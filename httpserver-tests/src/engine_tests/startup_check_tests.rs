@@ -0,0 +1,145 @@
+// Startup check tests: route targets are probed once before serving, logged, and can abort startup
+use httpserver_config::{ Config, ProxyRoute, StartupCheckConfig };
+use httpserver_engine::HttpServerEngine;
+use serde_json::{ Value, json };
+use std::collections::HashMap;
+use std::sync::{ Arc, Mutex };
+use tracing::field::{ Field, Visit };
+use tracing_subscriber::layer::{ Context, Layer, SubscriberExt };
+use crate::test_support::{ free_port, start_text_backend };
+
+/// Fields of every captured event, including its message
+#[derive(Clone, Default)]
+struct CapturedEvents(Arc<Mutex<Vec<HashMap<String, String>>>>);
+
+struct FieldMap(HashMap<String, String>);
+
+impl Visit for FieldMap {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl<S: tracing::Subscriber> Layer<S> for CapturedEvents {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = FieldMap(HashMap::new());
+        event.record(&mut fields);
+        fields.0.insert("level".to_string(), event.metadata().level().to_string());
+        self.0.lock().unwrap().push(fields.0);
+    }
+}
+
+impl CapturedEvents {
+    fn install(&self) -> tracing::subscriber::DefaultGuard {
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(self.clone()))
+    }
+
+    fn with_message(&self, message: &str) -> Vec<HashMap<String, String>> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|fields| fields.get("message").map(String::as_str) == Some(message))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Address nothing listens on
+fn closed_target() -> String {
+    format!("http://127.0.0.1:{}", free_port())
+}
+
+fn create_route(path: &str, settings: Value) -> ProxyRoute {
+    let mut route = json!({ "path": path });
+    route.as_object_mut().unwrap().extend(settings.as_object().unwrap().clone());
+    serde_json::from_value(route).unwrap()
+}
+
+fn create_engine(proxy: Vec<ProxyRoute>, enabled: bool, fail_fast: bool) -> HttpServerEngine {
+    let config = Config {
+        proxy,
+        startup_check: StartupCheckConfig { enabled, fail_fast, timeout: 1 },
+        ..Default::default()
+    };
+    HttpServerEngine::new(config, 0).unwrap()
+}
+
+#[tokio::test]
+async fn test_reachable_and_unreachable_targets_are_logged() {
+    let captured = CapturedEvents::default();
+    let _guard = captured.install();
+    let (up, down) = (start_text_backend("ok").await, closed_target());
+    let routes = vec![
+        create_route("/mixed/*", json!({ "targets": [{ "url": up }, { "url": down }] })),
+        create_route("/down/*", json!({ "target": down })),
+    ];
+
+    let checks = create_engine(routes, true, false).startup_check().await.unwrap();
+    assert_eq!(checks.len(), 2);
+    assert!(checks[0].has_reachable_target());
+    assert!(!checks[1].has_reachable_target());
+
+    let reachable = captured.with_message("Startup check: target reachable");
+    assert_eq!(reachable.len(), 1, "{:?}", reachable);
+    assert_eq!(reachable[0]["target"], up);
+    assert_eq!(reachable[0]["level"], "INFO");
+    let unreachable = captured.with_message("Startup check: target unreachable");
+    assert_eq!(unreachable.len(), 2, "{:?}", unreachable);
+    assert!(unreachable.iter().all(|fields| fields["target"] == down && fields["level"] == "WARN"));
+    assert!(unreachable.iter().all(|fields| fields.contains_key("error")));
+    let empty = captured.with_message("Startup check: route has no reachable targets");
+    assert_eq!(empty.len(), 1, "{:?}", empty);
+    assert_eq!(empty[0]["route"], "/down/*");
+}
+
+#[tokio::test]
+async fn test_fail_fast_aborts_on_route_without_reachable_targets() {
+    let (up, down) = (start_text_backend("ok").await, closed_target());
+    let routes = vec![
+        create_route("/up/*", json!({ "targets": [{ "url": up }, { "url": closed_target() }] })),
+        create_route("/down/*", json!({ "target": down })),
+    ];
+
+    let error = create_engine(routes.clone(), true, true).startup_check().await.unwrap_err();
+    assert_eq!(error.to_string(), "Startup check failed: no reachable targets for /down/*");
+
+    // One reachable target is enough for a route to pass
+    assert!(create_engine(routes[..1].to_vec(), true, true).startup_check().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_http_health_path_is_used_when_configured() {
+    let up = start_text_backend("ok").await;
+    let route = create_route("/api/*", json!({ "target": up, "http_health": { "path": "/healthz" } }));
+
+    let checks = create_engine(vec![route], true, true).startup_check().await.unwrap();
+    assert_eq!(checks[0].targets[0].error, None);
+}
+
+#[tokio::test]
+async fn test_routes_opt_in_and_out() {
+    let down = closed_target();
+    let routes = vec![
+        create_route("/skipped/*", json!({ "target": down, "startup_check": false })),
+        create_route("/checked/*", json!({ "target": down, "startup_check": true })),
+    ];
+
+    // Checked globally, the opted-out route is left alone
+    let checks = create_engine(routes.clone(), true, false).startup_check().await.unwrap();
+    assert_eq!(checks.iter().map(|check| check.route.as_str()).collect::<Vec<_>>(), ["/checked/*"]);
+
+    // Off globally, a route can still opt in
+    let error = create_engine(routes, false, true).startup_check().await.unwrap_err();
+    assert!(error.to_string().ends_with("/checked/*"), "{}", error);
+
+    // Nothing is probed by default
+    let checks = create_engine(vec![create_route("/api/*", json!({ "target": closed_target() }))], false, true)
+        .startup_check().await
+        .unwrap();
+    assert!(checks.is_empty());
+}
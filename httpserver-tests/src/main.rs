@@ -1,3 +1,4 @@
+
 #[cfg(test)]
 mod config_tests;
 #[cfg(test)]
@@ -5,11 +6,15 @@ mod core_tests;
 #[cfg(test)]
 mod balancer_tests;
 #[cfg(test)]
+mod engine_tests;
+#[cfg(test)]
 mod static_tests;
 #[cfg(test)]
 mod proxy_tests;
 #[cfg(test)]
 mod tunnel_tests;
+#[cfg(test)]
+mod test_support;
 
 fn main() {
     println!("Hello from httpserver-tests!");
@@ -17,6 +22,7 @@ fn main() {
     println!("- config_tests: Configuration parsing and validation tests");
     println!("- core_tests: Core server functionality tests");
     println!("- balancer_tests: Load balancing and circuit breaker tests");
+    println!("- engine_tests: Engine construction tests");
     println!("- static_tests: Static file serving tests");
     println!("- proxy_tests: Proxy handling and WebSocket tests");
     println!("- tunnel_tests: Tunneling and connection tests");
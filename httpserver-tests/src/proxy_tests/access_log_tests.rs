@@ -0,0 +1,125 @@
+// Per-route access log tests: routes can turn their access log line off or write it in a custom format
+
+use httpserver_proxy::{ AccessLogHandled, ProxyHandler };
+use httpserver_config::ProxyRoute;
+use httpserver_core::logging_middleware;
+use axum::{ Router, body::Body, extract::Request, http::StatusCode, middleware, response::IntoResponse };
+use serde_json::{ Value, json };
+use std::net::SocketAddr;
+use std::sync::{ Arc, Mutex };
+use tower::ServiceExt;
+use tracing::field::{ Field, Visit };
+use tracing_subscriber::layer::{ Context, Layer, SubscriberExt };
+use crate::test_support::start_text_backend;
+
+/// Target and message of every captured event
+#[derive(Clone, Default)]
+struct CapturedLines(Arc<Mutex<Vec<(String, String)>>>);
+
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{:?}", value);
+        }
+    }
+}
+
+impl<S: tracing::Subscriber> Layer<S> for CapturedLines {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+        let mut message = Message(String::new());
+        event.record(&mut message);
+        self.0.lock().unwrap().push((event.metadata().target().to_string(), message.0));
+    }
+}
+
+impl CapturedLines {
+    fn install(&self) -> tracing::subscriber::DefaultGuard {
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(self.clone()))
+    }
+
+    fn access_log(&self) -> Vec<String> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(target, _)| target == "access_log")
+            .map(|(_, message)| message.clone())
+            .collect()
+    }
+
+    fn messages(&self) -> Vec<String> {
+        self.0.lock().unwrap().iter().map(|(_, message)| message.clone()).collect()
+    }
+}
+
+fn create_route(target: &str, settings: Value) -> ProxyRoute {
+    let mut route = json!({ "path": "/api/*", "target": target });
+    route.as_object_mut().unwrap().extend(settings.as_object().unwrap().clone());
+    serde_json::from_value(route).unwrap()
+}
+
+fn client_ip() -> SocketAddr {
+    "203.0.113.7:40000".parse().unwrap()
+}
+
+/// Send `uri` to the route behind the server-wide request log
+async fn send_through_request_log(route: ProxyRoute, uri: &str) -> StatusCode {
+    let handler = Arc::new(ProxyHandler::new(vec![route]));
+    let app = Router::new()
+        .fallback(move |req: Request| async move {
+            handler.handle_request(req, client_ip()).await.unwrap().unwrap().into_response()
+        })
+        .layer(middleware::from_fn(logging_middleware));
+    let request = axum::http::Request::builder().uri(uri).body(Body::empty()).unwrap();
+    app.oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn test_custom_format_route_writes_formatted_line() {
+    let captured = CapturedLines::default();
+    let _guard = captured.install();
+    let backend = start_text_backend("hello").await;
+    let format = "$remote_addr - [$route] \"$request\" $status $body_bytes_sent \"$http_referer\" \"$http_user_agent\" $request_time";
+    let handler = ProxyHandler::new(vec![create_route(&backend, json!({ "access_log_format": format }))]);
+
+    let request = axum::http::Request::builder()
+        .uri("/api/items?page=2")
+        .header("user-agent", "curl/8.0")
+        .body(Body::empty())
+        .unwrap();
+    let response = handler.handle_request(request, client_ip()).await.unwrap().unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.extensions().get::<AccessLogHandled>().is_some());
+
+    let lines = captured.access_log();
+    assert_eq!(lines.len(), 1, "{:?}", lines);
+    let (line, request_time) = lines[0].rsplit_once(' ').unwrap();
+    assert_eq!(line, "203.0.113.7 - [/api/*] \"GET /api/items?page=2 HTTP/1.1\" 200 5 \"-\" \"curl/8.0\"");
+    assert!(request_time.parse::<f64>().is_ok(), "{}", request_time);
+}
+
+#[tokio::test]
+async fn test_route_with_access_log_off_writes_no_line() {
+    let captured = CapturedLines::default();
+    let _guard = captured.install();
+    let backend = start_text_backend("hello").await;
+    let route = create_route(&backend, json!({ "access_log": false, "path": "/health/*" }));
+
+    // The server-wide request log skips requests the route opted out of
+    assert_eq!(send_through_request_log(route, "/health/live").await, StatusCode::OK);
+    assert!(captured.access_log().is_empty());
+    assert!(!captured.messages().iter().any(|message| message == "Request completed"), "{:?}", captured.messages());
+}
+
+#[tokio::test]
+async fn test_default_route_keeps_server_request_log() {
+    let captured = CapturedLines::default();
+    let _guard = captured.install();
+    let backend = start_text_backend("hello").await;
+
+    assert_eq!(send_through_request_log(create_route(&backend, json!({})), "/api/items").await, StatusCode::OK);
+    assert!(captured.access_log().is_empty());
+    assert!(captured.messages().iter().any(|message| message == "Request completed"), "{:?}", captured.messages());
+}
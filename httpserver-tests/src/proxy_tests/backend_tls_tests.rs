@@ -0,0 +1,247 @@
+// Backend TLS tests: certificate verification and mutual TLS for proxied routes
+
+use httpserver_proxy::ProxyHandler;
+use httpserver_config::{ ProxyRoute, LoadBalancingStrategy, RouteSslConfig };
+use axum::body::Body;
+use axum::http::Request;
+use rcgen::{ BasicConstraints, Certificate, CertificateParams, DistinguishedName, IsCa };
+use rustls::server::AllowAnyAuthenticatedClient;
+use rustls::{ PrivateKey, RootCertStore, ServerConfig };
+use std::net::SocketAddr;
+use std::path::{ Path, PathBuf };
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio::io::{ AsyncReadExt, AsyncWriteExt };
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+
+fn certificate(common_name: &str, is_ca: bool) -> Certificate {
+    let mut params = CertificateParams::new(vec!["localhost".to_string()]);
+    params.distinguished_name = DistinguishedName::new();
+    params.distinguished_name.push(rcgen::DnType::CommonName, common_name);
+    if is_ca {
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    }
+    Certificate::from_params(params).unwrap()
+}
+
+/// Client CA plus a client certificate it issued, written as PEM files
+struct ClientPki {
+    ca_der: Vec<u8>,
+    cert_file: PathBuf,
+    key_file: PathBuf,
+}
+
+fn create_client_pki(dir: &Path) -> ClientPki {
+    let ca = certificate("Test Client CA", true);
+    let client = certificate("gateway-client", false);
+
+    let cert_file = dir.join("client.crt");
+    let key_file = dir.join("client.key");
+    std::fs::write(&cert_file, client.serialize_pem_with_signer(&ca).unwrap()).unwrap();
+    std::fs::write(&key_file, client.serialize_private_key_pem()).unwrap();
+
+    ClientPki {
+        ca_der: ca.serialize_der().unwrap(),
+        cert_file,
+        key_file,
+    }
+}
+
+/// Start an HTTPS backend with a self-signed certificate. When `client_ca` is set,
+/// the backend requires a client certificate issued by that CA.
+async fn start_tls_backend(client_ca: Option<Vec<u8>>) -> u16 {
+    let server_cert = certificate("localhost", false);
+    let chain = vec![rustls::Certificate(server_cert.serialize_der().unwrap())];
+    let key = PrivateKey(server_cert.serialize_private_key_der());
+
+    let builder = ServerConfig::builder().with_safe_defaults();
+    let config = match client_ca {
+        Some(ca_der) => {
+            let mut roots = RootCertStore::empty();
+            roots.add(&rustls::Certificate(ca_der)).unwrap();
+            builder
+                .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed())
+                .with_single_cert(chain, key)
+                .unwrap()
+        }
+        None => builder.with_no_client_auth().with_single_cert(chain, key).unwrap(),
+    };
+
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                let Ok(mut tls) = acceptor.accept(stream).await else {
+                    return;
+                };
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match tls.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let body = "backend-ok";
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = tls.write_all(response.as_bytes()).await;
+                let _ = tls.shutdown().await;
+            });
+        }
+    });
+    port
+}
+
+fn create_tls_route(port: u16, ssl: RouteSslConfig) -> ProxyRoute {
+    ProxyRoute {
+        path: "/secure/*".to_string(),
+        target: Some(format!("https://localhost:{}", port)),
+        targets: vec![],
+        strategy: LoadBalancingStrategy::RoundRobin,
+        timeout: 5,
+        sticky_sessions: false,
+        http_health: None,
+        websocket_health: None,
+        tcp_health: None,
+        circuit_breaker: None,
+        middleware: None,
+        ssl: Some(ssl),
+        client: None,
+        cache: None,
+        http2: false,
+        forwarded_headers: true,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
+    }
+}
+
+fn backend_ssl(verify: bool, client: Option<&ClientPki>) -> RouteSslConfig {
+    RouteSslConfig {
+        enabled: false,
+        cert_file: None,
+        key_file: None,
+        backend_ssl: true,
+        verify_backend_ssl: verify,
+        client_cert_file: client.map(|pki| pki.cert_file.clone()),
+        client_key_file: client.map(|pki| pki.key_file.clone()),
+    }
+}
+
+async fn proxy_get(route: ProxyRoute) -> Result<(u16, String), String> {
+    let handler = ProxyHandler::new(vec![route]);
+    let request = Request::builder().uri("/secure/data").body(Body::empty()).unwrap();
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+
+    match handler.handle_request(request, client_ip).await.expect("Route should match") {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            Ok((status, String::from_utf8_lossy(&body).to_string()))
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[tokio::test]
+async fn test_route_with_client_certificate_passes_mtls() {
+    let temp_dir = TempDir::new().unwrap();
+    let pki = create_client_pki(temp_dir.path());
+    let port = start_tls_backend(Some(pki.ca_der.clone())).await;
+
+    let route = create_tls_route(port, backend_ssl(false, Some(&pki)));
+    let (status, body) = proxy_get(route).await.expect("mTLS request should succeed");
+
+    assert_eq!(status, 200);
+    assert_eq!(body, "backend-ok");
+}
+
+#[tokio::test]
+async fn test_route_without_client_certificate_is_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let pki = create_client_pki(temp_dir.path());
+    let port = start_tls_backend(Some(pki.ca_der.clone())).await;
+
+    let route = create_tls_route(port, backend_ssl(false, None));
+    let result = proxy_get(route).await;
+
+    assert!(result.is_err(), "Backend requiring mTLS should reject the request: {:?}", result);
+}
+
+#[tokio::test]
+async fn test_incomplete_client_identity_is_a_configuration_error() {
+    let temp_dir = TempDir::new().unwrap();
+    let pki = create_client_pki(temp_dir.path());
+    let port = start_tls_backend(Some(pki.ca_der.clone())).await;
+
+    let mut ssl = backend_ssl(false, Some(&pki));
+    ssl.client_key_file = None;
+    let error = proxy_get(create_tls_route(port, ssl)).await.unwrap_err();
+
+    assert!(error.contains("client_cert_file and client_key_file"), "Unexpected error: {}", error);
+}
+
+#[tokio::test]
+async fn test_self_signed_backend_with_verification_disabled() {
+    let port = start_tls_backend(None).await;
+
+    let route = create_tls_route(port, backend_ssl(false, None));
+    let (status, body) = proxy_get(route).await.expect("Unverified backend should be reachable");
+
+    assert_eq!(status, 200);
+    assert_eq!(body, "backend-ok");
+}
+
+#[tokio::test]
+async fn test_self_signed_backend_with_verification_enabled_fails() {
+    let port = start_tls_backend(None).await;
+
+    // Explicitly enabled and the default (no ssl section) both verify
+    for ssl in [Some(backend_ssl(true, None)), None] {
+        let mut route = create_tls_route(port, backend_ssl(true, None));
+        route.ssl = ssl;
+
+        let error = proxy_get(route).await.unwrap_err();
+        assert!(error.contains("certificate verification failed"), "Unexpected error: {}", error);
+        assert!(error.contains("verify_backend_ssl"), "Error should point at the setting: {}", error);
+    }
+}
+
+#[tokio::test]
+async fn test_untrusted_backend_certificate_maps_to_bad_gateway() {
+    use axum::response::IntoResponse;
+
+    let port = start_tls_backend(None).await;
+    let handler = ProxyHandler::new(vec![create_tls_route(port, backend_ssl(true, None))]);
+    let request = Request::builder().uri("/secure/data").body(Body::empty()).unwrap();
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+
+    let error = handler.handle_request(request, client_ip).await.unwrap().unwrap_err();
+    assert_eq!(error.into_response().status(), 502);
+}
@@ -0,0 +1,102 @@
+// Body integrity tests: checksum and HMAC signature headers are verified before forwarding
+
+use httpserver_proxy::{ ProxyError, ProxyHandler };
+use httpserver_config::ProxyRoute;
+use axum::{ Router, body::{ Body, Bytes }, http::{ Request, StatusCode }, routing::post };
+use serde_json::{ Value, json };
+use std::net::SocketAddr;
+use crate::test_support::serve_url;
+
+const BODY: &str = "The quick brown fox jumps over the lazy dog";
+/// MD5 of BODY, base64 encoded as in Content-MD5
+const BODY_MD5: &str = "nhB9nTcrtoJr2B01QqQZ1g==";
+const BODY_SHA256: &str = "d7a8fbb307d7809469ca9abcb0082e4f8d5651e46d3cdb762d02d0bf37c9e592";
+/// HMAC-SHA256 of BODY keyed with "key"
+const BODY_HMAC: &str = "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8";
+
+/// Backend echoing the request body
+async fn start_echo_backend() -> String {
+    let app = Router::new().route("/echo", post(|body: Bytes| async move { body }));
+    serve_url(app).await
+}
+
+/// Route to the echo backend checking the body as described by `body_integrity`
+async fn create_handler(body_integrity: Value) -> ProxyHandler {
+    let route: ProxyRoute = serde_json
+        ::from_value(
+            json!({ "path": "/api/*", "target": start_echo_backend().await, "body_integrity": body_integrity })
+        )
+        .unwrap();
+    route.validate(0).unwrap();
+    ProxyHandler::new(vec![route])
+}
+
+/// POST BODY with the given headers, returning the status and response body
+async fn send(handler: &ProxyHandler, headers: &[(&str, &str)]) -> (StatusCode, String) {
+    let mut request = Request::builder().method("POST").uri("/api/echo");
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+    let response = match handler.handle_request(request.body(Body::from(BODY)).unwrap(), client_ip).await.unwrap() {
+        Ok(response) => response,
+        Err(error) => {
+            assert!(matches!(error, ProxyError::ChecksumMismatch(_) | ProxyError::InvalidSignature(_)));
+            error.into_negotiated_response(None)
+        }
+    };
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8_lossy(&body).to_string())
+}
+
+#[tokio::test]
+async fn test_valid_checksum_is_forwarded() {
+    let handler = create_handler(json!({ "algorithm": "md5" })).await;
+    assert_eq!(send(&handler, &[("content-md5", BODY_MD5)]).await, (StatusCode::OK, BODY.to_string()));
+
+    let handler = create_handler(json!({ "algorithm": "sha256", "header": "x-body-sha256" })).await;
+    assert_eq!(send(&handler, &[("x-body-sha256", BODY_SHA256)]).await, (StatusCode::OK, BODY.to_string()));
+}
+
+#[tokio::test]
+async fn test_invalid_checksum_is_rejected() {
+    let handler = create_handler(json!({ "algorithm": "md5" })).await;
+
+    // A checksum of a different body, and a value that is not a checksum at all
+    assert_eq!(send(&handler, &[("content-md5", "1B2M2Y8AsgTpgAmY7PhCfg==")]).await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(send(&handler, &[("content-md5", "not-a-checksum")]).await.0, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_missing_header_rejected_only_when_required() {
+    let handler = create_handler(json!({ "algorithm": "md5" })).await;
+    assert_eq!(send(&handler, &[]).await.0, StatusCode::BAD_REQUEST);
+
+    let handler = create_handler(json!({ "algorithm": "md5", "required": false })).await;
+    assert_eq!(send(&handler, &[]).await, (StatusCode::OK, BODY.to_string()));
+    assert_eq!(send(&handler, &[("content-md5", "1B2M2Y8AsgTpgAmY7PhCfg==")]).await.0, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_hmac_signature_verified_with_secret() {
+    let handler = create_handler(json!({ "algorithm": "hmac_sha256", "secret": "key" })).await;
+
+    let signature = format!("sha256={}", BODY_HMAC);
+    assert_eq!(send(&handler, &[("x-signature", &signature)]).await, (StatusCode::OK, BODY.to_string()));
+
+    // Signed with another secret, or not signed at all
+    assert_eq!(send(&handler, &[("x-signature", BODY_SHA256)]).await.0, StatusCode::UNAUTHORIZED);
+    assert_eq!(send(&handler, &[]).await.0, StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+fn test_hmac_requires_secret() {
+    let route: ProxyRoute = serde_json
+        ::from_value(
+            json!({ "path": "/api/*", "target": "http://localhost:3000", "body_integrity": { "algorithm": "hmac_sha256" } })
+        )
+        .unwrap();
+    let error = route.validate(0).unwrap_err().to_string();
+    assert!(error.contains("secret is required"), "{}", error);
+}
@@ -0,0 +1,125 @@
+// Body logging tests: logged bodies have sensitive fields redacted and are capped in size
+
+use httpserver_proxy::ProxyHandler;
+use httpserver_proxy::body_log::{ redact_body, redact_headers };
+use httpserver_config::{ BodyLogConfig, ProxyRoute };
+use axum::{ Router, body::Body, http::{ HeaderMap, HeaderValue, Request }, routing::post };
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::{ Arc, Mutex };
+use tracing_subscriber::fmt::MakeWriter;
+use crate::test_support::serve;
+
+/// Log output collected in memory
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).to_string()
+    }
+}
+
+impl std::io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = CapturedLogs;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[test]
+fn test_json_fields_redacted_at_any_depth() {
+    let config = BodyLogConfig::default();
+    let body = json!({
+        "user": "alice",
+        "Password": "hunter2",
+        "session": { "token": "abc123", "expires": 3600 },
+        "keys": [{ "api_key": "k-1" }, { "name": "public" }]
+    });
+
+    let logged = redact_body(body.to_string().as_bytes(), &config);
+    let logged: serde_json::Value = serde_json::from_str(&logged).unwrap();
+    assert_eq!(logged["user"], "alice");
+    assert_eq!(logged["Password"], "***");
+    assert_eq!(logged["session"]["token"], "***");
+    assert_eq!(logged["session"]["expires"], 3600);
+    assert_eq!(logged["keys"][0]["api_key"], "***");
+    assert_eq!(logged["keys"][1]["name"], "public");
+}
+
+#[test]
+fn test_oversized_body_truncated_with_marker() {
+    let config = BodyLogConfig { max_bytes: 10, ..BodyLogConfig::default() };
+
+    assert_eq!(redact_body(b"short", &config), "short");
+    assert_eq!(redact_body(b"0123456789abcdef", &config), "0123456789...[truncated 6 bytes]");
+    // Multi-byte characters are never split
+    assert_eq!(redact_body("aéééééé".as_bytes(), &config), "aéééé...[truncated 4 bytes]");
+}
+
+#[test]
+fn test_sensitive_headers_redacted() {
+    let config = BodyLogConfig::default();
+    let mut headers = HeaderMap::new();
+    headers.insert("content-type", HeaderValue::from_static("application/json"));
+    headers.insert("Authorization", HeaderValue::from_static("Bearer secret-token"));
+
+    let logged = redact_headers(&headers, &config);
+    assert!(logged.contains("content-type: application/json"));
+    assert!(logged.contains("authorization: ***"));
+    assert!(!logged.contains("secret-token"));
+}
+
+#[tokio::test]
+async fn test_proxied_bodies_logged_with_redaction() {
+    let app = Router::new().route(
+        "/login",
+        post(|| async { r#"{"access_token":"issued-token-xyz","user":"alice"}"# })
+    );
+    let port = serve(app).await;
+
+    let route: ProxyRoute = serde_json::from_value(json!({
+        "path": "/api/*",
+        "target": format!("http://127.0.0.1:{}", port),
+        "log_bodies": { "max_bytes": 1024 }
+    })).unwrap();
+    let handler = ProxyHandler::new(vec![route]);
+
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::fmt().with_writer(logs.clone()).with_ansi(false).finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/login")
+        .header("authorization", "Bearer client-credential")
+        .body(Body::from(r#"{"user":"alice","password":"hunter2"}"#))
+        .unwrap();
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+    let response = handler.handle_request(request, client_ip).await.unwrap().unwrap();
+
+    // The client still gets the real response
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("issued-token-xyz"));
+
+    let logs = logs.contents();
+    assert!(logs.contains("Proxy request body"));
+    assert!(logs.contains("Proxy response body"));
+    assert!(logs.contains(r#""password":"***""#), "Request body not redacted: {}", logs);
+    assert!(logs.contains(r#""access_token":"***""#), "Response body not redacted: {}", logs);
+    assert!(!logs.contains("hunter2"));
+    assert!(!logs.contains("issued-token-xyz"));
+    assert!(!logs.contains("client-credential"));
+}
@@ -0,0 +1,76 @@
+// Canary routing tests: a percentage of requests, or those with a tester header, go to the canary target
+
+use httpserver_proxy::ProxyHandler;
+use httpserver_config::{ CanaryConfig, CanaryHeaderMatch, ProxyRoute };
+use axum::{ body::Body, http::Request };
+use serde_json::json;
+use std::net::SocketAddr;
+use crate::test_support::start_text_backend;
+
+/// Route balancing /api/* over a stable target, with `percentage` of traffic sent to a canary
+async fn create_route(percentage: f64) -> ProxyRoute {
+    let mut route: ProxyRoute = serde_json
+        ::from_value(json!({ "path": "/api/*", "targets": [{ "url": start_text_backend("stable").await }] }))
+        .unwrap();
+    route.canary = Some(CanaryConfig {
+        target: start_text_backend("canary").await,
+        percentage,
+        header_match: Some(CanaryHeaderMatch { name: "X-Canary".to_string(), value: "always".to_string() }),
+    });
+    route
+}
+
+/// Which backend served the request
+async fn served_by(handler: &ProxyHandler, canary_header: Option<&str>) -> String {
+    let mut request = Request::builder().uri("/api/orders");
+    if let Some(value) = canary_header {
+        request = request.header("x-canary", value);
+    }
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+    let response = handler.handle_request(request.body(Body::empty()).unwrap(), client_ip).await.unwrap().unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8_lossy(&body).to_string()
+}
+
+#[tokio::test]
+async fn test_canary_receives_its_percentage_of_requests() {
+    let handler = ProxyHandler::new(vec![create_route(5.0).await]);
+
+    let mut canary = 0;
+    for _ in 0..2000 {
+        if served_by(&handler, None).await == "canary" {
+            canary += 1;
+        }
+    }
+
+    // 5% of 2000 is 100; the bounds are about four standard deviations either side
+    assert!((60..=140).contains(&canary), "Canary served {} of 2000 requests", canary);
+}
+
+#[tokio::test]
+async fn test_matching_header_always_routes_to_canary() {
+    let handler = ProxyHandler::new(vec![create_route(0.0).await]);
+
+    for _ in 0..50 {
+        assert_eq!(served_by(&handler, Some("always")).await, "canary");
+    }
+
+    // Other values and requests without the header stay on the stable target
+    assert_eq!(served_by(&handler, Some("sometimes")).await, "stable");
+    assert_eq!(served_by(&handler, None).await, "stable");
+}
+
+#[tokio::test]
+async fn test_invalid_canary_is_rejected() {
+    let mut route = create_route(150.0).await;
+    let error = route.validate(0).unwrap_err();
+    assert_eq!(error.to_string(), "Proxy route 0: canary percentage must be between 0 and 100");
+
+    route.canary.as_mut().unwrap().percentage = 5.0;
+    route.canary.as_mut().unwrap().target = "canary.internal:8080".to_string();
+    let error = route.validate(0).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Proxy route 0: canary target must be a valid HTTP/HTTPS URL: canary.internal:8080"
+    );
+}
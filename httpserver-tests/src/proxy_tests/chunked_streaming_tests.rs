@@ -0,0 +1,134 @@
+// Chunked streaming tests: unsized backend responses reach the client chunk by chunk instead of being buffered
+
+use httpserver_proxy::ProxyHandler;
+use httpserver_config::ProxyRoute;
+use axum::{ Router, body::Body, extract::Request, http::StatusCode, response::{ IntoResponse, Response } };
+use axum::routing::get;
+use futures_util::stream;
+use serde_json::json;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{ Duration, Instant };
+use crate::test_support::{ serve, serve_url };
+
+/// Gap between the chunks of the backend's /ticks stream
+const TICK: Duration = Duration::from_millis(300);
+
+/// Backend with a /ticks stream sending "tick N\n" three times, TICK apart, and a sized /fixed body
+async fn start_backend() -> String {
+    let app = Router::new()
+        .route(
+            "/ticks",
+            get(|| async {
+                let ticks = stream::unfold(0, |sent| async move {
+                    if sent == 3 {
+                        return None;
+                    }
+                    if sent > 0 {
+                        tokio::time::sleep(TICK).await;
+                    }
+                    Some((Ok::<_, Infallible>(format!("tick {}\n", sent + 1)), sent + 1))
+                });
+                Response::new(Body::from_stream(ticks))
+            })
+        )
+        .route("/fixed", get(|| async { "fixed body" }));
+    serve_url(app).await
+}
+
+/// Gateway proxying /api/* to `target`, served over TCP so hyper does the re-chunking
+async fn start_proxy(target: &str) -> u16 {
+    start_proxy_with_route(json!({ "path": "/api/*", "target": target })).await
+}
+
+/// Gateway serving a single route given as its JSON configuration
+async fn start_proxy_with_route(route: serde_json::Value) -> u16 {
+    let route: ProxyRoute = serde_json::from_value(route).unwrap();
+    let handler = Arc::new(ProxyHandler::new(vec![route]));
+    let app = Router::new().fallback(move |request: Request| {
+        let handler = handler.clone();
+        async move {
+            let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+            match handler.handle_request(request, client_ip).await {
+                Some(Ok(response)) => response,
+                Some(Err(e)) => e.into_response(),
+                None => StatusCode::NOT_FOUND.into_response(),
+            }
+        }
+    });
+    serve(app).await
+}
+
+#[tokio::test]
+async fn test_chunked_response_arrives_incrementally() {
+    let backend = start_backend().await;
+    let proxy_port = start_proxy(&backend).await;
+
+    let started = Instant::now();
+    let mut response = reqwest::get(format!("http://127.0.0.1:{}/api/ticks", proxy_port)).await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["transfer-encoding"], "chunked");
+    assert!(response.headers().get("content-length").is_none());
+
+    // Each chunk is relayed as the backend sends it, not once the stream has finished
+    let mut received = String::new();
+    let mut arrivals = Vec::new();
+    while let Some(chunk) = response.chunk().await.unwrap() {
+        received.push_str(&String::from_utf8_lossy(&chunk));
+        arrivals.push(started.elapsed());
+    }
+    assert_eq!(received, "tick 1\ntick 2\ntick 3\n");
+    assert!(arrivals[0] < TICK, "First chunk took {:?}, the stream was buffered", arrivals[0]);
+    assert!(arrivals.last().unwrap() >= &(TICK * 2));
+}
+
+/// Read a response to the end, returning the body and when its first chunk arrived
+async fn read_chunks(mut response: reqwest::Response, started: Instant) -> (String, Duration) {
+    let mut received = String::new();
+    let mut first_chunk = None;
+    while let Some(chunk) = response.chunk().await.unwrap() {
+        received.push_str(&String::from_utf8_lossy(&chunk));
+        first_chunk.get_or_insert(started.elapsed());
+    }
+    (received, first_chunk.unwrap())
+}
+
+#[tokio::test]
+async fn test_cached_and_compressed_route_still_streams() {
+    let backend = start_backend().await;
+    let proxy_port = start_proxy_with_route(
+        json!({
+            "path": "/api/*",
+            "target": backend,
+            "cache": {},
+            "middleware": { "compression": { "threshold_bytes": 1 } },
+        })
+    ).await;
+
+    let client = reqwest::Client::new();
+    let url = format!("http://127.0.0.1:{}/api/ticks", proxy_port);
+    for _ in 0..2 {
+        let started = Instant::now();
+        let response = client.get(&url).header("accept-encoding", "gzip").send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        // Neither stored nor compressed, since both would buffer the whole stream
+        assert_eq!(response.headers()["x-cache"], "MISS");
+        assert!(response.headers().get("content-encoding").is_none());
+
+        let (received, first_chunk) = read_chunks(response, started).await;
+        assert_eq!(received, "tick 1\ntick 2\ntick 3\n");
+        assert!(first_chunk < TICK, "First chunk took {:?}, the stream was buffered", first_chunk);
+    }
+}
+
+#[tokio::test]
+async fn test_sized_response_keeps_content_length() {
+    let backend = start_backend().await;
+    let proxy_port = start_proxy(&backend).await;
+
+    let response = reqwest::get(format!("http://127.0.0.1:{}/api/fixed", proxy_port)).await.unwrap();
+    assert_eq!(response.headers()["content-length"], "10");
+    assert!(response.headers().get("transfer-encoding").is_none());
+    assert_eq!(response.text().await.unwrap(), "fixed body");
+}
@@ -0,0 +1,167 @@
+// Proxy client tests: connect timeouts, connection pooling and redirect handling
+
+use httpserver_proxy::{ ProxyHandler, ProxyError };
+use httpserver_config::{ ProxyRoute, LoadBalancingStrategy, ProxyClientConfig, RouteClientConfig };
+use axum::{ Router, body::Body, extract::ConnectInfo, http::Request, response::Redirect, routing::get };
+use std::net::SocketAddr;
+use std::time::{ Duration, Instant };
+use tokio::net::{ TcpListener, TcpSocket, TcpStream };
+
+fn create_route(target: String, client: Option<RouteClientConfig>) -> ProxyRoute {
+    ProxyRoute {
+        path: "/api/*".to_string(),
+        target: Some(target),
+        targets: vec![],
+        strategy: LoadBalancingStrategy::RoundRobin,
+        timeout: 30,
+        sticky_sessions: false,
+        http_health: None,
+        websocket_health: None,
+        tcp_health: None,
+        circuit_breaker: None,
+        middleware: None,
+        ssl: None,
+        client,
+        cache: None,
+        http2: false,
+        forwarded_headers: true,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
+    }
+}
+
+/// Backend that reports the port of the connection each request arrived on
+async fn start_backend() -> u16 {
+    let app = Router::new()
+        .route(
+            "/peer",
+            get(|ConnectInfo(peer): ConnectInfo<SocketAddr>| async move { peer.port().to_string() })
+        )
+        .route("/moved", get(|| async { Redirect::temporary("/peer") }));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap();
+    });
+    port
+}
+
+/// Listener whose accept queue is full, so new connection attempts hang
+async fn start_unresponsive_backend() -> (u16, TcpListener, Vec<TcpStream>) {
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.bind("127.0.0.1:0".parse().unwrap()).unwrap();
+    let listener = socket.listen(1).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mut queued = Vec::new();
+    while
+        let Ok(Ok(stream)) = tokio::time::timeout(
+            Duration::from_millis(200),
+            TcpStream::connect(addr)
+        ).await
+    {
+        queued.push(stream);
+    }
+    (addr.port(), listener, queued)
+}
+
+async fn proxy_get(handler: &ProxyHandler, path: &str) -> Result<(u16, String), ProxyError> {
+    let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+
+    let response = handler.handle_request(request, client_ip).await.expect("Route should match")?;
+    let status = response.status().as_u16();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    Ok((status, String::from_utf8_lossy(&body).to_string()))
+}
+
+#[tokio::test]
+async fn test_connect_timeout_surfaces_timeout_error() {
+    let (port, _listener, _queued) = start_unresponsive_backend().await;
+    let route = create_route(
+        format!("http://127.0.0.1:{}", port),
+        Some(RouteClientConfig {
+            connect_timeout: Some(1),
+            ..Default::default()
+        })
+    );
+    let handler = ProxyHandler::new(vec![route]);
+
+    let started = Instant::now();
+    let result = proxy_get(&handler, "/api/peer").await;
+
+    assert!(
+        matches!(result, Err(ProxyError::Timeout(1))),
+        "Expected a connect timeout, got {:?}",
+        result
+    );
+    assert!(started.elapsed() < Duration::from_secs(10), "Route timeout should not apply");
+}
+
+#[tokio::test]
+async fn test_pooled_client_reuses_connections() {
+    let port = start_backend().await;
+    let route = create_route(format!("http://127.0.0.1:{}", port), None);
+    let handler = ProxyHandler::new(vec![route]);
+
+    let (status, first_port) = proxy_get(&handler, "/api/peer").await.unwrap();
+    assert_eq!(status, 200);
+    let (_, second_port) = proxy_get(&handler, "/api/peer").await.unwrap();
+
+    assert_eq!(first_port, second_port, "Second request should reuse the pooled connection");
+}
+
+#[tokio::test]
+async fn test_route_override_disables_pooling() {
+    let port = start_backend().await;
+    let route = create_route(
+        format!("http://127.0.0.1:{}", port),
+        Some(RouteClientConfig {
+            pool_max_idle_per_host: Some(0),
+            ..Default::default()
+        })
+    );
+    let handler = ProxyHandler::with_client_config(vec![route], ProxyClientConfig::default());
+
+    let (_, first_port) = proxy_get(&handler, "/api/peer").await.unwrap();
+    let (_, second_port) = proxy_get(&handler, "/api/peer").await.unwrap();
+
+    assert_ne!(first_port, second_port, "Each request should open a new connection");
+}
+
+#[tokio::test]
+async fn test_follow_redirects_can_be_disabled_globally() {
+    let port = start_backend().await;
+    let target = format!("http://127.0.0.1:{}", port);
+
+    let following = ProxyHandler::new(vec![create_route(target.clone(), None)]);
+    let (status, _) = proxy_get(&following, "/api/moved").await.unwrap();
+    assert_eq!(status, 200);
+
+    let client_config = ProxyClientConfig {
+        follow_redirects: false,
+        ..Default::default()
+    };
+    let passthrough = ProxyHandler::with_client_config(vec![create_route(target, None)], client_config);
+    let (status, _) = proxy_get(&passthrough, "/api/moved").await.unwrap();
+    assert_eq!(status, 307);
+}
@@ -0,0 +1,192 @@
+// CORS preflight tests: routes with CORS settings answer OPTIONS preflights without the backend
+
+use httpserver_proxy::ProxyHandler;
+use httpserver_config::{ ProxyRoute, LoadBalancingStrategy, MiddlewareConfig, CorsConfig };
+use axum::{ Router, body::Body, http::{ Request, StatusCode }, response::Response, routing::get };
+use std::net::SocketAddr;
+use std::sync::{ Arc, atomic::{ AtomicUsize, Ordering } };
+use crate::test_support::serve;
+
+fn create_route(target: String, cors: CorsConfig) -> ProxyRoute {
+    ProxyRoute {
+        path: "/api/*".to_string(),
+        target: Some(target),
+        targets: vec![],
+        strategy: LoadBalancingStrategy::RoundRobin,
+        timeout: 30,
+        sticky_sessions: false,
+        http_health: None,
+        websocket_health: None,
+        tcp_health: None,
+        circuit_breaker: None,
+        middleware: Some(MiddlewareConfig {
+            headers: None,
+            rate_limit: None,
+            transform: None,
+            auth: None,
+            compression: None,
+            cors: Some(cors),
+        }),
+        ssl: None,
+        client: None,
+        cache: None,
+        http2: false,
+        forwarded_headers: true,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
+    }
+}
+
+fn cors_config() -> CorsConfig {
+    CorsConfig {
+        allowed_origins: vec!["https://app.example.com".to_string()],
+        allowed_methods: vec!["GET".to_string(), "POST".to_string()],
+        allowed_headers: vec!["content-type".to_string(), "authorization".to_string()],
+        exposed_headers: vec!["x-request-id".to_string()],
+        max_age: Some(600),
+        ..CorsConfig::default()
+    }
+}
+
+/// Backend answering GET and OPTIONS on /items, counting every request it receives
+async fn start_backend() -> (u16, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let get_hits = hits.clone();
+    let options_hits = hits.clone();
+    let app = Router::new().route(
+        "/items",
+        get(move || async move {
+            get_hits.fetch_add(1, Ordering::SeqCst);
+            "items from backend"
+        }).options(move || async move {
+            options_hits.fetch_add(1, Ordering::SeqCst);
+            "options from backend"
+        })
+    );
+    let port = serve(app).await;
+    (port, hits)
+}
+
+async fn send(handler: &ProxyHandler, request: Request<Body>) -> Response<Body> {
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+    handler.handle_request(request, client_ip).await.unwrap().unwrap()
+}
+
+fn preflight(origin: &str) -> Request<Body> {
+    Request::builder()
+        .method("OPTIONS")
+        .uri("/api/items")
+        .header("origin", origin)
+        .header("access-control-request-method", "POST")
+        .header("access-control-request-headers", "content-type")
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn header<'a>(response: &'a Response<Body>, name: &str) -> Option<&'a str> {
+    response.headers().get(name).map(|value| value.to_str().unwrap())
+}
+
+#[tokio::test]
+async fn test_preflight_answered_without_backend() {
+    let (port, hits) = start_backend().await;
+    let handler = ProxyHandler::new(vec![create_route(format!("http://127.0.0.1:{}", port), cors_config())]);
+
+    let response = send(&handler, preflight("https://app.example.com")).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(header(&response, "access-control-allow-origin"), Some("https://app.example.com"));
+    assert_eq!(header(&response, "access-control-allow-methods"), Some("GET, POST"));
+    assert_eq!(header(&response, "access-control-allow-headers"), Some("content-type, authorization"));
+    assert_eq!(header(&response, "access-control-max-age"), Some("600"));
+    assert_eq!(header(&response, "vary"), Some("origin"));
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+    // Origins outside the list are refused, still without a backend round-trip
+    let response = send(&handler, preflight("https://evil.example.com")).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert!(header(&response, "access-control-allow-origin").is_none());
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_actual_request_forwarded_with_cors_headers() {
+    let (port, hits) = start_backend().await;
+    let handler = ProxyHandler::new(vec![create_route(format!("http://127.0.0.1:{}", port), cors_config())]);
+
+    let request = Request::builder()
+        .uri("/api/items")
+        .header("origin", "https://app.example.com")
+        .body(Body::empty())
+        .unwrap();
+    let response = send(&handler, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(header(&response, "access-control-allow-origin"), Some("https://app.example.com"));
+    assert_eq!(header(&response, "access-control-expose-headers"), Some("x-request-id"));
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(&body[..], b"items from backend");
+}
+
+#[tokio::test]
+async fn test_any_origin_and_requested_headers_by_default() {
+    let (port, hits) = start_backend().await;
+    let handler = ProxyHandler::new(
+        vec![create_route(format!("http://127.0.0.1:{}", port), CorsConfig::default())]
+    );
+
+    let response = send(&handler, preflight("https://anywhere.example.com")).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(header(&response, "access-control-allow-origin"), Some("*"));
+    assert_eq!(header(&response, "access-control-allow-headers"), Some("content-type"));
+    assert!(header(&response, "access-control-max-age").is_none());
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_preflight_forwarded_when_gateway_handling_disabled() {
+    let (port, hits) = start_backend().await;
+    let cors = CorsConfig { handle_preflight: false, ..cors_config() };
+    let handler = ProxyHandler::new(vec![create_route(format!("http://127.0.0.1:{}", port), cors)]);
+
+    let response = send(&handler, preflight("https://app.example.com")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_credentials_only_for_listed_origins() {
+    let (port, _hits) = start_backend().await;
+    let target = format!("http://127.0.0.1:{}", port);
+
+    let listed = CorsConfig { allow_credentials: true, ..cors_config() };
+    let handler = ProxyHandler::new(vec![create_route(target.clone(), listed)]);
+    let response = send(&handler, preflight("https://app.example.com")).await;
+    assert_eq!(header(&response, "access-control-allow-origin"), Some("https://app.example.com"));
+    assert_eq!(header(&response, "access-control-allow-credentials"), Some("true"));
+
+    // A route built in code can skip validation; any origin still never gets credentials
+    let any_origin = CorsConfig { allow_credentials: true, ..CorsConfig::default() };
+    let handler = ProxyHandler::new(vec![create_route(target, any_origin)]);
+    let response = send(&handler, preflight("https://evil.example.com")).await;
+    assert_eq!(header(&response, "access-control-allow-origin"), Some("*"));
+    assert!(header(&response, "access-control-allow-credentials").is_none());
+}
@@ -0,0 +1,101 @@
+// Dynamic route tests: routes added or removed on a live handler take effect immediately
+
+use httpserver_proxy::ProxyHandler;
+use httpserver_config::{ ProxyRoute, LoadBalancingStrategy };
+use axum::{ body::Body, http::Request };
+use std::net::SocketAddr;
+use crate::test_support::start_text_backend;
+
+fn create_route(path: &str, target: &str) -> ProxyRoute {
+    ProxyRoute {
+        path: path.to_string(),
+        target: Some(target.to_string()),
+        targets: vec![],
+        strategy: LoadBalancingStrategy::RoundRobin,
+        timeout: 30,
+        sticky_sessions: false,
+        http_health: None,
+        websocket_health: None,
+        tcp_health: None,
+        circuit_breaker: None,
+        middleware: None,
+        ssl: None,
+        client: None,
+        cache: None,
+        http2: false,
+        forwarded_headers: true,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
+    }
+}
+
+/// Send GET `path` through the handler; None when no route matches
+async fn proxy_get(handler: &ProxyHandler, path: &str) -> Option<String> {
+    let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+
+    let response = handler.handle_request(request, client_ip).await?.unwrap();
+    assert_eq!(response.status(), 200);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    Some(String::from_utf8_lossy(&body).to_string())
+}
+
+#[tokio::test]
+async fn test_added_route_forwards_requests() {
+    let backend = start_text_backend("hello from backend").await;
+    let mut handler = ProxyHandler::new(vec![]);
+    assert!(proxy_get(&handler, "/api/hello").await.is_none());
+
+    handler.add_route(create_route("/api/*", &backend));
+    assert!(handler.has_routes());
+    assert_eq!(proxy_get(&handler, "/api/hello").await.as_deref(), Some("hello from backend"));
+
+    let removed = handler.remove_route("/api/*").unwrap();
+    assert_eq!(removed.path, "/api/*");
+    assert!(proxy_get(&handler, "/api/hello").await.is_none());
+    assert!(handler.load_balancer("/api/*").is_none());
+    assert!(handler.remove_route("/api/*").is_none());
+}
+
+#[test]
+fn test_replacing_route_keeps_order_and_other_route_state() {
+    let mut handler = ProxyHandler::new(vec![
+        create_route("/api/*", "http://localhost:3000"),
+        create_route("/admin/*", "http://localhost:3001")
+    ]);
+    handler.load_balancer("/admin/*").unwrap().set_target_health("http://localhost:3001", false);
+    let snapshot = handler.clone();
+
+    // Same path: replaced where it was, not appended
+    handler.add_route(create_route("/api/*", "http://localhost:4000"));
+    let paths: Vec<&str> = handler.routes().iter().map(|route| route.path.as_str()).collect();
+    assert_eq!(paths, vec!["/api/*", "/admin/*"]);
+    let route_match = handler.find_route("/api/users").unwrap();
+    assert_eq!(route_match.route.get_primary_target().unwrap(), "http://localhost:4000");
+
+    // The untouched route keeps its balancer, shared with earlier clones
+    assert_eq!(handler.unready_routes(), vec!["/admin/*".to_string()]);
+    assert_eq!(snapshot.unready_routes(), vec!["/admin/*".to_string()]);
+
+    // Earlier clones keep the routes they had
+    let route_match = snapshot.find_route("/api/users").unwrap();
+    assert_eq!(route_match.route.get_primary_target().unwrap(), "http://localhost:3000");
+}
@@ -0,0 +1,88 @@
+// Proxy error response tests: JSON vs HTML negotiation and Retry-After on transient failures
+
+use httpserver_proxy::{ ProxyError, ProxyHandler, PROXY_RETRY_AFTER_SECS };
+use httpserver_config::{ ProxyClientConfig, ProxyRoute };
+use axum::body::Body;
+use axum::http::{ HeaderValue, Request, StatusCode, header };
+use axum::response::{ IntoResponse, Response };
+use serde_json::json;
+
+async fn body_text(response: Response) -> String {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8_lossy(&body).to_string()
+}
+
+fn content_type(response: &Response) -> &str {
+    response.headers()[header::CONTENT_TYPE].to_str().unwrap()
+}
+
+#[tokio::test]
+async fn test_json_error_when_client_accepts_json() {
+    let accept = HeaderValue::from_static("application/json");
+    let response = ProxyError::ConnectionFailed(
+        "http://localhost:9".to_string()
+    ).into_negotiated_response(Some(&accept));
+
+    assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    assert!(content_type(&response).starts_with("application/json"));
+
+    let body: serde_json::Value = serde_json::from_str(&body_text(response).await).unwrap();
+    assert_eq!(body["error"], "Backend server unavailable");
+    assert_eq!(body["status"], 502);
+    assert_eq!(body["detail"], "Connection failed to: http://localhost:9");
+}
+
+#[tokio::test]
+async fn test_html_error_by_default() {
+    // Browsers list text/html first; no Accept header also means HTML
+    let browser = HeaderValue::from_static("text/html,application/xhtml+xml,application/json;q=0.9");
+    for accept in [Some(&browser), None] {
+        let response = ProxyError::RequestFailed("boom".to_string()).into_negotiated_response(accept);
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(content_type(&response).starts_with("text/html"));
+        assert!(body_text(response).await.contains("<h1>500 Internal Server Error</h1>"));
+    }
+
+    let response = ProxyError::RequestFailed("boom".to_string()).into_response();
+    assert!(content_type(&response).starts_with("text/html"));
+    assert!(!response.headers().contains_key(header::RETRY_AFTER));
+}
+
+#[tokio::test]
+async fn test_gateway_timeout_sets_retry_after() {
+    let accept = HeaderValue::from_static("application/problem+json");
+    let response = ProxyError::Timeout(30).into_negotiated_response(Some(&accept));
+
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    assert_eq!(
+        response.headers()[header::RETRY_AFTER],
+        PROXY_RETRY_AFTER_SECS.to_string().as_str()
+    );
+    let body: serde_json::Value = serde_json::from_str(&body_text(response).await).unwrap();
+    assert_eq!(body["status"], 504);
+}
+
+#[tokio::test]
+async fn test_all_targets_unhealthy_is_503_with_configured_retry_after() {
+    let targets = ["http://127.0.0.1:9101", "http://127.0.0.1:9102"];
+    let route: ProxyRoute = serde_json
+        ::from_value(json!({ "path": "/api/*", "targets": targets.map(|url| json!({ "url": url })) }))
+        .unwrap();
+    let client_config = ProxyClientConfig { no_healthy_targets_retry_after: 45, ..Default::default() };
+    let handler = ProxyHandler::with_client_config(vec![route], client_config);
+    for target in targets {
+        handler.load_balancer("/api/*").unwrap().set_target_health(target, false);
+    }
+
+    let request = Request::builder().uri("/api/users").body(Body::empty()).unwrap();
+    let error = handler.handle_request(request, "127.0.0.1:50000".parse().unwrap()).await.unwrap().unwrap_err();
+    assert!(matches!(error, ProxyError::NoHealthyTargets { retry_after: 45 }), "Got {:?}", error);
+
+    let accept = HeaderValue::from_static("application/json");
+    let response = error.into_negotiated_response(Some(&accept));
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "45");
+    let body: serde_json::Value = serde_json::from_str(&body_text(response).await).unwrap();
+    assert_eq!(body["error"], "No healthy backend available");
+    assert_eq!(body["status"], 503);
+}
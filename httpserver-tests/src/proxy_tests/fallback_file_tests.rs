@@ -0,0 +1,78 @@
+// Route fallback file tests: a route whose targets are all down serves its static fallback page with a 503
+
+use httpserver_proxy::{ ProxyError, ProxyHandler };
+use httpserver_config::ProxyRoute;
+use axum::{ body::Body, http::{ Request, StatusCode, header } };
+use serde_json::json;
+use tempfile::TempDir;
+use crate::test_support::start_text_backend;
+
+/// Static directory holding "maintenance.html"
+fn create_static_dir() -> TempDir {
+    let static_dir = TempDir::new().unwrap();
+    std::fs::write(static_dir.path().join("maintenance.html"), "<h1>Back soon</h1>").unwrap();
+    static_dir
+}
+
+fn create_route(path: &str, target: &str) -> ProxyRoute {
+    serde_json::from_value(json!({
+        "path": path,
+        "targets": [{ "url": target }],
+        "fallback_file": "maintenance.html",
+    })).unwrap()
+}
+
+async fn send(handler: &ProxyHandler, uri: &str) -> Result<(StatusCode, String, String), ProxyError> {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = handler.handle_request(request, "127.0.0.1:50000".parse().unwrap()).await.unwrap()?;
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|value| value.to_str().unwrap().to_string())
+        .unwrap_or_default();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    Ok((status, content_type, String::from_utf8_lossy(&body).to_string()))
+}
+
+#[tokio::test]
+async fn test_unhealthy_route_serves_fallback_file() {
+    let static_dir = create_static_dir();
+    let backend = start_text_backend("backend").await;
+    let down = "http://127.0.0.1:9103";
+    let handler = ProxyHandler::new(vec![create_route("/api/*", &backend), create_route("/shop/*", down)])
+        .with_static_dir(static_dir.path().to_path_buf());
+    handler.load_balancer("/shop/*").unwrap().set_target_health(down, false);
+
+    let (status, content_type, body) = send(&handler, "/shop/cart").await.unwrap();
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(content_type.starts_with("text/html"), "{}", content_type);
+    assert_eq!(body, "<h1>Back soon</h1>");
+
+    // The healthy route still reaches its backend
+    let (status, _, body) = send(&handler, "/api/items").await.unwrap();
+    assert_eq!((status, body.as_str()), (StatusCode::OK, "backend"));
+}
+
+#[tokio::test]
+async fn test_fallback_response_keeps_retry_after() {
+    let static_dir = create_static_dir();
+    let down = "http://127.0.0.1:9104";
+    let handler = ProxyHandler::new(vec![create_route("/shop/*", down)]).with_static_dir(static_dir.path().to_path_buf());
+    handler.load_balancer("/shop/*").unwrap().set_target_health(down, false);
+
+    let request = Request::builder().uri("/shop/").body(Body::empty()).unwrap();
+    let response = handler.handle_request(request, "127.0.0.1:50000".parse().unwrap()).await.unwrap().unwrap();
+    assert_eq!(response.headers()[header::RETRY_AFTER], "30");
+}
+
+#[tokio::test]
+async fn test_missing_fallback_file_uses_builtin_error() {
+    let static_dir = TempDir::new().unwrap();
+    let down = "http://127.0.0.1:9105";
+    let handler = ProxyHandler::new(vec![create_route("/shop/*", down)]).with_static_dir(static_dir.path().to_path_buf());
+    handler.load_balancer("/shop/*").unwrap().set_target_health(down, false);
+
+    let error = send(&handler, "/shop/").await.unwrap_err();
+    assert!(matches!(error, ProxyError::NoHealthyTargets { .. }), "Got {:?}", error);
+}
@@ -0,0 +1,240 @@
+// Forwarding header tests: X-Forwarded-For chaining, X-Forwarded-Proto/Port, X-Real-IP and RFC 7239 Forwarded
+
+use httpserver_proxy::{ ClientOrigin, ProxyHandler };
+use httpserver_config::{ Config, ProxyRoute, LoadBalancingStrategy };
+use httpserver_core::ServerListener;
+use httpserver_engine::HttpServerEngine;
+use axum::{ Json, Router, body::Body, http::{ HeaderMap, Request }, routing::get };
+use serde_json::Value;
+use std::net::SocketAddr;
+use crate::test_support::serve;
+
+/// Backend that echoes the forwarding headers it received
+async fn start_echo_backend() -> u16 {
+    let backend = Router::new().route(
+        "/echo",
+        get(|headers: HeaderMap| async move {
+            let values = |name: &str| -> Vec<String> {
+                headers
+                    .get_all(name)
+                    .iter()
+                    .map(|value| value.to_str().unwrap().to_string())
+                    .collect()
+            };
+            Json(
+                serde_json::json!({
+                "x-forwarded-for": values("x-forwarded-for"),
+                "x-forwarded-proto": values("x-forwarded-proto"),
+                "x-forwarded-port": values("x-forwarded-port"),
+                "x-real-ip": values("x-real-ip"),
+                "forwarded": values("forwarded"),
+            })
+            )
+        })
+    );
+    serve(backend).await
+}
+
+fn create_route(port: u16, forwarded_headers: bool) -> ProxyRoute {
+    ProxyRoute {
+        path: "/api/*".to_string(),
+        target: Some(format!("http://127.0.0.1:{}", port)),
+        targets: vec![],
+        strategy: LoadBalancingStrategy::RoundRobin,
+        timeout: 5,
+        sticky_sessions: false,
+        http_health: None,
+        websocket_health: None,
+        tcp_health: None,
+        circuit_breaker: None,
+        middleware: None,
+        ssl: None,
+        client: None,
+        cache: None,
+        http2: false,
+        forwarded_headers,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
+    }
+}
+
+async fn proxy_echo(handler: &ProxyHandler, request: Request<Body>) -> Value {
+    let client_ip: SocketAddr = "192.0.2.10:50000".parse().unwrap();
+    let response = handler.handle_request(request, client_ip).await.unwrap().unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_forwarding_headers_are_added() {
+    let port = start_echo_backend().await;
+    let handler = ProxyHandler::new(vec![create_route(port, true)]);
+
+    let request = Request::builder()
+        .uri("/api/echo")
+        .header("host", "gateway.example.com")
+        .body(Body::empty())
+        .unwrap();
+    let seen = proxy_echo(&handler, request).await;
+
+    assert_eq!(seen["x-forwarded-for"], serde_json::json!(["192.0.2.10"]));
+    assert_eq!(seen["x-forwarded-proto"], serde_json::json!(["http"]));
+    assert_eq!(seen["x-forwarded-port"], serde_json::json!(["80"]));
+    assert_eq!(seen["x-real-ip"], serde_json::json!(["192.0.2.10"]));
+    assert_eq!(
+        seen["forwarded"],
+        serde_json::json!(["for=192.0.2.10;proto=http;host=\"gateway.example.com\""])
+    );
+}
+
+#[tokio::test]
+async fn test_forwarding_headers_chain_upstream_values() {
+    let port = start_echo_backend().await;
+    let handler = ProxyHandler::new(vec![create_route(port, true)]);
+
+    // An upstream proxy already added its hop
+    let request = Request::builder()
+        .uri("/api/echo")
+        .header("host", "gateway.example.com")
+        .header("x-forwarded-for", "203.0.113.5")
+        .header("x-real-ip", "203.0.113.5")
+        .header("forwarded", "for=203.0.113.5;proto=https")
+        .body(Body::empty())
+        .unwrap();
+    let seen = proxy_echo(&handler, request).await;
+
+    // Existing values are appended to, never replaced or duplicated
+    assert_eq!(seen["x-forwarded-for"], serde_json::json!(["203.0.113.5, 192.0.2.10"]));
+    assert_eq!(seen["x-forwarded-proto"], serde_json::json!(["http"]));
+    assert_eq!(seen["x-real-ip"], serde_json::json!(["192.0.2.10"]));
+    assert_eq!(
+        seen["forwarded"],
+        serde_json::json!([
+            "for=203.0.113.5;proto=https, for=192.0.2.10;proto=http;host=\"gateway.example.com\"",
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_forwarded_headers_can_be_disabled_per_route() {
+    let port = start_echo_backend().await;
+    let handler = ProxyHandler::new(vec![create_route(port, false)]);
+
+    let request = Request::builder()
+        .uri("/api/echo")
+        .header("x-forwarded-for", "203.0.113.5")
+        .body(Body::empty())
+        .unwrap();
+    let seen = proxy_echo(&handler, request).await;
+
+    assert_eq!(seen["x-forwarded-for"], serde_json::json!(["203.0.113.5, 192.0.2.10"]));
+    assert_eq!(seen["x-real-ip"], serde_json::json!([]));
+    assert_eq!(seen["forwarded"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_real_client_behind_trusted_proxy() {
+    let port = start_echo_backend().await;
+    let handler = ProxyHandler::new(vec![create_route(port, true)]);
+
+    // The load balancer at 10.0.0.5 reported the client in X-Forwarded-For
+    let mut request = Request::builder()
+        .uri("/api/echo")
+        .header("x-forwarded-for", "198.51.100.7")
+        .body(Body::empty())
+        .unwrap();
+    let peer: SocketAddr = "10.0.0.5:40000".parse().unwrap();
+    request.extensions_mut().insert(axum::extract::ConnectInfo(peer));
+
+    let client_ip: SocketAddr = "198.51.100.7:40000".parse().unwrap();
+    let response = handler.handle_request(request, client_ip).await.unwrap().unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let seen: Value = serde_json::from_slice(&body).unwrap();
+
+    // X-Forwarded-For records the hop we received it from; X-Real-IP names the client
+    assert_eq!(seen["x-forwarded-for"], serde_json::json!(["198.51.100.7, 10.0.0.5"]));
+    assert_eq!(seen["x-real-ip"], serde_json::json!(["198.51.100.7"]));
+}
+
+#[tokio::test]
+async fn test_tls_terminated_request_reports_client_scheme_and_port() {
+    let port = start_echo_backend().await;
+    let handler = ProxyHandler::new(vec![create_route(port, true)]);
+
+    // The client reached us over HTTPS on 443; the backend is plain HTTP
+    let mut request = Request::builder()
+        .uri("/api/echo")
+        .header("host", "gateway.example.com")
+        .header("x-forwarded-proto", "http")
+        .header("x-forwarded-port", "8080")
+        .body(Body::empty())
+        .unwrap();
+    request.extensions_mut().insert(ClientOrigin { https: true, port: 443 });
+    let seen = proxy_echo(&handler, request).await;
+
+    // Client-supplied values are replaced, not passed through
+    assert_eq!(seen["x-forwarded-proto"], serde_json::json!(["https"]));
+    assert_eq!(seen["x-forwarded-port"], serde_json::json!(["443"]));
+    assert_eq!(
+        seen["forwarded"],
+        serde_json::json!(["for=192.0.2.10;proto=https;host=\"gateway.example.com\""])
+    );
+}
+
+#[tokio::test]
+async fn test_forwarded_port_falls_back_to_host_header() {
+    let port = start_echo_backend().await;
+    let handler = ProxyHandler::new(vec![create_route(port, true)]);
+
+    let request = Request::builder()
+        .uri("/api/echo")
+        .header("host", "gateway.example.com:8080")
+        .body(Body::empty())
+        .unwrap();
+    let seen = proxy_echo(&handler, request).await;
+
+    assert_eq!(seen["x-forwarded-proto"], serde_json::json!(["http"]));
+    assert_eq!(seen["x-forwarded-port"], serde_json::json!(["8080"]));
+}
+
+#[tokio::test]
+async fn test_engine_forwards_https_listener_origin() {
+    let port = start_echo_backend().await;
+    let static_dir = tempfile::TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.static_config.directory = static_dir.path().to_path_buf();
+    config.proxy = vec![create_route(port, true)];
+    let router = HttpServerEngine::new(config, 0).unwrap().router().await.unwrap();
+
+    // The server marks requests from its HTTPS listener this way
+    let mut request = Request::builder()
+        .uri("/api/echo")
+        .header("host", "gateway.example.com")
+        .body(Body::empty())
+        .unwrap();
+    request.extensions_mut().insert(ServerListener { tls: true, port: 8443 });
+    let response = tower::ServiceExt::oneshot(router, request).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let seen: Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(seen["x-forwarded-proto"], serde_json::json!(["https"]));
+    assert_eq!(seen["x-forwarded-port"], serde_json::json!(["8443"]));
+}
@@ -0,0 +1,221 @@
+// gRPC proxying tests: HTTP/2 forwarding with streamed bodies and trailers
+
+use httpserver_proxy::ProxyHandler;
+use httpserver_config::{ ProxyRoute, LoadBalancingStrategy };
+use axum::body::{ Body, Bytes };
+use axum::http::{ HeaderMap, Request, Response, Version, header };
+use http_body_util::{ BodyExt, StreamBody, combinators::BoxBody };
+use hyper::body::{ Frame, Incoming };
+use hyper::service::service_fn;
+use hyper_util::rt::{ TokioExecutor, TokioIo };
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::time::{ Duration, Instant };
+use tokio::net::TcpListener;
+
+/// Encode a message with the gRPC length-prefixed framing
+fn grpc_frame(message: &[u8]) -> Bytes {
+    let mut frame = vec![0u8];
+    frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    frame.extend_from_slice(message);
+    Bytes::from(frame)
+}
+
+/// Decode all length-prefixed messages in a gRPC body
+fn grpc_messages(body: &[u8]) -> Vec<String> {
+    let mut messages = Vec::new();
+    let mut rest = body;
+    while rest.len() >= 5 {
+        let len = u32::from_be_bytes([rest[1], rest[2], rest[3], rest[4]]) as usize;
+        messages.push(String::from_utf8_lossy(&rest[5..5 + len]).to_string());
+        rest = &rest[5 + len..];
+    }
+    messages
+}
+
+/// Stream of response frames fed by the service task
+struct ReceiverStream(tokio::sync::mpsc::Receiver<Result<Frame<Bytes>, Infallible>>);
+
+impl ReceiverStream {
+    fn new(receiver: tokio::sync::mpsc::Receiver<Result<Frame<Bytes>, Infallible>>) -> Self {
+        Self(receiver)
+    }
+}
+
+impl futures_util::Stream for ReceiverStream {
+    type Item = Result<Frame<Bytes>, Infallible>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+fn grpc_trailers(status: &str) -> HeaderMap {
+    let mut trailers = HeaderMap::new();
+    trailers.insert("grpc-status", status.parse().unwrap());
+    trailers
+}
+
+/// gRPC service: unary Say greets the caller, server-streaming Count sends three messages
+async fn grpc_service(
+    request: Request<Incoming>
+) -> Result<Response<BoxBody<Bytes, Infallible>>, Infallible> {
+    assert_eq!(request.version(), Version::HTTP_2);
+    assert_eq!(request.headers()[header::CONTENT_TYPE], "application/grpc");
+    assert_eq!(request.headers()["te"], "trailers");
+
+    let path = request.uri().path().to_string();
+    let body = request.into_body().collect().await.unwrap().to_bytes();
+    let name = grpc_messages(&body).pop().unwrap_or_default();
+
+    let (tx, rx) = tokio::sync::mpsc::channel(4);
+    tokio::spawn(async move {
+        if path == "/echo.Echo/Say" {
+            let reply = format!("hello, {}", name);
+            let _ = tx.send(Ok(Frame::data(grpc_frame(reply.as_bytes())))).await;
+        } else {
+            for i in 1..=3 {
+                let reply = format!("{} #{}", name, i);
+                let _ = tx.send(Ok(Frame::data(grpc_frame(reply.as_bytes())))).await;
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+        }
+        let _ = tx.send(Ok(Frame::trailers(grpc_trailers("0")))).await;
+    });
+
+    let response = Response::builder()
+        .header(header::CONTENT_TYPE, "application/grpc")
+        .body(StreamBody::new(ReceiverStream::new(rx)).boxed())
+        .unwrap();
+    Ok(response)
+}
+
+/// Start a plaintext HTTP/2 (h2c) gRPC backend
+async fn start_grpc_backend() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let _ = hyper::server::conn::http2::Builder
+                    ::new(TokioExecutor::new())
+                    .serve_connection(TokioIo::new(stream), service_fn(grpc_service)).await;
+            });
+        }
+    });
+    port
+}
+
+fn create_grpc_route(port: u16) -> ProxyRoute {
+    ProxyRoute {
+        path: "/echo.Echo/*".to_string(),
+        target: Some(format!("http://127.0.0.1:{}/echo.Echo", port)),
+        targets: vec![],
+        strategy: LoadBalancingStrategy::RoundRobin,
+        timeout: 5,
+        sticky_sessions: false,
+        http_health: None,
+        websocket_health: None,
+        tcp_health: None,
+        circuit_breaker: None,
+        middleware: None,
+        ssl: None,
+        client: None,
+        cache: None,
+        http2: true,
+        forwarded_headers: true,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
+    }
+}
+
+fn grpc_request(method: &str, message: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/echo.Echo/{}", method))
+        .header(header::CONTENT_TYPE, "application/grpc")
+        .header("te", "trailers")
+        .body(Body::from(grpc_frame(message.as_bytes())))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_grpc_unary_call_through_proxy() {
+    let port = start_grpc_backend().await;
+    let handler = ProxyHandler::new(vec![create_grpc_route(port)]);
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+
+    let response = handler
+        .handle_request(grpc_request("Say", "gateway"), client_ip).await
+        .expect("Route should match")
+        .expect("gRPC call should succeed");
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/grpc");
+
+    let collected = response.into_body().collect().await.unwrap();
+    let trailers = collected.trailers().cloned().expect("gRPC trailers should be forwarded");
+    assert_eq!(trailers["grpc-status"], "0");
+    assert_eq!(grpc_messages(&collected.to_bytes()), vec!["hello, gateway"]);
+
+    // A second call reuses the multiplexed backend connection
+    let response = handler
+        .handle_request(grpc_request("Say", "again"), client_ip).await
+        .unwrap()
+        .unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(grpc_messages(&body), vec!["hello, again"]);
+}
+
+#[tokio::test]
+async fn test_grpc_server_streaming_is_not_buffered() {
+    let port = start_grpc_backend().await;
+    let handler = ProxyHandler::new(vec![create_grpc_route(port)]);
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+
+    let started = Instant::now();
+    let response = handler
+        .handle_request(grpc_request("Count", "tick"), client_ip).await
+        .expect("Route should match")
+        .expect("gRPC call should succeed");
+    let mut body = response.into_body();
+
+    // The first message arrives before the backend finishes the stream
+    let first = body.frame().await.unwrap().unwrap().into_data().unwrap();
+    assert_eq!(grpc_messages(&first), vec!["tick #1"]);
+    assert!(started.elapsed() < Duration::from_millis(400), "First message was buffered");
+
+    let mut messages = Vec::new();
+    let mut trailers = None;
+    while let Some(frame) = body.frame().await {
+        let frame = frame.unwrap();
+        if let Some(data) = frame.data_ref() {
+            messages.extend(grpc_messages(data));
+        } else {
+            trailers = frame.into_trailers().ok();
+        }
+    }
+    assert_eq!(messages, vec!["tick #2", "tick #3"]);
+    assert_eq!(trailers.expect("gRPC trailers should be forwarded")["grpc-status"], "0");
+}
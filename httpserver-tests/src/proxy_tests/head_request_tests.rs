@@ -0,0 +1,62 @@
+// HEAD request tests: proxied HEAD responses keep the backend's headers and never carry a body
+
+use httpserver_proxy::ProxyHandler;
+use httpserver_config::ProxyRoute;
+use axum::{ Router, body::Body, http::{ Request, StatusCode }, routing::get };
+use serde_json::json;
+use std::net::SocketAddr;
+use crate::test_support::serve_url;
+
+const PAYLOAD: &str = "{\"items\":[1,2,3,4,5]}";
+
+/// Backend serving GET /data (axum answers HEAD on it with the GET headers)
+async fn start_backend() -> String {
+    let app = Router::new().route(
+        "/data",
+        get(|| async { ([("content-type", "application/json"), ("x-backend", "primary")], PAYLOAD) })
+    );
+    serve_url(app).await
+}
+
+async fn send(handler: &ProxyHandler, method: &str) -> (StatusCode, axum::http::HeaderMap, usize) {
+    let request = Request::builder().method(method).uri("/api/data").body(Body::empty()).unwrap();
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+    let response = handler.handle_request(request, client_ip).await.unwrap().unwrap();
+    let (parts, body) = response.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+    (parts.status, parts.headers, body.len())
+}
+
+#[tokio::test]
+async fn test_head_keeps_headers_without_body() {
+    let route: ProxyRoute = serde_json::from_value(json!({ "path": "/api/*", "target": start_backend().await })).unwrap();
+    let handler = ProxyHandler::new(vec![route]);
+
+    let (status, headers, body_len) = send(&handler, "HEAD").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body_len, 0);
+    assert_eq!(headers["content-type"], "application/json");
+    assert_eq!(headers["x-backend"], "primary");
+
+    // Content-Length is what a GET returns
+    let (_, get_headers, get_body_len) = send(&handler, "GET").await;
+    assert_eq!(get_body_len, PAYLOAD.len());
+    assert_eq!(headers["content-length"], PAYLOAD.len().to_string().as_str());
+    assert_eq!(headers["content-length"], get_headers["content-length"]);
+}
+
+#[tokio::test]
+async fn test_head_is_not_subject_to_response_body_limit() {
+    let mut route: ProxyRoute = serde_json::from_value(json!({ "path": "/api/*", "target": start_backend().await })).unwrap();
+    route.max_response_body_bytes = Some(5);
+    let handler = ProxyHandler::new(vec![route]);
+
+    // No body is transferred, so the limit only applies to the GET
+    let (status, headers, body_len) = send(&handler, "HEAD").await;
+    assert_eq!((status, body_len), (StatusCode::OK, 0));
+    assert_eq!(headers["content-length"], PAYLOAD.len().to_string().as_str());
+
+    let request = Request::builder().uri("/api/data").body(Body::empty()).unwrap();
+    let result = handler.handle_request(request, "127.0.0.1:50000".parse().unwrap()).await.unwrap();
+    assert!(result.is_err());
+}
@@ -0,0 +1,131 @@
+// Forwarded header filtering tests: hop-by-hop stripping, Connection-named headers and per-route allow/deny lists
+
+use httpserver_proxy::ProxyHandler;
+use httpserver_config::ProxyRoute;
+use axum::{ Json, Router, body::Body, http::{ HeaderMap, Request, StatusCode }, response::IntoResponse, routing::get };
+use serde_json::{ Value, json };
+use std::net::SocketAddr;
+use crate::test_support::serve;
+
+/// Backend whose /echo returns the request headers it received as a JSON object, and whose
+/// /connection responds with a header its own Connection header marks as hop-by-hop
+async fn start_echo_backend() -> u16 {
+    let backend = Router::new()
+        .route(
+            "/echo",
+            get(|headers: HeaderMap| async move {
+                let received: serde_json::Map<String, Value> = headers
+                    .iter()
+                    .map(|(name, value)| (name.as_str().to_string(), json!(value.to_str().unwrap())))
+                    .collect();
+                Json(Value::Object(received))
+            })
+        )
+        .route(
+            "/connection",
+            get(|| async {
+                ([("connection", "x-backend-internal"), ("x-backend-internal", "1"), ("x-public", "1")], "ok").into_response()
+            })
+        );
+    serve(backend).await
+}
+
+/// Route to the echo backend with extra route settings merged in
+fn create_route(port: u16, settings: Value) -> ProxyRoute {
+    let mut route = json!({ "path": "/api/*", "target": format!("http://127.0.0.1:{}", port) });
+    route.as_object_mut().unwrap().extend(settings.as_object().unwrap().clone());
+    serde_json::from_value(route).unwrap()
+}
+
+/// Send /api/echo with the given headers and return the headers the backend saw
+async fn forwarded_headers(route: ProxyRoute, headers: &[(&str, &str)]) -> Value {
+    let handler = ProxyHandler::new(vec![route]);
+    let mut request = Request::builder().uri("/api/echo");
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let client_ip: SocketAddr = "203.0.113.7:40000".parse().unwrap();
+    let response = handler.handle_request(request.body(Body::empty()).unwrap(), client_ip).await.unwrap().unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_headers_named_by_connection_are_stripped() {
+    let port = start_echo_backend().await;
+    let received = forwarded_headers(create_route(port, json!({})), &[
+        ("connection", "keep-alive, X-Secret"),
+        ("keep-alive", "timeout=5"),
+        ("x-secret", "internal"),
+        ("x-visible", "yes"),
+    ]).await;
+
+    assert!(received.get("x-secret").is_none(), "X-Secret was named by Connection: {}", received);
+    assert!(received.get("keep-alive").is_none(), "{}", received);
+    assert_eq!(received["x-visible"], "yes");
+}
+
+#[tokio::test]
+async fn test_standard_hop_by_hop_headers_are_stripped() {
+    let port = start_echo_backend().await;
+    let received = forwarded_headers(create_route(port, json!({})), &[
+        ("proxy-authorization", "Basic Zm9vOmJhcg=="),
+        ("te", "gzip"),
+        ("trailer", "x-checksum"),
+        ("authorization", "Bearer end-to-end"),
+    ]).await;
+
+    for name in ["proxy-authorization", "te", "trailer"] {
+        assert!(received.get(name).is_none(), "{} should not be forwarded: {}", name, received);
+    }
+    assert_eq!(received["authorization"], "Bearer end-to-end");
+
+    // gRPC needs "TE: trailers" to reach the backend
+    let received = forwarded_headers(create_route(port, json!({})), &[("te", "trailers")]).await;
+    assert_eq!(received["te"], "trailers");
+}
+
+#[tokio::test]
+async fn test_allowlist_drops_unlisted_headers() {
+    let port = start_echo_backend().await;
+    let route = create_route(port, json!({ "header_allowlist": ["Accept", "X-Tenant"] }));
+    let received = forwarded_headers(route, &[
+        ("accept", "application/json"),
+        ("x-tenant", "acme"),
+        ("cookie", "session=secret"),
+        ("x-debug", "1"),
+    ]).await;
+
+    assert_eq!(received["accept"], "application/json");
+    assert_eq!(received["x-tenant"], "acme");
+    assert!(received.get("cookie").is_none(), "{}", received);
+    assert!(received.get("x-debug").is_none(), "{}", received);
+
+    // Headers the proxy sets itself are always sent
+    assert!(received.get("host").is_some(), "{}", received);
+    assert_eq!(received["x-forwarded-for"], "203.0.113.7");
+}
+
+#[tokio::test]
+async fn test_denylist_drops_listed_headers() {
+    let port = start_echo_backend().await;
+    let route = create_route(port, json!({ "header_denylist": ["Cookie"] }));
+    let received = forwarded_headers(route, &[("cookie", "session=secret"), ("x-debug", "1")]).await;
+
+    assert!(received.get("cookie").is_none(), "{}", received);
+    assert_eq!(received["x-debug"], "1");
+}
+
+#[tokio::test]
+async fn test_response_headers_named_by_connection_are_stripped() {
+    let port = start_echo_backend().await;
+    let handler = ProxyHandler::new(vec![create_route(port, json!({}))]);
+    let request = Request::builder().uri("/api/connection").body(Body::empty()).unwrap();
+    let response = handler.handle_request(request, "127.0.0.1:40000".parse().unwrap()).await.unwrap().unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("x-backend-internal").is_none());
+    assert!(response.headers().get("connection").is_none());
+    assert_eq!(response.headers()["x-public"], "1");
+}
@@ -128,6 +128,7 @@ fn test_health_summary_display() {
         healthy_targets: 5,
         unhealthy_targets: 0,
         monitoring_enabled: true,
+        targets: vec![],
     };
 
     let display1 = format!("{}", summary1);
@@ -139,6 +140,7 @@ fn test_health_summary_display() {
         healthy_targets: 1,
         unhealthy_targets: 2,
         monitoring_enabled: false,
+        targets: vec![],
     };
 
     let display2 = format!("{}", summary2);
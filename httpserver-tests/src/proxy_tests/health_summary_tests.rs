@@ -0,0 +1,114 @@
+// Health summary tests: per-target status, last check and failure counts across check types
+
+use httpserver_proxy::{ HealthCheckIntegration, ProxyHandler };
+use httpserver_config::{ LoadBalancingStrategy, ProxyRoute };
+use httpserver_balancer::{ LoadBalancer, Target };
+use serde_json::json;
+use std::sync::Arc;
+
+fn integration(urls: &[&str]) -> (Arc<LoadBalancer>, HealthCheckIntegration) {
+    let targets = urls.iter().map(|url| Target::new(url.to_string())).collect();
+    let load_balancer = Arc::new(LoadBalancer::new(targets, LoadBalancingStrategy::RoundRobin));
+    (load_balancer.clone(), HealthCheckIntegration::new(load_balancer))
+}
+
+#[test]
+fn test_summary_reports_mixed_target_health() {
+    let (load_balancer, integration) = integration(
+        &["http://localhost:5000", "http://localhost:5001", "http://localhost:5002", "http://localhost:5003"]
+    );
+
+    integration.record_check("http", "http://localhost:5000", true);
+    integration.record_check("http", "http://localhost:5001", false);
+    integration.record_check("http", "http://localhost:5001", false);
+    // One failing check type keeps the target out even while the other passes
+    integration.record_check("http", "http://localhost:5002", true);
+    integration.record_check("websocket", "http://localhost:5002", false);
+
+    let summary = integration.get_health_summary();
+    assert_eq!(summary.total_targets, 4);
+    assert_eq!(summary.healthy_targets, 2);
+    assert_eq!(summary.unhealthy_targets, 2);
+    assert_eq!(load_balancer.healthy_targets_count(), 2);
+
+    let mut report = summary.to_json();
+    for target in report["targets"].as_array_mut().unwrap() {
+        let last_check = target.as_object_mut().unwrap().remove("last_check").unwrap();
+        let checked = target["checks"].as_object().is_some_and(|checks| !checks.is_empty());
+        assert_eq!(last_check.is_u64(), checked, "last_check set only once checked: {}", target);
+    }
+    assert_eq!(
+        report,
+        json!({
+            "total_targets": 4,
+            "healthy_targets": 2,
+            "unhealthy_targets": 2,
+            "monitoring_enabled": false,
+            "targets": [
+                {
+                    "url": "http://localhost:5000",
+                    "status": "healthy",
+                    "consecutive_failures": 0,
+                    "checks": { "http": true }
+                },
+                {
+                    "url": "http://localhost:5001",
+                    "status": "unhealthy",
+                    "consecutive_failures": 2,
+                    "checks": { "http": false }
+                },
+                {
+                    "url": "http://localhost:5002",
+                    "status": "unhealthy",
+                    "consecutive_failures": 1,
+                    "checks": { "http": true, "websocket": false }
+                },
+                {
+                    "url": "http://localhost:5003",
+                    "status": "healthy",
+                    "consecutive_failures": 0,
+                    "checks": {}
+                }
+            ]
+        })
+    );
+}
+
+#[test]
+fn test_passing_check_resets_failures() {
+    let (load_balancer, integration) = integration(&["http://localhost:5000"]);
+
+    for _ in 0..3 {
+        integration.record_check("tcp", "http://localhost:5000", false);
+    }
+    assert_eq!(integration.get_health_summary().targets[0].consecutive_failures, 3);
+    assert_eq!(load_balancer.healthy_targets_count(), 0);
+
+    integration.record_check("tcp", "http://localhost:5000", true);
+    let target = &integration.get_health_summary().targets[0];
+    assert!(target.healthy);
+    assert_eq!(target.consecutive_failures, 0);
+    assert_eq!(load_balancer.healthy_targets_count(), 1);
+}
+
+#[test]
+fn test_proxy_handler_reports_every_route() {
+    let route = |path: &str, target: &str| -> ProxyRoute {
+        serde_json::from_value(json!({ "path": path, "target": target })).unwrap()
+    };
+    let handler = ProxyHandler::new(
+        vec![route("/api/*", "http://localhost:3000"), route("/admin/*", "http://localhost:3001")]
+    );
+    handler.load_balancer("/admin/*").unwrap().set_target_health("http://localhost:3001", false);
+
+    let report = handler.health_report();
+    let routes = report["routes"].as_array().unwrap();
+    assert_eq!(routes.len(), 2);
+    assert_eq!(routes[0]["path"], "/api/*");
+    assert_eq!(routes[0]["targets"][0]["status"], "healthy");
+    assert_eq!(routes[1]["path"], "/admin/*");
+    assert_eq!(routes[1]["unhealthy_targets"], 1);
+    assert_eq!(routes[1]["targets"][0]["url"], "http://localhost:3001");
+    assert_eq!(routes[1]["targets"][0]["status"], "unhealthy");
+    assert!(routes[1]["targets"][0]["last_check"].is_null());
+}
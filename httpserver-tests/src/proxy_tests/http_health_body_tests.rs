@@ -0,0 +1,58 @@
+// HTTP health body tests: a 200 response is only healthy when its body meets the configured expectation
+
+use httpserver_proxy::HttpHealthChecker;
+use httpserver_config::HttpHealthConfig;
+use axum::{ Router, routing::get };
+use crate::test_support::serve_url;
+
+/// Backend whose /health always answers 200 with `body`
+async fn start_backend(body: &'static str) -> String {
+    let app = Router::new().route("/health", get(move || async move { body }));
+    serve_url(app).await
+}
+
+fn health_config(contains: Option<&str>, json: Option<&str>) -> HttpHealthConfig {
+    HttpHealthConfig {
+        interval: 30,
+        timeout: 5,
+        path: "/health".to_string(),
+        expected_status_codes: vec![200],
+        expected_body_contains: contains.map(str::to_string),
+        expected_body_json: json.map(str::to_string),
+    }
+}
+
+#[tokio::test]
+async fn test_body_contains_check() {
+    let healthy = start_backend("all systems ok").await;
+    let degraded = start_backend("database unreachable").await;
+    let checker = HttpHealthChecker::new(health_config(Some("ok"), None));
+
+    assert!(checker.check_health(&healthy).await);
+    assert!(!checker.check_health(&degraded).await);
+}
+
+#[tokio::test]
+async fn test_body_json_field_check() {
+    let healthy = start_backend(r#"{"status":"ok","checks":{"db":"up","ready":true}}"#).await;
+    let degraded = start_backend(r#"{"status":"degraded","checks":{"db":"down","ready":false}}"#).await;
+    let not_json = start_backend("ok").await;
+
+    let checker = HttpHealthChecker::new(health_config(None, Some("status=ok")));
+    assert!(checker.check_health(&healthy).await);
+    assert!(!checker.check_health(&degraded).await);
+    assert!(!checker.check_health(&not_json).await);
+
+    // Nested fields and non-string values
+    let checker = HttpHealthChecker::new(health_config(None, Some("checks.ready=true")));
+    assert!(checker.check_health(&healthy).await);
+    assert!(!checker.check_health(&degraded).await);
+}
+
+#[tokio::test]
+async fn test_status_only_check_ignores_body() {
+    let backend = start_backend(r#"{"status":"degraded"}"#).await;
+    let checker = HttpHealthChecker::new(health_config(None, None));
+
+    assert!(checker.check_health(&backend).await);
+}
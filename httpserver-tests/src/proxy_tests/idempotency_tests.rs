@@ -0,0 +1,118 @@
+// Idempotency key tests: retried POSTs with the same key are forwarded once and replayed the first response
+
+use httpserver_proxy::{ ProxyHandler, idempotency::IDEMPOTENT_REPLAYED };
+use httpserver_config::ProxyRoute;
+use axum::{ Router, body::Body, http::{ Request, StatusCode }, routing::post };
+use serde_json::json;
+use std::sync::{ Arc, atomic::{ AtomicUsize, Ordering } };
+use tokio::time::Duration;
+use crate::test_support::serve;
+
+/// Backend numbering the POSTs it receives; "/slow" answers after a delay and "/fail" with a 500
+async fn start_backend() -> (String, Arc<AtomicUsize>) {
+    let count = Arc::new(AtomicUsize::new(0));
+    let counter = count.clone();
+    let app = Router::new().fallback(
+        post(move |req: axum::extract::Request| async move {
+            let number = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            match req.uri().path() {
+                "/slow" => tokio::time::sleep(Duration::from_millis(200)).await,
+                "/fail" => {
+                    return (StatusCode::INTERNAL_SERVER_ERROR, format!("failure {}", number));
+                }
+                _ => {}
+            }
+            (StatusCode::CREATED, format!("payment {}", number))
+        })
+    );
+    let port = serve(app).await;
+    (format!("http://127.0.0.1:{}", port), count)
+}
+
+fn create_handler(target: &str) -> ProxyHandler {
+    let route: ProxyRoute = serde_json::from_value(json!({
+        "path": "/payments/*",
+        "target": target,
+        "idempotency": { "ttl": 60 },
+    })).unwrap();
+    ProxyHandler::new(vec![route])
+}
+
+/// POST `path`, returning status, body and whether the response was replayed
+async fn send(handler: &ProxyHandler, path: &str, key: Option<&str>) -> (StatusCode, String, bool) {
+    let mut request = Request::builder().method("POST").uri(format!("/payments{}", path));
+    if let Some(key) = key {
+        request = request.header("idempotency-key", key);
+    }
+    let request = request.body(Body::from("{\"amount\":100}")).unwrap();
+    let response = handler.handle_request(request, "127.0.0.1:50000".parse().unwrap()).await.unwrap().unwrap();
+    let status = response.status();
+    let replayed = response.headers().contains_key(IDEMPOTENT_REPLAYED);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8_lossy(&body).to_string(), replayed)
+}
+
+#[tokio::test]
+async fn test_same_key_forwards_once() {
+    let (backend, count) = start_backend().await;
+    let handler = create_handler(&backend);
+
+    let first = send(&handler, "/charge", Some("order-1")).await;
+    let retry = send(&handler, "/charge", Some("order-1")).await;
+    assert_eq!(first, (StatusCode::CREATED, "payment 1".to_string(), false));
+    assert_eq!(retry, (StatusCode::CREATED, "payment 1".to_string(), true));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_different_keys_and_no_key_forward_separately() {
+    let (backend, count) = start_backend().await;
+    let handler = create_handler(&backend);
+
+    assert_eq!(send(&handler, "/charge", Some("order-1")).await.1, "payment 1");
+    assert_eq!(send(&handler, "/charge", Some("order-2")).await.1, "payment 2");
+    assert_eq!(send(&handler, "/charge", None).await.1, "payment 3");
+    assert_eq!(send(&handler, "/charge", None).await.1, "payment 4");
+    assert_eq!(count.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn test_concurrent_duplicate_waits_for_first_response() {
+    let (backend, count) = start_backend().await;
+    let handler = create_handler(&backend);
+
+    let (first, duplicate) = tokio::join!(
+        send(&handler, "/slow", Some("order-1")),
+        send(&handler, "/slow", Some("order-1"))
+    );
+    assert_eq!(first.1, "payment 1");
+    assert_eq!(duplicate.1, "payment 1");
+    assert!(first.2 != duplicate.2, "Exactly one response is a replay");
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_server_errors_are_not_replayed() {
+    let (backend, count) = start_backend().await;
+    let handler = create_handler(&backend);
+
+    assert_eq!(send(&handler, "/fail", Some("order-1")).await.1, "failure 1");
+    assert_eq!(send(&handler, "/fail", Some("order-1")).await.1, "failure 2");
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_responses_over_body_limit_are_not_replayed() {
+    let (backend, count) = start_backend().await;
+    let route: ProxyRoute = serde_json::from_value(json!({
+        "path": "/payments/*",
+        "target": backend,
+        "idempotency": { "ttl": 60, "max_body_bytes": 8 },
+    })).unwrap();
+    let handler = ProxyHandler::new(vec![route]);
+
+    // "payment 1" is 9 bytes: passed through whole, and the retry is forwarded again
+    assert_eq!(send(&handler, "/charge", Some("order-1")).await, (StatusCode::CREATED, "payment 1".to_string(), false));
+    assert_eq!(send(&handler, "/charge", Some("order-1")).await, (StatusCode::CREATED, "payment 2".to_string(), false));
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,144 @@
+// Loop detection tests: the hop counter header is incremented on each forward and capped at max_hops
+
+use httpserver_proxy::{ ProxyHandler, ProxyError, HOPS_HEADER };
+use httpserver_config::{ ProxyRoute, LoadBalancingStrategy, ProxyClientConfig };
+use axum::{
+    Router,
+    body::Body,
+    extract::ConnectInfo,
+    http::{ HeaderMap, Request, StatusCode },
+    response::IntoResponse,
+    routing::get,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use crate::test_support::serve;
+
+fn create_route(target: String) -> ProxyRoute {
+    ProxyRoute {
+        path: "/api/*".to_string(),
+        target: Some(target),
+        targets: vec![],
+        strategy: LoadBalancingStrategy::RoundRobin,
+        timeout: 30,
+        sticky_sessions: false,
+        http_health: None,
+        websocket_health: None,
+        tcp_health: None,
+        circuit_breaker: None,
+        middleware: None,
+        ssl: None,
+        client: None,
+        cache: None,
+        http2: false,
+        forwarded_headers: true,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
+    }
+}
+
+fn handler_with_max_hops(target: String, max_hops: u32) -> ProxyHandler {
+    let client_config = ProxyClientConfig { max_hops, ..Default::default() };
+    ProxyHandler::with_client_config(vec![create_route(target)], client_config)
+}
+
+/// Backend that echoes the hop count header it received
+async fn start_backend() -> u16 {
+    let app = Router::new().route(
+        "/hops",
+        get(|headers: HeaderMap| async move {
+            headers
+                .get(HOPS_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or("none")
+                .to_string()
+        })
+    );
+    serve(app).await
+}
+
+async fn get_hops(handler: &ProxyHandler, hops: Option<&str>) -> Result<String, ProxyError> {
+    let mut request = Request::builder().uri("/api/hops");
+    if let Some(hops) = hops {
+        request = request.header(HOPS_HEADER, hops);
+    }
+    let request = request.body(Body::empty()).unwrap();
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+
+    let response = handler.handle_request(request, client_ip).await.unwrap()?;
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    Ok(String::from_utf8_lossy(&body).to_string())
+}
+
+#[tokio::test]
+async fn test_forwarding_increments_hop_count() {
+    let port = start_backend().await;
+    let handler = ProxyHandler::new(vec![create_route(format!("http://127.0.0.1:{}", port))]);
+
+    assert_eq!(get_hops(&handler, None).await.unwrap(), "1");
+    assert_eq!(get_hops(&handler, Some("4")).await.unwrap(), "5");
+    // Garbage counts as a fresh request rather than being passed along
+    assert_eq!(get_hops(&handler, Some("many")).await.unwrap(), "1");
+}
+
+#[tokio::test]
+async fn test_request_at_hop_limit_is_rejected() {
+    let port = start_backend().await;
+    let handler = handler_with_max_hops(format!("http://127.0.0.1:{}", port), 5);
+
+    // One hop below the limit may still be forwarded once more
+    assert_eq!(get_hops(&handler, Some("4")).await.unwrap(), "5");
+
+    match get_hops(&handler, Some("5")).await {
+        Err(error @ ProxyError::LoopDetected(5)) => {
+            let response = error.into_response();
+            assert_eq!(response.status(), StatusCode::LOOP_DETECTED);
+        }
+        other => panic!("Expected LoopDetected, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_route_targeting_itself_ends_with_loop_detected() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handler = Arc::new(handler_with_max_hops(format!("http://127.0.0.1:{}/api", port), 3));
+
+    let app = Router::new().fallback(
+        move |ConnectInfo(client_ip): ConnectInfo<SocketAddr>, request: Request<Body>| {
+            let handler = handler.clone();
+            async move {
+                match handler.handle_request(request, client_ip).await {
+                    Some(Ok(response)) => response,
+                    Some(Err(error)) => error.into_response(),
+                    None => StatusCode::NOT_FOUND.into_response(),
+                }
+            }
+        }
+    );
+    tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap();
+    });
+
+    let response = reqwest::get(format!("http://127.0.0.1:{}/api/hops", port)).await.unwrap();
+    assert_eq!(response.status().as_u16(), 508);
+}
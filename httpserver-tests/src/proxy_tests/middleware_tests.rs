@@ -5,6 +5,8 @@ use httpserver_config::{
     MiddlewareConfig,
     HeaderMiddlewareConfig,
     RateLimitConfig,
+    RateLimitBackend,
+    RateLimitAlgorithm,
     TransformConfig,
     RequestTransformConfig,
     ResponseTransformConfig,
@@ -41,6 +43,9 @@ fn create_test_proxy_handler_with_middleware() -> ProxyHandler {
             max_concurrent: 5,
             limit_by_header: None,
             rate_limit_message: "Rate limit exceeded!".to_string(),
+            backend: RateLimitBackend::Memory,
+            redis_url: None,
+            algorithm: RateLimitAlgorithm::FixedWindow,
         }),
         transform: Some(TransformConfig {
             request: Some(RequestTransformConfig {
@@ -85,6 +90,7 @@ fn create_test_proxy_handler_with_middleware() -> ProxyHandler {
             threshold_bytes: 100,
             level: 6,
         }),
+        cors: None,
     };    let routes = vec![ProxyRoute {
         path: "/api/*".to_string(),
         targets: vec![Target::new("http://localhost:3000".to_string())],
@@ -94,9 +100,34 @@ fn create_test_proxy_handler_with_middleware() -> ProxyHandler {
         sticky_sessions: false,
         http_health: None,
         websocket_health: None,
+        tcp_health: None,
         circuit_breaker: None,
         middleware: Some(middleware_config),
         ssl: None,
+        client: None,
+        cache: None,
+        http2: false,
+        forwarded_headers: true,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
     }];
 
     ProxyHandler::new(routes)
@@ -151,6 +182,7 @@ async fn test_header_injection_middleware() {
         transform: None,
         auth: None,
         compression: None,
+        cors: None,
     };
 
     let req = Request::builder().method("GET").uri("/test").body(Body::empty()).unwrap();
@@ -181,6 +213,7 @@ async fn test_authentication_middleware() {
             api_key: None,
         }),
         compression: None,
+        cors: None,
     };
 
     let req = Request::builder().method("POST").uri("/api/test").body(Body::empty()).unwrap();
@@ -207,10 +240,14 @@ async fn test_rate_limiting_middleware() {
             max_concurrent: 10, // Higher limit to allow multiple requests
             limit_by_header: None,
             rate_limit_message: "Too many requests".to_string(),
+            backend: RateLimitBackend::Memory,
+            redis_url: None,
+            algorithm: RateLimitAlgorithm::FixedWindow,
         }),
         transform: None,
         auth: None,
         compression: None,
+        cors: None,
     };
 
     let req1 = Request::builder().method("GET").uri("/test").body(Body::empty()).unwrap();
@@ -259,6 +296,7 @@ async fn test_json_transformation_middleware() {
         }),
         auth: None,
         compression: None,
+        cors: None,
     };
 
     let json_body =
@@ -290,6 +328,127 @@ async fn test_json_transformation_middleware() {
     assert!(parsed_json.get("remove_me").is_none());
 }
 
+/// Run a JSON request body through add/remove transforms and return the forwarded body
+async fn transform_json_request(
+    body: String,
+    content_type: &str,
+    add_json_fields: HashMap<String, serde_json::Value>,
+    remove_json_fields: Vec<&str>
+) -> String {
+    let processor = create_middleware_processor();
+    let client_ip = SocketAddr::from_str("127.0.0.1:12345").unwrap();
+
+    let middleware_config = MiddlewareConfig {
+        headers: None,
+        rate_limit: None,
+        transform: Some(TransformConfig {
+            request: Some(RequestTransformConfig {
+                replace_text: vec![],
+                add_json_fields,
+                remove_json_fields: remove_json_fields.into_iter().map(String::from).collect(),
+            }),
+            response: None,
+        }),
+        auth: None,
+        compression: None,
+        cors: None,
+    };
+
+    let req = Request::builder()
+        .method("POST")
+        .uri("/api/test")
+        .header("content-type", content_type)
+        .body(Body::from(body))
+        .unwrap();
+
+    let processed_req = processor.process_request(req, &client_ip, &middleware_config).await.unwrap();
+    let body_bytes = axum::body::to_bytes(processed_req.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(body_bytes.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_json_nested_field_add() {
+    let add_fields = HashMap::from([
+        ("user.profile.email".to_string(), json!("a@example.com")),
+        ("meta.source".to_string(), json!("proxy")),
+    ]);
+    let body = json!({ "user": { "profile": { "name": "Ada" } } }).to_string();
+
+    let result = transform_json_request(body, "application/json", add_fields, vec![]).await;
+    let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+    assert_eq!(parsed["user"]["profile"]["email"], "a@example.com");
+    assert_eq!(parsed["user"]["profile"]["name"], "Ada");
+    // Missing intermediate objects are created
+    assert_eq!(parsed["meta"]["source"], "proxy");
+}
+
+#[tokio::test]
+async fn test_json_nested_field_remove() {
+    let body = json!({
+        "user": { "profile": { "name": "Ada", "ssn": "123" }, "ssn": "top" },
+    }).to_string();
+
+    let result = transform_json_request(
+        body,
+        "application/json; charset=utf-8",
+        HashMap::new(),
+        vec!["user.profile.ssn", "user.missing.field"]
+    ).await;
+    let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+    assert!(parsed["user"]["profile"].get("ssn").is_none());
+    assert_eq!(parsed["user"]["profile"]["name"], "Ada");
+    // Only the addressed field goes
+    assert_eq!(parsed["user"]["ssn"], "top");
+}
+
+#[tokio::test]
+async fn test_json_transform_maps_over_arrays() {
+    let add_fields = HashMap::from([("tenant".to_string(), json!("acme"))]);
+    let body = json!([
+        { "id": 1, "secret": "a", "tags": [{ "name": "x", "internal": true }] },
+        { "id": 2, "secret": "b", "tags": [] },
+    ]).to_string();
+
+    let result = transform_json_request(
+        body,
+        "application/vnd.api+json",
+        add_fields,
+        vec!["secret", "tags.internal"]
+    ).await;
+    let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+    for item in parsed.as_array().unwrap() {
+        assert_eq!(item["tenant"], "acme");
+        assert!(item.get("secret").is_none());
+    }
+    assert_eq!(parsed[0]["tags"][0], json!({ "name": "x" }));
+}
+
+#[tokio::test]
+async fn test_non_json_body_passes_through() {
+    let add_fields = HashMap::from([("added".to_string(), json!(true))]);
+
+    // Wrong content type: body is not touched
+    let result = transform_json_request(
+        "plain text body".to_string(),
+        "text/plain",
+        add_fields.clone(),
+        vec!["field"]
+    ).await;
+    assert_eq!(result, "plain text body");
+
+    // Claims to be JSON but is not: forwarded unchanged instead of failing the request
+    let result = transform_json_request(
+        "not { json".to_string(),
+        "application/json",
+        add_fields,
+        vec![]
+    ).await;
+    assert_eq!(result, "not { json");
+}
+
 #[tokio::test]
 async fn test_text_replacement_middleware() {
     let processor = create_middleware_processor();
@@ -312,6 +471,7 @@ async fn test_text_replacement_middleware() {
         }),
         auth: None,
         compression: None,
+        cors: None,
     };
 
     let req = Request::builder()
@@ -345,6 +505,7 @@ async fn test_compression_middleware() {
             threshold_bytes: 10, // Very low threshold for testing
             level: 6,
         }),
+        cors: None,
     };
 
     let large_body =
@@ -382,6 +543,9 @@ fn test_middleware_configuration_defaults() {
         max_concurrent: 10,
         limit_by_header: None,
         rate_limit_message: "Rate limit exceeded. Please try again later.".to_string(),
+        backend: RateLimitBackend::Memory,
+        redis_url: None,
+        algorithm: RateLimitAlgorithm::FixedWindow,
     };
 
     assert_eq!(rate_config.requests_per_minute, 100);
@@ -436,6 +600,7 @@ async fn test_header_removal_middleware() {
         transform: None,
         auth: None,
         compression: None,
+        cors: None,
     };
 
     let req = Request::builder()
@@ -472,6 +637,7 @@ async fn test_basic_auth_middleware() {
             api_key: None,
         }),
         compression: None,
+        cors: None,
     };
 
     let req = Request::builder().method("GET").uri("/test").body(Body::empty()).unwrap();
@@ -0,0 +1,141 @@
+// Traffic mirroring tests: sampled copies reach the mirror while clients only see the primary's response
+
+use httpserver_proxy::{ ProxyHandler, ProxyError, MAX_MIRRORS_IN_FLIGHT };
+use httpserver_config::{ MirrorConfig, ProxyRoute };
+use axum::{ Router, body::Body, http::{ Request, StatusCode, Uri } };
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::{ Arc, Mutex, atomic::{ AtomicUsize, Ordering } };
+use tokio::net::TcpListener;
+use tokio::time::{ sleep, Duration, Instant };
+use crate::test_support::serve_url;
+
+/// Requests a mirror backend received, as "METHOD path body"
+type Received = Arc<Mutex<Vec<String>>>;
+
+/// Mirror backend recording each request and failing it
+async fn start_mirror() -> (String, Received) {
+    let received = Received::default();
+    let recorded = received.clone();
+    let app = Router::new().fallback(move |method: axum::http::Method, uri: Uri, body: String| {
+        let recorded = recorded.clone();
+        async move {
+            recorded.lock().unwrap().push(format!("{} {} {}", method, uri.path(), body));
+            (StatusCode::INTERNAL_SERVER_ERROR, "mirror failed")
+        }
+    });
+    (serve_url(app).await, received)
+}
+
+/// Mirror backend counting requests and never answering them
+async fn start_stalled_mirror() -> (String, Arc<AtomicUsize>) {
+    let count = Arc::new(AtomicUsize::new(0));
+    let counter = count.clone();
+    let app = Router::new().fallback(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        std::future::pending::<()>()
+    });
+    (serve_url(app).await, count)
+}
+
+async fn create_handler(mirror_target: String, percentage: f64) -> ProxyHandler {
+    let primary = serve_url(Router::new().fallback(|body: String| async move { format!("primary {}", body) })).await;
+    let mut route: ProxyRoute = serde_json::from_value(json!({ "path": "/api/*", "target": primary })).unwrap();
+    route.mirror = Some(MirrorConfig { target: mirror_target, percentage });
+    ProxyHandler::new(vec![route])
+}
+
+async fn send(handler: &ProxyHandler, body: &str) -> (StatusCode, String) {
+    let request = Request::builder().method("POST").uri("/api/orders").body(Body::from(body.to_string())).unwrap();
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+    let response = handler.handle_request(request, client_ip).await.unwrap().unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8_lossy(&body).to_string())
+}
+
+/// What the mirror received once copies stop arriving
+async fn settled(received: &Received) -> Vec<String> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut seen = usize::MAX;
+    while received.lock().unwrap().len() != seen && Instant::now() < deadline {
+        seen = received.lock().unwrap().len();
+        sleep(Duration::from_millis(200)).await;
+    }
+    received.lock().unwrap().clone()
+}
+
+#[tokio::test]
+async fn test_mirror_receives_copy_and_client_gets_primary_response() {
+    let (mirror, received) = start_mirror().await;
+    let handler = create_handler(mirror, 100.0).await;
+
+    assert_eq!(send(&handler, "order-1").await, (StatusCode::OK, "primary order-1".to_string()));
+    assert_eq!(send(&handler, "order-2").await, (StatusCode::OK, "primary order-2".to_string()));
+
+    // Copies are sent concurrently, so they may arrive in either order
+    let mut mirrored = settled(&received).await;
+    mirrored.sort();
+    assert_eq!(mirrored, vec!["POST /orders order-1", "POST /orders order-2"]);
+}
+
+#[tokio::test]
+async fn test_unreachable_mirror_does_not_affect_client() {
+    let unused = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let mirror = format!("http://{}", unused.local_addr().unwrap());
+    drop(unused);
+    let handler = create_handler(mirror, 100.0).await;
+
+    for _ in 0..5 {
+        assert_eq!(send(&handler, "order").await, (StatusCode::OK, "primary order".to_string()));
+    }
+}
+
+#[tokio::test]
+async fn test_mirror_samples_configured_percentage() {
+    let (mirror, received) = start_mirror().await;
+    let handler = create_handler(mirror, 20.0).await;
+
+    for _ in 0..500 {
+        assert_eq!(send(&handler, "order").await.0, StatusCode::OK);
+    }
+
+    // 20% of 500 is 100; the bounds are about four standard deviations either side
+    let mirrored = settled(&received).await.len();
+    assert!((60..=140).contains(&mirrored), "Mirror received {} of 500 requests", mirrored);
+}
+
+#[tokio::test]
+async fn test_body_over_route_limit_is_not_mirrored() {
+    let (mirror, received) = start_mirror().await;
+    let primary = serve_url(Router::new().fallback(|| async { "primary" })).await;
+    let mut route: ProxyRoute = serde_json::from_value(json!({
+        "path": "/api/*",
+        "target": primary,
+        "max_request_body_bytes": 8,
+    })).unwrap();
+    route.mirror = Some(MirrorConfig { target: mirror, percentage: 100.0 });
+    let handler = ProxyHandler::new(vec![route]);
+
+    let request = Request::builder().method("POST").uri("/api/orders").body(Body::from("order-1234")).unwrap();
+    let result = handler.handle_request(request, "127.0.0.1:50000".parse().unwrap()).await;
+    assert!(matches!(result, Some(Err(ProxyError::PayloadTooLarge(_)))), "{:?}", result.map(|result| result.map(|_| ())));
+    assert!(settled(&received).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_saturated_mirror_drops_copies() {
+    let (mirror, count) = start_stalled_mirror().await;
+    let handler = create_handler(mirror, 100.0).await;
+
+    // Copies beyond the in-flight limit are dropped; the primary still answers every request
+    for _ in 0..MAX_MIRRORS_IN_FLIGHT + 10 {
+        assert_eq!(send(&handler, "order").await, (StatusCode::OK, "primary order".to_string()));
+    }
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while count.load(Ordering::SeqCst) < MAX_MIRRORS_IN_FLIGHT && Instant::now() < deadline {
+        sleep(Duration::from_millis(50)).await;
+    }
+    sleep(Duration::from_millis(200)).await;
+    assert_eq!(count.load(Ordering::SeqCst), MAX_MIRRORS_IN_FLIGHT);
+}
@@ -1,11 +1,42 @@
+pub mod access_log_tests;
+pub mod backend_tls_tests;
+pub mod body_integrity_tests;
+pub mod body_log_tests;
+pub mod canary_tests;
+pub mod chunked_streaming_tests;
+pub mod client_pool_tests;
+pub mod cors_preflight_tests;
+pub mod dynamic_route_tests;
+pub mod error_response_tests;
+pub mod fallback_file_tests;
+pub mod forwarded_headers_tests;
+pub mod grpc_tests;
+pub mod head_request_tests;
+pub mod header_filter_tests;
 pub mod health_check_integration;
+pub mod health_summary_tests;
+pub mod http_health_body_tests;
+pub mod idempotency_tests;
+pub mod incoming_auth_tests;
+pub mod loop_detection_tests;
 pub mod middleware_tests;
+pub mod mirror_tests;
 pub mod proxy_handler;
+pub mod readiness_tests;
 pub mod rate_limiting_tests;
+pub mod response_cache_tests;
+pub mod response_decompression_tests;
 pub mod route_matching;
+pub mod route_timeout_tests;
 pub mod sticky_session_integration;
+pub mod tcp_health_tests;
 pub mod websocket_advanced;
+pub mod websocket_close_tests;
 pub mod websocket_e2e;
+pub mod websocket_fragment_tests;
+pub mod websocket_health_tests;
+pub mod websocket_limit_tests;
 pub mod websocket_sticky_sessions;
 pub mod websocket_support;
 pub mod websocket_test_server;
+pub mod websocket_tls_tests;
@@ -1,7 +1,7 @@
-use httpserver_proxy::MiddlewareProcessor;
-use httpserver_config::{ MiddlewareConfig, RateLimitConfig };
+use httpserver_proxy::{ MiddlewareProcessor, MemoryRateLimitStore, RateLimitFuture, RateLimitStore };
+use httpserver_config::{ MiddlewareConfig, RateLimitConfig, RateLimitBackend, RateLimitAlgorithm };
 use axum::{ extract::Request, body::Body };
-use std::{ net::SocketAddr, str::FromStr, time::Duration };
+use std::{ net::SocketAddr, str::FromStr, sync::{ Arc, atomic::{ AtomicUsize, Ordering } }, time::Duration };
 use tokio::time::sleep;
 
 fn create_rate_limit_config(requests_per_minute: u32, max_concurrent: u32) -> MiddlewareConfig {
@@ -13,10 +13,14 @@ fn create_rate_limit_config(requests_per_minute: u32, max_concurrent: u32) -> Mi
             max_concurrent,
             limit_by_header: None,
             rate_limit_message: "Rate limit exceeded".to_string(),
+            backend: RateLimitBackend::Memory,
+            redis_url: None,
+            algorithm: RateLimitAlgorithm::FixedWindow,
         }),
         transform: None,
         auth: None,
         compression: None,
+        cors: None,
     }
 }
 
@@ -86,10 +90,14 @@ async fn test_rate_limit_window_reset() {
             max_concurrent: 5,
             limit_by_header: None,
             rate_limit_message: "Rate limit exceeded".to_string(),
+            backend: RateLimitBackend::Memory,
+            redis_url: None,
+            algorithm: RateLimitAlgorithm::FixedWindow,
         }),
         transform: None,
         auth: None,
         compression: None,
+        cors: None,
     };
 
     // First request should succeed
@@ -116,6 +124,9 @@ fn test_rate_limit_configuration_validation() {
         max_concurrent: 10,
         limit_by_header: Some("X-API-Key".to_string()),
         rate_limit_message: "Custom rate limit message".to_string(),
+        backend: RateLimitBackend::Memory,
+        redis_url: None,
+        algorithm: RateLimitAlgorithm::FixedWindow,
     };
 
     assert_eq!(config.requests_per_minute, 100);
@@ -138,10 +149,14 @@ async fn test_rate_limiting_with_custom_message() {
             max_concurrent: 5,
             limit_by_header: None,
             rate_limit_message: "Custom rate limit message!".to_string(),
+            backend: RateLimitBackend::Memory,
+            redis_url: None,
+            algorithm: RateLimitAlgorithm::FixedWindow,
         }),
         transform: None,
         auth: None,
         compression: None,
+        cors: None,
     };
 
     // First request should succeed
@@ -203,3 +218,108 @@ async fn test_zero_rate_limit() {
     let req2 = Request::builder().method("GET").uri("/test").body(Body::empty()).unwrap();
     assert!(processor.process_request(req2, &client_ip, &middleware_config).await.is_err());
 }
+
+/// Store standing in for Redis: one set of counters seen by several processors
+struct SharedStore {
+    inner: MemoryRateLimitStore,
+    calls: AtomicUsize,
+}
+
+impl RateLimitStore for SharedStore {
+    fn increment<'a>(
+        &'a self,
+        key: &'a str,
+        window: Duration,
+        algorithm: RateLimitAlgorithm
+    ) -> RateLimitFuture<'a> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        self.inner.increment(key, window, algorithm)
+    }
+}
+
+#[tokio::test]
+async fn test_rate_limit_shared_across_processors() {
+    let store = Arc::new(SharedStore { inner: MemoryRateLimitStore::new(), calls: AtomicUsize::new(0) });
+    let first = MiddlewareProcessor::new().with_rate_limit_store(store.clone());
+    let second = MiddlewareProcessor::new().with_rate_limit_store(store.clone());
+    let client_ip = SocketAddr::from_str("127.0.0.1:12345").unwrap();
+
+    let middleware_config = create_rate_limit_config(3, 10);
+    let request = || Request::builder().method("GET").uri("/test").body(Body::empty()).unwrap();
+
+    // Requests spread over two instances count against one limit
+    assert!(first.process_request(request(), &client_ip, &middleware_config).await.is_ok());
+    assert!(second.process_request(request(), &client_ip, &middleware_config).await.is_ok());
+    assert!(first.process_request(request(), &client_ip, &middleware_config).await.is_ok());
+    assert!(second.process_request(request(), &client_ip, &middleware_config).await.is_err());
+    assert!(first.process_request(request(), &client_ip, &middleware_config).await.is_err());
+    assert_eq!(store.calls.load(Ordering::SeqCst), 5);
+
+    // Separate in-memory processors keep their own counters
+    let local = MiddlewareProcessor::new();
+    assert!(local.process_request(request(), &client_ip, &middleware_config).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_unreachable_redis_allows_requests() {
+    // Nothing listens on port 1, so the store errors and the limiter fails open
+    let processor = MiddlewareProcessor::new();
+    let client_ip = SocketAddr::from_str("127.0.0.1:12345").unwrap();
+    let mut middleware_config = create_rate_limit_config(1, 10);
+    if let Some(rate_limit) = middleware_config.rate_limit.as_mut() {
+        rate_limit.backend = RateLimitBackend::Redis;
+        rate_limit.redis_url = Some("redis://127.0.0.1:1/0".to_string());
+    }
+
+    for _ in 0..3 {
+        let request = Request::builder().method("GET").uri("/test").body(Body::empty()).unwrap();
+        assert!(processor.process_request(request, &client_ip, &middleware_config).await.is_ok());
+    }
+}
+
+/// Send `count` requests and return how many were allowed
+async fn allowed_requests(
+    processor: &MiddlewareProcessor,
+    client_ip: &SocketAddr,
+    config: &MiddlewareConfig,
+    count: usize
+) -> usize {
+    let mut allowed = 0;
+    for _ in 0..count {
+        let request = Request::builder().method("GET").uri("/test").body(Body::empty()).unwrap();
+        if processor.process_request(request, client_ip, config).await.is_ok() {
+            allowed += 1;
+        }
+    }
+    allowed
+}
+
+#[tokio::test]
+async fn test_sliding_window_smooths_boundary_burst() {
+    let processor = MiddlewareProcessor::new();
+    let fixed_client = SocketAddr::from_str("127.0.0.1:12345").unwrap();
+    let sliding_client = SocketAddr::from_str("192.168.1.100:54321").unwrap();
+
+    let mut fixed_config = create_rate_limit_config(4, 100);
+    fixed_config.rate_limit.as_mut().unwrap().window_seconds = 1;
+    let mut sliding_config = fixed_config.clone();
+    sliding_config.rate_limit.as_mut().unwrap().algorithm = RateLimitAlgorithm::SlidingWindow;
+
+    // Sliding windows are aligned to the clock; start just after a second boundary
+    let millis = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().subsec_millis();
+    sleep(Duration::from_millis((1050 - (millis as u64)) % 1000)).await;
+
+    // One request opens the fixed window, the rest of the limit lands at the end of it
+    assert_eq!(allowed_requests(&processor, &fixed_client, &fixed_config, 1).await, 1);
+    assert_eq!(allowed_requests(&processor, &sliding_client, &sliding_config, 1).await, 1);
+    sleep(Duration::from_millis(800)).await;
+    assert_eq!(allowed_requests(&processor, &fixed_client, &fixed_config, 3).await, 3);
+    assert_eq!(allowed_requests(&processor, &sliding_client, &sliding_config, 3).await, 3);
+
+    // Just past the boundary the fixed window has reset and lets a second full burst through
+    sleep(Duration::from_millis(300)).await;
+    assert_eq!(allowed_requests(&processor, &fixed_client, &fixed_config, 4).await, 4);
+
+    // The sliding window still counts most of the previous burst
+    assert_eq!(allowed_requests(&processor, &sliding_client, &sliding_config, 4).await, 0);
+}
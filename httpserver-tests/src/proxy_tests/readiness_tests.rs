@@ -0,0 +1,108 @@
+// Readiness probe tests: /readyz reflects backend health, /livez only that the process is up
+
+use httpserver_core::{ create_probe_router, ReadinessCheck };
+use httpserver_proxy::ProxyHandler;
+use httpserver_config::{ ProxyRoute, LoadBalancingStrategy, Target };
+use axum::{ Router, body::Body, http::{ Request, StatusCode } };
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const BACKENDS: [&str; 2] = ["http://localhost:5000", "http://localhost:5001"];
+
+fn create_handler() -> Arc<ProxyHandler> {
+    let route = ProxyRoute {
+        path: "/api/*".to_string(),
+        target: None,
+        targets: BACKENDS.iter()
+            .map(|url| Target::new(url.to_string()))
+            .collect(),
+        strategy: LoadBalancingStrategy::RoundRobin,
+        timeout: 30,
+        sticky_sessions: false,
+        http_health: None,
+        websocket_health: None,
+        tcp_health: None,
+        circuit_breaker: None,
+        middleware: None,
+        ssl: None,
+        client: None,
+        cache: None,
+        http2: false,
+        forwarded_headers: true,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
+    };
+    Arc::new(ProxyHandler::new(vec![route]))
+}
+
+/// Probe router whose readiness follows the handler's backend health
+fn create_app(handler: Arc<ProxyHandler>) -> Router {
+    let check: ReadinessCheck = Arc::new(move || handler.unready_routes());
+    create_probe_router(vec![check])
+}
+
+async fn probe(app: &Router, path: &str) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_readyz_unavailable_when_all_backends_unhealthy() {
+    let handler = create_handler();
+    let balancer = handler.load_balancer("/api/*").unwrap();
+    for backend in BACKENDS {
+        balancer.set_target_health(backend, false);
+    }
+    let app = create_app(handler);
+
+    let (status, body) = probe(&app, "/readyz").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "not_ready");
+    assert_eq!(body["failures"][0], "/api/*");
+
+    // Liveness does not depend on backends
+    let (status, _) = probe(&app, "/livez").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_readyz_ok_with_one_healthy_backend() {
+    let handler = create_handler();
+    let balancer = handler.load_balancer("/api/*").unwrap();
+    balancer.set_target_health(BACKENDS[0], false);
+    let app = create_app(handler.clone());
+
+    let (status, body) = probe(&app, "/readyz").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ready");
+
+    // Losing the last healthy backend flips readiness
+    balancer.set_target_health(BACKENDS[1], false);
+    let (status, _) = probe(&app, "/readyz").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+    balancer.set_target_health(BACKENDS[1], true);
+    let (status, _) = probe(&app, "/readyz").await;
+    assert_eq!(status, StatusCode::OK);
+}
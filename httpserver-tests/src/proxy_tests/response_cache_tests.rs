@@ -0,0 +1,267 @@
+// Response cache tests: freshness, expiry, bypass and isolation rules for proxied requests
+
+use httpserver_proxy::ProxyHandler;
+use httpserver_config::{ ProxyRoute, LoadBalancingStrategy, CacheConfig };
+use axum::{
+    Router,
+    body::Body,
+    http::{ Method, Request, header },
+    response::{ IntoResponse, Response },
+    routing::get,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::time::Duration;
+use crate::test_support::serve;
+
+/// Handler returning the number of earlier backend hits, with an optional Cache-Control header
+fn counting_handler(
+    hits: Arc<AtomicUsize>,
+    cache_control: Option<&'static str>
+) -> impl Fn() -> std::future::Ready<Response> + Clone {
+    move || {
+        let body = hits.fetch_add(1, Ordering::SeqCst).to_string();
+        let response = match cache_control {
+            Some(value) => ([(header::CACHE_CONTROL, value)], body).into_response(),
+            None => body.into_response(),
+        };
+        std::future::ready(response)
+    }
+}
+
+/// Handler setting a session cookie on a response the backend marks as cacheable
+fn set_cookie_handler(hits: Arc<AtomicUsize>) -> impl Fn() -> std::future::Ready<Response> + Clone {
+    move || {
+        let session = format!("session={}", hits.fetch_add(1, Ordering::SeqCst));
+        let headers = [(header::CACHE_CONTROL, "public, max-age=60".to_string()), (header::SET_COOKIE, session)];
+        std::future::ready((headers, "private").into_response())
+    }
+}
+
+/// Handler returning a body bigger than the default cache body limit
+fn large_handler(hits: Arc<AtomicUsize>) -> impl Fn() -> std::future::Ready<Response> + Clone {
+    move || {
+        hits.fetch_add(1, Ordering::SeqCst);
+        std::future::ready(vec![b'x'; 2 * 1024 * 1024].into_response())
+    }
+}
+
+/// Backend that counts the requests it actually receives
+async fn start_backend() -> (u16, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let default_ttl = counting_handler(hits.clone(), None);
+    let app = Router::new()
+        .route("/fresh", get(counting_handler(hits.clone(), Some("public, max-age=60"))))
+        .route("/default-ttl", get(default_ttl.clone()).post(default_ttl))
+        .route("/no-store", get(counting_handler(hits.clone(), Some("no-store"))))
+        .route("/set-cookie", get(set_cookie_handler(hits.clone())))
+        .route("/large", get(large_handler(hits.clone())));
+
+    let port = serve(app).await;
+    (port, hits)
+}
+
+fn create_cached_route(port: u16, default_ttl: u64) -> ProxyRoute {
+    ProxyRoute {
+        path: "/api/*".to_string(),
+        target: Some(format!("http://127.0.0.1:{}", port)),
+        targets: vec![],
+        strategy: LoadBalancingStrategy::RoundRobin,
+        timeout: 5,
+        sticky_sessions: false,
+        http_health: None,
+        websocket_health: None,
+        tcp_health: None,
+        circuit_breaker: None,
+        middleware: None,
+        ssl: None,
+        client: None,
+        cache: Some(CacheConfig {
+            default_ttl,
+            ..Default::default()
+        }),
+        http2: false,
+        forwarded_headers: true,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
+    }
+}
+
+/// Send a request through the proxy, returning the X-Cache marker, Age and body
+async fn proxy_request(
+    handler: &ProxyHandler,
+    method: Method,
+    path: &str
+) -> (Option<String>, Option<String>, String) {
+    proxy_request_with_headers(handler, method, path, &[]).await
+}
+
+/// Send a request with extra headers through the proxy
+async fn proxy_request_with_headers(
+    handler: &ProxyHandler,
+    method: Method,
+    path: &str,
+    headers: &[(header::HeaderName, &str)]
+) -> (Option<String>, Option<String>, String) {
+    let mut request = Request::builder().method(method).uri(path);
+    for (name, value) in headers {
+        request = request.header(name, *value);
+    }
+    let request = request.body(Body::empty()).unwrap();
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+
+    let response = handler
+        .handle_request(request, client_ip).await
+        .expect("Route should match")
+        .expect("Proxy request should succeed");
+    let header_value = |name: &str| {
+        response.headers()
+            .get(name)
+            .map(|value| value.to_str().unwrap().to_string())
+    };
+    let (x_cache, age) = (header_value("x-cache"), header_value("age"));
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (x_cache, age, String::from_utf8_lossy(&body).to_string())
+}
+
+#[tokio::test]
+async fn test_fresh_response_is_served_from_cache() {
+    let (port, hits) = start_backend().await;
+    let handler = ProxyHandler::new(vec![create_cached_route(port, 1)]);
+
+    let (x_cache, age, first_body) = proxy_request(&handler, Method::GET, "/api/fresh").await;
+    assert_eq!(x_cache.as_deref(), Some("MISS"));
+    assert!(age.is_none());
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    // max-age=60 from the backend outlives the route's 1 second default
+    let (x_cache, age, second_body) = proxy_request(&handler, Method::GET, "/api/fresh").await;
+    assert_eq!(x_cache.as_deref(), Some("HIT"));
+    assert_eq!(age.as_deref(), Some("1"));
+    assert_eq!(first_body, second_body);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    // A different query string is a different cache key
+    let (x_cache, _, _) = proxy_request(&handler, Method::GET, "/api/fresh?page=2").await;
+    assert_eq!(x_cache.as_deref(), Some("MISS"));
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_expired_response_is_fetched_again() {
+    let (port, hits) = start_backend().await;
+    let handler = ProxyHandler::new(vec![create_cached_route(port, 1)]);
+
+    let (x_cache, _, _) = proxy_request(&handler, Method::GET, "/api/default-ttl").await;
+    assert_eq!(x_cache.as_deref(), Some("MISS"));
+    let (x_cache, _, _) = proxy_request(&handler, Method::GET, "/api/default-ttl").await;
+    assert_eq!(x_cache.as_deref(), Some("HIT"));
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    let (x_cache, _, body) = proxy_request(&handler, Method::GET, "/api/default-ttl").await;
+    assert_eq!(x_cache.as_deref(), Some("MISS"));
+    assert_eq!(body, "1");
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_no_store_response_bypasses_cache() {
+    let (port, hits) = start_backend().await;
+    let handler = ProxyHandler::new(vec![create_cached_route(port, 60)]);
+
+    for expected in ["0", "1"] {
+        let (x_cache, _, body) = proxy_request(&handler, Method::GET, "/api/no-store").await;
+        assert_eq!(x_cache.as_deref(), Some("MISS"));
+        assert_eq!(body, expected);
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_non_get_requests_bypass_cache() {
+    let (port, hits) = start_backend().await;
+    let handler = ProxyHandler::new(vec![create_cached_route(port, 60)]);
+
+    for _ in 0..2 {
+        let (x_cache, _, _) = proxy_request(&handler, Method::POST, "/api/default-ttl").await;
+        assert!(x_cache.is_none());
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_set_cookie_response_is_not_stored() {
+    let (port, hits) = start_backend().await;
+    let handler = ProxyHandler::new(vec![create_cached_route(port, 60)]);
+
+    for _ in 0..2 {
+        let (x_cache, _, _) = proxy_request(&handler, Method::GET, "/api/set-cookie").await;
+        assert_eq!(x_cache.as_deref(), Some("MISS"));
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_requests_with_cookies_bypass_cache() {
+    let (port, hits) = start_backend().await;
+    let handler = ProxyHandler::new(vec![create_cached_route(port, 60)]);
+
+    let cookie = [(header::COOKIE, "session=alice")];
+    for _ in 0..2 {
+        let (x_cache, _, _) = proxy_request_with_headers(&handler, Method::GET, "/api/fresh", &cookie).await;
+        assert!(x_cache.is_none());
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    // Nothing was stored for the cookie-bearing requests
+    let (x_cache, _, _) = proxy_request(&handler, Method::GET, "/api/fresh").await;
+    assert_eq!(x_cache.as_deref(), Some("MISS"));
+}
+
+#[tokio::test]
+async fn test_cache_key_includes_host() {
+    let (port, hits) = start_backend().await;
+    let handler = ProxyHandler::new(vec![create_cached_route(port, 60)]);
+
+    let (x_cache, _, _) = proxy_request_with_headers(&handler, Method::GET, "/api/fresh", &[(header::HOST, "a.example.com")]).await;
+    assert_eq!(x_cache.as_deref(), Some("MISS"));
+    let (x_cache, _, _) = proxy_request_with_headers(&handler, Method::GET, "/api/fresh", &[(header::HOST, "b.example.com")]).await;
+    assert_eq!(x_cache.as_deref(), Some("MISS"));
+    let (x_cache, _, _) = proxy_request_with_headers(&handler, Method::GET, "/api/fresh", &[(header::HOST, "A.example.com")]).await;
+    assert_eq!(x_cache.as_deref(), Some("HIT"));
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_bodies_over_limit_are_not_stored() {
+    let (port, hits) = start_backend().await;
+    let handler = ProxyHandler::new(vec![create_cached_route(port, 60)]);
+
+    for _ in 0..2 {
+        let (x_cache, _, body) = proxy_request(&handler, Method::GET, "/api/large").await;
+        assert_eq!(x_cache.as_deref(), Some("MISS"));
+        assert_eq!(body.len(), 2 * 1024 * 1024);
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,130 @@
+// Response decompression tests: encoded backend bodies are decoded for transforms and re-encoded only by compression
+
+use httpserver_proxy::{ ProxyHandler, middleware::MAX_DECODED_BODY_BYTES };
+use httpserver_config::ProxyRoute;
+use axum::{ Router, body::Body, http::{ HeaderMap, Request, StatusCode, header }, routing::get };
+use flate2::{ Compression, read::GzDecoder, write::GzEncoder };
+use serde_json::{ Value, json };
+use std::io::{ Read, Write };
+use std::net::SocketAddr;
+use crate::test_support::serve_url;
+
+const BACKEND_TEXT: &str = "Served by the internal backend at backend.internal";
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn gunzip(data: &[u8]) -> String {
+    let mut decoded = String::new();
+    GzDecoder::new(data).read_to_string(&mut decoded).unwrap();
+    decoded
+}
+
+/// Backend serving BACKEND_TEXT gzipped at /gzip and as an opaque "br" body at /br, plus a
+/// small gzip body at /bomb that decodes past MAX_DECODED_BODY_BYTES
+async fn start_backend() -> String {
+    let bomb = gzip(&vec![0; (MAX_DECODED_BODY_BYTES as usize) + 1]);
+    let app = Router::new()
+        .route(
+            "/gzip",
+            get(|| async {
+                ([(header::CONTENT_TYPE, "text/plain"), (header::CONTENT_ENCODING, "gzip")], gzip(BACKEND_TEXT.as_bytes()))
+            })
+        )
+        .route(
+            "/br",
+            get(|| async { ([(header::CONTENT_TYPE, "text/plain"), (header::CONTENT_ENCODING, "br")], "opaque brotli bytes") })
+        )
+        .route(
+            "/bomb",
+            get(|| async move { ([(header::CONTENT_TYPE, "text/plain"), (header::CONTENT_ENCODING, "gzip")], bomb) })
+        );
+    serve_url(app).await
+}
+
+/// Route to the backend with the given middleware section
+async fn create_handler(middleware: Value) -> ProxyHandler {
+    let route: ProxyRoute = serde_json
+        ::from_value(json!({ "path": "/api/*", "target": start_backend().await, "middleware": middleware }))
+        .unwrap();
+    ProxyHandler::new(vec![route])
+}
+
+/// Middleware replacing the backend host name in response bodies
+fn replace_host_transform() -> Value {
+    json!({
+        "transform": {
+            "response": {
+                "replace_text": [{ "find": "backend.internal", "replace": "example.com", "regex_enabled": false }]
+            }
+        }
+    })
+}
+
+async fn get_response(handler: &ProxyHandler, path: &str) -> (StatusCode, HeaderMap, Vec<u8>) {
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+    let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+    let response = handler.handle_request(request, client_ip).await.unwrap().unwrap();
+    let (parts, body) = response.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+    (parts.status, parts.headers, body.to_vec())
+}
+
+fn content_length(headers: &HeaderMap) -> usize {
+    headers[header::CONTENT_LENGTH].to_str().unwrap().parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_gzipped_response_is_decoded_for_transforms() {
+    let handler = create_handler(replace_host_transform()).await;
+    let (status, headers, body) = get_response(&handler, "/api/gzip").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(!headers.contains_key(header::CONTENT_ENCODING));
+    assert_eq!(String::from_utf8(body.clone()).unwrap(), "Served by the internal backend at example.com");
+    assert_eq!(content_length(&headers), body.len());
+}
+
+#[tokio::test]
+async fn test_transformed_response_is_recompressed_when_configured() {
+    let mut middleware = replace_host_transform();
+    middleware["compression"] = json!({ "gzip": true, "brotli": false, "threshold_bytes": 10, "level": 6 });
+    let handler = create_handler(middleware).await;
+    let (_, headers, body) = get_response(&handler, "/api/gzip").await;
+
+    assert_eq!(headers[header::CONTENT_ENCODING], "gzip");
+    assert_eq!(content_length(&headers), body.len());
+    assert_eq!(gunzip(&body), "Served by the internal backend at example.com");
+}
+
+#[tokio::test]
+async fn test_encoded_response_without_transforms_keeps_its_encoding() {
+    let handler = create_handler(json!({})).await;
+    let (_, headers, body) = get_response(&handler, "/api/gzip").await;
+
+    assert_eq!(headers[header::CONTENT_ENCODING], "gzip");
+    assert_eq!(gunzip(&body), BACKEND_TEXT);
+}
+
+#[tokio::test]
+async fn test_unsupported_encoding_skips_transforms() {
+    let handler = create_handler(replace_host_transform()).await;
+    let (status, headers, body) = get_response(&handler, "/api/br").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers[header::CONTENT_ENCODING], "br");
+    assert_eq!(body, b"opaque brotli bytes");
+}
+
+#[tokio::test]
+async fn test_oversized_decoded_body_is_bad_gateway() {
+    let handler = create_handler(replace_host_transform()).await;
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+    let request = Request::builder().uri("/api/bomb").body(Body::empty()).unwrap();
+
+    let error = handler.handle_request(request, client_ip).await.unwrap().unwrap_err();
+    assert_eq!(error.into_negotiated_response(None).status(), StatusCode::BAD_GATEWAY);
+}
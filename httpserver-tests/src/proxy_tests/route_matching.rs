@@ -1,5 +1,16 @@
-use httpserver_proxy::RouteMatcher;
+use httpserver_proxy::{ ProxyHandler, RouteMatcher };
 use httpserver_config::{ ProxyRoute, LoadBalancingStrategy };
+use axum::body::Body;
+use std::net::SocketAddr;
+use crate::test_support::start_text_backend;
+
+fn create_regex_route(path: &str, host: Option<&str>, target: &str) -> ProxyRoute {
+    ProxyRoute {
+        host: host.map(str::to_string),
+        match_regex: true,
+        ..create_test_route(path, target)
+    }
+}
 
 fn create_test_route(path: &str, target: &str) -> ProxyRoute {
     ProxyRoute {
@@ -11,9 +22,34 @@ fn create_test_route(path: &str, target: &str) -> ProxyRoute {
         sticky_sessions: false,
         http_health: None,
         websocket_health: None,
+        tcp_health: None,
         circuit_breaker: None,
         middleware: None,
         ssl: None,
+        client: None,
+        cache: None,
+        http2: false,
+        forwarded_headers: true,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
     }
 }
 
@@ -129,6 +165,116 @@ fn test_global_wildcard() {
     assert!(result.is_wildcard);
 }
 
+#[test]
+fn test_literal_then_wildcard_then_regex_precedence() {
+    // Listed in reverse precedence order; config order must not decide between kinds
+    let routes = vec![
+        create_regex_route("/api/v[0-9]+/.*", None, "http://localhost:3002"),
+        create_test_route("/api/*", "http://localhost:3001"),
+        create_test_route("/api/v1/health", "http://localhost:3000"),
+        create_regex_route("/reports/[0-9]+", None, "http://localhost:3003"),
+        create_regex_route("/reports/.*", None, "http://localhost:3004")
+    ];
+
+    let matcher = RouteMatcher::new(routes);
+
+    let result = matcher.find_match("/api/v1/health").unwrap();
+    assert_eq!(result.route.target, Some("http://localhost:3000".to_string()));
+
+    let result = matcher.find_match("/api/v1/users").unwrap();
+    assert_eq!(result.route.target, Some("http://localhost:3001".to_string()));
+    assert_eq!(result.stripped_path, "/v1/users");
+
+    // Regexes are anchored and forward the full path; the first one in config order wins
+    let result = matcher.find_match("/reports/42").unwrap();
+    assert_eq!(result.route.target, Some("http://localhost:3003".to_string()));
+    assert_eq!(result.stripped_path, "/reports/42");
+    assert!(!result.is_wildcard);
+
+    let result = matcher.find_match("/reports/latest").unwrap();
+    assert_eq!(result.route.target, Some("http://localhost:3004".to_string()));
+    assert!(matcher.find_match("/old/reports/42").is_none());
+}
+
+#[test]
+fn test_host_matching() {
+    let mut admin = create_test_route("/admin/*", "http://localhost:3001");
+    admin.host = Some("Admin.Example.com".to_string());
+    let routes = vec![
+        create_regex_route("/shop/.*", Some("(eu|us)\\.example\\.com"), "http://localhost:3000"),
+        admin,
+        create_test_route("/docs/*", "http://localhost:3002")
+    ];
+
+    let matcher = RouteMatcher::new(routes);
+
+    // Hosts are compared without their port and case
+    let result = matcher.find_host_match(Some("eu.example.com:8443"), "/shop/cart").unwrap();
+    assert_eq!(result.route.target, Some("http://localhost:3000".to_string()));
+    assert!(matcher.find_host_match(Some("asia.example.com"), "/shop/cart").is_none());
+    assert!(matcher.find_host_match(Some("xeu.example.com"), "/shop/cart").is_none());
+
+    let result = matcher.find_host_match(Some("admin.example.com"), "/admin/users").unwrap();
+    assert_eq!(result.route.target, Some("http://localhost:3001".to_string()));
+    assert!(matcher.find_host_match(Some("www.example.com"), "/admin/users").is_none());
+
+    // Routes limited to a host never match when the host is unknown
+    assert!(matcher.find_match("/admin/users").is_none());
+    assert!(matcher.find_host_match(Some("www.example.com"), "/docs/intro").is_some());
+}
+
+#[test]
+fn test_request_route_uses_host_header() {
+    let mut admin = create_test_route("/admin/*", "http://localhost:3001");
+    admin.host = Some("admin.example.com".to_string());
+    let handler = ProxyHandler::new(vec![admin]);
+
+    let request = axum::http::Request::builder()
+        .uri("/admin/users")
+        .header("host", "admin.example.com")
+        .body(())
+        .unwrap();
+    assert!(handler.find_request_route(&request).is_some());
+
+    let request = axum::http::Request::builder()
+        .uri("/admin/users")
+        .header("host", "www.example.com")
+        .body(())
+        .unwrap();
+    assert!(handler.find_request_route(&request).is_none());
+}
+
+#[tokio::test]
+async fn test_same_path_on_two_hosts_reaches_each_hosts_backend() {
+    let mut shop = create_test_route("/api/*", &start_text_backend("shop backend").await);
+    shop.host = Some("shop.example.com".to_string());
+    let mut blog = create_test_route("/api/*", &start_text_backend("blog backend").await);
+    blog.host = Some("blog.example.com".to_string());
+    let handler = ProxyHandler::new(vec![shop, blog]);
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+
+    for (host, expected) in [("shop.example.com", "shop backend"), ("blog.example.com", "blog backend")] {
+        let request = axum::http::Request::builder()
+            .uri("/api/items")
+            .header("host", host)
+            .body(Body::empty())
+            .unwrap();
+        let response = handler.handle_request(request, client_ip).await.unwrap().unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, expected);
+    }
+
+    // Each route keeps its own counters
+    let stats = handler.stats_report();
+    let requests: Vec<_> = stats["routes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|route| (route["host"].as_str().unwrap(), route["requests"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(requests, [("shop.example.com", 1), ("blog.example.com", 1)]);
+}
+
 #[test]
 fn test_empty_routes() {
     let routes = vec![];
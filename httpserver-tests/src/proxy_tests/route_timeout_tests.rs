@@ -0,0 +1,146 @@
+// Route timeout tests: connect and read timeouts fail stalled backends without cutting off slow streams
+
+use httpserver_proxy::{ ProxyError, ProxyHandler };
+use httpserver_config::ProxyRoute;
+use axum::{ Router, body::Body, http::{ Request, Response, StatusCode }, routing::get };
+use futures_util::stream;
+use serde_json::{ Value, json };
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::time::{ Duration, Instant };
+use crate::test_support::serve_url;
+
+/// Body sending `chunks` pieces of "chunk\n", waiting `delay` before each
+fn delayed_chunks(chunks: usize, delay: Duration) -> Body {
+    Body::from_stream(
+        stream::unfold(0, move |sent| async move {
+            if sent == chunks {
+                return None;
+            }
+            tokio::time::sleep(delay).await;
+            Some((Ok::<_, Infallible>("chunk\n"), sent + 1))
+        })
+    )
+}
+
+/// Backend with a /steady stream (a chunk every 400ms for 2s), a /stalled stream (one chunk, then
+/// 5s of silence) and a /silent endpoint that waits 5s before sending headers
+async fn start_backend() -> String {
+    let app = Router::new()
+        .route("/steady", get(|| async { Response::new(delayed_chunks(5, Duration::from_millis(400))) }))
+        .route(
+            "/stalled",
+            get(|| async {
+                let first = stream::once(async { Ok::<_, Infallible>("chunk\n".to_string()) });
+                let rest = stream::once(async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Ok::<_, Infallible>("late\n".to_string())
+                });
+                Response::new(Body::from_stream(futures_util::StreamExt::chain(first, rest)))
+            })
+        )
+        .route(
+            "/silent",
+            get(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                "too late"
+            })
+        );
+    serve_url(app).await
+}
+
+/// Backend that is up but never accepts, with its accept queue already full so new connection
+/// attempts hang; the returned listener and connections must be kept alive
+async fn start_unresponsive_backend() -> (String, tokio::net::TcpListener, Vec<tokio::net::TcpStream>) {
+    let socket = tokio::net::TcpSocket::new_v4().unwrap();
+    socket.bind("127.0.0.1:0".parse().unwrap()).unwrap();
+    let listener = socket.listen(0).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mut queued = Vec::new();
+    while
+        let Ok(Ok(stream)) = tokio::time::timeout(
+            Duration::from_millis(200),
+            tokio::net::TcpStream::connect(addr)
+        ).await
+    {
+        queued.push(stream);
+    }
+    (format!("http://{}", addr), listener, queued)
+}
+
+fn create_handler(route: Value) -> ProxyHandler {
+    let route: ProxyRoute = serde_json::from_value(route).unwrap();
+    route.validate(0).unwrap();
+    ProxyHandler::new(vec![route])
+}
+
+/// Proxy GET `path`, returning the outcome and how long it took
+async fn timed_get(handler: &ProxyHandler, path: &str) -> (Result<(StatusCode, String), ProxyError>, Duration) {
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+    let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+    let started = Instant::now();
+    let result = match handler.handle_request(request, client_ip).await.unwrap() {
+        Ok(response) => {
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            Ok((status, String::from_utf8_lossy(&body).to_string()))
+        }
+        Err(error) => Err(error),
+    };
+    (result, started.elapsed())
+}
+
+#[tokio::test]
+async fn test_down_backend_fails_at_connect_timeout() {
+    let (target, _listener, _queued) = start_unresponsive_backend().await;
+    let handler = create_handler(json!({ "path": "/api/*", "target": target, "timeout": 30, "connect_timeout": 1 }));
+
+    let (result, elapsed) = timed_get(&handler, "/api/anything").await;
+    assert!(matches!(result, Err(ProxyError::Timeout(1))), "Expected a connect timeout, got {:?}", result);
+    assert!(elapsed < Duration::from_secs(3), "Connect should give up after 1s, took {:?}", elapsed);
+}
+
+#[tokio::test]
+async fn test_slow_but_progressing_backend_is_not_cut_off() {
+    let target = start_backend().await;
+    let handler = create_handler(json!({ "path": "/api/*", "target": target, "timeout": 10, "read_timeout": 1 }));
+
+    let (result, elapsed) = timed_get(&handler, "/api/steady").await;
+    assert_eq!(result.unwrap(), (StatusCode::OK, "chunk\n".repeat(5)));
+    assert!(elapsed >= Duration::from_secs(2), "The whole stream should arrive, took {:?}", elapsed);
+}
+
+#[tokio::test]
+async fn test_stalled_backend_fails_at_read_timeout() {
+    let target = start_backend().await;
+    let handler = create_handler(
+        json!({ "path": "/api/*", "target": target, "timeout": 30, "connect_timeout": 1, "read_timeout": 1 })
+    );
+
+    let (result, elapsed) = timed_get(&handler, "/api/silent").await;
+    assert!(matches!(result, Err(ProxyError::Timeout(1))), "{:?}", result);
+    assert!(elapsed < Duration::from_secs(4), "Should time out well before 5s, took {:?}", elapsed);
+
+    // A streamed body that stalls part way is aborted after the chunks already relayed
+    let client_ip: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+    let request = Request::builder().uri("/api/stalled").body(Body::empty()).unwrap();
+    let started = Instant::now();
+    let response = handler.handle_request(request, client_ip).await.unwrap().unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut body = response.into_body().into_data_stream();
+    assert_eq!(futures_util::StreamExt::next(&mut body).await.unwrap().unwrap(), "chunk\n");
+    assert!(futures_util::StreamExt::next(&mut body).await.unwrap().is_err());
+    assert!(started.elapsed() < Duration::from_secs(4), "Should time out well before 5s, took {:?}", started.elapsed());
+}
+
+#[test]
+fn test_zero_connect_and_read_timeouts_rejected() {
+    for field in ["connect_timeout", "read_timeout"] {
+        let mut route = json!({ "path": "/api/*", "target": "http://localhost:3000" });
+        route[field] = json!(0);
+        let route: ProxyRoute = serde_json::from_value(route).unwrap();
+        let error = route.validate(0).unwrap_err().to_string();
+        assert!(error.contains(&format!("{} must be greater than 0", field)), "{}", error);
+    }
+}
@@ -76,9 +76,34 @@ fn test_proxy_handler_sticky_sessions() {
         sticky_sessions: true,
         http_health: None,
         websocket_health: None,
+        tcp_health: None,
         circuit_breaker: None,
         middleware: None,
         ssl: None,
+        client: None,
+        cache: None,
+        http2: false,
+        forwarded_headers: true,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
     }];
 
     let handler = ProxyHandler::new(routes);
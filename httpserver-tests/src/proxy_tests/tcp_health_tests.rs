@@ -0,0 +1,83 @@
+// TCP health check tests: targets are healthy when their host:port accepts a connection
+
+use httpserver_proxy::{ HealthCheckIntegration, TcpHealthChecker };
+use httpserver_config::{ ProxyRoute, TcpHealthConfig };
+use httpserver_balancer::{ LoadBalancer, LoadBalancingStrategy, Target };
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+/// Listening port that accepts connections for the rest of the test
+async fn open_port() -> (u16, TcpListener) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    (listener.local_addr().unwrap().port(), listener)
+}
+
+/// Port that was just released, so connections are refused
+async fn closed_port() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+fn tcp_config() -> TcpHealthConfig {
+    TcpHealthConfig { interval: 1, timeout: 2 }
+}
+
+#[tokio::test]
+async fn test_reachable_port_is_healthy() {
+    let (port, _listener) = open_port().await;
+    let checker = TcpHealthChecker::new(tcp_config());
+
+    assert!(checker.check_health(&format!("http://127.0.0.1:{}", port)).await);
+}
+
+#[tokio::test]
+async fn test_closed_port_is_unhealthy() {
+    let port = closed_port().await;
+    let checker = TcpHealthChecker::new(tcp_config());
+
+    assert!(!checker.check_health(&format!("http://127.0.0.1:{}", port)).await);
+    assert!(!checker.check_health("not a url").await);
+}
+
+#[tokio::test]
+async fn test_monitoring_updates_balancer_health() {
+    let (open, _listener) = open_port().await;
+    let closed = closed_port().await;
+    let open_url = format!("http://127.0.0.1:{}", open);
+    let closed_url = format!("http://127.0.0.1:{}", closed);
+    let balancer = Arc::new(
+        LoadBalancer::new(
+            vec![Target::new(open_url.clone()), Target::new(closed_url.clone())],
+            LoadBalancingStrategy::RoundRobin
+        )
+    );
+
+    let mut integration = HealthCheckIntegration::new(balancer.clone());
+    integration.start_tcp_health_monitoring(tcp_config()).await.unwrap();
+
+    // The first round of checks runs immediately
+    for _ in 0..50 {
+        if balancer.healthy_targets_count() == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(balancer.healthy_targets_count(), 1);
+    assert_eq!(balancer.select_target().unwrap().url, open_url);
+    assert!(integration.get_health_summary().monitoring_enabled);
+}
+
+#[test]
+fn test_tcp_health_selectable_in_route_config() {
+    let route: ProxyRoute = serde_json::from_value(json!({
+        "path": "/db/*",
+        "target": "http://127.0.0.1:5432",
+        "tcp_health": { "timeout": 2 }
+    })).unwrap();
+
+    let tcp_health = route.tcp_health.unwrap();
+    assert_eq!(tcp_health.timeout, 2);
+    assert_eq!(tcp_health.interval, TcpHealthConfig::default().interval);
+}
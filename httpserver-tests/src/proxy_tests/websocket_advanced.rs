@@ -18,9 +18,34 @@ fn create_comprehensive_websocket_handler() -> ProxyHandler {
             sticky_sessions: false,
             http_health: None,
             websocket_health: None,
+            tcp_health: None,
             circuit_breaker: None,
             middleware: None,
             ssl: None,
+            client: None,
+            cache: None,
+            http2: false,
+            forwarded_headers: true,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            websocket_limits: None,
+            log_bodies: None,
+            canary: None,
+            mirror: None,
+            host: None,
+            match_regex: false,
+            body_integrity: None,
+            connect_timeout: None,
+            read_timeout: None,
+            header_allowlist: None,
+            header_denylist: Vec::new(),
+            access_log: true,
+            access_log_format: None,
+            startup_check: None,
+            fallback_file: None,
+            idempotency: None,
+            jwt_auth: None,
+            forward_auth: None,
         },        // WebSocket notifications with round-robin
         ProxyRoute {
             path: "/ws/notifications/*".to_string(),
@@ -34,9 +59,34 @@ fn create_comprehensive_websocket_handler() -> ProxyHandler {
             sticky_sessions: false,
             http_health: None,
             websocket_health: None,
+            tcp_health: None,
             circuit_breaker: None,
             middleware: None,
             ssl: None,
+            client: None,
+            cache: None,
+            http2: false,
+            forwarded_headers: true,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            websocket_limits: None,
+            log_bodies: None,
+            canary: None,
+            mirror: None,
+            host: None,
+            match_regex: false,
+            body_integrity: None,
+            connect_timeout: None,
+            read_timeout: None,
+            header_allowlist: None,
+            header_denylist: Vec::new(),
+            access_log: true,
+            access_log_format: None,
+            startup_check: None,
+            fallback_file: None,
+            idempotency: None,
+            jwt_auth: None,
+            forward_auth: None,
         },        // Weighted WebSocket route
         ProxyRoute {
             path: "/ws/realtime/*".to_string(),
@@ -51,9 +101,34 @@ fn create_comprehensive_websocket_handler() -> ProxyHandler {
             sticky_sessions: false,
             http_health: None,
             websocket_health: None,
+            tcp_health: None,
             circuit_breaker: None,
             middleware: None,
             ssl: None,
+            client: None,
+            cache: None,
+            http2: false,
+            forwarded_headers: true,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            websocket_limits: None,
+            log_bodies: None,
+            canary: None,
+            mirror: None,
+            host: None,
+            match_regex: false,
+            body_integrity: None,
+            connect_timeout: None,
+            read_timeout: None,
+            header_allowlist: None,
+            header_denylist: Vec::new(),
+            access_log: true,
+            access_log_format: None,
+            startup_check: None,
+            fallback_file: None,
+            idempotency: None,
+            jwt_auth: None,
+            forward_auth: None,
         },        // Single WebSocket endpoint (legacy)
         ProxyRoute {
             path: "/ws/events".to_string(),
@@ -64,9 +139,34 @@ fn create_comprehensive_websocket_handler() -> ProxyHandler {
             sticky_sessions: false,
             http_health: None,
             websocket_health: None,
+            tcp_health: None,
             circuit_breaker: None,
             middleware: None,
             ssl: None,
+            client: None,
+            cache: None,
+            http2: false,
+            forwarded_headers: true,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            websocket_limits: None,
+            log_bodies: None,
+            canary: None,
+            mirror: None,
+            host: None,
+            match_regex: false,
+            body_integrity: None,
+            connect_timeout: None,
+            read_timeout: None,
+            header_allowlist: None,
+            header_denylist: Vec::new(),
+            access_log: true,
+            access_log_format: None,
+            startup_check: None,
+            fallback_file: None,
+            idempotency: None,
+            jwt_auth: None,
+            forward_auth: None,
         },        // HTTP route for comparison
         ProxyRoute {
             path: "/api/*".to_string(),
@@ -80,9 +180,34 @@ fn create_comprehensive_websocket_handler() -> ProxyHandler {
             sticky_sessions: false,
             http_health: None,
             websocket_health: None,
+            tcp_health: None,
             circuit_breaker: None,
             middleware: None,
             ssl: None,
+            client: None,
+            cache: None,
+            http2: false,
+            forwarded_headers: true,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            websocket_limits: None,
+            log_bodies: None,
+            canary: None,
+            mirror: None,
+            host: None,
+            match_regex: false,
+            body_integrity: None,
+            connect_timeout: None,
+            read_timeout: None,
+            header_allowlist: None,
+            header_denylist: Vec::new(),
+            access_log: true,
+            access_log_format: None,
+            startup_check: None,
+            fallback_file: None,
+            idempotency: None,
+            jwt_auth: None,
+            forward_auth: None,
         }
     ];
 
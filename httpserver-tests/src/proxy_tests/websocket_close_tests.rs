@@ -0,0 +1,125 @@
+// WebSocket close tests: close codes and reasons pass through the relay in both directions
+
+use httpserver_proxy::relay_websocket;
+use futures_util::{ SinkExt, StreamExt };
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time::{ timeout, Duration };
+use tokio_tungstenite::{
+    accept_async,
+    connect_async,
+    tungstenite::{ protocol::{ frame::coding::CloseCode, CloseFrame }, Message },
+};
+
+/// Backend that closes with 1008 when sent "close" and reports every close frame it receives,
+/// including the reply to its own close; a connection dropped without one reports an error
+async fn start_backend() -> (u16, mpsc::UnboundedReceiver<Result<Option<CloseFrame<'static>>, String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (close_tx, close_rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let close_tx = close_tx.clone();
+            tokio::spawn(async move {
+                let Ok(mut socket) = accept_async(stream).await else {
+                    return;
+                };
+                while let Some(Ok(message)) = socket.next().await {
+                    match message {
+                        Message::Text(text) if text == "close" => {
+                            let close = CloseFrame {
+                                code: CloseCode::Policy,
+                                reason: "policy violated".into(),
+                            };
+                            let _ = socket.send(Message::Close(Some(close))).await;
+                            let reply = match socket.next().await {
+                                Some(Ok(Message::Close(frame))) => Ok(frame),
+                                other => Err(format!("{:?}", other)),
+                            };
+                            let _ = close_tx.send(reply);
+                            return;
+                        }
+                        Message::Close(frame) => {
+                            let _ = close_tx.send(Ok(frame));
+                            // Reading on flushes the automatic close reply
+                            let _ = socket.next().await;
+                            return;
+                        }
+                        _ => {}
+                    }
+                }
+            });
+        }
+    });
+    (port, close_rx)
+}
+
+/// Proxy relaying each accepted connection to the backend without limits
+async fn start_proxy(backend_port: u16) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let client = accept_async(stream).await.unwrap();
+                let backend_url = format!("ws://127.0.0.1:{}", backend_port);
+                let (backend, _) = connect_async(backend_url).await.unwrap();
+                relay_websocket(client, backend, None).await;
+            });
+        }
+    });
+    port
+}
+
+#[tokio::test]
+async fn test_backend_close_code_reaches_client() {
+    let (backend_port, mut backend_closes) = start_backend().await;
+    let proxy_port = start_proxy(backend_port).await;
+    let (mut socket, _) = connect_async(format!("ws://127.0.0.1:{}", proxy_port)).await.unwrap();
+
+    socket.send(Message::Text("close".to_string())).await.unwrap();
+    let message = timeout(Duration::from_secs(5), socket.next()).await
+        .expect("Connection stayed open")
+        .unwrap()
+        .unwrap();
+    match message {
+        Message::Close(Some(frame)) => {
+            assert_eq!(frame.code, CloseCode::Policy);
+            assert_eq!(frame.reason, "policy violated");
+        }
+        other => panic!("Expected close frame, got {:?}", other),
+    }
+
+    // Reading on sends the client's reply, which the relay passes back to finish the handshake
+    assert!(socket.next().await.is_none());
+    let reply = timeout(Duration::from_secs(5), backend_closes.recv()).await
+        .expect("Backend never saw a reply")
+        .unwrap()
+        .expect("Backend connection dropped without a close reply")
+        .expect("Close reply lost its code");
+    assert_eq!(reply.code, CloseCode::Policy);
+}
+
+#[tokio::test]
+async fn test_client_close_code_reaches_backend() {
+    let (backend_port, mut backend_closes) = start_backend().await;
+    let proxy_port = start_proxy(backend_port).await;
+    let (mut socket, _) = connect_async(format!("ws://127.0.0.1:{}", proxy_port)).await.unwrap();
+
+    let close = CloseFrame { code: CloseCode::Away, reason: "page closed".into() };
+    socket.send(Message::Close(Some(close))).await.unwrap();
+
+    let frame = timeout(Duration::from_secs(5), backend_closes.recv()).await
+        .expect("Backend never saw the close")
+        .unwrap()
+        .unwrap()
+        .expect("Close frame lost its code");
+    assert_eq!(frame.code, CloseCode::Away);
+    assert_eq!(frame.reason, "page closed");
+
+    // The backend's reply is relayed back to complete the client's handshake
+    match timeout(Duration::from_secs(5), socket.next()).await.expect("No close reply") {
+        Some(Ok(Message::Close(Some(reply)))) => assert_eq!(reply.code, CloseCode::Away),
+        other => panic!("Expected close reply, got {:?}", other),
+    }
+}
@@ -0,0 +1,96 @@
+// WebSocket fragmentation tests: messages split across continuation frames reach the other side reassembled
+
+use httpserver_proxy::relay_websocket;
+use futures_util::{ SinkExt, StreamExt };
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time::{ timeout, Duration };
+use tokio_tungstenite::{
+    accept_async,
+    connect_async,
+    tungstenite::{ protocol::frame::{ coding::{ Data, OpCode }, Frame }, Message },
+};
+
+/// Raw frames carrying one message split into `fragments` parts: the first frame has the
+/// message opcode, the rest are continuations, and only the last is final
+fn fragment(data: &[u8], opcode: Data, fragments: usize) -> Vec<Message> {
+    let chunk_size = data.len().div_ceil(fragments);
+    let chunks: Vec<&[u8]> = data.chunks(chunk_size).collect();
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let opcode = if index == 0 { opcode } else { Data::Continue };
+            let is_final = index == chunks.len() - 1;
+            Message::Frame(Frame::message(chunk.to_vec(), OpCode::Data(opcode), is_final))
+        })
+        .collect()
+}
+
+/// Backend that answers "send" with `text` split over eight frames and reports
+/// every other message it receives
+async fn start_backend(text: String) -> (u16, mpsc::UnboundedReceiver<Message>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (received_tx, received_rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut socket = accept_async(stream).await.unwrap();
+        while let Some(Ok(message)) = socket.next().await {
+            if message == Message::Text("send".to_string()) {
+                for frame in fragment(text.as_bytes(), Data::Text, 8) {
+                    socket.send(frame).await.unwrap();
+                }
+            } else {
+                let _ = received_tx.send(message);
+            }
+        }
+    });
+    (port, received_rx)
+}
+
+/// Proxy relaying one accepted connection to the backend without limits
+async fn start_proxy(backend_port: u16) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let client = accept_async(stream).await.unwrap();
+        let (backend, _) = connect_async(format!("ws://127.0.0.1:{}", backend_port)).await.unwrap();
+        relay_websocket(client, backend, None).await;
+    });
+    port
+}
+
+#[tokio::test]
+async fn test_fragmented_backend_message_reaches_client_whole() {
+    let text: String = (0..256 * 1024).map(|i| char::from(b'a' + (i % 26) as u8)).collect();
+    let (backend_port, _) = start_backend(text.clone()).await;
+    let proxy_port = start_proxy(backend_port).await;
+    let (mut socket, _) = connect_async(format!("ws://127.0.0.1:{}", proxy_port)).await.unwrap();
+
+    socket.send(Message::Text("send".to_string())).await.unwrap();
+    let received = timeout(Duration::from_secs(5), socket.next()).await.unwrap().unwrap().unwrap();
+    match received {
+        Message::Text(received) => assert!(received == text, "Received {} of {} bytes", received.len(), text.len()),
+        other => panic!("Expected one text message, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_fragmented_client_message_reaches_backend_whole() {
+    let (backend_port, mut backend_received) = start_backend(String::new()).await;
+    let proxy_port = start_proxy(backend_port).await;
+    let (mut socket, _) = connect_async(format!("ws://127.0.0.1:{}", proxy_port)).await.unwrap();
+
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    for frame in fragment(&data, Data::Binary, 5) {
+        socket.send(frame).await.unwrap();
+    }
+    socket.send(Message::Text("after".to_string())).await.unwrap();
+
+    let first = timeout(Duration::from_secs(5), backend_received.recv()).await.unwrap().unwrap();
+    assert!(first == Message::Binary(data), "Expected the reassembled binary message first");
+    let second = timeout(Duration::from_secs(5), backend_received.recv()).await.unwrap().unwrap();
+    assert_eq!(second, Message::Text("after".to_string()));
+}
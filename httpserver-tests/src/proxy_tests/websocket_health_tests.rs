@@ -0,0 +1,106 @@
+// WebSocket health check tests: the ping must be answered in time for a target to stay healthy
+
+use httpserver_proxy::{ ProxyHandler, WebSocketHealthChecker };
+use httpserver_config::{ ProxyRoute, WebSocketHealthConfig };
+use futures_util::{ SinkExt, StreamExt };
+use serde_json::json;
+use std::sync::{ Arc, atomic::{ AtomicBool, Ordering } };
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_tungstenite::{ accept_async, tungstenite::Message };
+
+/// How the mock backend treats health check pings
+#[derive(Clone, Copy)]
+enum Behavior {
+    Echo,
+    Close,
+}
+
+/// WebSocket backend that answers pings according to `behavior` while `responding` is set,
+/// and otherwise accepts connections but never replies
+async fn start_backend(behavior: Behavior, responding: Arc<AtomicBool>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let responding = responding.clone();
+            tokio::spawn(async move {
+                let Ok(mut socket) = accept_async(stream).await else {
+                    return;
+                };
+                while let Some(Ok(message)) = socket.next().await {
+                    if !message.is_text() || !responding.load(Ordering::SeqCst) {
+                        continue;
+                    }
+                    let reply = match behavior {
+                        Behavior::Echo => message,
+                        Behavior::Close => Message::Close(None),
+                    };
+                    if socket.send(reply).await.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    format!("http://127.0.0.1:{}", port)
+}
+
+fn health_config() -> WebSocketHealthConfig {
+    WebSocketHealthConfig {
+        interval: 1,
+        timeout: 1,
+        path: "/health".to_string(),
+        ping_message: "ping".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_checker_requires_a_reply() {
+    let responding = Arc::new(AtomicBool::new(true));
+    let echo = start_backend(Behavior::Echo, responding.clone()).await;
+    let closing = start_backend(Behavior::Close, Arc::new(AtomicBool::new(true))).await;
+    let checker = WebSocketHealthChecker::new(health_config());
+
+    assert!(checker.check_health(&echo).await);
+    // Closing the connection is not an answer to the ping
+    assert!(!checker.check_health(&closing).await);
+
+    // A server that accepts but stays silent fails once the timeout passes
+    responding.store(false, Ordering::SeqCst);
+    assert!(!checker.check_health(&echo).await);
+}
+
+/// Wait until the route's only target has the expected health
+async fn wait_for_health(handler: &ProxyHandler, healthy: bool) -> bool {
+    for _ in 0..80 {
+        let balancer = handler.load_balancer("/ws/*").unwrap();
+        if (balancer.healthy_targets_count() == 1) == healthy {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn test_unresponsive_target_marked_unhealthy_and_recovers() {
+    let responding = Arc::new(AtomicBool::new(true));
+    let target = start_backend(Behavior::Echo, responding.clone()).await;
+    let route: ProxyRoute = serde_json::from_value(json!({
+        "path": "/ws/*",
+        "target": target,
+        "websocket_health": { "interval": 1, "timeout": 1, "path": "/health" }
+    })).unwrap();
+    let handler = ProxyHandler::new(vec![route]);
+
+    handler.start_health_checks().await;
+    assert!(wait_for_health(&handler, true).await);
+
+    responding.store(false, Ordering::SeqCst);
+    assert!(wait_for_health(&handler, false).await, "Silent target was never marked unhealthy");
+    assert_eq!(handler.unready_routes(), vec!["/ws/*".to_string()]);
+
+    responding.store(true, Ordering::SeqCst);
+    assert!(wait_for_health(&handler, true).await, "Target did not recover");
+}
@@ -0,0 +1,170 @@
+// WebSocket limit tests: oversized, over-rate and idle connections are closed by the relay
+
+use httpserver_proxy::{ relay_websocket, websocket_config };
+use httpserver_config::WebSocketLimitsConfig;
+use futures_util::{ SinkExt, StreamExt };
+use tokio::net::TcpListener;
+use tokio::time::{ timeout, Duration };
+use tokio_tungstenite::{
+    accept_async,
+    accept_async_with_config,
+    connect_async,
+    connect_async_with_config,
+    tungstenite::{ protocol::frame::coding::CloseCode, Message },
+};
+
+/// Echo backend; the text "big" is answered with a 4 KiB binary message
+async fn start_backend() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let Ok(mut socket) = accept_async(stream).await else {
+                    return;
+                };
+                while let Some(Ok(message)) = socket.next().await {
+                    let reply = match message {
+                        Message::Text(text) if text == "big" => Message::Binary(vec![0; 4096]),
+                        Message::Text(_) | Message::Binary(_) => message,
+                        _ => {
+                            continue;
+                        }
+                    };
+                    if socket.send(reply).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+    port
+}
+
+/// Proxy relaying each accepted connection to the backend with the given limits, opening both
+/// sides with the limits' read config as the gateway does
+async fn start_proxy(backend_port: u16, limits: WebSocketLimitsConfig) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let limits = limits.clone();
+            tokio::spawn(async move {
+                let config = websocket_config(Some(&limits));
+                let client = accept_async_with_config(stream, config).await.unwrap();
+                let backend_url = format!("ws://127.0.0.1:{}", backend_port);
+                let (backend, _) = connect_async_with_config(backend_url, config, false).await.unwrap();
+                relay_websocket(client, backend, Some(limits)).await;
+            });
+        }
+    });
+    port
+}
+
+/// Read until the proxy closes the connection, returning the close code and echoes received
+async fn read_until_close<S>(socket: &mut S) -> (Option<CloseCode>, usize)
+    where S: StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin
+{
+    let mut echoes = 0;
+    loop {
+        match timeout(Duration::from_secs(5), socket.next()).await.expect("Connection stayed open") {
+            Some(Ok(Message::Close(frame))) => {
+                return (frame.map(|frame| frame.code), echoes);
+            }
+            Some(Ok(_)) => {
+                echoes += 1;
+            }
+            Some(Err(_)) | None => {
+                return (None, echoes);
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_oversized_client_frame_closes_connection() {
+    let limits = WebSocketLimitsConfig { max_frame_bytes: Some(1024), ..Default::default() };
+    let proxy_port = start_proxy(start_backend().await, limits).await;
+    let (mut socket, _) = connect_async(format!("ws://127.0.0.1:{}", proxy_port)).await.unwrap();
+
+    // At the limit is relayed
+    socket.send(Message::Binary(vec![1; 1024])).await.unwrap();
+    assert_eq!(socket.next().await.unwrap().unwrap(), Message::Binary(vec![1; 1024]));
+
+    socket.send(Message::Binary(vec![1; 1025])).await.unwrap();
+    let (code, echoes) = read_until_close(&mut socket).await;
+    assert_eq!(code, Some(CloseCode::Size));
+    assert_eq!(echoes, 0);
+}
+
+#[tokio::test]
+async fn test_oversized_backend_frame_closes_connection() {
+    let limits = WebSocketLimitsConfig { max_frame_bytes: Some(1024), ..Default::default() };
+    let proxy_port = start_proxy(start_backend().await, limits).await;
+    let (mut socket, _) = connect_async(format!("ws://127.0.0.1:{}", proxy_port)).await.unwrap();
+
+    // The backend's 4 KiB reply is never delivered
+    socket.send(Message::Text("big".to_string())).await.unwrap();
+    let (code, echoes) = read_until_close(&mut socket).await;
+    assert_eq!(code, Some(CloseCode::Size));
+    assert_eq!(echoes, 0);
+}
+
+#[tokio::test]
+async fn test_message_flood_closes_connection() {
+    let limits = WebSocketLimitsConfig { max_messages_per_second: Some(5), ..Default::default() };
+    let proxy_port = start_proxy(start_backend().await, limits).await;
+    let (mut socket, _) = connect_async(format!("ws://127.0.0.1:{}", proxy_port)).await.unwrap();
+
+    for i in 0..10 {
+        socket.send(Message::Text(format!("message {}", i))).await.unwrap();
+    }
+    let (code, echoes) = read_until_close(&mut socket).await;
+    assert_eq!(code, Some(CloseCode::Policy));
+    assert!(echoes <= 5, "Only messages within the rate should be relayed, got {}", echoes);
+}
+
+#[tokio::test]
+async fn test_idle_connection_is_closed() {
+    let limits = WebSocketLimitsConfig { idle_timeout_secs: Some(1), ..Default::default() };
+    let proxy_port = start_proxy(start_backend().await, limits).await;
+
+    // Idle: closed with 1001 once the timeout passes
+    let (mut idle, _) = connect_async(format!("ws://127.0.0.1:{}", proxy_port)).await.unwrap();
+    let (code, _) = read_until_close(&mut idle).await;
+    assert_eq!(code, Some(CloseCode::Away));
+
+    // Active: each message resets the timer, so the connection outlives the timeout
+    let (mut active, _) = connect_async(format!("ws://127.0.0.1:{}", proxy_port)).await.unwrap();
+    for i in 0..6 {
+        active.send(Message::Text(format!("message {}", i))).await.unwrap();
+        assert_eq!(active.next().await.unwrap().unwrap(), Message::Text(format!("message {}", i)));
+        tokio::time::sleep(Duration::from_millis(400)).await;
+    }
+    active.send(Message::Text("still open".to_string())).await.unwrap();
+    assert_eq!(active.next().await.unwrap().unwrap(), Message::Text("still open".to_string()));
+}
+
+#[tokio::test]
+async fn test_keepalive_ping_holds_idle_connection_open() {
+    let limits = WebSocketLimitsConfig {
+        idle_timeout_secs: Some(2),
+        ping_interval_secs: Some(1),
+        ..Default::default()
+    };
+    let proxy_port = start_proxy(start_backend().await, limits).await;
+    let (mut socket, _) = connect_async(format!("ws://127.0.0.1:{}", proxy_port)).await.unwrap();
+
+    // Reading answers the proxy's pings; the pongs count as activity
+    let mut pings = 0;
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(3500);
+    while let Ok(message) = tokio::time::timeout_at(deadline, socket.next()).await {
+        match message {
+            Some(Ok(Message::Ping(_))) => {
+                pings += 1;
+            }
+            other => panic!("Expected only pings on an idle connection, got {:?}", other),
+        }
+    }
+    assert!(pings >= 2, "Expected keepalive pings, got {}", pings);
+}
@@ -22,9 +22,34 @@ fn create_sticky_session_handler() -> ProxyHandler {
             sticky_sessions: false,
             http_health: None,
             websocket_health: None,
+            tcp_health: None,
             circuit_breaker: None,
             middleware: None,
             ssl: None,
+            client: None,
+            cache: None,
+            http2: false,
+            forwarded_headers: true,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            websocket_limits: None,
+            log_bodies: None,
+            canary: None,
+            mirror: None,
+            host: None,
+            match_regex: false,
+            body_integrity: None,
+            connect_timeout: None,
+            read_timeout: None,
+            header_allowlist: None,
+            header_denylist: Vec::new(),
+            access_log: true,
+            access_log_format: None,
+            startup_check: None,
+            fallback_file: None,
+            idempotency: None,
+            jwt_auth: None,
+            forward_auth: None,
         },        // WebSocket route for broadcast scenarios (no sticky sessions needed)
         ProxyRoute {
             path: "/ws/broadcast/*".to_string(),
@@ -38,9 +63,34 @@ fn create_sticky_session_handler() -> ProxyHandler {
             sticky_sessions: false,
             http_health: None,
             websocket_health: None,
+            tcp_health: None,
             circuit_breaker: None,
             middleware: None,
             ssl: None,
+            client: None,
+            cache: None,
+            http2: false,
+            forwarded_headers: true,
+            max_request_body_bytes: None,
+            max_response_body_bytes: None,
+            websocket_limits: None,
+            log_bodies: None,
+            canary: None,
+            mirror: None,
+            host: None,
+            match_regex: false,
+            body_integrity: None,
+            connect_timeout: None,
+            read_timeout: None,
+            header_allowlist: None,
+            header_denylist: Vec::new(),
+            access_log: true,
+            access_log_format: None,
+            startup_check: None,
+            fallback_file: None,
+            idempotency: None,
+            jwt_auth: None,
+            forward_auth: None,
         }
     ];
 
@@ -132,13 +182,10 @@ fn test_least_connections_for_websockets() {
     for _ in 0..10 {
         let target = balancer.select_target().unwrap();
         // Should consistently avoid the overloaded target
-        if
-            balancer.get_connection_count("http://localhost:5000") >
-            balancer.get_connection_count(&target.url)
-        {
-            // This is good - we're avoiding the overloaded target
-            assert!(true);
-        }
+        assert!(
+            balancer.get_connection_count(&target.url) <=
+            balancer.get_connection_count("http://localhost:5000")
+        );
     }
 }
 
@@ -186,7 +233,6 @@ fn test_websocket_sticky_session_concept() {
 
     // This test demonstrates the concept but doesn't test actual implementation
     // since sticky sessions are not yet implemented
-    assert!(true, "Sticky session concept test completed");
 }
 
 // Test WebSocket route prioritization over HTTP routes
@@ -327,9 +373,34 @@ fn test_websocket_routing_edge_cases() {
         sticky_sessions: false,
         http_health: None,
         websocket_health: None,
+        tcp_health: None,
         circuit_breaker: None,
         middleware: None,
         ssl: None,
+        client: None,
+        cache: None,
+        http2: false,
+        forwarded_headers: true,
+        max_request_body_bytes: None,
+        max_response_body_bytes: None,
+        websocket_limits: None,
+        log_bodies: None,
+        canary: None,
+        mirror: None,
+        host: None,
+        match_regex: false,
+        body_integrity: None,
+        connect_timeout: None,
+        read_timeout: None,
+        header_allowlist: None,
+        header_denylist: Vec::new(),
+        access_log: true,
+        access_log_format: None,
+        startup_check: None,
+        fallback_file: None,
+        idempotency: None,
+        jwt_auth: None,
+        forward_auth: None,
     }];
 
     let empty_handler = ProxyHandler::new(empty_routes);
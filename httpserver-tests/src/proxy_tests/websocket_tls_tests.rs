@@ -0,0 +1,130 @@
+// WebSocket backend TLS tests: wss targets honour the route's certificate verification and client certificate
+
+use httpserver_proxy::{ ProxyError, connect_backend };
+use httpserver_config::{ ProxyRoute, RouteSslConfig };
+use futures_util::{ SinkExt, StreamExt };
+use rcgen::{ BasicConstraints, Certificate, CertificateParams, DistinguishedName, IsCa };
+use rustls::server::AllowAnyAuthenticatedClient;
+use rustls::{ PrivateKey, RootCertStore, ServerConfig };
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tokio_tungstenite::{ accept_async, tungstenite::Message };
+
+fn certificate(common_name: &str, is_ca: bool) -> Certificate {
+    let mut params = CertificateParams::new(vec!["localhost".to_string()]);
+    params.distinguished_name = DistinguishedName::new();
+    params.distinguished_name.push(rcgen::DnType::CommonName, common_name);
+    if is_ca {
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    }
+    Certificate::from_params(params).unwrap()
+}
+
+/// Start a wss echo backend with a self-signed certificate. When `client_ca` is set,
+/// the backend requires a client certificate issued by that CA.
+async fn start_wss_backend(client_ca: Option<&Certificate>) -> u16 {
+    let server_cert = certificate("localhost", false);
+    let chain = vec![rustls::Certificate(server_cert.serialize_der().unwrap())];
+    let key = PrivateKey(server_cert.serialize_private_key_der());
+
+    let builder = ServerConfig::builder().with_safe_defaults();
+    let config = match client_ca {
+        Some(ca) => {
+            let mut roots = RootCertStore::empty();
+            roots.add(&rustls::Certificate(ca.serialize_der().unwrap())).unwrap();
+            builder
+                .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed())
+                .with_single_cert(chain, key)
+                .unwrap()
+        }
+        None => builder.with_no_client_auth().with_single_cert(chain, key).unwrap(),
+    };
+
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                let Ok(tls) = acceptor.accept(stream).await else {
+                    return;
+                };
+                let Ok(mut socket) = accept_async(tls).await else {
+                    return;
+                };
+                while let Some(Ok(Message::Text(text))) = socket.next().await {
+                    let _ = socket.send(Message::Text(format!("echo {}", text))).await;
+                }
+            });
+        }
+    });
+    port
+}
+
+/// WebSocket route to a wss backend with the given backend TLS settings
+fn create_route(port: u16, ssl: RouteSslConfig) -> ProxyRoute {
+    let mut route: ProxyRoute = serde_json::from_value(json!({
+        "path": "/ws/*",
+        "target": format!("https://localhost:{}", port),
+    })).unwrap();
+    route.ssl = Some(ssl);
+    route
+}
+
+fn backend_ssl(verify: bool, client_identity: Option<(PathBuf, PathBuf)>) -> RouteSslConfig {
+    let (client_cert_file, client_key_file) = client_identity.unzip();
+    RouteSslConfig {
+        enabled: false,
+        cert_file: None,
+        key_file: None,
+        backend_ssl: true,
+        verify_backend_ssl: verify,
+        client_cert_file,
+        client_key_file,
+    }
+}
+
+/// Connect through the route and check a message round-trips
+async fn assert_echo(route: &ProxyRoute, port: u16) {
+    let mut backend = connect_backend(route, &format!("wss://localhost:{}", port)).await.unwrap();
+    backend.send(Message::Text("hello".to_string())).await.unwrap();
+    assert_eq!(backend.next().await.unwrap().unwrap(), Message::Text("echo hello".to_string()));
+}
+
+#[tokio::test]
+async fn test_self_signed_backend_without_verification() {
+    let port = start_wss_backend(None).await;
+    assert_echo(&create_route(port, backend_ssl(false, None)), port).await;
+}
+
+#[tokio::test]
+async fn test_self_signed_backend_rejected_with_verification() {
+    let port = start_wss_backend(None).await;
+    let route = create_route(port, backend_ssl(true, None));
+
+    let result = connect_backend(&route, &format!("wss://localhost:{}", port)).await;
+    assert!(matches!(result, Err(ProxyError::BackendCertificate(_))), "{:?}", result.err());
+}
+
+#[tokio::test]
+async fn test_client_certificate_presented_to_backend() {
+    let temp_dir = TempDir::new().unwrap();
+    let ca = certificate("Test Client CA", true);
+    let client = certificate("gateway-client", false);
+    let cert_file = temp_dir.path().join("client.crt");
+    let key_file = temp_dir.path().join("client.key");
+    std::fs::write(&cert_file, client.serialize_pem_with_signer(&ca).unwrap()).unwrap();
+    std::fs::write(&key_file, client.serialize_private_key_pem()).unwrap();
+    let port = start_wss_backend(Some(&ca)).await;
+
+    assert_echo(&create_route(port, backend_ssl(false, Some((cert_file, key_file)))), port).await;
+
+    // Without the client certificate the backend ends the handshake
+    let route = create_route(port, backend_ssl(false, None));
+    assert!(connect_backend(&route, &format!("wss://localhost:{}", port)).await.is_err());
+}
@@ -0,0 +1,84 @@
+// Static basic auth tests: every file requires matching HTTP Basic credentials when configured
+
+use httpserver_static::StaticHandler;
+use axum::{ Router, body::Body, http::{ Request, StatusCode, header } };
+use base64::{ Engine as _, engine::general_purpose };
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+/// Static directory with an index and a nested page, gated by the given credentials
+fn create_app(dir: &TempDir, credentials: &[&str]) -> Router {
+    std::fs::write(dir.path().join("index.html"), "docs index").unwrap();
+    std::fs::create_dir_all(dir.path().join("guides")).unwrap();
+    std::fs::write(dir.path().join("guides/setup.html"), "setup guide").unwrap();
+
+    StaticHandler::new(dir.path().to_path_buf())
+        .unwrap()
+        .with_basic_auth(credentials.iter().map(|credential| credential.to_string()).collect())
+        .create_router()
+}
+
+/// Request `uri` with an optional Authorization header value
+async fn send(app: &Router, uri: &str, authorization: Option<String>) -> axum::response::Response {
+    let mut request = Request::builder().uri(uri);
+    if let Some(authorization) = authorization {
+        request = request.header(header::AUTHORIZATION, authorization);
+    }
+    app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+fn basic(credential: &str) -> Option<String> {
+    Some(format!("Basic {}", general_purpose::STANDARD.encode(credential)))
+}
+
+#[tokio::test]
+async fn test_missing_credentials_are_challenged() {
+    let dir = TempDir::new().unwrap();
+    let app = create_app(&dir, &["docs:s3cret"]);
+
+    for uri in ["/", "/guides/setup.html", "/missing.png"] {
+        let response = send(&app, uri, None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+        assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Basic realm=\"static\", charset=\"UTF-8\"");
+    }
+}
+
+#[tokio::test]
+async fn test_wrong_credentials_are_rejected() {
+    let dir = TempDir::new().unwrap();
+    let app = create_app(&dir, &["docs:s3cret"]);
+
+    for authorization in [
+        basic("docs:wrong"),
+        basic("other:s3cret"),
+        basic("docs:s3cret-and-more"),
+        Some("Bearer docs:s3cret".to_string()),
+        Some("Basic not-base64!".to_string()),
+    ] {
+        let response = send(&app, "/", authorization.clone()).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{:?}", authorization);
+    }
+}
+
+#[tokio::test]
+async fn test_correct_credentials_are_served() {
+    let dir = TempDir::new().unwrap();
+    let app = create_app(&dir, &["docs:s3cret", "ops:hunter2"]);
+
+    let response = send(&app, "/guides/setup.html", basic("docs:s3cret")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(&body[..], b"setup guide");
+
+    // Any listed credential works, and the scheme is case-insensitive
+    let authorization = basic("ops:hunter2").map(|value| value.replacen("Basic", "basic", 1));
+    assert_eq!(send(&app, "/", authorization).await.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_no_credentials_configured_serves_everyone() {
+    let dir = TempDir::new().unwrap();
+    let app = create_app(&dir, &[]);
+
+    assert_eq!(send(&app, "/", None).await.status(), StatusCode::OK);
+}
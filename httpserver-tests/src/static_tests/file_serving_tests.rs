@@ -1,4 +1,4 @@
-use httpserver_static::StaticHandler;
+use httpserver_static::{ CacheControl, SpaFallback, StaticHandler };
 use std::path::PathBuf;
 use tempfile::TempDir;
 use tokio::fs;
@@ -193,3 +193,136 @@ async fn test_cache_headers() {
     let cache_control = response.headers().get("cache-control").unwrap();
     assert!(cache_control.to_str().unwrap().contains("max-age=3600"));
 }
+
+async fn get_status_and_body(app: axum::Router, uri: &str) -> (StatusCode, String) {
+    let request = Request::builder().uri(uri).body(axum::body::Body::empty()).unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8_lossy(&body).to_string())
+}
+
+#[tokio::test]
+async fn test_spa_fallback_only_for_extensionless_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = setup_test_files(&temp_dir).await;
+    let app = StaticHandler::new(temp_path).unwrap().create_router();
+
+    let (status, body) = get_status_and_body(app.clone(), "/about").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("Home Page"));
+
+    // Missing assets are genuine 404s rather than the SPA shell
+    let (status, body) = get_status_and_body(app.clone(), "/missing.js").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(!body.contains("Home Page"));
+
+    let (status, _) = get_status_and_body(app, "/assets/missing.png").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_spa_fallback_toggle_and_prefixes() {
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = setup_test_files(&temp_dir).await;
+
+    // Disabled: every missing path is a 404
+    let app = StaticHandler::new(temp_path.clone())
+        .unwrap()
+        .with_spa_fallback(SpaFallback { enabled: false, prefixes: vec![] })
+        .create_router();
+    let (status, _) = get_status_and_body(app, "/about").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Configured prefixes fall back even for paths with an extension
+    let app = StaticHandler::new(temp_path)
+        .unwrap()
+        .with_spa_fallback(SpaFallback { enabled: false, prefixes: vec!["/app/".to_string()] })
+        .create_router();
+    let (status, body) = get_status_and_body(app.clone(), "/app/users/jane.doe").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("Home Page"));
+
+    let (status, _) = get_status_and_body(app, "/other/page").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_cache_control_per_file_type() {
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = setup_test_files(&temp_dir).await;
+    fs::write(temp_path.join("app.abc123.js"), "console.log('bundle');").await.unwrap();
+
+    let app = StaticHandler::new(temp_path)
+        .unwrap()
+        .with_cache_control(CacheControl {
+            default: "public, max-age=600".to_string(),
+            rules: vec![
+                ("*.html".to_string(), "no-cache".to_string()),
+                ("*.*.js".to_string(), "public, max-age=31536000, immutable".to_string()),
+                ("assets/*.svg".to_string(), "public, max-age=86400".to_string())
+            ],
+        })
+        .create_router();
+
+    let cache_control = |uri: &'static str| {
+        let app = app.clone();
+        async move {
+            let request = Request::builder().uri(uri).body(axum::body::Body::empty()).unwrap();
+            let response = app.oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            response.headers()["cache-control"].to_str().unwrap().to_string()
+        }
+    };
+
+    assert_eq!(cache_control("/").await, "no-cache");
+    assert_eq!(cache_control("/index.html").await, "no-cache");
+    assert_eq!(cache_control("/app.abc123.js").await, "public, max-age=31536000, immutable");
+    assert_eq!(cache_control("/assets/logo.svg").await, "public, max-age=86400");
+
+    // Unmatched files use the default; the SPA shell follows the index.html rule
+    assert_eq!(cache_control("/test.js").await, "public, max-age=600");
+    assert_eq!(cache_control("/app/dashboard").await, "no-cache");
+}
+
+#[tokio::test]
+async fn test_head_returns_headers_without_body() {
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = setup_test_files(&temp_dir).await;
+
+    let app = StaticHandler::new(temp_path).unwrap().create_router();
+
+    for uri in ["/", "/test.css"] {
+        let get = Request::builder().uri(uri).body(axum::body::Body::empty()).unwrap();
+        let get = app.clone().oneshot(get).await.unwrap();
+        let head = Request::builder().method("HEAD").uri(uri).body(axum::body::Body::empty()).unwrap();
+        let head = app.clone().oneshot(head).await.unwrap();
+
+        assert_eq!(head.status(), StatusCode::OK);
+        assert_eq!(head.headers()["content-type"], get.headers()["content-type"]);
+        assert_eq!(head.headers()["cache-control"], get.headers()["cache-control"]);
+        let get_body = to_bytes(get.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(head.headers()["content-length"], get_body.len().to_string().as_str());
+        assert!(to_bytes(head.into_body(), usize::MAX).await.unwrap().is_empty());
+    }
+
+    // Missing files are still a 404
+    let head = Request::builder().method("HEAD").uri("/missing.txt").body(axum::body::Body::empty()).unwrap();
+    assert_eq!(app.oneshot(head).await.unwrap().status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_other_methods_return_405_with_allow_header() {
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = setup_test_files(&temp_dir).await;
+
+    let app = StaticHandler::new(temp_path).unwrap().create_router();
+
+    for (method, uri) in [("POST", "/"), ("PUT", "/test.css"), ("DELETE", "/assets/logo.svg")] {
+        let request = Request::builder().method(method).uri(uri).body(axum::body::Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED, "{} {}", method, uri);
+        assert_eq!(response.headers()["allow"], "GET, HEAD");
+    }
+}
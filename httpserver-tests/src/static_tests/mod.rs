@@ -1,2 +1,4 @@
+pub mod basic_auth_tests;
 pub mod file_serving_tests;
+pub mod mount_tests;
 pub mod static_handler_tests;
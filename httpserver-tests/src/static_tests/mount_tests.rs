@@ -0,0 +1,143 @@
+// Static mount tests: each prefix serves its own directory, the longest prefix wins
+
+use httpserver_static::{ SpaFallback, StaticError, StaticHandler };
+use axum::{ Router, body::{ Body, to_bytes }, http::{ Request, StatusCode } };
+use std::path::Path;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+fn write(dir: &Path, name: &str, contents: &str) {
+    let path = dir.join(name);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, contents).unwrap();
+}
+
+fn no_fallback() -> SpaFallback {
+    SpaFallback { enabled: false, prefixes: vec![] }
+}
+
+/// Root SPA plus /assets, /docs and the nested /docs/api mounts, each in its own directory
+fn create_app(root: &TempDir, assets: &TempDir, docs: &TempDir, api_docs: &TempDir) -> Router {
+    write(root.path(), "index.html", "root index");
+    write(root.path(), "app.js", "root app");
+    write(assets.path(), "logo.svg", "assets logo");
+    write(assets.path(), "css/site.css", "assets css");
+    write(docs.path(), "index.html", "docs index");
+    write(docs.path(), "guide.html", "docs guide");
+    write(api_docs.path(), "index.html", "api index");
+
+    StaticHandler::new(root.path().to_path_buf())
+        .unwrap()
+        .with_mount("/assets", assets.path().to_path_buf(), no_fallback())
+        .unwrap()
+        .with_mount("/docs/", docs.path().to_path_buf(), SpaFallback::default())
+        .unwrap()
+        .with_mount("/docs/api", api_docs.path().to_path_buf(), no_fallback())
+        .unwrap()
+        .create_router()
+}
+
+async fn get(app: &Router, uri: &str) -> (StatusCode, String) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8_lossy(&body).to_string())
+}
+
+#[tokio::test]
+async fn test_files_resolve_from_their_mount_directory() {
+    let dirs: Vec<TempDir> = (0..4).map(|_| TempDir::new().unwrap()).collect();
+    let app = create_app(&dirs[0], &dirs[1], &dirs[2], &dirs[3]);
+
+    assert_eq!(get(&app, "/app.js").await, (StatusCode::OK, "root app".to_string()));
+    assert_eq!(get(&app, "/assets/logo.svg").await, (StatusCode::OK, "assets logo".to_string()));
+    assert_eq!(get(&app, "/assets/css/site.css").await, (StatusCode::OK, "assets css".to_string()));
+    assert_eq!(get(&app, "/docs/guide.html").await, (StatusCode::OK, "docs guide".to_string()));
+    assert_eq!(get(&app, "/docs").await, (StatusCode::OK, "docs index".to_string()));
+
+    // The longest prefix wins over /docs
+    assert_eq!(get(&app, "/docs/api/").await, (StatusCode::OK, "api index".to_string()));
+
+    // Prefixes only match whole path segments
+    assert_eq!(get(&app, "/assetsfoo").await, (StatusCode::OK, "root index".to_string()));
+}
+
+#[tokio::test]
+async fn test_fallbacks_are_per_mount() {
+    let dirs: Vec<TempDir> = (0..4).map(|_| TempDir::new().unwrap()).collect();
+    let app = create_app(&dirs[0], &dirs[1], &dirs[2], &dirs[3]);
+
+    // Root keeps its SPA fallback; /docs falls back to its own index
+    assert_eq!(get(&app, "/dashboard").await, (StatusCode::OK, "root index".to_string()));
+    assert_eq!(get(&app, "/docs/getting-started").await, (StatusCode::OK, "docs index".to_string()));
+
+    // Mounts without a fallback return real 404s
+    assert_eq!(get(&app, "/assets/missing").await.0, StatusCode::NOT_FOUND);
+    assert_eq!(get(&app, "/docs/api/missing").await.0, StatusCode::NOT_FOUND);
+
+    // A missing asset outside every mount is a 404 too
+    assert_eq!(get(&app, "/missing.css").await.0, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_traversal_is_blocked_per_mount() {
+    let dirs: Vec<TempDir> = (0..4).map(|_| TempDir::new().unwrap()).collect();
+    let app = create_app(&dirs[0], &dirs[1], &dirs[2], &dirs[3]);
+
+    // app.js exists in the root directory but is outside the /assets mount
+    let escape = format!(
+        "/assets/../{}/app.js",
+        dirs[0].path().file_name().unwrap().to_string_lossy()
+    );
+    assert_eq!(get(&app, &escape).await.0, StatusCode::FORBIDDEN);
+}
+
+#[test]
+fn test_invalid_mounts_are_rejected() {
+    let root = TempDir::new().unwrap();
+    let assets = TempDir::new().unwrap();
+    let handler = || StaticHandler::new(root.path().to_path_buf()).unwrap();
+
+    assert!(handler().with_mount("/", assets.path().to_path_buf(), no_fallback()).is_err());
+    assert!(
+        handler()
+            .with_mount("/assets", assets.path().join("missing"), no_fallback())
+            .is_err()
+    );
+    assert!(
+        handler()
+            .with_mount("/assets", assets.path().to_path_buf(), no_fallback())
+            .unwrap()
+            .with_mount("/assets/", assets.path().to_path_buf(), no_fallback())
+            .is_err()
+    );
+}
+
+#[test]
+fn test_mount_errors_identify_the_failure() {
+    let root = TempDir::new().unwrap();
+    let assets = TempDir::new().unwrap();
+    let handler = || StaticHandler::new(root.path().to_path_buf()).unwrap();
+
+    let result = handler().with_mount("/", assets.path().to_path_buf(), no_fallback());
+    assert!(matches!(result, Err(StaticError::RootMount)));
+
+    let missing = assets.path().join("missing");
+    match handler().with_mount("/assets", missing.clone(), no_fallback()) {
+        Err(StaticError::Directory { path, source }) => {
+            assert_eq!(path, missing);
+            assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+        }
+        other => panic!("Expected Directory error, got {:?}", other.err()),
+    }
+
+    let result = handler()
+        .with_mount("/assets", assets.path().to_path_buf(), no_fallback())
+        .unwrap()
+        .with_mount("assets/", assets.path().to_path_buf(), no_fallback());
+    match result {
+        Err(StaticError::DuplicateMount(prefix)) => assert_eq!(prefix, "/assets"),
+        other => panic!("Expected DuplicateMount error, got {:?}", other.err()),
+    }
+}
@@ -0,0 +1,88 @@
+// Helpers shared by the test modules: local backends, minimal proxy routes and tunnel servers
+use axum::{ Router, http::Uri };
+use httpserver_config::ProxyRoute;
+use httpserver_tunnel::config::{ TunnelServerConfig, TunnelServerNetworkConfig, TunnelRateLimitConfig };
+use httpserver_tunnel::server::TunnelServer;
+use serde_json::json;
+use std::sync::atomic::{ AtomicU16, Ordering };
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tokio::time::{ sleep, Duration };
+
+/// Serve `app` on an ephemeral local port in the background, returning the port
+pub async fn serve(app: Router) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    port
+}
+
+/// Base URL of `app` served on an ephemeral local port in the background
+pub async fn serve_url(app: Router) -> String {
+    format!("http://127.0.0.1:{}", serve(app).await)
+}
+
+/// Backend answering every request with `text`
+pub async fn start_text_backend(text: &'static str) -> String {
+    let app = Router::new().fallback(move || async move { text });
+    serve_url(app).await
+}
+
+/// Backend answering every request with the path it received
+pub async fn start_path_backend() -> String {
+    let app = Router::new().fallback(|uri: Uri| async move { format!("backend {}", uri.path()) });
+    serve_url(app).await
+}
+
+/// Proxy route with only the required fields set
+pub fn route(path: &str, target: &str) -> ProxyRoute {
+    serde_json::from_value(json!({ "path": path, "target": target })).unwrap()
+}
+
+/// Port for a server that binds its own listener. Ports come from below the kernel's
+/// ephemeral range, so the `127.0.0.1:0` listeners of concurrent tests never take them.
+pub fn free_port() -> u16 {
+    static NEXT_PORT: AtomicU16 = AtomicU16::new(20000);
+    loop {
+        let port = NEXT_PORT.fetch_add(1, Ordering::Relaxed);
+        if std::net::TcpListener::bind(("127.0.0.1", port)).is_ok() {
+            return port;
+        }
+    }
+}
+
+/// Tunnel server configuration for `base_domain` on free localhost ports, with auth and rate limiting off
+pub fn tunnel_server_config(base_domain: &str) -> TunnelServerConfig {
+    let mut config = TunnelServerConfig {
+        enabled: true,
+        tunnel_port: free_port(),
+        public_port: free_port(),
+        base_domain: base_domain.to_string(),
+        rate_limiting: TunnelRateLimitConfig {
+            enabled: false,
+            ..Default::default()
+        },
+        network: TunnelServerNetworkConfig {
+            bind_address: "127.0.0.1".to_string(),
+            public_bind_address: "127.0.0.1".to_string(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    config.auth.required = false;
+    config
+}
+
+/// Start a tunnel server keeping its subdomains in `storage_dir`, giving it time to bind
+pub async fn start_tunnel_server(config: TunnelServerConfig, storage_dir: &TempDir) -> JoinHandle<()> {
+    let server = TunnelServer::with_storage_path(config, storage_dir.path().join("subdomains.json"))
+        .unwrap();
+    let handle = tokio::spawn(async move {
+        let _ = server.start().await;
+    });
+    sleep(Duration::from_millis(200)).await;
+    handle
+}
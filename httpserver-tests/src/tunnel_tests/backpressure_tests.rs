@@ -0,0 +1,75 @@
+//! Tunnel Backpressure Tests
+//! Tests for public requests meeting a tunnel whose request queue is full
+
+use httpserver_tunnel::config::TunnelServerConfig;
+use httpserver_tunnel::protocol::{TunnelMessage, TunnelProtocol};
+use futures_util::{SinkExt, StreamExt};
+use tempfile::TempDir;
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use crate::test_support::{start_tunnel_server, tunnel_server_config};
+
+/// Create a server configuration with a one-request queue and a short wait for room
+fn create_backpressure_test_config() -> TunnelServerConfig {
+    let mut config = tunnel_server_config("queue.test");
+    config.request_queue_size = 1;
+    config.request_queue_timeout_ms = 200;
+    config
+}
+
+
+#[tokio::test]
+async fn test_saturated_tunnel_returns_503() {
+    let storage_dir = TempDir::new().unwrap();
+    let config = create_backpressure_test_config();
+    let tunnel_port = config.tunnel_port;
+    let public_port = config.public_port;
+    let server_handle = start_tunnel_server(config, &storage_dir).await;
+
+    // A client that authenticates and then stops reading, so its socket backs up
+    let url = format!("ws://127.0.0.1:{}/connect", tunnel_port);
+    let (mut socket, _) = connect_async(url.as_str()).await.unwrap();
+    let auth = TunnelProtocol::create_auth_message("any-token", Some("slow-app"));
+    socket.send(Message::Text(serde_json::to_string(&auth).unwrap())).await.unwrap();
+    let reply = socket.next().await.unwrap().unwrap();
+    let reply: TunnelMessage = serde_json::from_str(reply.to_text().unwrap()).unwrap();
+    assert!(matches!(reply, TunnelMessage::AuthResponse { success: true, .. }));
+
+    // Large bodies fill the socket buffers, then the queue; the next request is refused
+    let client = reqwest::Client::new();
+    let body = vec![255u8; 1_500_000];
+    let mut stalled = Vec::new();
+    let mut rejected = None;
+    for _ in 0..10 {
+        let request = client.post(format!("http://127.0.0.1:{}/upload", public_port))
+            .header("host", "slow-app.queue.test")
+            .body(body.clone())
+            .send();
+        let mut request = tokio::spawn(request);
+        match timeout(Duration::from_secs(2), &mut request).await {
+            Ok(response) => {
+                rejected = Some(response.unwrap().unwrap());
+                break;
+            }
+            Err(_) => stalled.push(request),
+        }
+    }
+
+    let response = rejected.expect("Saturated tunnel never refused a request");
+    assert_eq!(response.status(), 503);
+    assert_eq!(response.text().await.unwrap(), "Tunnel is overloaded");
+
+    // The full queue shows in the tunnel health metrics
+    let health: serde_json::Value = client.get(format!("http://127.0.0.1:{}/health", tunnel_port))
+        .send().await.unwrap()
+        .json().await.unwrap();
+    assert_eq!(health["tunnels"][0]["subdomain"], "slow-app");
+    assert_eq!(health["tunnels"][0]["queued_requests"], 1);
+    assert_eq!(health["server_config"]["request_queue_size"], 1);
+
+    for request in stalled {
+        request.abort();
+    }
+    server_handle.abort();
+}
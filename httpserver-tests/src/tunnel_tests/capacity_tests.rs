@@ -0,0 +1,103 @@
+//! Tunnel Capacity Tests
+//! Tests that the tunnel server refuses tunnels beyond max_tunnels and frees slots on disconnect
+
+use httpserver_tunnel::protocol::TunnelMessage;
+use futures_util::{SinkExt, StreamExt};
+use tempfile::TempDir;
+use tokio::net::TcpStream;
+use tokio::time::{sleep, timeout, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use crate::test_support::{start_tunnel_server, tunnel_server_config};
+
+type TestSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Start a server allowing `max_tunnels` tunnels with no auth, returning its tunnel port
+async fn start_server(max_tunnels: u32, storage_dir: &TempDir) -> (u16, tokio::task::JoinHandle<()>) {
+    let mut config = tunnel_server_config("capacity.test");
+    config.max_tunnels = max_tunnels;
+    let tunnel_port = config.tunnel_port;
+    let handle = start_tunnel_server(config, storage_dir).await;
+    (tunnel_port, handle)
+}
+
+/// Connect to the tunnel endpoint and authenticate with the requested subdomain
+async fn connect_tunnel(tunnel_port: u16, subdomain: &str) -> (TestSocket, TunnelMessage) {
+    let url = format!("ws://127.0.0.1:{}/connect", tunnel_port);
+    let (mut socket, _) = connect_async(url.as_str()).await.expect("tunnel connect failed");
+
+    let auth = TunnelMessage::Auth {
+        token: "any-token".to_string(),
+        subdomain: Some(subdomain.to_string()),
+        protocol_version: "1.0".to_string(),
+        capabilities: vec![],
+    };
+    socket.send(Message::Text(serde_json::to_string(&auth).unwrap())).await.unwrap();
+
+    let reply = timeout(Duration::from_secs(5), socket.next()).await
+        .expect("no auth response")
+        .expect("socket closed")
+        .expect("socket error");
+    let reply = serde_json::from_str(reply.to_text().unwrap()).unwrap();
+    (socket, reply)
+}
+
+/// Read the active tunnel count from the tunnel server health endpoint
+async fn active_tunnel_count(tunnel_port: u16) -> u64 {
+    let body: serde_json::Value = reqwest::get(format!("http://127.0.0.1:{}/health", tunnel_port))
+        .await.unwrap()
+        .json().await.unwrap();
+    body["active_tunnels"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn test_tunnel_beyond_capacity_is_rejected() {
+    let storage_dir = TempDir::new().unwrap();
+    let (tunnel_port, server_handle) = start_server(2, &storage_dir).await;
+
+    let (first, reply) = connect_tunnel(tunnel_port, "first-app").await;
+    assert!(matches!(reply, TunnelMessage::AuthResponse { success: true, .. }));
+    let (_second, reply) = connect_tunnel(tunnel_port, "second-app").await;
+    assert!(matches!(reply, TunnelMessage::AuthResponse { success: true, .. }));
+
+    // Full: the third tunnel is refused and its subdomain is not taken
+    let (_third, reply) = connect_tunnel(tunnel_port, "third-app").await;
+    match reply {
+        TunnelMessage::AuthResponse { success, error, .. } => {
+            assert!(!success);
+            assert_eq!(error.as_deref(), Some("server at capacity"));
+        }
+        other => panic!("Expected AuthResponse, got {:?}", other),
+    }
+    assert_eq!(active_tunnel_count(tunnel_port).await, 2);
+
+    // Disconnecting one frees its slot
+    drop(first);
+    for _ in 0..50 {
+        if active_tunnel_count(tunnel_port).await < 2 {
+            break;
+        }
+        sleep(Duration::from_millis(50)).await;
+    }
+    let (_third, reply) = connect_tunnel(tunnel_port, "third-app").await;
+    assert!(matches!(reply, TunnelMessage::AuthResponse { success: true, .. }));
+    assert_eq!(active_tunnel_count(tunnel_port).await, 2);
+
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn test_zero_max_tunnels_is_unlimited() {
+    let storage_dir = TempDir::new().unwrap();
+    let (tunnel_port, server_handle) = start_server(0, &storage_dir).await;
+
+    let mut sockets = Vec::new();
+    for i in 0..3 {
+        let (socket, reply) = connect_tunnel(tunnel_port, &format!("app-{}", i)).await;
+        assert!(matches!(reply, TunnelMessage::AuthResponse { success: true, .. }));
+        sockets.push(socket);
+    }
+    assert_eq!(active_tunnel_count(tunnel_port).await, 3);
+
+    server_handle.abort();
+}
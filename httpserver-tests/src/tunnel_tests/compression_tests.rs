@@ -0,0 +1,142 @@
+//! Tunnel Compression Tests
+//! Tests for negotiated deflate compression of large tunnel messages
+
+use httpserver_tunnel::protocol::{TunnelFrame, TunnelMessage, TunnelProtocol};
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use tempfile::TempDir;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use crate::test_support::{start_tunnel_server, tunnel_server_config};
+
+/// Start a tunnel server without auth or rate limiting, returning its ports
+async fn start_test_server(storage_dir: &TempDir) -> (u16, u16, tokio::task::JoinHandle<()>) {
+    let config = tunnel_server_config("zip.test");
+    let tunnel_port = config.tunnel_port;
+    let public_port = config.public_port;
+    let handle = start_tunnel_server(config, storage_dir).await;
+    (tunnel_port, public_port, handle)
+}
+
+/// Run a tunnel client that echoes request bodies back, returning the accepted capabilities
+/// and whether the forwarded request arrived compressed
+async fn run_echo_client(
+    tunnel_port: u16,
+    subdomain: &str,
+    protocol_version: &str,
+    offered_capabilities: &[&str]
+) -> (Vec<String>, tokio::task::JoinHandle<bool>) {
+    let url = format!("ws://127.0.0.1:{}/connect", tunnel_port);
+    let (socket, _) = connect_async(url.as_str()).await.unwrap();
+    let (mut ws_sender, mut ws_receiver) = socket.split();
+
+    let auth = TunnelMessage::Auth {
+        token: "any-token".to_string(),
+        subdomain: Some(subdomain.to_string()),
+        protocol_version: protocol_version.to_string(),
+        capabilities: offered_capabilities.iter().map(|cap| cap.to_string()).collect(),
+    };
+    ws_sender.send(Message::Text(serde_json::to_string(&auth).unwrap())).await.unwrap();
+
+    let reply = ws_receiver.next().await.unwrap().unwrap();
+    let capabilities = match serde_json::from_str(reply.to_text().unwrap()).unwrap() {
+        TunnelMessage::AuthResponse { success: true, capabilities, .. } => capabilities,
+        other => panic!("Authentication failed: {:?}", other),
+    };
+    let compress = !capabilities.is_empty();
+
+    let handle = tokio::spawn(async move {
+        let (request, was_compressed) = match ws_receiver.next().await.unwrap().unwrap() {
+            Message::Text(text) => (TunnelProtocol::decode_frame(text.as_bytes(), compress).unwrap(), false),
+            Message::Binary(data) => (TunnelProtocol::decode_frame(&data, compress).unwrap(), true),
+            other => panic!("Unexpected frame: {:?}", other),
+        };
+        let (id, body) = match request {
+            TunnelMessage::HttpRequest { id, body, .. } => (id, body),
+            other => panic!("Expected HttpRequest, got {:?}", other),
+        };
+
+        let response = TunnelProtocol::create_http_response_message(&id, 200, HashMap::new(), body);
+        let frame = match TunnelProtocol::encode_frame(&response, compress).unwrap() {
+            TunnelFrame::Text(text) => Message::Text(text),
+            TunnelFrame::Binary(data) => Message::Binary(data),
+        };
+        ws_sender.send(frame).await.unwrap();
+        was_compressed
+    });
+
+    (capabilities, handle)
+}
+
+/// Text-heavy body well above the compression threshold
+fn large_body() -> String {
+    "<li>tunneled content that compresses well</li>\n".repeat(2000)
+}
+
+#[tokio::test]
+async fn test_large_body_round_trips_with_compression() {
+    let storage_dir = TempDir::new().unwrap();
+    let (tunnel_port, public_port, server_handle) = start_test_server(&storage_dir).await;
+
+    let (capabilities, client_handle) = run_echo_client(tunnel_port, "zip-on", "1.0+deflate", &[]).await;
+    assert_eq!(capabilities, vec!["deflate".to_string()]);
+
+    let body = large_body();
+    let response = reqwest::Client::new()
+        .post(format!("http://127.0.0.1:{}/echo", public_port))
+        .header("host", "zip-on.zip.test")
+        .body(body.clone())
+        .send().await.unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), body);
+    assert!(client_handle.await.unwrap(), "Large request should arrive compressed");
+
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn test_peer_without_compression_still_interoperates() {
+    let storage_dir = TempDir::new().unwrap();
+    let (tunnel_port, public_port, server_handle) = start_test_server(&storage_dir).await;
+
+    let (capabilities, client_handle) = run_echo_client(tunnel_port, "zip-off", "1.0", &[]).await;
+    assert!(capabilities.is_empty());
+
+    let body = large_body();
+    let response = reqwest::Client::new()
+        .post(format!("http://127.0.0.1:{}/echo", public_port))
+        .header("host", "zip-off.zip.test")
+        .body(body.clone())
+        .send().await.unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), body);
+    assert!(!client_handle.await.unwrap(), "Legacy peer must receive plain text frames");
+
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn test_capability_list_negotiates_supported_subset() {
+    let storage_dir = TempDir::new().unwrap();
+    let (tunnel_port, public_port, server_handle) = start_test_server(&storage_dir).await;
+
+    // The server echoes only what it supports; unknown features are dropped
+    let (capabilities, client_handle) =
+        run_echo_client(tunnel_port, "zip-list", "1.0", &["multiplex-v2", "deflate"]).await;
+    assert_eq!(capabilities, vec!["deflate".to_string()]);
+
+    let body = large_body();
+    let response = reqwest::Client::new()
+        .post(format!("http://127.0.0.1:{}/echo", public_port))
+        .header("host", "zip-list.zip.test")
+        .body(body.clone())
+        .send().await.unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), body);
+    assert!(client_handle.await.unwrap(), "Negotiated deflate should compress large requests");
+
+    server_handle.abort();
+}
@@ -6,6 +6,7 @@ use httpserver_tunnel::server::TunnelServer;
 use std::time::Duration;
 use tokio::time::sleep;
 use tokio::net::TcpListener;
+use tempfile::TempDir;
 
 /// Test helper to find available ports
 async fn find_available_ports(count: usize) -> Vec<u16> {
@@ -72,7 +73,8 @@ async fn test_server_startup_with_valid_bind_addresses() {
     let ports = find_available_ports(3).await;
     let config = create_test_config(ports[0], ports[1], ports[2]);
     
-    let server = TunnelServer::new(config.clone()).unwrap();
+    let storage_dir = TempDir::new().unwrap();
+    let server = TunnelServer::with_storage_path(config.clone(), storage_dir.path().join("subdomains.json")).unwrap();
     
     // Start server in background task
     let server_handle = tokio::spawn(async move {
@@ -104,7 +106,8 @@ async fn test_server_with_invalid_bind_address() {
     // Use an invalid bind address
     config.network.bind_address = "999.999.999.999".to_string();
     
-    let server = TunnelServer::new(config).unwrap();
+    let storage_dir = TempDir::new().unwrap();
+    let server = TunnelServer::with_storage_path(config, storage_dir.path().join("subdomains.json")).unwrap();
     
     // Server should fail to start due to invalid bind address
     let result = server.start().await;
@@ -144,8 +147,9 @@ async fn test_port_binding_conflicts() {
     let config1 = create_test_config(ports[0], ports[1], 8443);
     let config2 = create_test_config(ports[0], 8082, 8444); // Same tunnel port
     
-    let server1 = TunnelServer::new(config1).unwrap();
-    let server2 = TunnelServer::new(config2).unwrap();
+    let storage_dir = TempDir::new().unwrap();
+    let server1 = TunnelServer::with_storage_path(config1, storage_dir.path().join("subdomains1.json")).unwrap();
+    let server2 = TunnelServer::with_storage_path(config2, storage_dir.path().join("subdomains2.json")).unwrap();
     
     // Start first server
     let server1_handle = tokio::spawn(async move {
@@ -180,7 +184,8 @@ async fn test_health_endpoint_availability() {
     let ports = find_available_ports(3).await;
     let config = create_test_config(ports[0], ports[1], ports[2]);
     
-    let server = TunnelServer::new(config.clone()).unwrap();
+    let storage_dir = TempDir::new().unwrap();
+    let server = TunnelServer::with_storage_path(config.clone(), storage_dir.path().join("subdomains.json")).unwrap();
     
     // Start server in background
     let server_handle = tokio::spawn(async move {
@@ -252,7 +257,8 @@ async fn test_complete_port_configuration_flow() {
     assert_eq!(config.network.public_bind_address, "127.0.0.1");
     
     // Create server
-    let server = TunnelServer::new(config.clone()).unwrap();
+    let storage_dir = TempDir::new().unwrap();
+    let server = TunnelServer::with_storage_path(config.clone(), storage_dir.path().join("subdomains.json")).unwrap();
     
     // Start server
     let server_handle = tokio::spawn(async move {
@@ -31,6 +31,8 @@ async fn test_tunnel_endpoint_creation() {
         connection_timeout: 30,
         keepalive_interval: 30,
         max_connections: 100,
+        compression: false,
+        proxy_protocol: None,
     };
 
     assert_eq!(endpoint.server_url, "wss://tunnel.example.com/connect");
@@ -63,10 +65,12 @@ async fn test_tunnel_auth_config_api_key() {
 
 #[tokio::test]
 async fn test_tunnel_auth_config_token() {
-    let mut refresh_config = TokenRefreshConfig::default();
-    refresh_config.enabled = true;
-    refresh_config.refresh_url = Some("https://auth.example.com/refresh".to_string());
-    refresh_config.interval = 3600;
+    let refresh_config = TokenRefreshConfig {
+        enabled: true,
+        refresh_url: Some("https://auth.example.com/refresh".to_string()),
+        interval: 3600,
+        ..Default::default()
+    };
 
     let auth_config = TunnelAuthConfig {
         method: "token".to_string(),
@@ -172,9 +176,11 @@ async fn test_subdomain_strategy_enum() {
 
 #[tokio::test]
 async fn test_tunnel_config_serialization() {
-    let mut config = TunnelConfig::default();
-    config.enabled = true;
-    config.local_port = Some(3000);
+    let mut config = TunnelConfig {
+        enabled: true,
+        local_port: Some(3000),
+        ..Default::default()
+    };
     
     let endpoint = TunnelEndpoint {
         server_url: "wss://tunnel.example.com".to_string(),
@@ -184,6 +190,8 @@ async fn test_tunnel_config_serialization() {
         connection_timeout: 30,
         keepalive_interval: 30,
         max_connections: 100,
+        compression: false,
+        proxy_protocol: None,
     };
     config.endpoints.push(endpoint);
 
@@ -195,7 +203,7 @@ async fn test_tunnel_config_serialization() {
 
     // Test deserialization
     let deserialized: TunnelConfig = serde_json::from_str(&json).unwrap();
-    assert_eq!(deserialized.enabled, true);
+    assert!(deserialized.enabled);
     assert_eq!(deserialized.local_port, Some(3000));
     assert_eq!(deserialized.endpoints.len(), 1);
     assert_eq!(deserialized.endpoints[0].server_url, "wss://tunnel.example.com");
@@ -222,6 +230,8 @@ async fn test_complete_tunnel_config() {
         connection_timeout: 30,
         keepalive_interval: 30,
         max_connections: 100,
+        compression: false,
+        proxy_protocol: None,
     };
 
     let config = TunnelConfig {
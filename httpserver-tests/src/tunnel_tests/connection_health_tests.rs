@@ -0,0 +1,143 @@
+//! Connection Health Tests
+//! Keep-alive ping/pong cycles feed the client's latency, last ping and health score
+
+use httpserver_tunnel::TunnelClient;
+use httpserver_tunnel::config::{TunnelConfig, TunnelEndpoint, TunnelAuthConfig, ReconnectionConfig};
+use httpserver_tunnel::protocol::TunnelMessage;
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+/// Fake tunnel server that accepts the client's Auth and answers its keep-alive pings
+/// after `pong_delay`, skipping every ping for which `answer` returns false.
+/// Each answered ping is reported on the returned channel.
+async fn start_pong_server(pong_delay: Duration, answer: fn(u32) -> bool) -> (u16, mpsc::UnboundedReceiver<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (pong_tx, pong_rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let socket = loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            if let Ok(socket) = accept_async(stream).await {
+                break socket;
+            }
+        };
+        let (mut sender, mut receiver) = socket.split();
+
+        let mut pings = 0;
+        while let Some(Ok(Message::Text(text))) = receiver.next().await {
+            let reply = match serde_json::from_str(&text) {
+                Ok(TunnelMessage::Auth { .. }) => TunnelMessage::AuthResponse {
+                    success: true,
+                    assigned_subdomain: Some("myapp".to_string()),
+                    error: None,
+                    public_url: Some("http://myapp.httpserver.io".to_string()),
+                    capabilities: vec![],
+                },
+                Ok(TunnelMessage::Ping { timestamp }) => {
+                    pings += 1;
+                    if !answer(pings) {
+                        continue;
+                    }
+                    tokio::time::sleep(pong_delay).await;
+                    let _ = pong_tx.send(());
+                    TunnelMessage::Pong { timestamp }
+                }
+                _ => continue,
+            };
+            let _ = sender.send(Message::Text(serde_json::to_string(&reply).unwrap())).await;
+        }
+    });
+
+    (port, pong_rx)
+}
+
+fn create_client_config(port: u16) -> TunnelConfig {
+    let auth = TunnelAuthConfig {
+        method: "api_key".to_string(),
+        api_key: Some("test-api-key".to_string()),
+        token: None,
+        cert_file: None,
+        key_file: None,
+        user: None,
+        headers: HashMap::new(),
+        token_refresh: Default::default(),
+    };
+
+    let endpoint = TunnelEndpoint {
+        server_url: format!("ws://127.0.0.1:{}/connect", port),
+        subdomain: Some("myapp".to_string()),
+        custom_domain: None,
+        protocol_version: "1.0".to_string(),
+        connection_timeout: 5,
+        keepalive_interval: 1,
+        max_connections: 1,
+        compression: false,
+        proxy_protocol: None,
+    };
+
+    TunnelConfig {
+        enabled: true,
+        local_port: Some(3000),
+        local_host: "127.0.0.1".to_string(),
+        endpoints: vec![endpoint],
+        auth,
+        reconnection: ReconnectionConfig { enabled: false, ..Default::default() },
+        monitoring: Default::default(),
+        ssl: Default::default(),
+        server: Default::default(),
+    }
+}
+
+/// Wait for the fake server to answer `count` pings, then let the client read the last pong
+async fn wait_for_pongs(pongs: &mut mpsc::UnboundedReceiver<()>, count: usize) {
+    for _ in 0..count {
+        timeout(Duration::from_secs(5), pongs.recv()).await
+            .expect("Server stopped receiving pings")
+            .unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+}
+
+#[tokio::test]
+async fn test_ping_cycles_populate_latency_and_health() {
+    let (port, mut pongs) = start_pong_server(Duration::from_millis(50), |_| true).await;
+    let mut client = TunnelClient::new(create_client_config(port), 3000).unwrap();
+    client.start().await.unwrap();
+
+    wait_for_pongs(&mut pongs, 3).await;
+    let report = client.status_report().await;
+    let tunnel = &report.tunnels[0];
+
+    let latency = tunnel.avg_ping_latency_ms.expect("No ping latency recorded");
+    assert!((50..1000).contains(&latency), "Unexpected latency: {}ms", latency);
+    let last_ping = tunnel.last_ping.expect("No ping recorded");
+    assert!(chrono::Utc::now().signed_duration_since(last_ping).num_seconds() < 5);
+
+    // Fresh but answering every ping promptly: only the short uptime costs points
+    assert_eq!(tunnel.health_score, 90);
+
+    client.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_unanswered_pings_lower_health_score() {
+    // Only every other ping is answered
+    let (port, mut pongs) = start_pong_server(Duration::ZERO, |ping| ping % 2 == 1).await;
+    let mut client = TunnelClient::new(create_client_config(port), 3000).unwrap();
+    client.start().await.unwrap();
+
+    wait_for_pongs(&mut pongs, 2).await;
+    let report = client.status_report().await;
+    let tunnel = &report.tunnels[0];
+
+    assert!(tunnel.avg_ping_latency_ms.is_some());
+    assert!(tunnel.last_ping.is_some());
+    assert!(tunnel.health_score > 0 && tunnel.health_score < 90, "Unexpected score: {}", tunnel.health_score);
+
+    client.stop().await.unwrap();
+}
@@ -101,6 +101,8 @@ async fn test_tunnel_endpoint_creation() {
         connection_timeout: 30,
         keepalive_interval: 30,
         max_connections: 100,
+        compression: false,
+        proxy_protocol: None,
     };
 
     assert_eq!(endpoint.server_url, "wss://tunnel.example.com/connect");
@@ -122,6 +124,8 @@ async fn test_tunnel_endpoint_with_custom_domain() {
         connection_timeout: 60,
         keepalive_interval: 15,
         max_connections: 50,
+        compression: false,
+        proxy_protocol: None,
     };
 
     assert_eq!(endpoint.custom_domain, Some("api.mycompany.com".to_string()));
@@ -0,0 +1,111 @@
+//! Idle Tunnel Eviction Tests
+//! Tests that the tunnel server evicts tunnels whose clients stop talking without closing
+
+use httpserver_tunnel::config::TunnelServerConfig;
+use httpserver_tunnel::protocol::TunnelMessage;
+use futures_util::{SinkExt, StreamExt};
+use tempfile::TempDir;
+use tokio::net::TcpStream;
+use tokio::time::{sleep, timeout, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use crate::test_support::{start_tunnel_server, tunnel_server_config};
+
+type TestSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Create a server configuration with a short idle timeout and no auth
+fn create_idle_test_config() -> TunnelServerConfig {
+    let mut config = tunnel_server_config("idle.test");
+    config.idle_timeout = 1;
+    config
+}
+
+
+/// Connect to the tunnel endpoint and authenticate with the requested subdomain
+async fn connect_tunnel(tunnel_port: u16, subdomain: &str) -> (TestSocket, TunnelMessage) {
+    let url = format!("ws://127.0.0.1:{}/connect", tunnel_port);
+    let (mut socket, _) = connect_async(url.as_str()).await.expect("tunnel connect failed");
+
+    let auth = TunnelMessage::Auth {
+        token: "any-token".to_string(),
+        subdomain: Some(subdomain.to_string()),
+        protocol_version: "1.0".to_string(),
+        capabilities: vec![],
+    };
+    socket.send(Message::Text(serde_json::to_string(&auth).unwrap())).await.unwrap();
+
+    let reply = timeout(Duration::from_secs(5), socket.next()).await
+        .expect("no auth response")
+        .expect("socket closed")
+        .expect("socket error");
+    let reply = serde_json::from_str(reply.to_text().unwrap()).unwrap();
+    (socket, reply)
+}
+
+/// Read the active tunnel count from the tunnel server health endpoint
+async fn active_tunnel_count(tunnel_port: u16) -> u64 {
+    let body: serde_json::Value = reqwest::get(format!("http://127.0.0.1:{}/health", tunnel_port))
+        .await.unwrap()
+        .json().await.unwrap();
+    body["active_tunnels"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn test_silent_tunnel_is_evicted_and_subdomain_released() {
+    let storage_dir = TempDir::new().unwrap();
+    let config = create_idle_test_config();
+    let tunnel_port = config.tunnel_port;
+    let public_port = config.public_port;
+    let server_handle = start_tunnel_server(config, &storage_dir).await;
+
+    let (mut socket, reply) = connect_tunnel(tunnel_port, "silent-app").await;
+    assert!(matches!(reply, TunnelMessage::AuthResponse { success: true, .. }));
+    assert_eq!(active_tunnel_count(tunnel_port).await, 1);
+
+    // Stay silent: the server should close the socket once the idle timeout passes
+    let closed = timeout(Duration::from_secs(5), async {
+        loop {
+            match socket.next().await {
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                Some(Ok(_)) => continue,
+            }
+        }
+    }).await;
+    assert!(closed.is_ok(), "Idle tunnel was not closed by the server");
+    assert_eq!(active_tunnel_count(tunnel_port).await, 0);
+
+    // Public traffic for the evicted subdomain no longer routes anywhere
+    let response = reqwest::Client::new()
+        .get(format!("http://127.0.0.1:{}/", public_port))
+        .header("host", "silent-app.idle.test")
+        .send().await.unwrap();
+    assert_eq!(response.status(), 404);
+
+    // The subdomain was released and can be claimed again
+    let (_socket, reply) = connect_tunnel(tunnel_port, "silent-app").await;
+    assert!(matches!(reply, TunnelMessage::AuthResponse { success: true, .. }));
+
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn test_active_tunnel_is_not_evicted() {
+    let storage_dir = TempDir::new().unwrap();
+    let config = create_idle_test_config();
+    let tunnel_port = config.tunnel_port;
+    let server_handle = start_tunnel_server(config, &storage_dir).await;
+
+    let (mut socket, reply) = connect_tunnel(tunnel_port, "chatty-app").await;
+    assert!(matches!(reply, TunnelMessage::AuthResponse { success: true, .. }));
+
+    // Keep pinging well within the idle timeout
+    for _ in 0..6 {
+        let ping = TunnelMessage::Ping { timestamp: 0 };
+        socket.send(Message::Text(serde_json::to_string(&ping).unwrap())).await.unwrap();
+        sleep(Duration::from_millis(400)).await;
+    }
+
+    assert_eq!(active_tunnel_count(tunnel_port).await, 1);
+
+    server_handle.abort();
+}
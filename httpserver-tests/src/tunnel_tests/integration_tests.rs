@@ -28,6 +28,8 @@ fn create_test_tunnel_config() -> TunnelConfig {
         connection_timeout: 5, // Short timeout for tests
         keepalive_interval: 10,
         max_connections: 1,
+        compression: false,
+        proxy_protocol: None,
     };
 
     TunnelConfig {
@@ -58,8 +60,7 @@ async fn test_tunnel_client_creation() {
     // Test that we can get the current status (returns a vector)
     let statuses = client.get_status().await;
       // Initially should have no active tunnels (or empty status)
-    // Status vector length is always >= 0, so just check if it's accessible
-    assert!(statuses.len() == 0 || statuses.len() > 0);
+    assert!(statuses.is_empty());
     
     // Test connection count
     let connection_count = client.connection_count().await;
@@ -144,14 +145,16 @@ async fn test_tunnel_client_multiple_endpoints() {
         connection_timeout: 5,
         keepalive_interval: 10,
         max_connections: 1,
+        compression: false,
+        proxy_protocol: None,
     };
     config.endpoints.push(endpoint2);
     
     let client = TunnelClient::new(config, 3000).unwrap();
       // Should be able to create client with multiple endpoints
     let statuses = client.get_status().await;
-    // Vector access is always valid, just check it's accessible
-    assert!(statuses.len() == 0 || statuses.len() > 0);
+    // No endpoint reports a status before the client is started
+    assert!(statuses.is_empty());
 }
 
 #[tokio::test]
@@ -225,10 +228,12 @@ async fn test_health_score_calculation() {
     assert_eq!(score, 0);
     
     // Update to connected state
-    let mut health = httpserver_tunnel::status::ConnectionHealth::default();
-    health.state = ConnectionState::Connected;
-    health.retry_count = 0;
-    health.last_ping = Some(chrono::Utc::now());
+    let health = httpserver_tunnel::status::ConnectionHealth {
+        state: ConnectionState::Connected,
+        retry_count: 0,
+        last_ping: Some(chrono::Utc::now()),
+        ..Default::default()
+    };
     
     monitor.update_health(health);
     
@@ -259,6 +264,6 @@ async fn test_status_subscription() {
     let status_receiver = client.subscribe_status();
       // Should be able to get initial status
     let initial_status = status_receiver.borrow().clone();
-    // Vector access is always valid, just check it's accessible  
-    assert!(initial_status.len() == 0 || initial_status.len() > 0);
+    // Nothing has connected yet
+    assert!(initial_status.is_empty());
 }
@@ -1,9 +1,22 @@
 pub mod auth_tests;
+pub mod backpressure_tests;
+pub mod capacity_tests;
+pub mod compression_tests;
 pub mod configuration_tests;
+pub mod connection_health_tests;
 pub mod config_integration;
+pub mod idle_eviction_tests;
 pub mod connection_tests;
 pub mod integration_tests;
+pub mod multiplexing_tests;
+pub mod proxy_protocol_tests;
+pub mod public_url_tests;
+pub mod request_timeout_tests;
 pub mod server_tests;
+pub mod sni_tests;
+pub mod ssl_passthrough_tests;
+pub mod status_endpoint_tests;
 pub mod status_tests;
 pub mod subdomain_integration;
+pub mod subdomain_pool_tests;
 pub mod tunnel_http_forwarding;
@@ -0,0 +1,103 @@
+//! Tunnel Multiplexing Tests
+//! Tests for serving several subdomains over a single tunnel client connection
+
+use httpserver_tunnel::protocol::{TunnelMessage, TunnelProtocol};
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use tempfile::TempDir;
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use crate::test_support::{start_tunnel_server, tunnel_server_config};
+
+/// Send a tunnel message as a text frame
+fn text(message: &TunnelMessage) -> Message {
+    Message::Text(serde_json::to_string(message).unwrap())
+}
+
+#[tokio::test]
+async fn test_two_subdomains_over_one_connection() {
+    let storage_dir = TempDir::new().unwrap();
+    let config = tunnel_server_config("mux.test");
+    let tunnel_port = config.tunnel_port;
+    let public_port = config.public_port;
+    let server_handle = start_tunnel_server(config, &storage_dir).await;
+
+    let url = format!("ws://127.0.0.1:{}/connect", tunnel_port);
+    let (socket, _) = connect_async(url.as_str()).await.unwrap();
+    let (mut ws_sender, mut ws_receiver) = socket.split();
+
+    // Authenticate once, registering the first subdomain
+    let auth = TunnelProtocol::create_auth_message("any-token", Some("mux-web"));
+    ws_sender.send(text(&auth)).await.unwrap();
+    let reply = ws_receiver.next().await.unwrap().unwrap();
+    let reply: TunnelMessage = serde_json::from_str(reply.to_text().unwrap()).unwrap();
+    assert!(matches!(reply, TunnelMessage::AuthResponse { success: true, .. }));
+
+    // Open a second logical tunnel on the same socket
+    let open = TunnelProtocol::create_open_tunnel_message("api", Some("mux-api"));
+    ws_sender.send(text(&open)).await.unwrap();
+    let reply = ws_receiver.next().await.unwrap().unwrap();
+    let reply: TunnelMessage = serde_json::from_str(reply.to_text().unwrap()).unwrap();
+    match reply {
+        TunnelMessage::TunnelOpened { tunnel_id, success, assigned_subdomain, .. } => {
+            assert_eq!(tunnel_id, "api");
+            assert!(success);
+            assert_eq!(assigned_subdomain, Some("mux-api".to_string()));
+        }
+        other => panic!("Expected TunnelOpened, got {:?}", other),
+    }
+
+    // Answer every forwarded request with the logical tunnel it arrived on
+    let client_task = tokio::spawn(async move {
+        while let Some(Ok(Message::Text(frame))) = ws_receiver.next().await {
+            if let Ok(TunnelMessage::HttpRequest { id, path, tunnel_id, .. }) = serde_json::from_str(&frame) {
+                let body = format!("{}:{}", tunnel_id.unwrap_or_else(|| "primary".to_string()), path);
+                let response = TunnelProtocol::create_http_response_message(
+                    &id,
+                    200,
+                    HashMap::new(),
+                    Some(body.into_bytes())
+                );
+                ws_sender.send(text(&response)).await.unwrap();
+            }
+        }
+    });
+
+    let client = reqwest::Client::new();
+    let public_url = format!("http://127.0.0.1:{}", public_port);
+
+    let web = client.get(format!("{}/index", public_url))
+        .header("host", "mux-web.mux.test")
+        .send().await.unwrap();
+    assert_eq!(web.status(), 200);
+    assert_eq!(web.text().await.unwrap(), "primary:/index");
+
+    let api = client.get(format!("{}/users", public_url))
+        .header("host", "mux-api.mux.test")
+        .send().await.unwrap();
+    assert_eq!(api.status(), 200);
+    assert_eq!(api.text().await.unwrap(), "api:/users");
+
+    client_task.abort();
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn test_open_tunnel_requires_authentication() {
+    let storage_dir = TempDir::new().unwrap();
+    let config = tunnel_server_config("mux.test");
+    let tunnel_port = config.tunnel_port;
+    let server_handle = start_tunnel_server(config, &storage_dir).await;
+
+    let url = format!("ws://127.0.0.1:{}/connect", tunnel_port);
+    let (mut socket, _) = connect_async(url.as_str()).await.unwrap();
+
+    let open = TunnelProtocol::create_open_tunnel_message("api", Some("mux-noauth"));
+    socket.send(text(&open)).await.unwrap();
+    let reply = timeout(Duration::from_secs(5), socket.next()).await.unwrap().unwrap().unwrap();
+    let reply: TunnelMessage = serde_json::from_str(reply.to_text().unwrap()).unwrap();
+    assert!(matches!(reply, TunnelMessage::TunnelOpened { success: false, .. }));
+
+    server_handle.abort();
+}
@@ -0,0 +1,51 @@
+//! PROXY Protocol Tests
+//! Tests for the headers that carry the client address to passthrough backends
+
+use httpserver_tunnel::ProxyProtocolVersion;
+use httpserver_tunnel::protocol::TunnelProtocol;
+use std::net::SocketAddr;
+
+fn addr(value: &str) -> SocketAddr {
+    value.parse().unwrap()
+}
+
+#[test]
+fn test_v1_header() {
+    let header = ProxyProtocolVersion::V1.header(addr("192.0.2.10:56324"), addr("198.51.100.1:443"));
+    assert_eq!(header, b"PROXY TCP4 192.0.2.10 198.51.100.1 56324 443\r\n");
+
+    let header = ProxyProtocolVersion::V1.header(addr("[2001:db8::1]:56324"), addr("[2001:db8::2]:443"));
+    assert_eq!(header, b"PROXY TCP6 2001:db8::1 2001:db8::2 56324 443\r\n");
+
+    // Mixed families are both described as IPv6
+    let header = ProxyProtocolVersion::V1.header(addr("192.0.2.10:56324"), addr("[2001:db8::2]:443"));
+    assert_eq!(header, b"PROXY TCP6 ::ffff:192.0.2.10 2001:db8::2 56324 443\r\n");
+}
+
+#[test]
+fn test_v2_header() {
+    let header = ProxyProtocolVersion::V2.header(addr("192.0.2.10:56324"), addr("198.51.100.1:443"));
+    let mut expected = vec![0x0d, 0x0a, 0x0d, 0x0a, 0x00, 0x0d, 0x0a, 0x51, 0x55, 0x49, 0x54, 0x0a];
+    expected.extend([0x21, 0x11, 0x00, 0x0c]);
+    expected.extend([192, 0, 2, 10, 198, 51, 100, 1]);
+    expected.extend(56324u16.to_be_bytes());
+    expected.extend(443u16.to_be_bytes());
+    assert_eq!(header, expected);
+
+    let header = ProxyProtocolVersion::V2.header(addr("[2001:db8::1]:1"), addr("[2001:db8::2]:2"));
+    assert_eq!(header.len(), 16 + 36);
+    assert_eq!(&header[12..16], &[0x21, 0x21, 0x00, 0x24]);
+}
+
+#[test]
+fn test_version_follows_negotiated_capabilities() {
+    let offered = vec!["proxy-v1".to_string()];
+    let agreed = TunnelProtocol::negotiate_capabilities("1.0", &offered);
+    assert_eq!(ProxyProtocolVersion::from_capabilities(&agreed), Some(ProxyProtocolVersion::V1));
+
+    assert_eq!(ProxyProtocolVersion::from_capabilities(&["deflate".to_string()]), None);
+
+    // Endpoint configuration names the version
+    let version: ProxyProtocolVersion = serde_json::from_str("\"v2\"").unwrap();
+    assert_eq!(version, ProxyProtocolVersion::V2);
+}
@@ -0,0 +1,117 @@
+//! Public URL Tests
+//! The server reports each tunnel's public URL and the client uses it as given
+
+use httpserver_tunnel::TunnelClient;
+use httpserver_tunnel::config::{
+    TunnelConfig, TunnelEndpoint, TunnelAuthConfig, ReconnectionConfig,
+};
+use httpserver_tunnel::protocol::{TunnelMessage, TunnelProtocol};
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use tempfile::TempDir;
+use tokio::time::{sleep, Duration, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use crate::test_support::{free_port, start_tunnel_server, tunnel_server_config};
+
+fn create_client_config(tunnel_port: u16) -> TunnelConfig {
+    let auth = TunnelAuthConfig {
+        method: "api_key".to_string(),
+        api_key: Some("test-api-key".to_string()),
+        token: None,
+        cert_file: None,
+        key_file: None,
+        user: None,
+        headers: HashMap::new(),
+        token_refresh: Default::default(),
+    };
+
+    let endpoint = TunnelEndpoint {
+        server_url: format!("ws://127.0.0.1:{}/connect", tunnel_port),
+        subdomain: Some("myapp".to_string()),
+        custom_domain: None,
+        protocol_version: "1.0".to_string(),
+        connection_timeout: 5,
+        keepalive_interval: 30,
+        max_connections: 1,
+        compression: false,
+        proxy_protocol: None,
+    };
+
+    TunnelConfig {
+        enabled: true,
+        local_port: Some(3000),
+        local_host: "127.0.0.1".to_string(),
+        endpoints: vec![endpoint],
+        auth,
+        reconnection: ReconnectionConfig { enabled: false, ..Default::default() },
+        monitoring: Default::default(),
+        ssl: Default::default(),
+        server: Default::default(),
+    }
+}
+
+#[test]
+fn test_public_url_follows_scheme_domain_and_port() {
+    let mut config = tunnel_server_config("tunnels.example.com");
+    config.public_port = 80;
+    assert_eq!(config.public_url("myapp"), "http://myapp.tunnels.example.com");
+
+    config.public_port = 8080;
+    assert_eq!(config.public_url("myapp"), "http://myapp.tunnels.example.com:8080");
+
+    // With SSL the HTTPS port applies instead
+    config.ssl.enabled = true;
+    assert_eq!(config.public_url("myapp"), "https://myapp.tunnels.example.com");
+    config.public_https_port = 8443;
+    assert_eq!(config.public_url("myapp"), "https://myapp.tunnels.example.com:8443");
+}
+
+#[tokio::test]
+async fn test_auth_response_carries_public_url() {
+    let storage_dir = TempDir::new().unwrap();
+    let config = tunnel_server_config("tunnels.example.com");
+    let tunnel_port = config.tunnel_port;
+    let public_port = config.public_port;
+    start_tunnel_server(config, &storage_dir).await;
+
+    let url = format!("ws://127.0.0.1:{}/connect", tunnel_port);
+    let (mut socket, _) = connect_async(url.as_str()).await.unwrap();
+    let auth = TunnelProtocol::create_auth_message("any-token", Some("myapp"));
+    socket.send(Message::Text(serde_json::to_string(&auth).unwrap())).await.unwrap();
+    let reply = socket.next().await.unwrap().unwrap();
+
+    match serde_json::from_str(reply.to_text().unwrap()).unwrap() {
+        TunnelMessage::AuthResponse { success: true, public_url, .. } => {
+            assert_eq!(public_url, Some(format!("http://myapp.tunnels.example.com:{}", public_port)));
+        }
+        other => panic!("Expected successful AuthResponse, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_client_reports_https_public_url_from_server() {
+    let storage_dir = TempDir::new().unwrap();
+    let mut config = tunnel_server_config("secure.example.org");
+    config.ssl.enabled = true;
+    config.public_https_port = free_port();
+    let tunnel_port = config.tunnel_port;
+    let https_port = config.public_https_port;
+    start_tunnel_server(config, &storage_dir).await;
+
+    let mut client = TunnelClient::new(create_client_config(tunnel_port), 3000).unwrap();
+    client.start().await.unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let public_url = loop {
+        let report = client.status_report().await;
+        if let Some(public_url) = report.tunnels.first().and_then(|tunnel| tunnel.public_url.clone()) {
+            break public_url;
+        }
+        assert!(Instant::now() < deadline, "Client never reported a public URL");
+        sleep(Duration::from_millis(20)).await;
+    };
+    assert_eq!(public_url, format!("https://myapp.secure.example.org:{}", https_port));
+
+    client.stop().await.unwrap();
+}
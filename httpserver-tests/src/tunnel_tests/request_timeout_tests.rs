@@ -0,0 +1,99 @@
+//! Tunnel Request Timeout Tests
+//! Tests for the configurable wait on tunneled responses
+
+use httpserver_tunnel::config::TunnelServerConfig;
+use httpserver_tunnel::protocol::{TunnelMessage, TunnelProtocol};
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::time::Instant;
+use tempfile::TempDir;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use crate::test_support::{start_tunnel_server, tunnel_server_config};
+
+/// Create a server configuration with a one-second response wait
+fn create_timeout_test_config() -> TunnelServerConfig {
+    let mut config = tunnel_server_config("timeout.test");
+    config.request_timeout_seconds = 1;
+    config
+}
+
+
+/// Send a tunnel message as a text frame
+fn text(message: &TunnelMessage) -> Message {
+    Message::Text(serde_json::to_string(message).unwrap())
+}
+
+#[tokio::test]
+async fn test_slow_tunnel_response_times_out() {
+    let storage_dir = TempDir::new().unwrap();
+    let config = create_timeout_test_config();
+    let tunnel_port = config.tunnel_port;
+    let public_port = config.public_port;
+    let server_handle = start_tunnel_server(config, &storage_dir).await;
+
+    let url = format!("ws://127.0.0.1:{}/connect", tunnel_port);
+    let (socket, _) = connect_async(url.as_str()).await.unwrap();
+    let (mut ws_sender, mut ws_receiver) = socket.split();
+    let auth = TunnelProtocol::create_auth_message("any-token", Some("slow-local"));
+    ws_sender.send(text(&auth)).await.unwrap();
+    let reply = ws_receiver.next().await.unwrap().unwrap();
+    let reply: TunnelMessage = serde_json::from_str(reply.to_text().unwrap()).unwrap();
+    assert!(matches!(reply, TunnelMessage::AuthResponse { success: true, .. }));
+
+    // Answer /slow after two seconds and everything else at once
+    let (response_tx, mut response_rx) = mpsc::unbounded_channel::<TunnelMessage>();
+    let client_task = tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                frame = ws_receiver.next() => {
+                    let Some(Ok(Message::Text(frame))) = frame else { break };
+                    if let Ok(TunnelMessage::HttpRequest { id, path, .. }) = serde_json::from_str(&frame) {
+                        let delay = if path == "/slow" { Duration::from_secs(2) } else { Duration::ZERO };
+                        let response_tx = response_tx.clone();
+                        tokio::spawn(async move {
+                            sleep(delay).await;
+                            let response = TunnelProtocol::create_http_response_message(
+                                &id,
+                                200,
+                                HashMap::new(),
+                                Some(path.into_bytes())
+                            );
+                            let _ = response_tx.send(response);
+                        });
+                    }
+                }
+                Some(response) = response_rx.recv() => {
+                    ws_sender.send(text(&response)).await.unwrap();
+                }
+            }
+        }
+    });
+
+    let client = reqwest::Client::new();
+    let started = Instant::now();
+    let response = client.get(format!("http://127.0.0.1:{}/slow", public_port))
+        .header("host", "slow-local.timeout.test")
+        .send().await.unwrap();
+    assert_eq!(response.status(), 504);
+    assert!(started.elapsed() < Duration::from_secs(2), "Waited {:?}", started.elapsed());
+
+    // The timed-out request no longer occupies the pending table
+    let health_url = format!("http://127.0.0.1:{}/health", tunnel_port);
+    let health: serde_json::Value = client.get(&health_url).send().await.unwrap().json().await.unwrap();
+    assert_eq!(health["pending_requests"], 0);
+    assert_eq!(health["server_config"]["request_timeout_seconds"], 1);
+
+    // The late response is dropped and the tunnel keeps serving
+    sleep(Duration::from_millis(1500)).await;
+    let response = client.get(format!("http://127.0.0.1:{}/fast", public_port))
+        .header("host", "slow-local.timeout.test")
+        .send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "/fast");
+
+    client_task.abort();
+    server_handle.abort();
+}
@@ -179,7 +179,7 @@ async fn test_tunnel_protocol_messages() {
     // Test authentication message creation
     let auth_msg = TunnelProtocol::create_auth_message("test-token", Some("myapp"));
     match auth_msg {
-        TunnelMessage::Auth { token, subdomain, protocol_version } => {
+        TunnelMessage::Auth { token, subdomain, protocol_version, .. } => {
             assert_eq!(token, "test-token");
             assert_eq!(subdomain, Some("myapp".to_string()));
             assert_eq!(protocol_version, "1.0");
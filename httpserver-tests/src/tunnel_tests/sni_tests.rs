@@ -0,0 +1,152 @@
+//! SNI Parsing Tests
+//! Tests for reading the server name from fragmented, truncated and malformed Client Hellos
+
+use httpserver_tunnel::TunnelError;
+use httpserver_tunnel::sni::{parse_client_hello, read_client_hello, ClientHello};
+use tokio::io::{duplex, AsyncWriteExt};
+
+/// Big-endian two-byte length
+fn len16(len: usize) -> [u8; 2] {
+    (len as u16).to_be_bytes()
+}
+
+/// Client Hello body naming `hostname`, optionally behind a padding extension of `padding` bytes
+fn client_hello_body(hostname: Option<&str>, padding: usize) -> Vec<u8> {
+    let mut body = vec![0x03, 0x03];
+    body.extend([7u8; 32]); // random
+    body.push(32);
+    body.extend([1u8; 32]); // session id
+    body.extend([0x00, 0x04, 0x13, 0x01, 0x13, 0x02]); // cipher suites
+    body.extend([0x01, 0x00]); // compression methods
+
+    let mut extensions = Vec::new();
+    if padding > 0 {
+        extensions.extend([0x00, 0x15]);
+        extensions.extend(len16(padding));
+        extensions.extend(vec![0u8; padding]);
+    }
+    if let Some(hostname) = hostname {
+        let mut names = vec![0x00];
+        names.extend(len16(hostname.len()));
+        names.extend(hostname.bytes());
+        extensions.extend([0x00, 0x00]);
+        extensions.extend(len16(names.len() + 2));
+        extensions.extend(len16(names.len()));
+        extensions.extend(names);
+    }
+    body.extend(len16(extensions.len()));
+    body.extend(extensions);
+    body
+}
+
+/// Client Hello handshake message carried in records of at most `fragment` bytes
+fn wire(body: &[u8], fragment: usize) -> Vec<u8> {
+    let mut handshake = vec![0x01];
+    handshake.extend(&(body.len() as u32).to_be_bytes()[1..]);
+    handshake.extend(body);
+
+    let mut records = Vec::new();
+    for chunk in handshake.chunks(fragment) {
+        records.extend([0x16, 0x03, 0x01]);
+        records.extend(len16(chunk.len()));
+        records.extend(chunk);
+    }
+    records
+}
+
+/// Offset of `hostname` within `data`
+fn hostname_offset(data: &[u8], hostname: &str) -> usize {
+    data.windows(hostname.len()).position(|window| window == hostname.as_bytes()).unwrap()
+}
+
+#[tokio::test]
+async fn test_fragmented_handshake() {
+    // Split across three TLS records, each delivered over several small writes
+    let body = client_hello_body(Some("app.tunnel.test"), 0);
+    let data = wire(&body, 40);
+    assert_eq!(data.len(), 4 + body.len() + 3 * 5);
+
+    let (mut client, mut server) = duplex(64);
+    let sent = data.clone();
+    let writer = tokio::spawn(async move {
+        for chunk in sent.chunks(7) {
+            client.write_all(chunk).await.unwrap();
+            tokio::task::yield_now().await;
+        }
+        client
+    });
+
+    let (read, hostname) = read_client_hello(&mut server).await.unwrap();
+    assert_eq!(hostname, "app.tunnel.test");
+    assert_eq!(read, data);
+    writer.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_sni_past_first_kilobyte() {
+    // Padding puts the hostname across byte 1024, where a single 1 KiB read used to stop
+    let data = wire(&client_hello_body(Some("boundary.tunnel.test"), 921), 16 * 1024);
+    let offset = hostname_offset(&data, "boundary.tunnel.test");
+    assert!(offset < 1024 && offset + "boundary.tunnel.test".len() > 1024);
+
+    assert_eq!(parse_client_hello(&data[..1024]), ClientHello::Incomplete);
+    assert_eq!(parse_client_hello(&data), ClientHello::Complete(Some("boundary.tunnel.test".to_string())));
+
+    let (mut client, mut server) = duplex(4096);
+    client.write_all(&data[..1024]).await.unwrap();
+    client.write_all(&data[1024..]).await.unwrap();
+    let (_, hostname) = read_client_hello(&mut server).await.unwrap();
+    assert_eq!(hostname, "boundary.tunnel.test");
+}
+
+#[tokio::test]
+async fn test_truncated_record() {
+    let data = wire(&client_hello_body(Some("app.tunnel.test"), 0), 16 * 1024);
+    for cut in [3, 5, 40, data.len() - 1] {
+        assert_eq!(parse_client_hello(&data[..cut]), ClientHello::Incomplete, "cut at {}", cut);
+    }
+
+    // A client hanging up mid-record is an error, not a hang or a guess
+    let (mut client, mut server) = duplex(4096);
+    client.write_all(&data[..data.len() - 10]).await.unwrap();
+    drop(client);
+    let result = read_client_hello(&mut server).await;
+    assert!(matches!(result, Err(TunnelError::NetworkError(_))));
+}
+
+#[test]
+fn test_malformed_lengths_are_rejected() {
+    let hostname = "app.tunnel.test";
+    let data = wire(&client_hello_body(Some(hostname), 0), 16 * 1024);
+    let name_at = hostname_offset(&data, hostname);
+
+    // Hostname length running past its extension
+    let mut bad = data.clone();
+    bad[name_at - 1] += 1;
+    assert_eq!(parse_client_hello(&bad), ClientHello::Invalid);
+
+    // Extension length running past the extensions block
+    let mut bad = data.clone();
+    bad[name_at - 6] += 10;
+    assert_eq!(parse_client_hello(&bad), ClientHello::Invalid);
+
+    // Not a handshake record
+    let mut bad = data.clone();
+    bad[0] = 0x17;
+    assert_eq!(parse_client_hello(&bad), ClientHello::Invalid);
+
+    // Record longer than TLS allows
+    let mut bad = data.clone();
+    bad[3..5].copy_from_slice(&len16(20_000));
+    assert_eq!(parse_client_hello(&bad), ClientHello::Invalid);
+
+    // Hostname that is not a DNS name
+    let bad = wire(&client_hello_body(Some("app tunnel/test"), 0), 16 * 1024);
+    assert_eq!(parse_client_hello(&bad), ClientHello::Invalid);
+}
+
+#[test]
+fn test_client_hello_without_sni() {
+    let data = wire(&client_hello_body(None, 0), 16 * 1024);
+    assert_eq!(parse_client_hello(&data), ClientHello::Complete(None));
+}
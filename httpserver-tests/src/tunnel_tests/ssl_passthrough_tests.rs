@@ -0,0 +1,143 @@
+//! SSL Passthrough Tests
+//! Tests for TLS connections routed by SNI to a tunnel client
+
+use httpserver_tunnel::config::TunnelServerConfig;
+use httpserver_tunnel::protocol::TunnelMessage;
+use httpserver_tunnel::ProxyProtocolVersion;
+use futures_util::{SinkExt, StreamExt};
+use tempfile::TempDir;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use crate::test_support::{free_port, start_tunnel_server, tunnel_server_config};
+
+/// Create a server configuration with SSL passthrough on a free port
+fn create_ssl_test_config() -> TunnelServerConfig {
+    let mut config = tunnel_server_config("ssl.test");
+    config.public_https_port = free_port();
+    config.ssl.enabled = true;
+    config
+}
+
+
+/// Single-record Client Hello naming `hostname`
+fn client_hello(hostname: &str) -> Vec<u8> {
+    let mut body = vec![0x03, 0x03];
+    body.extend([7u8; 32]); // random
+    body.push(0); // session id
+    body.extend([0x00, 0x02, 0x13, 0x01]); // cipher suites
+    body.extend([0x01, 0x00]); // compression methods
+
+    let name_len = hostname.len() as u16;
+    let mut extensions = vec![0x00, 0x00];
+    extensions.extend((name_len + 5).to_be_bytes());
+    extensions.extend((name_len + 3).to_be_bytes());
+    extensions.push(0x00);
+    extensions.extend(name_len.to_be_bytes());
+    extensions.extend(hostname.bytes());
+    body.extend((extensions.len() as u16).to_be_bytes());
+    body.extend(extensions);
+
+    let mut handshake = vec![0x01];
+    handshake.extend(&(body.len() as u32).to_be_bytes()[1..]);
+    handshake.extend(body);
+
+    let mut record = vec![0x16, 0x03, 0x01];
+    record.extend((handshake.len() as u16).to_be_bytes());
+    record.extend(handshake);
+    record
+}
+
+/// Start a server with SSL passthrough and authenticate a tunnel for "tls-app" offering
+/// `capabilities`; returns the tunnel socket, the passthrough port and the server task
+async fn start_tls_tunnel(
+    storage_dir: &TempDir,
+    capabilities: Vec<String>
+) -> (WebSocketStream<MaybeTlsStream<TcpStream>>, u16, JoinHandle<()>) {
+    let config = create_ssl_test_config();
+    let tunnel_port = config.tunnel_port;
+    let https_port = config.public_https_port;
+    let server_handle = start_tunnel_server(config, storage_dir).await;
+
+    let url = format!("ws://127.0.0.1:{}/connect", tunnel_port);
+    let (mut socket, _) = connect_async(url.as_str()).await.unwrap();
+    let auth = TunnelMessage::Auth {
+        token: "any-token".to_string(),
+        subdomain: Some("tls-app".to_string()),
+        protocol_version: "1.0".to_string(),
+        capabilities,
+    };
+    socket.send(Message::Text(serde_json::to_string(&auth).unwrap())).await.unwrap();
+    let reply = socket.next().await.unwrap().unwrap();
+    let reply: TunnelMessage = serde_json::from_str(reply.to_text().unwrap()).unwrap();
+    assert!(matches!(reply, TunnelMessage::AuthResponse { success: true, .. }));
+
+    (socket, https_port, server_handle)
+}
+
+/// Connect a TLS client for the tunnel's hostname; returns it and the SslConnect the tunnel received
+async fn connect_tls_client(
+    socket: &mut WebSocketStream<MaybeTlsStream<TcpStream>>,
+    https_port: u16,
+    hello: &[u8]
+) -> (TcpStream, TunnelMessage) {
+    let mut tls_client = TcpStream::connect(("127.0.0.1", https_port)).await.unwrap();
+    tls_client.write_all(hello).await.unwrap();
+
+    let frame = timeout(Duration::from_secs(5), socket.next()).await
+        .expect("No SSL connect forwarded")
+        .unwrap()
+        .unwrap();
+    (tls_client, serde_json::from_str(frame.to_text().unwrap()).unwrap())
+}
+
+#[tokio::test]
+async fn test_ssl_connect_carries_client_ip() {
+    let storage_dir = TempDir::new().unwrap();
+    let (mut socket, https_port, server_handle) = start_tls_tunnel(&storage_dir, vec![]).await;
+
+    let hello = client_hello("tls-app.ssl.test");
+    let (tls_client, message) = connect_tls_client(&mut socket, https_port, &hello).await;
+    match message {
+        TunnelMessage::SslConnect { client_ip, initial_data, .. } => {
+            assert_eq!(client_ip, tls_client.local_addr().unwrap().ip().to_string());
+            // Without PROXY protocol the TLS bytes arrive untouched
+            assert_eq!(initial_data, Some(hello));
+        }
+        other => panic!("Expected SslConnect, got {:?}", other),
+    }
+
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn test_proxy_v1_header_precedes_tls_bytes() {
+    let storage_dir = TempDir::new().unwrap();
+    let capabilities = vec![ProxyProtocolVersion::V1.capability().to_string()];
+    let (mut socket, https_port, server_handle) = start_tls_tunnel(&storage_dir, capabilities).await;
+
+    let hello = client_hello("tls-app.ssl.test");
+    let (tls_client, message) = connect_tls_client(&mut socket, https_port, &hello).await;
+    let client_port = tls_client.local_addr().unwrap().port();
+    match message {
+        TunnelMessage::SslConnect { initial_data: Some(data), .. } => {
+            let header = format!("PROXY TCP4 127.0.0.1 127.0.0.1 {} {}\r\n", client_port, https_port);
+            assert_eq!(String::from_utf8_lossy(&data[..header.len()]), header);
+            assert_eq!(&data[header.len()..], &hello[..]);
+        }
+        other => panic!("Expected SslConnect with data, got {:?}", other),
+    }
+
+    server_handle.abort();
+}
+
+#[test]
+fn test_ssl_connect_without_client_ip_still_parses() {
+    // Messages from servers predating the field
+    let json = r#"{"type":"SslConnect","id":"c1","initial_data":null}"#;
+    let message: TunnelMessage = serde_json::from_str(json).unwrap();
+    assert!(matches!(message, TunnelMessage::SslConnect { client_ip, .. } if client_ip.is_empty()));
+}
@@ -0,0 +1,210 @@
+//! Status Endpoint Tests
+//! The client's /tunnel/status endpoint follows each tunnel through its connection lifecycle
+
+use httpserver_tunnel::TunnelClient;
+use httpserver_tunnel::config::{TunnelConfig, TunnelEndpoint, TunnelAuthConfig, ReconnectionConfig};
+use httpserver_tunnel::protocol::TunnelMessage;
+use axum::{Router, body::Body, http::Request};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{Duration, Instant};
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+use tower::ServiceExt;
+
+/// Signals for driving the fake tunnel server through one connection
+struct ServerControl {
+    /// Receives once the client's Auth message has arrived
+    auth_received: mpsc::UnboundedReceiver<()>,
+    /// Answer the pending Auth message (true accepts it)
+    respond: Option<oneshot::Sender<bool>>,
+    /// Close the connection from the server side
+    close: Option<oneshot::Sender<()>>,
+}
+
+/// Fake tunnel server handling a single WebSocket connection under test control.
+/// Plain HTTP requests (credential validation) fail the handshake and are dropped.
+async fn start_fake_server() -> (u16, ServerControl) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (auth_tx, auth_received) = mpsc::unbounded_channel();
+    let (respond_tx, respond_rx) = oneshot::channel::<bool>();
+    let (close_tx, close_rx) = oneshot::channel::<()>();
+
+    tokio::spawn(async move {
+        let socket = loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            if let Ok(socket) = accept_async(stream).await {
+                break socket;
+            }
+        };
+        let (mut sender, mut receiver) = socket.split();
+
+        while let Some(Ok(Message::Text(text))) = receiver.next().await {
+            if let Ok(TunnelMessage::Auth { .. }) = serde_json::from_str(&text) {
+                break;
+            }
+        }
+        let _ = auth_tx.send(());
+
+        let success = respond_rx.await.unwrap_or(false);
+        let response = TunnelMessage::AuthResponse {
+            success,
+            assigned_subdomain: success.then(|| "myapp".to_string()),
+            error: (!success).then(|| "Invalid token".to_string()),
+            public_url: success.then(|| "http://myapp.httpserver.io".to_string()),
+            capabilities: vec![],
+        };
+        let _ = sender.send(Message::Text(serde_json::to_string(&response).unwrap())).await;
+
+        // Keep reading (pings) until asked to close
+        tokio::select! {
+            _ = close_rx => {}
+            _ = async { while let Some(Ok(_)) = receiver.next().await {} } => {}
+        }
+        let _ = sender.send(Message::Close(None)).await;
+    });
+
+    let control = ServerControl {
+        auth_received,
+        respond: Some(respond_tx),
+        close: Some(close_tx),
+    };
+    (port, control)
+}
+
+fn create_client_config(port: u16, reconnection: ReconnectionConfig) -> TunnelConfig {
+    let auth = TunnelAuthConfig {
+        method: "api_key".to_string(),
+        api_key: Some("test-api-key".to_string()),
+        token: None,
+        cert_file: None,
+        key_file: None,
+        user: None,
+        headers: HashMap::new(),
+        token_refresh: Default::default(),
+    };
+
+    let endpoint = TunnelEndpoint {
+        server_url: format!("ws://127.0.0.1:{}/connect", port),
+        subdomain: Some("myapp".to_string()),
+        custom_domain: None,
+        protocol_version: "1.0".to_string(),
+        connection_timeout: 5,
+        keepalive_interval: 30,
+        max_connections: 1,
+        compression: false,
+        proxy_protocol: None,
+    };
+
+    TunnelConfig {
+        enabled: true,
+        local_port: Some(3000),
+        local_host: "127.0.0.1".to_string(),
+        endpoints: vec![endpoint],
+        auth,
+        reconnection,
+        monitoring: Default::default(),
+        ssl: Default::default(),
+        server: Default::default(),
+    }
+}
+
+/// GET /tunnel/status and parse the JSON body
+async fn fetch_status(router: &Router) -> Value {
+    let request = Request::builder().uri("/tunnel/status").body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), 200);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+/// Poll the endpoint until the first tunnel matches, returning that status
+async fn wait_for_tunnel(router: &Router, matches: impl Fn(&Value) -> bool) -> Value {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let status = fetch_status(router).await;
+        if matches(&status["tunnels"][0]) {
+            return status;
+        }
+        assert!(Instant::now() < deadline, "Tunnel status never matched, last: {}", status);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_status_endpoint_tracks_connection_lifecycle() {
+    let (port, mut server) = start_fake_server().await;
+    let reconnection = ReconnectionConfig { enabled: false, ..Default::default() };
+    let mut client = TunnelClient::new(create_client_config(port, reconnection), 3000).unwrap();
+    let router = client.status_router();
+
+    // Disconnected: nothing started yet
+    let status = fetch_status(&router).await;
+    assert_eq!(status["running"], false);
+    assert_eq!(status["connected"], false);
+    assert_eq!(status["tunnels"], Value::Array(vec![]));
+
+    // Connected: the WebSocket is open and authentication is pending
+    client.start().await.unwrap();
+    server.auth_received.recv().await.unwrap();
+    let status = wait_for_tunnel(&router, |tunnel| tunnel["state"] == "Authenticating").await;
+    assert_eq!(status["running"], true);
+    let tunnel = &status["tunnels"][0];
+    assert_eq!(tunnel["id"], "tunnel-0");
+    assert_eq!(tunnel["server_url"], format!("ws://127.0.0.1:{}/connect", port));
+    assert_eq!(tunnel["subdomain"], "myapp");
+    assert_eq!(tunnel["public_url"], Value::Null);
+    assert_eq!(tunnel["retry_count"], 0);
+
+    // Authenticated: public URL assigned and traffic counted
+    server.respond.take().unwrap().send(true).unwrap();
+    let status = wait_for_tunnel(&router, |tunnel| {
+        tunnel["state"] == "Authenticated" && tunnel["bytes_transferred"].as_u64() > Some(0)
+    }).await;
+    assert_eq!(status["connected"], true);
+    let tunnel = &status["tunnels"][0];
+    assert_eq!(tunnel["public_url"], "http://myapp.httpserver.io");
+    assert_eq!(tunnel["last_error"], Value::Null);
+
+    // Disconnected again once the server closes the tunnel
+    server.close.take().unwrap().send(()).unwrap();
+    let status = wait_for_tunnel(&router, |tunnel| tunnel["state"] == "Disconnected").await;
+    assert_eq!(status["connected"], false);
+    assert_eq!(status["tunnels"][0]["uptime_secs"], 0);
+
+    client.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_status_endpoint_reports_retries_and_last_error() {
+    let (port, mut server) = start_fake_server().await;
+    let reconnection = ReconnectionConfig {
+        enabled: true,
+        initial_delay: 30,
+        jitter_factor: 0.0,
+        ..Default::default()
+    };
+    let mut client = TunnelClient::new(create_client_config(port, reconnection), 3000).unwrap();
+    let router = client.status_router();
+
+    client.start().await.unwrap();
+    server.auth_received.recv().await.unwrap();
+    server.respond.take().unwrap().send(false).unwrap();
+
+    // Rejected: the client waits to retry and reports why
+    let status = wait_for_tunnel(&router, |tunnel| tunnel["retry_count"] == 1).await;
+    assert_eq!(status["connected"], false);
+    let tunnel = &status["tunnels"][0];
+    assert!(tunnel["state"]["Failed"].is_string(), "Unexpected state: {}", tunnel["state"]);
+    let last_error = tunnel["last_error"].as_str().unwrap();
+    assert!(last_error.contains("rejected"), "Unexpected error: {}", last_error);
+
+    // The same report is available directly
+    let report = client.status_report().await;
+    assert_eq!(report.tunnels[0].retry_count, 1);
+
+    client.stop().await.unwrap();
+}
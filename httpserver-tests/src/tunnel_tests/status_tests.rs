@@ -3,7 +3,7 @@
 
 use httpserver_tunnel::status::{
     ConnectionHealth, TunnelMetrics, TunnelStatusMonitor, 
-    ConfigSummary, TunnelEvent, TunnelEventType
+    ConfigSummary, TunnelEvent, TunnelEventType, calculate_health_score
 };
 use httpserver_tunnel::connection::ConnectionState;
 use std::time::Duration;
@@ -140,6 +140,32 @@ async fn test_metrics_ping_latency() {
     assert!((avg_latency.as_millis() as f64 - 100.0).abs() < 1.0);
 }
 
+#[tokio::test]
+async fn test_health_score_from_connection_metrics() {
+    let uptime = Duration::from_secs(120);
+    let mut metrics = TunnelMetrics::new();
+
+    // Only a live connection scores
+    assert_eq!(calculate_health_score(&ConnectionState::Reconnecting, uptime, 0, &metrics), 0);
+
+    // Connected but no pong yet, and freshly opened
+    assert_eq!(calculate_health_score(&ConnectionState::Authenticated, Duration::from_secs(5), 0, &metrics), 70);
+
+    // Every ping answered quickly
+    for _ in 0..3 {
+        metrics.record_pong(Duration::from_millis(20));
+    }
+    assert_eq!(calculate_health_score(&ConnectionState::Authenticated, uptime, 0, &metrics), 100);
+
+    // Retries, lost pings and slow round trips each cost points
+    assert_eq!(calculate_health_score(&ConnectionState::Authenticated, uptime, 2, &metrics), 90);
+    metrics.missed_pings = 1;
+    assert_eq!(metrics.ping_success_rate(), Some(0.75));
+    assert_eq!(calculate_health_score(&ConnectionState::Authenticated, uptime, 0, &metrics), 90);
+    metrics.record_pong(Duration::from_secs(2));
+    assert!(calculate_health_score(&ConnectionState::Authenticated, uptime, 0, &metrics) < 90);
+}
+
 #[tokio::test]
 async fn test_tunnel_event_creation() {
     let event = TunnelEvent::new(
@@ -209,10 +235,12 @@ async fn test_status_monitor_event_recording() {
 async fn test_status_monitor_health_updates() {
     let mut monitor = TunnelStatusMonitor::new();
     
-    let mut health = ConnectionHealth::default();
-    health.state = ConnectionState::Connected;
-    health.health_score = 95;
-    health.uptime = Duration::from_secs(3600); // 1 hour
+    let health = ConnectionHealth {
+        state: ConnectionState::Connected,
+        health_score: 95,
+        uptime: Duration::from_secs(3600), // 1 hour
+        ..Default::default()
+    };
     
     monitor.update_health(health);
     
@@ -84,7 +84,6 @@ async fn test_subdomain_persistence_simulation() {
     }
     
     // Both servers should handle the same configuration
-    assert!(true); // Test passes if both servers create successfully
 }
 
 /// Test custom domain and subdomain validation
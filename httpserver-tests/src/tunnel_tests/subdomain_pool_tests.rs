@@ -0,0 +1,127 @@
+//! Subdomain Pool Tests
+//! Tests for several tunnel clients sharing one custom subdomain for redundancy
+
+use httpserver_tunnel::config::TunnelServerConfig;
+use httpserver_tunnel::protocol::{TunnelMessage, TunnelProtocol};
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use tempfile::TempDir;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use crate::test_support::{start_tunnel_server, tunnel_server_config};
+
+/// Create a server configuration with subdomain pools on or off
+fn create_pool_test_config(subdomain_pools: bool) -> TunnelServerConfig {
+    let mut config = tunnel_server_config("pool.test");
+    config.subdomain_pools = subdomain_pools;
+    config
+}
+
+
+/// Send a tunnel message as a text frame
+fn text(message: &TunnelMessage) -> Message {
+    Message::Text(serde_json::to_string(message).unwrap())
+}
+
+/// Connect a tunnel client requesting `subdomain`; returns the auth reply and, when accepted,
+/// a task answering every forwarded request with `name`. Aborting the task disconnects the client.
+async fn connect_client(tunnel_port: u16, subdomain: &str, name: &'static str) -> (TunnelMessage, Option<JoinHandle<()>>) {
+    let url = format!("ws://127.0.0.1:{}/connect", tunnel_port);
+    let (socket, _) = connect_async(url.as_str()).await.unwrap();
+    let (mut ws_sender, mut ws_receiver) = socket.split();
+
+    let auth = TunnelProtocol::create_auth_message("any-token", Some(subdomain));
+    ws_sender.send(text(&auth)).await.unwrap();
+    let reply = ws_receiver.next().await.unwrap().unwrap();
+    let reply: TunnelMessage = serde_json::from_str(reply.to_text().unwrap()).unwrap();
+    if !matches!(reply, TunnelMessage::AuthResponse { success: true, .. }) {
+        return (reply, None);
+    }
+
+    let task = tokio::spawn(async move {
+        while let Some(Ok(Message::Text(frame))) = ws_receiver.next().await {
+            if let Ok(TunnelMessage::HttpRequest { id, .. }) = serde_json::from_str(&frame) {
+                let response = TunnelProtocol::create_http_response_message(
+                    &id,
+                    200,
+                    HashMap::new(),
+                    Some(name.as_bytes().to_vec())
+                );
+                ws_sender.send(text(&response)).await.unwrap();
+            }
+        }
+    });
+    (reply, Some(task))
+}
+
+/// GET through the public port for `host`, returning the status and body
+async fn public_get(public_port: u16, host: &str) -> (u16, String) {
+    let response = reqwest::Client::new()
+        .get(format!("http://127.0.0.1:{}/", public_port))
+        .header("host", host)
+        .send().await.unwrap();
+    (response.status().as_u16(), response.text().await.unwrap())
+}
+
+#[tokio::test]
+async fn test_pooled_subdomain_distributes_and_fails_over() {
+    let storage_dir = TempDir::new().unwrap();
+    let config = create_pool_test_config(true);
+    let tunnel_port = config.tunnel_port;
+    let public_port = config.public_port;
+    let server_handle = start_tunnel_server(config, &storage_dir).await;
+
+    let (_, first) = connect_client(tunnel_port, "pool-app", "first").await;
+    let (reply, second) = connect_client(tunnel_port, "pool-app", "second").await;
+    match reply {
+        TunnelMessage::AuthResponse { assigned_subdomain, .. } => {
+            assert_eq!(assigned_subdomain, Some("pool-app".to_string()));
+        }
+        other => panic!("Expected AuthResponse, got {:?}", other),
+    }
+    let (first, second) = (first.unwrap(), second.unwrap());
+
+    // Requests alternate between the two clients
+    let mut served = Vec::new();
+    for _ in 0..4 {
+        let (status, body) = public_get(public_port, "pool-app.pool.test").await;
+        assert_eq!(status, 200);
+        served.push(body);
+    }
+    assert_eq!(served.iter().filter(|body| *body == "first").count(), 2);
+    assert_eq!(served.iter().filter(|body| *body == "second").count(), 2);
+    assert_ne!(served[0], served[1]);
+
+    // Once one client drops, the other serves everything
+    first.abort();
+    sleep(Duration::from_millis(300)).await;
+    for _ in 0..4 {
+        assert_eq!(public_get(public_port, "pool-app.pool.test").await, (200, "second".to_string()));
+    }
+
+    second.abort();
+    sleep(Duration::from_millis(300)).await;
+    assert_eq!(public_get(public_port, "pool-app.pool.test").await.0, 404);
+
+    server_handle.abort();
+}
+
+#[tokio::test]
+async fn test_subdomain_in_use_without_pools() {
+    let storage_dir = TempDir::new().unwrap();
+    let config = create_pool_test_config(false);
+    let tunnel_port = config.tunnel_port;
+    let public_port = config.public_port;
+    let server_handle = start_tunnel_server(config, &storage_dir).await;
+
+    let (_, first) = connect_client(tunnel_port, "solo-app", "first").await;
+    let (reply, second) = connect_client(tunnel_port, "solo-app", "second").await;
+    assert!(matches!(reply, TunnelMessage::AuthResponse { success: false, .. }));
+    assert!(second.is_none());
+    assert_eq!(public_get(public_port, "solo-app.pool.test").await, (200, "first".to_string()));
+
+    first.unwrap().abort();
+    server_handle.abort();
+}
@@ -7,6 +7,7 @@ use tokio_tungstenite::{connect_async, tungstenite::Message};
 use futures_util::{SinkExt, StreamExt};
 use std::collections::HashMap;
 use tracing::{info, debug};
+use tempfile::TempDir;
 
 #[tokio::test]
 #[ignore] // Requires complex setup with multiple services
@@ -18,7 +19,9 @@ async fn test_tunnel_http_forwarding() {
     let config = create_test_tunnel_config();
     
     // Start tunnel server
-    let tunnel_server = TunnelServer::new(config.clone()).expect("Failed to create tunnel server");
+    let storage_dir = TempDir::new().unwrap();
+    let tunnel_server = TunnelServer::with_storage_path(config.clone(), storage_dir.path().join("subdomains.json"))
+        .expect("Failed to create tunnel server");
     
     // Start server in background
     let server_handle = tokio::spawn(async move {
@@ -76,7 +79,18 @@ fn create_test_tunnel_config() -> TunnelServerConfig {
             requests_per_minute: 100,
             max_concurrent_connections: 10,
             max_bandwidth_bps: 10485760, // 10 MB/s
-        },        ssl: httpserver_tunnel::config::TunnelServerSslConfig {
+        },
+        reserved_subdomains: vec![],
+        replace_default_reserved: false,
+        max_allocation_history: 1000,
+        max_subdomains_per_ip: 0,
+        subdomain_pools: false,
+        storage_flush_interval_ms: 1000,
+        storage_path: None,
+        idle_timeout: 90,
+        request_timeout_seconds: 30,
+        request_queue_size: 100,
+        request_queue_timeout_ms: 5000,        ssl: httpserver_tunnel::config::TunnelServerSslConfig {
             enabled: false,
             wildcard_cert_file: None,
             wildcard_key_file: None,
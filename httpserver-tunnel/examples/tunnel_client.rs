@@ -40,9 +40,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {    // Initialize tra
         if let Message::Binary(data) = msg {
             let response = TunnelProtocol::deserialize_message(&data)?;
             match response {
-                TunnelMessage::AuthResponse { success, assigned_subdomain, error } => {
+                TunnelMessage::AuthResponse { success, assigned_subdomain, error, public_url, .. } => {
                     if success {
                         info!("Authentication successful! Assigned subdomain: {:?}", assigned_subdomain);
+                        info!("Public URL: {:?}", public_url);
                     } else {
                         error!("Authentication failed: {:?}", error);
                         return Ok(());
@@ -80,7 +81,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {    // Initialize tra
         if let Message::Binary(data) = msg {
             if let Ok(tunnel_msg) = TunnelProtocol::deserialize_message(&data) {
                 match tunnel_msg {
-                    TunnelMessage::HttpRequest { id, method, path, headers, body, client_ip: _ } => {
+                    TunnelMessage::HttpRequest { id, method, path, headers, body, .. } => {
                         debug!("Received HTTP request {}: {} {}", id, method, path);
                         
                         // Forward request to local server
@@ -110,8 +111,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {    // Initialize tra
                             let _ = sender.send(Message::Binary(data)).await;
                         }
                     }
-                    TunnelMessage::SslConnect { id, initial_data: _ } => {
-                        debug!("Received SSL connect request {}", id);
+                    TunnelMessage::SslConnect { id, client_ip, .. } => {
+                        debug!("Received SSL connect request {} from {}", id, client_ip);
                         // TODO: Handle SSL passthrough connections
                     }
                     _ => {
@@ -245,7 +246,7 @@ fn parse_http_response(data: &[u8]) -> HttpResponse {
     let mut headers = HashMap::new();
     let mut body_start = 0;
 
-    for (_i, line) in lines.enumerate() {
+    for line in lines {
         if line.is_empty() {
             body_start = response_str.find("\r\n\r\n")
                 .or_else(|| response_str.find("\n\n"))
@@ -5,8 +5,9 @@ use crate::{TunnelError, TunnelResult};
 use crate::auth::TunnelAuthenticator;
 use crate::config::TunnelConfig;
 use crate::connection::{TunnelConnection, ConnectionState};
-use crate::status::{TunnelStatus, TunnelStatusMonitor, TunnelEvent, TunnelEventType, ConfigSummary};
+use crate::status::{TunnelStatus, TunnelStatusMonitor, TunnelEvent, TunnelEventType, ConfigSummary, ClientStatusReport, TunnelReport};
 
+use axum::{Json, Router, routing::get};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{RwLock, watch};
@@ -122,23 +123,12 @@ impl TunnelClient {
 
             let connection_arc = Arc::new(connection);
             self.connections.write().await.insert(connection_id.clone(), connection_arc.clone());            // Start connection in background
-            let local_port = self.local_port;
-            let local_host = self.local_host.clone();
             let status_monitor = self.status_monitor.clone();
             let mut shutdown_rx_clone = shutdown_tx.subscribe();
-            let endpoint_clone = endpoint.clone();
-            let authenticator_clone = self.authenticator.clone();
-            let reconnection_clone = self.config.reconnection.clone();
             let connection_id_for_task = connection_id.clone();
               let task = tokio::spawn(async move {
-                // Create a new connection instance for this task
-                let mut connection = TunnelConnection::new(
-                    endpoint_clone,
-                    authenticator_clone,
-                    reconnection_clone,
-                    local_port,
-                    &local_host,
-                );
+                // Run the stored connection so status queries see its live state
+                let connection = connection_arc;
                 
                 tokio::select! {
                     result = connection.start() => {
@@ -384,6 +374,21 @@ impl TunnelClient {
         count
     }
 
+    /// Snapshot of the client and every tunnel connection for the admin endpoint
+    pub async fn status_report(&self) -> ClientStatusReport {
+        build_status_report(&self.connections, &self.is_running).await
+    }
+
+    /// Router serving `GET /tunnel/status` with the client's [`ClientStatusReport`] as JSON
+    pub fn status_router(&self) -> Router {
+        let connections = self.connections.clone();
+        let is_running = self.is_running.clone();
+
+        Router::new().route("/tunnel/status", get(move || async move {
+            Json(build_status_report(&connections, &is_running).await)
+        }))
+    }
+
     /// Subscribe to status updates
     pub fn subscribe_status(&self) -> watch::Receiver<Vec<TunnelStatus>> {
         self.status_receiver.clone()
@@ -437,3 +442,40 @@ impl TunnelClient {
         Ok(())
     }
 }
+
+/// Collect live state from each connection into a status report
+async fn build_status_report(
+    connections: &RwLock<HashMap<String, Arc<TunnelConnection>>>,
+    is_running: &RwLock<bool>,
+) -> ClientStatusReport {
+    let mut tunnels = Vec::new();
+
+    for (connection_id, connection) in connections.read().await.iter() {
+        let health = connection.get_health().await;
+        let metrics = connection.get_metrics().await;
+
+        tunnels.push(TunnelReport {
+            id: connection_id.clone(),
+            server_url: connection.endpoint().server_url.clone(),
+            subdomain: connection.endpoint().subdomain.clone(),
+            state: health.state,
+            public_url: connection.get_public_url().await,
+            uptime_secs: health.uptime.as_secs(),
+            retry_count: health.retry_count,
+            bytes_transferred: metrics.bytes_transferred,
+            last_error: health.last_error,
+            health_score: health.health_score,
+            last_ping: health.last_ping,
+            avg_ping_latency_ms: health.avg_ping_latency.map(|latency| latency.as_millis() as u64),
+        });
+    }
+    tunnels.sort_by(|a, b| a.id.cmp(&b.id));
+
+    ClientStatusReport {
+        running: *is_running.read().await,
+        connected: tunnels.iter().any(|tunnel| {
+            matches!(tunnel.state, ConnectionState::Connected | ConnectionState::Authenticated)
+        }),
+        tunnels,
+    }
+}
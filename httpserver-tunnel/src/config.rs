@@ -2,6 +2,7 @@
 // Phase 7.2 Tunnel Server Configuration
 // Configuration structures for both tunnel client and server settings
 
+use crate::proxy_protocol::ProxyProtocolVersion;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::collections::HashMap;
@@ -66,7 +67,7 @@ pub struct TunnelServerConfig {
     #[serde(default = "default_base_domain")]
     pub base_domain: String,
 
-    /// Maximum number of concurrent tunnels
+    /// Maximum number of concurrent tunnels (0 = unlimited)
     #[serde(default = "default_max_tunnels")]
     pub max_tunnels: u32,
 
@@ -74,13 +75,61 @@ pub struct TunnelServerConfig {
     #[serde(default)]
     pub subdomain_strategy: SubdomainStrategy,
 
+    /// Subdomains that cannot be allocated, added to the built-in reserved words
+    #[serde(default)]
+    pub reserved_subdomains: Vec<String>,
+
+    /// Reserve only `reserved_subdomains`, allowing the built-in reserved words
+    #[serde(default)]
+    pub replace_default_reserved: bool,
+
+    /// Past subdomain allocations kept in storage; oldest are pruned first (0 = unlimited)
+    #[serde(default = "default_max_allocation_history")]
+    pub max_allocation_history: usize,
+
+    /// Active subdomains a single client IP may hold (0 = unlimited)
+    #[serde(default)]
+    pub max_subdomains_per_ip: u32,
+
+    /// Let tunnels requesting a custom subdomain already in use join it as a pool;
+    /// public requests are spread round-robin across the pool's live tunnels
+    #[serde(default)]
+    pub subdomain_pools: bool,
+
+    /// Milliseconds between batched subdomain storage writes (0 = write on every change)
+    #[serde(default = "default_storage_flush_interval_ms")]
+    pub storage_flush_interval_ms: u64,
+
+    /// File persisting subdomain allocations (default: tunnel_data/subdomains.json in the working directory)
+    #[serde(default)]
+    pub storage_path: Option<PathBuf>,
+
     /// Authentication settings for tunnel connections
     #[serde(default)]
     pub auth: TunnelServerAuthConfig,
 
     /// Rate limiting settings
     #[serde(default)]
-    pub rate_limiting: TunnelRateLimitConfig,    /// SSL/TLS settings for public endpoints
+    pub rate_limiting: TunnelRateLimitConfig,
+
+    /// Seconds without any message from a tunnel client before it is evicted (0 = never)
+    #[serde(default = "default_idle_timeout")]
+    pub idle_timeout: u64,
+
+    /// Seconds a public request waits for the tunnel client's response before a 504;
+    /// unanswered requests are swept from the pending table after the same time
+    #[serde(default = "default_request_timeout_seconds")]
+    pub request_timeout_seconds: u64,
+
+    /// Public requests queued per tunnel connection before senders wait for room
+    #[serde(default = "default_request_queue_size")]
+    pub request_queue_size: usize,
+
+    /// Milliseconds a public request waits for room in a full queue before a 503 (0 = reject at once)
+    #[serde(default = "default_request_queue_timeout_ms")]
+    pub request_queue_timeout_ms: u64,
+
+    /// SSL/TLS settings for public endpoints
     #[serde(default)]
     pub ssl: TunnelServerSslConfig,
 
@@ -90,9 +139,10 @@ pub struct TunnelServerConfig {
 }
 
 /// Subdomain allocation strategy
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub enum SubdomainStrategy {
     /// Random alphanumeric subdomain
+    #[default]
     Random,
     /// User-specified subdomain (if available)
     UserSpecified,
@@ -242,6 +292,15 @@ pub struct TunnelEndpoint {
     /// Maximum concurrent connections through this tunnel
     #[serde(default = "default_max_connections")]
     pub max_connections: u32,
+
+    /// Offer deflate compression of large messages to the tunnel server
+    #[serde(default)]
+    pub compression: bool,
+
+    /// PROXY protocol header ("v1" or "v2") the local TLS server expects ahead of
+    /// passthrough connections, carrying the public client's address
+    #[serde(default)]
+    pub proxy_protocol: Option<ProxyProtocolVersion>,
 }
 
 /// Tunnel authentication configuration
@@ -460,6 +519,12 @@ fn default_max_concurrent() -> u32 { 100 }
 fn default_max_bandwidth() -> u64 { 10_485_760 } // 10 MB/s
 fn default_token_expiry() -> u64 { 86400 } // 24 hours
 fn default_key_rotation_hours() -> u64 { 168 } // 7 days
+fn default_idle_timeout() -> u64 { 90 } // 3x the client keepalive interval
+fn default_request_timeout_seconds() -> u64 { 30 }
+fn default_request_queue_size() -> usize { 100 }
+fn default_request_queue_timeout_ms() -> u64 { 5000 }
+fn default_max_allocation_history() -> usize { 1000 }
+fn default_storage_flush_interval_ms() -> u64 { 1000 }
 
 // Network configuration defaults
 fn default_bind_address() -> String { "0.0.0.0".to_string() }
@@ -477,13 +542,40 @@ impl Default for TunnelServerConfig {
             public_https_port: default_public_https_port(),
             base_domain: default_base_domain(),
             max_tunnels: default_max_tunnels(),
-            subdomain_strategy: SubdomainStrategy::Random,            auth: TunnelServerAuthConfig::default(),
+            subdomain_strategy: SubdomainStrategy::Random,
+            reserved_subdomains: Vec::new(),
+            replace_default_reserved: false,
+            max_allocation_history: default_max_allocation_history(),
+            max_subdomains_per_ip: 0,
+            subdomain_pools: false,
+            storage_flush_interval_ms: default_storage_flush_interval_ms(),
+            storage_path: None,            auth: TunnelServerAuthConfig::default(),
             rate_limiting: TunnelRateLimitConfig::default(),
+            idle_timeout: default_idle_timeout(),
+            request_timeout_seconds: default_request_timeout_seconds(),
+            request_queue_size: default_request_queue_size(),
+            request_queue_timeout_ms: default_request_queue_timeout_ms(),
             ssl: TunnelServerSslConfig::default(),            network: TunnelServerNetworkConfig::default(),
         }
     }
 }
 
+impl TunnelServerConfig {
+    /// Public URL of a subdomain: HTTPS when SSL is enabled, with the port unless it is the scheme's default
+    pub fn public_url(&self, subdomain: &str) -> String {
+        let (scheme, port, default_port) = if self.ssl.enabled {
+            ("https", self.public_https_port, 443)
+        } else {
+            ("http", self.public_port, 80)
+        };
+        if port == default_port {
+            format!("{}://{}.{}", scheme, subdomain, self.base_domain)
+        } else {
+            format!("{}://{}.{}:{}", scheme, subdomain, self.base_domain, port)
+        }
+    }
+}
+
 impl Default for TunnelServerNetworkConfig {
     fn default() -> Self {
         Self {
@@ -500,12 +592,6 @@ impl Default for TunnelServerNetworkConfig {
     }
 }
 
-impl Default for SubdomainStrategy {
-    fn default() -> Self {
-        SubdomainStrategy::Random
-    }
-}
-
 impl Default for TunnelRateLimitConfig {
     fn default() -> Self {
         Self {
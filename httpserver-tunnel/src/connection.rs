@@ -4,12 +4,13 @@
 use crate::{TunnelError, TunnelResult};
 use crate::auth::{TunnelAuthenticator, TunnelCredentials};
 use crate::config::{TunnelEndpoint, ReconnectionConfig};
-use crate::status::{ConnectionHealth, TunnelMetrics};
-use crate::protocol::TunnelMessage;
+use crate::status::{calculate_health_score, ConnectionHealth, TunnelMetrics};
+use crate::protocol::{CAPABILITY_DEFLATE, TunnelFrame, TunnelMessage, TunnelProtocol};
 
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, RwLock, watch};
 use tokio::time::{interval, sleep};
@@ -94,19 +95,23 @@ pub struct TunnelConnection {
     reconnection_config: ReconnectionConfig,
     
     // Communication channels
-    message_sender: Option<mpsc::UnboundedSender<TunnelMessage>>,
+    message_sender: RwLock<Option<mpsc::UnboundedSender<TunnelMessage>>>,
     status_sender: watch::Sender<ConnectionState>,
     status_receiver: watch::Receiver<ConnectionState>,
     
     // Connection metrics
     metrics: Arc<RwLock<TunnelMetrics>>,
-    connection_start: Option<Instant>,
-    retry_count: u32,    
+    connection_start: RwLock<Option<Instant>>, // Set while the WebSocket is open
+    retry_count: AtomicU32,
+    last_error: RwLock<Option<String>>,
+    pending_ping: RwLock<Option<(u64, Instant)>>, // Timestamp and send time of the unanswered keep-alive
+
     // Connection info
     public_url: Arc<RwLock<Option<String>>>,
     tunnel_id: Arc<RwLock<Option<String>>>,
     #[allow(dead_code)]
     session_id: Arc<RwLock<Option<String>>>,
+    compression: AtomicBool, // Negotiated with the server during authentication
     
     // HTTP client for forwarding requests to local server
     http_client: reqwest::Client,
@@ -137,27 +142,30 @@ impl TunnelConnection {    /// Create new tunnel connection
             state: Arc::new(RwLock::new(ConnectionState::Disconnected)),
             reconnection_strategy,
             reconnection_config,
-            message_sender: None,
+            message_sender: RwLock::new(None),
             status_sender,
             status_receiver,
             metrics: Arc::new(RwLock::new(TunnelMetrics::new())),
-            connection_start: None,
-            retry_count: 0,
+            connection_start: RwLock::new(None),
+            retry_count: AtomicU32::new(0),
+            last_error: RwLock::new(None),
+            pending_ping: RwLock::new(None),
             public_url: Arc::new(RwLock::new(None)),
             tunnel_id: Arc::new(RwLock::new(None)),
             session_id: Arc::new(RwLock::new(None)),
+            compression: AtomicBool::new(false),
             http_client,
             local_server_url,
         }
     }    /// Start tunnel connection with auto-reconnection
-    pub async fn start(&mut self) -> TunnelResult<()> {
+    pub async fn start(&self) -> TunnelResult<()> {
         self.set_state(ConnectionState::Connecting).await;
         
         loop {
             match self.connect_once().await {
                 Ok(()) => {
                     // Connection successful, reset retry count
-                    self.retry_count = 0;
+                    self.retry_count.store(0, Ordering::Relaxed);
                     tracing::info!("Tunnel connection established successfully");
                     
                     // Connection is maintained in connect_once until it fails
@@ -171,6 +179,9 @@ impl TunnelConnection {    /// Create new tunnel connection
                 }
                 Err(e) => {
                     tracing::error!(error = %e, "Tunnel connection failed");
+                    *self.connection_start.write().await = None;
+                    *self.last_error.write().await = Some(e.to_string());
+                    self.metrics.write().await.record_connection_failure(&e.to_string());
                     self.set_state(ConnectionState::Failed(e.to_string())).await;
                     
                     if !self.reconnection_config.enabled {
@@ -178,10 +189,11 @@ impl TunnelConnection {    /// Create new tunnel connection
                     }
                     
                     // Check retry limit
+                    let retry_count = self.retry_count.load(Ordering::Relaxed);
                     if self.reconnection_config.max_attempts > 0 && 
-                       self.retry_count >= self.reconnection_config.max_attempts {
+                       retry_count >= self.reconnection_config.max_attempts {
                         tracing::error!(
-                            attempts = self.retry_count,
+                            attempts = retry_count,
                             max_attempts = self.reconnection_config.max_attempts,
                             "Maximum retry attempts reached"
                         );
@@ -191,12 +203,12 @@ impl TunnelConnection {    /// Create new tunnel connection
             }
             
             // Calculate retry delay
-            let delay = self.reconnection_strategy.next_delay(self.retry_count);
-            self.retry_count += 1;
+            let retry_count = self.retry_count.fetch_add(1, Ordering::Relaxed);
+            let delay = self.reconnection_strategy.next_delay(retry_count);
             
             tracing::info!(
                 delay_secs = delay.as_secs(),
-                attempt = self.retry_count,
+                attempt = retry_count + 1,
                 "Retrying tunnel connection"
             );
             
@@ -207,9 +219,7 @@ impl TunnelConnection {    /// Create new tunnel connection
     }
 
     /// Attempt single connection
-    async fn connect_once(&mut self) -> TunnelResult<()> {
-        self.connection_start = Some(Instant::now());
-        
+    async fn connect_once(&self) -> TunnelResult<()> {
         // Parse WebSocket URL
         let ws_url = Url::parse(&self.endpoint.server_url)
             .map_err(|e| TunnelError::InvalidConfig(format!("Invalid server URL: {}", e)))?;
@@ -240,6 +250,7 @@ impl TunnelConnection {    /// Create new tunnel connection
 
         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
         
+        *self.connection_start.write().await = Some(Instant::now());
         self.set_state(ConnectionState::Connected).await;
         
         // Authenticate
@@ -249,7 +260,9 @@ impl TunnelConnection {    /// Create new tunnel connection
         if !auth_success {
             return Err(TunnelError::AuthenticationFailed("Server rejected authentication".to_string()));
         }
-          self.set_state(ConnectionState::Authenticated).await;
+          self.metrics.write().await.record_connection_success();
+        *self.last_error.write().await = None;
+        self.set_state(ConnectionState::Authenticated).await;
         let public_url = self.get_public_url().await.unwrap_or_default();
         tracing::info!(
             public_url = %public_url,
@@ -258,7 +271,7 @@ impl TunnelConnection {    /// Create new tunnel connection
         
         // Start message handling
         let (message_tx, mut message_rx) = mpsc::unbounded_channel::<TunnelMessage>();
-        self.message_sender = Some(message_tx);
+        *self.message_sender.write().await = Some(message_tx);
         
         // Keep-alive task
         let keepalive_interval_secs = self.endpoint.keepalive_interval;
@@ -271,6 +284,7 @@ impl TunnelConnection {    /// Create new tunnel connection
                 msg_result = ws_receiver.next() => {
                     match msg_result {
                         Some(Ok(msg)) => {
+                            self.record_bytes(msg.len()).await;
                             if let Err(e) = self.handle_websocket_message(msg).await {
                                 tracing::error!(error = %e, "Error handling WebSocket message");
                                 break;
@@ -289,7 +303,15 @@ impl TunnelConnection {    /// Create new tunnel connection
                 
                 // Handle outgoing messages
                 Some(tunnel_msg) = message_rx.recv() => {
-                    let ws_msg = Message::Text(serde_json::to_string(&tunnel_msg).unwrap());
+                    let ws_msg = match TunnelProtocol::encode_frame(&tunnel_msg, self.compression.load(Ordering::Relaxed)) {
+                        Ok(TunnelFrame::Text(text)) => Message::Text(text),
+                        Ok(TunnelFrame::Binary(data)) => Message::Binary(data),
+                        Err(e) => {
+                            tracing::error!(error = %e, "Failed to encode tunnel message");
+                            continue;
+                        }
+                    };
+                    self.record_bytes(ws_msg.len()).await;
                     if let Err(e) = ws_sender.send(ws_msg).await {
                         tracing::error!(error = %e, "Failed to send WebSocket message");
                         break;
@@ -298,10 +320,11 @@ impl TunnelConnection {    /// Create new tunnel connection
                 
                 // Send keep-alive ping
                 _ = keepalive_timer.tick() => {
-                    let ping_msg = TunnelMessage::Ping {
-                        timestamp: chrono::Utc::now().timestamp() as u64,
-                    };
+                    let timestamp = chrono::Utc::now().timestamp() as u64;
+                    let ping_msg = TunnelMessage::Ping { timestamp };
+                    self.record_ping_sent(timestamp).await;
                     let ws_msg = Message::Text(serde_json::to_string(&ping_msg).unwrap());
+                    self.record_bytes(ws_msg.len()).await;
                     if let Err(e) = ws_sender.send(ws_msg).await {
                         tracing::error!(error = %e, "Failed to send keep-alive ping");
                         break;
@@ -310,6 +333,9 @@ impl TunnelConnection {    /// Create new tunnel connection
             }
         }
         
+        *self.message_sender.write().await = None;
+        *self.connection_start.write().await = None;
+        *self.pending_ping.write().await = None;
         self.set_state(ConnectionState::Disconnected).await;
         Ok(())
     }
@@ -366,10 +392,20 @@ impl TunnelConnection {    /// Create new tunnel connection
             auth_header.clone()
         };
         
+        // Advertise the optional features this endpoint wants
+        let mut capabilities = Vec::new();
+        if self.endpoint.compression {
+            capabilities.push(CAPABILITY_DEFLATE.to_string());
+        }
+        if let Some(version) = self.endpoint.proxy_protocol {
+            capabilities.push(version.capability().to_string());
+        }
+
         let auth_msg = TunnelMessage::Auth {
             token,
             subdomain: self.endpoint.subdomain.clone(),
             protocol_version: self.endpoint.protocol_version.clone(),
+            capabilities,
         };
 
         // Send authentication
@@ -390,15 +426,18 @@ impl TunnelConnection {    /// Create new tunnel connection
             match tokio::time::timeout(Duration::from_secs(5), ws_receiver.next()).await {
                 Ok(Some(Ok(Message::Text(text)))) => {
                     match serde_json::from_str::<TunnelMessage>(&text) {
-                        Ok(TunnelMessage::AuthResponse { success, assigned_subdomain, error }) => {
+                        Ok(TunnelMessage::AuthResponse { success, assigned_subdomain, error, public_url, capabilities }) => {
                             if success {
                                 tracing::info!("Authentication successful");
-                                if let Some(subdomain) = assigned_subdomain {
-                                    // Construct public URL from subdomain and base domain
-                                    // This should ideally come from server config but we'll construct it
-                                    let public_url = format!("http://{}.httpserver.io", subdomain);
-                                    *self.public_url.write().await = Some(public_url);
+                                // Only use features that were offered and agreed
+                                let compression = self.endpoint.compression
+                                    && capabilities.iter().any(|cap| cap == CAPABILITY_DEFLATE);
+                                self.compression.store(compression, Ordering::Relaxed);
+                                // The server knows its own scheme, base domain and port
+                                if public_url.is_none() {
+                                    tracing::warn!(subdomain = ?assigned_subdomain, "Server did not report a public URL");
                                 }
+                                *self.public_url.write().await = public_url;
                                 return Ok(true);
                             } else {
                                 let error_msg = error.unwrap_or_else(|| "Unknown error".to_string());
@@ -451,9 +490,16 @@ impl TunnelConnection {    /// Create new tunnel connection
                     }
                 }
             }
-            Message::Binary(_) => {
-                tracing::warn!("Received unexpected binary message");
-                Ok(())
+            Message::Binary(data) => {
+                match TunnelProtocol::decode_frame(&data, self.compression.load(Ordering::Relaxed)) {
+                    Ok(tunnel_msg) => {
+                        self.process_tunnel_message(tunnel_msg).await
+                    }
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Failed to decode binary tunnel message");
+                        Ok(())
+                    }
+                }
             }            Message::Ping(_data) => {
                 // WebSocket ping handled automatically by tungstenite
                 tracing::debug!("Received WebSocket ping");
@@ -475,14 +521,14 @@ impl TunnelConnection {    /// Create new tunnel connection
     }    /// Process tunnel protocol message
     async fn process_tunnel_message(&self, message: TunnelMessage) -> TunnelResult<()> {
         match message {
-            TunnelMessage::HttpRequest { id, method, path, headers, body, client_ip } => {
+            TunnelMessage::HttpRequest { id, method, path, headers, body, client_ip, .. } => {
                 tracing::debug!(id = %id, method = %method, path = %path, client_ip = %client_ip, "Received HTTP request, forwarding to local server");
                 
                 // Forward the HTTP request to the local server
                 match self.forward_http_request(&id, &method, &path, headers, body).await {
                     Ok(response) => {
                         // Send the response back through the tunnel
-                        if let Some(sender) = &self.message_sender {
+                        if let Some(sender) = self.message_sender.read().await.as_ref() {
                             if let Err(e) = sender.send(response) {
                                 tracing::error!(id = %id, error = %e, "Failed to send HTTP response through tunnel");
                             } else {
@@ -495,7 +541,7 @@ impl TunnelConnection {    /// Create new tunnel connection
                     Err(e) => {
                         tracing::error!(id = %id, error = %e, "Failed to forward HTTP request to local server");
                         // Send error response back through tunnel
-                        if let Some(sender) = &self.message_sender {
+                        if let Some(sender) = self.message_sender.read().await.as_ref() {
                             let error_response = TunnelMessage::HttpResponse {
                                 id: id.clone(),
                                 status: 500,
@@ -510,7 +556,7 @@ impl TunnelConnection {    /// Create new tunnel connection
             }
             TunnelMessage::Pong { timestamp } => {
                 tracing::debug!(timestamp = timestamp, "Received pong");
-                self.update_metrics_on_pong().await;
+                self.update_metrics_on_pong(timestamp).await;
                 Ok(())
             }
             TunnelMessage::Status { connections, bytes_sent, bytes_received } => {
@@ -655,6 +701,11 @@ impl TunnelConnection {    /// Create new tunnel connection
         let _ = self.status_sender.send(state);
     }
 
+    /// Get the endpoint this connection targets
+    pub fn endpoint(&self) -> &TunnelEndpoint {
+        &self.endpoint
+    }
+
     /// Get current connection state
     pub async fn get_state(&self) -> ConnectionState {
         self.state.read().await.clone()
@@ -673,30 +724,52 @@ impl TunnelConnection {    /// Create new tunnel connection
     /// Get connection health status
     pub async fn get_health(&self) -> ConnectionHealth {
         let state = self.get_state().await;
-        let uptime = self.connection_start
+        let uptime = self.connection_start.read().await
             .map(|start| start.elapsed())
-            .unwrap_or_default();        ConnectionHealth {
+            .unwrap_or_default();
+        let retry_count = self.retry_count.load(Ordering::Relaxed);
+        let metrics = self.metrics.read().await;
+
+        ConnectionHealth {
+            health_score: calculate_health_score(&state, uptime, retry_count, &metrics),
             state,
             uptime,
-            retry_count: self.retry_count,
-            last_error: None, // TODO: Track last error
-            health_score: 0, // TODO: Calculate health score
-            last_ping: None, // TODO: Track last ping
-            avg_ping_latency: None, // TODO: Calculate average latency
+            retry_count,
+            last_error: self.last_error.read().await.clone(),
+            last_ping: metrics.last_ping_time,
+            avg_ping_latency: metrics.avg_latency(),
         }
     }
 
-    /// Update metrics on pong received
-    async fn update_metrics_on_pong(&self) {
-        let mut metrics = self.metrics.write().await;
-        metrics.last_ping_time = Some(chrono::Utc::now());
-        metrics.total_pings += 1;
+    /// Count bytes of a frame sent or received over the tunnel
+    async fn record_bytes(&self, len: usize) {
+        self.metrics.write().await.bytes_transferred += len as u64;
+    }
+
+    /// Remember a keep-alive ping as sent, counting the previous one as missed if unanswered
+    async fn record_ping_sent(&self, timestamp: u64) {
+        let previous = self.pending_ping.write().await.replace((timestamp, Instant::now()));
+        if previous.is_some() {
+            self.metrics.write().await.missed_pings += 1;
+        }
+    }
+
+    /// Update metrics on pong received, measuring the round trip of the ping it answers
+    async fn update_metrics_on_pong(&self, timestamp: u64) {
+        let mut pending_ping = self.pending_ping.write().await;
+        match *pending_ping {
+            Some((sent_timestamp, sent_at)) if sent_timestamp == timestamp => {
+                *pending_ping = None;
+                self.metrics.write().await.record_pong(sent_at.elapsed());
+            }
+            _ => tracing::debug!(timestamp = timestamp, "Ignoring pong for no pending ping"),
+        }
     }    /// Update server metrics
-    async fn update_server_metrics(&self, connections: u32, bytes_sent: u64, bytes_received: u64) {
+    async fn update_server_metrics(&self, connections: u32, _bytes_sent: u64, _bytes_received: u64) {
         let mut metrics = self.metrics.write().await;
         // Map new field names to existing metrics structure
         metrics.connected_clients = connections;
-        metrics.bytes_transferred = bytes_sent + bytes_received; // Combined for now
+        // bytes_transferred is counted locally as frames pass through the connection
         // Keep server_uptime as is since we don't have uptime in new protocol
     }
 
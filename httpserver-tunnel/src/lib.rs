@@ -10,6 +10,8 @@ pub mod status;
 pub mod server;  // Phase 7.2: Tunnel Server
 pub mod protocol;  // Phase 7.3: Tunnel Protocol
 pub mod subdomain;  // Phase 7.2: Subdomain Management
+pub mod sni;
+pub mod proxy_protocol;
 
 // Re-export main types for easy usage
 pub use client::TunnelClient;
@@ -17,8 +19,9 @@ pub use server::TunnelServer;  // Phase 7.2
 pub use config::{TunnelConfig, TunnelEndpoint, TunnelAuthConfig, TunnelServerConfig};
 pub use auth::TunnelAuthenticator;
 pub use connection::{TunnelConnection, ConnectionState, ReconnectionStrategy};
-pub use status::{TunnelStatus, ConnectionHealth, TunnelMetrics};
-pub use protocol::{TunnelMessage, TunnelProtocol};  // Phase 7.3
+pub use status::{TunnelStatus, ConnectionHealth, TunnelMetrics, ClientStatusReport, TunnelReport};
+pub use protocol::{TunnelMessage, TunnelProtocol, TunnelFrame};  // Phase 7.3
+pub use proxy_protocol::ProxyProtocolVersion;
 
 use std::error::Error;
 use std::fmt;
@@ -1,10 +1,41 @@
 // Phase 7.3 Tunnel Protocol Implementation
 // Bidirectional communication protocol for HTTP request/response forwarding through WebSocket tunnels
 
+use crate::TunnelError;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::io::{Read, Write};
 use uuid::Uuid;
 
+/// Capability to receive compressed frames
+pub const CAPABILITY_DEFLATE: &str = "deflate";
+
+/// Capabilities to have a PROXY protocol v1 or v2 header prepended to passthrough connections
+pub const CAPABILITY_PROXY_V1: &str = "proxy-v1";
+pub const CAPABILITY_PROXY_V2: &str = "proxy-v2";
+
+/// Capabilities this implementation understands, in negotiation order
+pub const SUPPORTED_CAPABILITIES: &[&str] = &[CAPABILITY_DEFLATE, CAPABILITY_PROXY_V1, CAPABILITY_PROXY_V2];
+
+/// Serialized messages smaller than this are always sent uncompressed
+pub const COMPRESSION_THRESHOLD: usize = 1024;
+
+/// Largest decompressed message accepted, matching the WebSocket message size limit
+pub const MAX_FRAME_BYTES: usize = 64 << 20;
+
+/// Leading byte of a binary frame carrying deflate-compressed JSON
+const COMPRESSED_FRAME_FLAG: u8 = 0x01;
+
+/// Encoded WebSocket frame payload
+#[derive(Debug, Clone, PartialEq)]
+pub enum TunnelFrame {
+    /// Plain JSON text frame
+    Text(String),
+    /// Compressed binary frame
+    Binary(Vec<u8>),
+}
+
 /// Tunnel protocol message types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -14,12 +45,21 @@ pub enum TunnelMessage {
         token: String,
         subdomain: Option<String>,
         protocol_version: String,
+        /// Optional features the client supports (absent for older clients)
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        capabilities: Vec<String>,
     },
     /// Server authentication response
     AuthResponse {
         success: bool,
         assigned_subdomain: Option<String>,
         error: Option<String>,
+        /// Full public URL of the assigned subdomain (absent from older servers)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        public_url: Option<String>,
+        /// Capabilities the server accepted for this connection
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        capabilities: Vec<String>,
     },
     /// HTTP request forwarding from server to client
     HttpRequest {
@@ -29,6 +69,9 @@ pub enum TunnelMessage {
         headers: HashMap<String, String>,
         body: Option<Vec<u8>>,
         client_ip: String,
+        /// Connection-local id of the logical tunnel (None for the tunnel opened by Auth)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tunnel_id: Option<String>,
     },
     /// HTTP response forwarding from client to server
     HttpResponse {
@@ -56,10 +99,29 @@ pub enum TunnelMessage {
         bytes_sent: u64,
         bytes_received: u64,
     },
+    /// Open an additional logical tunnel over an authenticated connection
+    OpenTunnel {
+        tunnel_id: String,
+        subdomain: Option<String>,
+    },
+    /// Server response to an OpenTunnel request
+    TunnelOpened {
+        tunnel_id: String,
+        success: bool,
+        assigned_subdomain: Option<String>,
+        error: Option<String>,
+    },
+    /// Close a logical tunnel while keeping the connection open
+    CloseTunnel {
+        tunnel_id: String,
+    },
     /// SSL/TLS connection establishment for passthrough
     SslConnect {
         id: String,
         initial_data: Option<Vec<u8>>,
+        /// IP of the public client, for the local side to log or pass on (e.g. via PROXY protocol)
+        #[serde(default)]
+        client_ip: String,
     },
     /// SSL/TLS data forwarding
     SslData {
@@ -92,6 +154,7 @@ impl TunnelProtocol {
             token: token.to_string(),
             subdomain: subdomain.map(|s| s.to_string()),
             protocol_version: "1.0".to_string(),
+            capabilities: Vec::new(),
         }
     }
 
@@ -110,6 +173,7 @@ impl TunnelProtocol {
             headers,
             body,
             client_ip: client_ip.to_string(),
+            tunnel_id: None,
         }
     }
 
@@ -128,6 +192,21 @@ impl TunnelProtocol {
         }
     }
 
+    /// Create message opening an additional logical tunnel
+    pub fn create_open_tunnel_message(tunnel_id: &str, subdomain: Option<&str>) -> TunnelMessage {
+        TunnelMessage::OpenTunnel {
+            tunnel_id: tunnel_id.to_string(),
+            subdomain: subdomain.map(|s| s.to_string()),
+        }
+    }
+
+    /// Create message closing a logical tunnel
+    pub fn create_close_tunnel_message(tunnel_id: &str) -> TunnelMessage {
+        TunnelMessage::CloseTunnel {
+            tunnel_id: tunnel_id.to_string(),
+        }
+    }
+
     /// Create ping message
     pub fn create_ping_message() -> TunnelMessage {
         TunnelMessage::Ping {
@@ -154,10 +233,15 @@ impl TunnelProtocol {
     }
 
     /// Create SSL connect message
-    pub fn create_ssl_connect_message(connection_id: &str, initial_data: Option<Vec<u8>>) -> TunnelMessage {
+    pub fn create_ssl_connect_message(
+        connection_id: &str,
+        initial_data: Option<Vec<u8>>,
+        client_ip: &str
+    ) -> TunnelMessage {
         TunnelMessage::SslConnect {
             id: connection_id.to_string(),
             initial_data,
+            client_ip: client_ip.to_string(),
         }
     }
 
@@ -186,11 +270,75 @@ impl TunnelProtocol {
         serde_json::from_slice(data)
     }
 
+    /// Encode a message for the wire, compressing it when enabled and large enough
+    pub fn encode_frame(message: &TunnelMessage, compress: bool) -> Result<TunnelFrame, TunnelError> {
+        let json = serde_json::to_string(message)
+            .map_err(|e| TunnelError::SerializationError(format!("Failed to serialize message: {}", e)))?;
+
+        if !compress || json.len() < COMPRESSION_THRESHOLD {
+            return Ok(TunnelFrame::Text(json));
+        }
+
+        let mut encoder = DeflateEncoder::new(vec![COMPRESSED_FRAME_FLAG], Compression::default());
+        encoder.write_all(json.as_bytes())
+            .map_err(|e| TunnelError::SerializationError(format!("Failed to compress message: {}", e)))?;
+        let frame = encoder.finish()
+            .map_err(|e| TunnelError::SerializationError(format!("Failed to compress message: {}", e)))?;
+
+        Ok(TunnelFrame::Binary(frame))
+    }
+
+    /// Decode a binary frame, which is either compressed or plain JSON
+    ///
+    /// Compressed frames are only accepted when `compression` was negotiated for the connection,
+    /// and may not inflate past `MAX_FRAME_BYTES`.
+    pub fn decode_frame(data: &[u8], compression: bool) -> Result<TunnelMessage, TunnelError> {
+        match data.split_first() {
+            Some((&COMPRESSED_FRAME_FLAG, compressed)) => {
+                if !compression {
+                    return Err(TunnelError::ProtocolError("Compressed frame on a connection without deflate".to_string()));
+                }
+                let mut json = Vec::new();
+                DeflateDecoder::new(compressed).take(MAX_FRAME_BYTES as u64 + 1).read_to_end(&mut json)
+                    .map_err(|e| TunnelError::ProtocolError(format!("Failed to decompress message: {}", e)))?;
+                if json.len() > MAX_FRAME_BYTES {
+                    return Err(TunnelError::ProtocolError(format!(
+                        "Decompressed message exceeds {} bytes",
+                        MAX_FRAME_BYTES
+                    )));
+                }
+                Self::deserialize_message(&json)
+                    .map_err(|e| TunnelError::SerializationError(format!("Failed to parse message: {}", e)))
+            }
+            _ => Self::deserialize_message(data)
+                .map_err(|e| TunnelError::SerializationError(format!("Failed to parse message: {}", e))),
+        }
+    }
+
+    /// Split a protocol version such as "1.0+deflate" into version and capabilities
+    pub fn split_version(protocol_version: &str) -> (&str, Vec<&str>) {
+        let mut parts = protocol_version.split('+');
+        let version = parts.next().unwrap_or_default();
+        (version, parts.filter(|cap| !cap.is_empty()).collect())
+    }
+
+    /// Agree on the capabilities both sides support.
+    /// Offers come from the `capabilities` list and from legacy version suffixes ("1.0+deflate");
+    /// unknown capabilities are ignored and a version-only client negotiates none.
+    pub fn negotiate_capabilities(protocol_version: &str, offered: &[String]) -> Vec<String> {
+        let (_, suffix_capabilities) = Self::split_version(protocol_version);
+        SUPPORTED_CAPABILITIES
+            .iter()
+            .filter(|cap| suffix_capabilities.contains(cap) || offered.iter().any(|o| o == *cap))
+            .map(|cap| cap.to_string())
+            .collect()
+    }
+
     /// Validate protocol version compatibility
     pub fn is_compatible_version(&self, client_version: &str) -> bool {
-        // For now, only support exact version match
+        // Capabilities are negotiated separately; only the base version must match
         // Future: implement semantic versioning compatibility
-        client_version == self.protocol_version
+        Self::split_version(client_version).0 == self.protocol_version
     }
 }
 
@@ -208,15 +356,130 @@ mod tests {
     fn test_create_auth_message() {
         let msg = TunnelProtocol::create_auth_message("test-token", Some("myapp"));
         match msg {
-            TunnelMessage::Auth { token, subdomain, protocol_version } => {
+            TunnelMessage::Auth { token, subdomain, protocol_version, capabilities } => {
                 assert_eq!(token, "test-token");
                 assert_eq!(subdomain, Some("myapp".to_string()));
                 assert_eq!(protocol_version, "1.0");
+                assert!(capabilities.is_empty());
             }
             _ => panic!("Expected Auth message"),
         }
     }
 
+    #[test]
+    fn test_http_request_without_tunnel_id_is_compatible() {
+        let json = r#"{"type":"HttpRequest","id":"r1","method":"GET","path":"/","headers":{},"body":null,"client_ip":"1.2.3.4"}"#;
+        match TunnelProtocol::deserialize_message(json.as_bytes()).unwrap() {
+            TunnelMessage::HttpRequest { tunnel_id, .. } => assert_eq!(tunnel_id, None),
+            _ => panic!("Expected HttpRequest message"),
+        }
+
+        let msg = TunnelProtocol::create_http_request_message("GET", "/", HashMap::new(), None, "1.2.3.4");
+        let serialized = String::from_utf8(TunnelProtocol::serialize_message(&msg).unwrap()).unwrap();
+        assert!(!serialized.contains("tunnel_id"));
+    }
+
+    #[test]
+    fn test_compressed_frame_round_trip() {
+        let body = "<p>hello tunnel</p>".repeat(500).into_bytes();
+        let msg = TunnelProtocol::create_http_response_message("r1", 200, HashMap::new(), Some(body.clone()));
+
+        let frame = TunnelProtocol::encode_frame(&msg, true).unwrap();
+        let data = match frame {
+            TunnelFrame::Binary(data) => data,
+            TunnelFrame::Text(_) => panic!("Large message should be compressed"),
+        };
+        assert!(data.len() < body.len());
+
+        match TunnelProtocol::decode_frame(&data, true).unwrap() {
+            TunnelMessage::HttpResponse { body: decoded, .. } => assert_eq!(decoded, Some(body)),
+            _ => panic!("Expected HttpResponse message"),
+        }
+    }
+
+    #[test]
+    fn test_compressed_frame_rejected_without_negotiation() {
+        let msg = TunnelProtocol::create_http_response_message("r1", 200, HashMap::new(), Some(vec![b'a'; 4096]));
+        let TunnelFrame::Binary(data) = TunnelProtocol::encode_frame(&msg, true).unwrap() else {
+            panic!("Large message should be compressed");
+        };
+        assert!(matches!(TunnelProtocol::decode_frame(&data, false), Err(TunnelError::ProtocolError(_))));
+    }
+
+    #[test]
+    fn test_decompression_bomb_is_rejected() {
+        let mut encoder = DeflateEncoder::new(vec![COMPRESSED_FRAME_FLAG], Compression::best());
+        let zeros = vec![0u8; 1 << 20];
+        for _ in 0..=(MAX_FRAME_BYTES >> 20) {
+            encoder.write_all(&zeros).unwrap();
+        }
+        let data = encoder.finish().unwrap();
+        assert!(data.len() < 1 << 20);
+
+        match TunnelProtocol::decode_frame(&data, true) {
+            Err(TunnelError::ProtocolError(message)) => assert!(message.contains("exceeds")),
+            other => panic!("Expected size limit error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_small_or_disabled_frames_stay_plain() {
+        let ping = TunnelProtocol::create_ping_message();
+        assert!(matches!(TunnelProtocol::encode_frame(&ping, true).unwrap(), TunnelFrame::Text(_)));
+
+        let large = TunnelProtocol::create_http_response_message("r1", 200, HashMap::new(), Some(vec![b'a'; 4096]));
+        let frame = TunnelProtocol::encode_frame(&large, false).unwrap();
+        match frame {
+            TunnelFrame::Text(json) => assert!(TunnelProtocol::decode_frame(json.as_bytes(), false).is_ok()),
+            TunnelFrame::Binary(_) => panic!("Compression disabled"),
+        }
+    }
+
+    #[test]
+    fn test_version_capabilities() {
+        let protocol = TunnelProtocol::new();
+        assert_eq!(TunnelProtocol::split_version("1.0+deflate"), ("1.0", vec!["deflate"]));
+        assert_eq!(TunnelProtocol::split_version("1.0"), ("1.0", vec![]));
+        assert!(protocol.is_compatible_version("1.0+deflate"));
+        assert!(!protocol.is_compatible_version("2.0+deflate"));
+    }
+
+    #[test]
+    fn test_capability_negotiation() {
+        // Only capabilities both sides know are agreed, without duplicates
+        let offered = vec!["compress-v9".to_string(), "deflate".to_string(), "deflate".to_string()];
+        assert_eq!(TunnelProtocol::negotiate_capabilities("1.0", &offered), vec!["deflate"]);
+        assert_eq!(TunnelProtocol::negotiate_capabilities("1.0+deflate", &offered), vec!["deflate"]);
+        assert!(TunnelProtocol::negotiate_capabilities("1.0", &["compress-v9".to_string()]).is_empty());
+
+        // Legacy suffix offers still count
+        assert_eq!(TunnelProtocol::negotiate_capabilities("1.0+deflate", &[]), vec!["deflate"]);
+    }
+
+    #[test]
+    fn test_version_only_auth_is_compatible() {
+        // An older client sends no capabilities field at all
+        let json = r#"{"type":"Auth","token":"t","subdomain":null,"protocol_version":"1.0"}"#;
+        match TunnelProtocol::deserialize_message(json.as_bytes()).unwrap() {
+            TunnelMessage::Auth { protocol_version, capabilities, .. } => {
+                assert!(capabilities.is_empty());
+                assert!(TunnelProtocol::negotiate_capabilities(&protocol_version, &capabilities).is_empty());
+            }
+            other => panic!("Expected Auth message, got {:?}", other),
+        }
+
+        // Nothing negotiated means nothing extra on the wire
+        let response = TunnelMessage::AuthResponse {
+            success: true,
+            assigned_subdomain: Some("myapp".to_string()),
+            error: None,
+            public_url: None,
+            capabilities: Vec::new(),
+        };
+        let serialized = TunnelProtocol::serialize_message(&response).unwrap();
+        assert!(!String::from_utf8(serialized).unwrap().contains("capabilities"));
+    }
+
     #[test]
     fn test_serialize_deserialize() {
         let original = TunnelProtocol::create_ping_message();
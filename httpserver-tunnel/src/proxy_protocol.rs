@@ -0,0 +1,85 @@
+//! PROXY Protocol
+//! Headers that tell a backend behind a passthrough connection who the real client is
+
+use crate::protocol::{CAPABILITY_PROXY_V1, CAPABILITY_PROXY_V2};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+
+/// Binary signature opening every version 2 header
+const V2_SIGNATURE: [u8; 12] = [0x0d, 0x0a, 0x0d, 0x0a, 0x00, 0x0d, 0x0a, 0x51, 0x55, 0x49, 0x54, 0x0a];
+
+/// PROXY protocol version a backend expects ahead of the connection's own bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyProtocolVersion {
+    /// Human-readable text header
+    V1,
+    /// Binary header
+    V2,
+}
+
+impl ProxyProtocolVersion {
+    /// Capability a tunnel client offers to have this header prepended
+    pub fn capability(self) -> &'static str {
+        match self {
+            ProxyProtocolVersion::V1 => CAPABILITY_PROXY_V1,
+            ProxyProtocolVersion::V2 => CAPABILITY_PROXY_V2,
+        }
+    }
+
+    /// Version agreed during capability negotiation, preferring v2 if both were
+    pub fn from_capabilities(capabilities: &[String]) -> Option<Self> {
+        [ProxyProtocolVersion::V2, ProxyProtocolVersion::V1]
+            .into_iter()
+            .find(|version| capabilities.iter().any(|cap| cap == version.capability()))
+    }
+
+    /// Header describing a TCP connection from `source` to `destination`
+    pub fn header(self, source: SocketAddr, destination: SocketAddr) -> Vec<u8> {
+        // Both ends must share a family; mixed pairs are described as IPv6
+        let (source_ip, destination_ip) = match (source.ip(), destination.ip()) {
+            (IpAddr::V4(source_ip), IpAddr::V4(destination_ip)) => (IpAddr::V4(source_ip), IpAddr::V4(destination_ip)),
+            (source_ip, destination_ip) => (IpAddr::V6(to_ipv6(source_ip)), IpAddr::V6(to_ipv6(destination_ip))),
+        };
+
+        match self {
+            ProxyProtocolVersion::V1 => {
+                let family = if source_ip.is_ipv4() { "TCP4" } else { "TCP6" };
+                format!(
+                    "PROXY {} {} {} {} {}\r\n",
+                    family,
+                    source_ip,
+                    destination_ip,
+                    source.port(),
+                    destination.port()
+                ).into_bytes()
+            }
+            ProxyProtocolVersion::V2 => {
+                let mut header = V2_SIGNATURE.to_vec();
+                header.push(0x21); // version 2, PROXY command
+                let addresses = match (source_ip, destination_ip) {
+                    (IpAddr::V4(source_ip), IpAddr::V4(destination_ip)) => {
+                        header.push(0x11); // TCP over IPv4
+                        [source_ip.octets().to_vec(), destination_ip.octets().to_vec()].concat()
+                    }
+                    (source_ip, destination_ip) => {
+                        header.push(0x21); // TCP over IPv6
+                        [to_ipv6(source_ip).octets().to_vec(), to_ipv6(destination_ip).octets().to_vec()].concat()
+                    }
+                };
+                header.extend(((addresses.len() + 4) as u16).to_be_bytes());
+                header.extend(addresses);
+                header.extend(source.port().to_be_bytes());
+                header.extend(destination.port().to_be_bytes());
+                header
+            }
+        }
+    }
+}
+
+fn to_ipv6(ip: IpAddr) -> std::net::Ipv6Addr {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+        IpAddr::V6(ip) => ip,
+    }
+}
@@ -1,15 +1,22 @@
 // Phase 7.2 Tunnel Server - Public HTTP Server Integration
 // Tunnel server that accepts WebSocket connections from tunnel clients and routes public traffic
 
-use crate::{ TunnelError, config::TunnelServerConfig, protocol::{ TunnelMessage, TunnelProtocol } };
+use crate::{
+    TunnelError,
+    config::TunnelServerConfig,
+    protocol::{ CAPABILITY_DEFLATE, TunnelFrame, TunnelMessage, TunnelProtocol },
+};
 use crate::subdomain::SubdomainManager;
+use crate::sni::read_client_hello;
+use crate::proxy_protocol::ProxyProtocolVersion;
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::time::{ Duration, Instant };
 use std::cmp::min;
 use base64::{ Engine as _, engine::general_purpose };
-use tokio::sync::{ RwLock, broadcast, mpsc, oneshot };
+use tokio::sync::{ Notify, RwLock, broadcast, mpsc, oneshot };
 use futures_util::{ SinkExt, StreamExt };
 use uuid::Uuid;
 use axum::{
@@ -42,6 +49,12 @@ pub enum ConnectionType {
 /// Tunnel server result type
 pub type ServerResult<T> = Result<T, TunnelError>;
 
+/// Error returned to clients when `max_tunnels` is reached
+const SERVER_AT_CAPACITY: &str = "server at capacity";
+
+/// How long an SSL passthrough client may take to send its Client Hello
+const CLIENT_HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Pending HTTP request waiting for response
 #[derive(Debug)]
 pub struct PendingRequest {
@@ -50,16 +63,65 @@ pub struct PendingRequest {
     pub created_at: std::time::Instant,
 }
 
+/// Liveness state of a tunnel client connection, shared with the idle sweeper
+#[derive(Debug, Clone)]
+pub struct TunnelLiveness {
+    last_seen: Arc<std::sync::Mutex<Instant>>,
+    close_signal: Arc<Notify>,
+}
+
+impl TunnelLiveness {
+    /// Create liveness state for a freshly accepted connection
+    pub fn new() -> Self {
+        Self {
+            last_seen: Arc::new(std::sync::Mutex::new(Instant::now())),
+            close_signal: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Record that the client was heard from
+    pub fn touch(&self) {
+        *self.last_seen.lock().unwrap() = Instant::now();
+    }
+
+    /// Time elapsed since the client was last heard from
+    pub fn idle_for(&self) -> Duration {
+        self.last_seen.lock().unwrap().elapsed()
+    }
+
+    /// Ask the connection task to close the WebSocket
+    pub fn close(&self) {
+        self.close_signal.notify_one();
+    }
+}
+
+impl ActiveTunnel {
+    /// Requests waiting in the connection's queue to be written to the client
+    pub fn queued_requests(&self) -> usize {
+        self.request_sender.max_capacity() - self.request_sender.capacity()
+    }
+}
+
+impl Default for TunnelLiveness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Active tunnel connection
 #[derive(Debug, Clone)]
 pub struct ActiveTunnel {
     pub id: String,
+    pub connection_id: String, // WebSocket connection carrying this tunnel
+    pub local_id: Option<String>, // Connection-local id, None for the tunnel opened by Auth
     pub subdomain: String,
     pub client_ip: String,
     pub user_info: Option<String>, // User extracted from token
     pub authenticated: bool,
     pub connected_at: std::time::SystemTime,
     pub request_sender: mpsc::Sender<TunnelMessage>,
+    pub liveness: TunnelLiveness,
+    pub proxy_protocol: Option<ProxyProtocolVersion>, // Header prepended to passthrough connections
 }
 
 /// Tunnel server state
@@ -71,6 +133,7 @@ pub struct TunnelServerState {
     pub pending_requests: RwLock<HashMap<String, PendingRequest>>, // request_id -> pending_request
     pub active_ssl_connections: RwLock<HashMap<String, mpsc::Sender<Vec<u8>>>>, // connection_id -> ssl_data_sender
     pub rate_limiter: Arc<std::sync::Mutex<RateLimiter>>, // Rate limiting state
+    pub pool_cursors: std::sync::Mutex<HashMap<String, usize>>, // subdomain -> next pool turn
     pub protocol: TunnelProtocol,
     pub shutdown_sender: broadcast::Sender<()>,
 }
@@ -95,14 +158,24 @@ pub struct TunnelServer {
 impl TunnelServer {
     /// Create new tunnel server
     pub fn new(config: TunnelServerConfig) -> ServerResult<Self> {
-        let (shutdown_sender, _) = broadcast::channel(100);
-
         // Create subdomain manager with persistent storage
-        let storage_path = std::env
-            ::current_dir()
-            .unwrap_or_else(|_| std::path::PathBuf::from("."))
-            .join("tunnel_data")
-            .join("subdomains.json");
+        let storage_path = config.storage_path.clone().unwrap_or_else(|| {
+            std::env
+                ::current_dir()
+                .unwrap_or_else(|_| std::path::PathBuf::from("."))
+                .join("tunnel_data")
+                .join("subdomains.json")
+        });
+
+        Self::with_storage_path(config, storage_path)
+    }
+
+    /// Create new tunnel server persisting subdomain allocations at the given path
+    pub fn with_storage_path(
+        config: TunnelServerConfig,
+        storage_path: std::path::PathBuf
+    ) -> ServerResult<Self> {
+        let (shutdown_sender, _) = broadcast::channel(100);
 
         let subdomain_manager = SubdomainManager::new(config.clone(), storage_path);
         let state = Arc::new(TunnelServerState {
@@ -112,12 +185,19 @@ impl TunnelServer {
             pending_requests: RwLock::new(HashMap::new()),
             active_ssl_connections: RwLock::new(HashMap::new()),
             rate_limiter: Arc::new(std::sync::Mutex::new(RateLimiter::default())),
+            pool_cursors: std::sync::Mutex::new(HashMap::new()),
             protocol: TunnelProtocol::new(),
             shutdown_sender,
         });
 
         Ok(Self { config, state })
     }
+    /// Signal background tasks to stop and flush subdomain storage to disk
+    pub async fn shutdown(&self) -> ServerResult<()> {
+        let _ = self.state.shutdown_sender.send(());
+        self.state.subdomain_manager.shutdown().await
+    }
+
     /// Start the tunnel server
     pub async fn start(&self) -> ServerResult<()> {
         if !self.config.enabled {
@@ -149,7 +229,17 @@ impl TunnelServer {
         let state_for_cleanup = self.state.clone();
         tokio::spawn(async move {
             Self::cleanup_expired_requests(state_for_cleanup).await;
-        }); // Start both servers concurrently
+        });
+
+        // Start sweeper for tunnels whose clients went silent
+        if self.config.idle_timeout > 0 {
+            let state_for_sweeper = self.state.clone();
+            tokio::spawn(async move {
+                Self::evict_idle_tunnels(state_for_sweeper).await;
+            });
+        }
+
+        // Start both servers concurrently
         let public_addr = SocketAddr::new(
             self.config.network.public_bind_address
                 .parse()
@@ -244,44 +334,44 @@ impl TunnelServer {
         let subdomain = Self::extract_subdomain(host, &state.config.base_domain);
         info!("Extracted subdomain: {:?} from host: {} with base domain: {}", subdomain, host, state.config.base_domain);
         
-        let tunnel_id = if let Some(subdomain) = &subdomain {
+        let tunnel_ids = if let Some(subdomain) = &subdomain {
             // Standard subdomain routing (e.g., abc123.httpserver.io)
-            let tunnel_for_subdomain = state.subdomain_manager.get_tunnel_for_subdomain(subdomain).await;
-            info!("Tunnel for subdomain '{}': {:?}", subdomain, tunnel_for_subdomain);
-            tunnel_for_subdomain
+            let tunnels_for_subdomain = state.subdomain_manager.get_tunnels_for_subdomain(subdomain).await;
+            info!("Tunnels for subdomain '{}': {:?}", subdomain, tunnels_for_subdomain);
+            tunnels_for_subdomain
         } else {
             // Check if it's a custom domain (e.g., myapp.com)
             info!("No subdomain extracted, checking custom domain for: {}", host);
-            state.subdomain_manager.get_tunnel_for_custom_domain(host).await
-        };        let tunnel_id = match tunnel_id {
-            Some(id) => {
-                info!("Found tunnel ID: {} for host: {}", id, host);
-                id
-            },
-            None => {
-                // Debug: Show all active tunnels
-                let tunnels = state.active_tunnels.read().await;
-                let tunnel_count = tunnels.len();
-                info!("No tunnel found for host: {}. Active tunnels ({}): {:?}", 
-                      host, tunnel_count, 
-                      tunnels.keys().collect::<Vec<_>>());
-                if tunnel_count > 0 {
-                    for (tid, tunnel) in tunnels.iter() {
-                        info!("  Tunnel {}: subdomain '{}', authenticated: {}", 
-                              tid, tunnel.subdomain, tunnel.authenticated);
-                    }
-                }
-                return (StatusCode::NOT_FOUND, "Tunnel not found for this domain").into_response();
-            }
-        };// Get the tunnel connection
-        let tunnel = {
+            state.subdomain_manager.get_tunnel_for_custom_domain(host).await.into_iter().collect()
+        };
+
+        if tunnel_ids.is_empty() {
+            // Debug: Show all active tunnels
             let tunnels = state.active_tunnels.read().await;
-            match tunnels.get(&tunnel_id) {
-                Some(tunnel) => tunnel.clone(),
-                None => {
-                    return (StatusCode::BAD_GATEWAY, "Tunnel disconnected").into_response();
+            let tunnel_count = tunnels.len();
+            info!("No tunnel found for host: {}. Active tunnels ({}): {:?}", 
+                  host, tunnel_count, 
+                  tunnels.keys().collect::<Vec<_>>());
+            if tunnel_count > 0 {
+                for (tid, tunnel) in tunnels.iter() {
+                    info!("  Tunnel {}: subdomain '{}', authenticated: {}", 
+                          tid, tunnel.subdomain, tunnel.authenticated);
                 }
             }
+            return (StatusCode::NOT_FOUND, "Tunnel not found for this domain").into_response();
+        }
+
+        // Get the tunnel connections, in the order they take this request
+        let pool_key = subdomain.as_deref().unwrap_or(host);
+        let candidates = Self::pool_rotation(pool_key, &tunnel_ids, &state).await;
+        let tunnel_id = match candidates.first() {
+            Some(tunnel) => {
+                info!("Found tunnel ID: {} for host: {}", tunnel.id, host);
+                tunnel.id.clone()
+            }
+            None => {
+                return (StatusCode::BAD_GATEWAY, "Tunnel disconnected").into_response();
+            }
         };
 
         // Apply rate limiting if enabled
@@ -297,46 +387,67 @@ impl TunnelServer {
         // Create channel for response
         let (response_sender, response_receiver) = oneshot::channel();
 
-        // Store pending request
+        // Store pending request; the response wait and the expiry sweep share its deadline
+        let created_at = Instant::now();
+        let deadline = created_at + Self::request_timeout(&state.config);
         {
             let mut pending = state.pending_requests.write().await;
             pending.insert(request_id.clone(), PendingRequest {
                 id: request_id.clone(),
                 response_sender,
-                created_at: std::time::Instant::now(),
+                created_at,
             });
         }
 
-        // Forward the request through the tunnel
-        let request_message = TunnelMessage::HttpRequest {
-            id: request_id.clone(),
-            method: method.as_str().to_string(),
-            path: uri
-                .path_and_query()
-                .map(|pq| pq.as_str())
-                .unwrap_or(uri.path())
-                .to_string(),
-            headers: Self::headers_to_hashmap(&headers),
-            body: if body.is_empty() {
-                None
-            } else {
-                Some(body.to_vec())
-            },
-            client_ip: "0.0.0.0".to_string(), // TODO: Extract real client IP
-        };
+        // Forward the request through the tunnel, failing over to the rest of the pool
+        let path = uri
+            .path_and_query()
+            .map(|pq| pq.as_str())
+            .unwrap_or(uri.path())
+            .to_string();
+        let request_headers = Self::headers_to_hashmap(&headers);
+        let request_body = if body.is_empty() { None } else { Some(body.to_vec()) };
+
+        let queue_timeout = Duration::from_millis(state.config.request_queue_timeout_ms);
+        let mut sent = false;
+        let mut saturated = false;
+        for tunnel in &candidates {
+            let request_message = TunnelMessage::HttpRequest {
+                id: request_id.clone(),
+                method: method.as_str().to_string(),
+                path: path.clone(),
+                headers: request_headers.clone(),
+                body: request_body.clone(),
+                client_ip: "0.0.0.0".to_string(), // TODO: Extract real client IP
+                tunnel_id: tunnel.local_id.clone(),
+            };
 
-        // Send request to tunnel client
-        if let Err(e) = tunnel.request_sender.send(request_message).await {
-            error!("Failed to send request to tunnel: {}", e);
+            // Send request to tunnel client, waiting a bounded time for room in its queue
+            match tokio::time::timeout(queue_timeout, tunnel.request_sender.send(request_message)).await {
+                Ok(Ok(())) => {
+                    sent = true;
+                    break;
+                }
+                Ok(Err(e)) => error!("Failed to send request to tunnel {}: {}", tunnel.id, e),
+                Err(_) => {
+                    warn!("Request queue for tunnel {} is full ({} queued)", tunnel.id, tunnel.queued_requests());
+                    saturated = true;
+                }
+            }
+        }
+        if !sent {
             // Clean up pending request
             state.pending_requests.write().await.remove(&request_id);
+            Self::update_rate_limit_completion(&tunnel_id, &state, 0).await;
+            if saturated {
+                return (StatusCode::SERVICE_UNAVAILABLE, "Tunnel is overloaded").into_response();
+            }
             return (StatusCode::BAD_GATEWAY, "Tunnel communication error").into_response();
-        } // Wait for response with timeout
-        let response_timeout = Duration::from_secs(30);
+        } // Wait for response until the deadline
         let tunnel_id_for_rate_limit = tunnel_id.clone();
         let state_for_rate_limit = state.clone();
 
-        match tokio::time::timeout(response_timeout, response_receiver).await {
+        match tokio::time::timeout_at(deadline.into(), response_receiver).await {
             Ok(Ok(tunnel_response)) => {
                 // Calculate bytes transferred for rate limiting
                 let bytes_transferred = match &tunnel_response {
@@ -365,6 +476,10 @@ impl TunnelServer {
                     &state_for_rate_limit,
                     0
                 ).await;
+                if Instant::now() >= deadline {
+                    // Swept as expired just as the wait ran out
+                    return (StatusCode::GATEWAY_TIMEOUT, "Request timeout").into_response();
+                }
                 (StatusCode::BAD_GATEWAY, "Tunnel closed during request").into_response()
             }
             Err(_) => {
@@ -400,11 +515,20 @@ impl TunnelServer {
         info!("New tunnel connection: {} from {}", tunnel_id, client_ip);
 
         // Create channel for sending requests to this tunnel
-        let (request_sender, mut request_receiver) = mpsc::channel::<TunnelMessage>(100);
+        let queue_size = state.config.request_queue_size.max(1);
+        let (request_sender, mut request_receiver) = mpsc::channel::<TunnelMessage>(queue_size);
 
         // Store request sender for authentication phase
         let request_sender_for_auth = request_sender.clone();
 
+        // Track when the client was last heard from so silent tunnels can be evicted
+        let liveness = TunnelLiveness::new();
+        let liveness_for_incoming = liveness.clone();
+
+        // Whether the client negotiated compressed frames during authentication
+        let compression = Arc::new(AtomicBool::new(false));
+        let compression_for_incoming = compression.clone();
+
         // Handle incoming messages from tunnel client
         let state_clone = state.clone();
         let tunnel_id_clone = tunnel_id.clone();
@@ -412,8 +536,11 @@ impl TunnelServer {
 
         // Handle incoming WebSocket messages
         let sender_for_incoming = sender_handle.clone();
-        let incoming_task = tokio::spawn(async move {
+        let mut incoming_task = tokio::spawn(async move {
             while let Some(msg) = receiver.next().await {
+                if msg.is_ok() {
+                    liveness_for_incoming.touch();
+                }
                 match msg {
                     Ok(axum::extract::ws::Message::Text(text)) => {
                         if
@@ -426,18 +553,27 @@ impl TunnelServer {
                                 &tunnel_id_clone,
                                 &state_clone,
                                 &sender_for_incoming,
-                                &request_sender_for_auth
+                                &request_sender_for_auth,
+                                &liveness_for_incoming,
+                                &compression_for_incoming
                             ).await;
                         }
                     }
                     Ok(axum::extract::ws::Message::Binary(data)) => {
-                        if let Ok(tunnel_msg) = TunnelProtocol::deserialize_message(&data) {
+                        if
+                            let Ok(tunnel_msg) = TunnelProtocol::decode_frame(
+                                &data,
+                                compression_for_incoming.load(Ordering::Relaxed)
+                            )
+                        {
                             Self::handle_tunnel_message(
                                 tunnel_msg,
                                 &tunnel_id_clone,
                                 &state_clone,
                                 &sender_for_incoming,
-                                &request_sender_for_auth
+                                &request_sender_for_auth,
+                                &liveness_for_incoming,
+                                &compression_for_incoming
                             ).await;
                         }
                     }
@@ -453,20 +589,20 @@ impl TunnelServer {
                 }
             }
 
-            // Clean up tunnel on disconnect
-            Self::cleanup_tunnel(&tunnel_id_clone, &state_clone).await;
+            // Clean up every tunnel carried by this connection
+            Self::cleanup_connection(&tunnel_id_clone, &state_clone).await;
         }); // Handle outgoing requests to tunnel client
         let sender_for_outgoing = sender_handle.clone();
-        let outgoing_task = tokio::spawn(async move {
+        let mut outgoing_task = tokio::spawn(async move {
             while let Some(request_msg) = request_receiver.recv().await {
-                match serde_json::to_string(&request_msg) {
-                    Ok(text) => {
+                match TunnelProtocol::encode_frame(&request_msg, compression.load(Ordering::Relaxed)) {
+                    Ok(frame) => {
+                        let ws_message = match frame {
+                            TunnelFrame::Text(text) => axum::extract::ws::Message::Text(text),
+                            TunnelFrame::Binary(data) => axum::extract::ws::Message::Binary(data),
+                        };
                         let mut sender_guard = sender_for_outgoing.lock().await;
-                        if
-                            let Err(e) = sender_guard.send(
-                                axum::extract::ws::Message::Text(text)
-                            ).await
-                        {
+                        if let Err(e) = sender_guard.send(ws_message).await {
                             error!("Failed to send request to tunnel: {}", e);
                             break;
                         }
@@ -479,10 +615,22 @@ impl TunnelServer {
             }
         });
 
-        // Wait for either task to complete
+        // Wait for either task to complete, or for the idle sweeper to evict this tunnel
         tokio::select! {
-            _ = incoming_task => {},
-            _ = outgoing_task => {},
+            _ = &mut incoming_task => {},
+            _ = &mut outgoing_task => {},
+            _ = liveness.close_signal.notified() => {
+                info!("Closing idle tunnel connection {}", tunnel_id);
+                let close_frame = axum::extract::ws::CloseFrame {
+                    code: axum::extract::ws::close_code::AWAY,
+                    reason: "Idle timeout".into(),
+                };
+                let _ = sender_handle
+                    .lock().await
+                    .send(axum::extract::ws::Message::Close(Some(close_frame))).await;
+                incoming_task.abort();
+                outgoing_task.abort();
+            }
         }
     }
     /// Handle individual tunnel protocol messages
@@ -498,20 +646,40 @@ impl TunnelServer {
                 >
             >
         >,
-        request_sender: &mpsc::Sender<TunnelMessage>
+        request_sender: &mpsc::Sender<TunnelMessage>,
+        liveness: &TunnelLiveness,
+        compression: &Arc<AtomicBool>
     ) {
         match message {
-            TunnelMessage::Auth { token, subdomain, protocol_version } => {
+            TunnelMessage::Auth { token, subdomain, protocol_version, capabilities } => {
                 Self::handle_auth_message(
                     tunnel_id,
                     token,
                     subdomain,
                     protocol_version,
+                    capabilities,
                     state,
                     sender,
-                    request_sender.clone()
+                    request_sender.clone(),
+                    liveness.clone(),
+                    compression
                 ).await;
             }
+            TunnelMessage::OpenTunnel { tunnel_id: local_id, subdomain } => {
+                Self::handle_open_tunnel(
+                    tunnel_id,
+                    local_id,
+                    subdomain,
+                    state,
+                    sender,
+                    request_sender.clone(),
+                    liveness.clone()
+                ).await;
+            }
+            TunnelMessage::CloseTunnel { tunnel_id: local_id } => {
+                let logical_id = Self::logical_tunnel_id(tunnel_id, &local_id);
+                Self::cleanup_tunnel(&logical_id, state).await;
+            }
             TunnelMessage::HttpResponse { id, status, headers, body } => {
                 Self::handle_http_response(id, status, headers, body, state).await;
             }
@@ -579,7 +747,7 @@ impl TunnelServer {
             };
 
             // Send response back to the waiting HTTP handler
-            if let Err(_) = pending_request.response_sender.send(response_message) {
+            if pending_request.response_sender.send(response_message).is_err() {
                 warn!(
                     "Failed to send response for request {}: receiver dropped",
                     pending_request.id
@@ -629,7 +797,8 @@ impl TunnelServer {
 
     /// Cleanup expired pending requests
     async fn cleanup_expired_requests(state: Arc<TunnelServerState>) {
-        let mut interval = tokio::time::interval(Duration::from_secs(30));
+        let request_timeout = Self::request_timeout(&state.config);
+        let mut interval = tokio::time::interval(request_timeout);
 
         loop {
             interval.tick().await;
@@ -640,7 +809,7 @@ impl TunnelServer {
             {
                 let pending = state.pending_requests.read().await;
                 for (id, request) in pending.iter() {
-                    if now.duration_since(request.created_at) > Duration::from_secs(60) {
+                    if now.duration_since(request.created_at) > request_timeout {
                         expired_requests.push(id.clone());
                     }
                 }
@@ -657,12 +826,50 @@ impl TunnelServer {
             }
         }
     }
+    /// How long a public request waits for its tunneled response (at least a second)
+    fn request_timeout(config: &TunnelServerConfig) -> Duration {
+        Duration::from_secs(config.request_timeout_seconds.max(1))
+    }
+
+    /// Evict tunnels that have not sent anything within the configured idle timeout
+    async fn evict_idle_tunnels(state: Arc<TunnelServerState>) {
+        let idle_timeout = Duration::from_secs(state.config.idle_timeout);
+        let mut interval = tokio::time::interval(
+            Duration::from_secs((state.config.idle_timeout / 3).max(1))
+        );
+
+        loop {
+            interval.tick().await;
+
+            let idle_tunnels: Vec<(String, TunnelLiveness)> = {
+                let tunnels = state.active_tunnels.read().await;
+                tunnels
+                    .values()
+                    .filter(|tunnel| tunnel.liveness.idle_for() > idle_timeout)
+                    .map(|tunnel| (tunnel.id.clone(), tunnel.liveness.clone()))
+                    .collect()
+            };
+
+            for (tunnel_id, liveness) in idle_tunnels {
+                warn!(
+                    "Evicting tunnel {} after {}s without client activity",
+                    tunnel_id,
+                    liveness.idle_for().as_secs()
+                );
+                liveness.close();
+                Self::cleanup_tunnel(&tunnel_id, &state).await;
+            }
+        }
+    }
+
     /// Handle tunnel authentication
+    #[allow(clippy::too_many_arguments)]
     async fn handle_auth_message(
         tunnel_id: &str,
         token: String,
         requested_subdomain: Option<String>,
         protocol_version: String,
+        offered_capabilities: Vec<String>,
         state: &Arc<TunnelServerState>,
         sender: &Arc<
             tokio::sync::Mutex<
@@ -672,7 +879,9 @@ impl TunnelServer {
                 >
             >
         >,
-        request_sender: mpsc::Sender<TunnelMessage>
+        request_sender: mpsc::Sender<TunnelMessage>,
+        liveness: TunnelLiveness,
+        compression: &Arc<AtomicBool>
     ) {
         // Validate protocol version
         if !state.protocol.is_compatible_version(&protocol_version) {
@@ -690,7 +899,15 @@ impl TunnelServer {
             };
             Self::send_tunnel_message(&error_msg, sender).await;
             return;
-        } // Extract user information from token for logging purposes only
+        }
+
+        if Self::at_capacity(&*state.active_tunnels.read().await, &state.config) {
+            warn!("Rejecting tunnel {}: server at capacity ({} tunnels)", tunnel_id, state.config.max_tunnels);
+            Self::send_capacity_rejection(sender).await;
+            return;
+        }
+
+        // Extract user information from token for logging purposes only
         let user_info = Self::extract_user_info(&token, &state.config);
 
         // Use the requested subdomain as-is, don't derive from user info
@@ -710,23 +927,38 @@ impl TunnelServer {
                     success: false,
                     assigned_subdomain: None,
                     error: Some(format!("Subdomain allocation failed: {}", e)),
+                    public_url: None,
+                    capabilities: Vec::new(),
                 };
                 Self::send_tunnel_message(&error_msg, sender).await;
                 return;
             }
-        }; // Create tunnel entry
+        };
+
+        // Agree on the optional features both sides support
+        let capabilities = TunnelProtocol::negotiate_capabilities(&protocol_version, &offered_capabilities);
+
+        // Create tunnel entry
         let tunnel = ActiveTunnel {
             id: tunnel_id.to_string(),
+            connection_id: tunnel_id.to_string(),
+            local_id: None,
             subdomain: subdomain.clone(),
             client_ip: "0.0.0.0".to_string(), // TODO: Extract real IP
             user_info: user_info.clone(),
             authenticated: true,
             connected_at: std::time::SystemTime::now(),
             request_sender,
+            liveness,
+            proxy_protocol: ProxyProtocolVersion::from_capabilities(&capabilities),
         }; // Register tunnel
-        {
-            let mut tunnels = state.active_tunnels.write().await;
-            tunnels.insert(tunnel_id.to_string(), tunnel);
+        if !Self::register_tunnel(tunnel, state).await {
+            Self::send_capacity_rejection(sender).await;
+            return;
+        }
+
+        if capabilities.iter().any(|cap| cap == CAPABILITY_DEFLATE) {
+            compression.store(true, Ordering::Relaxed);
         }
 
         // Send authentication response
@@ -734,6 +966,8 @@ impl TunnelServer {
             success: true,
             assigned_subdomain: Some(subdomain.clone()),
             error: None,
+            public_url: Some(state.config.public_url(&subdomain)),
+            capabilities,
         };
         Self::send_tunnel_message(&auth_response, sender).await;
 
@@ -744,6 +978,190 @@ impl TunnelServer {
             user_info
         );
     }
+    /// Handle a request to open an additional logical tunnel on an authenticated connection
+    async fn handle_open_tunnel(
+        connection_id: &str,
+        local_id: String,
+        requested_subdomain: Option<String>,
+        state: &Arc<TunnelServerState>,
+        sender: &Arc<
+            tokio::sync::Mutex<
+                futures_util::stream::SplitSink<
+                    axum::extract::ws::WebSocket,
+                    axum::extract::ws::Message
+                >
+            >
+        >,
+        request_sender: mpsc::Sender<TunnelMessage>,
+        liveness: TunnelLiveness
+    ) {
+        let logical_id = Self::logical_tunnel_id(connection_id, &local_id);
+
+        // Authentication is per connection: the tunnel opened by Auth must exist
+        let opened = {
+            let tunnels = state.active_tunnels.read().await;
+            let primary = tunnels.get(connection_id).map(|t| (t.user_info.clone(), t.proxy_protocol));
+            if local_id.is_empty() {
+                Err("Tunnel id must not be empty".to_string())
+            } else if tunnels.contains_key(&logical_id) {
+                Err(format!("Tunnel '{}' is already open on this connection", local_id))
+            } else if Self::at_capacity(&tunnels, &state.config) {
+                Err(SERVER_AT_CAPACITY.to_string())
+            } else {
+                primary.ok_or_else(|| "Connection is not authenticated".to_string())
+            }
+        };
+
+        // Logical tunnels share the connection's negotiated features
+        let (user_info, proxy_protocol) = match opened {
+            Ok(primary) => primary,
+            Err(error) => {
+                let error_msg = TunnelMessage::TunnelOpened {
+                    tunnel_id: local_id,
+                    success: false,
+                    assigned_subdomain: None,
+                    error: Some(error),
+                };
+                Self::send_tunnel_message(&error_msg, sender).await;
+                return;
+            }
+        };
+
+        let subdomain = match
+            state.subdomain_manager.allocate_subdomain(
+                &logical_id,
+                requested_subdomain,
+                Some("0.0.0.0".to_string()) // TODO: Extract real client IP
+            ).await
+        {
+            Ok(subdomain) => subdomain,
+            Err(e) => {
+                let error_msg = TunnelMessage::TunnelOpened {
+                    tunnel_id: local_id,
+                    success: false,
+                    assigned_subdomain: None,
+                    error: Some(format!("Subdomain allocation failed: {}", e)),
+                };
+                Self::send_tunnel_message(&error_msg, sender).await;
+                return;
+            }
+        };
+
+        let tunnel = ActiveTunnel {
+            id: logical_id.clone(),
+            connection_id: connection_id.to_string(),
+            local_id: Some(local_id.clone()),
+            subdomain: subdomain.clone(),
+            client_ip: "0.0.0.0".to_string(), // TODO: Extract real IP
+            user_info,
+            authenticated: true,
+            connected_at: std::time::SystemTime::now(),
+            request_sender,
+            liveness,
+            proxy_protocol,
+        };
+        if !Self::register_tunnel(tunnel, state).await {
+            let error_msg = TunnelMessage::TunnelOpened {
+                tunnel_id: local_id,
+                success: false,
+                assigned_subdomain: None,
+                error: Some(SERVER_AT_CAPACITY.to_string()),
+            };
+            Self::send_tunnel_message(&error_msg, sender).await;
+            return;
+        }
+
+        let opened_msg = TunnelMessage::TunnelOpened {
+            tunnel_id: local_id,
+            success: true,
+            assigned_subdomain: Some(subdomain.clone()),
+            error: None,
+        };
+        Self::send_tunnel_message(&opened_msg, sender).await;
+
+        info!("Opened tunnel {} with subdomain: {}", logical_id, subdomain);
+    }
+
+    /// Live tunnels among `tunnel_ids`, rotated so each request to a pool starts at the next one
+    async fn pool_rotation(
+        pool_key: &str,
+        tunnel_ids: &[String],
+        state: &Arc<TunnelServerState>
+    ) -> Vec<ActiveTunnel> {
+        let mut live: Vec<ActiveTunnel> = {
+            let tunnels = state.active_tunnels.read().await;
+            tunnel_ids
+                .iter()
+                .filter_map(|id| tunnels.get(id))
+                .filter(|tunnel| !tunnel.request_sender.is_closed())
+                .cloned()
+                .collect()
+        };
+
+        if live.len() > 1 {
+            let turn = {
+                let mut cursors = state.pool_cursors.lock().unwrap();
+                let cursor = cursors.entry(pool_key.to_string()).or_insert(0);
+                let turn = *cursor;
+                *cursor = cursor.wrapping_add(1);
+                turn
+            };
+            let len = live.len();
+            live.rotate_left(turn % len);
+        }
+        live
+    }
+
+    /// Whether the server already holds `max_tunnels` tunnels (0 = unlimited)
+    fn at_capacity(tunnels: &HashMap<String, ActiveTunnel>, config: &TunnelServerConfig) -> bool {
+        config.max_tunnels > 0 && tunnels.len() >= config.max_tunnels as usize
+    }
+
+    /// Register a tunnel unless another took the last slot while its subdomain was allocated,
+    /// in which case the subdomain is released again
+    async fn register_tunnel(tunnel: ActiveTunnel, state: &Arc<TunnelServerState>) -> bool {
+        let subdomain = tunnel.subdomain.clone();
+        let tunnel_id = tunnel.id.clone();
+        {
+            let mut tunnels = state.active_tunnels.write().await;
+            if !Self::at_capacity(&tunnels, &state.config) {
+                tunnels.insert(tunnel.id.clone(), tunnel);
+                return true;
+            }
+        }
+
+        if let Err(e) = state.subdomain_manager.release_tunnel(&subdomain, &tunnel_id).await {
+            warn!("Failed to release subdomain {}: {}", subdomain, e);
+        }
+        false
+    }
+
+    /// Refuse an Auth request because the server is full
+    async fn send_capacity_rejection(
+        sender: &Arc<
+            tokio::sync::Mutex<
+                futures_util::stream::SplitSink<
+                    axum::extract::ws::WebSocket,
+                    axum::extract::ws::Message
+                >
+            >
+        >
+    ) {
+        let error_msg = TunnelMessage::AuthResponse {
+            success: false,
+            assigned_subdomain: None,
+            error: Some(SERVER_AT_CAPACITY.to_string()),
+            public_url: None,
+            capabilities: Vec::new(),
+        };
+        Self::send_tunnel_message(&error_msg, sender).await;
+    }
+
+    /// Server-wide id of a logical tunnel opened over a connection
+    fn logical_tunnel_id(connection_id: &str, local_id: &str) -> String {
+        format!("{}/{}", connection_id, local_id)
+    }
+
     /// Send message through tunnel WebSocket
     async fn send_tunnel_message(
         message: &TunnelMessage,
@@ -769,6 +1187,22 @@ impl TunnelServer {
         }
     }
 
+    /// Clean up all tunnels multiplexed over a connection
+    async fn cleanup_connection(connection_id: &str, state: &Arc<TunnelServerState>) {
+        let tunnel_ids: Vec<String> = {
+            let tunnels = state.active_tunnels.read().await;
+            tunnels
+                .values()
+                .filter(|tunnel| tunnel.connection_id == connection_id)
+                .map(|tunnel| tunnel.id.clone())
+                .collect()
+        };
+
+        for tunnel_id in tunnel_ids {
+            Self::cleanup_tunnel(&tunnel_id, state).await;
+        }
+    }
+
     /// Clean up tunnel on disconnect
     async fn cleanup_tunnel(tunnel_id: &str, state: &Arc<TunnelServerState>) {
         let subdomain = {
@@ -781,24 +1215,44 @@ impl TunnelServer {
             }
         };
 
-        // Release subdomain using SubdomainManager
-        if let Err(e) = state.subdomain_manager.release_subdomain(&subdomain).await {
+        // Release subdomain using SubdomainManager; pooled subdomains stay with the other tunnels
+        if let Err(e) = state.subdomain_manager.release_tunnel(&subdomain, tunnel_id).await {
             warn!("Failed to release subdomain {}: {}", subdomain, e);
         }
+        if state.subdomain_manager.get_tunnels_for_subdomain(&subdomain).await.is_empty() {
+            state.pool_cursors.lock().unwrap().remove(&subdomain);
+        }
 
         info!("Cleaned up tunnel {} with subdomain {}", tunnel_id, subdomain);
     }
 
     /// Handle health check endpoint
     async fn handle_health_check(State(state): State<Arc<TunnelServerState>>) -> Response {
-        let tunnel_count = state.active_tunnels.read().await.len();
+        let (tunnel_count, tunnels) = {
+            let tunnels = state.active_tunnels.read().await;
+            let queues: Vec<serde_json::Value> = tunnels
+                .values()
+                .map(|tunnel| {
+                    serde_json::json!({
+                        "id": tunnel.id,
+                        "subdomain": tunnel.subdomain,
+                        "queued_requests": tunnel.queued_requests()
+                    })
+                })
+                .collect();
+            (tunnels.len(), queues)
+        };
         let response =
             serde_json::json!({
             "status": "healthy",
             "active_tunnels": tunnel_count,
+            "pending_requests": state.pending_requests.read().await.len(),
+            "tunnels": tunnels,
             "server_config": {
                 "base_domain": state.config.base_domain,
-                "max_tunnels": state.config.max_tunnels
+                "max_tunnels": state.config.max_tunnels,
+                "request_queue_size": state.config.request_queue_size,
+                "request_timeout_seconds": state.config.request_timeout_seconds
             }
         });
         (StatusCode::OK, response.to_string()).into_response()
@@ -926,7 +1380,6 @@ impl TunnelServer {
         // Check request rate limit
         let should_reset_window;
         let current_count;
-        let current_connections;
 
         {
             let (count, window_start) = rate_limiter.request_counts
@@ -952,7 +1405,7 @@ impl TunnelServer {
         }
 
         // Check concurrent connections
-        current_connections = rate_limiter.active_connections.get(tunnel_id).copied().unwrap_or(0);
+        let current_connections = rate_limiter.active_connections.get(tunnel_id).copied().unwrap_or(0);
 
         if current_connections >= config.max_concurrent_connections {
             return Err("Concurrent connection limit exceeded".to_string());
@@ -1014,7 +1467,7 @@ impl TunnelServer {
             self.config.network.public_bind_address
                 .parse()
                 .map_err(|e| TunnelError::ConfigError(format!("Invalid SSL bind address: {}", e)))?,
-            443 // Standard HTTPS port
+            self.config.public_https_port
         );
 
         info!("Starting SSL passthrough on {}", ssl_addr);
@@ -1053,25 +1506,13 @@ impl TunnelServer {
     /// Handle SSL/TLS connections for passthrough
     async fn handle_ssl_connection(
         mut stream: TcpStream,
-        _client_addr: SocketAddr,
+        client_addr: SocketAddr,
         state: Arc<TunnelServerState>
     ) -> ServerResult<()> {
-        // Read the initial bytes to detect TLS handshake and extract SNI
-        let mut buffer = [0u8; 1024];
-        let bytes_read = stream
-            .read(&mut buffer).await
-            .map_err(|e|
-                TunnelError::NetworkError(format!("Failed to read TLS handshake: {}", e))
-            )?;
-
-        if bytes_read == 0 {
-            return Err(TunnelError::NetworkError("Empty TLS handshake".to_string()));
-        }
-
-        // Extract SNI (Server Name Indication) from TLS handshake
-        let hostname = Self::extract_sni_from_tls(&buffer[..bytes_read]).ok_or_else(||
-            TunnelError::ValidationError("Could not extract SNI from TLS handshake".to_string())
-        )?;
+        // Read the full Client Hello, however it was fragmented, and extract SNI from it
+        let (initial_data, hostname) = tokio::time
+            ::timeout(CLIENT_HELLO_TIMEOUT, read_client_hello(&mut stream)).await
+            .map_err(|_| TunnelError::NetworkError("Timed out reading TLS handshake".to_string()))??;
 
         // Extract subdomain from hostname
         let subdomain = Self::extract_subdomain(&hostname, &state.config.base_domain).ok_or_else(||
@@ -1095,102 +1536,13 @@ impl TunnelServer {
         };
 
         // Forward the TLS traffic through the tunnel
-        Self::forward_ssl_through_tunnel(stream, buffer[..bytes_read].to_vec(), tunnel, state).await
-    }
-
-    /// Extract SNI (Server Name Indication) from TLS Client Hello
-    fn extract_sni_from_tls(data: &[u8]) -> Option<String> {
-        // TLS handshake parsing to extract SNI
-        // This is a simplified version - in production, you'd want a more robust parser
-
-        if data.len() < 43 {
-            return None;
-        }
-
-        // Check if it's a TLS handshake record (type 22)
-        if data[0] != 0x16 {
-            return None;
-        }
-
-        // Check if it's a Client Hello (type 1)
-        if data[5] != 0x01 {
-            return None;
-        }
-
-        // Parse through the handshake to find extensions
-        let mut offset = 43; // Skip to session ID length
-
-        // Skip session ID
-        if offset >= data.len() {
-            return None;
-        }
-        offset += (data[offset] as usize) + 1;
-
-        // Skip cipher suites
-        if offset + 1 >= data.len() {
-            return None;
-        }
-        let cipher_suites_len = u16::from_be_bytes([data[offset], data[offset + 1]]) as usize;
-        offset += 2 + cipher_suites_len;
-
-        // Skip compression methods
-        if offset >= data.len() {
-            return None;
-        }
-        offset += (data[offset] as usize) + 1;
-
-        // Check for extensions
-        if offset + 1 >= data.len() {
-            return None;
-        }
-        let extensions_len = u16::from_be_bytes([data[offset], data[offset + 1]]) as usize;
-        offset += 2;
-
-        let extensions_end = offset + extensions_len;
-        while offset + 3 < extensions_end && offset + 3 < data.len() {
-            let ext_type = u16::from_be_bytes([data[offset], data[offset + 1]]);
-            let ext_len = u16::from_be_bytes([data[offset + 2], data[offset + 3]]) as usize;
-            offset += 4;
-
-            // SNI extension type is 0
-            if ext_type == 0 && offset + ext_len <= data.len() {
-                return Self::parse_sni_extension(&data[offset..offset + ext_len]);
-            }
-
-            offset += ext_len;
-        }
-
-        None
+        Self::forward_ssl_through_tunnel(stream, client_addr, initial_data, tunnel, state).await
     }
 
-    /// Parse SNI extension data
-    fn parse_sni_extension(data: &[u8]) -> Option<String> {
-        if data.len() < 5 {
-            return None;
-        }
-
-        // Skip server name list length (2 bytes)
-        let mut offset = 2;
-
-        // Check name type (1 byte) - should be 0 for hostname
-        if data[offset] != 0 {
-            return None;
-        }
-        offset += 1;
-
-        // Get hostname length (2 bytes)
-        let hostname_len = u16::from_be_bytes([data[offset], data[offset + 1]]) as usize;
-        offset += 2;
-
-        if offset + hostname_len <= data.len() {
-            String::from_utf8(data[offset..offset + hostname_len].to_vec()).ok()
-        } else {
-            None
-        }
-    }
     /// Forward SSL traffic through tunnel WebSocket
     async fn forward_ssl_through_tunnel(
         client_stream: TcpStream,
+        client_addr: SocketAddr,
         initial_data: Vec<u8>,
         tunnel: ActiveTunnel,
         state: Arc<TunnelServerState>
@@ -1198,18 +1550,32 @@ impl TunnelServer {
         // Generate unique connection ID for this SSL session
         let connection_id = Uuid::new_v4().to_string();
 
-        // Create TLS tunnel message
-        let ssl_connect_message = TunnelMessage::SslConnect {
-            id: connection_id.clone(),
-            initial_data: Some(initial_data),
+        // Tell a backend that asked for it who the client is, ahead of the TLS bytes
+        let initial_data = match tunnel.proxy_protocol {
+            Some(version) => {
+                let local_addr = client_stream
+                    .local_addr()
+                    .map_err(|e| TunnelError::NetworkError(format!("Failed to read local address: {}", e)))?;
+                let mut data = version.header(client_addr, local_addr);
+                data.extend(initial_data);
+                data
+            }
+            None => initial_data,
         };
 
+        // Create TLS tunnel message
+        let ssl_connect_message = TunnelProtocol::create_ssl_connect_message(
+            &connection_id,
+            Some(initial_data),
+            &client_addr.ip().to_string()
+        );
+
         // Send SSL connection request to tunnel client
         if let Err(e) = tunnel.request_sender.send(ssl_connect_message).await {
             return Err(TunnelError::NetworkError(format!("Failed to send SSL connect: {}", e)));
         }
 
-        info!("SSL connection {} established for tunnel {}", connection_id, tunnel.id);
+        info!("SSL connection {} from {} established for tunnel {}", connection_id, client_addr, tunnel.id);
 
         // Create channels for bidirectional SSL data forwarding
         let (ssl_tx, mut ssl_rx) = mpsc::channel::<Vec<u8>>(100);
@@ -0,0 +1,213 @@
+//! SNI Extraction
+//! Reads the TLS Client Hello of a passthrough connection to route it by server name
+
+use crate::TunnelError;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Largest Client Hello accepted, across however many records and reads it arrives in
+pub const MAX_CLIENT_HELLO_BYTES: usize = 16 * 1024;
+
+/// Largest TLS record fragment allowed by the protocol
+const MAX_RECORD_LEN: usize = 16 * 1024;
+
+/// Record content type of handshake messages
+const HANDSHAKE_RECORD: u8 = 0x16;
+
+/// Handshake message type of a Client Hello
+const CLIENT_HELLO: u8 = 0x01;
+
+/// Extension type of server_name
+const SERVER_NAME_EXTENSION: u16 = 0;
+
+/// Name type of a DNS hostname in the server_name list
+const HOST_NAME: u8 = 0;
+
+/// Outcome of parsing the bytes a client has sent so far
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientHello {
+    /// A full Client Hello, with its server name if it sent one
+    Complete(Option<String>),
+    /// Well-formed so far, but more bytes are needed
+    Incomplete,
+    /// Not a well-formed TLS Client Hello
+    Invalid,
+}
+
+/// Bounds-checked cursor over handshake bytes
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.data.len() {
+            return None;
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(taken)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// A block prefixed by a one-byte length
+    fn block8(&mut self) -> Option<Reader<'a>> {
+        let len = self.u8()? as usize;
+        self.take(len).map(Reader::new)
+    }
+
+    /// A block prefixed by a two-byte length
+    fn block16(&mut self) -> Option<Reader<'a>> {
+        let len = self.u16()? as usize;
+        self.take(len).map(Reader::new)
+    }
+}
+
+/// Parse the start of a TLS connection, reassembling a Client Hello split across records
+pub fn parse_client_hello(data: &[u8]) -> ClientHello {
+    let mut handshake = Vec::new();
+    let mut records = Reader::new(data);
+
+    loop {
+        // Record header: content type, protocol version, fragment length
+        let Some(header) = records.take(5) else {
+            return ClientHello::Incomplete;
+        };
+        let fragment_len = u16::from_be_bytes([header[3], header[4]]) as usize;
+        if header[0] != HANDSHAKE_RECORD || header[1] != 0x03 || fragment_len == 0 || fragment_len > MAX_RECORD_LEN {
+            return ClientHello::Invalid;
+        }
+        let Some(fragment) = records.take(fragment_len) else {
+            return ClientHello::Incomplete;
+        };
+        handshake.extend_from_slice(fragment);
+
+        // Handshake header: message type and three-byte length
+        if handshake.len() < 4 {
+            continue;
+        }
+        let message_len = u32::from_be_bytes([0, handshake[1], handshake[2], handshake[3]]) as usize;
+        if handshake[0] != CLIENT_HELLO || message_len > MAX_CLIENT_HELLO_BYTES {
+            return ClientHello::Invalid;
+        }
+        if handshake.len() >= 4 + message_len {
+            return match parse_client_hello_body(&handshake[4..4 + message_len]) {
+                Some(server_name) => ClientHello::Complete(server_name),
+                None => ClientHello::Invalid,
+            };
+        }
+    }
+}
+
+/// Server name from a Client Hello body; None when any length field is inconsistent
+fn parse_client_hello_body(body: &[u8]) -> Option<Option<String>> {
+    let mut hello = Reader::new(body);
+    hello.take(2)?; // legacy_version
+    hello.take(32)?; // random
+
+    let session_id = hello.block8()?;
+    if session_id.data.len() > 32 {
+        return None;
+    }
+    let cipher_suites = hello.block16()?;
+    if cipher_suites.is_empty() || cipher_suites.data.len() % 2 != 0 {
+        return None;
+    }
+    if hello.block8()?.is_empty() {
+        return None; // compression methods
+    }
+
+    // Extensions are optional, but nothing may follow them
+    if hello.is_empty() {
+        return Some(None);
+    }
+    let mut extensions = hello.block16()?;
+    if !hello.is_empty() {
+        return None;
+    }
+
+    let mut server_name = None;
+    while !extensions.is_empty() {
+        let extension_type = extensions.u16()?;
+        let extension = extensions.block16()?;
+        if extension_type == SERVER_NAME_EXTENSION {
+            if server_name.is_some() {
+                return None;
+            }
+            server_name = Some(parse_server_name(extension)?);
+        }
+    }
+    Some(server_name)
+}
+
+/// First hostname in a server_name extension
+fn parse_server_name(mut extension: Reader) -> Option<String> {
+    let mut names = extension.block16()?;
+    if !extension.is_empty() || names.is_empty() {
+        return None;
+    }
+
+    let mut hostname = None;
+    while !names.is_empty() {
+        let name_type = names.u8()?;
+        let name = names.block16()?;
+        if name_type == HOST_NAME && hostname.is_none() {
+            hostname = Some(name.data);
+        }
+    }
+
+    let hostname = hostname?;
+    let valid = !hostname.is_empty()
+        && hostname.len() <= 253
+        && hostname.iter().all(|byte| byte.is_ascii_alphanumeric() || *byte == b'-' || *byte == b'.');
+    valid.then(|| String::from_utf8_lossy(hostname).to_ascii_lowercase())
+}
+
+/// Read from `stream` until a full Client Hello has arrived, however it was fragmented;
+/// returns every byte read, to be replayed to the backend, and the requested server name
+pub async fn read_client_hello<R: AsyncRead + Unpin>(stream: &mut R) -> Result<(Vec<u8>, String), TunnelError> {
+    let mut data = Vec::new();
+    let mut buffer = [0u8; 4096];
+
+    loop {
+        let bytes_read = stream
+            .read(&mut buffer).await
+            .map_err(|e| TunnelError::NetworkError(format!("Failed to read TLS handshake: {}", e)))?;
+        if bytes_read == 0 {
+            return Err(TunnelError::NetworkError(if data.is_empty() {
+                "Empty TLS handshake".to_string()
+            } else {
+                "Connection closed before a full Client Hello".to_string()
+            }));
+        }
+        data.extend_from_slice(&buffer[..bytes_read]);
+
+        match parse_client_hello(&data) {
+            ClientHello::Complete(Some(hostname)) => return Ok((data, hostname)),
+            ClientHello::Complete(None) => {
+                return Err(TunnelError::ValidationError("Client Hello carries no SNI".to_string()));
+            }
+            ClientHello::Invalid => {
+                return Err(TunnelError::ValidationError("Malformed TLS Client Hello".to_string()));
+            }
+            ClientHello::Incomplete if data.len() > MAX_CLIENT_HELLO_BYTES + MAX_RECORD_LEN => {
+                return Err(TunnelError::ValidationError("TLS Client Hello too large".to_string()));
+            }
+            ClientHello::Incomplete => {}
+        }
+    }
+}
@@ -32,6 +32,97 @@ pub struct TunnelStatus {
     pub config_summary: ConfigSummary,
 }
 
+/// Client status served by the admin endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientStatusReport {
+    /// Whether the client has been started
+    pub running: bool,
+    
+    /// Whether any tunnel is connected or authenticated
+    pub connected: bool,
+    
+    /// Per-tunnel details, ordered by tunnel ID
+    pub tunnels: Vec<TunnelReport>,
+}
+
+/// Live details of one tunnel connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TunnelReport {
+    /// Client-assigned tunnel ID (e.g. "tunnel-0")
+    pub id: String,
+    
+    /// Tunnel server URL
+    pub server_url: String,
+    
+    /// Requested subdomain
+    pub subdomain: Option<String>,
+    
+    /// Current connection state
+    pub state: ConnectionState,
+    
+    /// Public URL once authenticated
+    pub public_url: Option<String>,
+    
+    /// Seconds since the current WebSocket connection was opened
+    pub uptime_secs: u64,
+    
+    /// Reconnection attempts since the last successful connection
+    pub retry_count: u32,
+    
+    /// Bytes sent and received over the tunnel
+    pub bytes_transferred: u64,
+    
+    /// Error from the last failed attempt, cleared once authenticated
+    pub last_error: Option<String>,
+    
+    /// Health score (0-100)
+    pub health_score: u8,
+    
+    /// Last time the server answered a keep-alive ping
+    pub last_ping: Option<DateTime<Utc>>,
+    
+    /// Average keep-alive round trip in milliseconds
+    pub avg_ping_latency_ms: Option<u64>,
+}
+
+/// Score a connection's health (0-100) from its state, uptime, retries and keep-alive pings
+pub fn calculate_health_score(state: &ConnectionState, uptime: Duration, retry_count: u32, metrics: &TunnelMetrics) -> u8 {
+    if !matches!(state, ConnectionState::Connected | ConnectionState::Authenticated) {
+        return 0;
+    }
+    let mut score = 100u8;
+
+    // Each retry since the last good connection costs a little, up to 30
+    score = score.saturating_sub((retry_count.min(6) * 5) as u8);
+
+    // A connection that has not yet lasted a minute is unproven
+    if uptime < Duration::from_secs(60) {
+        score = score.saturating_sub(10);
+    }
+
+    // Keep-alive pings: never answered, gone quiet, lost, or slow
+    match metrics.last_ping_time {
+        Some(last_ping) => {
+            if Utc::now().signed_duration_since(last_ping).num_minutes() > 5 {
+                score = score.saturating_sub(30);
+            }
+        }
+        None => score = score.saturating_sub(20),
+    }
+    if let Some(success_rate) = metrics.ping_success_rate() {
+        score = score.saturating_sub(((1.0 - success_rate) * 40.0).round() as u8);
+    }
+    if let Some(latency) = metrics.avg_latency() {
+        if latency > Duration::from_secs(1) {
+            score = score.saturating_sub(20);
+        } else if latency > Duration::from_millis(250) {
+            score = score.saturating_sub(10);
+        }
+    }
+
+    score
+}
+
 /// Connection health status
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionHealth {
@@ -104,6 +195,9 @@ pub struct TunnelMetrics {
     /// Total ping/pong exchanges
     pub total_pings: u64,
     
+    /// Keep-alive pings still unanswered when the next one was sent
+    pub missed_pings: u64,
+    
     /// Last ping time
     pub last_ping_time: Option<DateTime<Utc>>,
     
@@ -131,6 +225,7 @@ impl TunnelMetrics {
             connected_clients: 0,
             server_uptime: Duration::default(),
             total_pings: 0,
+            missed_pings: 0,
             last_ping_time: None,
             connection_start: None,
             latency_history: VecDeque::with_capacity(100), // Keep last 100 measurements
@@ -184,6 +279,22 @@ impl TunnelMetrics {
         }
     }
 
+    /// Record a keep-alive pong and the round trip of its ping
+    pub fn record_pong(&mut self, latency: Duration) {
+        self.total_pings += 1;
+        self.last_ping_time = Some(Utc::now());
+        self.record_ping_latency(latency);
+    }
+
+    /// Share of keep-alive pings answered, if any have been settled
+    pub fn ping_success_rate(&self) -> Option<f64> {
+        let settled = self.total_pings + self.missed_pings;
+        if settled == 0 {
+            return None;
+        }
+        Some(self.total_pings as f64 / settled as f64)
+    }
+
     /// Get connection success rate
     pub fn success_rate(&self) -> f64 {
         if self.total_connections == 0 {
@@ -6,10 +6,14 @@ use serde::{Serialize, Deserialize};
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
 use tokio::fs;
 use rand::{Rng, thread_rng};
-use tracing::info;
+use tracing::{info, warn};
 use uuid::Uuid;
 
 /// Subdomain allocation record
@@ -20,6 +24,16 @@ pub struct SubdomainRecord {
     pub allocated_at: chrono::DateTime<chrono::Utc>,
     pub is_custom: bool,
     pub client_ip: Option<String>,
+    /// Further tunnels sharing the subdomain as a pool, in the order they joined
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pool: Vec<String>,
+}
+
+impl SubdomainRecord {
+    /// Every tunnel serving the subdomain, the holder first
+    pub fn tunnel_ids(&self) -> Vec<String> {
+        std::iter::once(&self.tunnel_id).chain(&self.pool).cloned().collect()
+    }
 }
 
 /// Persistent subdomain storage
@@ -30,6 +44,77 @@ pub struct SubdomainStorage {
     pub allocation_history: Vec<SubdomainRecord>, // Past allocations for analytics
 }
 
+/// Writes storage snapshots to disk atomically (temp file + rename)
+#[derive(Debug)]
+struct StorageWriter {
+    path: PathBuf,
+    max_history: usize,
+    /// Set when storage has changes not yet written
+    dirty: AtomicBool,
+    /// Completed writes to disk
+    writes: AtomicU64,
+    /// Serializes writers so they never share the temp file
+    write_lock: Mutex<()>,
+}
+
+impl StorageWriter {
+    /// Temp file written before being renamed over the storage file
+    fn temp_path(&self) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".tmp");
+        self.path.with_file_name(name)
+    }
+
+    /// Prune allocation history beyond the limit and serialize
+    fn serialize(&self, storage: &mut SubdomainStorage) -> Result<String, TunnelError> {
+        if self.max_history > 0 && storage.allocation_history.len() > self.max_history {
+            let excess = storage.allocation_history.len() - self.max_history;
+            storage.allocation_history.drain(..excess);
+        }
+        serde_json::to_string_pretty(storage)
+            .map_err(|e| TunnelError::SerializationError(format!("Failed to serialize storage: {}", e)))
+    }
+
+    /// Write the current storage; a crash leaves either the old or the new file, never a partial one
+    async fn write(&self, storage: &RwLock<SubdomainStorage>) -> Result<(), TunnelError> {
+        let _guard = self.write_lock.lock().await;
+        self.dirty.store(false, Ordering::SeqCst);
+        let content = self.serialize(&mut *storage.write().await)?;
+
+        let temp_path = self.temp_path();
+        let result = async {
+            let mut file = fs::File::create(&temp_path).await?;
+            file.write_all(content.as_bytes()).await?;
+            file.sync_all().await?;
+            fs::rename(&temp_path, &self.path).await
+        }.await;
+        if let Err(e) = result {
+            self.dirty.store(true, Ordering::SeqCst);
+            return Err(TunnelError::IoError(format!("Failed to write storage file: {}", e)));
+        }
+
+        self.writes.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Write only if there are unsaved changes
+    async fn flush(&self, storage: &RwLock<SubdomainStorage>) -> Result<(), TunnelError> {
+        if self.dirty.load(Ordering::SeqCst) {
+            self.write(storage).await?;
+        }
+        Ok(())
+    }
+
+    /// Blocking write for use from `Drop`
+    fn write_blocking(&self, storage: &mut SubdomainStorage) -> Result<(), TunnelError> {
+        let content = self.serialize(storage)?;
+        let temp_path = self.temp_path();
+        std::fs::write(&temp_path, content)
+            .and_then(|_| std::fs::rename(&temp_path, &self.path))
+            .map_err(|e| TunnelError::IoError(format!("Failed to write storage file: {}", e)))
+    }
+}
+
 /// Subdomain manager with persistence and word generation
 #[derive(Debug)]
 pub struct SubdomainManager {
@@ -37,18 +122,29 @@ pub struct SubdomainManager {
     storage_path: PathBuf,
     word_list: Vec<String>,
     config: TunnelServerConfig,
+    writer: Arc<StorageWriter>,
+    flush_task: std::sync::Mutex<Option<JoinHandle<()>>>,
 }
 
 impl SubdomainManager {
     /// Create new subdomain manager
     pub fn new(config: TunnelServerConfig, storage_path: PathBuf) -> Self {
         let word_list = Self::generate_word_list();
+        let writer = Arc::new(StorageWriter {
+            path: storage_path.clone(),
+            max_history: config.max_allocation_history,
+            dirty: AtomicBool::new(false),
+            writes: AtomicU64::new(0),
+            write_lock: Mutex::new(()),
+        });
         
         Self {
             storage: Arc::new(RwLock::new(SubdomainStorage::default())),
             storage_path,
             word_list,
             config,
+            writer,
+            flush_task: std::sync::Mutex::new(None),
         }
     }
 
@@ -66,13 +162,32 @@ impl SubdomainManager {
                 fs::create_dir_all(parent).await
                     .map_err(|e| TunnelError::IoError(format!("Failed to create storage directory: {}", e)))?;
             }
-            
-            // Initialize with reserved words
-            self.initialize_reserved_words().await;
-            self.save_storage().await?;
             info!("Initialized new subdomain storage");
         }
         
+        // Reserved words always follow the current configuration
+        self.initialize_reserved_words().await;
+        self.save_storage().await?;
+        
+        // Batch later changes into periodic writes
+        if self.config.storage_flush_interval_ms > 0 {
+            let storage = self.storage.clone();
+            let writer = self.writer.clone();
+            let flush_interval = Duration::from_millis(self.config.storage_flush_interval_ms);
+            let task = tokio::spawn(async move {
+                let mut interval = tokio::time::interval(flush_interval);
+                loop {
+                    interval.tick().await;
+                    if let Err(e) = writer.flush(&storage).await {
+                        warn!("Failed to flush subdomain storage: {}", e);
+                    }
+                }
+            });
+            if let Some(previous) = self.flush_task.lock().unwrap().replace(task) {
+                previous.abort();
+            }
+        }
+        
         Ok(())
     }
 
@@ -111,12 +226,24 @@ impl SubdomainManager {
 
         // Check if subdomain is available
         {
-            let storage = self.storage.read().await;
-            if storage.active_subdomains.contains_key(subdomain) {
-                return Err(TunnelError::ConflictError(format!(
-                    "Subdomain '{}' is already in use",
-                    subdomain
-                )));
+            let mut storage = self.storage.write().await;
+            if let Some(record) = storage.active_subdomains.get_mut(subdomain) {
+                if !self.config.subdomain_pools {
+                    return Err(TunnelError::ConflictError(format!(
+                        "Subdomain '{}' is already in use",
+                        subdomain
+                    )));
+                }
+
+                // Join the existing allocation instead of taking a new one
+                if record.tunnel_id != tunnel_id && !record.pool.iter().any(|id| id == tunnel_id) {
+                    record.pool.push(tunnel_id.to_string());
+                }
+                drop(storage);
+                self.schedule_save().await?;
+
+                info!("Tunnel {} joined the pool for subdomain '{}'", tunnel_id, subdomain);
+                return Ok(subdomain.to_string());
             }
             
             if storage.reserved_subdomains.contains(subdomain) {
@@ -125,6 +252,8 @@ impl SubdomainManager {
                     subdomain
                 )));
             }
+            
+            self.check_ip_quota(&storage, client_ip.as_deref())?;
         }
 
         // Allocate the subdomain
@@ -134,6 +263,7 @@ impl SubdomainManager {
             allocated_at: chrono::Utc::now(),
             is_custom: true,
             client_ip,
+            pool: Vec::new(),
         };
 
         {
@@ -142,7 +272,7 @@ impl SubdomainManager {
             storage.allocation_history.push(record);
         }
 
-        self.save_storage().await?;
+        self.schedule_save().await?;
         
         info!("Allocated custom subdomain '{}' to tunnel {}", subdomain, tunnel_id);
         Ok(subdomain.to_string())
@@ -154,6 +284,8 @@ impl SubdomainManager {
         tunnel_id: &str,
         client_ip: Option<String>,
     ) -> Result<String, TunnelError> {
+        self.check_ip_quota(&*self.storage.read().await, client_ip.as_deref())?;
+        
         let mut attempts = 0;
         const MAX_ATTEMPTS: u32 = 50;
 
@@ -185,6 +317,7 @@ impl SubdomainManager {
                         allocated_at: chrono::Utc::now(),
                         is_custom: false,
                         client_ip,
+                        pool: Vec::new(),
                     };
 
                     {
@@ -193,7 +326,7 @@ impl SubdomainManager {
                         storage.allocation_history.push(record);
                     }
 
-                    self.save_storage().await?;
+                    self.schedule_save().await?;
                     
                     info!("Allocated random subdomain '{}' to tunnel {}", subdomain, tunnel_id);
                     return Ok(subdomain);
@@ -226,6 +359,26 @@ impl SubdomainManager {
         Uuid::new_v4().to_string().replace('-', "")[..8].to_string()
     }
 
+    /// Reject an allocation once the client IP holds its quota of active subdomains
+    fn check_ip_quota(&self, storage: &SubdomainStorage, client_ip: Option<&str>) -> Result<(), TunnelError> {
+        let quota = self.config.max_subdomains_per_ip;
+        let Some(ip) = client_ip.filter(|_| quota > 0) else {
+            return Ok(());
+        };
+        
+        let held = storage.active_subdomains
+            .values()
+            .filter(|record| record.client_ip.as_deref() == Some(ip))
+            .count();
+        if held >= quota as usize {
+            return Err(TunnelError::ConflictError(format!(
+                "Client {} already holds the maximum of {} subdomains",
+                ip, quota
+            )));
+        }
+        Ok(())
+    }
+
     /// Check if a subdomain is available
     pub async fn is_subdomain_available(&self, subdomain: &str) -> bool {
         let storage = self.storage.read().await;
@@ -242,7 +395,33 @@ impl SubdomainManager {
             }
         }
 
-        self.save_storage().await?;
+        self.schedule_save().await?;
+        Ok(())
+    }
+
+    /// Release one tunnel's share of a subdomain; a pooled subdomain stays allocated
+    /// to the tunnels left, the longest-joined taking over as holder
+    pub async fn release_tunnel(&self, subdomain: &str, tunnel_id: &str) -> Result<(), TunnelError> {
+        {
+            let mut storage = self.storage.write().await;
+            let Some(record) = storage.active_subdomains.get_mut(subdomain) else {
+                return Ok(());
+            };
+
+            if let Some(position) = record.pool.iter().position(|id| id == tunnel_id) {
+                record.pool.remove(position);
+            } else if record.tunnel_id != tunnel_id {
+                // Allocated to other tunnels only
+                return Ok(());
+            } else if !record.pool.is_empty() {
+                record.tunnel_id = record.pool.remove(0);
+            } else {
+                storage.active_subdomains.remove(subdomain);
+                info!("Released subdomain '{}' from tunnel {}", subdomain, tunnel_id);
+            }
+        }
+
+        self.schedule_save().await?;
         Ok(())
     }
 
@@ -252,6 +431,15 @@ impl SubdomainManager {
         storage.active_subdomains.get(subdomain).map(|record| record.tunnel_id.clone())
     }
 
+    /// Get every tunnel serving a subdomain, the holder first; empty when unallocated
+    pub async fn get_tunnels_for_subdomain(&self, subdomain: &str) -> Vec<String> {
+        let storage = self.storage.read().await;
+        storage.active_subdomains
+            .get(subdomain)
+            .map(|record| record.tunnel_ids())
+            .unwrap_or_default()
+    }
+
     /// Get tunnel ID for a custom domain
     pub async fn get_tunnel_for_custom_domain(&self, domain: &str) -> Option<String> {
         // For now, we'll store custom domains in the same storage with a special prefix
@@ -284,6 +472,8 @@ impl SubdomainManager {
                     domain
                 )));
             }
+            
+            self.check_ip_quota(&storage, client_ip.as_deref())?;
         }
 
         // Allocate the custom domain
@@ -293,6 +483,7 @@ impl SubdomainManager {
             allocated_at: chrono::Utc::now(),
             is_custom: true,
             client_ip,
+            pool: Vec::new(),
         };
 
         {
@@ -301,7 +492,7 @@ impl SubdomainManager {
             storage.allocation_history.push(record);
         }
 
-        self.save_storage().await?;
+        self.schedule_save().await?;
         
         info!("Allocated custom domain '{}' to tunnel {}", domain, tunnel_id);
         Ok(domain.to_string())
@@ -343,23 +534,41 @@ impl SubdomainManager {
         Ok(())
     }
 
-    /// Save storage to file
+    /// Save storage to file now, pruning allocation history beyond the configured limit
     async fn save_storage(&self) -> Result<(), TunnelError> {
-        let storage = self.storage.read().await;
-        let content = serde_json::to_string_pretty(&*storage)
-            .map_err(|e| TunnelError::SerializationError(format!("Failed to serialize storage: {}", e)))?;
-        
-        drop(storage);
-
-        fs::write(&self.storage_path, content).await
-            .map_err(|e| TunnelError::IoError(format!("Failed to write storage file: {}", e)))?;
+        self.writer.write(&self.storage).await
+    }
 
+    /// Record a change; it is written by the next periodic flush, or now when batching is off
+    async fn schedule_save(&self) -> Result<(), TunnelError> {
+        if self.config.storage_flush_interval_ms == 0 {
+            return self.save_storage().await;
+        }
+        self.writer.dirty.store(true, Ordering::SeqCst);
         Ok(())
     }
 
-    /// Initialize reserved words that cannot be allocated
+    /// Write any changes not yet saved
+    pub async fn flush(&self) -> Result<(), TunnelError> {
+        self.writer.flush(&self.storage).await
+    }
+
+    /// Stop periodic flushing and write any changes not yet saved
+    pub async fn shutdown(&self) -> Result<(), TunnelError> {
+        if let Some(task) = self.flush_task.lock().unwrap().take() {
+            task.abort();
+        }
+        self.flush().await
+    }
+
+    /// Number of times storage has been written to disk
+    pub fn storage_writes(&self) -> u64 {
+        self.writer.writes.load(Ordering::Relaxed)
+    }
+
+    /// Initialize reserved words that cannot be allocated from the built-in list and config
     async fn initialize_reserved_words(&self) {
-        let reserved = vec![
+        let defaults = vec![
             // System subdomains
             "www", "api", "admin", "app", "mail", "ftp", "ssh",
             "vpn", "cdn", "static", "assets", "img", "images",
@@ -383,8 +592,15 @@ impl SubdomainManager {
             "tunnel", "connect", "client", "server", "endpoint"
         ];
 
+        let mut reserved: HashSet<String> = if self.config.replace_default_reserved {
+            HashSet::new()
+        } else {
+            defaults.iter().map(|s| s.to_string()).collect()
+        };
+        reserved.extend(self.config.reserved_subdomains.iter().map(|s| s.trim().to_lowercase()));
+
         let mut storage = self.storage.write().await;
-        storage.reserved_subdomains.extend(reserved.iter().map(|s| s.to_string()));
+        storage.reserved_subdomains = reserved;
     }
 
     /// Generate word list for pronounceable subdomains
@@ -414,6 +630,26 @@ impl SubdomainManager {
     }
 }
 
+impl Drop for SubdomainManager {
+    /// Final flush so changes made since the last periodic write are not lost
+    fn drop(&mut self) {
+        if let Some(task) = self.flush_task.get_mut().unwrap().take() {
+            task.abort();
+        }
+        if !self.writer.dirty.load(Ordering::SeqCst) {
+            return;
+        }
+        match self.storage.try_write() {
+            Ok(mut storage) => {
+                if let Err(e) = self.writer.write_blocking(&mut storage) {
+                    warn!("Failed to flush subdomain storage on shutdown: {}", e);
+                }
+            }
+            Err(_) => warn!("Subdomain storage busy on shutdown; latest changes not saved"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -498,6 +734,35 @@ mod tests {
         assert!(matches!(result.unwrap_err(), TunnelError::ConflictError(_)));
     }
 
+    #[tokio::test]
+    async fn test_subdomain_pool() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = TunnelServerConfig {
+            enabled: true,
+            subdomain_pools: true,
+            ..Default::default()
+        };
+        let manager = SubdomainManager::new(config, temp_dir.path().join("subdomains.json"));
+        manager.initialize().await.unwrap();
+
+        manager.allocate_subdomain("test-tunnel-1", Some("pooled".to_string()), None).await.unwrap();
+        manager.allocate_subdomain("test-tunnel-2", Some("pooled".to_string()), None).await.unwrap();
+        assert_eq!(
+            manager.get_tunnels_for_subdomain("pooled").await,
+            vec!["test-tunnel-1".to_string(), "test-tunnel-2".to_string()]
+        );
+
+        // The holder leaving hands the subdomain to the remaining tunnel
+        manager.release_tunnel("pooled", "test-tunnel-1").await.unwrap();
+        assert_eq!(
+            manager.get_tunnel_for_subdomain("pooled").await,
+            Some("test-tunnel-2".to_string())
+        );
+
+        manager.release_tunnel("pooled", "test-tunnel-2").await.unwrap();
+        assert!(manager.is_subdomain_available("pooled").await);
+    }
+
     #[tokio::test]
     async fn test_reserved_subdomain() {
         let (manager, _temp_dir) = create_test_manager().await;
@@ -512,6 +777,167 @@ mod tests {
         assert!(matches!(result.unwrap_err(), TunnelError::ConflictError(_)));
     }
 
+    #[tokio::test]
+    async fn test_custom_reserved_subdomain() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = TunnelServerConfig {
+            reserved_subdomains: vec!["acme".to_string(), "Billing".to_string()],
+            ..Default::default()
+        };
+        let manager = SubdomainManager::new(config, temp_dir.path().join("subdomains.json"));
+        manager.initialize().await.unwrap();
+        
+        for subdomain in ["acme", "billing", "admin"] {
+            let result = manager.allocate_subdomain("test-tunnel-1", Some(subdomain.to_string()), None).await;
+            assert!(matches!(result, Err(TunnelError::ConflictError(_))), "'{}' should be reserved", subdomain);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replace_default_reserved_subdomains() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage_path = temp_dir.path().join("subdomains.json");
+        
+        // Storage written with the defaults reserves "admin"
+        {
+            let manager = SubdomainManager::new(TunnelServerConfig::default(), storage_path.clone());
+            manager.initialize().await.unwrap();
+            assert!(!manager.is_subdomain_available("admin").await);
+        }
+        
+        // Replacing the defaults on restart allows it
+        let config = TunnelServerConfig {
+            reserved_subdomains: vec!["acme".to_string()],
+            replace_default_reserved: true,
+            ..Default::default()
+        };
+        let manager = SubdomainManager::new(config, storage_path.clone());
+        manager.initialize().await.unwrap();
+        
+        let subdomain = manager.allocate_subdomain("test-tunnel-1", Some("admin".to_string()), None).await.unwrap();
+        assert_eq!(subdomain, "admin");
+        assert!(!manager.is_subdomain_available("acme").await);
+        
+        // The configured set is what gets persisted
+        let saved: SubdomainStorage = serde_json::from_str(&std::fs::read_to_string(&storage_path).unwrap()).unwrap();
+        assert_eq!(saved.reserved_subdomains, HashSet::from(["acme".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_allocation_history_is_bounded() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage_path = temp_dir.path().join("subdomains.json");
+        let config = TunnelServerConfig {
+            max_allocation_history: 5,
+            ..Default::default()
+        };
+        let manager = SubdomainManager::new(config, storage_path.clone());
+        manager.initialize().await.unwrap();
+        
+        for i in 0..12 {
+            let tunnel_id = format!("tunnel-{}", i);
+            manager.allocate_subdomain(&tunnel_id, Some(format!("history-{}", i)), None).await.unwrap();
+        }
+        
+        manager.flush().await.unwrap();
+        let saved: SubdomainStorage = serde_json::from_str(&std::fs::read_to_string(&storage_path).unwrap()).unwrap();
+        assert_eq!(saved.allocation_history.len(), 5);
+        // Newest allocations are the ones kept
+        assert_eq!(saved.allocation_history.first().unwrap().subdomain, "history-7");
+        assert_eq!(saved.allocation_history.last().unwrap().subdomain, "history-11");
+        
+        // Every active allocation survives pruning
+        assert_eq!(saved.active_subdomains.len(), 12);
+        for i in 0..12 {
+            assert!(!manager.is_subdomain_available(&format!("history-{}", i)).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_save_keeps_existing_data() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage_path = temp_dir.path().join("subdomains.json");
+        
+        {
+            let manager = SubdomainManager::new(TunnelServerConfig::default(), storage_path.clone());
+            manager.initialize().await.unwrap();
+            manager.allocate_subdomain("test-tunnel-1", Some("durable".to_string()), None).await.unwrap();
+            manager.flush().await.unwrap();
+            
+            // A crash mid-save leaves a partial temp file; the storage file itself is untouched
+            std::fs::write(manager.writer.temp_path(), "{\"active_subdomains\": {").unwrap();
+            let saved: SubdomainStorage = serde_json::from_str(&std::fs::read_to_string(&storage_path).unwrap()).unwrap();
+            assert!(saved.active_subdomains.contains_key("durable"));
+        }
+        
+        let manager = SubdomainManager::new(TunnelServerConfig::default(), storage_path);
+        manager.initialize().await.unwrap();
+        assert_eq!(manager.get_tunnel_for_subdomain("durable").await, Some("test-tunnel-1".to_string()));
+        
+        // The next save replaces the leftover temp file
+        manager.allocate_subdomain("test-tunnel-2", Some("after-crash".to_string()), None).await.unwrap();
+        manager.flush().await.unwrap();
+        assert!(!manager.writer.temp_path().exists());
+    }
+
+    #[tokio::test]
+    async fn test_rapid_allocations_are_batched() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage_path = temp_dir.path().join("subdomains.json");
+        let config = TunnelServerConfig {
+            storage_flush_interval_ms: 50,
+            ..Default::default()
+        };
+        let manager = SubdomainManager::new(config, storage_path.clone());
+        manager.initialize().await.unwrap();
+        let writes_before = manager.storage_writes();
+        
+        for i in 0..20 {
+            let tunnel_id = format!("tunnel-{}", i);
+            manager.allocate_subdomain(&tunnel_id, Some(format!("batch-{}", i)), None).await.unwrap();
+        }
+        manager.release_subdomain("batch-0").await.unwrap();
+        
+        // Picked up by the periodic flush without an explicit call
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let writes = manager.storage_writes() - writes_before;
+        assert!((1..21).contains(&writes), "Expected batched writes, got {}", writes);
+        
+        let saved: SubdomainStorage = serde_json::from_str(&std::fs::read_to_string(&storage_path).unwrap()).unwrap();
+        assert_eq!(saved.active_subdomains.len(), 19);
+        assert!(!saved.active_subdomains.contains_key("batch-0"));
+    }
+
+    #[tokio::test]
+    async fn test_per_ip_subdomain_quota() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = TunnelServerConfig {
+            max_subdomains_per_ip: 2,
+            ..Default::default()
+        };
+        let manager = SubdomainManager::new(config, temp_dir.path().join("subdomains.json"));
+        manager.initialize().await.unwrap();
+        let ip = || Some("203.0.113.7".to_string());
+        
+        manager.allocate_subdomain("tunnel-1", Some("quota-one".to_string()), ip()).await.unwrap();
+        let random = manager.allocate_subdomain("tunnel-2", None, ip()).await.unwrap();
+        
+        // Third allocation from the same IP is rejected, custom or random
+        let result = manager.allocate_subdomain("tunnel-3", Some("quota-three".to_string()), ip()).await;
+        assert!(matches!(result, Err(TunnelError::ConflictError(_))));
+        let result = manager.allocate_subdomain("tunnel-3", None, ip()).await;
+        assert!(matches!(result, Err(TunnelError::ConflictError(_))));
+        assert!(manager.is_subdomain_available("quota-three").await);
+        
+        // Other clients are unaffected
+        manager.allocate_subdomain("tunnel-4", None, Some("198.51.100.1".to_string())).await.unwrap();
+        
+        // Releasing frees quota
+        manager.release_subdomain(&random).await.unwrap();
+        let subdomain = manager.allocate_subdomain("tunnel-3", Some("quota-three".to_string()), ip()).await.unwrap();
+        assert_eq!(subdomain, "quota-three");
+    }
+
     #[tokio::test]
     async fn test_subdomain_release() {
         let (manager, _temp_dir) = create_test_manager().await;